@@ -0,0 +1,231 @@
+//! `@cm:` markers: tags kept in source comments instead of a side file. `# @cm:entrypoint`,
+//! `// @cm:deprecated` or `/* @cm:owner team-payments */` in a comment on a symbol's first
+//! line, or in the comments, decorators and attributes directly above it, become that
+//! symbol's annotations. A tag is letters, digits, `-` and `_` (matched without regard to
+//! case); anything after it up to the end of the comment is its value.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol};
+
+pub const MARKER: &str = "@cm:";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Lowercased
+    pub tag: String,
+    pub value: Option<String>,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}{} {}", MARKER, self.tag, value),
+            None => write!(f, "{}{}", MARKER, self.tag),
+        }
+    }
+}
+
+/// `--annotated TAG` or `--annotated TAG=VALUE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationFilter {
+    tag: String,
+    value: Option<String>,
+}
+
+impl AnnotationFilter {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().trim_start_matches(MARKER);
+        let (tag, value) = match text.split_once('=') {
+            Some((tag, value)) => (tag.trim(), Some(value.trim().to_string())),
+            None => (text, None),
+        };
+        if tag.is_empty() || !tag.chars().all(is_tag_char) {
+            return Err(format!(
+                "invalid annotation '{}' (expected TAG or TAG=VALUE)",
+                text
+            ));
+        }
+        Ok(AnnotationFilter {
+            tag: tag.to_ascii_lowercase(),
+            value,
+        })
+    }
+
+    pub fn matches(&self, symbol: &Symbol) -> bool {
+        symbol.annotations.iter().any(|annotation| {
+            annotation.tag == self.tag
+                && self
+                    .value
+                    .as_ref()
+                    .is_none_or(|value| annotation.value.as_ref() == Some(value))
+        })
+    }
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+fn comment_openers(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Python => &["#"],
+        Language::Lua => &["--"],
+        Language::Markdown => &["<!--"],
+        _ => &["//", "/*"],
+    }
+}
+
+/// Whether `before`, the text of a line ahead of a marker, puts the marker in a comment. Inside
+/// a block comment only lines continuing it with `*` count.
+fn in_comment(before: &str, language: Language) -> bool {
+    comment_openers(language)
+        .iter()
+        .any(|opener| before.contains(opener))
+        || (language != Language::Python && before.trim_start().starts_with('*'))
+}
+
+/// Every marker in the comments of `content`, with its line (1-based)
+pub fn scan(content: &str, language: Language) -> Vec<(usize, Annotation)> {
+    let mut markers = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let Some(first) = line.find(MARKER) else {
+            continue;
+        };
+        if !in_comment(&line[..first], language) {
+            continue;
+        }
+        for piece in line[first..].split(MARKER).skip(1) {
+            let tag_len = piece.find(|c| !is_tag_char(c)).unwrap_or(piece.len());
+            if tag_len == 0 {
+                continue;
+            }
+            let rest = &piece[tag_len..];
+            let rest = rest.split("*/").next().unwrap_or_default();
+            let rest = rest.split("-->").next().unwrap_or_default().trim();
+            markers.push((
+                line_idx + 1,
+                Annotation {
+                    tag: piece[..tag_len].to_ascii_lowercase(),
+                    value: (!rest.is_empty()).then(|| rest.to_string()),
+                },
+            ));
+        }
+    }
+    markers
+}
+
+/// A line that may sit between a marker and the declaration it tags: blank, a comment, a
+/// decorator or an attribute
+fn is_preamble(line: &str, language: Language) -> bool {
+    let line = line.trim_start();
+    line.is_empty()
+        || line.starts_with('@')
+        || line.starts_with("#[")
+        || line.starts_with('*')
+        || comment_openers(language)
+            .iter()
+            .any(|opener| line.starts_with(opener))
+}
+
+/// Give each symbol of a file the markers written for it. A marker goes to the outermost
+/// symbol starting on its line or on the first line after it that isn't preamble; one with
+/// no such symbol is dropped.
+pub fn attach(symbols: &mut [Symbol], content: &str, language: Language) {
+    if !content.contains(MARKER) {
+        return;
+    }
+    let markers = scan(content, language);
+    if markers.is_empty() {
+        return;
+    }
+
+    let mut outermost: HashMap<usize, usize> = HashMap::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        outermost
+            .entry(symbol.line_start)
+            .and_modify(|best| {
+                if symbol.line_end > symbols[*best].line_end {
+                    *best = i;
+                }
+            })
+            .or_insert(i);
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    for (line, annotation) in markers {
+        let mut at = line;
+        let target = loop {
+            if let Some(&i) = outermost.get(&at) {
+                break Some(i);
+            }
+            match lines.get(at) {
+                Some(next) if is_preamble(next, language) => at += 1,
+                Some(_) => break outermost.get(&(at + 1)).copied(),
+                None => break None,
+            }
+        };
+        if let Some(i) = target {
+            symbols[i].annotations.push(annotation);
+        }
+    }
+}
+
+/// A copy of `index` with only the symbols `filter` matches, and only the files holding one
+/// (`cm map --annotated`)
+pub fn filter_index(index: &CodeIndex, filter: &AnnotationFilter) -> CodeIndex {
+    index.filtered(|s| filter.matches(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+    use std::path::Path;
+
+    #[test]
+    fn test_markers_attach_to_the_declaration_below() {
+        let source = "# @cm:entrypoint\n@app.route('/pay')\ndef pay():\n    pass\n\n# @cm:owner team-payments @cm:Deprecated\n\nclass Billing:\n    def charge(self):  # @cm:hot\n        x = \"@cm:not-a-comment\"\n\n# @cm:orphan\nx = 1\n\ndef later():\n    pass\n";
+        let file = index_file(Path::new("a.py"), source, Language::Python, None).unwrap();
+        let tags = |name: &str| -> Vec<String> {
+            let symbol = file.symbols.iter().find(|s| s.name == name).unwrap();
+            symbol.annotations.iter().map(|a| a.to_string()).collect()
+        };
+
+        assert_eq!(tags("pay"), ["@cm:entrypoint"]);
+        assert_eq!(
+            tags("Billing"),
+            ["@cm:owner team-payments", "@cm:deprecated"]
+        );
+        assert_eq!(tags("charge"), ["@cm:hot"]);
+        assert!(tags("later").is_empty());
+
+        let owner = AnnotationFilter::parse("owner=team-payments").unwrap();
+        let billing = file.symbols.iter().find(|s| s.name == "Billing").unwrap();
+        assert!(owner.matches(billing));
+        assert!(!AnnotationFilter::parse("owner=other")
+            .unwrap()
+            .matches(billing));
+        assert!(AnnotationFilter::parse("@cm:DEPRECATED")
+            .unwrap()
+            .matches(billing));
+        assert!(AnnotationFilter::parse("no tag").is_err());
+    }
+
+    #[test]
+    fn test_block_comments_and_rust_attributes() {
+        let source = "/**\n * Charges a card.\n * @cm:owner team-payments\n */\n#[inline]\npub fn charge() {}\n\n/* @cm:deprecated use charge */\nfn old() {}\n";
+        let markers = scan(source, Language::Rust);
+        assert_eq!(markers.len(), 2);
+        assert_eq!(markers[1].1.value.as_deref(), Some("use charge"));
+
+        let file = index_file(Path::new("a.rs"), source, Language::Rust, None).unwrap();
+        let charge = file.symbols.iter().find(|s| s.name == "charge").unwrap();
+        assert_eq!(charge.annotations[0].tag, "owner");
+        let old = file.symbols.iter().find(|s| s.name == "old").unwrap();
+        assert_eq!(old.annotations[0].to_string(), "@cm:deprecated use charge");
+    }
+}
@@ -0,0 +1,306 @@
+//! Public API lock files: `cm api freeze` writes one line per exported symbol with its
+//! signature, sorted, so the file diffs cleanly in review; `cm api check` compares the
+//! current exports against it and reports what was added, removed or re-signed since. A
+//! cross-language take on the cargo-public-api workflow of committing the API next to the
+//! code and failing CI when it changes without the file being updated.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::callgraph;
+use crate::diff::{ChangeType, SymbolDiff};
+use crate::failure::{ErrorCode, Failure};
+use crate::index::CodeIndex;
+use crate::models::{Language, SymbolType};
+use crate::paths::DisplaySlash;
+
+/// Bumped when the line format changes, so old locks are not misread
+const LOCK_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ApiAction {
+    /// Print the lock of the current exports
+    Freeze,
+    /// Compare the current exports against the lock
+    Check,
+}
+
+/// An exported symbol as the lock records it
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ApiEntry {
+    /// Relative to the frozen directory, `/`-separated
+    pub file: String,
+    /// Qualified by its parent: `Client.send`
+    pub name: String,
+    pub kind: String,
+    /// On one line; empty for symbols without one
+    pub signature: String,
+    /// Where the symbol is now; locks do not record lines
+    pub lines: Option<(usize, usize)>,
+}
+
+/// Exported symbols of every non-test file in `index`, sorted, paths relative to `root`
+pub fn freeze(index: &CodeIndex, root: &Path) -> Vec<ApiEntry> {
+    let mut entries = Vec::new();
+    for file in index.files() {
+        if file.language == Language::Markdown || callgraph::is_test_file(&file.path, file.language)
+        {
+            continue;
+        }
+        let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+        let symbols = index.get_file_symbols(&file.path);
+        for symbol in symbols.iter().filter(|s| s.is_exported) {
+            // Rust methods hang off an `impl Client` block; some parsers already name members
+            // after their parent (`Status.open`)
+            let parent = symbol
+                .parent_id
+                .and_then(|id| symbols.get(id))
+                .map(|parent| parent.name.trim_start_matches("impl "));
+            let name = match parent {
+                Some(parent) if !symbol.name.starts_with(&format!("{}.", parent)) => {
+                    format!("{}.{}", parent, symbol.name)
+                }
+                _ => symbol.name.to_string(),
+            };
+            entries.push(ApiEntry {
+                file: relative.display_slash().to_string(),
+                kind: symbol.symbol_type.as_str().to_string(),
+                name,
+                signature: symbol
+                    .signature
+                    .as_deref()
+                    .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .unwrap_or_default(),
+                lines: Some((symbol.line_start, symbol.line_end)),
+            });
+        }
+    }
+    entries.sort();
+    entries
+}
+
+/// The lock file text: a version header, then `file<TAB>kind<TAB>name[<TAB>signature]` lines
+pub fn render(entries: &[ApiEntry]) -> String {
+    let mut output = format!(
+        "# cm api lock v{}: exported symbols and their signatures\n\
+         # Regenerate with `cm api freeze > api.lock` when an API change is intended\n",
+        LOCK_VERSION
+    );
+    for entry in entries {
+        output.push_str(&format!("{}\t{}\t{}", entry.file, entry.kind, entry.name));
+        if !entry.signature.is_empty() {
+            output.push_str(&format!("\t{}", entry.signature));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+pub fn parse(text: &str) -> Result<Vec<ApiEntry>> {
+    let version = text
+        .lines()
+        .next()
+        .and_then(|header| header.strip_prefix("# cm api lock v"))
+        .and_then(|rest| rest.split(':').next())
+        .and_then(|version| version.trim().parse::<u32>().ok());
+    match version {
+        Some(LOCK_VERSION) => {}
+        Some(version) => anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "API lock has version {}, this cm writes version {}; freeze it again",
+                version, LOCK_VERSION
+            )
+        )),
+        None => anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            "Not an API lock: the first line should be `# cm api lock v1`"
+        )),
+    }
+
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.splitn(4, '\t');
+        let (Some(file), Some(kind), Some(name)) = (fields.next(), fields.next(), fields.next())
+        else {
+            anyhow::bail!(Failure::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "API lock line {}: expected file, kind and name separated by tabs",
+                    i + 1
+                )
+            ));
+        };
+        entries.push(ApiEntry {
+            file: file.to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+            signature: fields.next().unwrap_or_default().to_string(),
+            lines: None,
+        });
+    }
+    Ok(entries)
+}
+
+pub fn load(path: &Path) -> Result<Vec<ApiEntry>> {
+    if !path.exists() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!(
+                "No API lock at {}; create one with `cm api freeze > {}`",
+                path.display_slash(),
+                path.display_slash()
+            )
+        ));
+    }
+    let text = fs::read_to_string(path).context("Failed to read API lock")?;
+    parse(&text).with_context(|| format!("Failed to parse {}", path.display_slash()))
+}
+
+/// What changed between the lock and the current exports. A symbol with one signature
+/// gone and one new is re-signed; any other mismatch is reported as removals and additions,
+/// so adding an overload shows up as just that.
+pub fn check(locked: &[ApiEntry], current: &[ApiEntry]) -> Vec<SymbolDiff> {
+    type Key<'a> = (&'a str, &'a str, &'a str);
+    let mut symbols: BTreeMap<Key, (Vec<&ApiEntry>, Vec<&ApiEntry>)> = BTreeMap::new();
+    for entry in locked {
+        let key = (
+            entry.file.as_str(),
+            entry.name.as_str(),
+            entry.kind.as_str(),
+        );
+        symbols.entry(key).or_default().0.push(entry);
+    }
+    for entry in current {
+        let key = (
+            entry.file.as_str(),
+            entry.name.as_str(),
+            entry.kind.as_str(),
+        );
+        symbols.entry(key).or_default().1.push(entry);
+    }
+
+    let mut changes = Vec::new();
+    for (mut removed, after) in symbols.into_values() {
+        let mut added: Vec<&ApiEntry> = Vec::new();
+        for entry in after {
+            match removed
+                .iter()
+                .position(|old| old.signature == entry.signature)
+            {
+                Some(i) => {
+                    removed.remove(i);
+                }
+                None => added.push(entry),
+            }
+        }
+        if let ([old], [new]) = (removed.as_slice(), added.as_slice()) {
+            let mut change = symbol_diff(new, ChangeType::SignatureChanged);
+            change.old_signature = Some(old.signature.clone());
+            changes.push(change);
+            continue;
+        }
+        changes.extend(
+            removed
+                .into_iter()
+                .map(|e| symbol_diff(e, ChangeType::Deleted)),
+        );
+        changes.extend(added.into_iter().map(|e| symbol_diff(e, ChangeType::Added)));
+    }
+    changes
+}
+
+fn symbol_diff(entry: &ApiEntry, change_type: ChangeType) -> SymbolDiff {
+    let signature = (!entry.signature.is_empty()).then(|| entry.signature.clone());
+    let (old_signature, new_signature) = match change_type {
+        ChangeType::Deleted => (signature, None),
+        _ => (None, signature),
+    };
+    SymbolDiff {
+        name: entry.name.clone(),
+        // Kinds only a plugin registers read back as functions when it is not loaded
+        symbol_type: SymbolType::from_str(&entry.kind).unwrap_or(SymbolType::Function),
+        change_type,
+        file_path: PathBuf::from(&entry.file),
+        old_lines: None,
+        new_lines: entry.lines,
+        old_signature,
+        new_signature,
+        is_exported: true,
+        old_name: None,
+        old_file_path: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+
+    fn frozen(files: &[(&str, &str)]) -> Vec<ApiEntry> {
+        let root = Path::new("/project");
+        let mut index = CodeIndex::new();
+        for (name, source) in files {
+            let path = root.join(name);
+            index.add_file(index_file(&path, source, Language::Rust, None).unwrap());
+        }
+        freeze(&index, root)
+    }
+
+    #[test]
+    fn test_freeze_round_trips_and_checks() {
+        let source = "pub struct Client;\n\nimpl Client {\n    pub fn send(&self, body: &str) -> bool {\n        true\n    }\n}\n\nfn helper() {}\n\npub fn connect(url: &str) -> Client {\n    Client\n}\n";
+        let entries = frozen(&[("src/lib.rs", source), ("tests/it.rs", "pub fn it() {}\n")]);
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Client", "Client.send", "connect"]);
+
+        let lock = render(&entries);
+        assert!(lock.contains("src/lib.rs\tfunction\tconnect\t(url: &str)\n"));
+        let locked = parse(&lock).unwrap();
+        assert!(check(&locked, &entries).is_empty());
+
+        let changed = source
+            .replace(
+                "pub fn connect(url: &str)",
+                "pub fn connect(url: &str, retries: u8)",
+            )
+            .replace("pub fn send", "fn send")
+            + "\npub fn close() {}\n";
+        let current = frozen(&[("src/lib.rs", &changed)]);
+        let changes: Vec<_> = check(&locked, &current)
+            .into_iter()
+            .map(|c| (c.name, c.change_type, c.old_signature, c.new_signature))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (
+                    "Client.send".to_string(),
+                    ChangeType::Deleted,
+                    Some("(&self, body: &str)".to_string()),
+                    None
+                ),
+                (
+                    "close".to_string(),
+                    ChangeType::Added,
+                    None,
+                    Some("()".to_string())
+                ),
+                (
+                    "connect".to_string(),
+                    ChangeType::SignatureChanged,
+                    Some("(url: &str)".to_string()),
+                    Some("(url: &str, retries: u8)".to_string())
+                ),
+            ]
+        );
+
+        assert!(parse("src/lib.rs\tfunction\tconnect\n").is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! `cm audit`: a lightweight security sweep over non-test code. Flags Rust `unsafe`,
+//! dynamic code evaluation (`eval`/`exec`/`new Function`), shell commands built from
+//! interpolated strings, and SQL assembled by concatenation or formatting, each mapped to
+//! the symbol it lives in. Line-based and heuristic: a starting point for review, not proof.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use regex::Regex;
+use std::path::PathBuf;
+
+use crate::callgraph;
+use crate::index::CodeIndex;
+use crate::models::Language;
+use crate::routes::group_text;
+use crate::scan::{self, regex};
+use crate::value_refs::enclosing_symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum Rule {
+    /// Rust `unsafe` blocks, functions and impls
+    Unsafe,
+    /// `eval`/`exec` (Python), `eval`/`new Function` (JS/TS)
+    Eval,
+    /// `os.system`, `subprocess(..., shell=True)`, `child_process.exec`, `sh -c` with
+    /// interpolated arguments
+    Shell,
+    /// SQL literals joined with `+`, f-strings, `%`, `.format`, `format!` or `Sprintf`
+    SqlConcat,
+}
+
+impl Rule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rule::Unsafe => "unsafe",
+            Rule::Eval => "eval",
+            Rule::Shell => "shell",
+            Rule::SqlConcat => "sql-concat",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::Unsafe => "unsafe code",
+            Rule::Eval => "dynamic code evaluation",
+            Rule::Shell => "shell command built from interpolated strings",
+            Rule::SqlConcat => "SQL built by concatenation or formatting",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub rule: Rule,
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// The source line, trimmed
+    pub context: String,
+    /// Innermost symbol containing the construct
+    pub enclosing: Option<String>,
+}
+
+/// Every finding in non-test files, in file and line order
+pub fn audit(index: &CodeIndex) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    for file in index.files() {
+        if callgraph::is_test_file(&file.path, file.language) {
+            continue;
+        }
+        let content = scan::non_test_source(&file.path, file.language);
+        for (rule, line, context) in audit_in(&content, file.language) {
+            findings.push(Finding {
+                rule,
+                file_path: file.path.clone(),
+                line,
+                context,
+                enclosing: enclosing_symbol(index, &file.path, line).map(|s| s.name.to_string()),
+            });
+        }
+    }
+    findings.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+    Ok(findings)
+}
+
+/// A shell pattern and whether the call must also pass `shell=True` to be dangerous
+struct ShellCall {
+    pattern: Regex,
+    needs_shell_flag: bool,
+}
+
+fn eval_patterns(language: Language) -> Vec<Regex> {
+    match language {
+        // `model.eval()` and `re.exec()` are methods, not the builtins
+        Language::Python => vec![regex(r"(?:^|[^.\w])(?:eval|exec)\s*\(")],
+        Language::JavaScript | Language::TypeScript => vec![
+            regex(r"(?:^|[^.\w])eval\s*\("),
+            regex(r"\bnew\s+Function\s*\("),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn shell_calls(language: Language) -> Vec<ShellCall> {
+    let call = |pattern: &str, needs_shell_flag| ShellCall {
+        pattern: regex(pattern),
+        needs_shell_flag,
+    };
+    match language {
+        Language::Python => vec![
+            call(
+                r"\bos\.(?:system|popen)\s*\(|\bsubprocess\.(?:getoutput|getstatusoutput)\s*\(",
+                false,
+            ),
+            call(
+                r"\bsubprocess\.(?:run|call|Popen|check_output|check_call)\s*\(",
+                true,
+            ),
+        ],
+        Language::JavaScript | Language::TypeScript => vec![call(
+            r"(?:\bchild_process\.|(?:^|[^.\w]))(?:exec|execSync)\s*\(",
+            false,
+        )],
+        Language::Rust => vec![call(
+            r#"\bCommand::new\s*\(\s*"(?:sh|bash|zsh|cmd|powershell)""#,
+            false,
+        )],
+        Language::Go => vec![call(
+            r#"\bexec\.Command(?:Context)?\s*\([^)]*"(?:sh|bash|cmd)""#,
+            false,
+        )],
+        Language::Java => vec![call(r"\bgetRuntime\(\)\s*\.exec\s*\(", false)],
+        _ => Vec::new(),
+    }
+}
+
+/// Formatting or concatenation that splices values into a string
+const INTERPOLATION: &str = r#"\b[rRbB]?[fF][rRbB]?["']|\$\{|\.format\(|\bformat!\s*\(|\bSprintf\s*\(|\bString\.format\s*\(|["'`]\s*\+|\+\s*["'`]|["']\s+%\s*[\w(]"#;
+
+/// Whether `line` opens a string literal with a DML keyword (matched by `start`) and the
+/// keyword's companion clause follows on the same or the next line
+fn sql_literal(line: &str, next: &str, start: &Regex) -> bool {
+    let Some(caps) = start.captures(line) else {
+        return false;
+    };
+    let rest = format!("{} {}", &line[caps.get(0).map_or(0, |m| m.end())..], next).to_uppercase();
+    let companion = match caps[1].to_uppercase().as_str() {
+        "SELECT" | "DELETE" => "FROM",
+        "INSERT" => "INTO",
+        _ => "SET",
+    };
+    rest.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|word| word == companion)
+}
+
+/// (rule, 1-based line, trimmed source line) of every finding in `content`
+pub fn audit_in(content: &str, language: Language) -> Vec<(Rule, usize, String)> {
+    let unsafe_code = regex(r"\bunsafe\s*(?:\{|fn\b|impl\b|extern\b|trait\b)");
+    let eval = eval_patterns(language);
+    let shell = shell_calls(language);
+    let interpolation = regex(INTERPOLATION);
+    let shell_flag = regex(r"\bshell\s*=\s*True\b");
+    let sql_start = regex(r#"["'`]\s*(SELECT|INSERT|UPDATE|DELETE|select|insert|update|delete)\s"#);
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut found = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if scan::is_comment(line, language) || language == Language::Markdown {
+            continue;
+        }
+        let mut rules = Vec::new();
+        if language == Language::Rust && unsafe_code.is_match(line) {
+            rules.push(Rule::Unsafe);
+        }
+        if eval.iter().any(|p| p.is_match(line)) {
+            rules.push(Rule::Eval);
+        }
+        for call in &shell {
+            let Some(m) = call.pattern.find(line) else {
+                continue;
+            };
+            let text = group_text(&lines, i, m.start(), language == Language::Rust);
+            if interpolation.is_match(&text)
+                && (!call.needs_shell_flag || shell_flag.is_match(&text))
+            {
+                rules.push(Rule::Shell);
+                break;
+            }
+        }
+        // A statement split over two lines (`"SELECT * " +` / `"FROM t"`) still pairs up
+        let next = lines.get(i + 1).copied().unwrap_or("");
+        if interpolation.is_match(line) && sql_literal(line, next, &sql_start) {
+            rules.push(Rule::SqlConcat);
+        }
+        for rule in rules {
+            found.push((rule, i + 1, line.trim().to_string()));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str, language: Language) -> Vec<(Rule, usize)> {
+        audit_in(content, language)
+            .into_iter()
+            .map(|(rule, line, _)| (rule, line))
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_rules() {
+        let python = r#"def run(user, cmd):
+    model.eval()
+    eval(cmd)
+    os.system(f"tar czf {user}.tgz data")
+    subprocess.run(["ls", user])
+    subprocess.run("grep " + user, shell=True)
+    db.execute("SELECT * FROM users WHERE name = '%s'" % user)
+    db.execute("SELECT * FROM users WHERE name = %s", (user,))
+    print("update " + user)
+"#;
+        assert_eq!(
+            rules(python, Language::Python),
+            vec![
+                (Rule::Eval, 3),
+                (Rule::Shell, 4),
+                (Rule::Shell, 6),
+                (Rule::SqlConcat, 7)
+            ]
+        );
+
+        let rust = "fn raw(p: *const u8, table: &str) {\n    let v = unsafe { *p };\n    let q = format!(\"DELETE FROM {} WHERE id = ?\", table);\n    Command::new(\"sh\")\n        .arg(\"-c\")\n        .arg(format!(\"rm -rf {}\", table));\n}\n";
+        assert_eq!(
+            rules(rust, Language::Rust),
+            vec![(Rule::Unsafe, 2), (Rule::SqlConcat, 3), (Rule::Shell, 4)]
+        );
+
+        let js = "const q = `SELECT * FROM t WHERE id = ${id}`;\nexec(`git checkout ${branch}`);\nconst m = /x/.exec(s);\nconst f = new Function('a', body);\n";
+        assert_eq!(
+            rules(js, Language::TypeScript),
+            vec![(Rule::SqlConcat, 1), (Rule::Shell, 2), (Rule::Eval, 4)]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Timings for the stages every command goes through (walk, parse, cache save/load,
+//! symbol lookups), reported as JSON that can be saved and compared against later runs.
+
+use crate::cache::CacheManager;
+use crate::index::CodeIndex;
+use crate::indexer;
+use crate::paths::DisplaySlash;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// Lookup metrics time this many names sampled from the index
+pub const QUERY_SAMPLES: usize = 50;
+
+/// Changes smaller than this are timer noise and never count as regressions
+const NOISE_FLOOR_MS: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub version: String,
+    pub files: usize,
+    pub symbols: usize,
+    pub iterations: usize,
+    /// Median wall time per stage in milliseconds (lower is better)
+    pub metrics_ms: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MetricChange {
+    pub metric: String,
+    pub baseline_ms: f64,
+    pub current_ms: f64,
+    /// Positive when the current run is slower
+    pub change_pct: f64,
+}
+
+impl MetricChange {
+    pub fn is_regression(&self, threshold_pct: f64) -> bool {
+        self.change_pct > threshold_pct && self.current_ms - self.baseline_ms > NOISE_FLOOR_MS
+    }
+}
+
+/// Run every stage `iterations` times against `root`. The cache is written to a scratch
+/// directory so the project's own `.codemapper/` is left alone.
+pub fn run(root: &Path, extensions: &[&str], iterations: usize) -> Result<BenchReport> {
+    let iterations = iterations.max(1);
+    let scratch = std::env::temp_dir().join(format!("cm-bench-{}", std::process::id()));
+
+    let mut samples: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    let mut record = |metric, start: Instant| {
+        samples
+            .entry(metric)
+            .or_default()
+            .push(start.elapsed().as_secs_f64() * 1000.0);
+    };
+
+    let mut counts = (0, 0);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let files = indexer::discover_files(root, extensions);
+        record("walk", start);
+
+        let start = Instant::now();
+        let index = indexer::index_files_with_progress(&files, None)?;
+        record("parse", start);
+
+        let start = Instant::now();
+        CacheManager::save(&index, root, extensions, Some(&scratch))?;
+        record("cache_save", start);
+
+        let start = Instant::now();
+        let loaded = CacheManager::load(root, extensions, Some(&scratch))?;
+        record("cache_load", start);
+        loaded.context("Benchmark cache was not readable after saving it")?;
+
+        let names = sample_names(&index, QUERY_SAMPLES);
+
+        let start = Instant::now();
+        for name in &names {
+            std::hint::black_box(index.query_symbol(name));
+        }
+        record("query_exact", start);
+
+        let start = Instant::now();
+        for name in &names {
+            std::hint::black_box(index.fuzzy_search(name));
+        }
+        record("query_fuzzy", start);
+
+        counts = (index.total_files(), index.total_symbols());
+    }
+
+    let _ = fs::remove_dir_all(&scratch);
+
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        files: counts.0,
+        symbols: counts.1,
+        iterations,
+        metrics_ms: samples
+            .into_iter()
+            .map(|(metric, mut times)| (metric.to_string(), round3(median(&mut times))))
+            .collect(),
+    })
+}
+
+/// Up to `count` distinct names spread evenly over the sorted name list
+fn sample_names(index: &CodeIndex, count: usize) -> Vec<String> {
+    let names: BTreeSet<&str> = index
+        .all_symbols()
+        .into_iter()
+        .map(|s| s.name.as_str())
+        .filter(|name| !name.is_empty())
+        .collect();
+    let step = (names.len() / count.max(1)).max(1);
+    names
+        .into_iter()
+        .step_by(step)
+        .take(count)
+        .map(str::to_string)
+        .collect()
+}
+
+fn median(times: &mut [f64]) -> f64 {
+    if times.is_empty() {
+        return 0.0;
+    }
+    times.sort_by(|a, b| a.total_cmp(b));
+    times[times.len() / 2]
+}
+
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// Metrics present in both reports, in name order
+pub fn compare(baseline: &BenchReport, current: &BenchReport) -> Vec<MetricChange> {
+    current
+        .metrics_ms
+        .iter()
+        .filter_map(|(metric, &current_ms)| {
+            let baseline_ms = *baseline.metrics_ms.get(metric)?;
+            let change_pct = if baseline_ms > 0.0 {
+                (current_ms - baseline_ms) / baseline_ms * 100.0
+            } else {
+                0.0
+            };
+            Some(MetricChange {
+                metric: metric.clone(),
+                baseline_ms,
+                current_ms,
+                change_pct,
+            })
+        })
+        .collect()
+}
+
+impl BenchReport {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline: {}", path.display_slash()))?;
+        serde_json::from_str(&content).with_context(|| format!("in {}", path.display_slash()))
+    }
+}
+
+/// Parse a threshold like `10%` or `10` into a percentage
+pub fn parse_percent(value: &str) -> Result<f64, String> {
+    let number = value.trim().trim_end_matches('%');
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|pct| *pct >= 0.0)
+        .ok_or_else(|| format!("expected a percentage like '10%', got '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(metrics: &[(&str, f64)]) -> BenchReport {
+        BenchReport {
+            version: "0.1.0".to_string(),
+            files: 1,
+            symbols: 1,
+            iterations: 1,
+            metrics_ms: metrics.iter().map(|(m, v)| (m.to_string(), *v)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare_flags_regressions_above_threshold() {
+        let baseline = report(&[("parse", 100.0), ("walk", 10.0), ("query_exact", 0.1)]);
+        let current = report(&[("parse", 125.0), ("walk", 10.5), ("query_exact", 0.3)]);
+
+        let changes = compare(&baseline, &current);
+        let regressed: Vec<&str> = changes
+            .iter()
+            .filter(|c| c.is_regression(10.0))
+            .map(|c| c.metric.as_str())
+            .collect();
+
+        // query_exact tripled but only by 0.2ms, which is below the noise floor
+        assert_eq!(regressed, vec!["parse"]);
+        assert_eq!(parse_percent("10%"), Ok(10.0));
+        assert_eq!(parse_percent("2.5"), Ok(2.5));
+        assert!(parse_percent("fast").is_err());
+    }
+}
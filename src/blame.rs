@@ -1,8 +1,12 @@
+use crate::diff::ChangeType;
+use crate::failure::{code_of, ErrorCode, Failure};
 use crate::git::{self, CommitInfo};
 use crate::indexer;
 use crate::models::{Language, Symbol, SymbolType};
+use crate::paths::{self, DisplaySlash};
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct BlameResult {
@@ -22,188 +26,429 @@ pub struct HistoryEntry {
     pub existed: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct ApiChange {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub change_type: ChangeType,
+    pub old_signature: Option<String>,
+    pub new_signature: Option<String>,
+}
+
+/// One commit that changed the exported API of a file
+#[derive(Debug, Clone)]
+pub struct FileApiEntry {
+    pub commit: CommitInfo,
+    pub exported_count: usize,
+    pub changes: Vec<ApiChange>,
+}
+
+/// The commit that last touched any line of a symbol, for `cm blame --file`
+#[derive(Debug, Clone)]
+pub struct SymbolBlame {
+    pub symbol_name: String,
+    pub symbol_type: SymbolType,
+    pub lines: (usize, usize),
+    /// None when some of its lines are not committed yet
+    pub commit: Option<CommitInfo>,
+}
+
+/// Signatures of the exported symbols by name and kind; overloads share a name, so each
+/// name holds the signatures of all of them
+type ApiSurface = HashMap<(String, SymbolType), Vec<Option<String>>>;
+
+/// One `SYMBOL FILE` line of a `--batch` list
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchEntry {
+    pub symbol: String,
+    pub file: PathBuf,
+}
+
 pub fn blame_symbol(repo_path: &Path, file_path: &Path, symbol_name: &str) -> Result<BlameResult> {
+    require_git_repo(repo_path)?;
+    let (canonical_file, language) = resolve_file(file_path)?;
+    blame_symbols(
+        repo_path,
+        &canonical_file,
+        file_path,
+        language,
+        &[symbol_name],
+    )?
+    .remove(0)
+}
+
+pub fn history_symbol(
+    repo_path: &Path,
+    file_path: &Path,
+    symbol_name: &str,
+) -> Result<Vec<HistoryEntry>> {
+    require_git_repo(repo_path)?;
+    let (canonical_file, language) = resolve_file(file_path)?;
+    Ok(history_symbols(
+        repo_path,
+        &canonical_file,
+        file_path,
+        language,
+        &[symbol_name],
+    )?
+    .remove(0))
+}
+
+/// `blame_symbol` for every entry, in order. Entries are grouped by file so each file's
+/// history is listed and each past version parsed once, however many of its symbols are
+/// asked for. An entry that cannot be blamed gets its own error.
+pub fn blame_batch(repo_path: &Path, entries: &[BatchEntry]) -> Result<Vec<Result<BlameResult>>> {
+    require_git_repo(repo_path)?;
+    Ok(run_batch(
+        entries,
+        |canonical_file, file_path, language, names| {
+            blame_symbols(repo_path, canonical_file, file_path, language, names)
+        },
+    ))
+}
+
+/// `history_symbol` for every entry, in order, with one pass over each file's history
+pub fn history_batch(
+    repo_path: &Path,
+    entries: &[BatchEntry],
+) -> Result<Vec<Result<Vec<HistoryEntry>>>> {
+    require_git_repo(repo_path)?;
+    Ok(run_batch(
+        entries,
+        |canonical_file, file_path, language, names| {
+            Ok(
+                history_symbols(repo_path, canonical_file, file_path, language, names)?
+                    .into_iter()
+                    .map(Ok)
+                    .collect(),
+            )
+        },
+    ))
+}
+
+/// Read a `--batch` list: one `SYMBOL FILE` per line, blank lines and `#` comments skipped
+pub fn parse_batch(text: &str) -> Result<Vec<BatchEntry>> {
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((symbol, file)) = line.split_once(char::is_whitespace) else {
+            anyhow::bail!(Failure::new(
+                ErrorCode::InvalidArgument,
+                format!("Batch line {} has no file: expected `SYMBOL FILE`", i + 1)
+            ));
+        };
+        entries.push(BatchEntry {
+            symbol: symbol.to_string(),
+            file: PathBuf::from(file.trim()),
+        });
+    }
+    Ok(entries)
+}
+
+/// Run `per_file` once for each distinct file of `entries`, with the symbols asked for in
+/// it, and put the results back in entry order. A failure of the whole file is reported
+/// for each of its entries.
+fn run_batch<T>(
+    entries: &[BatchEntry],
+    mut per_file: impl FnMut(&Path, &Path, Language, &[&str]) -> Result<Vec<Result<T>>>,
+) -> Vec<Result<T>> {
+    let mut groups: Vec<(PathBuf, Vec<usize>)> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        match groups.iter_mut().find(|(file, _)| *file == entry.file) {
+            Some((_, indices)) => indices.push(i),
+            None => groups.push((entry.file.clone(), vec![i])),
+        }
+    }
+
+    let mut results: Vec<Option<Result<T>>> = entries.iter().map(|_| None).collect();
+    for (file_path, indices) in groups {
+        let names: Vec<&str> = indices
+            .iter()
+            .map(|&i| entries[i].symbol.as_str())
+            .collect();
+        let outcome = resolve_file(&file_path).and_then(|(canonical_file, language)| {
+            per_file(&canonical_file, &file_path, language, &names)
+        });
+        match outcome {
+            Ok(per_symbol) => {
+                for (&i, result) in indices.iter().zip(per_symbol) {
+                    results[i] = Some(result);
+                }
+            }
+            Err(e) => {
+                let code = code_of(&e);
+                let message = format!("{:#}", e);
+                for &i in &indices {
+                    results[i] = Some(Err(Failure::new(code, message.clone()).into()));
+                }
+            }
+        }
+    }
+    results.into_iter().flatten().collect()
+}
+
+fn require_git_repo(repo_path: &Path) -> Result<()> {
     if !git::is_git_repo(repo_path) {
-        anyhow::bail!("Not a git repository: {}", repo_path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("Not a git repository: {}", repo_path.display_slash())
+        ));
     }
+    Ok(())
+}
 
-    let canonical_file = std::fs::canonicalize(file_path).context("Failed to resolve file path")?;
+/// Canonical path and language of an existing source file
+fn resolve_file(file_path: &Path) -> Result<(PathBuf, Language)> {
+    let canonical_file = paths::canonicalize(file_path).context("Failed to resolve file path")?;
 
     if !canonical_file.exists() {
-        anyhow::bail!("File does not exist: {}", file_path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::PathNotFound,
+            format!("File does not exist: {}", file_path.display_slash())
+        ));
     }
 
     let language = indexer::detect_language(&canonical_file);
     if language == Language::Unknown {
-        anyhow::bail!("Unknown or unsupported file type: {}", file_path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::UnsupportedLanguage,
+            format!("Unknown or unsupported file type: {}", file_path.display_slash())
+        ));
     }
 
+    Ok((canonical_file, language))
+}
+
+/// Blame of each of `names` in one file. Walking newest to oldest, a symbol's last
+/// modification is the newest commit whose parent version has a different signature or
+/// length, or lacks it; every version is parsed once for all of them.
+fn blame_symbols(
+    repo_path: &Path,
+    canonical_file: &Path,
+    file_path: &Path,
+    language: Language,
+    names: &[&str],
+) -> Result<Vec<Result<BlameResult>>> {
     let current_content =
-        std::fs::read_to_string(&canonical_file).context("Failed to read current file")?;
-    let current_file_info = indexer::index_file(&canonical_file, &current_content, language, None)?;
+        std::fs::read_to_string(canonical_file).context("Failed to read current file")?;
+    let current_file_info = indexer::index_file(canonical_file, &current_content, language, None)?;
 
-    let current_symbol = current_file_info
-        .symbols
+    let current: Vec<Option<&Symbol>> = names
         .iter()
-        .find(|s| s.name == symbol_name)
-        .ok_or_else(|| anyhow::anyhow!("Symbol '{}' not found in current file", symbol_name))?;
+        .map(|name| current_file_info.symbols.iter().find(|s| s.name == *name))
+        .collect();
+    if current.iter().all(Option::is_none) {
+        return Ok(names
+            .iter()
+            .map(|name| Err(symbol_not_found(name)))
+            .collect());
+    }
 
-    let commits = git::get_commits_for_file(repo_path, &canonical_file, Some(100))?;
+    let commits = git::get_commits_for_file(repo_path, canonical_file, Some(100))?;
 
     if commits.is_empty() {
-        anyhow::bail!("No git history found for file: {}", file_path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("No git history found for file: {}", file_path.display_slash())
+        ));
     }
 
     let repo_root = git::get_repo_root(repo_path)?;
 
-    let mut last_modifying_commit: Option<&CommitInfo> = None;
-    let mut previous_signature: Option<String> = None;
-
-    for i in 0..commits.len() {
-        let commit = &commits[i];
-
-        let symbol_at_commit = get_symbol_at_commit(
-            &repo_root,
-            &canonical_file,
-            &commit.hash,
-            symbol_name,
-            language,
-        )?;
+    // (index of the last modifying commit, signature before it) of each symbol still traced
+    let mut found: Vec<Option<(usize, Option<String>)>> = current
+        .iter()
+        .map(|symbol| symbol.is_none().then_some((0, None)))
+        .collect();
+    let mut newer = symbols_at_commit(&repo_root, canonical_file, &commits[0].hash, language)?;
 
-        if i == 0 {
-            last_modifying_commit = Some(commit);
-            continue;
+    for (i, commit) in commits.iter().enumerate().skip(1) {
+        if found.iter().all(Option::is_some) {
+            break;
         }
+        let older = symbols_at_commit(&repo_root, canonical_file, &commit.hash, language)?;
 
-        let prev_commit = &commits[i - 1];
-        let symbol_at_prev = get_symbol_at_commit(
-            &repo_root,
-            &canonical_file,
-            &prev_commit.hash,
-            symbol_name,
-            language,
-        )?;
-
-        let current_sig = symbol_at_prev.as_ref().and_then(|s| s.signature.clone());
-        let prev_sig = symbol_at_commit.as_ref().and_then(|s| s.signature.clone());
-
-        match (&symbol_at_prev, &symbol_at_commit) {
-            (Some(_), None) => {
-                last_modifying_commit = Some(&commits[i - 1]);
-                previous_signature = None;
-                break;
+        for (name, slot) in names.iter().zip(found.iter_mut()) {
+            if slot.is_some() {
+                continue;
             }
-            (Some(curr), Some(prev)) => {
-                let curr_lines = curr.line_end - curr.line_start;
-                let prev_lines = prev.line_end - prev.line_start;
-
-                if current_sig != prev_sig || curr_lines != prev_lines {
-                    last_modifying_commit = Some(&commits[i - 1]);
-                    previous_signature = prev_sig;
-                    break;
+            let symbol_at_prev = newer.iter().find(|s| s.name == *name);
+            let symbol_at_commit = older.iter().find(|s| s.name == *name);
+
+            match (symbol_at_prev, symbol_at_commit) {
+                (Some(_), None) => *slot = Some((i - 1, None)),
+                (Some(curr), Some(prev)) => {
+                    let curr_lines = curr.line_end - curr.line_start;
+                    let prev_lines = prev.line_end - prev.line_start;
+
+                    if curr.signature != prev.signature || curr_lines != prev_lines {
+                        *slot = Some((i - 1, prev.signature.clone()));
+                    }
                 }
+                _ => {}
             }
-            _ => {}
         }
+        newer = older;
     }
 
-    let last_commit = last_modifying_commit.cloned().unwrap_or_else(|| {
-        commits.first().cloned().unwrap_or_else(|| CommitInfo {
-            hash: "unknown".to_string(),
-            short_hash: "unknown".to_string(),
-            author: "unknown".to_string(),
-            date: "unknown".to_string(),
-            message: "unknown".to_string(),
+    Ok(names
+        .iter()
+        .zip(current)
+        .zip(found)
+        .map(|((name, current_symbol), found)| {
+            let current_symbol = current_symbol.ok_or_else(|| symbol_not_found(name))?;
+            let (commit, previous_signature) = found.unwrap_or((0, None));
+            Ok(BlameResult {
+                symbol_name: current_symbol.name.to_string(),
+                symbol_type: current_symbol.symbol_type,
+                last_commit: commits[commit].clone(),
+                old_signature: previous_signature,
+                new_signature: current_symbol.signature.clone(),
+                current_lines: (current_symbol.line_start, current_symbol.line_end),
+            })
         })
-    });
-
-    Ok(BlameResult {
-        symbol_name: current_symbol.name.clone(),
-        symbol_type: current_symbol.symbol_type,
-        last_commit,
-        old_signature: previous_signature,
-        new_signature: current_symbol.signature.clone(),
-        current_lines: (current_symbol.line_start, current_symbol.line_end),
-    })
+        .collect())
 }
 
-pub fn history_symbol(
+/// History of each of `names` in one file, oldest to newest with every version parsed once,
+/// returned newest first
+fn history_symbols(
     repo_path: &Path,
+    canonical_file: &Path,
     file_path: &Path,
-    symbol_name: &str,
-) -> Result<Vec<HistoryEntry>> {
-    if !git::is_git_repo(repo_path) {
-        anyhow::bail!("Not a git repository: {}", repo_path.display());
+    language: Language,
+    names: &[&str],
+) -> Result<Vec<Vec<HistoryEntry>>> {
+    let commits = git::get_commits_for_file(repo_path, canonical_file, None)?;
+
+    if commits.is_empty() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("No git history found for file: {}", file_path.display_slash())
+        ));
     }
 
-    let canonical_file = std::fs::canonicalize(file_path).context("Failed to resolve file path")?;
+    let repo_root = git::get_repo_root(repo_path)?;
+    let mut histories: Vec<Vec<HistoryEntry>> = vec![Vec::new(); names.len()];
+    // Signature and lines of each symbol at the previous commit
+    let mut previous = vec![(None, None); names.len()];
 
-    if !canonical_file.exists() {
-        anyhow::bail!("File does not exist: {}", file_path.display());
+    for commit in commits.iter().rev() {
+        let symbols = symbols_at_commit(&repo_root, canonical_file, &commit.hash, language)?;
+
+        for ((name, history), (prev_signature, prev_lines)) in
+            names.iter().zip(&mut histories).zip(&mut previous)
+        {
+            match symbols.iter().find(|s| s.name == *name) {
+                Some(sym) => {
+                    let current_sig = sym.signature.clone();
+                    let current_lines = Some((sym.line_start, sym.line_end));
+
+                    let sig_changed = prev_signature.as_ref() != current_sig.as_ref();
+                    let lines_changed = match (*prev_lines, current_lines) {
+                        (Some((ps, pe)), Some((cs, ce))) => (pe - ps) != (ce - cs),
+                        (None, Some(_)) => true,
+                        _ => false,
+                    };
+
+                    if history.is_empty() || sig_changed || lines_changed {
+                        history.push(HistoryEntry {
+                            commit: commit.clone(),
+                            signature: current_sig.clone(),
+                            lines: current_lines,
+                            existed: true,
+                        });
+                    }
+
+                    *prev_signature = current_sig;
+                    *prev_lines = current_lines;
+                }
+                None => {
+                    if prev_signature.is_some() {
+                        history.push(HistoryEntry {
+                            commit: commit.clone(),
+                            signature: None,
+                            lines: None,
+                            existed: false,
+                        });
+                        *prev_signature = None;
+                        *prev_lines = None;
+                    }
+                }
+            }
+        }
     }
 
+    for history in &mut histories {
+        history.reverse();
+    }
+
+    Ok(histories)
+}
+
+fn symbol_not_found(name: &str) -> anyhow::Error {
+    Failure::new(
+        ErrorCode::NotFound,
+        format!("Symbol '{}' not found in current file", name),
+    )
+    .into()
+}
+
+/// Track how the set of exported symbols (and their signatures) of a file evolved, newest first
+pub fn history_file_api(repo_path: &Path, file_path: &Path) -> Result<Vec<FileApiEntry>> {
+    if !git::is_git_repo(repo_path) {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("Not a git repository: {}", repo_path.display_slash())
+        ));
+    }
+
+    let canonical_file = paths::canonicalize(file_path).context("Failed to resolve file path")?;
+
     let language = indexer::detect_language(&canonical_file);
     if language == Language::Unknown {
-        anyhow::bail!("Unknown or unsupported file type: {}", file_path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::UnsupportedLanguage,
+            format!("Unknown or unsupported file type: {}", file_path.display_slash())
+        ));
     }
 
     let commits = git::get_commits_for_file(repo_path, &canonical_file, None)?;
 
     if commits.is_empty() {
-        anyhow::bail!("No git history found for file: {}", file_path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("No git history found for file: {}", file_path.display_slash())
+        ));
     }
 
     let repo_root = git::get_repo_root(repo_path)?;
-    let mut history: Vec<HistoryEntry> = Vec::new();
-    let mut prev_signature: Option<String> = None;
-    let mut prev_lines: Option<(usize, usize)> = None;
+    let mut history: Vec<FileApiEntry> = Vec::new();
+    let mut prev_surface = ApiSurface::new();
 
     for commit in commits.iter().rev() {
-        let symbol_at_commit = get_symbol_at_commit(
-            &repo_root,
-            &canonical_file,
-            &commit.hash,
-            symbol_name,
-            language,
-        )?;
-
-        match symbol_at_commit {
-            Some(sym) => {
-                let current_sig = sym.signature.clone();
-                let current_lines = Some((sym.line_start, sym.line_end));
-
-                let sig_changed = prev_signature.as_ref() != current_sig.as_ref();
-                let lines_changed = match (prev_lines, current_lines) {
-                    (Some((ps, pe)), Some((cs, ce))) => (pe - ps) != (ce - cs),
-                    (None, Some(_)) => true,
-                    _ => false,
-                };
-
-                if history.is_empty() || sig_changed || lines_changed {
-                    history.push(HistoryEntry {
-                        commit: commit.clone(),
-                        signature: current_sig.clone(),
-                        lines: current_lines,
-                        existed: true,
-                    });
-                }
-
-                prev_signature = current_sig;
-                prev_lines = current_lines;
-            }
-            None => {
-                if prev_signature.is_some() {
-                    history.push(HistoryEntry {
-                        commit: commit.clone(),
-                        signature: None,
-                        lines: None,
-                        existed: false,
-                    });
-                    prev_signature = None;
-                    prev_lines = None;
-                }
+        let surface = match git::get_file_at_commit(&repo_root, &canonical_file, &commit.hash)? {
+            Some(content) => {
+                let file_info = indexer::index_file(&canonical_file, &content, language, None)
+                    .context("Failed to parse file at commit")?;
+                api_surface(&file_info.symbols)
             }
+            None => ApiSurface::new(),
+        };
+
+        let changes = compare_surfaces(&prev_surface, &surface);
+        if !changes.is_empty() {
+            history.push(FileApiEntry {
+                commit: commit.clone(),
+                exported_count: surface.values().map(Vec::len).sum(),
+                changes,
+            });
         }
+
+        prev_surface = surface;
     }
 
     history.reverse();
@@ -211,27 +456,160 @@ pub fn history_symbol(
     Ok(history)
 }
 
-fn get_symbol_at_commit(
+/// Last commit touching each symbol of a file, in source order. One `git blame` of the
+/// working-tree file covers every symbol, where `blame_symbol` re-parses each past version.
+pub fn blame_file(repo_path: &Path, file_path: &Path) -> Result<Vec<SymbolBlame>> {
+    if !git::is_git_repo(repo_path) {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("Not a git repository: {}", repo_path.display_slash())
+        ));
+    }
+
+    let canonical_file = paths::canonicalize(file_path).context("Failed to resolve file path")?;
+
+    if !canonical_file.exists() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::PathNotFound,
+            format!("File does not exist: {}", file_path.display_slash())
+        ));
+    }
+
+    let language = indexer::detect_language(&canonical_file);
+    if language == Language::Unknown {
+        anyhow::bail!(Failure::new(
+            ErrorCode::UnsupportedLanguage,
+            format!("Unknown or unsupported file type: {}", file_path.display_slash())
+        ));
+    }
+
+    let Some(lines) = git::blame_lines(repo_path, &canonical_file)? else {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("No git history found for file: {}", file_path.display_slash())
+        ));
+    };
+
+    let content =
+        std::fs::read_to_string(&canonical_file).context("Failed to read current file")?;
+    let mut symbols = indexer::index_file(&canonical_file, &content, language, None)?.symbols;
+    symbols.sort_by_key(|s| (s.line_start, std::cmp::Reverse(s.line_end)));
+
+    let newest: Vec<Option<&str>> = symbols
+        .iter()
+        .map(|s| newest_commit(&lines, s.line_start, s.line_end))
+        .collect();
+    let mut hashes: Vec<&str> = newest.iter().flatten().copied().collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+    let repo_root = git::get_repo_root(repo_path)?;
+    let commits: HashMap<String, CommitInfo> = git::commit_infos(&repo_root, &hashes)?
+        .into_iter()
+        .map(|c| (c.hash.clone(), c))
+        .collect();
+
+    Ok(symbols
+        .iter()
+        .zip(newest)
+        .map(|(symbol, hash)| SymbolBlame {
+            symbol_name: symbol.name.to_string(),
+            symbol_type: symbol.symbol_type,
+            lines: (symbol.line_start, symbol.line_end),
+            commit: hash.and_then(|h| commits.get(h).cloned()),
+        })
+        .collect())
+}
+
+/// Newest commit among lines `start..=end` (1-based) of a blame, None when one of them is
+/// not committed yet
+fn newest_commit(lines: &[(String, i64)], start: usize, end: usize) -> Option<&str> {
+    let end = end.min(lines.len());
+    let span = lines.get(start.saturating_sub(1)..end)?;
+    if span.iter().any(|(hash, _)| hash.bytes().all(|b| b == b'0')) {
+        return None;
+    }
+    span.iter()
+        .max_by_key(|(_, time)| *time)
+        .map(|(hash, _)| hash.as_str())
+}
+
+fn api_surface(symbols: &[Symbol]) -> ApiSurface {
+    let mut surface = ApiSurface::new();
+    for symbol in symbols.iter().filter(|s| s.is_exported) {
+        surface
+            .entry((symbol.name.to_string(), symbol.symbol_type))
+            .or_default()
+            .push(symbol.signature.clone());
+    }
+    surface
+}
+
+/// Overloads are matched by signature. A name with one signature gone and one new is
+/// re-signed; any other mismatch is reported as the overloads added and deleted.
+fn compare_surfaces(old: &ApiSurface, new: &ApiSurface) -> Vec<ApiChange> {
+    let mut changes = Vec::new();
+    let none = Vec::new();
+
+    let mut keys: Vec<&(String, SymbolType)> = old.keys().chain(new.keys()).collect();
+    keys.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.as_str().cmp(b.1.as_str())));
+    keys.dedup();
+    for key in keys {
+        let (name, symbol_type) = key;
+        let mut deleted: Vec<&Option<String>> = old.get(key).unwrap_or(&none).iter().collect();
+        let mut added: Vec<&Option<String>> = Vec::new();
+        for signature in new.get(key).unwrap_or(&none) {
+            match deleted.iter().position(|old| *old == signature) {
+                Some(i) => {
+                    deleted.remove(i);
+                }
+                None => added.push(signature),
+            }
+        }
+        let change = |change_type,
+                      old_signature: Option<&Option<String>>,
+                      new_signature: Option<&Option<String>>| ApiChange {
+            name: name.clone(),
+            symbol_type: *symbol_type,
+            change_type,
+            old_signature: old_signature.cloned().flatten(),
+            new_signature: new_signature.cloned().flatten(),
+        };
+        if let ([old], [new]) = (deleted.as_slice(), added.as_slice()) {
+            changes.push(change(ChangeType::SignatureChanged, Some(old), Some(new)));
+            continue;
+        }
+        changes.extend(
+            added
+                .into_iter()
+                .map(|sig| change(ChangeType::Added, None, Some(sig))),
+        );
+        changes.extend(
+            deleted
+                .into_iter()
+                .map(|sig| change(ChangeType::Deleted, Some(sig), None)),
+        );
+    }
+
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    changes
+}
+
+/// Symbols of the file as of `commit`, none when it did not exist then
+fn symbols_at_commit(
     repo_root: &Path,
     file_path: &Path,
     commit: &str,
-    symbol_name: &str,
     language: Language,
-) -> Result<Option<Symbol>> {
+) -> Result<Vec<Symbol>> {
     let content = match git::get_file_at_commit(repo_root, file_path, commit)? {
         Some(c) => c,
-        None => return Ok(None),
+        None => return Ok(Vec::new()),
     };
 
     let file_info = indexer::index_file(file_path, &content, language, None)
         .context("Failed to parse file at commit")?;
 
-    let symbol = file_info
-        .symbols
-        .into_iter()
-        .find(|s| s.name == symbol_name);
-
-    Ok(symbol)
+    Ok(file_info.symbols)
 }
 
 #[cfg(test)]
@@ -247,4 +625,97 @@ mod tests {
         );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_batch() {
+        let entries =
+            parse_batch("# audit\nparse src/a.rs\n\n  Config   src/dir with space/b.py\n").unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                BatchEntry {
+                    symbol: "parse".to_string(),
+                    file: PathBuf::from("src/a.rs"),
+                },
+                BatchEntry {
+                    symbol: "Config".to_string(),
+                    file: PathBuf::from("src/dir with space/b.py"),
+                },
+            ]
+        );
+        assert!(parse_batch("parse\n").is_err());
+    }
+
+    #[test]
+    fn test_newest_commit() {
+        let lines: Vec<(String, i64)> = [("a", 100), ("b", 300), ("c", 200), ("0", 400)]
+            .iter()
+            .map(|(c, time)| (c.repeat(40), *time))
+            .collect();
+        let b = "b".repeat(40);
+        assert_eq!(newest_commit(&lines, 1, 3), Some(b.as_str()));
+        assert_eq!(newest_commit(&lines, 3, 3), Some(lines[2].0.as_str()));
+        assert_eq!(newest_commit(&lines, 2, 4), None);
+        assert_eq!(newest_commit(&lines, 9, 9), None);
+    }
+
+    #[test]
+    fn test_compare_surfaces() {
+        let mut old = ApiSurface::new();
+        old.insert(
+            ("keep".to_string(), SymbolType::Function),
+            vec![Some("()".to_string())],
+        );
+        old.insert(("gone".to_string(), SymbolType::Function), vec![None]);
+
+        let mut new = ApiSurface::new();
+        new.insert(
+            ("keep".to_string(), SymbolType::Function),
+            vec![Some("(x: i32)".to_string())],
+        );
+        new.insert(("fresh".to_string(), SymbolType::Class), vec![None]);
+
+        let changes = compare_surfaces(&old, &new);
+        let summary: Vec<(&str, ChangeType)> = changes
+            .iter()
+            .map(|c| (c.name.as_str(), c.change_type))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("fresh", ChangeType::Added),
+                ("gone", ChangeType::Deleted),
+                ("keep", ChangeType::SignatureChanged),
+            ]
+        );
+        assert!(compare_surfaces(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_compare_surfaces_keeps_overloads_apart() {
+        let overloads = |signatures: &[&str]| -> ApiSurface {
+            let signatures = signatures.iter().map(|s| Some(s.to_string())).collect();
+            HashMap::from([(("send".to_string(), SymbolType::Method), signatures)])
+        };
+        let old = overloads(&["(String body)", "(byte[] body)"]);
+
+        // Reordering overloads changes nothing; adding one is an addition, not a re-sign
+        assert!(compare_surfaces(&old, &overloads(&["(byte[] body)", "(String body)"])).is_empty());
+        let added = compare_surfaces(&old, &overloads(&["(String body)", "(byte[] body)", "()"]));
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].change_type, ChangeType::Added);
+        assert_eq!(added[0].new_signature.as_deref(), Some("()"));
+
+        let changed = compare_surfaces(
+            &old,
+            &overloads(&["(String body, int retries)", "(byte[] body)"]),
+        );
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].change_type, ChangeType::SignatureChanged);
+        assert_eq!(changed[0].old_signature.as_deref(), Some("(String body)"));
+        assert_eq!(
+            changed[0].new_signature.as_deref(),
+            Some("(String body, int retries)")
+        );
+    }
 }
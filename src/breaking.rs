@@ -0,0 +1,164 @@
+use crate::diff::{ChangeType, DiffResult, SymbolDiff};
+use crate::paths::DisplaySlash;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Default allowlist file, looked up in the current directory
+pub const DEFAULT_ALLOWLIST_FILE: &str = ".cm-allow-breaking";
+
+/// Intentional breaking changes that should not fail `cm breaking --check`
+#[derive(Debug, Default)]
+pub struct Allowlist {
+    entries: Vec<AllowEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AllowEntry {
+    file: Option<String>,
+    symbol: String,
+}
+
+impl Allowlist {
+    /// Parse allowlist contents: one `symbol` or `path:symbol` per line, `#` starts a comment
+    pub fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| match split_file_and_symbol(line) {
+                Some((file, symbol)) => AllowEntry {
+                    file: Some(file.trim().to_string()),
+                    symbol: symbol.trim().to_string(),
+                },
+                None => AllowEntry {
+                    file: None,
+                    symbol: line.to_string(),
+                },
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read allowlist: {}", path.display_slash()))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn allows(&self, diff: &SymbolDiff) -> bool {
+        self.entries.iter().any(|entry| {
+            if entry.symbol != diff.name && diff.old_name.as_deref() != Some(entry.symbol.as_str())
+            {
+                return false;
+            }
+            match &entry.file {
+                Some(file) => diff.file_path.ends_with(file),
+                None => true,
+            }
+        })
+    }
+}
+
+/// Split `path:symbol` on a single colon, leaving `Type::method` qualifiers intact
+fn split_file_and_symbol(line: &str) -> Option<(&str, &str)> {
+    let bytes = line.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        if b != b':' {
+            continue;
+        }
+        let prev_colon = i > 0 && bytes[i - 1] == b':';
+        let next_colon = i + 1 < bytes.len() && bytes[i + 1] == b':';
+        if !prev_colon && !next_colon {
+            return Some((&line[..i], &line[i + 1..]));
+        }
+    }
+    None
+}
+
+/// Keep only breaking changes to exported symbols; returns the changes that were allowlisted
+pub fn filter_breaking(result: &mut DiffResult, allowlist: &Allowlist) -> Vec<SymbolDiff> {
+    result.symbols.retain(|s| {
+        s.is_exported
+            && matches!(
+                s.change_type,
+                ChangeType::Deleted
+                    | ChangeType::SignatureChanged
+                    | ChangeType::Moved
+                    | ChangeType::Renamed
+            )
+    });
+
+    let (allowed, remaining): (Vec<SymbolDiff>, Vec<SymbolDiff>) = result
+        .symbols
+        .drain(..)
+        .partition(|s| allowlist.allows(s));
+    result.symbols = remaining;
+
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SymbolType;
+    use std::path::PathBuf;
+
+    fn diff(name: &str, file: &str, change_type: ChangeType, is_exported: bool) -> SymbolDiff {
+        SymbolDiff {
+            name: name.to_string(),
+            symbol_type: SymbolType::Function,
+            change_type,
+            file_path: PathBuf::from(file),
+            old_lines: Some((1, 2)),
+            new_lines: None,
+            old_signature: None,
+            new_signature: None,
+            is_exported,
+            old_name: None,
+            old_file_path: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_allowlist() {
+        let allowlist = Allowlist::parse(
+            "# intentional breaks for 2.0\nold_api\nsrc/lib.rs:Parser::new  # renamed\n\n",
+        );
+        assert_eq!(allowlist.entries.len(), 2);
+        assert!(allowlist.allows(&diff("old_api", "/repo/any.rs", ChangeType::Deleted, true)));
+        assert!(allowlist.allows(&diff(
+            "Parser::new",
+            "/repo/src/lib.rs",
+            ChangeType::Deleted,
+            true
+        )));
+        assert!(!allowlist.allows(&diff(
+            "Parser::new",
+            "/repo/src/other.rs",
+            ChangeType::Deleted,
+            true
+        )));
+    }
+
+    #[test]
+    fn test_filter_breaking() {
+        let mut result = DiffResult {
+            commit: "abc".to_string(),
+            against_worktree: false,
+            symbols: vec![
+                diff("removed", "a.rs", ChangeType::Deleted, true),
+                diff("private", "a.rs", ChangeType::Deleted, false),
+                diff("added", "a.rs", ChangeType::Added, true),
+                diff("allowed", "a.rs", ChangeType::SignatureChanged, true),
+            ],
+            files_analyzed: 1,
+        };
+
+        let allowed = filter_breaking(&mut result, &Allowlist::parse("allowed"));
+        let names: Vec<&str> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["removed"]);
+        assert_eq!(allowed.len(), 1);
+    }
+}
@@ -1,5 +1,10 @@
+use crate::failure::{ErrorCode, Failure};
+use crate::fingerprint;
 use crate::index::CodeIndex;
+use crate::indexer;
+use crate::paths::DisplaySlash;
 use anyhow::{anyhow, Context, Result};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
@@ -10,7 +15,7 @@ use std::time::SystemTime;
 
 const CACHE_DIR_NAME: &str = ".codemapper";
 const CACHE_SUBDIR: &str = "cache";
-const CACHE_VERSION: &str = "1.2";
+const CACHE_VERSION: &str = "1.11";
 
 #[derive(Debug)]
 pub enum ValidationResult {
@@ -52,6 +57,9 @@ pub struct CacheMetadata {
     pub symbol_count: usize,
     pub cache_key: String,
     pub file_metadata: HashMap<PathBuf, FileMetadata>,
+    /// Structural fingerprint of the indexed files (see `fingerprint`)
+    #[serde(default)]
+    pub fingerprint: Option<String>,
 }
 
 impl CacheMetadata {
@@ -72,6 +80,7 @@ impl CacheMetadata {
             symbol_count,
             cache_key,
             file_metadata,
+            fingerprint: None,
         }
     }
 }
@@ -95,16 +104,20 @@ impl CacheManager {
     }
 
     /// Get cache file paths (binary and metadata)
+    /// `.codemapper/` in the project root, or the `--cache-dir` override
+    pub fn base_dir(root: &Path, cache_dir: Option<&Path>) -> PathBuf {
+        cache_dir
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| root.join(CACHE_DIR_NAME))
+    }
+
     fn get_cache_paths(
         root: &Path,
         extensions: &[&str],
         cache_dir: Option<&Path>,
     ) -> Result<(PathBuf, PathBuf)> {
         let cache_key = Self::compute_cache_key(root, extensions)?;
-        let base_dir = cache_dir
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| root.join(CACHE_DIR_NAME));
-        let cache_dir_path = base_dir.join(CACHE_SUBDIR);
+        let cache_dir_path = Self::base_dir(root, cache_dir).join(CACHE_SUBDIR);
         let cache_file = cache_dir_path.join(format!("project-{}.bin", &cache_key[..16]));
         let meta_file = cache_dir_path.join(format!("project-{}.meta.json", &cache_key[..16]));
         Ok((cache_file, meta_file))
@@ -133,53 +146,14 @@ impl CacheManager {
         root: &Path,
         extensions: &[&str],
     ) -> Result<HashMap<PathBuf, FileMetadata>> {
-        use walkdir::WalkDir;
-
-        const IGNORED_DIRS: &[&str] = &[
-            ".codemapper",
-            ".git",
-            "node_modules",
-            "__pycache__",
-            "target",
-            "dist",
-            "build",
-        ];
-
-        let mut metadata_map = HashMap::new();
-
-        for entry in WalkDir::new(root)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| {
-                if e.file_type().is_dir() {
-                    let dir_name = e.file_name().to_string_lossy();
-                    !IGNORED_DIRS.contains(&dir_name.as_ref())
-                } else {
-                    true
-                }
+        let metadata_map = indexer::discover_files(root, extensions)
+            .into_par_iter()
+            // Skip files we can't read
+            .filter_map(|path| {
+                let metadata = Self::compute_file_metadata_single(&path).ok()?;
+                Some((path, metadata))
             })
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-
-            if let Some(ext) = path.extension() {
-                let ext_str = ext.to_string_lossy();
-                if extensions.iter().any(|&e| e == ext_str) {
-                    match Self::compute_file_metadata_single(path) {
-                        Ok(metadata) => {
-                            metadata_map.insert(path.to_path_buf(), metadata);
-                        }
-                        Err(_) => {
-                            // Skip files we can't read
-                            continue;
-                        }
-                    }
-                }
-            }
-        }
+            .collect();
 
         Ok(metadata_map)
     }
@@ -218,7 +192,9 @@ impl CacheManager {
                     None
                 }
             })
-            .ok_or_else(|| anyhow::anyhow!("Missing size for change: {}", change.path.display()))?;
+            .ok_or_else(|| {
+                anyhow::anyhow!("Missing size for change: {}", change.path.display_slash())
+            })?;
 
         let mtime = change
             .mtime
@@ -230,7 +206,7 @@ impl CacheManager {
                 }
             })
             .ok_or_else(|| {
-                anyhow::anyhow!("Missing mtime for change: {}", change.path.display())
+                anyhow::anyhow!("Missing mtime for change: {}", change.path.display_slash())
             })?;
 
         Ok(FileMetadata { hash, size, mtime })
@@ -241,52 +217,17 @@ impl CacheManager {
         root: &Path,
         extensions: &[String],
     ) -> Result<HashMap<PathBuf, (u64, SystemTime)>> {
-        use walkdir::WalkDir;
-
-        const IGNORED_DIRS: &[&str] = &[
-            ".codemapper",
-            ".git",
-            "node_modules",
-            "__pycache__",
-            "target",
-            "dist",
-            "build",
-        ];
-
-        let mut stats = HashMap::new();
-
-        for entry in WalkDir::new(root)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| {
-                if e.file_type().is_dir() {
-                    let dir_name = e.file_name().to_string_lossy();
-                    !IGNORED_DIRS.contains(&dir_name.as_ref())
-                } else {
-                    true
-                }
+        let ext_refs: Vec<&str> = extensions.iter().map(|e| e.as_str()).collect();
+
+        let stats = indexer::discover_files(root, &ext_refs)
+            .into_par_iter()
+            .filter_map(|path| {
+                let metadata = fs::metadata(&path).ok()?;
+                let size = metadata.len();
+                let mtime = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                Some((path, (size, mtime)))
             })
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-
-            if let Some(ext) = path.extension() {
-                let ext_str = ext.to_string_lossy();
-                if extensions.iter().any(|e| e == &ext_str) {
-                    match fs::metadata(path) {
-                        Ok(metadata) => {
-                            let size = metadata.len();
-                            let mtime = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-                            stats.insert(path.to_path_buf(), (size, mtime));
-                        }
-                        Err(_) => continue,
-                    }
-                }
-            }
-        }
+            .collect();
 
         Ok(stats)
     }
@@ -359,6 +300,7 @@ impl CacheManager {
 
         metadata.file_count = index.total_files();
         metadata.symbol_count = index.total_symbols();
+        metadata.fingerprint = Some(fingerprint::compute(index, root).hash);
 
         let cache_data = bincode::serialize(index).context("Failed to serialize index")?;
         let mut cache_writer =
@@ -392,19 +334,79 @@ impl CacheManager {
 
         for file in index.files() {
             if !file_metadata.contains_key(&file.path) {
-                return Err(anyhow!("missing metadata for {}", file.path.display()));
+                return Err(anyhow!("missing metadata for {}", file.path.display_slash()));
             }
         }
 
         for (path, metadata) in file_metadata {
             if metadata.hash.is_empty() {
-                return Err(anyhow!("empty hash for {}", path.display()));
+                return Err(anyhow!("empty hash for {}", path.display_slash()));
             }
         }
 
         Ok(())
     }
 
+    /// File count recorded in the cache metadata, without validating the cache.
+    /// Lets callers size up a repository without walking it.
+    pub fn cached_file_count(
+        root: &Path,
+        extensions: &[&str],
+        cache_dir: Option<&Path>,
+    ) -> Option<usize> {
+        #[derive(Deserialize)]
+        struct Counts {
+            version: String,
+            file_count: usize,
+        }
+
+        let (cache_file, meta_file) = Self::get_cache_paths(root, extensions, cache_dir).ok()?;
+        if !cache_file.exists() {
+            return None;
+        }
+
+        let meta_data = fs::read_to_string(meta_file).ok()?;
+        let counts: Counts = serde_json::from_str(&meta_data).ok()?;
+        (counts.version == CACHE_VERSION).then_some(counts.file_count)
+    }
+
+    /// Index as last saved, without checking files for changes. Quick enough for shell
+    /// completion; `None` when there is no readable cache for these extensions.
+    pub fn load_unchecked(
+        root: &Path,
+        extensions: &[&str],
+        cache_dir: Option<&Path>,
+    ) -> Option<CodeIndex> {
+        Self::cached_file_count(root, extensions, cache_dir)?;
+        let (cache_file, _) = Self::get_cache_paths(root, extensions, cache_dir).ok()?;
+        let cache_reader = BufReader::new(File::open(cache_file).ok()?);
+        bincode::deserialize_from(cache_reader).ok()
+    }
+
+    /// Identifies the saved index while no indexed file has changed since it was saved: a
+    /// hash of its metadata. `None` when there is no cache or it is out of date.
+    pub fn generation(
+        root: &Path,
+        extensions: &[&str],
+        cache_dir: Option<&Path>,
+    ) -> Option<String> {
+        let (cache_file, meta_file) = Self::get_cache_paths(root, extensions, cache_dir).ok()?;
+        if !cache_file.exists() {
+            return None;
+        }
+        let meta_data = fs::read_to_string(meta_file).ok()?;
+        let metadata: CacheMetadata = serde_json::from_str(&meta_data).ok()?;
+        if metadata.version != CACHE_VERSION {
+            return None;
+        }
+        match Self::validate_with_hashes(&metadata, root).ok()? {
+            ValidationResult::Valid => {
+                Some(blake3::hash(meta_data.as_bytes()).to_hex().to_string())
+            }
+            _ => None,
+        }
+    }
+
     /// Load CodeIndex from cache with validation
     /// Returns (index, metadata, changed_files)
     /// changed_files is empty if cache is valid, or contains files that need updating
@@ -423,7 +425,10 @@ impl CacheManager {
         // Load metadata
         let meta_data = fs::read_to_string(&meta_file).context("Failed to read metadata file")?;
         let metadata: CacheMetadata =
-            serde_json::from_str(&meta_data).context("Failed to parse metadata")?;
+            serde_json::from_str(&meta_data).context(Failure::new(
+                ErrorCode::CacheCorrupt,
+                "Failed to parse cache metadata",
+            ))?;
 
         // Validate cache version
         if metadata.version != CACHE_VERSION {
@@ -442,8 +447,9 @@ impl CacheManager {
                 // Load cache as-is
                 let cache_reader =
                     BufReader::new(File::open(&cache_file).context("Failed to open cache file")?);
-                let index: CodeIndex = bincode::deserialize_from(cache_reader)
-                    .context("Failed to deserialize index")?;
+                let index: CodeIndex = bincode::deserialize_from(cache_reader).context(
+                    Failure::new(ErrorCode::CacheCorrupt, "Failed to deserialize index"),
+                )?;
 
                 Ok(Some((index, metadata, Vec::new())))
             }
@@ -451,8 +457,9 @@ impl CacheManager {
                 // Load cache but needs incremental update
                 let cache_reader =
                     BufReader::new(File::open(&cache_file).context("Failed to open cache file")?);
-                let index: CodeIndex = bincode::deserialize_from(cache_reader)
-                    .context("Failed to deserialize index")?;
+                let index: CodeIndex = bincode::deserialize_from(cache_reader).context(
+                    Failure::new(ErrorCode::CacheCorrupt, "Failed to deserialize index"),
+                )?;
 
                 Ok(Some((index, metadata, changed_files)))
             }
@@ -1,11 +1,16 @@
+use crate::confidence::Confidence;
+use crate::deadline;
+use crate::failure::{ErrorCode, Failure};
 use crate::index::CodeIndex;
-use crate::models::{Language, Symbol, SymbolType};
+use crate::models::{self, FileInfo, Language, ReferenceKind, Symbol, SymbolType};
+use crate::parser::{cached_query, pooled_parse};
+use crate::paths::DisplaySlash;
 use anyhow::{Context, Result};
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Parser, Query, QueryCursor};
+use tree_sitter::QueryCursor;
 
 #[derive(Debug, Clone)]
 pub struct TraceStep {
@@ -38,6 +43,10 @@ pub struct TestInfo {
     pub line: usize,
     pub call_line: usize,
     pub context: String,
+    pub confidence: Confidence,
+    /// Linked by the test's name (`test_parse` for `parse`) rather than a call in it;
+    /// `call_line` is then the test's own line
+    pub by_name: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +67,26 @@ pub struct UntestedInfo {
     pub signature: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    /// As written; JS/TS test blocks lose the `test:` prefix they are indexed with
+    pub name: String,
+    pub file_path: String,
+    pub line: usize,
+    /// Enclosing suites, outermost first: `describe` blocks, test classes, Rust `mod`s
+    pub suites: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrphanTest {
+    pub name: String,
+    pub test_type: SymbolType,
+    pub file_path: String,
+    pub line: usize,
+    /// The symbol the name points at: mentioned in the test, defined nowhere
+    pub target: String,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EntrypointCategory {
     MainEntry,
@@ -82,9 +111,22 @@ pub struct CallInfo {
     pub file_path: String,
     pub line: usize,
     pub context: String,
+    /// For callers, how surely the call is to the queried symbol; for callees, how surely
+    /// `file_path` holds the called definition
+    pub confidence: Confidence,
 }
 
 pub fn find_callers(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result<Vec<CallInfo>> {
+    find_callers_in(index, symbol_name, fuzzy, None)
+}
+
+/// Like `find_callers`, but only parses `candidates` (e.g. files a grep prefilter matched)
+pub fn find_callers_in(
+    index: &CodeIndex,
+    symbol_name: &str,
+    fuzzy: bool,
+    candidates: Option<&HashSet<PathBuf>>,
+) -> Result<Vec<CallInfo>> {
     let mut callers = Vec::new();
     let mut seen = HashSet::new();
 
@@ -95,44 +137,55 @@ pub fn find_callers(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result
 
     let qualified_context = qualifier_context_pattern(raw);
     let qualified_context_lower = qualified_context.as_ref().map(|s| s.to_lowercase());
+    // A qualifier checked against the call site picks out one of several same-named definitions
+    let definitions = if qualified_context_lower.is_some() {
+        1
+    } else {
+        index.query_symbol(&needle).len()
+    };
+    let base_matches = |call_name: &str| {
+        let call_needle = normalize_qualified_name(call_name);
+        if fuzzy {
+            call_needle.to_lowercase().contains(&needle.to_lowercase())
+                || call_name
+                    .to_lowercase()
+                    .contains(&qualified_needle.to_lowercase())
+        } else {
+            call_needle == needle || call_name == qualified_needle
+        }
+    };
+    // An exact name only needs the files the index recorded a reference to it in
+    let referencing: Option<HashSet<&Path>> = (!fuzzy && index.has_references()).then(|| {
+        index
+            .references_to(&needle)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    });
 
-    for file_info in index.files() {
-        let content = match fs::read_to_string(&file_info.path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-
-        let calls = extract_calls_from_file(&content, &file_info.path, file_info.language)?;
+    for file_info in candidate_files(index, candidates) {
+        if referencing
+            .as_ref()
+            .is_some_and(|files| !files.contains(file_info.path.as_path()))
+        {
+            continue;
+        }
+        let calls = calls_matching(index, file_info, base_matches)?;
 
         for (call_name, line, context) in calls {
             let call_needle = normalize_qualified_name(&call_name);
 
-            let base_matches = if fuzzy {
-                call_needle.to_lowercase().contains(&needle.to_lowercase())
-                    || call_name
-                        .to_lowercase()
-                        .contains(&qualified_needle.to_lowercase())
-            } else {
-                call_needle == needle || call_name == qualified_needle
-            };
-
-            let matches = if let Some(ref qual) = qualified_context_lower {
-                if !base_matches {
-                    false
-                } else {
-                    let ctx_no_ws: String = context
-                        .chars()
-                        .filter(|c| !c.is_whitespace())
-                        .collect::<String>()
-                        .to_lowercase();
-                    ctx_no_ws.contains(qual)
-                }
-            } else {
-                base_matches
-            };
+            let matches = qualified_context_lower.as_ref().is_none_or(|qual| {
+                let ctx_no_ws: String = context
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect::<String>()
+                    .to_lowercase();
+                ctx_no_ws.contains(qual)
+            });
 
             if matches {
-                let key = format!("{}:{}", file_info.path.display(), line);
+                let key = format!("{}:{}", file_info.path.display_slash(), line);
                 if seen.contains(&key) {
                     continue;
                 }
@@ -142,14 +195,15 @@ pub fn find_callers(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result
 
                 callers.push(CallInfo {
                     caller_name: caller_symbol
-                        .map(|s| s.name.clone())
+                        .map(|s| s.name.to_string())
                         .unwrap_or_else(|| "<top-level>".to_string()),
                     caller_type: caller_symbol
                         .map(|s| s.symbol_type)
                         .unwrap_or(SymbolType::Function),
-                    file_path: file_info.path.display().to_string(),
+                    file_path: file_info.path.display_slash().to_string(),
                     line,
                     context: context.trim().to_string(),
+                    confidence: Confidence::of_ast_match(&call_needle, &needle, definitions),
                 });
             }
         }
@@ -158,6 +212,75 @@ pub fn find_callers(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result
     Ok(callers)
 }
 
+/// Lines of `file`'s `content` (1-based) holding a call to exactly `name`, as the syntax tree
+/// sees them; from the references the index recorded for the file when it has them
+pub fn lines_calling(
+    index: &CodeIndex,
+    file: &FileInfo,
+    content: &str,
+    name: &str,
+) -> Result<HashSet<usize>> {
+    if let Some(references) = index.file_references(&file.path) {
+        return Ok(references
+            .iter()
+            .filter(|r| r.kind == ReferenceKind::Call && r.target() == name)
+            .map(|r| r.line)
+            .collect());
+    }
+    Ok(extract_calls_from_source(content, file.language)?
+        .into_iter()
+        .filter(|(call_name, _, _)| normalize_qualified_name(call_name) == name)
+        .map(|(_, line, _)| line)
+        .collect())
+}
+
+/// The callers of each of `names` (unqualified), in one pass over the index instead of one
+/// `find_callers` per name
+pub fn callers_of_each(
+    index: &CodeIndex,
+    names: &HashSet<&str>,
+) -> Result<HashMap<String, Vec<CallInfo>>> {
+    let mut callers: HashMap<String, Vec<CallInfo>> = HashMap::new();
+    let definitions: HashMap<&str, usize> = names
+        .iter()
+        .map(|name| (*name, index.query_symbol(name).len()))
+        .collect();
+
+    for file_info in candidate_files(index, None) {
+        let mut seen = HashSet::new();
+        for (call_name, line, context) in calls_matching(index, file_info, |call_name| {
+            definitions.contains_key(models::unqualified(call_name))
+        })? {
+            let call_needle = normalize_qualified_name(&call_name);
+            let Some(&definitions) = definitions.get(call_needle.as_str()) else {
+                continue;
+            };
+            if !seen.insert((call_needle.clone(), line)) {
+                continue;
+            }
+
+            let caller_symbol = find_enclosing_symbol(index, &file_info.path, line);
+            callers
+                .entry(call_needle.clone())
+                .or_default()
+                .push(CallInfo {
+                    caller_name: caller_symbol
+                        .map(|s| s.name.to_string())
+                        .unwrap_or_else(|| "<top-level>".to_string()),
+                    caller_type: caller_symbol
+                        .map(|s| s.symbol_type)
+                        .unwrap_or(SymbolType::Function),
+                    file_path: file_info.path.display_slash().to_string(),
+                    line,
+                    context: context.trim().to_string(),
+                    confidence: Confidence::of_ast_match(&call_needle, &call_needle, definitions),
+                });
+        }
+    }
+
+    Ok(callers)
+}
+
 pub fn find_callees(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result<Vec<CallInfo>> {
     let symbol_name = normalize_qualified_name(symbol_name);
 
@@ -204,7 +327,7 @@ pub fn find_callees(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result
         for (call_name, relative_line, context) in calls {
             let dedup_key = format!(
                 "{}:{}:{}",
-                symbol.file_path.display(),
+                symbol.file_path.display_slash(),
                 call_name,
                 relative_line
             );
@@ -214,14 +337,16 @@ pub fn find_callees(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result
             global_seen.insert(dedup_key);
 
             let target_symbols = index.query_symbol(&call_name);
+            let confidence = Confidence::of_ast_match(&call_name, &call_name, target_symbols.len());
 
             if let Some(target) = target_symbols.first() {
                 all_callees.push(CallInfo {
                     caller_name: call_name,
                     caller_type: target.symbol_type,
-                    file_path: target.file_path.display().to_string(),
+                    file_path: target.file_path.display_slash().to_string(),
                     line: target.line_start,
                     context: target.signature.clone().unwrap_or_default(),
+                    confidence,
                 });
             } else {
                 all_callees.push(CallInfo {
@@ -230,6 +355,7 @@ pub fn find_callees(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result
                     file_path: "<external>".to_string(),
                     line: symbol.line_start + relative_line,
                     context: context.trim().to_string(),
+                    confidence,
                 });
             }
         }
@@ -239,17 +365,8 @@ pub fn find_callees(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result
 }
 
 fn normalize_qualified_name(name: &str) -> String {
-    let trimmed = name.trim();
-
     // allow callers/callees/impact to accept `Type::method` or `Type.method`
-    if let Some((_, tail)) = trimmed.rsplit_once("::") {
-        return tail.to_string();
-    }
-    if let Some((_, tail)) = trimmed.rsplit_once('.') {
-        return tail.to_string();
-    }
-
-    trimmed.to_string()
+    models::unqualified(name).to_string()
 }
 
 fn qualifier_context_pattern(raw: &str) -> Option<String> {
@@ -261,15 +378,68 @@ fn qualifier_context_pattern(raw: &str) -> Option<String> {
     }
 }
 
+/// Files to scan, in index order; stops early once the `--timeout` budget runs out
+fn candidate_files<'a>(
+    index: &'a CodeIndex,
+    candidates: Option<&'a HashSet<PathBuf>>,
+) -> impl Iterator<Item = &'a FileInfo> {
+    index
+        .files()
+        .filter(move |f| candidates.is_none_or(|c| c.contains(&f.path)))
+        .take_while(|_| !deadline::expired())
+}
+
 fn find_enclosing_symbol<'a>(index: &'a CodeIndex, path: &Path, line: usize) -> Option<&'a Symbol> {
     let symbols = index.get_file_symbols(path);
 
     symbols
         .into_iter()
         .filter(|s| s.line_start <= line && s.line_end >= line)
+        .filter(|s| s.symbol_type != SymbolType::Field)
         .min_by_key(|s| s.line_end - s.line_start)
 }
 
+/// Calls in `file_info` whose name `wanted` accepts, as (name, line, context line). With the
+/// file's references recorded in the index, the file is only read for the lines of matches,
+/// and not at all without any; otherwise it is read and parsed.
+fn calls_matching(
+    index: &CodeIndex,
+    file_info: &FileInfo,
+    wanted: impl Fn(&str) -> bool,
+) -> Result<Vec<(String, usize, String)>> {
+    if let Some(references) = index.file_references(&file_info.path) {
+        let hits: Vec<_> = references
+            .iter()
+            .filter(|r| r.kind == ReferenceKind::Call && wanted(&r.name))
+            .collect();
+        if hits.is_empty() {
+            return Ok(Vec::new());
+        }
+        let Ok(content) = fs::read_to_string(&file_info.path) else {
+            return Ok(Vec::new());
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        return Ok(hits
+            .into_iter()
+            .map(|r| {
+                let context = lines.get(r.line.wrapping_sub(1)).unwrap_or(&"");
+                (r.name.clone(), r.line, context.to_string())
+            })
+            .collect());
+    }
+
+    let content = match fs::read_to_string(&file_info.path) {
+        Ok(c) => c,
+        Err(_) => return Ok(Vec::new()),
+    };
+    Ok(
+        extract_calls_from_file(&content, &file_info.path, file_info.language)?
+            .into_iter()
+            .filter(|(name, _, _)| wanted(name))
+            .collect(),
+    )
+}
+
 fn extract_calls_from_file(
     content: &str,
     path: &Path,
@@ -285,7 +455,7 @@ fn extract_calls_from_file(
     extract_calls_from_source(content, language)
 }
 
-fn extract_calls_from_source(
+pub(crate) fn extract_calls_from_source(
     content: &str,
     language: Language,
 ) -> Result<Vec<(String, usize, String)>> {
@@ -296,23 +466,26 @@ fn extract_calls_from_source(
         Language::Go => extract_go_calls(content),
         Language::Java => extract_java_calls(content),
         Language::C => extract_c_calls(content),
+        Language::Cpp => extract_cpp_calls(content),
+        Language::CSharp => extract_csharp_calls(content),
+        Language::Php => extract_php_calls(content),
+        Language::Kotlin => extract_kotlin_calls(content),
+        Language::Swift => extract_swift_calls(content),
+        Language::Scala => extract_scala_calls(content),
+        Language::Lua => extract_lua_calls(content),
+        Language::Zig => extract_zig_calls(content),
         _ => Ok(Vec::new()),
     }
 }
 
 fn extract_rust_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
-    let mut parser = Parser::new();
     let language = tree_sitter_rust::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set Rust language")?;
-
-    let tree = match parser.parse(content, None) {
+    let tree = match pooled_parse(&language, content).context("Failed to set Rust language")? {
         Some(t) => t,
         None => return Ok(Vec::new()),
     };
 
-    let query = Query::new(
+    let query = cached_query(
         &language,
         r#"
         (call_expression
@@ -330,7 +503,7 @@ fn extract_rust_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
     .context("Failed to create call query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
     let mut calls = Vec::new();
     let mut seen_lines = HashSet::new();
 
@@ -463,32 +636,409 @@ fn collect_identifiers_from_token_tree(
     }
 }
 
-fn extract_python_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_python::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set Python language")?;
-
-    let tree = match parser.parse(content, None) {
+fn extract_python_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_python::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set Python language")? {
+        Some(t) => t,
+        None => return Ok(Vec::new()),
+    };
+
+    let query = cached_query(
+        &language,
+        r#"
+        (call
+            function: (identifier) @call.name) @call.expr
+        (call
+            function: (attribute
+                attribute: (identifier) @call.method)) @call.method_expr
+        "#,
+    )
+    .context("Failed to create call query")?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    let mut calls = Vec::new();
+    let mut seen_lines = HashSet::new();
+
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            let capture_name = query
+                .capture_names()
+                .get(capture.index as usize)
+                .map(|s| s.as_ref());
+
+            let is_name = matches!(capture_name, Some("call.name") | Some("call.method"));
+
+            if is_name {
+                let name = capture
+                    .node
+                    .utf8_text(content.as_bytes())
+                    .unwrap_or_default()
+                    .to_string();
+                let line = capture.node.start_position().row + 1;
+
+                if seen_lines.contains(&(name.clone(), line)) {
+                    continue;
+                }
+                seen_lines.insert((name.clone(), line));
+
+                let context = content.lines().nth(line - 1).unwrap_or("").to_string();
+                calls.push((name, line, context));
+            }
+        }
+    }
+
+    Ok(calls)
+}
+
+fn extract_js_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_javascript::LANGUAGE.into();
+    let tree =
+        match pooled_parse(&language, content).context("Failed to set JavaScript language")? {
+            Some(t) => t,
+            None => return Ok(Vec::new()),
+        };
+
+    let query = cached_query(
+        &language,
+        r#"
+        (call_expression
+            function: (identifier) @call.name) @call.expr
+        (call_expression
+            function: (member_expression
+                property: (property_identifier) @call.method)) @call.method_expr
+        "#,
+    )
+    .context("Failed to create call query")?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    let mut calls = Vec::new();
+    let mut seen_lines = HashSet::new();
+
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            let capture_name = query
+                .capture_names()
+                .get(capture.index as usize)
+                .map(|s| s.as_ref());
+
+            let is_name = matches!(capture_name, Some("call.name") | Some("call.method"));
+
+            if is_name {
+                let name = capture
+                    .node
+                    .utf8_text(content.as_bytes())
+                    .unwrap_or_default()
+                    .to_string();
+                let line = capture.node.start_position().row + 1;
+
+                if seen_lines.contains(&(name.clone(), line)) {
+                    continue;
+                }
+                seen_lines.insert((name.clone(), line));
+
+                let context = content.lines().nth(line - 1).unwrap_or("").to_string();
+                calls.push((name, line, context));
+            }
+        }
+    }
+
+    Ok(calls)
+}
+
+fn extract_go_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_go::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set Go language")? {
+        Some(t) => t,
+        None => return Ok(Vec::new()),
+    };
+
+    let query = cached_query(
+        &language,
+        r#"
+        (call_expression
+            function: (identifier) @call.name) @call.expr
+        (call_expression
+            function: (selector_expression
+                field: (field_identifier) @call.method)) @call.method_expr
+        "#,
+    )
+    .context("Failed to create call query")?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    let mut calls = Vec::new();
+    let mut seen_lines = HashSet::new();
+
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            let capture_name = query
+                .capture_names()
+                .get(capture.index as usize)
+                .map(|s| s.as_ref());
+
+            let is_name = matches!(capture_name, Some("call.name") | Some("call.method"));
+
+            if is_name {
+                let name = capture
+                    .node
+                    .utf8_text(content.as_bytes())
+                    .unwrap_or_default()
+                    .to_string();
+                let line = capture.node.start_position().row + 1;
+
+                if seen_lines.contains(&(name.clone(), line)) {
+                    continue;
+                }
+                seen_lines.insert((name.clone(), line));
+
+                let context = content.lines().nth(line - 1).unwrap_or("").to_string();
+                calls.push((name, line, context));
+            }
+        }
+    }
+
+    Ok(calls)
+}
+
+fn extract_java_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_java::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set Java language")? {
+        Some(t) => t,
+        None => return Ok(Vec::new()),
+    };
+
+    let query = cached_query(
+        &language,
+        r#"
+        (method_invocation
+            name: (identifier) @call.name) @call.expr
+        "#,
+    )
+    .context("Failed to create call query")?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    let mut calls = Vec::new();
+    let mut seen_lines = HashSet::new();
+
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            let capture_name = query
+                .capture_names()
+                .get(capture.index as usize)
+                .map(|s| s.as_ref());
+
+            if capture_name == Some("call.name") {
+                let name = capture
+                    .node
+                    .utf8_text(content.as_bytes())
+                    .unwrap_or_default()
+                    .to_string();
+                let line = capture.node.start_position().row + 1;
+
+                if seen_lines.contains(&(name.clone(), line)) {
+                    continue;
+                }
+                seen_lines.insert((name.clone(), line));
+
+                let context = content.lines().nth(line - 1).unwrap_or("").to_string();
+                calls.push((name, line, context));
+            }
+        }
+    }
+
+    Ok(calls)
+}
+
+fn extract_csharp_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_c_sharp::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set C# language")? {
+        Some(t) => t,
+        None => return Ok(Vec::new()),
+    };
+
+    let query = cached_query(
+        &language,
+        r#"
+        (invocation_expression
+            function: [
+                (identifier) @call.name
+                (generic_name (identifier) @call.name)
+                (member_access_expression name: (identifier) @call.name)
+                (member_access_expression name: (generic_name (identifier) @call.name))
+            ]) @call.expr
+        "#,
+    )
+    .context("Failed to create call query")?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    let mut calls = Vec::new();
+    let mut seen_lines = HashSet::new();
+
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            let capture_name = query
+                .capture_names()
+                .get(capture.index as usize)
+                .map(|s| s.as_ref());
+
+            if capture_name == Some("call.name") {
+                let name = capture
+                    .node
+                    .utf8_text(content.as_bytes())
+                    .unwrap_or_default()
+                    .to_string();
+                let line = capture.node.start_position().row + 1;
+
+                if seen_lines.contains(&(name.clone(), line)) {
+                    continue;
+                }
+                seen_lines.insert((name.clone(), line));
+
+                let context = content.lines().nth(line - 1).unwrap_or("").to_string();
+                calls.push((name, line, context));
+            }
+        }
+    }
+
+    Ok(calls)
+}
+
+fn extract_php_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_php::LANGUAGE_PHP.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set PHP language")? {
+        Some(t) => t,
+        None => return Ok(Vec::new()),
+    };
+
+    let query = cached_query(
+        &language,
+        r#"
+        (function_call_expression
+            function: [
+                (name) @call.name
+                (qualified_name (name) @call.name)
+            ]) @call.expr
+        (member_call_expression name: (name) @call.name) @call.expr
+        (nullsafe_member_call_expression name: (name) @call.name) @call.expr
+        (scoped_call_expression name: (name) @call.name) @call.expr
+        "#,
+    )
+    .context("Failed to create call query")?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    let mut calls = Vec::new();
+    let mut seen_lines = HashSet::new();
+
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            let capture_name = query
+                .capture_names()
+                .get(capture.index as usize)
+                .map(|s| s.as_ref());
+
+            if capture_name == Some("call.name") {
+                let name = capture
+                    .node
+                    .utf8_text(content.as_bytes())
+                    .unwrap_or_default()
+                    .to_string();
+                let line = capture.node.start_position().row + 1;
+
+                if seen_lines.contains(&(name.clone(), line)) {
+                    continue;
+                }
+                seen_lines.insert((name.clone(), line));
+
+                let context = content.lines().nth(line - 1).unwrap_or("").to_string();
+                calls.push((name, line, context));
+            }
+        }
+    }
+
+    Ok(calls)
+}
+
+fn extract_kotlin_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_kotlin_ng::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set Kotlin language")? {
+        Some(t) => t,
+        None => return Ok(Vec::new()),
+    };
+
+    let query = cached_query(
+        &language,
+        r#"
+        (call_expression . (identifier) @call.name) @call.expr
+        (call_expression
+            . (navigation_expression (identifier) @call.name .)) @call.expr
+        "#,
+    )
+    .context("Failed to create call query")?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    let mut calls = Vec::new();
+    let mut seen_lines = HashSet::new();
+
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            let capture_name = query
+                .capture_names()
+                .get(capture.index as usize)
+                .map(|s| s.as_ref());
+
+            if capture_name == Some("call.name") {
+                let name = capture
+                    .node
+                    .utf8_text(content.as_bytes())
+                    .unwrap_or_default()
+                    .to_string();
+                let line = capture.node.start_position().row + 1;
+
+                if seen_lines.contains(&(name.clone(), line)) {
+                    continue;
+                }
+                seen_lines.insert((name.clone(), line));
+
+                let context = content.lines().nth(line - 1).unwrap_or("").to_string();
+                calls.push((name, line, context));
+            }
+        }
+    }
+
+    Ok(calls)
+}
+
+fn extract_scala_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_scala::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set Scala language")? {
         Some(t) => t,
         None => return Ok(Vec::new()),
     };
 
-    let query = Query::new(
+    // `f(x)`, `a.f(x)`, `f[T](x)`, and `new Order(x)` as a call of the class
+    let query = cached_query(
         &language,
         r#"
-        (call
-            function: (identifier) @call.name) @call.expr
-        (call
-            function: (attribute
-                attribute: (identifier) @call.method)) @call.method_expr
+        (call_expression function: (identifier) @call.name) @call.expr
+        (call_expression function: (field_expression field: (identifier) @call.name)) @call.expr
+        (call_expression
+            function: (generic_function function: (identifier) @call.name)) @call.expr
+        (call_expression
+            function: (generic_function
+                function: (field_expression field: (identifier) @call.name))) @call.expr
+        (instance_expression (type_identifier) @call.name) @call.expr
         "#,
     )
     .context("Failed to create call query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
     let mut calls = Vec::new();
     let mut seen_lines = HashSet::new();
 
@@ -499,9 +1049,7 @@ fn extract_python_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
                 .get(capture.index as usize)
                 .map(|s| s.as_ref());
 
-            let is_name = matches!(capture_name, Some("call.name") | Some("call.method"));
-
-            if is_name {
+            if capture_name == Some("call.name") {
                 let name = capture
                     .node
                     .utf8_text(content.as_bytes())
@@ -523,32 +1071,27 @@ fn extract_python_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
     Ok(calls)
 }
 
-fn extract_js_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_javascript::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set JavaScript language")?;
-
-    let tree = match parser.parse(content, None) {
+fn extract_lua_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_lua::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set Lua language")? {
         Some(t) => t,
         None => return Ok(Vec::new()),
     };
 
-    let query = Query::new(
+    // `f(x)`, `M.f(x)`, `obj:f(x)` and `require "x"`
+    let query = cached_query(
         &language,
         r#"
-        (call_expression
-            function: (identifier) @call.name) @call.expr
-        (call_expression
-            function: (member_expression
-                property: (property_identifier) @call.method)) @call.method_expr
+        (function_call name: (identifier) @call.name) @call.expr
+        (function_call name: (dot_index_expression field: (identifier) @call.name)) @call.expr
+        (function_call
+            name: (method_index_expression method: (identifier) @call.name)) @call.expr
         "#,
     )
     .context("Failed to create call query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
     let mut calls = Vec::new();
     let mut seen_lines = HashSet::new();
 
@@ -559,9 +1102,7 @@ fn extract_js_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
                 .get(capture.index as usize)
                 .map(|s| s.as_ref());
 
-            let is_name = matches!(capture_name, Some("call.name") | Some("call.method"));
-
-            if is_name {
+            if capture_name == Some("call.name") {
                 let name = capture
                     .node
                     .utf8_text(content.as_bytes())
@@ -583,32 +1124,26 @@ fn extract_js_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
     Ok(calls)
 }
 
-fn extract_go_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_go::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set Go language")?;
-
-    let tree = match parser.parse(content, None) {
+fn extract_zig_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_zig::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set Zig language")? {
         Some(t) => t,
         None => return Ok(Vec::new()),
     };
 
-    let query = Query::new(
+    // `f(x)` and `std.debug.print(x)`
+    let query = cached_query(
         &language,
         r#"
+        (call_expression function: (identifier) @call.name) @call.expr
         (call_expression
-            function: (identifier) @call.name) @call.expr
-        (call_expression
-            function: (selector_expression
-                field: (field_identifier) @call.method)) @call.method_expr
+            function: (field_expression member: (identifier) @call.name)) @call.expr
         "#,
     )
     .context("Failed to create call query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
     let mut calls = Vec::new();
     let mut seen_lines = HashSet::new();
 
@@ -619,9 +1154,7 @@ fn extract_go_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
                 .get(capture.index as usize)
                 .map(|s| s.as_ref());
 
-            let is_name = matches!(capture_name, Some("call.name") | Some("call.method"));
-
-            if is_name {
+            if capture_name == Some("call.name") {
                 let name = capture
                     .node
                     .utf8_text(content.as_bytes())
@@ -643,29 +1176,94 @@ fn extract_go_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
     Ok(calls)
 }
 
-fn extract_java_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_java::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set Java language")?;
+fn extract_swift_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_swift::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set Swift language")? {
+        Some(t) => t,
+        None => return Ok(Vec::new()),
+    };
+
+    let query = cached_query(
+        &language,
+        r#"
+        (call_expression . (simple_identifier) @call.name . (call_suffix) @call.suffix)
+        (call_expression
+            . (navigation_expression
+                suffix: (navigation_suffix suffix: (simple_identifier) @call.name))
+            . (call_suffix) @call.suffix)
+        ; `a + f(x)` parses as a call of `a + f`
+        (call_expression
+            . (_ rhs: (simple_identifier) @call.name)
+            . (call_suffix) @call.suffix)
+        "#,
+    )
+    .context("Failed to create call query")?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    let mut calls = Vec::new();
+    let mut seen_lines = HashSet::new();
+
+    while let Some(match_) = matches.next() {
+        let mut name_node = None;
+        let mut subscript = false;
+        for capture in match_.captures {
+            match query
+                .capture_names()
+                .get(capture.index as usize)
+                .map(|s| s.as_ref())
+            {
+                Some("call.name") => name_node = Some(capture.node),
+                // `environment["HOME"]` is a subscript, not a call
+                Some("call.suffix") => {
+                    subscript = capture
+                        .node
+                        .utf8_text(content.as_bytes())
+                        .is_ok_and(|text| text.starts_with('['))
+                }
+                _ => {}
+            }
+        }
+        let Some(node) = name_node.filter(|_| !subscript) else {
+            continue;
+        };
+
+        let name = node
+            .utf8_text(content.as_bytes())
+            .unwrap_or_default()
+            .to_string();
+        let line = node.start_position().row + 1;
+
+        if seen_lines.contains(&(name.clone(), line)) {
+            continue;
+        }
+        seen_lines.insert((name.clone(), line));
+
+        let context = content.lines().nth(line - 1).unwrap_or("").to_string();
+        calls.push((name, line, context));
+    }
 
-    let tree = match parser.parse(content, None) {
+    Ok(calls)
+}
+
+fn extract_c_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_c::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set C language")? {
         Some(t) => t,
         None => return Ok(Vec::new()),
     };
 
-    let query = Query::new(
+    let query = cached_query(
         &language,
         r#"
-        (method_invocation
-            name: (identifier) @call.name) @call.expr
+        (call_expression
+            function: (identifier) @call.name) @call.expr
         "#,
     )
     .context("Failed to create call query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
     let mut calls = Vec::new();
     let mut seen_lines = HashSet::new();
 
@@ -698,29 +1296,29 @@ fn extract_java_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
     Ok(calls)
 }
 
-fn extract_c_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_c::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set C language")?;
-
-    let tree = match parser.parse(content, None) {
+fn extract_cpp_calls(content: &str) -> Result<Vec<(String, usize, String)>> {
+    let language = tree_sitter_cpp::LANGUAGE.into();
+    let tree = match pooled_parse(&language, content).context("Failed to set C++ language")? {
         Some(t) => t,
         None => return Ok(Vec::new()),
     };
 
-    let query = Query::new(
+    let query = cached_query(
         &language,
         r#"
         (call_expression
-            function: (identifier) @call.name) @call.expr
+            function: [
+                (identifier) @call.name
+                (field_expression field: (field_identifier) @call.name)
+                (qualified_identifier name: (identifier) @call.name)
+                (template_function name: (identifier) @call.name)
+            ]) @call.expr
         "#,
     )
     .context("Failed to create call query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
     let mut calls = Vec::new();
     let mut seen_lines = HashSet::new();
 
@@ -799,10 +1397,13 @@ pub fn find_test_deps(index: &CodeIndex, test_file: &Path) -> Result<Vec<TestDep
     let language = Language::from_extension(ext);
 
     if !is_test_file(test_file, language) {
-        anyhow::bail!(
-            "File does not appear to be a test file: {}",
-            test_file.display()
-        );
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "File does not appear to be a test file: {}",
+                test_file.display_slash()
+            )
+        ));
     }
 
     let content = fs::read_to_string(test_file).context("Failed to read test file")?;
@@ -820,7 +1421,7 @@ pub fn find_test_deps(index: &CodeIndex, test_file: &Path) -> Result<Vec<TestDep
         let target_symbols = index.query_symbol(&call_name);
 
         if let Some(target) = target_symbols.first() {
-            if target.file_path == test_file {
+            if *target.file_path == *test_file {
                 continue;
             }
 
@@ -848,9 +1449,9 @@ pub fn find_test_deps(index: &CodeIndex, test_file: &Path) -> Result<Vec<TestDep
             seen.insert(call_name.clone());
 
             deps.push(TestDep {
-                name: target.name.clone(),
+                name: target.name.to_string(),
                 symbol_type: target.symbol_type,
-                file_path: target.file_path.display().to_string(),
+                file_path: target.file_path.display_slash().to_string(),
                 line: target.line_start,
                 called_from_line: call_line,
             });
@@ -862,27 +1463,44 @@ pub fn find_test_deps(index: &CodeIndex, test_file: &Path) -> Result<Vec<TestDep
     Ok(deps)
 }
 
+fn is_rust_test_attribute(line: &str) -> bool {
+    line.contains("#[test]") || line.contains("#[tokio::test")
+}
+
+fn is_java_test_annotation(line: &str) -> bool {
+    line.contains("@Test") || line.contains("@ParameterizedTest")
+}
+
+/// Whether a line `is_marker` accepts is on `symbol`'s first line or above it, past blank
+/// lines, comments and other lines starting with `prefix` (attributes, annotations)
+fn marked_by(symbol: &Symbol, content: &str, prefix: &str, is_marker: fn(&str) -> bool) -> bool {
+    let Some(line_idx) = symbol.line_start.checked_sub(1) else {
+        return false;
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    for i in (0..=line_idx.min(lines.len().saturating_sub(1))).rev() {
+        let line = lines.get(i).unwrap_or(&"");
+        if is_marker(line) {
+            return true;
+        }
+        let trimmed = line.trim();
+        if i < line_idx
+            && !trimmed.is_empty()
+            && !trimmed.starts_with(prefix)
+            && !trimmed.starts_with("//")
+        {
+            break;
+        }
+    }
+    false
+}
+
 pub fn is_test_symbol(symbol: &Symbol, content: &str, language: Language) -> bool {
     let name = &symbol.name;
 
     match language {
         Language::Rust => {
-            if let Some(line_idx) = symbol.line_start.checked_sub(1) {
-                let lines: Vec<&str> = content.lines().collect();
-                for i in (0..=line_idx.min(lines.len().saturating_sub(1))).rev() {
-                    let line = lines.get(i).unwrap_or(&"");
-                    if line.contains("#[test]") || line.contains("#[tokio::test]") {
-                        return true;
-                    }
-                    if !line.trim().is_empty()
-                        && !line.trim().starts_with("#[")
-                        && !line.trim().starts_with("//")
-                    {
-                        break;
-                    }
-                }
-            }
-            name.starts_with("test_")
+            marked_by(symbol, content, "#[", is_rust_test_attribute) || name.starts_with("test_")
         }
         Language::Python => name.starts_with("test_") || name.starts_with("Test"),
         Language::JavaScript | Language::TypeScript => {
@@ -893,32 +1511,28 @@ pub fn is_test_symbol(symbol: &Symbol, content: &str, language: Language) -> boo
         }
         Language::Go => name.starts_with("Test") || name.starts_with("Benchmark"),
         Language::Java => {
-            if let Some(line_idx) = symbol.line_start.checked_sub(1) {
-                let lines: Vec<&str> = content.lines().collect();
-                for i in (0..=line_idx.min(lines.len().saturating_sub(1))).rev() {
-                    let line = lines.get(i).unwrap_or(&"");
-                    if line.contains("@Test") {
-                        return true;
-                    }
-                    if !line.trim().is_empty()
-                        && !line.trim().starts_with("@")
-                        && !line.trim().starts_with("//")
-                    {
-                        break;
-                    }
-                }
-            }
-            name.starts_with("test")
+            marked_by(symbol, content, "@", is_java_test_annotation) || name.starts_with("test")
         }
         _ => false,
     }
 }
 
 pub fn find_tests(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result<Vec<TestInfo>> {
+    find_tests_in(index, symbol_name, fuzzy, None)
+}
+
+/// Like `find_tests`, but only parses `candidates`
+pub fn find_tests_in(
+    index: &CodeIndex,
+    symbol_name: &str,
+    fuzzy: bool,
+    candidates: Option<&HashSet<PathBuf>>,
+) -> Result<Vec<TestInfo>> {
     let mut tests = Vec::new();
     let mut seen = HashSet::new();
+    let definitions = index.query_symbol(symbol_name).len();
 
-    for file_info in index.files() {
+    for file_info in candidate_files(index, candidates) {
         let is_test_file = is_test_file(&file_info.path, file_info.language);
 
         let content = match fs::read_to_string(&file_info.path) {
@@ -952,14 +1566,14 @@ pub fn find_tests(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result<V
                 continue;
             }
 
-            let key = format!("{}:{}", file_info.path.display(), line);
+            let key = format!("{}:{}", file_info.path.display_slash(), line);
             if seen.contains(&key) {
                 continue;
             }
             seen.insert(key);
 
             let (test_name, test_line) = match caller_symbol {
-                Some(sym) => (sym.name.clone(), sym.line_start),
+                Some(sym) => (sym.name.to_string(), sym.line_start),
                 None => ("<test-file-level>".to_string(), line),
             };
 
@@ -968,14 +1582,47 @@ pub fn find_tests(index: &CodeIndex, symbol_name: &str, fuzzy: bool) -> Result<V
                 test_type: caller_symbol
                     .map(|s| s.symbol_type)
                     .unwrap_or(SymbolType::Function),
-                file_path: file_info.path.display().to_string(),
+                file_path: file_info.path.display_slash().to_string(),
                 line: test_line,
                 call_line: line,
                 context: context.trim().to_string(),
+                confidence: Confidence::of_ast_match(&call_name, symbol_name, definitions),
+                by_name: false,
             });
         }
     }
 
+    // Tests named after the symbol that never call it by that name: through a trait, a
+    // fixture or a test helper
+    let linked: HashSet<(String, usize)> = tests
+        .iter()
+        .map(|t| (t.file_path.clone(), t.line))
+        .collect();
+    for (test, names) in named_tests(index, candidates) {
+        let Some(target) = first_defined(index, &names) else {
+            continue;
+        };
+        let matches = if fuzzy {
+            target.to_lowercase().contains(&symbol_name.to_lowercase())
+        } else {
+            target == symbol_name
+        };
+        let file_path = test.file_path.display_slash().to_string();
+        if !matches || linked.contains(&(file_path.clone(), test.line_start)) {
+            continue;
+        }
+        tests.push(TestInfo {
+            test_name: test.name.to_string(),
+            test_type: test.symbol_type,
+            file_path,
+            line: test.line_start,
+            call_line: test.line_start,
+            context: String::new(),
+            confidence: Confidence::NameMatch,
+            by_name: true,
+        });
+    }
+
     Ok(tests)
 }
 
@@ -1015,6 +1662,11 @@ pub fn find_untested(index: &CodeIndex) -> Result<Vec<UntestedInfo>> {
             }
         }
     }
+    for (_, names) in named_tests(index, None) {
+        if let Some(target) = first_defined(index, &names) {
+            tested_symbols.insert(target.clone());
+        }
+    }
 
     let mut untested = Vec::new();
 
@@ -1045,16 +1697,16 @@ pub fn find_untested(index: &CodeIndex) -> Result<Vec<UntestedInfo>> {
 
             if matches!(
                 symbol.symbol_type,
-                SymbolType::Heading | SymbolType::CodeBlock
+                SymbolType::Heading | SymbolType::CodeBlock | SymbolType::Field
             ) {
                 continue;
             }
 
-            if !tested_symbols.contains(&symbol.name) {
+            if !tested_symbols.contains(symbol.name.as_str()) {
                 untested.push(UntestedInfo {
-                    name: symbol.name.clone(),
+                    name: symbol.name.to_string(),
                     symbol_type: symbol.symbol_type,
-                    file_path: file_info.path.display().to_string(),
+                    file_path: file_info.path.display_slash().to_string(),
                     line: symbol.line_start,
                     signature: symbol.signature.clone(),
                 });
@@ -1067,10 +1719,319 @@ pub fn find_untested(index: &CodeIndex) -> Result<Vec<UntestedInfo>> {
     Ok(untested)
 }
 
-pub fn find_entrypoints(index: &CodeIndex) -> Result<Vec<EntrypointInfo>> {
-    let mut all_called_symbols: HashSet<String> = HashSet::new();
+/// Every test a test runner would collect, going by the same file names, attributes and
+/// name prefixes it does, in file and line order
+pub fn list_tests(index: &CodeIndex) -> Vec<TestCase> {
+    list_tests_in(index, None)
+}
+
+/// Like `list_tests`, but only reads `candidates`
+pub fn list_tests_in(index: &CodeIndex, candidates: Option<&HashSet<PathBuf>>) -> Vec<TestCase> {
+    let mut tests = Vec::new();
+
+    for file_info in candidate_files(index, candidates) {
+        let in_test_file = is_test_file(&file_info.path, file_info.language);
+        let language = file_info.language;
+        if !may_hold_tests(language, in_test_file) {
+            continue;
+        }
+        let content = match fs::read_to_string(&file_info.path) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        let symbols = index.get_file_symbols(&file_info.path);
+        let modules = match language {
+            Language::Rust => rust_modules(&content),
+            _ => Vec::new(),
+        };
+
+        for symbol in &symbols {
+            if !is_collected_test(symbol, &content, language, in_test_file) {
+                continue;
+            }
+            let name = symbol.name.as_str();
+
+            let mut enclosing: Vec<(usize, usize, String)> = modules
+                .iter()
+                .filter(|(_, start, end)| *start < symbol.line_start && *end >= symbol.line_end)
+                .map(|(module, start, end)| (*start, *end, module.clone()))
+                .collect();
+            enclosing.extend(
+                symbols
+                    .iter()
+                    .filter(|s| s.line_start <= symbol.line_start && s.line_end >= symbol.line_end)
+                    .filter(|s| !std::ptr::eq(**s, *symbol))
+                    .filter_map(|s| {
+                        let suite = match s.name.strip_prefix("describe:") {
+                            Some(description) => description.to_string(),
+                            None if s.symbol_type == SymbolType::Class => s.name.to_string(),
+                            None => return None,
+                        };
+                        Some((s.line_start, s.line_end, suite))
+                    }),
+            );
+            enclosing.sort_by_key(|(start, end, _)| (*start, std::cmp::Reverse(*end)));
+
+            tests.push(TestCase {
+                name: name.strip_prefix("test:").unwrap_or(name).to_string(),
+                file_path: file_info.path.display_slash().to_string(),
+                line: symbol.line_start,
+                suites: enclosing.into_iter().map(|(_, _, suite)| suite).collect(),
+            });
+        }
+    }
+
+    tests.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+    tests
+}
+
+/// Tests named after a symbol (`test_parse_config`, `UserServiceTest`) that the index no
+/// longer defines under any reading of the name, in file and line order. The test must still
+/// call the symbol (or name the type), so descriptive names (`test_round_trip`) are not
+/// taken for orphans.
+pub fn find_orphan_tests(index: &CodeIndex) -> Vec<OrphanTest> {
+    let mut sources: HashMap<&Path, String> = HashMap::new();
+    let mut orphans = Vec::new();
+
+    for (test, names) in named_tests(index, None) {
+        if first_defined(index, &names).is_some() {
+            continue;
+        }
+        let content = sources
+            .entry(&test.file_path)
+            .or_insert_with(|| fs::read_to_string(&test.file_path).unwrap_or_default());
+        let body = content
+            .lines()
+            .skip(test.line_start.saturating_sub(1))
+            .take(test.line_end + 1 - test.line_start.max(1))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let ext = test
+            .file_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let calls: HashSet<String> =
+            extract_calls_from_source(&body, Language::from_extension(ext))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, _, _)| name)
+                .collect();
+        let Some(target) = names.iter().find(|name| {
+            calls.contains(name.as_str())
+                || (name.starts_with(|c: char| c.is_uppercase())
+                    && Confidence::of_text_match(&body, name) == Confidence::NameMatch)
+        }) else {
+            continue;
+        };
+        orphans.push(OrphanTest {
+            name: test.name.to_string(),
+            test_type: test.symbol_type,
+            file_path: test.file_path.display_slash().to_string(),
+            line: test.line_start,
+            target: target.clone(),
+        });
+    }
+
+    orphans.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+    orphans
+}
+
+/// Tests and test suites with the names `tested_names` reads off them. Suites are test
+/// classes (`UserServiceTest`, Python's `TestUserService`) and `describe` blocks titled with
+/// an identifier.
+fn named_tests<'a>(
+    index: &'a CodeIndex,
+    candidates: Option<&HashSet<PathBuf>>,
+) -> Vec<(&'a Symbol, Vec<String>)> {
+    let mut named = Vec::new();
+
+    for file_info in candidate_files(index, candidates) {
+        let in_test_file = is_test_file(&file_info.path, file_info.language);
+        if !may_hold_tests(file_info.language, in_test_file) {
+            continue;
+        }
+        let content = match fs::read_to_string(&file_info.path) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+
+        for symbol in index.get_file_symbols(&file_info.path) {
+            let name = symbol.name.as_str();
+            let names = if let Some(title) = name.strip_prefix("describe:") {
+                let is_identifier = !title.is_empty()
+                    && !title.starts_with(|c: char| c.is_ascii_digit())
+                    && title
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+                if !is_identifier {
+                    continue;
+                }
+                vec![title.to_string()]
+            } else if (symbol.symbol_type == SymbolType::Class && in_test_file)
+                || (!name.starts_with("test:")
+                    && is_collected_test(symbol, &content, file_info.language, in_test_file))
+            {
+                tested_names(name)
+            } else {
+                continue;
+            };
+            if !names.is_empty() {
+                named.push((symbol, names));
+            }
+        }
+    }
+
+    named
+}
+
+/// Names a test says it tests, longest first: `test_parse_config` and `parse_config_test` may
+/// test `parse_config` or `parse`, `UserServiceTest` `UserService` or `User`, and Go's
+/// `TestParseConfig` `ParseConfig`, `parseConfig`, `Parse` or `parse`. Empty for a name that
+/// follows none of these conventions.
+fn tested_names(name: &str) -> Vec<String> {
+    let stem = name
+        .strip_prefix("test_")
+        .or_else(|| name.strip_suffix("_test"))
+        .or_else(|| name.strip_suffix("_tests"))
+        .or_else(|| name.strip_suffix("Tests"))
+        .or_else(|| name.strip_suffix("Test"))
+        .or_else(|| name.strip_suffix("Spec"))
+        .or_else(|| {
+            ["Test", "test"].into_iter().find_map(|prefix| {
+                name.strip_prefix(prefix)
+                    .filter(|rest| rest.starts_with(|c: char| c.is_uppercase()))
+            })
+        });
+    let Some(stem) = stem.filter(|stem| !stem.is_empty()) else {
+        return Vec::new();
+    };
+
+    // Where each shorter reading ends: at underscores in snake_case, before a capital in
+    // camelCase (but not inside an acronym)
+    let snake = stem.contains('_');
+    let mut ends = Vec::new();
+    let mut previous: Option<char> = None;
+    for (i, c) in stem.char_indices() {
+        let boundary = if snake {
+            c == '_'
+        } else {
+            c.is_uppercase() && previous.is_some_and(|p| !p.is_uppercase())
+        };
+        if boundary {
+            ends.push(i);
+        }
+        previous = Some(c);
+    }
+    ends.push(stem.len());
+
+    let mut names: Vec<String> = Vec::new();
+    for end in ends.into_iter().rev() {
+        let prefix = stem[..end].trim_end_matches('_');
+        if prefix.is_empty() {
+            continue;
+        }
+        let mut chars = prefix.chars();
+        let lowered: String = chars
+            .next()
+            .into_iter()
+            .flat_map(char::to_lowercase)
+            .chain(chars)
+            .collect();
+        for candidate in [prefix.to_string(), lowered] {
+            if !names.contains(&candidate) {
+                names.push(candidate);
+            }
+        }
+    }
+    names
+}
+
+/// The first of `names` the index defines as code
+fn first_defined<'a>(index: &CodeIndex, names: &'a [String]) -> Option<&'a String> {
+    names.iter().find(|name| {
+        index
+            .query_symbol(name)
+            .iter()
+            .any(|s| !matches!(s.symbol_type, SymbolType::Heading | SymbolType::CodeBlock))
+    })
+}
+
+/// Only test files and these languages' sources hold tests
+fn may_hold_tests(language: Language, in_test_file: bool) -> bool {
+    in_test_file
+        || matches!(
+            language,
+            Language::Rust | Language::Java | Language::JavaScript | Language::TypeScript
+        )
+}
+
+/// Whether a test runner would collect `symbol` as a test
+fn is_collected_test(
+    symbol: &Symbol,
+    content: &str,
+    language: Language,
+    in_test_file: bool,
+) -> bool {
+    if !matches!(
+        symbol.symbol_type,
+        SymbolType::Function | SymbolType::Method
+    ) {
+        return false;
+    }
+    let name = symbol.name.as_str();
+    match language {
+        Language::Rust => marked_by(symbol, content, "#[", is_rust_test_attribute),
+        Language::Python => name.starts_with("test"),
+        Language::JavaScript | Language::TypeScript => name.starts_with("test:"),
+        Language::Go => {
+            name.starts_with("Test") || name.starts_with("Benchmark") || name.starts_with("Fuzz")
+        }
+        Language::Java => {
+            marked_by(symbol, content, "@", is_java_test_annotation)
+                || (in_test_file && name.starts_with("test"))
+        }
+        _ => false,
+    }
+}
+
+/// Inline `mod name { ... }` blocks of a Rust file as (name, first line, last line)
+fn rust_modules(content: &str) -> Vec<(String, usize, usize)> {
+    let language = tree_sitter_rust::LANGUAGE.into();
+    let Ok(Some(tree)) = pooled_parse(&language, content) else {
+        return Vec::new();
+    };
+    let mut modules = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.kind() == "mod_item" && node.child_by_field_name("body").is_some() {
+            if let Some(name) = node.child_by_field_name("name") {
+                modules.push((
+                    content[name.byte_range()].to_string(),
+                    node.start_position().row + 1,
+                    node.end_position().row + 1,
+                ));
+            }
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    modules
+}
+
+/// Number of call sites per called name, across every indexed file
+pub fn call_counts(index: &CodeIndex) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
 
     for file_info in index.files() {
+        if let Some(references) = index.file_references(&file_info.path) {
+            for reference in references {
+                if reference.kind == ReferenceKind::Call {
+                    *counts.entry(reference.name.clone()).or_default() += 1;
+                }
+            }
+            continue;
+        }
         let content = match fs::read_to_string(&file_info.path) {
             Ok(c) => c,
             Err(_) => continue,
@@ -1082,10 +2043,22 @@ pub fn find_entrypoints(index: &CodeIndex) -> Result<Vec<EntrypointInfo>> {
         };
 
         for (call_name, _, _) in calls {
-            all_called_symbols.insert(call_name);
+            *counts.entry(call_name).or_default() += 1;
         }
     }
 
+    counts
+}
+
+pub fn find_entrypoints(index: &CodeIndex) -> Result<Vec<EntrypointInfo>> {
+    Ok(entrypoints_given_calls(index, &call_counts(index)))
+}
+
+/// `find_entrypoints` for callers that already have `call_counts`
+pub fn entrypoints_given_calls(
+    index: &CodeIndex,
+    all_called_symbols: &HashMap<String, usize>,
+) -> Vec<EntrypointInfo> {
     let mut entrypoints = Vec::new();
 
     for file_info in index.files() {
@@ -1107,7 +2080,7 @@ pub fn find_entrypoints(index: &CodeIndex) -> Result<Vec<EntrypointInfo>> {
 
             if matches!(
                 symbol.symbol_type,
-                SymbolType::Heading | SymbolType::CodeBlock
+                SymbolType::Heading | SymbolType::CodeBlock | SymbolType::Field
             ) {
                 continue;
             }
@@ -1116,7 +2089,7 @@ pub fn find_entrypoints(index: &CodeIndex) -> Result<Vec<EntrypointInfo>> {
                 continue;
             }
 
-            if all_called_symbols.contains(&symbol.name) {
+            if all_called_symbols.contains_key(symbol.name.as_str()) {
                 continue;
             }
 
@@ -1129,9 +2102,9 @@ pub fn find_entrypoints(index: &CodeIndex) -> Result<Vec<EntrypointInfo>> {
             let category = categorize_entrypoint(&symbol.name, symbol.symbol_type);
 
             entrypoints.push(EntrypointInfo {
-                name: symbol.name.clone(),
+                name: symbol.name.to_string(),
                 symbol_type: symbol.symbol_type,
-                file_path: file_info.path.display().to_string(),
+                file_path: file_info.path.display_slash().to_string(),
                 line: symbol.line_start,
                 signature: symbol.signature.clone(),
                 category,
@@ -1153,7 +2126,7 @@ pub fn find_entrypoints(index: &CodeIndex) -> Result<Vec<EntrypointInfo>> {
         _ => a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)),
     });
 
-    Ok(entrypoints)
+    entrypoints
 }
 
 fn is_symbol_exported(symbol: &Symbol, content: &str, language: Language) -> bool {
@@ -1191,8 +2164,28 @@ fn is_symbol_exported(symbol: &Symbol, content: &str, language: Language) -> boo
             .next()
             .map(|c| c.is_uppercase())
             .unwrap_or(false),
-        Language::Java => definition_line.contains("public "),
+        Language::Java | Language::CSharp => definition_line.contains("public "),
         Language::C => !name.starts_with('_'),
+        Language::Cpp => {
+            !definition_line.trim_start().starts_with("static ") && !name.starts_with('_')
+        }
+        Language::Php => {
+            !definition_line.contains("private ") && !definition_line.contains("protected ")
+        }
+        Language::Kotlin => !["private ", "protected ", "internal "]
+            .iter()
+            .any(|modifier| definition_line.contains(modifier)),
+        Language::Swift => !["private ", "fileprivate "]
+            .iter()
+            .any(|modifier| definition_line.contains(modifier)),
+        Language::Scala => !["private ", "private[", "protected ", "protected["]
+            .iter()
+            .any(|modifier| definition_line.contains(modifier)),
+        Language::Lua => !definition_line.trim_start().starts_with("local "),
+        Language::Zig => {
+            let line = definition_line.trim_start();
+            line.starts_with("pub ") || line.starts_with("export ")
+        }
         _ => true,
     }
 }
@@ -1288,15 +2281,15 @@ pub fn trace_path(index: &CodeIndex, from: &str, to: &str, fuzzy: bool) -> Resul
     // Start BFS from ALL matching source symbols, not just the first one
     for source in &source_symbols {
         let start_step = TraceStep {
-            symbol_name: source.name.clone(),
+            symbol_name: source.name.to_string(),
             symbol_type: source.symbol_type,
-            file_path: source.file_path.display().to_string(),
+            file_path: source.file_path.display_slash().to_string(),
             line: source.line_start,
         };
 
         let visit_key = format!(
             "{}:{}",
-            source.file_path.display(),
+            source.file_path.display_slash(),
             source.name.to_lowercase()
         );
         if !visited.contains(&visit_key) {
@@ -1313,7 +2306,7 @@ pub fn trace_path(index: &CodeIndex, from: &str, to: &str, fuzzy: bool) -> Resul
         // Find the specific symbol instance from the current step
         let current_symbols = index.query_symbol(&current.symbol_name);
         let current_symbol = current_symbols.iter().find(|s| {
-            s.file_path.display().to_string() == current.file_path && s.line_start == current.line
+            *s.file_path == *Path::new(&current.file_path) && s.line_start == current.line
         });
 
         if current_symbol.is_none() {
@@ -1396,14 +2389,16 @@ fn find_callees_for_symbol(index: &CodeIndex, symbol: &Symbol) -> Result<Vec<Cal
         seen.insert(call_name.clone());
 
         let target_symbols = index.query_symbol(&call_name);
+        let confidence = Confidence::of_ast_match(&call_name, &call_name, target_symbols.len());
 
         if let Some(target) = target_symbols.first() {
             callees.push(CallInfo {
                 caller_name: call_name,
                 caller_type: target.symbol_type,
-                file_path: target.file_path.display().to_string(),
+                file_path: target.file_path.display_slash().to_string(),
                 line: target.line_start,
                 context: target.signature.clone().unwrap_or_default(),
+                confidence,
             });
         } else {
             callees.push(CallInfo {
@@ -1412,6 +2407,7 @@ fn find_callees_for_symbol(index: &CodeIndex, symbol: &Symbol) -> Result<Vec<Cal
                 file_path: "<external>".to_string(),
                 line: symbol.line_start + relative_line,
                 context: context.trim().to_string(),
+                confidence,
             });
         }
     }
@@ -1422,6 +2418,7 @@ fn find_callees_for_symbol(index: &CodeIndex, symbol: &Symbol) -> Result<Vec<Cal
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::index_of;
 
     #[test]
     fn test_extract_rust_calls() -> Result<()> {
@@ -1457,4 +2454,185 @@ def main():
         assert!(names.contains(&"method"));
         Ok(())
     }
+
+    #[test]
+    fn test_list_tests_with_suites() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let files = [
+            (
+                "lib.rs",
+                "pub fn add() {}\n\n#[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn adds() {}\n\n    fn helper() {}\n}\n",
+                Language::Rust,
+            ),
+            (
+                "cart.test.ts",
+                "describe('Cart', () => {\n  describe('add', () => {\n    it('counts items', () => {});\n  });\n  test('starts empty', () => {});\n});\n",
+                Language::TypeScript,
+            ),
+            (
+                "test_pay.py",
+                "def make():\n    pass\n\nclass TestPay:\n    def test_refund(self):\n        pass\n\ndef test_charge():\n    pass\n",
+                Language::Python,
+            ),
+        ];
+        let index = index_of(dir.path(), &files);
+
+        let tests = list_tests(&index);
+        let found: Vec<(&str, String)> = tests
+            .iter()
+            .map(|t| (t.name.as_str(), t.suites.join(" > ")))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("counts items", "Cart > add".to_string()),
+                ("starts empty", "Cart".to_string()),
+                ("adds", "tests".to_string()),
+                ("test_refund", "TestPay".to_string()),
+                ("test_charge", String::new()),
+            ]
+        );
+        assert_eq!(tests[2].line, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tests_linked_by_name_and_orphans() -> Result<()> {
+        assert_eq!(tested_names("test_parse_config"), ["parse_config", "parse"]);
+        assert_eq!(
+            tested_names("UserServiceTest"),
+            ["UserService", "userService", "User", "user"]
+        );
+        assert_eq!(tested_names("TestHTTPServer"), ["HTTPServer", "hTTPServer"]);
+        assert!(tested_names("adds_two_numbers").is_empty());
+
+        let dir = tempfile::tempdir()?;
+        let files = [
+            (
+                "config.py",
+                "def parse_config(text):\n    return text\n",
+                Language::Python,
+            ),
+            (
+                "test_config.py",
+                "def check(text):\n    return parse_config(text)\n\ndef test_parse_config_twice():\n    check('a')\n\ndef test_load_settings():\n    load_settings()\n\ndef test_round_trip():\n    check('b')\n\nclass TestUserService:\n    def setup(self):\n        self.service = UserService()\n",
+                Language::Python,
+            ),
+        ];
+        let index = index_of(dir.path(), &files);
+
+        let tests = find_tests(&index, "parse_config", false)?;
+        let linked: Vec<(&str, bool)> = tests
+            .iter()
+            .map(|t| (t.test_name.as_str(), t.by_name))
+            .collect();
+        assert_eq!(
+            linked,
+            [("check", false), ("test_parse_config_twice", true)]
+        );
+
+        let orphans = find_orphan_tests(&index);
+        let found: Vec<(&str, &str)> = orphans
+            .iter()
+            .map(|o| (o.name.as_str(), o.target.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("test_load_settings", "load_settings"),
+                ("TestUserService", "UserService")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_callers_from_recorded_references() -> Result<()> {
+        use crate::indexer::{index_file, record_references};
+
+        let dir = tempfile::tempdir()?;
+        let files = [
+            (
+                "auth.py",
+                "def login(user):\n    return check(user)\n\ndef check(user):\n    pass\n",
+            ),
+            (
+                "app.py",
+                "from auth import login\n\ndef main():\n    login('me')\n    print('done')\n",
+            ),
+        ];
+        let mut parsed = CodeIndex::new();
+        let mut recorded = CodeIndex::new();
+        for (name, source) in files {
+            let path = dir.path().join(name);
+            fs::write(&path, source)?;
+            let mut file = index_file(&path, source, Language::Python, None)?;
+            parsed.add_file(file.clone());
+            record_references(&mut file, source);
+            recorded.add_file(file);
+        }
+        // Written to and read back from the cache
+        let recorded: CodeIndex = bincode::deserialize(&bincode::serialize(&recorded)?)?;
+        assert!(recorded.has_references() && !parsed.has_references());
+
+        let summary = |callers: Vec<CallInfo>| -> Vec<(String, usize, String)> {
+            callers
+                .into_iter()
+                .map(|c| (c.caller_name, c.line, c.context))
+                .collect()
+        };
+        let expected = vec![("main".to_string(), 4, "login('me')".to_string())];
+        assert_eq!(summary(find_callers(&parsed, "login", false)?), expected);
+        assert_eq!(summary(find_callers(&recorded, "login", false)?), expected);
+
+        let kinds: Vec<(&str, usize)> = recorded
+            .references_to("login")
+            .into_iter()
+            .map(|(_, r)| (r.kind.as_str(), r.line))
+            .collect();
+        assert_eq!(kinds.len(), 2);
+        assert!(kinds.contains(&("import", 1)) && kinds.contains(&("call", 4)));
+        assert_eq!(call_counts(&recorded), call_counts(&parsed));
+        Ok(())
+    }
+    #[test]
+    fn test_scans_only_candidate_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let names = ["auth.py", "app.py", "test_auth.py"];
+        let index = index_of(
+            dir.path(),
+            &[
+                (names[0], "def login(user):\n    pass\n", Language::Python),
+                (names[1], "def main():\n    login('me')\n", Language::Python),
+                (
+                    names[2],
+                    "def test_login():\n    login('x')\n",
+                    Language::Python,
+                ),
+            ],
+        );
+        let paths = names.map(|name| dir.path().join(name));
+        let only = |path: &PathBuf| HashSet::from([path.clone()]);
+
+        let callers = |candidates: Option<&HashSet<PathBuf>>| -> Result<Vec<String>> {
+            Ok(find_callers_in(&index, "login", false, candidates)?
+                .into_iter()
+                .map(|c| c.caller_name)
+                .collect())
+        };
+        assert_eq!(callers(None)?.len(), 2);
+        assert_eq!(callers(Some(&only(&paths[1])))?, ["main"]);
+        assert!(callers(Some(&only(&paths[0])))?.is_empty());
+
+        let tests = |candidates: Option<&HashSet<PathBuf>>| -> Result<Vec<String>> {
+            Ok(find_tests_in(&index, "login", false, candidates)?
+                .into_iter()
+                .map(|t| t.test_name)
+                .collect())
+        };
+        assert_eq!(tests(None)?, ["test_login"]);
+        assert_eq!(tests(Some(&only(&paths[2])))?, ["test_login"]);
+        assert!(tests(Some(&only(&paths[1])))?.is_empty());
+        Ok(())
+    }
 }
@@ -0,0 +1,1048 @@
+//! `cm cli-map`: the command-line interface a project defines. Subcommands and their flags
+//! from clap (derive and builder), argparse, click/typer and cobra, each mapped to the
+//! function that handles it.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tree_sitter::Node;
+
+use crate::callgraph;
+use crate::index::CodeIndex;
+use crate::models::Language;
+use crate::parser::pooled_parse;
+use crate::routes::{args, first_string, group_text, split_args};
+use crate::scan::{self, regex};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliFlag {
+    /// `--long`, `-s`, or `<NAME>` for positionals
+    pub names: Vec<String>,
+    pub help: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CliCommand {
+    /// Program name followed by the subcommands leading here, e.g. `cm cache clear`
+    pub path: String,
+    pub about: Option<String>,
+    pub flags: Vec<CliFlag>,
+    pub handler: Option<String>,
+    pub framework: &'static str,
+    pub file_path: PathBuf,
+    pub line: usize,
+}
+
+pub fn find_cli_commands(index: &CodeIndex) -> Result<Vec<CliCommand>> {
+    let mut clap = ClapDefinitions::default();
+    let mut rust_sources: Vec<(PathBuf, String)> = Vec::new();
+    let mut commands = Vec::new();
+
+    for file in index.files() {
+        if callgraph::is_test_file(&file.path, file.language) {
+            continue;
+        }
+        // Commands built inside a Rust test module are fixtures, not the tool's interface
+        let content = scan::non_test_source(&file.path, file.language);
+        if content.is_empty() {
+            continue;
+        }
+        let lines: Vec<&str> = content.lines().collect();
+        match file.language {
+            Language::Rust => {
+                if regex(r"derive\([^)]*\b(?:Parser|Subcommand|Args)\b").is_match(&content) {
+                    clap.collect(&content, &file.path)?;
+                } else if content.contains("clap") {
+                    commands.extend(clap_builder_commands(&content, &lines, &file.path));
+                }
+                rust_sources.push((file.path.clone(), content));
+            }
+            Language::Python => {
+                if content.contains("argparse") {
+                    commands.extend(argparse_commands(&lines, &file.path));
+                }
+                if content.contains("click") || content.contains("typer") {
+                    commands.extend(click_commands(&content, &lines, &file.path));
+                }
+            }
+            Language::Go if content.contains("cobra.Command") => {
+                commands.extend(cobra_commands(&lines, &file.path));
+            }
+            _ => {}
+        }
+    }
+
+    commands.extend(clap.assemble(&rust_sources));
+    commands.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    Ok(commands)
+}
+
+/// `StashCompare` -> `stash-compare`, `dry_run` -> `dry-run`
+fn kebab(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('-');
+            }
+            out.extend(c.to_lowercase());
+        } else if c == '_' {
+            out.push('-');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} {}", prefix, name)
+    }
+}
+
+/// `help="..."` (or another keyword) in an argument list
+fn keyword_string(args: &str, keyword: &str) -> Option<String> {
+    split_args(args)
+        .into_iter()
+        .find(|a| {
+            a.strip_prefix(keyword)
+                .is_some_and(|rest| rest.trim_start().starts_with('=') || rest.starts_with(':'))
+        })
+        .and_then(first_string)
+}
+
+/// The first function called after the `=>` that follows `at`, within one match arm
+fn arm_handler(content: &str, at: usize) -> Option<String> {
+    let rest = &content[at..content.len().min(at + 2000)];
+    let arrow = rest.find("=>")?;
+    if rest[..arrow].contains(';') {
+        return None;
+    }
+    let call_re = regex(r"([A-Za-z_][\w:]*)\s*\(");
+    let body = &rest[arrow + 2..rest.len().min(arrow + 400)];
+    // `Commands::Cache(args) => match args.action { ... }` delegates to nested arms
+    if body.trim_start().starts_with("match ") {
+        return None;
+    }
+    let handler = call_re
+        .captures_iter(body)
+        .filter_map(|c| c.get(1).map(|m| m.as_str()))
+        .find(|name| {
+            !matches!(
+                *name,
+                "Some" | "Ok" | "Err" | "if" | "match" | "while" | "for" | "return"
+            )
+        })
+        .map(str::to_string);
+    handler
+}
+
+// ---- clap derive ------------------------------------------------------------------------
+
+#[derive(Debug, Default, Clone)]
+struct ClapField {
+    flag: Option<CliFlag>,
+    /// Type of a `#[command(subcommand)]` field
+    subcommand: Option<String>,
+    /// Type of a `#[command(flatten)]` field
+    flatten: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct ClapItem {
+    name: String,
+    /// `#[command(name = "...")]`
+    rename: Option<String>,
+    about: Option<String>,
+    fields: Vec<ClapField>,
+    /// `Variant(Type)`
+    tuple_type: Option<String>,
+    file_path: PathBuf,
+    line: usize,
+}
+
+#[derive(Default)]
+struct ClapDefinitions {
+    /// `#[derive(Parser)]` structs
+    parsers: Vec<ClapItem>,
+    /// `#[derive(Args)]` structs, by name
+    args: HashMap<String, ClapItem>,
+    /// `#[derive(Subcommand)]` enums, by name, with their variants
+    subcommands: HashMap<String, Vec<ClapItem>>,
+}
+
+fn node_text<'a>(node: Node, content: &'a str) -> &'a str {
+    &content[node.byte_range()]
+}
+
+/// Innermost type name: `Option<Box<CacheCommand>>` -> `CacheCommand`
+fn type_name(ty: &str) -> String {
+    ty.split(['<', '>', ':', ' ', '&'])
+        .rfind(|s| !s.is_empty() && !matches!(*s, "Option" | "Box" | "Vec"))
+        .unwrap_or(ty)
+        .to_string()
+}
+
+/// Attribute texts and the first doc comment line preceding an item
+#[derive(Default)]
+struct Preamble {
+    attributes: Vec<String>,
+    doc: Option<String>,
+}
+
+impl Preamble {
+    fn push(&mut self, node: Node, content: &str) -> bool {
+        match node.kind() {
+            "attribute_item" => self.attributes.push(node_text(node, content).to_string()),
+            "line_comment" => {
+                let text = node_text(node, content);
+                if let Some(doc) = text.strip_prefix("///") {
+                    if self.doc.is_none() && !doc.trim().is_empty() {
+                        self.doc = Some(doc.trim().to_string());
+                    }
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn attribute_value(&self, key: &str) -> Option<String> {
+        let re = regex(&format!(r#"\b{}\s*=\s*"((?:[^"\\]|\\.)*)""#, key));
+        self.attributes.iter().find_map(|a| {
+            re.captures(a)
+                .and_then(|c| c.get(1))
+                .map(|m| m.as_str().to_string())
+        })
+    }
+
+    fn has(&self, pattern: &Regex) -> bool {
+        self.attributes.iter().any(|a| pattern.is_match(a))
+    }
+}
+
+impl ClapDefinitions {
+    fn collect(&mut self, content: &str, path: &Path) -> Result<()> {
+        let language = tree_sitter_rust::LANGUAGE.into();
+        let Some(tree) = pooled_parse(&language, content).context("Failed to set Rust language")?
+        else {
+            return Ok(());
+        };
+
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let mut preamble = Preamble::default();
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                if preamble.push(child, content) {
+                    continue;
+                }
+                let derive = preamble
+                    .attributes
+                    .iter()
+                    .find(|a| a.contains("derive("))
+                    .cloned()
+                    .unwrap_or_default();
+                match child.kind() {
+                    "struct_item" if derive.contains("Parser") || derive.contains("Args") => {
+                        let item = self.struct_item(child, content, path, &preamble);
+                        if derive.contains("Parser") {
+                            self.parsers.push(item);
+                        } else {
+                            self.args.insert(item.name.clone(), item);
+                        }
+                    }
+                    "enum_item" if derive.contains("Subcommand") => {
+                        let name = child
+                            .child_by_field_name("name")
+                            .map(|n| node_text(n, content).to_string())
+                            .unwrap_or_default();
+                        let variants = child
+                            .child_by_field_name("body")
+                            .map(|body| Self::variants(body, content, path))
+                            .unwrap_or_default();
+                        self.subcommands.insert(name, variants);
+                    }
+                    "mod_item" => stack.extend(child.child_by_field_name("body")),
+                    _ => {}
+                }
+                preamble = Preamble::default();
+            }
+        }
+        Ok(())
+    }
+
+    fn struct_item(&self, node: Node, content: &str, path: &Path, preamble: &Preamble) -> ClapItem {
+        let name = node
+            .child_by_field_name("name")
+            .map(|n| node_text(n, content).to_string())
+            .unwrap_or_default();
+        let fields = node
+            .child_by_field_name("body")
+            .map(|body| Self::fields(body, content))
+            .unwrap_or_default();
+        ClapItem {
+            name,
+            rename: preamble.attribute_value("name"),
+            about: preamble.attribute_value("about").or(preamble.doc.clone()),
+            fields,
+            tuple_type: None,
+            file_path: path.to_path_buf(),
+            line: node.start_position().row + 1,
+        }
+    }
+
+    fn variants(body: Node, content: &str, path: &Path) -> Vec<ClapItem> {
+        let mut variants = Vec::new();
+        let mut preamble = Preamble::default();
+        let mut cursor = body.walk();
+        for child in body.children(&mut cursor) {
+            if preamble.push(child, content) || child.kind() != "enum_variant" {
+                continue;
+            }
+            let name = child
+                .child_by_field_name("name")
+                .map(|n| node_text(n, content).to_string())
+                .unwrap_or_default();
+            let fields_node = child.child_by_field_name("body");
+            let (fields, tuple_type) = match fields_node {
+                Some(n) if n.kind() == "field_declaration_list" => (Self::fields(n, content), None),
+                Some(n) => {
+                    let mut inner = n.walk();
+                    let ty = n
+                        .named_children(&mut inner)
+                        .find(|c| c.kind() != "attribute_item")
+                        .map(|c| type_name(node_text(c, content)));
+                    (Vec::new(), ty)
+                }
+                None => (Vec::new(), None),
+            };
+            variants.push(ClapItem {
+                name,
+                rename: preamble.attribute_value("name"),
+                about: preamble.attribute_value("about").or(preamble.doc.clone()),
+                fields,
+                tuple_type,
+                file_path: path.to_path_buf(),
+                line: child.start_position().row + 1,
+            });
+            preamble = Preamble::default();
+        }
+        variants
+    }
+
+    fn fields(body: Node, content: &str) -> Vec<ClapField> {
+        let subcommand_re = regex(r"\b(?:command|clap|structopt)\s*\(\s*subcommand");
+        let flatten_re = regex(r"\b(?:command|clap|structopt)\s*\(\s*flatten");
+        let long_re = regex(r#"\blong\b(?:\s*=\s*"([^"]+)")?"#);
+        let short_re = regex(r"\bshort\b(?:\s*=\s*'(.)')?");
+
+        let mut fields = Vec::new();
+        let mut preamble = Preamble::default();
+        let mut cursor = body.walk();
+        for child in body.children(&mut cursor) {
+            if preamble.push(child, content) || child.kind() != "field_declaration" {
+                continue;
+            }
+            let name = child
+                .child_by_field_name("name")
+                .map(|n| node_text(n, content).to_string())
+                .unwrap_or_default();
+            let ty = child
+                .child_by_field_name("type")
+                .map(|n| type_name(node_text(n, content)))
+                .unwrap_or_default();
+
+            let mut field = ClapField::default();
+            if preamble.has(&subcommand_re) {
+                field.subcommand = Some(ty);
+            } else if preamble.has(&flatten_re) {
+                field.flatten = Some(ty);
+            } else {
+                let arg = preamble
+                    .attributes
+                    .iter()
+                    .filter(|a| regex(r"#\[\s*(?:arg|clap|structopt)\s*\(").is_match(a))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut names = Vec::new();
+                if let Some(caps) = long_re.captures(&arg) {
+                    let long = caps
+                        .get(1)
+                        .map_or_else(|| kebab(&name), |m| m.as_str().to_string());
+                    names.push(format!("--{}", long));
+                }
+                if let Some(caps) = short_re.captures(&arg) {
+                    let short = caps
+                        .get(1)
+                        .map(|m| m.as_str().to_string())
+                        .or_else(|| name.chars().next().map(String::from))
+                        .unwrap_or_default();
+                    names.push(format!("-{}", short));
+                }
+                if names.is_empty() {
+                    names.push(format!("<{}>", name.to_uppercase()));
+                }
+                field.flag = Some(CliFlag {
+                    names,
+                    help: preamble.doc.clone(),
+                });
+            }
+            fields.push(field);
+            preamble = Preamble::default();
+        }
+        fields
+    }
+
+    fn assemble(&self, sources: &[(PathBuf, String)]) -> Vec<CliCommand> {
+        let mut commands = Vec::new();
+        let mut expanded = HashSet::new();
+
+        for parser in &self.parsers {
+            let program = parser.rename.clone().unwrap_or_else(|| kebab(&parser.name));
+            self.expand_item(
+                parser,
+                None,
+                &program,
+                sources,
+                &mut expanded,
+                &mut commands,
+            );
+        }
+        // Subcommand enums no parser refers to (defined in a library, wired up elsewhere)
+        let mut roots: Vec<&String> = self
+            .subcommands
+            .keys()
+            .filter(|name| !expanded.contains(*name))
+            .collect();
+        roots.sort();
+        for name in roots {
+            if !expanded.contains(name) {
+                self.expand_enum(name, "", sources, &mut expanded, &mut commands);
+            }
+        }
+        commands
+    }
+
+    /// A command (a parser struct or a variant) and everything below it
+    fn expand_item(
+        &self,
+        item: &ClapItem,
+        owner: Option<&str>,
+        path: &str,
+        sources: &[(PathBuf, String)],
+        expanded: &mut HashSet<String>,
+        commands: &mut Vec<CliCommand>,
+    ) {
+        let mut flags = Vec::new();
+        let mut nested = Vec::new();
+        let mut fields: Vec<&ClapField> = item.fields.iter().collect();
+        if let Some(ty) = &item.tuple_type {
+            match self.args.get(ty) {
+                Some(args) => fields.extend(&args.fields),
+                None => nested.push(ty.clone()),
+            }
+        }
+        let mut i = 0;
+        while i < fields.len() {
+            let field = fields[i];
+            if let Some(flag) = &field.flag {
+                flags.push(flag.clone());
+            }
+            if let Some(ty) = &field.subcommand {
+                nested.push(ty.clone());
+            }
+            if let Some(args) = field.flatten.as_ref().and_then(|ty| self.args.get(ty)) {
+                fields.extend(&args.fields);
+            }
+            i += 1;
+        }
+
+        let handler = owner.and_then(|owner| {
+            let re = regex(&format!(r"\b{}::{}\b", owner, regex::escape(&item.name)));
+            sources.iter().find_map(|(_, content)| {
+                re.find_iter(content)
+                    .find_map(|m| arm_handler(content, m.end()))
+            })
+        });
+        commands.push(CliCommand {
+            path: path.to_string(),
+            about: item.about.clone(),
+            flags,
+            handler,
+            framework: "clap",
+            file_path: item.file_path.clone(),
+            line: item.line,
+        });
+
+        for ty in nested {
+            self.expand_enum(&ty, path, sources, expanded, commands);
+        }
+    }
+
+    fn expand_enum(
+        &self,
+        name: &str,
+        prefix: &str,
+        sources: &[(PathBuf, String)],
+        expanded: &mut HashSet<String>,
+        commands: &mut Vec<CliCommand>,
+    ) {
+        let Some(variants) = self.subcommands.get(name) else {
+            return;
+        };
+        if !expanded.insert(name.to_string()) {
+            return;
+        }
+        for variant in variants {
+            let command = variant
+                .rename
+                .clone()
+                .unwrap_or_else(|| kebab(&variant.name));
+            let path = join(prefix, &command);
+            self.expand_item(variant, Some(name), &path, sources, expanded, commands);
+        }
+    }
+}
+
+// ---- clap builder -----------------------------------------------------------------------
+
+/// `"..."` passed to a builder method: `.about("...")`, `.help("...")`
+fn builder_string(statement: &str, method: &str) -> Option<String> {
+    regex(&format!(r#"\.{}\s*\(\s*"((?:[^"\\]|\\.)*)""#, method))
+        .captures(statement)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
+fn clap_builder_commands(content: &str, lines: &[&str], file_path: &Path) -> Vec<CliCommand> {
+    let command_re = regex(r#"(?:Command::new|App::new|SubCommand::with_name)\s*\(\s*"([^"]+)""#);
+    let arg_re = regex(r#"Arg::(?:new|with_name)\s*\(\s*"([^"]+)""#);
+    let long_re = regex(r#"\.long\s*\(\s*"([^"]+)""#);
+    let short_re = regex(r"\.short\s*\(\s*'(.)'");
+
+    let mut commands: Vec<CliCommand> = Vec::new();
+    let mut program = String::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        // Everything after the first command is one of its subcommands
+        for caps in command_re.captures_iter(line) {
+            let name = caps.get(1).map_or("", |m| m.as_str());
+            let start = caps.get(0).map_or(0, |m| m.start());
+            let statement = group_text(lines, i, start, true);
+            // `std::process::Command::new("git")` is not a CLI definition
+            let is_cli = ["Arg::", ".about(", ".subcommand(", ".version("]
+                .iter()
+                .any(|method| statement.contains(method));
+            if !is_cli && !line[..start].contains(".subcommand(") {
+                continue;
+            }
+            let (path, handler) = if program.is_empty() {
+                program = name.to_string();
+                (name.to_string(), None)
+            } else {
+                // `Some(("name", sub_matches)) => handler(...)`
+                let arm_re = regex(&format!(r#"\(\s*"{}"\s*,"#, regex::escape(name)));
+                let handler = arm_re
+                    .find_iter(content)
+                    .find_map(|m| arm_handler(content, m.end()));
+                (join(&program, name), handler)
+            };
+            commands.push(CliCommand {
+                path,
+                about: builder_string(&statement, "about"),
+                flags: Vec::new(),
+                handler,
+                framework: "clap",
+                file_path: file_path.to_path_buf(),
+                line: i + 1,
+            });
+        }
+
+        let Some(caps) = arg_re.captures(line) else {
+            continue;
+        };
+        let name = caps.get(1).map_or("", |m| m.as_str());
+        let start = caps.get(0).map_or(0, |m| m.start());
+        let statement = group_text(lines, i, start, true);
+        let mut names = Vec::new();
+        if let Some(long) = long_re.captures(&statement).and_then(|c| c.get(1)) {
+            names.push(format!("--{}", long.as_str()));
+        }
+        if let Some(short) = short_re.captures(&statement).and_then(|c| c.get(1)) {
+            names.push(format!("-{}", short.as_str()));
+        }
+        if names.is_empty() {
+            names.push(format!("<{}>", name.to_uppercase()));
+        }
+        if let Some(command) = commands.last_mut() {
+            command.flags.push(CliFlag {
+                names,
+                help: builder_string(&statement, "help"),
+            });
+        }
+    }
+
+    commands
+}
+
+// ---- argparse ---------------------------------------------------------------------------
+
+/// Flag names and help of an `add_argument(...)` or `click.option(...)` argument list
+fn python_flag(call_args: &str, positional: bool) -> CliFlag {
+    let strings: Vec<String> = split_args(call_args)
+        .into_iter()
+        .filter(|a| a.starts_with(['"', '\'']))
+        .filter_map(first_string)
+        .collect();
+    let mut names: Vec<String> = strings
+        .iter()
+        .filter(|s| s.starts_with('-'))
+        .cloned()
+        .collect();
+    if names.is_empty() || positional {
+        names = strings
+            .first()
+            .map(|s| vec![format!("<{}>", s.to_uppercase())])
+            .unwrap_or_default();
+    }
+    CliFlag {
+        names,
+        help: keyword_string(call_args, "help"),
+    }
+}
+
+fn argparse_commands(lines: &[&str], file_path: &Path) -> Vec<CliCommand> {
+    let root_re = regex(r"^\s*(\w+)\s*=\s*(?:argparse\.)?ArgumentParser\s*\(");
+    let subparsers_re = regex(r"^\s*(\w+)\s*=\s*(\w+)\.add_subparsers\s*\(");
+    let parser_re = regex(r#"^\s*(?:(\w+)\s*=\s*)?(\w+)\.add_parser\s*\(\s*["']([^"']+)["']"#);
+    let argument_re = regex(r"^\s*(\w+)\.add_argument\s*\(");
+    let defaults_re = regex(r"^\s*(\w+)\.set_defaults\s*\(");
+    let func_re = regex(r"\bfunc\s*=\s*([\w.]+)");
+
+    let program = file_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // parser variable -> index into commands
+    let mut parsers: HashMap<&str, usize> = HashMap::new();
+    // subparsers variable -> parent parser variable
+    let mut groups: HashMap<&str, &str> = HashMap::new();
+    let mut commands: Vec<CliCommand> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = root_re.captures(line) {
+            let call = group_text(lines, i, 0, false);
+            let call_args = args(&call);
+            commands.push(CliCommand {
+                path: keyword_string(call_args, "prog").unwrap_or_else(|| program.clone()),
+                about: keyword_string(call_args, "description"),
+                flags: Vec::new(),
+                handler: None,
+                framework: "argparse",
+                file_path: file_path.to_path_buf(),
+                line: i + 1,
+            });
+            parsers.insert(caps.get(1).map_or("", |m| m.as_str()), commands.len() - 1);
+        } else if let Some(caps) = subparsers_re.captures(line) {
+            groups.insert(
+                caps.get(1).map_or("", |m| m.as_str()),
+                caps.get(2).map_or("", |m| m.as_str()),
+            );
+        } else if let Some(caps) = parser_re.captures(line) {
+            let group = caps.get(2).map_or("", |m| m.as_str());
+            let parent = groups
+                .get(group)
+                .and_then(|parent| parsers.get(parent))
+                .map(|&p| commands[p].path.clone())
+                .unwrap_or_else(|| program.clone());
+            let call = group_text(lines, i, 0, false);
+            let call_args = args(&call);
+            commands.push(CliCommand {
+                path: join(&parent, caps.get(3).map_or("", |m| m.as_str())),
+                about: keyword_string(call_args, "help")
+                    .or_else(|| keyword_string(call_args, "description")),
+                flags: Vec::new(),
+                handler: None,
+                framework: "argparse",
+                file_path: file_path.to_path_buf(),
+                line: i + 1,
+            });
+            if let Some(var) = caps.get(1) {
+                parsers.insert(var.as_str(), commands.len() - 1);
+            }
+        } else if let Some(caps) = argument_re.captures(line) {
+            let Some(&p) = parsers.get(caps.get(1).map_or("", |m| m.as_str())) else {
+                continue;
+            };
+            let call = group_text(lines, i, 0, false);
+            commands[p].flags.push(python_flag(args(&call), false));
+        } else if let Some(caps) = defaults_re.captures(line) {
+            let Some(&p) = parsers.get(caps.get(1).map_or("", |m| m.as_str())) else {
+                continue;
+            };
+            let call = group_text(lines, i, 0, false);
+            if let Some(func) = func_re.captures(args(&call)).and_then(|c| c.get(1)) {
+                commands[p].handler = Some(func.as_str().to_string());
+            }
+        }
+    }
+
+    commands
+}
+
+// ---- click / typer ----------------------------------------------------------------------
+
+fn click_commands(content: &str, lines: &[&str], file_path: &Path) -> Vec<CliCommand> {
+    let framework = if content.contains("typer") {
+        "typer"
+    } else {
+        "click"
+    };
+    let app_re = regex(r"^\s*(\w+)\s*=\s*typer\.Typer\s*\(");
+    let command_re = regex(r"^\s*@(\w+)\.(group|command)\s*\(");
+    let option_re = regex(r"^\s*@click\.(option|argument)\s*\(");
+    let def_re = regex(r"^\s*(?:async\s+)?def\s+(\w+)");
+
+    // group function or Typer app -> its command path
+    let mut groups: HashMap<String, String> = HashMap::new();
+    let mut commands = Vec::new();
+    // (object, kind, decorator args, line) of the pending command decorator
+    let mut pending: Option<(String, String, String, usize)> = None;
+    let mut flags: Vec<CliFlag> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = app_re.captures(line) {
+            groups.insert(
+                caps.get(1).map_or("", |m| m.as_str()).to_string(),
+                String::new(),
+            );
+        } else if let Some(caps) = command_re.captures(line) {
+            let call = group_text(lines, i, 0, false);
+            pending = Some((
+                caps.get(1).map_or("", |m| m.as_str()).to_string(),
+                caps.get(2).map_or("", |m| m.as_str()).to_string(),
+                args(&call).to_string(),
+                i + 1,
+            ));
+        } else if let Some(caps) = option_re.captures(line) {
+            let call = group_text(lines, i, 0, false);
+            let positional = caps.get(1).is_some_and(|m| m.as_str() == "argument");
+            flags.push(python_flag(args(&call), positional));
+        } else if let Some(caps) = def_re.captures(line) {
+            let function = caps.get(1).map_or("", |m| m.as_str());
+            let Some((object, kind, decorator_args, line)) = pending.take() else {
+                flags.clear();
+                continue;
+            };
+            let name = split_args(&decorator_args)
+                .first()
+                .filter(|a| a.starts_with(['"', '\'']))
+                .and_then(|a| first_string(a))
+                .or_else(|| keyword_string(&decorator_args, "name"))
+                .unwrap_or_else(|| kebab(function));
+            // `@click.group()` starts a program; `@cli.command()` adds to group `cli`
+            let path = match groups.get(&object) {
+                Some(parent) if object != "click" => join(parent, &name),
+                _ => name,
+            };
+            if kind == "group" {
+                groups.insert(function.to_string(), path.clone());
+            }
+            let about = lines
+                .get(i + 1)
+                .map(|l| l.trim())
+                .and_then(|l| l.strip_prefix("\"\"\""))
+                .map(|l| l.trim_end_matches("\"\"\"").trim().to_string())
+                .filter(|l| !l.is_empty())
+                .or_else(|| keyword_string(&decorator_args, "help"));
+            commands.push(CliCommand {
+                path,
+                about,
+                flags: std::mem::take(&mut flags),
+                handler: Some(function.to_string()),
+                framework,
+                file_path: file_path.to_path_buf(),
+                line,
+            });
+        }
+    }
+
+    commands
+}
+
+// ---- cobra ------------------------------------------------------------------------------
+
+/// Text from `lines[line]` to the `}` closing the first `{` on it
+fn brace_block(lines: &[&str], line: usize) -> String {
+    let mut text = String::new();
+    let mut depth = 0i32;
+    for l in lines.iter().skip(line).take(200) {
+        text.push_str(l);
+        text.push('\n');
+        depth += l.matches('{').count() as i32 - l.matches('}').count() as i32;
+        if depth <= 0 {
+            break;
+        }
+    }
+    text
+}
+
+fn cobra_commands(lines: &[&str], file_path: &Path) -> Vec<CliCommand> {
+    let command_re = regex(r"^\s*(?:var\s+)?(\w+)\s*:?=\s*&cobra\.Command\s*\{");
+    let use_re = regex(r#"\bUse:\s*"([^"\s]+)"#);
+    let short_re = regex(r#"\bShort:\s*"((?:[^"\\]|\\.)*)""#);
+    let run_re = regex(r"\b(?:Run|RunE)\s*:\s*([\w.]+)\s*,");
+    let add_re = regex(r"\b(\w+)\.AddCommand\s*\(([^)]*)\)");
+    let flag_re = regex(r"\b(\w+)\.(?:Persistent)?Flags\(\)\.(\w+)\s*\(");
+
+    // variable -> (command name, index into commands)
+    let mut vars: HashMap<String, usize> = HashMap::new();
+    let mut parents: HashMap<String, String> = HashMap::new();
+    let mut commands: Vec<CliCommand> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = command_re.captures(line) {
+            let block = brace_block(lines, i);
+            let name = use_re
+                .captures(&block)
+                .and_then(|c| c.get(1))
+                .map_or_else(|| "?".to_string(), |m| m.as_str().to_string());
+            commands.push(CliCommand {
+                path: name,
+                about: short_re
+                    .captures(&block)
+                    .and_then(|c| c.get(1))
+                    .map(|m| m.as_str().to_string()),
+                flags: Vec::new(),
+                handler: run_re
+                    .captures(&block)
+                    .and_then(|c| c.get(1))
+                    .map(|m| m.as_str().to_string()),
+                framework: "cobra",
+                file_path: file_path.to_path_buf(),
+                line: i + 1,
+            });
+            vars.insert(
+                caps.get(1).map_or("", |m| m.as_str()).to_string(),
+                commands.len() - 1,
+            );
+        }
+        for caps in add_re.captures_iter(line) {
+            let parent = caps.get(1).map_or("", |m| m.as_str());
+            for child in split_args(caps.get(2).map_or("", |m| m.as_str())) {
+                parents.insert(child.to_string(), parent.to_string());
+            }
+        }
+        if let Some(caps) = flag_re.captures(line) {
+            let Some(&c) = vars.get(caps.get(1).map_or("", |m| m.as_str())) else {
+                continue;
+            };
+            let method = caps.get(2).map_or("", |m| m.as_str());
+            let call = group_text(lines, i, caps.get(0).map_or(0, |m| m.end()) - 1, false);
+            let strings: Vec<String> = split_args(args(&call))
+                .into_iter()
+                .filter(|a| a.starts_with('"'))
+                .filter_map(first_string)
+                .collect();
+            let Some(long) = strings.first() else {
+                continue;
+            };
+            let mut names = vec![format!("--{}", long)];
+            let shorthand = method.ends_with('P');
+            if let Some(short) = strings.get(1).filter(|s| shorthand && !s.is_empty()) {
+                names.push(format!("-{}", short));
+            }
+            let expected = if shorthand { 2 } else { 1 };
+            commands[c].flags.push(CliFlag {
+                names,
+                help: strings.last().filter(|_| strings.len() > expected).cloned(),
+            });
+        }
+    }
+
+    // Prefix each command with its parents' names: `root child grandchild`
+    let by_index: HashMap<usize, &String> = vars.iter().map(|(var, &i)| (i, var)).collect();
+    let names: Vec<String> = commands.iter().map(|c| c.path.clone()).collect();
+    for (i, command) in commands.iter_mut().enumerate() {
+        let mut path = names[i].clone();
+        let mut var = by_index.get(&i).copied();
+        let mut seen = HashSet::new();
+        while let Some(parent) = var.and_then(|v| parents.get(v)) {
+            if !seen.insert(parent.clone()) {
+                break;
+            }
+            let Some(&p) = vars.get(parent) else {
+                break;
+            };
+            path = join(&names[p], &path);
+            var = Some(parent);
+        }
+        command.path = path;
+    }
+
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+
+    #[test]
+    fn test_clap_derive_commands_and_handlers() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = r#"
+#[derive(clap::Parser)]
+#[command(name = "tool")]
+struct Cli {
+    /// Verbose output
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Index a directory
+    Index {
+        /// Directory to index
+        path: PathBuf,
+
+        #[arg(long = "ext")]
+        extensions: String,
+    },
+    #[command(about = "Manage the cache")]
+    Cache(CacheArgs),
+}
+
+#[derive(Args)]
+struct CacheArgs {
+    #[command(subcommand)]
+    action: CacheAction,
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    ClearAll,
+}
+
+fn main() {
+    match cli.command {
+        Commands::Index { path, extensions } => {
+            run_index(&path, extensions)?;
+        }
+        Commands::Cache(args) => match args.action {
+            CacheAction::ClearAll => cache::clear()?,
+        },
+    }
+}
+"#;
+        let index = index_of(dir.path(), &[("main.rs", source, Language::Rust)]);
+
+        let commands = find_cli_commands(&index).unwrap();
+        let summary: Vec<(&str, Option<&str>)> = commands
+            .iter()
+            .map(|c| (c.path.as_str(), c.handler.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("tool", None),
+                ("tool cache", None),
+                ("tool cache clear-all", Some("cache::clear")),
+                ("tool index", Some("run_index")),
+            ]
+        );
+        assert_eq!(commands[0].flags[0].names, vec!["--verbose", "-v"]);
+        assert_eq!(commands[1].about.as_deref(), Some("Manage the cache"));
+        let index_flags: Vec<&Vec<String>> = commands[3].flags.iter().map(|f| &f.names).collect();
+        assert_eq!(
+            index_flags,
+            vec![&vec!["<PATH>".to_string()], &vec!["--ext".to_string()]]
+        );
+        assert_eq!(
+            commands[3].flags[0].help.as_deref(),
+            Some("Directory to index")
+        );
+    }
+
+    #[test]
+    fn test_python_and_cobra_commands() {
+        let argparse = r#"
+import argparse
+parser = argparse.ArgumentParser(prog="deploy", description="Deploy tool")
+parser.add_argument("--dry-run", action="store_true", help="Only print")
+sub = parser.add_subparsers(dest="cmd")
+push = sub.add_parser("push", help="Push a release")
+push.add_argument("tag")
+push.set_defaults(func=do_push)
+"#;
+        let lines: Vec<&str> = argparse.lines().collect();
+        let commands = argparse_commands(&lines, Path::new("deploy.py"));
+        assert_eq!(commands[0].path, "deploy");
+        assert_eq!(commands[0].flags[0].names, vec!["--dry-run"]);
+        assert_eq!(commands[0].flags[0].help.as_deref(), Some("Only print"));
+        assert_eq!(commands[1].path, "deploy push");
+        assert_eq!(commands[1].flags[0].names, vec!["<TAG>"]);
+        assert_eq!(commands[1].handler.as_deref(), Some("do_push"));
+
+        let click = r#"
+import click
+
+@click.group()
+def cli():
+    pass
+
+@cli.command()
+@click.option("--force", "-f", is_flag=True, help="Overwrite")
+@click.argument("name")
+def init_project(force, name):
+    """Create a project."""
+"#;
+        let lines: Vec<&str> = click.lines().collect();
+        let commands = click_commands(click, &lines, Path::new("cli.py"));
+        assert_eq!(commands[1].path, "cli init-project");
+        assert_eq!(commands[1].about.as_deref(), Some("Create a project."));
+        assert_eq!(commands[1].flags[0].names, vec!["--force", "-f"]);
+        assert_eq!(commands[1].flags[1].names, vec!["<NAME>"]);
+
+        let cobra = r#"
+var rootCmd = &cobra.Command{
+	Use:   "app",
+	Short: "The app",
+}
+
+var serveCmd = &cobra.Command{
+	Use:   "serve [flags]",
+	Short: "Start the server",
+	RunE:  runServe,
+}
+
+func init() {
+	rootCmd.AddCommand(serveCmd)
+	serveCmd.Flags().IntVarP(&port, "port", "p", 8080, "Port to listen on")
+}
+"#;
+        let lines: Vec<&str> = cobra.lines().collect();
+        let commands = cobra_commands(&lines, Path::new("cmd.go"));
+        assert_eq!(commands[1].path, "app serve");
+        assert_eq!(commands[1].handler.as_deref(), Some("runServe"));
+        assert_eq!(commands[1].flags[0].names, vec!["--port", "-p"]);
+        assert_eq!(
+            commands[1].flags[0].help.as_deref(),
+            Some("Port to listen on")
+        );
+    }
+}
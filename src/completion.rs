@@ -0,0 +1,386 @@
+//! Shell completion scripts for `cm`, generated from the clap command tree. Symbol names and
+//! indexed file paths are completed dynamically: the scripts call back into `cm complete`,
+//! which answers from the on-disk cache without reindexing.
+
+use crate::index::CodeIndex;
+use clap::{Command, ValueEnum};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Candidates returned per completion request; shells slow down on huge lists
+pub const MAX_CANDIDATES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// What a positional argument holds, decided by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// A symbol name, completed from the cached index
+    Symbol,
+    /// A source file, completed from the cached index's file list
+    File,
+    /// A symbol or a file (`cm deps <target>`)
+    Target,
+    /// A directory
+    Dir,
+    /// Anything else (commits, snapshot names): left to the shell's default
+    Other,
+}
+
+impl ValueKind {
+    fn for_arg(id: &str) -> Self {
+        match id {
+            "symbol" | "from" | "to" | "interface" => Self::Symbol,
+            "file" | "file_path" | "test_file" | "paths" => Self::File,
+            "target" => Self::Target,
+            "path" => Self::Dir,
+            _ => Self::Other,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Symbol => "symbols",
+            Self::File => "files",
+            Self::Target => "targets",
+            Self::Dir => "dirs",
+            Self::Other => "other",
+        }
+    }
+}
+
+struct SubcommandInfo {
+    name: String,
+    about: String,
+    flags: Vec<String>,
+    positionals: Vec<ValueKind>,
+}
+
+fn subcommands(cmd: &Command) -> Vec<SubcommandInfo> {
+    cmd.get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .map(|sub| SubcommandInfo {
+            name: sub.get_name().to_string(),
+            about: sub.get_about().map(|a| a.to_string()).unwrap_or_default(),
+            flags: sub
+                .get_arguments()
+                .filter(|arg| !arg.is_hide_set())
+                .filter_map(|arg| arg.get_long().map(|l| format!("--{}", l)))
+                .collect(),
+            positionals: sub
+                .get_positionals()
+                .map(|arg| ValueKind::for_arg(arg.get_id().as_str()))
+                .collect(),
+        })
+        .collect()
+}
+
+fn global_flags(cmd: &Command) -> Vec<String> {
+    cmd.get_arguments()
+        .filter_map(|arg| arg.get_long().map(|l| format!("--{}", l)))
+        .chain(["--help".to_string(), "--version".to_string()])
+        .collect()
+}
+
+/// Completion script for `shell`; `cmd` is the `cm` command tree
+pub fn script(shell: Shell, cmd: &Command) -> String {
+    let subs = subcommands(cmd);
+    let globals = global_flags(cmd).join(" ");
+    match shell {
+        Shell::Bash => bash_script(&subs, &globals),
+        Shell::Zsh => zsh_script(&subs, &globals),
+        Shell::Fish => fish_script(&subs, &globals),
+    }
+}
+
+fn kinds_list(kinds: &[ValueKind]) -> String {
+    kinds
+        .iter()
+        .map(|k| k.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bash_script(subs: &[SubcommandInfo], globals: &str) -> String {
+    let names: Vec<&str> = subs.iter().map(|s| s.name.as_str()).collect();
+    let mut flags_case = String::new();
+    let mut kinds_case = String::new();
+    for sub in subs {
+        let _ = writeln!(
+            flags_case,
+            "        {}) opts=\"{}\" ;;",
+            sub.name,
+            sub.flags.join(" ")
+        );
+        let _ = writeln!(
+            kinds_case,
+            "        {}) kinds=({}) ;;",
+            sub.name,
+            kinds_list(&sub.positionals)
+        );
+    }
+
+    format!(
+        r#"# bash completion for cm
+_cm() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{names}" -- "$cur"))
+        return
+    fi
+
+    local cmd="${{COMP_WORDS[1]}}" opts="" kinds=()
+    case "$cmd" in
+{flags_case}    esac
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "$opts {globals}" -- "$cur"))
+        return
+    fi
+
+    case "$cmd" in
+{kinds_case}    esac
+    local pos=0 i
+    for ((i = 2; i < COMP_CWORD; i++)); do
+        [[ "${{COMP_WORDS[i]}}" == -* ]] || ((pos++))
+    done
+
+    local IFS=$'\n'
+    case "${{kinds[pos]}}" in
+        symbols|targets|files)
+            COMPREPLY=($(cm complete "${{kinds[pos]}}" "$cur" 2>/dev/null))
+            [[ ${{#COMPREPLY[@]}} -gt 0 ]] || COMPREPLY=($(compgen -f -- "$cur"))
+            ;;
+        dirs) COMPREPLY=($(compgen -d -- "$cur")) ;;
+        *) COMPREPLY=($(compgen -f -- "$cur")) ;;
+    esac
+}}
+complete -o filenames -F _cm cm
+"#,
+        names = names.join(" "),
+    )
+}
+
+fn zsh_script(subs: &[SubcommandInfo], globals: &str) -> String {
+    let mut commands = String::new();
+    let mut flags_case = String::new();
+    let mut kinds_case = String::new();
+    for sub in subs {
+        let _ = writeln!(
+            commands,
+            "        '{}:{}'",
+            sub.name,
+            sub.about.replace('\'', "'\\''").replace(':', "\\:")
+        );
+        let _ = writeln!(
+            flags_case,
+            "        {}) opts=({}) ;;",
+            sub.name,
+            sub.flags.join(" ")
+        );
+        let _ = writeln!(
+            kinds_case,
+            "        {}) kinds=({}) ;;",
+            sub.name,
+            kinds_list(&sub.positionals)
+        );
+    }
+
+    format!(
+        r#"#compdef cm
+# zsh completion for cm
+_cm() {{
+    if (( CURRENT == 2 )); then
+        local -a commands
+        commands=(
+{commands}        )
+        _describe 'command' commands
+        return
+    fi
+
+    local cmd=${{words[2]}}
+    local -a opts kinds
+    case $cmd in
+{flags_case}    esac
+    if [[ $PREFIX == -* ]]; then
+        compadd -- $opts {globals}
+        return
+    fi
+
+    case $cmd in
+{kinds_case}    esac
+    local pos=1 w
+    for w in ${{words[3,CURRENT-1]}}; do
+        [[ $w == -* ]] || (( pos++ ))
+    done
+
+    case ${{kinds[pos]}} in
+        symbols|targets|files)
+            local -a found
+            found=(${{(f)"$(cm complete ${{kinds[pos]}} "$PREFIX" 2>/dev/null)"}})
+            if (( ${{#found}} )); then
+                compadd -- $found
+            else
+                _files
+            fi
+            ;;
+        dirs) _files -/ ;;
+        *) _files ;;
+    esac
+}}
+compdef _cm cm
+"#
+    )
+}
+
+fn fish_script(subs: &[SubcommandInfo], globals: &str) -> String {
+    let mut out = String::from(
+        r#"# fish completion for cm
+function __cm_positional_kind
+    set -l tokens (commandline -opc)
+    set -l cmd $tokens[2]
+    set -l pos 1
+    for token in $tokens[3..-1]
+        string match -q -- '-*' $token; or set pos (math $pos + 1)
+    end
+    switch $cmd
+"#,
+    );
+    for sub in subs {
+        let _ = writeln!(out, "        case {}", sub.name);
+        let _ = writeln!(
+            out,
+            "            set -l kinds {}",
+            kinds_list(&sub.positionals)
+        );
+        out.push_str("            test $pos -le (count $kinds); and echo $kinds[$pos]\n");
+    }
+    out.push_str(
+        r#"    end
+end
+
+function __cm_dynamic
+    set -l kind (__cm_positional_kind)
+    contains -- "$kind" symbols targets files; or return
+    cm complete $kind (commandline -ct) 2>/dev/null
+end
+
+complete -c cm -n '__fish_use_subcommand' -f
+"#,
+    );
+
+    for sub in subs {
+        let _ = writeln!(
+            out,
+            "complete -c cm -n '__fish_use_subcommand' -a {} -d '{}'",
+            sub.name,
+            sub.about.replace('\'', "\\'")
+        );
+        for flag in &sub.flags {
+            let _ = writeln!(
+                out,
+                "complete -c cm -n '__fish_seen_subcommand_from {}' -l {}",
+                sub.name,
+                flag.trim_start_matches("--")
+            );
+        }
+    }
+    for flag in globals.split(' ') {
+        let _ = writeln!(out, "complete -c cm -l {}", flag.trim_start_matches("--"));
+    }
+    out.push_str("complete -c cm -n 'not __fish_use_subcommand' -a '(__cm_dynamic)'\n");
+    out
+}
+
+/// Distinct symbol names starting with `prefix`; falls back to a case-insensitive match
+/// when nothing matches exactly
+pub fn symbol_candidates(index: &CodeIndex, prefix: &str) -> Vec<String> {
+    let matching = |ignore_case: bool| -> BTreeSet<&str> {
+        let lower = prefix.to_lowercase();
+        index
+            .names()
+            .map(|name| name.as_str())
+            .filter(|name| !name.is_empty() && *name != "anonymous")
+            .filter(|name| {
+                if ignore_case {
+                    name.to_lowercase().starts_with(&lower)
+                } else {
+                    name.starts_with(prefix)
+                }
+            })
+            .collect()
+    };
+
+    let mut names = matching(false);
+    if names.is_empty() {
+        names = matching(true);
+    }
+    names
+        .into_iter()
+        .take(MAX_CANDIDATES)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Indexed file paths starting with `prefix` (a leading `./` on either side is ignored)
+pub fn file_candidates(index: &CodeIndex, prefix: &str) -> Vec<String> {
+    let prefix = prefix.trim_start_matches("./");
+    let paths: BTreeSet<String> = index
+        .files()
+        .map(|file| file.path.to_string_lossy().replace('\\', "/"))
+        .map(|path| path.trim_start_matches("./").to_string())
+        .filter(|path| path.starts_with(prefix))
+        .collect();
+    paths.into_iter().take(MAX_CANDIDATES).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+    use crate::models::Language;
+    use std::path::Path;
+
+    #[test]
+    fn test_candidates_from_index() {
+        let mut index = CodeIndex::new();
+        let source = "fn parse_config() {}\nfn parse_args() {}\nfn ParseError() {}\n";
+        index
+            .add_file(index_file(Path::new("./src/cli.rs"), source, Language::Rust, None).unwrap());
+
+        assert_eq!(
+            symbol_candidates(&index, "parse_"),
+            vec!["parse_args", "parse_config"]
+        );
+        assert_eq!(symbol_candidates(&index, "parsex"), Vec::<String>::new());
+        assert_eq!(symbol_candidates(&index, "parseE"), vec!["ParseError"]);
+        assert_eq!(file_candidates(&index, "src/"), vec!["src/cli.rs"]);
+        assert_eq!(file_candidates(&index, "./src/c"), vec!["src/cli.rs"]);
+    }
+
+    #[test]
+    fn test_script_lists_subcommands_and_positionals() {
+        let cmd = Command::new("cm")
+            .arg(clap::Arg::new("format").long("format").global(true))
+            .subcommand(
+                Command::new("callers")
+                    .about("Find callers")
+                    .arg(clap::Arg::new("symbol"))
+                    .arg(clap::Arg::new("path"))
+                    .arg(clap::Arg::new("fuzzy").long("fuzzy")),
+            );
+
+        let bash = script(Shell::Bash, &cmd);
+        assert!(bash.contains(r#"compgen -W "callers""#));
+        assert!(bash.contains("callers) kinds=(symbols dirs)"));
+        assert!(bash.contains("callers) opts=\"--fuzzy\""));
+
+        let fish = script(Shell::Fish, &cmd);
+        assert!(fish.contains("-a callers -d 'Find callers'"));
+        assert!(script(Shell::Zsh, &cmd).starts_with("#compdef cm"));
+    }
+}
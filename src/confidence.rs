@@ -0,0 +1,112 @@
+//! How much to trust an edge from `callers`, `tests`, `implements` and `deps --direction
+//! used-by`. None of them resolve types or bindings, so a result is only as good as the way it
+//! was found:
+//!
+//! - `exact-ast`: a syntax-tree node (call, import, declaration) with exactly the name asked
+//!   for, and only one definition of that name it could refer to
+//! - `name-match`: the name is right but the evidence is weaker: a fuzzy match, a name several
+//!   definitions share, or a whole-word occurrence outside any call
+//! - `text-match`: a substring or pattern hit in raw source text, which may be part of a longer
+//!   name or sit in a comment or string
+//!
+//! Levels order from `text-match` up to `exact-ast`, so `>=` compares them.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Confidence {
+    TextMatch,
+    NameMatch,
+    ExactAst,
+}
+
+impl Confidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::ExactAst => "exact-ast",
+            Confidence::NameMatch => "name-match",
+            Confidence::TextMatch => "text-match",
+        }
+    }
+
+    /// One-word form for the `ai` format
+    pub fn short(&self) -> &'static str {
+        match self {
+            Confidence::ExactAst => "ast",
+            Confidence::NameMatch => "name",
+            Confidence::TextMatch => "text",
+        }
+    }
+
+    /// A syntax-tree hit named `found` for a query for `wanted`, which the index defines
+    /// `definitions` times. Unqualified names defined more than once can't be told apart.
+    pub fn of_ast_match(found: &str, wanted: &str, definitions: usize) -> Self {
+        if found == wanted && definitions <= 1 {
+            Confidence::ExactAst
+        } else {
+            Confidence::NameMatch
+        }
+    }
+
+    /// A line that contains `name` as text: a whole identifier there is a `name-match`, a
+    /// piece of a longer one (`User` in `UserId`) only a `text-match`
+    pub fn of_text_match(line: &str, name: &str) -> Self {
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let whole_word = line.match_indices(name).any(|(at, _)| {
+            let before = line[..at].chars().next_back();
+            let after = line[at + name.len()..].chars().next();
+            !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+        });
+        if whole_word {
+            Confidence::NameMatch
+        } else {
+            Confidence::TextMatch
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_order_and_matches() {
+        assert!(Confidence::ExactAst > Confidence::NameMatch);
+        assert!(Confidence::NameMatch > Confidence::TextMatch);
+        assert_eq!(
+            serde_json::to_string(&Confidence::ExactAst).unwrap(),
+            "\"exact-ast\""
+        );
+
+        assert_eq!(
+            Confidence::of_ast_match("parse", "parse", 1),
+            Confidence::ExactAst
+        );
+        assert_eq!(
+            Confidence::of_ast_match("parse", "parse", 0),
+            Confidence::ExactAst
+        );
+        assert_eq!(
+            Confidence::of_ast_match("parse", "parse", 2),
+            Confidence::NameMatch
+        );
+        assert_eq!(
+            Confidence::of_ast_match("parse_args", "parse", 1),
+            Confidence::NameMatch
+        );
+
+        assert_eq!(
+            Confidence::of_text_match("let u: User = load();", "User"),
+            Confidence::NameMatch
+        );
+        assert_eq!(
+            Confidence::of_text_match("let id = UserId(3); // User", "User"),
+            Confidence::NameMatch
+        );
+        assert_eq!(
+            Confidence::of_text_match("let id = UserId(3);", "User"),
+            Confidence::TextMatch
+        );
+    }
+}
@@ -0,0 +1,617 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Project-level config file, looked up in the project root
+pub const PROJECT_CONFIG_FILE: &str = ".codemapper.toml";
+
+/// Environment variables that override config files (CLI flags override both)
+pub const ENV_FORMAT: &str = "CM_FORMAT";
+pub const ENV_EXTENSIONS: &str = "CM_EXTENSIONS";
+pub const ENV_NO_CACHE: &str = "CM_NO_CACHE";
+pub const ENV_NO_VENDOR: &str = "CM_NO_VENDOR";
+pub const ENV_TIMEOUT: &str = "CM_TIMEOUT";
+pub const ENV_EXPORTS_ONLY: &str = "CM_EXPORTS_ONLY";
+pub const ENV_SHOW_BODY: &str = "CM_SHOW_BODY";
+pub const ENV_CACHE_DIR: &str = "CM_CACHE_DIR";
+/// Older name for `CM_CACHE_DIR`, still honored
+pub const ENV_CACHE_DIR_LEGACY: &str = "CODEMAPPER_CACHE_DIR";
+
+/// Same schema for `.codemapper.toml` and the user-level `~/.config/codemapper/config.toml`
+#[derive(Debug, Default, Deserialize)]
+pub struct ProjectConfig {
+    /// Default flag values, e.g. `format = "ai"`
+    #[serde(default)]
+    pub defaults: Defaults,
+
+    /// Named sets of defaults selected with `--profile`, e.g. `[profile.agent]`
+    #[serde(default)]
+    pub profile: HashMap<String, Defaults>,
+
+    /// External parsers for languages cm does not support natively
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
+
+    /// Symbol kinds beyond the built-in ones, e.g. `[[kinds]] name = "route", code = "r"`
+    #[serde(default)]
+    pub kinds: Vec<KindConfig>,
+
+    /// Embedding backend for `cm embed` and `cm query --semantic`
+    #[serde(default)]
+    pub embeddings: EmbeddingsConfig,
+
+    /// External command for `cm summarize`
+    #[serde(default)]
+    pub summarizer: SummarizerConfig,
+
+    /// Feature flag detection for `cm flags`
+    #[serde(default)]
+    pub flags: FlagsConfig,
+
+    /// Architectural layers for `cm layers`
+    #[serde(default)]
+    pub layers: LayersConfig,
+
+    /// Plugins from the project file that `load_merged` held back
+    #[serde(skip)]
+    pub untrusted_plugins: Vec<PluginConfig>,
+
+    /// Whether `load_merged` dropped the project file's embedding backend
+    #[serde(skip)]
+    pub untrusted_embeddings: bool,
+
+    /// Whether `load_merged` dropped the project file's summarizer command
+    #[serde(skip)]
+    pub untrusted_summarizer: bool,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct LayersConfig {
+    /// Top layer first, e.g. `"ui -> service -> data"`; a layer may import the layers
+    /// below it, never the ones above
+    pub order: Option<String>,
+    /// Globs per layer, e.g. `ui = ["src/components/**"]` (default: a directory named
+    /// after the layer)
+    #[serde(default)]
+    pub paths: HashMap<String, Vec<String>>,
+}
+
+impl LayersConfig {
+    /// Layers declared here replace the lower ones entirely
+    pub fn or(self, lower: LayersConfig) -> LayersConfig {
+        if self.order.is_some() {
+            self
+        } else {
+            lower
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct FlagsConfig {
+    /// Regexes matching a flag check up to its call, e.g. `'isEnabled\('`; the first
+    /// argument, when a string literal, is the flag key
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+impl FlagsConfig {
+    /// Patterns set here replace the lower ones entirely
+    pub fn or(self, lower: FlagsConfig) -> FlagsConfig {
+        if self.patterns.is_empty() {
+            lower
+        } else {
+            self
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct SummarizerConfig {
+    /// Reads a file or symbol on stdin and prints a short summary, e.g. an LLM CLI
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Truncate input to this many characters (default 12000)
+    pub max_chars: Option<usize>,
+}
+
+impl SummarizerConfig {
+    /// Fill unset values from `lower`; a command set here replaces the lower one entirely
+    pub fn or(self, lower: SummarizerConfig) -> SummarizerConfig {
+        let max_chars = self.max_chars.or(lower.max_chars);
+        let base = if self.command.is_some() { self } else { lower };
+        SummarizerConfig { max_chars, ..base }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct EmbeddingsConfig {
+    /// HTTP endpoint, e.g. `http://localhost:11434/api/embed` (Ollama) or an OpenAI-compatible `/v1/embeddings`
+    pub endpoint: Option<String>,
+    pub model: Option<String>,
+    /// Alternative to `endpoint`: reads a JSON array of strings on stdin, prints a JSON array of vectors
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub batch_size: Option<usize>,
+}
+
+impl EmbeddingsConfig {
+    pub fn has_backend(&self) -> bool {
+        self.endpoint.is_some() || self.command.is_some()
+    }
+
+    /// Only the model and batch size, which select nothing to run or send code to
+    fn without_backend(self) -> EmbeddingsConfig {
+        EmbeddingsConfig {
+            model: self.model,
+            batch_size: self.batch_size,
+            ..EmbeddingsConfig::default()
+        }
+    }
+
+    /// Fill unset values from `lower`; a backend set here replaces the lower one entirely
+    pub fn or(self, lower: EmbeddingsConfig) -> EmbeddingsConfig {
+        if self.has_backend() {
+            EmbeddingsConfig {
+                model: self.model.or(lower.model),
+                batch_size: self.batch_size.or(lower.batch_size),
+                ..self
+            }
+        } else {
+            EmbeddingsConfig {
+                model: self.model.or(lower.model),
+                batch_size: self.batch_size.or(lower.batch_size),
+                ..lower
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Defaults {
+    pub format: Option<String>,
+    pub extensions: Option<Vec<String>>,
+    pub no_cache: Option<bool>,
+    pub no_vendor: Option<bool>,
+    /// `--timeout`, e.g. `"5s"`
+    pub timeout: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+    /// `--exports-only` for query and inspect
+    pub exports_only: Option<bool>,
+    /// `--show-body` for query and inspect
+    pub show_body: Option<bool>,
+    /// Format per subcommand, overriding `format`, e.g. `stats = "human"`
+    #[serde(default)]
+    pub formats: HashMap<String, String>,
+}
+
+impl Defaults {
+    /// Fill unset values from `lower`, keeping the values already set on `self`
+    pub fn or(self, lower: Defaults) -> Defaults {
+        let mut formats = lower.formats;
+        formats.extend(self.formats);
+        Defaults {
+            format: self.format.or(lower.format),
+            extensions: self.extensions.or(lower.extensions),
+            no_cache: self.no_cache.or(lower.no_cache),
+            no_vendor: self.no_vendor.or(lower.no_vendor),
+            timeout: self.timeout.or(lower.timeout),
+            cache_dir: self.cache_dir.or(lower.cache_dir),
+            exports_only: self.exports_only.or(lower.exports_only),
+            show_body: self.show_body.or(lower.show_body),
+            formats,
+        }
+    }
+
+    /// Apply the per-command format for `command`, if one is set
+    pub fn for_command(mut self, command: Option<&str>) -> Defaults {
+        if let Some(format) = command.and_then(|c| self.formats.remove(c)) {
+            self.format = Some(format);
+        }
+        self
+    }
+
+    /// Export as `CM_*` variables (without overwriting ones already set) so clap
+    /// resolves flags as: CLI flag > environment > project config > user config > built-in default
+    pub fn export_to_env(&self) {
+        let set_if_unset = |key: &str, value: String| {
+            if std::env::var_os(key).is_none() {
+                std::env::set_var(key, value);
+            }
+        };
+
+        if let Some(ref format) = self.format {
+            set_if_unset(ENV_FORMAT, format.clone());
+        }
+        if let Some(ref extensions) = self.extensions {
+            set_if_unset(ENV_EXTENSIONS, extensions.join(","));
+        }
+        if let Some(no_cache) = self.no_cache {
+            set_if_unset(ENV_NO_CACHE, no_cache.to_string());
+        }
+        if let Some(no_vendor) = self.no_vendor {
+            set_if_unset(ENV_NO_VENDOR, no_vendor.to_string());
+        }
+        if let Some(ref timeout) = self.timeout {
+            set_if_unset(ENV_TIMEOUT, timeout.clone());
+        }
+        if let Some(exports_only) = self.exports_only {
+            set_if_unset(ENV_EXPORTS_ONLY, exports_only.to_string());
+        }
+        if let Some(show_body) = self.show_body {
+            set_if_unset(ENV_SHOW_BODY, show_body.to_string());
+        }
+
+        // clap reads the legacy variable; CM_CACHE_DIR takes precedence over the config value
+        if std::env::var_os(ENV_CACHE_DIR_LEGACY).is_none() {
+            if let Some(dir) = std::env::var_os(ENV_CACHE_DIR) {
+                std::env::set_var(ENV_CACHE_DIR_LEGACY, dir);
+            } else if let Some(ref dir) = self.cache_dir {
+                std::env::set_var(ENV_CACHE_DIR_LEGACY, dir);
+            }
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/codemapper/config.toml`, falling back to `~/.config/codemapper/config.toml`
+pub fn user_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join("codemapper").join("config.toml"))
+}
+
+/// The directory whose `.codemapper.toml` applies to `target`: the nearest one up from it
+/// (from its directory, for a file), not looking past the enclosing git repository. When no
+/// directory has one, `target`'s own.
+pub fn project_root(target: &Path) -> PathBuf {
+    let start = if target.is_file() {
+        target.parent().unwrap_or(target)
+    } else {
+        target
+    };
+    for dir in start.ancestors() {
+        if dir.join(PROJECT_CONFIG_FILE).is_file() {
+            return dir.to_path_buf();
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    start.to_path_buf()
+}
+
+/// Project config merged over user config. A plugin runs its command on every file of its
+/// language, an embedding backend is a command to run or an endpoint to send code to, and a
+/// summarizer is a command, so the project file's only apply with `allow_project_commands`:
+/// mapping a freshly cloned repository must not run commands it chose. Held-back plugins are
+/// kept in `untrusted_plugins`; `untrusted_embeddings` and `untrusted_summarizer` say the
+/// others were dropped.
+pub fn load_merged(root: &Path, allow_project_commands: bool) -> Result<ProjectConfig> {
+    let user = match user_config_path() {
+        Some(path) => ProjectConfig::load_file(&path)?,
+        None => ProjectConfig::default(),
+    };
+    Ok(merge(
+        user,
+        ProjectConfig::load(root)?,
+        allow_project_commands,
+    ))
+}
+
+fn merge(
+    user: ProjectConfig,
+    project: ProjectConfig,
+    allow_project_commands: bool,
+) -> ProjectConfig {
+    let mut plugins = user.plugins;
+    let mut untrusted_plugins = Vec::new();
+    if allow_project_commands {
+        plugins.extend(project.plugins);
+    } else {
+        untrusted_plugins = project.plugins;
+    }
+    let untrusted_embeddings = !allow_project_commands && project.embeddings.has_backend();
+    let project_embeddings = if untrusted_embeddings {
+        project.embeddings.without_backend()
+    } else {
+        project.embeddings
+    };
+    let untrusted_summarizer = !allow_project_commands && project.summarizer.command.is_some();
+    let project_summarizer = if untrusted_summarizer {
+        SummarizerConfig {
+            max_chars: project.summarizer.max_chars,
+            ..SummarizerConfig::default()
+        }
+    } else {
+        project.summarizer
+    };
+    let mut kinds = user.kinds;
+    kinds.extend(project.kinds);
+
+    let mut profile = user.profile;
+    for (name, defaults) in project.profile {
+        let lower = profile.remove(&name).unwrap_or_default();
+        profile.insert(name, defaults.or(lower));
+    }
+
+    ProjectConfig {
+        defaults: project.defaults.or(user.defaults),
+        profile,
+        plugins,
+        kinds,
+        embeddings: project_embeddings.or(user.embeddings),
+        summarizer: project_summarizer.or(user.summarizer),
+        flags: project.flags.or(user.flags),
+        layers: project.layers.or(user.layers),
+        untrusted_plugins,
+        untrusted_embeddings,
+        untrusted_summarizer,
+    }
+}
+
+/// A subprocess parser: `command args... <file>` receives the source on stdin
+/// and prints `{"symbols": [...], "dependencies": [...]}` JSON on stdout
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginConfig {
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Kinds the plugin reports besides the built-in ones
+    #[serde(default)]
+    pub kinds: Vec<KindConfig>,
+}
+
+/// A custom symbol kind (see `kinds`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct KindConfig {
+    /// As plugins report it and `--type` takes it, e.g. `component`
+    pub name: String,
+    /// Short code for the ai format, 1-3 letters or digits, e.g. `co`
+    pub code: String,
+    /// For `--type` (default: name + "s")
+    pub plural: Option<String>,
+}
+
+impl ProjectConfig {
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).context("Invalid project config")
+    }
+
+    /// Defaults for one run: the selected profile layered over `[defaults]`, each with its
+    /// per-command format applied, so a profile's general format still beats a
+    /// `[defaults.formats]` entry
+    pub fn resolve_defaults(
+        &self,
+        profile: Option<&str>,
+        command: Option<&str>,
+    ) -> Result<Defaults> {
+        let defaults = self.defaults.clone().for_command(command);
+        let Some(name) = profile else {
+            return Ok(defaults);
+        };
+
+        let Some(selected) = self.profile.get(name) else {
+            let mut defined: Vec<&str> = self.profile.keys().map(String::as_str).collect();
+            defined.sort_unstable();
+            bail!(
+                "Unknown profile '{}' (defined: {})",
+                name,
+                if defined.is_empty() {
+                    "none".to_string()
+                } else {
+                    defined.join(", ")
+                }
+            );
+        };
+        Ok(selected.clone().for_command(command).or(defaults))
+    }
+
+    /// Load `.codemapper.toml` from `root`; a missing file yields the default config
+    pub fn load(root: &Path) -> Result<Self> {
+        Self::load_file(&root.join(PROJECT_CONFIG_FILE))
+    }
+
+    pub fn load_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config: {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("in {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plugins() {
+        let config = ProjectConfig::parse(
+            r#"
+[[plugins]]
+name = "elixir"
+extensions = ["ex", "exs"]
+command = "cm-elixir"
+args = ["--json"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.plugins.len(), 1);
+        assert_eq!(config.plugins[0].extensions, vec!["ex", "exs"]);
+        assert_eq!(config.plugins[0].args, vec!["--json"]);
+        assert!(ProjectConfig::parse("").unwrap().plugins.is_empty());
+    }
+
+    #[test]
+    fn test_project_root_is_found_from_the_target() {
+        let outer = tempfile::tempdir().unwrap();
+        fs::write(outer.path().join(PROJECT_CONFIG_FILE), "").unwrap();
+        let repo = outer.path().join("repo");
+        let src = repo.join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("lib.rs"), "").unwrap();
+
+        // The enclosing repository bounds the search; without a config the target is the root
+        fs::create_dir(repo.join(".git")).unwrap();
+        assert_eq!(project_root(&src), src);
+        assert_eq!(project_root(&src.join("lib.rs")), src);
+
+        fs::write(repo.join(PROJECT_CONFIG_FILE), "").unwrap();
+        assert_eq!(project_root(&src), repo);
+        assert_eq!(project_root(&src.join("lib.rs")), repo);
+        assert_eq!(project_root(outer.path()), outer.path());
+    }
+
+    #[test]
+    fn test_project_plugins_need_allowing() {
+        let plugin = |name: &str| {
+            format!(
+                "[[plugins]]\nname = \"{}\"\nextensions = [\"ex\"]\ncommand = \"{}\"\n",
+                name, name
+            )
+        };
+        let config = || {
+            (
+                ProjectConfig::parse(&plugin("mine")).unwrap(),
+                ProjectConfig::parse(&plugin("theirs")).unwrap(),
+            )
+        };
+        let names =
+            |plugins: &[PluginConfig]| plugins.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+
+        let (user, project) = config();
+        let held_back = merge(user, project, false);
+        assert_eq!(names(&held_back.plugins), ["mine"]);
+        assert_eq!(names(&held_back.untrusted_plugins), ["theirs"]);
+
+        let (user, project) = config();
+        let allowed = merge(user, project, true);
+        assert_eq!(names(&allowed.plugins), ["mine", "theirs"]);
+        assert!(allowed.untrusted_plugins.is_empty());
+    }
+
+    #[test]
+    fn test_project_embeddings_need_allowing() {
+        let project = || {
+            ProjectConfig::parse(
+                "[embeddings]\ncommand = \"exfiltrate\"\nendpoint = \"https://example.com\"\nmodel = \"small\"\n",
+            )
+            .unwrap()
+        };
+
+        let held_back = merge(ProjectConfig::default(), project(), false);
+        assert!(held_back.untrusted_embeddings);
+        assert!(!held_back.embeddings.has_backend());
+        assert!(held_back.embeddings.args.is_empty());
+        assert_eq!(held_back.embeddings.model.as_deref(), Some("small"));
+
+        // The user's own backend still applies
+        let user =
+            ProjectConfig::parse("[embeddings]\nendpoint = \"http://localhost:11434/api/embed\"\n")
+                .unwrap();
+        let merged = merge(user, project(), false);
+        assert_eq!(
+            merged.embeddings.endpoint.as_deref(),
+            Some("http://localhost:11434/api/embed")
+        );
+        assert_eq!(merged.embeddings.command, None);
+
+        let allowed = merge(ProjectConfig::default(), project(), true);
+        assert!(!allowed.untrusted_embeddings);
+        assert_eq!(allowed.embeddings.command.as_deref(), Some("exfiltrate"));
+    }
+
+    #[test]
+    fn test_project_summarizer_needs_allowing() {
+        let project = || {
+            ProjectConfig::parse("[summarizer]\ncommand = \"theirs\"\nmax_chars = 500\n").unwrap()
+        };
+        let user = || ProjectConfig::parse("[summarizer]\ncommand = \"mine\"\n").unwrap();
+
+        let held_back = merge(ProjectConfig::default(), project(), false);
+        assert!(held_back.untrusted_summarizer);
+        assert_eq!(held_back.summarizer.command, None);
+        assert_eq!(held_back.summarizer.max_chars, Some(500));
+        assert_eq!(
+            merge(user(), project(), false)
+                .summarizer
+                .command
+                .as_deref(),
+            Some("mine")
+        );
+        assert_eq!(
+            merge(user(), project(), true).summarizer.command.as_deref(),
+            Some("theirs")
+        );
+    }
+
+    #[test]
+    fn test_defaults_precedence() {
+        let project = ProjectConfig::parse("[defaults]\nformat = \"ai\"\n").unwrap();
+        let user = ProjectConfig::parse(
+            "[defaults]\nformat = \"human\"\nextensions = [\"py\", \"rs\"]\nno_cache = true\n",
+        )
+        .unwrap();
+
+        let merged = project.defaults.or(user.defaults);
+        assert_eq!(merged.format.as_deref(), Some("ai"));
+        assert_eq!(merged.extensions, Some(vec!["py".to_string(), "rs".to_string()]));
+        assert_eq!(merged.no_cache, Some(true));
+        assert_eq!(merged.cache_dir, None);
+    }
+
+    #[test]
+    fn test_profiles_and_command_formats() {
+        let config = ProjectConfig::parse(
+            r#"
+[defaults]
+format = "default"
+extensions = ["py"]
+
+[defaults.formats]
+stats = "human"
+map = "human"
+
+[profile.agent]
+format = "ai"
+exports_only = true
+show_body = false
+
+[profile.agent.formats]
+map = "compact"
+"#,
+        )
+        .unwrap();
+
+        let plain = config.resolve_defaults(None, Some("stats")).unwrap();
+        assert_eq!(plain.format.as_deref(), Some("human"));
+        assert_eq!(plain.exports_only, None);
+
+        let agent = config
+            .resolve_defaults(Some("agent"), Some("stats"))
+            .unwrap();
+        assert_eq!(agent.format.as_deref(), Some("ai"));
+        assert_eq!(agent.exports_only, Some(true));
+        assert_eq!(agent.extensions, Some(vec!["py".to_string()]));
+        let agent_map = config.resolve_defaults(Some("agent"), Some("map")).unwrap();
+        assert_eq!(agent_map.format.as_deref(), Some("compact"));
+
+        let err = config.resolve_defaults(Some("ci"), None).unwrap_err();
+        assert!(err.to_string().contains("defined: agent"));
+    }
+}
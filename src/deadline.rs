@@ -0,0 +1,79 @@
+//! `--timeout`: a time budget for the whole command. Long loops (indexing, fast-mode
+//! validation, repo-wide call scans) poll `expired()` and skip the rest of their work once it
+//! runs out, so the command prints what it has instead of blocking.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+static DEADLINE: OnceLock<(Instant, Duration)> = OnceLock::new();
+static TRUNCATED: AtomicBool = AtomicBool::new(false);
+
+/// Start the budget now; only the first call counts
+pub fn start(budget: Duration) {
+    let _ = DEADLINE.set((Instant::now() + budget, budget));
+}
+
+/// The budget given to `start`, if any
+pub fn budget() -> Option<Duration> {
+    DEADLINE.get().map(|&(_, budget)| budget)
+}
+
+/// Whether the budget has run out. A `true` answer marks the run as truncated, so ask right
+/// before skipping work, not to report on it
+pub fn expired() -> bool {
+    let expired = DEADLINE
+        .get()
+        .is_some_and(|&(deadline, _)| Instant::now() >= deadline);
+    if expired {
+        TRUNCATED.store(true, Ordering::Relaxed);
+    }
+    expired
+}
+
+/// Whether any work was skipped because the budget ran out
+pub fn truncated() -> bool {
+    TRUNCATED.load(Ordering::Relaxed)
+}
+
+/// Parse a budget like `5s`, `500ms`, `2m`, or a bare number of seconds
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let invalid = || {
+        format!(
+            "expected a duration like '5s', '500ms' or '2m', got '{}'",
+            value
+        )
+    };
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        _ => return Err(invalid()),
+    };
+    if seconds <= 0.0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1.5"), Ok(Duration::from_millis(1500)));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("5h").is_err());
+        assert!(parse_duration("soon").is_err());
+    }
+}
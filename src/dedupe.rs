@@ -0,0 +1,215 @@
+//! Collapsing repeated query results. A symbol found more than once under the same name and
+//! kind is shown once when the extra copies add nothing: TypeScript declarations (`.d.ts`),
+//! JavaScript compiled next to its TypeScript source, or byte-for-byte copies of a top-level
+//! definition (a module copied into a second package). The dropped locations are kept as
+//! alternates so the output can still point at them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::models::{Symbol, SymbolType};
+use crate::shadows::normalized_body;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateReason {
+    /// Ambient declaration in a `.d.ts` file
+    Declaration,
+    /// `.js` beside the `.ts`/`.tsx` it was compiled from
+    Compiled,
+    /// Same body as the kept definition
+    Copy,
+}
+
+impl DuplicateReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateReason::Declaration => "declaration",
+            DuplicateReason::Compiled => "compiled",
+            DuplicateReason::Copy => "copy",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alternate {
+    pub file_path: Arc<Path>,
+    pub line_start: usize,
+    pub reason: DuplicateReason,
+}
+
+/// Alternates of each kept symbol, keyed by the kept symbol's file and first line
+pub type Alternates = HashMap<(Arc<Path>, usize), Vec<Alternate>>;
+
+fn is_declaration_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map_or(Default::default(), |n| n.to_string_lossy());
+    [".d.ts", ".d.mts", ".d.cts"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+fn is_compiled_js(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("js" | "mjs" | "cjs")
+    ) && ["ts", "tsx", "mts", "cts"]
+        .iter()
+        .any(|ext| path.with_extension(ext).is_file())
+}
+
+/// Why `symbol` would be dropped in favour of another definition, if it is a secondary copy
+fn secondary(symbol: &Symbol) -> Option<DuplicateReason> {
+    if is_declaration_file(&symbol.file_path) {
+        Some(DuplicateReason::Declaration)
+    } else if is_compiled_js(&symbol.file_path) {
+        Some(DuplicateReason::Compiled)
+    } else {
+        None
+    }
+}
+
+/// `src/config` for `src/config.ts`, `src/config.d.ts` and `src/config.js` alike
+fn source_stem(path: &Path) -> PathBuf {
+    let mut stem = path.with_extension("");
+    if is_declaration_file(path) {
+        stem.set_extension("");
+    }
+    stem
+}
+
+/// `symbols` in their original order with duplicates removed, and the removed locations.
+/// Methods and fields are only collapsed out of declaration or compiled files: an identical
+/// `new` or `id` in two types is two symbols.
+pub fn dedupe(symbols: Vec<&Symbol>) -> (Vec<&Symbol>, Alternates) {
+    let mut groups: HashMap<(&str, SymbolType), Vec<usize>> = HashMap::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        groups
+            .entry((symbol.name.as_str(), symbol.symbol_type))
+            .or_default()
+            .push(i);
+    }
+
+    let mut dropped = vec![false; symbols.len()];
+    let mut alternates = Alternates::new();
+    let mut contents: HashMap<Arc<Path>, String> = HashMap::new();
+    let mut body = |symbol: &Symbol| -> String {
+        let content = contents
+            .entry(symbol.file_path.clone())
+            .or_insert_with(|| fs::read_to_string(&symbol.file_path).unwrap_or_default());
+        normalized_body(content, symbol)
+    };
+
+    for members in groups.values().filter(|members| members.len() > 1) {
+        let primaries: Vec<usize> = members
+            .iter()
+            .copied()
+            .filter(|&i| secondary(symbols[i]).is_none())
+            .collect();
+        let first = primaries.first().copied().unwrap_or(members[0]);
+        let top_level = !matches!(
+            symbols[first].symbol_type,
+            SymbolType::Method | SymbolType::Field
+        );
+        let first_body = if top_level {
+            body(symbols[first])
+        } else {
+            String::new()
+        };
+
+        for &i in members.iter().filter(|&&i| i != first) {
+            let symbol = symbols[i];
+            let (kept, reason) = match secondary(symbol) {
+                // A declaration or compiled file belongs to the source file it was generated from
+                Some(reason) => {
+                    let stem = source_stem(&symbol.file_path);
+                    let source = primaries
+                        .iter()
+                        .copied()
+                        .find(|&p| source_stem(&symbols[p].file_path) == stem)
+                        .unwrap_or(first);
+                    (symbols[source], reason)
+                }
+                None if top_level && !first_body.is_empty() && body(symbol) == first_body => {
+                    (symbols[first], DuplicateReason::Copy)
+                }
+                None => continue,
+            };
+            dropped[i] = true;
+            alternates
+                .entry((kept.file_path.clone(), kept.line_start))
+                .or_default()
+                .push(Alternate {
+                    file_path: symbol.file_path.clone(),
+                    line_start: symbol.line_start,
+                    reason,
+                });
+        }
+    }
+
+    let kept = symbols
+        .into_iter()
+        .zip(dropped)
+        .filter(|(_, dropped)| !dropped)
+        .map(|(symbol, _)| symbol)
+        .collect();
+    (kept, alternates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_directory;
+
+    #[test]
+    fn test_dedupe_declarations_and_copies() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        };
+        write("src/config.ts", "export class Config {}\n");
+        write("src/config.d.ts", "export declare class Config {}\n");
+        write(
+            "src/config.js",
+            "class Config {}\nmodule.exports = { Config };\n",
+        );
+        write("src/util.py", "def slug(text):\n    return text.lower()\n");
+        write(
+            "legacy/util.py",
+            "def slug(text):\n    return text.lower()\n",
+        );
+        write("other.py", "def slug(text):\n    return text.strip()\n");
+
+        let index = index_directory(dir.path(), &["ts", "js", "py"]).unwrap();
+        let located = |symbols: &[&Symbol]| -> Vec<String> {
+            let mut found: Vec<String> = symbols
+                .iter()
+                .map(|s| {
+                    let path = s.file_path.strip_prefix(dir.path()).unwrap();
+                    path.display().to_string()
+                })
+                .collect();
+            found.sort();
+            found
+        };
+
+        let (kept, alternates) = dedupe(index.query_symbol("Config"));
+        assert_eq!(located(&kept), vec!["src/config.ts"]);
+        let reasons: Vec<DuplicateReason> =
+            alternates.values().flatten().map(|a| a.reason).collect();
+        assert_eq!(reasons.len(), 2);
+        assert!(reasons.contains(&DuplicateReason::Declaration));
+        assert!(reasons.contains(&DuplicateReason::Compiled));
+
+        let (kept, alternates) = dedupe(index.query_symbol("slug"));
+        assert_eq!(kept.len(), 2);
+        assert!(located(&kept).contains(&"other.py".to_string()));
+        let copies: Vec<DuplicateReason> =
+            alternates.values().flatten().map(|a| a.reason).collect();
+        assert_eq!(copies, vec![DuplicateReason::Copy]);
+    }
+}
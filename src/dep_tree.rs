@@ -0,0 +1,507 @@
+//! Transitive file dependencies for `cm deps <file> --depth N`. Each import that resolves to
+//! an indexed file is expanded in turn; an import back into the current path is marked as a
+//! cycle and a file already expanded elsewhere in the tree is not expanded again.
+//!
+//! Resolution is by convention, not by build configuration: relative JS/TS specifiers,
+//! Python modules, Rust `crate`/`self`/`super`/own-crate paths, Java and Kotlin classes, PHP
+//! namespaces and quoted C includes. Go packages and everything external stay unresolved
+//! leaves.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::index::CodeIndex;
+use crate::models::{Dependency, Language};
+use crate::paths;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DepNode {
+    /// The import as written (`crate::models::Symbol`, `./utils`, `os.path`)
+    pub name: String,
+    /// Indexed file the import resolves to; `None` for external packages
+    #[serde(serialize_with = "paths::serialize_slash")]
+    pub file: Option<PathBuf>,
+    /// Direct imports of `file`, whether or not they are expanded below
+    pub child_count: usize,
+    /// `file` is already on the path from the root
+    pub cycle: bool,
+    /// `file` was expanded earlier in the tree
+    pub repeated: bool,
+    #[serde(rename = "dependencies")]
+    pub children: Vec<DepNode>,
+}
+
+/// Imports of `root` expanded `depth` levels deep (0 = until every branch ends)
+pub fn dependency_tree(index: &CodeIndex, root: &Path, depth: usize) -> Vec<DepNode> {
+    let resolver = Resolver::new(index);
+    let depth = if depth == 0 { usize::MAX } else { depth };
+    let mut stack = vec![root.to_path_buf()];
+    let mut expanded = HashSet::from([root.to_path_buf()]);
+    resolver.children(root, depth, &mut stack, &mut expanded)
+}
+
+/// Every import of an indexed file resolving to another indexed file, as (importing file,
+/// import, imported file), in index order
+pub fn resolved_imports(index: &CodeIndex) -> Vec<(&Path, &Dependency, PathBuf)> {
+    let resolver = Resolver::new(index);
+    let mut imports = Vec::new();
+    for file in index.files() {
+        for dep in &file.dependencies {
+            // `from core import parse` is stored as `parse` from module `core`
+            let import = match (&dep.from_file, file.language) {
+                (Some(module), Language::Python) => format!("{}.{}", module, dep.import_name),
+                _ => dep.import_name.clone(),
+            };
+            if let Some(target) = resolver.resolve(&file.path, &import) {
+                if target != file.path {
+                    imports.push((file.path.as_path(), dep, target));
+                }
+            }
+        }
+    }
+    imports
+}
+
+/// Every (importing file, imported file) pair between two different indexed files
+fn import_edges(index: &CodeIndex) -> HashSet<(&Path, PathBuf)> {
+    resolved_imports(index)
+        .into_iter()
+        .map(|(from, _, target)| (from, target))
+        .collect()
+}
+
+/// Number of other indexed files importing each file, keyed as the index stores paths
+pub fn importers(index: &CodeIndex) -> HashMap<PathBuf, usize> {
+    let edges = import_edges(index);
+    let mut counts = HashMap::new();
+    for (_, target) in edges {
+        *counts.entry(target).or_default() += 1;
+    }
+    counts
+}
+
+/// `target` as the index stores it, whether given as `src/a.rs`, `./src/a.rs` or absolute
+pub fn find_file(index: &CodeIndex, target: &Path) -> Option<PathBuf> {
+    let wanted = normalize(target);
+    if let Some(file) = index.files().find(|f| normalize(&f.path) == wanted) {
+        return Some(file.path.clone());
+    }
+    let canonical = paths::canonicalize(target).ok()?;
+    index
+        .files()
+        .find(|f| paths::canonicalize(&f.path).is_ok_and(|c| c == canonical))
+        .map(|f| f.path.clone())
+}
+
+/// Indexed files importing `target` (as `find_file` returns it), sorted
+pub fn importing_files(index: &CodeIndex, target: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = import_edges(index)
+        .into_iter()
+        .filter(|(_, imported)| imported == target)
+        .map(|(from, _)| from.to_path_buf())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Lexically resolve `.` and `..` so `./src/a/../b.ts` and `src/b.ts` compare equal, and fold
+/// case where the file system ignores it
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    paths::fold_case(&out)
+}
+
+struct Resolver<'a> {
+    index: &'a CodeIndex,
+    /// Normalized path -> path as the index stores it
+    files: HashMap<PathBuf, &'a Path>,
+    languages: HashMap<&'a Path, Language>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(index: &'a CodeIndex) -> Self {
+        let mut files = HashMap::new();
+        let mut languages = HashMap::new();
+        for file in index.files() {
+            files.insert(normalize(&file.path), file.path.as_path());
+            languages.insert(file.path.as_path(), file.language);
+        }
+        Resolver {
+            index,
+            files,
+            languages,
+        }
+    }
+
+    fn child_count(&self, file: &Path) -> usize {
+        self.index
+            .get_dependencies(file)
+            .map_or(0, |deps| deps.len())
+    }
+
+    fn children(
+        &self,
+        file: &Path,
+        depth: usize,
+        stack: &mut Vec<PathBuf>,
+        expanded: &mut HashSet<PathBuf>,
+    ) -> Vec<DepNode> {
+        let Some(imports) = self.index.get_dependencies(file) else {
+            return Vec::new();
+        };
+        let mut nodes = Vec::new();
+        for import in imports {
+            let resolved = self.resolve(file, import).filter(|r| r != file);
+            let mut node = DepNode {
+                name: import.clone(),
+                child_count: resolved.as_deref().map_or(0, |r| self.child_count(r)),
+                file: resolved.clone(),
+                cycle: false,
+                repeated: false,
+                children: Vec::new(),
+            };
+            if let Some(target) = resolved {
+                if stack.contains(&target) {
+                    node.cycle = true;
+                } else if expanded.contains(&target) {
+                    node.repeated = node.child_count > 0;
+                } else if depth > 1 {
+                    expanded.insert(target.clone());
+                    stack.push(target.clone());
+                    node.children = self.children(&target, depth - 1, stack, expanded);
+                    stack.pop();
+                }
+            }
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    /// The first candidate that is an indexed file, as the index stores it
+    fn first_indexed(&self, candidates: impl IntoIterator<Item = PathBuf>) -> Option<PathBuf> {
+        candidates
+            .into_iter()
+            .find_map(|c| self.files.get(&normalize(&c)).map(|p| p.to_path_buf()))
+    }
+
+    /// The indexed file whose path ends with one of `suffixes`, earliest suffix first
+    fn ending_with(&self, suffixes: &[PathBuf]) -> Option<PathBuf> {
+        suffixes.iter().find_map(|suffix| {
+            let suffix = paths::fold_case(suffix);
+            let mut matches: Vec<&&Path> = self
+                .files
+                .iter()
+                .filter(|(normalized, _)| normalized.ends_with(&suffix))
+                .map(|(_, stored)| stored)
+                .collect();
+            matches.sort();
+            matches.first().map(|p| p.to_path_buf())
+        })
+    }
+
+    fn resolve(&self, from: &Path, import: &str) -> Option<PathBuf> {
+        let language = *self.languages.get(from)?;
+        let dir = from.parent().unwrap_or(Path::new(""));
+        match language {
+            Language::JavaScript | Language::TypeScript => {
+                if !import.starts_with('.') {
+                    return None;
+                }
+                let base = dir.join(import);
+                let base = base.to_string_lossy();
+                let extensions = ["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+                let mut candidates = vec![PathBuf::from(base.as_ref())];
+                candidates.extend(
+                    extensions
+                        .iter()
+                        .map(|e| PathBuf::from(format!("{base}.{e}"))),
+                );
+                candidates.extend(
+                    extensions
+                        .iter()
+                        .map(|e| PathBuf::from(format!("{base}/index.{e}"))),
+                );
+                self.first_indexed(candidates)
+            }
+            Language::Python => {
+                let dots = import.chars().take_while(|&c| c == '.').count();
+                let segments: Vec<&str> = import[dots..]
+                    .split('.')
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                // `a.b.c` may name a module, or a name inside module `a.b`
+                let prefixes = (0..=segments.len()).rev().map(|k| segments[..k].join("/"));
+                if dots == 0 {
+                    let suffixes: Vec<PathBuf> = prefixes
+                        .filter(|p| !p.is_empty())
+                        .flat_map(|p| [format!("{p}.py"), format!("{p}/__init__.py")])
+                        .map(PathBuf::from)
+                        .collect();
+                    return self.ending_with(&suffixes);
+                }
+                let mut base = dir.to_path_buf();
+                for _ in 1..dots {
+                    base.pop();
+                }
+                self.first_indexed(prefixes.flat_map(|p| {
+                    let module = base.join(p);
+                    [module.with_extension("py"), module.join("__init__.py")]
+                }))
+            }
+            Language::Rust => self.resolve_rust(from, import),
+            // Android projects mix the two, so a class may be in either language's file
+            Language::Java | Language::Kotlin => {
+                let path = import.trim_end_matches(".*").replace('.', "/");
+                let (own, other) = match language {
+                    Language::Kotlin => ("kt", "java"),
+                    _ => ("java", "kt"),
+                };
+                self.ending_with(&[
+                    PathBuf::from(format!("{path}.{own}")),
+                    PathBuf::from(format!("{path}.{other}")),
+                ])
+            }
+            // Mostly one top-level type per file, named after it; sbt projects mix in Java
+            Language::Scala => {
+                let path = import.replace('.', "/");
+                self.ending_with(&[
+                    PathBuf::from(format!("{path}.scala")),
+                    PathBuf::from(format!("{path}.java")),
+                ])
+            }
+            // `require("a.b")` loads `a/b.lua`, or `a/b/init.lua` for a package, from the path
+            Language::Lua => {
+                let path = import.replace('.', "/");
+                self.ending_with(&[
+                    PathBuf::from(format!("{path}.lua")),
+                    PathBuf::from(format!("{path}/init.lua")),
+                ])
+            }
+            // `@import("util.zig")` is a path from the importing file; `@import("std")` a package
+            Language::Zig if import.ends_with(".zig") => self.first_indexed([dir.join(import)]),
+            Language::C | Language::Cpp => self
+                .first_indexed([dir.join(import)])
+                .or_else(|| self.ending_with(&[PathBuf::from(import)])),
+            Language::Php if import.ends_with(".php") => self
+                .first_indexed([dir.join(import)])
+                .or_else(|| self.ending_with(&[PathBuf::from(import.trim_start_matches("./"))])),
+            Language::Php => {
+                // PSR-4: `App\Models\Order` lives in `App/Models/Order.php`, or in
+                // `Models/Order.php` under the directory the `App\` prefix maps to
+                let segments: Vec<&str> = import.split('\\').collect();
+                let suffixes: Vec<PathBuf> = (0..segments.len().min(2))
+                    .map(|skip| PathBuf::from(format!("{}.php", segments[skip..].join("/"))))
+                    .collect();
+                self.ending_with(&suffixes)
+            }
+            _ => None,
+        }
+    }
+
+    fn resolve_rust(&self, from: &Path, import: &str) -> Option<PathBuf> {
+        // `crate::models::{Language, Symbol}` and `super::*` name the module before the braces
+        let path = import.split("::{").next().unwrap_or(import);
+        let mut segments: Vec<&str> = path.split("::").filter(|s| *s != "*").collect();
+        let crate_root = from
+            .ancestors()
+            .find(|a| a.file_name().is_some_and(|n| n == "src"))?;
+        let file_name = from.file_name()?.to_str()?;
+        let mut base = if ["mod.rs", "lib.rs", "main.rs"].contains(&file_name) {
+            from.parent()?.to_path_buf()
+        } else {
+            from.with_extension("")
+        };
+
+        match segments.first().copied() {
+            Some("crate") => {
+                base = crate_root.to_path_buf();
+                segments.remove(0);
+            }
+            Some("self") => {
+                segments.remove(0);
+            }
+            Some("super") => {
+                while segments.first() == Some(&"super") {
+                    base.pop();
+                    segments.remove(0);
+                }
+            }
+            // `use codemapper::index` from the crate's own binary
+            Some(name) if crate_names(crate_root).contains(&name.replace('-', "_")) => {
+                base = crate_root.to_path_buf();
+                segments.remove(0);
+            }
+            _ => base = crate_root.to_path_buf(),
+        }
+
+        self.first_indexed((1..=segments.len()).rev().flat_map(|k| {
+            let module = base.join(segments[..k].join("/"));
+            [module.with_extension("rs"), module.join("mod.rs")]
+        }))
+    }
+}
+
+/// `[package]` and `[lib]` names from the Cargo.toml next to `src`, with `-` as `_`
+fn crate_names(src: &Path) -> Vec<String> {
+    let manifest = src
+        .parent()
+        .map(|p| p.join("Cargo.toml"))
+        .unwrap_or_default();
+    fs::read_to_string(manifest)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "name").then(|| value.trim().trim_matches('"').replace('-', "_"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_directory;
+
+    #[test]
+    fn test_dependency_tree_marks_cycles_and_repeats() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("lib")).unwrap();
+        fs::write(
+            src.join("app.ts"),
+            "import { a } from './lib/a';\nimport { b } from './lib/b';\nimport fs from 'fs';\n",
+        )
+        .unwrap();
+        fs::write(
+            src.join("lib/a.ts"),
+            "import { b } from './b';\nexport const a = 1;\n",
+        )
+        .unwrap();
+        fs::write(
+            src.join("lib/b.ts"),
+            "import { a } from './a';\nexport const b = 2;\n",
+        )
+        .unwrap();
+
+        let index = index_directory(dir.path(), &["ts"]).unwrap();
+        let root = index
+            .files()
+            .find(|f| f.path.ends_with("app.ts"))
+            .map(|f| f.path.clone())
+            .unwrap();
+        let tree = dependency_tree(&index, &root, 0);
+
+        let summary: Vec<(&str, usize, bool, bool, usize)> = tree
+            .iter()
+            .map(|n| {
+                (
+                    n.name.as_str(),
+                    n.child_count,
+                    n.cycle,
+                    n.repeated,
+                    n.children.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("./lib/a", 1, false, false, 1),
+                ("./lib/b", 1, false, true, 0),
+                ("fs", 0, false, false, 0)
+            ]
+        );
+        let b = &tree[0].children[0];
+        assert!(b.file.as_ref().unwrap().ends_with("lib/b.ts"));
+        assert!(b.children[0].cycle);
+
+        let shallow = dependency_tree(&index, &root, 1);
+        assert!(shallow.iter().all(|n| n.children.is_empty()));
+        assert_eq!(shallow[0].child_count, 1);
+
+        // `used-by`: the same file however it is spelled
+        let b_file = b.file.clone().unwrap();
+        let absolute = find_file(&index, &src.join("lib/../lib/b.ts")).unwrap();
+        assert_eq!(absolute, b_file);
+        let importing: Vec<String> = importing_files(&index, &b_file)
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(importing, vec!["app.ts", "a.ts"]);
+    }
+
+    #[test]
+    fn test_resolve_python_and_rust() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("pkg")).unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"my-tool\"\n",
+        )
+        .unwrap();
+        fs::write(
+            src.join("main.rs"),
+            "use my_tool::models::Symbol;\nuse crate::pkg::{x, y};\nuse anyhow::Result;\n",
+        )
+        .unwrap();
+        fs::write(src.join("models.rs"), "pub struct Symbol;\n").unwrap();
+        fs::write(src.join("pkg/mod.rs"), "use super::models::Symbol;\n").unwrap();
+        fs::write(
+            src.join("pkg/util.py"),
+            "from . import helpers\nimport pkg.helpers\n",
+        )
+        .unwrap();
+        fs::write(src.join("pkg/helpers.py"), "X = 1\n").unwrap();
+
+        let index = index_directory(dir.path(), &["rs", "py"]).unwrap();
+        let resolver = Resolver::new(&index);
+        let path = |name: &str| {
+            index
+                .files()
+                .find(|f| f.path.ends_with(name))
+                .map(|f| f.path.clone())
+                .unwrap()
+        };
+        let resolved = |from: &str, import: &str| {
+            resolver
+                .resolve(&path(from), import)
+                .map(|p| p.strip_prefix(&src).unwrap_or(&p).to_path_buf())
+        };
+
+        assert_eq!(
+            resolved("main.rs", "my_tool::models::Symbol"),
+            Some("models.rs".into())
+        );
+        assert_eq!(
+            resolved("main.rs", "crate::pkg::{x, y}"),
+            Some("pkg/mod.rs".into())
+        );
+        assert_eq!(resolved("main.rs", "anyhow::Result"), None);
+        assert_eq!(
+            resolved("pkg/mod.rs", "super::models::Symbol"),
+            Some("models.rs".into())
+        );
+        assert_eq!(
+            resolved("util.py", ".helpers"),
+            Some("pkg/helpers.py".into())
+        );
+        assert_eq!(
+            resolved("util.py", "pkg.helpers"),
+            Some("pkg/helpers.py".into())
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! `cm deprecated`: symbols marked deprecated the way their language does it, and the calls
+//! still reaching them. Recognized markers are Rust's `#[deprecated]`, Java's `@Deprecated`,
+//! `@deprecated` in JSDoc/Javadoc/LDoc comments, Go's `// Deprecated:` doc line, C/C++
+//! `[[deprecated]]`, Swift's `@available(*, deprecated)`, Scala's `@deprecated("...", "1.0")`,
+//! Python's `@deprecated` decorator, `.. deprecated::` docstrings and
+//! `warnings.warn(..., DeprecationWarning)` in a body, plus a `deprecated` tag in an `@cm:`
+//! marker in any language.
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::callgraph::{self, CallInfo};
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+use crate::paths::DisplaySlash;
+use crate::scan;
+
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// How it is marked, as written: `#[deprecated]`, `@Deprecated`, `Deprecated:`,
+    /// `DeprecationWarning`...
+    pub marker: &'static str,
+    /// The deprecation message, which usually names the replacement
+    pub note: Option<String>,
+    /// Calls still to be migrated, not counting the symbol's calls to itself
+    pub callers: Vec<CallInfo>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DeprecationReport {
+    /// In file and line order
+    pub deprecations: Vec<Deprecation>,
+}
+
+impl DeprecationReport {
+    pub fn remaining_calls(&self) -> usize {
+        self.deprecations.iter().map(|d| d.callers.len()).sum()
+    }
+
+    /// Deprecated symbols nothing calls any more, ready to delete
+    pub fn migrated(&self) -> usize {
+        self.deprecations
+            .iter()
+            .filter(|d| d.callers.is_empty())
+            .count()
+    }
+
+    /// Share of deprecated symbols with no callers left; 100% when none are deprecated
+    pub fn migrated_pct(&self) -> f64 {
+        if self.deprecations.is_empty() {
+            100.0
+        } else {
+            self.migrated() as f64 * 100.0 / self.deprecations.len() as f64
+        }
+    }
+}
+
+/// Every deprecated symbol in `index` with its remaining callers
+pub fn find_deprecated(index: &CodeIndex) -> Result<DeprecationReport> {
+    let mut deprecations = Vec::new();
+
+    for file in index.files() {
+        let symbols = index.get_file_symbols(&file.path);
+        let Ok(content) = fs::read_to_string(&file.path) else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut seen = HashSet::new();
+        for &symbol in &symbols {
+            let Some((marker, note)) = deprecation_marker(symbol, &lines, file.language) else {
+                continue;
+            };
+            // A warning in `__init__` deprecates constructing the class
+            let target = match symbol.name.as_str() {
+                "__init__" => enclosing_class(&symbols, symbol).unwrap_or(symbol),
+                _ => symbol,
+            };
+            if !seen.insert((target.line_start, target.name.as_str())) {
+                continue;
+            }
+            deprecations.push((
+                target,
+                Deprecation {
+                    name: target.name.to_string(),
+                    symbol_type: target.symbol_type,
+                    file_path: file.path.clone(),
+                    line: target.line_start,
+                    marker,
+                    note,
+                    callers: Vec::new(),
+                },
+            ));
+        }
+    }
+
+    let names: HashSet<&str> = deprecations.iter().map(|(s, _)| s.name.as_str()).collect();
+    let callers = callgraph::callers_of_each(index, &names)?;
+
+    let mut deprecations: Vec<Deprecation> = deprecations
+        .into_iter()
+        .map(|(symbol, mut deprecation)| {
+            let path = symbol.file_path.display_slash().to_string();
+            deprecation.callers = callers
+                .get(symbol.name.as_str())
+                .into_iter()
+                .flatten()
+                .filter(|call| {
+                    !(call.file_path == path
+                        && (symbol.line_start..=symbol.line_end).contains(&call.line))
+                })
+                .cloned()
+                .collect();
+            deprecation
+        })
+        .collect();
+
+    deprecations.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+    Ok(DeprecationReport { deprecations })
+}
+
+fn enclosing_class<'a>(symbols: &[&'a Symbol], method: &Symbol) -> Option<&'a Symbol> {
+    symbols
+        .iter()
+        .copied()
+        .filter(|s| s.symbol_type == SymbolType::Class)
+        .filter(|s| s.line_start < method.line_start && s.line_end >= method.line_end)
+        .min_by_key(|s| s.line_end - s.line_start)
+}
+
+/// The deprecation marker on `symbol`, if any, with its message. `lines` are the file's.
+pub fn deprecation_marker(
+    symbol: &Symbol,
+    lines: &[&str],
+    language: Language,
+) -> Option<(&'static str, Option<String>)> {
+    if let Some(annotation) = symbol.annotations.iter().find(|a| a.tag == "deprecated") {
+        return Some(("@cm:deprecated", annotation.value.clone()));
+    }
+
+    if language == Language::Markdown {
+        return None;
+    }
+
+    let header = header_lines(symbol, lines, language);
+    // Attributes may spread their arguments over the lines that follow
+    let attribute = |i: usize| header[i..].join("\n");
+    for (i, line) in header.iter().enumerate() {
+        let line = line.trim();
+        if scan::is_comment(line, language) {
+            // Both are block tags, so they start the comment's text
+            let text = line
+                .trim_start_matches(['/', '*', '#', '!', '-'])
+                .trim_start();
+            if let Some(rest) = text.strip_prefix("@deprecated") {
+                return Some(("@deprecated", rest_of(rest)));
+            }
+            if let Some(rest) = text.strip_prefix("Deprecated:") {
+                return Some(("Deprecated:", rest_of(rest)));
+            }
+            continue;
+        }
+        if line.starts_with("#[deprecated") {
+            return Some(("#[deprecated]", message(&attribute(i))));
+        }
+        if matches!(language, Language::C | Language::Cpp)
+            && (line.starts_with("[[deprecated") || line.contains("__attribute__((deprecated"))
+        {
+            return Some(("[[deprecated]]", message(&attribute(i))));
+        }
+        if language == Language::CSharp && line.starts_with('[') && line.contains("Obsolete") {
+            return Some(("[Obsolete]", message(&attribute(i))));
+        }
+        // PHP 8.4's `#[\Deprecated(message: "...")]`
+        if language == Language::Php
+            && (line.starts_with("#[Deprecated") || line.starts_with("#[\\Deprecated"))
+        {
+            return Some(("#[Deprecated]", message(&attribute(i))));
+        }
+        // Kotlin's `@Deprecated("Use amount()", ReplaceWith(...))` requires a message
+        if language == Language::Kotlin && line.starts_with("@Deprecated") {
+            return Some(("@Deprecated", message(&attribute(i))));
+        }
+        // Swift's `@available(*, deprecated, message: "...")`, or `renamed:` for a plain rename
+        if language == Language::Swift
+            && line.starts_with("@available")
+            && attribute(i).contains("deprecated")
+        {
+            let text = attribute(i);
+            let note = match (text.find("message:"), text.find("renamed:")) {
+                (Some(at), _) => message(&text[at..]),
+                (None, Some(at)) => message(&text[at..]).map(|name| format!("Renamed to {}", name)),
+                (None, None) => None,
+            };
+            return Some(("@available(deprecated)", note));
+        }
+        // Scala's `@deprecated("Use charge", "2.0") def pay`, often on the declaration's line
+        if language == Language::Scala
+            && line
+                .strip_prefix("@deprecated")
+                .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric()))
+        {
+            return Some(("@deprecated", message(&attribute(i))));
+        }
+        if line.starts_with("@Deprecated") {
+            return Some(("@Deprecated", None));
+        }
+        if is_deprecated_decorator(line) {
+            return Some(("@deprecated", message(&attribute(i))));
+        }
+    }
+
+    if language == Language::Python {
+        let docstring = symbol.docstring.as_deref().unwrap_or_default();
+        if let Some(at) = docstring.find(".. deprecated::") {
+            let rest = &docstring[at + ".. deprecated::".len()..];
+            return Some((
+                ".. deprecated::",
+                rest_of(rest.lines().next().unwrap_or("")),
+            ));
+        }
+        if matches!(
+            symbol.symbol_type,
+            SymbolType::Function | SymbolType::Method
+        ) {
+            return deprecation_warning(symbol, lines);
+        }
+    }
+
+    None
+}
+
+/// The comments, decorators and attributes directly above `symbol`, then its lines up to and
+/// including the declaration itself
+fn header_lines<'a>(symbol: &Symbol, lines: &[&'a str], language: Language) -> Vec<&'a str> {
+    // Attribute and decorator arguments may run over several lines, so a line that starts no
+    // preamble still belongs to it while it closes or sits inside a bracket
+    let first = symbol.line_start.saturating_sub(1).min(lines.len());
+    let mut start = first;
+    let mut depth = 0;
+    while start > 0 {
+        let line = lines[start - 1];
+        let closes = brackets(line, ")]", "([");
+        if depth == 0 && closes <= 0 && !is_preamble(line, language) {
+            break;
+        }
+        depth = (depth + closes).max(0);
+        start -= 1;
+    }
+    let mut end = first;
+    depth = 0;
+    while end < lines.len() && (depth > 0 || is_preamble(lines[end], language)) {
+        depth = (depth + brackets(lines[end], "([", ")]")).max(0);
+        end += 1;
+    }
+    lines[start..(end + 1).min(lines.len())].to_vec()
+}
+
+/// How many more of `opening` than `closing` characters `line` holds
+fn brackets(line: &str, opening: &str, closing: &str) -> i32 {
+    line.chars().fold(0, |depth, c| {
+        if opening.contains(c) {
+            depth + 1
+        } else if closing.contains(c) {
+            depth - 1
+        } else {
+            depth
+        }
+    })
+}
+
+/// A comment, decorator or attribute line
+fn is_preamble(line: &str, language: Language) -> bool {
+    let line = line.trim();
+    scan::is_comment(line, language)
+        || (language == Language::CSharp && line.starts_with('['))
+        || line.starts_with('@')
+        || line.starts_with("#[")
+        || line.starts_with("[[")
+        || line.starts_with("__attribute__")
+}
+
+fn is_deprecated_decorator(line: &str) -> bool {
+    line.strip_prefix('@').is_some_and(|decorator| {
+        let name = decorator.split('(').next().unwrap_or_default().trim();
+        name.rsplit('.').next() == Some("deprecated")
+    })
+}
+
+/// `warnings.warn("...", DeprecationWarning)` anywhere in the body
+fn deprecation_warning(symbol: &Symbol, lines: &[&str]) -> Option<(&'static str, Option<String>)> {
+    let body = lines.get(symbol.line_start.saturating_sub(1)..symbol.line_end.min(lines.len()))?;
+    let at = body.iter().position(|line| {
+        let line = line.trim_start();
+        !line.starts_with('#') && line.contains("DeprecationWarning") && !line.contains("except")
+    })?;
+    // The message usually sits on the `warn(` line, which may be a few lines up; without a
+    // `warn(` the name is only mentioned (`simplefilter`, a docstring)
+    let call = (0..=at)
+        .rev()
+        .take(4)
+        .find(|&i| body[i].contains("warn("))?;
+    Some(("DeprecationWarning", message(&body[call..=at].join("\n"))))
+}
+
+/// Text after a comment marker, without the comment's closing
+fn rest_of(text: &str) -> Option<String> {
+    let text = text.split("*/").next().unwrap_or_default();
+    let text = text.trim().trim_start_matches(':').trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// The message of an attribute, decorator or call: the `note = "..."` argument if there is
+/// one, otherwise the first string literal
+fn message(text: &str) -> Option<String> {
+    let text = match text.find("note") {
+        Some(at) => &text[at..],
+        None => text,
+    };
+    let open = text.find(['"', '\''])?;
+    let quote = text[open..].chars().next()?;
+    let rest = &text[open + 1..];
+    let close = rest.find(quote)?;
+    let message = rest[..close].trim();
+    (!message.is_empty()).then(|| message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+    use crate::test_support::index_of;
+    use std::path::Path;
+
+    fn markers(path: &str, source: &str, language: Language) -> Vec<(String, String, String)> {
+        let file = index_file(Path::new(path), source, language, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        file.symbols
+            .iter()
+            .filter_map(|s| {
+                deprecation_marker(s, &lines, language).map(|(marker, note)| {
+                    (
+                        s.name.to_string(),
+                        marker.to_string(),
+                        note.unwrap_or_default(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_native_markers() {
+        let rust = "#[deprecated(\n    since = \"2.0\",\n    note = \"use charge\"\n)]\npub fn pay() {}\n\n/// Charges.\n#[inline]\npub fn charge() {}\n";
+        assert_eq!(
+            markers("a.rs", rust, Language::Rust),
+            [("pay".into(), "#[deprecated]".into(), "use charge".into())]
+        );
+
+        let python = "import warnings\n\n@deprecated(\"use charge\")\ndef pay():\n    pass\n\ndef old():\n    warnings.warn(\n        \"old() is going away\",\n        DeprecationWarning,\n    )\n\ndef charge():\n    try:\n        pass\n    except DeprecationWarning:\n        pass\n";
+        assert_eq!(
+            markers("a.py", python, Language::Python),
+            [
+                ("pay".into(), "@deprecated".into(), "use charge".into()),
+                (
+                    "old".into(),
+                    "DeprecationWarning".into(),
+                    "old() is going away".into()
+                ),
+            ]
+        );
+
+        let js = "/**\n * Pays.\n * @deprecated Use charge() instead.\n */\nfunction pay() {}\n\n/** Charges. */\nfunction charge() {}\n";
+        assert_eq!(
+            markers("a.js", js, Language::JavaScript),
+            [(
+                "pay".into(),
+                "@deprecated".into(),
+                "Use charge() instead.".into()
+            )]
+        );
+
+        let go = "// Pay pays.\n//\n// Deprecated: use Charge.\nfunc Pay() {}\n\n// Charge charges.\nfunc Charge() {}\n";
+        assert_eq!(
+            markers("a.go", go, Language::Go),
+            [("Pay".into(), "Deprecated:".into(), "use Charge.".into())]
+        );
+
+        let swift = "@available(*, deprecated, message: \"Use charge\")\nfunc pay() {}\n\n@available(iOS, deprecated: 13,\n    renamed: \"charge()\")\nfunc bill() {}\n\n@available(iOS 13, *)\nfunc charge() {}\n";
+        assert_eq!(
+            markers("a.swift", swift, Language::Swift),
+            [
+                (
+                    "pay".into(),
+                    "@available(deprecated)".into(),
+                    "Use charge".into()
+                ),
+                (
+                    "bill".into(),
+                    "@available(deprecated)".into(),
+                    "Renamed to charge()".into()
+                ),
+            ]
+        );
+
+        let scala = "@deprecated(\"Use charge\", \"2.0\")\ndef pay(): Unit = ()\n\n@deprecated def bill(): Unit = ()\n";
+        assert_eq!(
+            markers("a.scala", scala, Language::Scala),
+            [
+                ("pay".into(), "@deprecated".into(), "Use charge".into()),
+                ("bill".into(), "@deprecated".into(), String::new()),
+            ]
+        );
+
+        let lua = "--- Pays\n-- @deprecated use charge\nfunction pay() end\n";
+        assert_eq!(
+            markers("a.lua", lua, Language::Lua),
+            [("pay".into(), "@deprecated".into(), "use charge".into())]
+        );
+    }
+
+    #[test]
+    fn test_report_counts_callers_outside_the_symbol() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = "import warnings\n\nclass Client:\n    def __init__(self):\n        warnings.warn(\"use Session\", DeprecationWarning)\n\ndef old():\n    warnings.warn(\"use new\", DeprecationWarning)\n    old()\n\n# @cm:deprecated\ndef legacy():\n    pass\n\ndef main():\n    Client()\n    old()\n    old()\n";
+        let index = index_of(dir.path(), &[("a.py", source, Language::Python)]);
+
+        let report = find_deprecated(&index).unwrap();
+        let summary: Vec<(&str, &str, usize)> = report
+            .deprecations
+            .iter()
+            .map(|d| (d.name.as_str(), d.marker, d.callers.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Client", "DeprecationWarning", 1),
+                ("old", "DeprecationWarning", 2),
+                ("legacy", "@cm:deprecated", 0),
+            ]
+        );
+        assert_eq!(report.remaining_calls(), 3);
+        assert_eq!(report.migrated(), 1);
+    }
+}
@@ -1,8 +1,12 @@
+use crate::failure::{ErrorCode, Failure};
 use crate::git;
 use crate::indexer;
 use crate::models::{Language, Symbol, SymbolType};
+use crate::paths::{self, DisplaySlash};
+use crate::value_refs;
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -11,6 +15,8 @@ pub enum ChangeType {
     Deleted,
     Modified,
     SignatureChanged,
+    Moved,
+    Renamed,
 }
 
 impl ChangeType {
@@ -20,6 +26,8 @@ impl ChangeType {
             ChangeType::Deleted => "DELETED",
             ChangeType::Modified => "MODIFIED",
             ChangeType::SignatureChanged => "SIGNATURE_CHANGED",
+            ChangeType::Moved => "MOVED",
+            ChangeType::Renamed => "RENAMED",
         }
     }
 
@@ -29,6 +37,8 @@ impl ChangeType {
             ChangeType::Deleted => "-",
             ChangeType::Modified => "~",
             ChangeType::SignatureChanged => "!",
+            ChangeType::Moved => ">",
+            ChangeType::Renamed => "=",
         }
     }
 }
@@ -43,15 +53,36 @@ pub struct SymbolDiff {
     pub new_lines: Option<(usize, usize)>,
     pub old_signature: Option<String>,
     pub new_signature: Option<String>,
+    pub is_exported: bool,
+    /// Previous name, set for Renamed symbols
+    pub old_name: Option<String>,
+    /// Previous file, set when a Moved/Renamed symbol came from another file
+    pub old_file_path: Option<PathBuf>,
 }
 
 #[derive(Debug)]
 pub struct DiffResult {
+    /// The ref, path or lock the changes are relative to
     pub commit: String,
+    /// Whether the working tree, uncommitted edits included, was compared rather than HEAD
+    pub against_worktree: bool,
     pub symbols: Vec<SymbolDiff>,
     pub files_analyzed: usize,
 }
 
+/// A symbol whose lines changed between two refs; `cm changed-symbols`
+#[derive(Debug, Clone)]
+pub struct ChangedSymbol {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    /// Added, Deleted or Modified; signatures are not compared
+    pub change_type: ChangeType,
+    /// Relative to the repository root
+    pub file_path: PathBuf,
+    /// First line, on the new side unless the symbol was deleted
+    pub line: usize,
+}
+
 pub fn compute_diff(
     repo_path: &Path,
     commit: &str,
@@ -59,24 +90,641 @@ pub fn compute_diff(
     extensions: &[&str],
 ) -> Result<DiffResult> {
     if !git::is_git_repo(repo_path) {
-        anyhow::bail!("Not a git repository: {}", repo_path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("Not a git repository: {}", repo_path.display_slash())
+        ));
     }
 
     let resolved_commit = git::resolve_commit(repo_path, commit)?;
     let repo_root = git::get_repo_root(repo_path)?;
 
     let changed_files = git::get_changed_files(repo_path, &resolved_commit, subpath)?;
+    let filtered_files = filter_changed_files(changed_files, extensions);
 
-    let all_changed: Vec<PathBuf> = changed_files
-        .added
+    let (symbols, files_analyzed) = diff_files(&filtered_files, |file_path, language| {
+        get_symbols_at_commit(&repo_root, file_path, &resolved_commit, language)
+    })?;
+
+    Ok(DiffResult {
+        commit: resolved_commit,
+        against_worktree: false,
+        symbols,
+        files_analyzed,
+    })
+}
+
+/// Symbol diff of the working tree (including uncommitted edits) against a stash entry or any ref
+pub fn compute_worktree_diff(
+    repo_path: &Path,
+    target: &str,
+    subpath: Option<&Path>,
+    extensions: &[&str],
+) -> Result<DiffResult> {
+    if !git::is_git_repo(repo_path) {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("Not a git repository: {}", repo_path.display_slash())
+        ));
+    }
+
+    let resolved_commit = git::resolve_commit(repo_path, target)?;
+    let repo_root = git::get_repo_root(repo_path)?;
+
+    let changed_files = git::get_worktree_changed_files(repo_path, &resolved_commit, subpath)?;
+    let filtered_files = filter_changed_files(changed_files, extensions);
+
+    let (symbols, files_analyzed) = diff_files(&filtered_files, |file_path, language| {
+        get_symbols_at_commit(&repo_root, file_path, &resolved_commit, language)
+    })?;
+
+    Ok(DiffResult {
+        // As written, so `stash@{0}` is reported rather than its hash
+        commit: target.to_string(),
+        against_worktree: true,
+        symbols,
+        files_analyzed,
+    })
+}
+
+/// Symbol diff of `current_root` against another checkout of the same tree (e.g. a git worktree).
+/// Files are paired by their path relative to each root.
+pub fn compute_directory_diff(
+    current_root: &Path,
+    other_root: &Path,
+    subpath: Option<&Path>,
+    extensions: &[&str],
+) -> Result<DiffResult> {
+    if !other_root.is_dir() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::PathNotFound,
+            format!("Not a directory: {}", other_root.display_slash())
+        ));
+    }
+
+    let subpath = subpath.map(|sp| sp.strip_prefix(current_root).unwrap_or(sp));
+    let relative_files = |root: &Path| -> HashSet<PathBuf> {
+        let base = match subpath {
+            Some(sp) => root.join(sp),
+            None => root.to_path_buf(),
+        };
+        indexer::discover_files(&base, extensions)
+            .into_iter()
+            .filter_map(|f| f.strip_prefix(root).ok().map(Path::to_path_buf))
+            .collect()
+    };
+
+    let current_files = relative_files(current_root);
+    let other_files = relative_files(other_root);
+
+    let mut changed: Vec<PathBuf> = current_files
+        .union(&other_files)
+        .filter(|rel| files_differ(&current_root.join(rel), &other_root.join(rel)))
+        .map(|rel| current_root.join(rel))
+        .collect();
+    changed.sort();
+
+    let (symbols, files_analyzed) = diff_files(&changed, |file_path, language| {
+        let relative = file_path.strip_prefix(current_root).unwrap_or(file_path);
+        get_symbols_at_path(&other_root.join(relative), language)
+    })?;
+
+    Ok(DiffResult {
+        commit: other_root.display_slash().to_string(),
+        against_worktree: true,
+        symbols,
+        files_analyzed,
+    })
+}
+
+/// One definition of a symbol on both sides of `cm diff --symbol`, with the unified diff of
+/// its lines
+#[derive(Debug, Clone)]
+pub struct SymbolChange {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    /// Added, Deleted or Modified
+    pub change_type: ChangeType,
+    /// Relative to the repository root
+    pub old_file: Option<PathBuf>,
+    pub new_file: Option<PathBuf>,
+    pub old_lines: Option<(usize, usize)>,
+    pub new_lines: Option<(usize, usize)>,
+    pub hunks: Vec<Hunk>,
+}
+
+/// A unified diff hunk; line numbers are the file's, not the symbol's
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// `@@ -12,3 +12,4 @@`
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_count, self.new_start, self.new_count
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+impl HunkLine {
+    /// The line as unified diff prints it: ` `, `-` or `+`, then the text
+    pub fn to_diff_line(&self) -> String {
+        match self {
+            HunkLine::Context(text) => format!(" {}", text),
+            HunkLine::Removed(text) => format!("-{}", text),
+            HunkLine::Added(text) => format!("+{}", text),
+        }
+    }
+}
+
+/// Diffs past this many line pairs (after common leading and trailing lines) are shown as
+/// every old line removed and every new one added, rather than aligned
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// The definitions of `symbol` (`name`, or `Container::name` / `Container.name`) that changed
+/// between `from` and `to` (the working tree when `None`), each with a unified diff of its
+/// own lines and `context` lines of context inside the symbol. Only files git reports as
+/// changed are parsed, so an unchanged symbol yields nothing.
+pub fn symbol_changes(
+    repo_path: &Path,
+    symbol: &str,
+    from: &str,
+    to: Option<&str>,
+    subpath: Option<&Path>,
+    extensions: &[&str],
+    context: usize,
+) -> Result<Vec<SymbolChange>> {
+    if !git::is_git_repo(repo_path) {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("Not a git repository: {}", repo_path.display_slash())
+        ));
+    }
+
+    let from = git::resolve_commit(repo_path, from)?;
+    let to = to
+        .map(|to| git::resolve_commit(repo_path, to))
+        .transpose()?;
+    let repo_root = git::get_repo_root(repo_path)?;
+    let (container, name) = match symbol.rsplit_once("::").or_else(|| symbol.rsplit_once('.')) {
+        Some((container, name)) => (Some(container), name),
+        None => (None, symbol),
+    };
+
+    // Every matching definition per side, across files, so a symbol moved to another file
+    // still pairs up: (kind, file, lines, body)
+    type Found = (SymbolType, PathBuf, (usize, usize), Vec<String>);
+    let mut old_found: Vec<Found> = Vec::new();
+    let mut new_found: Vec<Found> = Vec::new();
+    let collect =
+        |found: &mut Vec<Found>, path: &Path, (symbols, content): (Vec<Symbol>, String)| {
+            let lines: Vec<&str> = content.lines().collect();
+            let keys = symbol_keys(&symbols);
+            for (symbol, (_, symbol_name, symbol_container)) in symbols.iter().zip(&keys) {
+                let in_container = |c: &str| symbol_container.is_some_and(|s| container_is(s, c));
+                if *symbol_name != name || container.is_some_and(|c| !in_container(c)) {
+                    continue;
+                }
+                let start = symbol.line_start.max(1);
+                let end = symbol.line_end.min(lines.len()).max(start);
+                let body = lines
+                    .get(start - 1..end)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect();
+                let relative = path.strip_prefix(&repo_root).unwrap_or(path);
+                found.push((
+                    symbol.symbol_type,
+                    relative.to_path_buf(),
+                    (start, end),
+                    body,
+                ));
+            }
+        };
+
+    for hunks in git::diff_hunks(repo_path, &from, to.as_deref(), subpath)? {
+        let Some(path) = hunks.new_path.as_ref().or(hunks.old_path.as_ref()) else {
+            continue;
+        };
+        if !extensions.is_empty() && !paths::has_extension(path, extensions) {
+            continue;
+        }
+        let language = indexer::detect_language(path);
+        if language == Language::Unknown {
+            continue;
+        }
+        if let Some(old_path) = &hunks.old_path {
+            let old = get_symbols_at_commit(&repo_root, old_path, &from, language)?;
+            collect(&mut old_found, old_path, old);
+        }
+        if let Some(new_path) = &hunks.new_path {
+            let new = match &to {
+                Some(to) => get_symbols_at_commit(&repo_root, new_path, to, language)?,
+                None => get_symbols_at_path(new_path, language)?,
+            };
+            collect(&mut new_found, new_path, new);
+        }
+    }
+
+    // Pair definitions of the same kind in order; the rest were added or deleted
+    let mut changes = Vec::new();
+    let mut new_left: Vec<Option<Found>> = new_found.into_iter().map(Some).collect();
+    for (kind, old_file, old_lines, old_body) in old_found {
+        let paired = new_left
+            .iter_mut()
+            .find(|found| found.as_ref().is_some_and(|(k, ..)| *k == kind))
+            .and_then(Option::take);
+        let (change_type, new_file, new_lines, new_body) = match paired {
+            Some((_, file, lines, body)) => (ChangeType::Modified, Some(file), Some(lines), body),
+            None => (ChangeType::Deleted, None, None, Vec::new()),
+        };
+        let hunks = unified_hunks(
+            &old_body,
+            &new_body,
+            old_lines.0,
+            new_lines.map_or(0, |lines| lines.0),
+            context,
+        );
+        if hunks.is_empty() && old_file.as_path() == new_file.as_deref().unwrap_or(&old_file) {
+            continue;
+        }
+        changes.push(SymbolChange {
+            name: name.to_string(),
+            symbol_type: kind,
+            change_type,
+            old_file: Some(old_file),
+            new_file,
+            old_lines: Some(old_lines),
+            new_lines,
+            hunks,
+        });
+    }
+    for (kind, new_file, new_lines, new_body) in new_left.into_iter().flatten() {
+        changes.push(SymbolChange {
+            name: name.to_string(),
+            symbol_type: kind,
+            change_type: ChangeType::Added,
+            old_file: None,
+            new_file: Some(new_file),
+            old_lines: None,
+            new_lines: Some(new_lines),
+            hunks: unified_hunks(&[], &new_body, 0, new_lines.0, context),
+        });
+    }
+    Ok(changes)
+}
+
+/// Whether a container symbol is `name`; Rust `impl Auth` and `impl Display for Auth<T>`
+/// blocks count as `Auth`
+fn container_is(container: &str, name: &str) -> bool {
+    let implemented = container
+        .split_whitespace()
+        .last()
+        .and_then(|ty| ty.split('<').next())
+        .unwrap_or(container);
+    container == name || (container.starts_with("impl") && implemented == name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Shortest edit script by longest common subsequence, deletions before insertions
+fn edit_script(old: &[String], new: &[String]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
         .iter()
-        .chain(changed_files.deleted.iter())
-        .chain(changed_files.modified.iter())
-        .cloned()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut edits = vec![Edit::Equal; prefix];
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        edits.extend(std::iter::repeat_n(Edit::Delete, a.len()));
+        edits.extend(std::iter::repeat_n(Edit::Insert, b.len()));
+    } else {
+        // lcs[i * width + j]: longest common subsequence of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                edits.push(Edit::Equal);
+                i += 1;
+                j += 1;
+            } else if i < a.len()
+                && (j == b.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                edits.push(Edit::Delete);
+                i += 1;
+            } else {
+                edits.push(Edit::Insert);
+                j += 1;
+            }
+        }
+    }
+    edits.extend(std::iter::repeat_n(Edit::Equal, suffix));
+    edits
+}
+
+/// Unified diff hunks of `old` (starting at file line `old_first`) against `new`
+fn unified_hunks(
+    old: &[String],
+    new: &[String],
+    old_first: usize,
+    new_first: usize,
+    context: usize,
+) -> Vec<Hunk> {
+    let edits = edit_script(old, new);
+    let changed: Vec<usize> = (0..edits.len())
+        .filter(|&k| edits[k] != Edit::Equal)
         .collect();
+    // An edit is shown when it is a change or within `context` edits of one
+    let shown = |k: usize| {
+        let at = changed.partition_point(|&c| c + context < k);
+        changed.get(at).is_some_and(|&c| c <= k + context)
+    };
+
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut open = false;
+    for (k, edit) in edits.iter().enumerate() {
+        if shown(k) {
+            if !open {
+                hunks.push(Hunk {
+                    old_start: old_first + i,
+                    old_count: 0,
+                    new_start: new_first + j,
+                    new_count: 0,
+                    lines: Vec::new(),
+                });
+                open = true;
+            }
+            let hunk = hunks.last_mut().expect("hunk just opened");
+            match edit {
+                Edit::Equal => {
+                    hunk.old_count += 1;
+                    hunk.new_count += 1;
+                    hunk.lines.push(HunkLine::Context(old[i].clone()));
+                }
+                Edit::Delete => {
+                    hunk.old_count += 1;
+                    hunk.lines.push(HunkLine::Removed(old[i].clone()));
+                }
+                Edit::Insert => {
+                    hunk.new_count += 1;
+                    hunk.lines.push(HunkLine::Added(new[j].clone()));
+                }
+            }
+        } else {
+            open = false;
+        }
+        match edit {
+            Edit::Equal => {
+                i += 1;
+                j += 1;
+            }
+            Edit::Delete => i += 1,
+            Edit::Insert => j += 1,
+        }
+    }
+    // As in `diff -u`, an empty side starts at the line before the hunk
+    for hunk in &mut hunks {
+        if hunk.old_count == 0 {
+            hunk.old_start = hunk.old_start.saturating_sub(1);
+        }
+        if hunk.new_count == 0 {
+            hunk.new_start = hunk.new_start.saturating_sub(1);
+        }
+    }
+    hunks
+}
 
-    let filtered_files: Vec<PathBuf> = all_changed
+/// Whether two files' contents differ, or only one of them can be read. Files of different
+/// sizes are not opened, and equal-sized ones are read side by side only up to the first
+/// differing block.
+fn files_differ(a: &Path, b: &Path) -> bool {
+    let (Ok(a_meta), Ok(b_meta)) = (std::fs::metadata(a), std::fs::metadata(b)) else {
+        return true;
+    };
+    if a_meta.len() != b_meta.len() {
+        return true;
+    }
+    let (Ok(a_file), Ok(b_file)) = (std::fs::File::open(a), std::fs::File::open(b)) else {
+        return true;
+    };
+    let (mut a_reader, mut b_reader) = (BufReader::new(a_file), BufReader::new(b_file));
+    loop {
+        let (Ok(a_block), Ok(b_block)) = (a_reader.fill_buf(), b_reader.fill_buf()) else {
+            return true;
+        };
+        if a_block.is_empty() || b_block.is_empty() {
+            return a_block.len() != b_block.len();
+        }
+        let n = a_block.len().min(b_block.len());
+        if a_block[..n] != b_block[..n] {
+            return true;
+        }
+        a_reader.consume(n);
+        b_reader.consume(n);
+    }
+}
+
+/// Symbols with lines in the hunks `git diff` reports between `from` and `to`, or between
+/// `from` and the working tree when `to` is None. Only the changed files are parsed, and each
+/// changed line is charged to the innermost named symbol containing it, so an edit inside a
+/// method lists the method rather than its class.
+pub fn changed_symbols(
+    repo_path: &Path,
+    from: &str,
+    to: Option<&str>,
+    subpath: Option<&Path>,
+    extensions: &[&str],
+) -> Result<Vec<ChangedSymbol>> {
+    if !git::is_git_repo(repo_path) {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("Not a git repository: {}", repo_path.display_slash())
+        ));
+    }
+
+    let from = git::resolve_commit(repo_path, from)?;
+    let to = to
+        .map(|to| git::resolve_commit(repo_path, to))
+        .transpose()?;
+    let repo_root = git::get_repo_root(repo_path)?;
+
+    let mut changed = Vec::new();
+    for hunks in git::diff_hunks(repo_path, &from, to.as_deref(), subpath)? {
+        let Some(path) = hunks.new_path.as_ref().or(hunks.old_path.as_ref()) else {
+            continue;
+        };
+        if !extensions.is_empty() && !paths::has_extension(path, extensions) {
+            continue;
+        }
+        let language = indexer::detect_language(path);
+        if language == Language::Unknown {
+            continue;
+        }
+
+        let old = match &hunks.old_path {
+            Some(old_path) => get_symbols_at_commit(&repo_root, old_path, &from, language)?,
+            None => (Vec::new(), String::new()),
+        };
+        let new = match (&hunks.new_path, &to) {
+            (Some(new_path), Some(to)) => {
+                get_symbols_at_commit(&repo_root, new_path, to, language)?
+            }
+            (Some(new_path), None) => get_symbols_at_path(new_path, language)?,
+            (None, _) => (Vec::new(), String::new()),
+        };
+        let relative = path.strip_prefix(&repo_root).unwrap_or(path);
+        changed.extend(touched_symbols(relative, &old, &new, &hunks));
+    }
+
+    changed.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+    Ok(changed)
+}
+
+/// Innermost named symbol spanning `line`
+fn innermost(symbols: &[Symbol], line: usize) -> Option<&Symbol> {
+    symbols
+        .iter()
+        .filter(|s| s.name != "anonymous" && s.line_start <= line && line <= s.line_end)
+        .min_by_key(|s| s.line_end - s.line_start)
+}
+
+/// A symbol's identity across the two sides: its kind, name and the name of the symbol
+/// around it, so two structs' `name` fields stay apart
+type SymbolKey<'a> = (SymbolType, &'a str, Option<&'a str>);
+
+fn symbol_keys(symbols: &[Symbol]) -> Vec<SymbolKey<'_>> {
+    symbols
+        .iter()
+        .map(|symbol| {
+            let container = symbols
+                .iter()
+                .filter(|s| {
+                    !std::ptr::eq(*s, symbol)
+                        && s.line_start <= symbol.line_start
+                        && s.line_end >= symbol.line_end
+                        && (s.line_start, s.line_end) != (symbol.line_start, symbol.line_end)
+                })
+                .min_by_key(|s| s.line_end - s.line_start);
+            (
+                symbol.symbol_type,
+                symbol.name.as_str(),
+                container.map(|s| s.name.as_str()),
+            )
+        })
+        .collect()
+}
+
+/// Symbols of one file owning a changed line on either side, given each side's symbols and
+/// content. Blank lines don't count. A symbol on both sides is Modified; one only on the new
+/// or old side was Added or Deleted.
+fn touched_symbols(
+    file_path: &Path,
+    (old_symbols, old_content): &(Vec<Symbol>, String),
+    (new_symbols, new_content): &(Vec<Symbol>, String),
+    hunks: &git::FileHunks,
+) -> Vec<ChangedSymbol> {
+    // Indices into `symbols` of the symbols owning a changed line
+    let touched = |symbols: &[Symbol], content: &str, ranges: &[(usize, usize)]| {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut owners: Vec<usize> = Vec::new();
+        for &(start, count) in ranges {
+            for line in start..start + count {
+                if lines
+                    .get(line - 1)
+                    .is_none_or(|text| text.trim().is_empty())
+                {
+                    continue;
+                }
+                let Some(symbol) = innermost(symbols, line) else {
+                    continue;
+                };
+                let index = symbols.iter().position(|s| std::ptr::eq(s, symbol));
+                if let Some(index) = index.filter(|i| !owners.contains(i)) {
+                    owners.push(index);
+                }
+            }
+        }
+        owners
+    };
+
+    let old_keys = symbol_keys(old_symbols);
+    let new_keys = symbol_keys(new_symbols);
+    let mut found: Vec<(SymbolKey, ChangeType, usize)> = Vec::new();
+    for i in touched(new_symbols, new_content, &hunks.new_ranges) {
+        let key = new_keys[i];
+        let change_type = if old_keys.contains(&key) {
+            ChangeType::Modified
+        } else {
+            ChangeType::Added
+        };
+        found.push((key, change_type, new_symbols[i].line_start));
+    }
+    for i in touched(old_symbols, old_content, &hunks.old_ranges) {
+        let key = old_keys[i];
+        found.push(match new_keys.iter().position(|k| *k == key) {
+            Some(j) => (key, ChangeType::Modified, new_symbols[j].line_start),
+            None => (key, ChangeType::Deleted, old_symbols[i].line_start),
+        });
+    }
+
+    let mut seen = HashSet::new();
+    found
+        .into_iter()
+        .filter(|(key, _, _)| seen.insert(*key))
+        .map(
+            |((symbol_type, name, _), change_type, line)| ChangedSymbol {
+                name: name.to_string(),
+                symbol_type,
+                change_type,
+                file_path: file_path.to_path_buf(),
+                line,
+            },
+        )
+        .collect()
+}
+
+fn filter_changed_files(changed_files: git::ChangedFiles, extensions: &[&str]) -> Vec<PathBuf> {
+    changed_files
+        .added
         .into_iter()
+        .chain(changed_files.deleted)
+        .chain(changed_files.modified)
         .filter(|f| {
             if extensions.is_empty() {
                 true
@@ -87,29 +735,171 @@ pub fn compute_diff(
                     .unwrap_or(false)
             }
         })
-        .collect();
+        .collect()
+}
 
+/// Compare each file's old symbols (from `load_old`) with its current contents on disk.
+/// Returns the symbol changes (with moves/renames collapsed) and the number of files analyzed.
+fn diff_files<F>(files: &[PathBuf], load_old: F) -> Result<(Vec<SymbolDiff>, usize)>
+where
+    F: Fn(&Path, Language) -> Result<(Vec<Symbol>, String)>,
+{
     let mut symbol_diffs = Vec::new();
-    let files_analyzed = filtered_files.len();
+    let mut bodies: Vec<Option<Vec<String>>> = Vec::new();
 
-    for file_path in &filtered_files {
+    for file_path in files {
         let language = indexer::detect_language(file_path);
         if language == Language::Unknown {
             continue;
         }
 
-        let old_symbols = get_symbols_at_commit(&repo_root, file_path, &resolved_commit, language)?;
-        let new_symbols = get_current_symbols(file_path, language)?;
+        let (old_symbols, old_content) = load_old(file_path, language)?;
+        let (new_symbols, new_content) = get_symbols_at_path(file_path, language)?;
 
         let diffs = compare_symbols(&old_symbols, &new_symbols, file_path);
+        for diff in &diffs {
+            let body = match diff.change_type {
+                ChangeType::Added => new_symbols
+                    .iter()
+                    .find(|s| *s.name == *diff.name && s.symbol_type == diff.symbol_type)
+                    .map(|s| normalized_body(&new_content, s)),
+                ChangeType::Deleted => old_symbols
+                    .iter()
+                    .find(|s| *s.name == *diff.name && s.symbol_type == diff.symbol_type)
+                    .map(|s| normalized_body(&old_content, s)),
+                _ => None,
+            };
+            bodies.push(body);
+        }
         symbol_diffs.extend(diffs);
     }
 
-    Ok(DiffResult {
-        commit: resolved_commit,
-        symbols: symbol_diffs,
-        files_analyzed,
-    })
+    Ok((detect_moves(symbol_diffs, &bodies), files.len()))
+}
+
+/// Bodies shorter than this are too generic to be matched as moves
+const MIN_MOVE_BODY_LINES: usize = 3;
+
+/// Minimum body similarity (0.0-1.0) for a Deleted+Added pair to count as one symbol
+const MOVE_SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// Body lines with indentation and blank lines stripped and the symbol's own name masked,
+/// so the same body compares equal after a move or rename
+fn normalized_body(content: &str, symbol: &Symbol) -> Vec<String> {
+    content
+        .lines()
+        .skip(symbol.line_start.saturating_sub(1))
+        .take(symbol.line_end + 1 - symbol.line_start.max(1))
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| mask_word(line, &symbol.name))
+        .collect()
+}
+
+/// `line` with whole-word occurrences of `word` replaced by `$`, leaving longer identifiers that contain it
+fn mask_word(line: &str, word: &str) -> String {
+    let mut masked = String::with_capacity(line.len());
+    let mut last = 0;
+    for start in value_refs::word_matches(line, word) {
+        masked.push_str(&line[last..start]);
+        masked.push('$');
+        last = start + word.len();
+    }
+    masked.push_str(&line[last..]);
+    masked
+}
+
+/// Dice coefficient over the multiset of body lines
+fn body_similarity(a: &[String], b: &[String]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in a {
+        *counts.entry(line.as_str()).or_insert(0) += 1;
+    }
+    let mut common = 0;
+    for line in b {
+        if let Some(count) = counts.get_mut(line.as_str()) {
+            if *count > 0 {
+                *count -= 1;
+                common += 1;
+            }
+        }
+    }
+
+    (2 * common) as f64 / (a.len() + b.len()) as f64
+}
+
+/// Collapse Deleted+Added pairs whose bodies match into a single Moved or Renamed change.
+/// `bodies` is parallel to `diffs` and holds normalized bodies for Added/Deleted entries.
+fn detect_moves(diffs: Vec<SymbolDiff>, bodies: &[Option<Vec<String>>]) -> Vec<SymbolDiff> {
+    let candidate = |i: usize, change_type: ChangeType| {
+        diffs[i].change_type == change_type
+            && bodies[i]
+                .as_ref()
+                .is_some_and(|b| b.len() >= MIN_MOVE_BODY_LINES)
+    };
+
+    let mut paired_with: HashMap<usize, usize> = HashMap::new();
+    let mut consumed: Vec<bool> = vec![false; diffs.len()];
+
+    for deleted in (0..diffs.len()).filter(|&i| candidate(i, ChangeType::Deleted)) {
+        let old_body = bodies[deleted].as_ref().expect("candidate has a body");
+
+        let best = (0..diffs.len())
+            .filter(|&i| !consumed[i] && candidate(i, ChangeType::Added))
+            .filter(|&i| diffs[i].symbol_type == diffs[deleted].symbol_type)
+            .map(|i| {
+                let new_body = bodies[i].as_ref().expect("candidate has a body");
+                (i, body_similarity(old_body, new_body))
+            })
+            .filter(|(_, score)| *score >= MOVE_SIMILARITY_THRESHOLD)
+            // On equal similarity prefer a same-name match (a move), then the earliest candidate
+            .max_by(|a, b| {
+                let same_name = |i: usize| diffs[i].name == diffs[deleted].name;
+                a.1.total_cmp(&b.1)
+                    .then_with(|| same_name(a.0).cmp(&same_name(b.0)))
+                    .then_with(|| b.0.cmp(&a.0))
+            });
+
+        if let Some((added, _)) = best {
+            consumed[added] = true;
+            consumed[deleted] = true;
+            paired_with.insert(added, deleted);
+        }
+    }
+
+    let mut result = Vec::with_capacity(diffs.len());
+    for (i, diff) in diffs.iter().enumerate() {
+        if let Some(&deleted) = paired_with.get(&i) {
+            let old = &diffs[deleted];
+            let mut merged = diff.clone();
+            merged.change_type = if old.name != diff.name {
+                ChangeType::Renamed
+            } else {
+                ChangeType::Moved
+            };
+            merged.old_lines = old.old_lines;
+            merged.old_signature = old.old_signature.clone();
+            merged.is_exported = old.is_exported || diff.is_exported;
+            if old.name != diff.name {
+                merged.old_name = Some(old.name.clone());
+            }
+            if old.file_path != diff.file_path {
+                merged.old_file_path = Some(old.file_path.clone());
+            }
+            result.push(merged);
+        } else if !consumed[i] {
+            result.push(diff.clone());
+        }
+    }
+
+    result
 }
 
 fn get_symbols_at_commit(
@@ -117,29 +907,30 @@ fn get_symbols_at_commit(
     file_path: &Path,
     commit: &str,
     language: Language,
-) -> Result<Vec<Symbol>> {
+) -> Result<(Vec<Symbol>, String)> {
     let content = match git::get_file_at_commit(repo_root, file_path, commit)? {
         Some(c) => c,
-        None => return Ok(Vec::new()),
+        None => return Ok((Vec::new(), String::new())),
     };
 
     let file_info = indexer::index_file(file_path, &content, language, None)
         .context("Failed to parse file at commit")?;
 
-    Ok(file_info.symbols)
+    Ok((file_info.symbols, content))
 }
 
-fn get_current_symbols(file_path: &Path, language: Language) -> Result<Vec<Symbol>> {
+fn get_symbols_at_path(file_path: &Path, language: Language) -> Result<(Vec<Symbol>, String)> {
     if !file_path.exists() {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), String::new()));
     }
 
-    let content = std::fs::read_to_string(file_path).context("Failed to read current file")?;
+    let content = std::fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read file: {}", file_path.display_slash()))?;
 
     let file_info = indexer::index_file(file_path, &content, language, None)
         .context("Failed to parse current file")?;
 
-    Ok(file_info.symbols)
+    Ok((file_info.symbols, content))
 }
 
 fn compare_symbols(
@@ -165,18 +956,23 @@ fn compare_symbols(
         match old_map.get(&key) {
             None => {
                 diffs.push(SymbolDiff {
-                    name: new_sym.name.clone(),
+                    name: new_sym.name.to_string(),
                     symbol_type: new_sym.symbol_type,
                     change_type: ChangeType::Added,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     old_lines: None,
                     new_lines: Some((new_sym.line_start, new_sym.line_end)),
                     old_signature: None,
                     new_signature: new_sym.signature.clone(),
+                    is_exported: new_sym.is_exported,
+                    old_name: None,
+                    old_file_path: None,
                 });
             }
             Some(old_sym) => {
-                let sig_changed = old_sym.signature != new_sym.signature;
+                // Losing export visibility breaks callers even when the signature text is unchanged
+                let sig_changed = old_sym.signature != new_sym.signature
+                    || (old_sym.is_exported && !new_sym.is_exported);
                 let lines_changed = old_sym.line_start != new_sym.line_start
                     || old_sym.line_end != new_sym.line_end;
                 let size_changed = (old_sym.line_end - old_sym.line_start)
@@ -184,25 +980,31 @@ fn compare_symbols(
 
                 if sig_changed {
                     diffs.push(SymbolDiff {
-                        name: new_sym.name.clone(),
+                        name: new_sym.name.to_string(),
                         symbol_type: new_sym.symbol_type,
                         change_type: ChangeType::SignatureChanged,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         old_lines: Some((old_sym.line_start, old_sym.line_end)),
                         new_lines: Some((new_sym.line_start, new_sym.line_end)),
                         old_signature: old_sym.signature.clone(),
                         new_signature: new_sym.signature.clone(),
+                        is_exported: old_sym.is_exported || new_sym.is_exported,
+                        old_name: None,
+                        old_file_path: None,
                     });
                 } else if lines_changed || size_changed {
                     diffs.push(SymbolDiff {
-                        name: new_sym.name.clone(),
+                        name: new_sym.name.to_string(),
                         symbol_type: new_sym.symbol_type,
                         change_type: ChangeType::Modified,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         old_lines: Some((old_sym.line_start, old_sym.line_end)),
                         new_lines: Some((new_sym.line_start, new_sym.line_end)),
                         old_signature: old_sym.signature.clone(),
                         new_signature: new_sym.signature.clone(),
+                        is_exported: old_sym.is_exported || new_sym.is_exported,
+                        old_name: None,
+                        old_file_path: None,
                     });
                 }
             }
@@ -214,14 +1016,17 @@ fn compare_symbols(
 
         if !new_map.contains_key(&key) {
             diffs.push(SymbolDiff {
-                name: old_sym.name.clone(),
+                name: old_sym.name.to_string(),
                 symbol_type: old_sym.symbol_type,
                 change_type: ChangeType::Deleted,
-                file_path: file_path.to_path_buf(),
+                file_path: file_path.into(),
                 old_lines: Some((old_sym.line_start, old_sym.line_end)),
                 new_lines: None,
                 old_signature: old_sym.signature.clone(),
                 new_signature: None,
+                is_exported: old_sym.is_exported,
+                old_name: None,
+                old_file_path: None,
             });
         }
     }
@@ -240,4 +1045,247 @@ mod tests {
         assert_eq!(ChangeType::Modified.as_str(), "MODIFIED");
         assert_eq!(ChangeType::SignatureChanged.as_str(), "SIGNATURE_CHANGED");
     }
+
+    fn symbol_diff(name: &str, file: &str, change_type: ChangeType) -> SymbolDiff {
+        SymbolDiff {
+            name: name.to_string(),
+            symbol_type: SymbolType::Function,
+            change_type,
+            file_path: PathBuf::from(file),
+            old_lines: None,
+            new_lines: None,
+            old_signature: None,
+            new_signature: None,
+            is_exported: true,
+            old_name: None,
+            old_file_path: None,
+        }
+    }
+
+    fn body(name: &str) -> Option<Vec<String>> {
+        let content = format!(
+            "fn {}(x: i32) -> i32 {{\n    let y = x * 2;\n    y + 1\n}}\n",
+            name
+        );
+        let symbol = Symbol {
+            name: name.into(),
+            symbol_type: SymbolType::Function,
+            signature: None,
+            docstring: None,
+            line_start: 1,
+            line_end: 4,
+            parent_id: None,
+            file_path: Path::new("a.rs").into(),
+            is_exported: true,
+            byte_range: None,
+            annotations: Vec::new(),
+        };
+        Some(normalized_body(&content, &symbol))
+    }
+
+    #[test]
+    fn test_detect_moves() {
+        let diffs = vec![
+            symbol_diff("compute", "a.rs", ChangeType::Deleted),
+            symbol_diff("compute", "b.rs", ChangeType::Added),
+            symbol_diff("old_name", "a.rs", ChangeType::Deleted),
+            symbol_diff("new_name", "a.rs", ChangeType::Added),
+            symbol_diff("unrelated", "a.rs", ChangeType::Added),
+        ];
+        let bodies = vec![
+            body("compute"),
+            body("compute"),
+            body("old_name"),
+            body("new_name"),
+            Some(vec![
+                "something".to_string(),
+                "else".to_string(),
+                "entirely".to_string(),
+            ]),
+        ];
+
+        let result = detect_moves(diffs, &bodies);
+        assert_eq!(result.len(), 3);
+
+        assert_eq!(result[0].change_type, ChangeType::Moved);
+        assert_eq!(result[0].file_path, PathBuf::from("b.rs"));
+        assert_eq!(result[0].old_file_path, Some(PathBuf::from("a.rs")));
+
+        assert_eq!(result[1].change_type, ChangeType::Renamed);
+        assert_eq!(result[1].name, "new_name");
+        assert_eq!(result[1].old_name.as_deref(), Some("old_name"));
+        assert_eq!(result[1].old_file_path, None);
+
+        assert_eq!(result[2].change_type, ChangeType::Added);
+    }
+
+    #[test]
+    fn test_losing_export_is_a_signature_change() {
+        let path = Path::new("lib.rs");
+        let parse = |source: &str| {
+            indexer::index_file(path, source, Language::Rust, None)
+                .unwrap()
+                .symbols
+        };
+        let old = parse("pub fn connect(url: &str) {}\n");
+        let new = parse("fn connect(url: &str) {}\n");
+
+        let diffs = compare_symbols(&old, &new, path);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].name, "connect");
+        assert_eq!(diffs[0].change_type, ChangeType::SignatureChanged);
+        // Still public API as far as `cm breaking` is concerned
+        assert!(diffs[0].is_exported);
+        assert!(compare_symbols(&old, &old, path).is_empty());
+    }
+
+    #[test]
+    fn test_mask_word_only_replaces_whole_identifiers() {
+        assert_eq!(
+            mask_word("let id = get_id(valid_id, \"id\") + id;", "id"),
+            "let $ = get_id(valid_id, \"$\") + $;"
+        );
+        assert_eq!(mask_word("a.b = alpha(a)", "a"), "$.b = alpha($)");
+        assert_eq!(mask_word("nothing here", "get"), "nothing here");
+    }
+
+    #[test]
+    fn test_touched_symbols() {
+        let old = "struct Cart;\n\nimpl Cart {\n    fn add(&self) {\n        one();\n    }\n\n    fn clear(&self) {}\n}\n\nfn gone() {}\n";
+        let new = "struct Cart;\n\nimpl Cart {\n    fn add(&self) {\n        two();\n    }\n\n    fn clear(&self) {}\n\n    fn total(&self) {}\n}\n";
+        let parse = |source: &str| {
+            let file = indexer::index_file(Path::new("cart.rs"), source, Language::Rust, None);
+            (file.unwrap().symbols, source.to_string())
+        };
+        let hunks = git::FileHunks {
+            old_path: Some(PathBuf::from("cart.rs")),
+            new_path: Some(PathBuf::from("cart.rs")),
+            old_ranges: vec![(5, 1), (10, 0), (11, 1)],
+            new_ranges: vec![(5, 1), (9, 2), (12, 0)],
+        };
+
+        let changed = touched_symbols(Path::new("cart.rs"), &parse(old), &parse(new), &hunks);
+        let found: Vec<(&str, ChangeType, usize)> = changed
+            .iter()
+            .map(|c| (c.name.as_str(), c.change_type, c.line))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("add", ChangeType::Modified, 4),
+                ("total", ChangeType::Added, 10),
+                ("gone", ChangeType::Deleted, 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unified_hunks() {
+        let lines = |text: &str| text.lines().map(String::from).collect::<Vec<_>>();
+        let old =
+            lines("fn total(x: i32) -> i32 {\n    let y = x * 2;\n    let z = y;\n    z + 1\n}");
+        let new =
+            lines("fn total(x: i32) -> i32 {\n    let y = x * 3;\n    let z = y;\n    z + 1\n}");
+
+        // Lines 11-15 of the file, one line of context
+        let hunks = unified_hunks(&old, &new, 11, 11, 1);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].header(), "@@ -11,3 +11,3 @@");
+        let diff: Vec<String> = hunks[0].lines.iter().map(HunkLine::to_diff_line).collect();
+        assert_eq!(
+            diff,
+            [
+                " fn total(x: i32) -> i32 {",
+                "-    let y = x * 2;",
+                "+    let y = x * 3;",
+                "     let z = y;"
+            ]
+        );
+
+        assert!(unified_hunks(&old, &old, 11, 11, 3).is_empty());
+        let added = unified_hunks(&[], &new, 0, 4, 3);
+        assert_eq!(added[0].header(), "@@ -0,0 +4,5 @@");
+    }
+
+    fn changes(result: &DiffResult) -> Vec<(&str, ChangeType)> {
+        let mut changes: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.change_type))
+            .collect();
+        changes.sort_by_key(|(name, _)| *name);
+        changes
+    }
+
+    #[test]
+    fn test_directory_diff() {
+        let (current, other) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let write = |root: &Path, name: &str, content: &str| {
+            std::fs::write(root.join(name), content).unwrap()
+        };
+        write(current.path(), "same.rs", "fn same() {}\n");
+        write(other.path(), "same.rs", "fn same() {}\n");
+        write(current.path(), "lib.rs", "fn kept() {}\n\nfn added() {}\n");
+        write(other.path(), "lib.rs", "fn kept() {}\n\nfn removed() {}\n");
+        write(other.path(), "gone.rs", "fn gone() {}\n");
+
+        assert!(!files_differ(
+            &current.path().join("same.rs"),
+            &other.path().join("same.rs")
+        ));
+        assert!(files_differ(
+            &current.path().join("lib.rs"),
+            &other.path().join("lib.rs")
+        ));
+        assert!(files_differ(
+            &current.path().join("gone.rs"),
+            &other.path().join("gone.rs")
+        ));
+
+        let result = compute_directory_diff(current.path(), other.path(), None, &["rs"]).unwrap();
+        assert!(result.against_worktree);
+        assert_eq!(result.files_analyzed, 2);
+        assert_eq!(
+            changes(&result),
+            [
+                ("added", ChangeType::Added),
+                ("gone", ChangeType::Deleted),
+                ("removed", ChangeType::Deleted)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_worktree_diff_against_a_stash() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=cm", "-c", "user.email=cm@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q"]);
+        std::fs::write(root.join("lib.rs"), "fn base() {}\n").unwrap();
+        git(&["add", "lib.rs"]);
+        git(&["commit", "-qm", "base"]);
+        std::fs::write(root.join("lib.rs"), "fn base() {}\n\nfn stashed() {}\n").unwrap();
+        git(&["stash", "-q"]);
+        std::fs::write(root.join("lib.rs"), "fn base() {}\n\nfn current() {}\n").unwrap();
+
+        let result = compute_worktree_diff(root, "stash@{0}", None, &["rs"]).unwrap();
+        assert_eq!(result.commit, "stash@{0}");
+        assert!(result.against_worktree);
+        assert_eq!(
+            changes(&result),
+            [
+                ("current", ChangeType::Added),
+                ("stashed", ChangeType::Deleted)
+            ]
+        );
+    }
 }
@@ -0,0 +1,413 @@
+//! Symbol-aligned text chunks for embedding models, and a small on-disk vector store
+//! for `cm query --semantic`.
+
+use crate::config::EmbeddingsConfig;
+use crate::failure::{ErrorCode, Failure};
+use crate::index::CodeIndex;
+use crate::models::SymbolType;
+use crate::paths::DisplaySlash;
+use crate::process;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Vector store file, inside the cache base directory (`.codemapper/`)
+pub const EMBEDDINGS_FILE: &str = "embeddings.bin";
+pub const DEFAULT_MAX_CHUNK_CHARS: usize = 2000;
+const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// One embeddable unit: a symbol's name, signature, docstring, and body.
+/// Symbols longer than the chunk limit are split into several parts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chunk {
+    pub id: String,
+    pub file_path: PathBuf,
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub signature: Option<String>,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub text: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VectorStore {
+    pub model: Option<String>,
+    pub chunks: Vec<Chunk>,
+    pub vectors: Vec<Vec<f32>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SemanticMatch {
+    pub score: f32,
+    pub chunk: Chunk,
+}
+
+pub fn build_chunks(index: &CodeIndex, max_chars: usize) -> Vec<Chunk> {
+    let mut paths: Vec<&Path> = index.files().map(|f| f.path.as_path()).collect();
+    paths.sort();
+
+    let mut chunks = Vec::new();
+    for path in paths {
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+
+        for symbol in index.get_file_symbols(path) {
+            if symbol.name == "anonymous"
+                || matches!(
+                    symbol.symbol_type,
+                    SymbolType::CodeBlock | SymbolType::Field
+                )
+            {
+                continue;
+            }
+
+            let mut header = format!(
+                "{} {}{}\n",
+                symbol.symbol_type.as_str(),
+                symbol.name,
+                symbol.signature.as_deref().unwrap_or("")
+            );
+            if let Some(ref doc) = symbol.docstring {
+                header.push_str(doc.trim());
+                header.push('\n');
+            }
+
+            let start = symbol.line_start.saturating_sub(1).min(lines.len());
+            let end = symbol.line_end.min(lines.len()).max(start);
+            let body_parts =
+                split_lines(&lines[start..end], max_chars.saturating_sub(header.len()));
+
+            let part_count = body_parts.len();
+            for (part, body) in body_parts.into_iter().enumerate() {
+                let text = format!("{}{}", header, body);
+                let id = if part_count > 1 {
+                    format!(
+                        "{}:{}:{}#{}",
+                        path.display_slash(),
+                        symbol.line_start,
+                        symbol.name,
+                        part
+                    )
+                } else {
+                    format!(
+                        "{}:{}:{}",
+                        path.display_slash(),
+                        symbol.line_start,
+                        symbol.name
+                    )
+                };
+
+                chunks.push(Chunk {
+                    id,
+                    file_path: path.to_path_buf(),
+                    name: symbol.name.to_string(),
+                    symbol_type: symbol.symbol_type,
+                    signature: symbol.signature.clone(),
+                    line_start: symbol.line_start,
+                    line_end: symbol.line_end,
+                    hash: blake3::hash(text.as_bytes()).to_hex().to_string(),
+                    text,
+                });
+            }
+        }
+    }
+
+    chunks
+}
+
+/// Group whole lines into parts of at most `max_chars` (a single longer line is kept intact)
+fn split_lines(lines: &[&str], max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut parts = vec![String::new()];
+
+    for line in lines {
+        let current = parts.last_mut().unwrap();
+        if !current.is_empty() && current.len() + line.len() + 1 > max_chars {
+            parts.push(String::new());
+        }
+        let current = parts.last_mut().unwrap();
+        current.push_str(line);
+        current.push('\n');
+    }
+
+    parts
+}
+
+/// Calls the configured embedding backend: either a local HTTP endpoint
+/// (Ollama `/api/embed` or an OpenAI-compatible `/v1/embeddings`) or a command that
+/// reads a JSON array of strings on stdin and prints a JSON array of vectors.
+pub struct Embedder {
+    config: EmbeddingsConfig,
+}
+
+impl Embedder {
+    pub fn new(config: EmbeddingsConfig) -> Result<Self> {
+        if !config.has_backend() {
+            anyhow::bail!(Failure::new(
+                ErrorCode::NotConfigured,
+                "No embedding backend configured. Add an [embeddings] section with `endpoint` or `command` to .codemapper.toml"
+            ));
+        }
+        Ok(Self { config })
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.config.model.as_deref()
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.config.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1)
+    }
+
+    pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let vectors = match (&self.config.command, &self.config.endpoint) {
+            (Some(command), _) => self.embed_with_command(command, texts)?,
+            (None, Some(endpoint)) => self.embed_with_endpoint(endpoint, texts)?,
+            (None, None) => unreachable!("checked in Embedder::new"),
+        };
+
+        if vectors.len() != texts.len() {
+            anyhow::bail!(
+                "Embedding backend returned {} vectors for {} inputs",
+                vectors.len(),
+                texts.len()
+            );
+        }
+        Ok(vectors)
+    }
+
+    fn embed_with_command(&self, command: &str, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let child = Command::new(command)
+            .args(&self.config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to start embedding command '{}'", command))?;
+
+        let output = process::feed_and_wait(child, &serde_json::to_vec(texts)?)
+            .with_context(|| format!("Embedding command '{}' did not finish", command))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Embedding command '{}' failed: {}",
+                command,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        serde_json::from_slice(&output.stdout)
+            .with_context(|| format!("Invalid output from embedding command '{}'", command))
+    }
+
+    #[cfg(feature = "cli")]
+    fn embed_with_endpoint(&self, endpoint: &str, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let body = serde_json::json!({
+            "model": self.config.model.as_deref().unwrap_or_default(),
+            "input": texts,
+        });
+
+        let response = attohttpc::post(endpoint)
+            .header("Content-Type", "application/json")
+            .bytes(serde_json::to_vec(&body)?)
+            .send()
+            .with_context(|| format!("Failed to reach embedding endpoint {}", endpoint))?;
+
+        if !response.is_success() {
+            let status = response.status();
+            anyhow::bail!(
+                "Embedding endpoint {} returned {}: {}",
+                endpoint,
+                status,
+                response.text().unwrap_or_default().trim()
+            );
+        }
+
+        let value: serde_json::Value = serde_json::from_slice(&response.bytes()?)
+            .with_context(|| format!("Invalid JSON from embedding endpoint {}", endpoint))?;
+        parse_endpoint_response(value)
+    }
+
+    #[cfg(not(feature = "cli"))]
+    fn embed_with_endpoint(&self, endpoint: &str, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        anyhow::bail!(
+            "Embedding endpoint {} needs the cli feature (HTTP support)",
+            endpoint
+        )
+    }
+}
+
+/// Accepts both `{"embeddings": [[...]]}` (Ollama) and `{"data": [{"embedding": [...]}]}` (OpenAI)
+#[cfg_attr(not(feature = "cli"), allow(dead_code))]
+fn parse_endpoint_response(value: serde_json::Value) -> Result<Vec<Vec<f32>>> {
+    if let Some(embeddings) = value.get("embeddings") {
+        return Ok(serde_json::from_value(embeddings.clone())?);
+    }
+
+    if let Some(data) = value.get("data").and_then(|d| d.as_array()) {
+        return data
+            .iter()
+            .map(|item| {
+                let embedding = item
+                    .get("embedding")
+                    .context("Missing `embedding` in response item")?;
+                Ok(serde_json::from_value(embedding.clone())?)
+            })
+            .collect();
+    }
+
+    anyhow::bail!("Unrecognized embedding response (expected `embeddings` or `data`)")
+}
+
+impl VectorStore {
+    pub fn path(base_dir: &Path) -> PathBuf {
+        base_dir.join(EMBEDDINGS_FILE)
+    }
+
+    pub fn load(base_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path(base_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path)
+            .with_context(|| format!("Failed to read embeddings: {}", path.display_slash()))?;
+        let store = bincode::deserialize(&bytes)
+            .context("Embeddings file is corrupt, re-run `cm embed`")?;
+        Ok(Some(store))
+    }
+
+    pub fn save(&self, base_dir: &Path) -> Result<()> {
+        fs::create_dir_all(base_dir).context("Failed to create cache directory")?;
+        fs::write(Self::path(base_dir), bincode::serialize(self)?)
+            .context("Failed to write embeddings")?;
+        Ok(())
+    }
+
+    /// Vectors from this store keyed by chunk hash, if it was built with `model`
+    pub fn reusable_vectors(&self, model: Option<&str>) -> HashMap<&str, &Vec<f32>> {
+        if self.model.as_deref() != model {
+            return HashMap::new();
+        }
+        self.chunks
+            .iter()
+            .zip(&self.vectors)
+            .map(|(chunk, vector)| (chunk.hash.as_str(), vector))
+            .collect()
+    }
+
+    /// Best-scoring chunks by cosine similarity; split symbols are reported once
+    pub fn search(&self, query: &[f32], limit: usize) -> Vec<SemanticMatch> {
+        let mut scored: Vec<(f32, &Chunk)> = self
+            .chunks
+            .iter()
+            .zip(&self.vectors)
+            .map(|(chunk, vector)| (cosine_similarity(query, vector), chunk))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut seen = std::collections::HashSet::new();
+        scored
+            .into_iter()
+            .filter(|(_, chunk)| {
+                seen.insert((
+                    chunk.file_path.clone(),
+                    chunk.line_start,
+                    chunk.name.clone(),
+                ))
+            })
+            .take(limit)
+            .map(|(score, chunk)| SemanticMatch {
+                score,
+                chunk: chunk.clone(),
+            })
+            .collect()
+    }
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_lines() {
+        let lines = ["fn a() {", "    retry();", "}"];
+        assert_eq!(split_lines(&lines, 100).len(), 1);
+
+        let parts = split_lines(&lines, 12);
+        assert_eq!(parts, vec!["fn a() {\n", "    retry();\n", "}\n"]);
+    }
+
+    #[test]
+    fn test_search_ranks_by_similarity() {
+        let chunk = |name: &str| Chunk {
+            id: name.to_string(),
+            file_path: PathBuf::from("lib.rs"),
+            name: name.to_string(),
+            symbol_type: SymbolType::Function,
+            signature: None,
+            line_start: 1,
+            line_end: 1,
+            text: String::new(),
+            hash: name.to_string(),
+        };
+        let store = VectorStore {
+            model: None,
+            chunks: vec![chunk("parse"), chunk("retry")],
+            vectors: vec![vec![1.0, 0.0], vec![0.6, 0.8]],
+        };
+
+        let results = store.search(&[0.0, 1.0], 5);
+        assert_eq!(results[0].chunk.name, "retry");
+        assert!((results[0].score - 0.8).abs() < 1e-6);
+        assert_eq!(results.len(), 2);
+
+        let value = serde_json::json!({"data": [{"embedding": [0.5, 0.5]}]});
+        assert_eq!(
+            parse_endpoint_response(value).unwrap(),
+            vec![vec![0.5, 0.5]]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_embedding_command_gets_large_batches() {
+        // A full batch is over a pipe buffer; the command answers on stderr before reading
+        let embedder = Embedder::new(EmbeddingsConfig {
+            command: Some("sh".to_string()),
+            args: vec![
+                "-c".to_string(),
+                "head -c 200000 /dev/zero >&2; cat >/dev/null; \
+                 printf '['; for i in $(seq 31); do printf '[1],'; done; echo '[1]]'"
+                    .to_string(),
+            ],
+            ..EmbeddingsConfig::default()
+        })
+        .unwrap();
+
+        let texts = vec!["x".repeat(DEFAULT_MAX_CHUNK_CHARS); DEFAULT_BATCH_SIZE];
+        let vectors = embedder.embed(&texts).unwrap();
+        assert_eq!(vectors, vec![vec![1.0]; DEFAULT_BATCH_SIZE]);
+    }
+}
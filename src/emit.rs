@@ -0,0 +1,265 @@
+//! `cm map --emit`: the markdown map kept in a standing file. Each file's section sits under
+//! a stable `<a id>` anchor, so links into the map survive updates. With `--update` the
+//! previous emit is read back and a summary of the sections added, changed and removed
+//! since then is put on top; unchanged sections come out byte for byte as before, which
+//! keeps the file's diffs down to what moved in the code.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::failure::{ErrorCode, Failure};
+use crate::paths::DisplaySlash;
+
+/// First line of an emitted map, marking a file `--update` may rewrite
+const HEADER: &str = "<!-- Generated by `cm map --emit`; file sections are rewritten in place -->";
+/// Heading opening the per-file sections in the markdown map
+const FILES_HEADING: &str = "## Files";
+const SUMMARY_HEADING: &str = "## Changed Since Last Emit";
+
+/// A markdown map split at its file sections
+#[derive(Debug, Default, PartialEq)]
+struct MapDoc {
+    /// Everything before the file sections: overview, languages and statistics
+    overview: String,
+    /// (file path, section text from its `###` heading), in map order
+    sections: Vec<(String, String)>,
+}
+
+impl MapDoc {
+    /// Split a freshly rendered map (`### path` headings under `## Files`)
+    fn from_map(map: &str) -> Self {
+        let (overview, files) = match map.find(&format!("\n{}\n", FILES_HEADING)) {
+            Some(at) => (&map[..at + 1], &map[at + FILES_HEADING.len() + 2..]),
+            None => (map, ""),
+        };
+        let mut sections: Vec<(String, String)> = Vec::new();
+        for line in files.lines() {
+            if let Some(path) = line.strip_prefix("### ") {
+                sections.push((path.to_string(), String::new()));
+            }
+            if let Some((_, text)) = sections.last_mut() {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+        MapDoc {
+            overview: overview.trim_end().to_string(),
+            sections,
+        }
+    }
+
+    /// Read back a map written by `render`; sections start at their anchors, so a docstring
+    /// line starting with `###` cannot split one
+    fn from_emitted(text: &str) -> Self {
+        let text = match text.find(&format!("\n{}\n", FILES_HEADING)) {
+            Some(at) => &text[at + FILES_HEADING.len() + 2..],
+            None => "",
+        };
+        let mut sections: Vec<(String, String)> = Vec::new();
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            if line.starts_with("<a id=\"") {
+                if let Some(path) = lines.next().and_then(|l| l.strip_prefix("### ")) {
+                    sections.push((path.to_string(), format!("### {}\n", path)));
+                    continue;
+                }
+            }
+            if let Some((_, section)) = sections.last_mut() {
+                section.push_str(line);
+                section.push('\n');
+            }
+        }
+        MapDoc {
+            overview: String::new(),
+            sections,
+        }
+    }
+}
+
+/// What changed between two emits, by file path
+#[derive(Debug, Default, PartialEq)]
+pub struct EmitSummary {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+    /// No previous emit was compared: the first one, or one without `--update`
+    pub fresh: bool,
+}
+
+impl EmitSummary {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Anchor id of a file's section: its path, lowercased, with every run of other characters
+/// as a single `-`
+pub fn anchor(path: &str) -> String {
+    let mut id = String::from("file-");
+    for c in path.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+/// The emitted file for `map`, compared with the `previous` emit when updating
+pub fn render(map: &str, previous: Option<&str>) -> (String, EmitSummary) {
+    let doc = MapDoc::from_map(map);
+    let summary = match previous {
+        Some(previous) => compare(&MapDoc::from_emitted(previous), &doc),
+        None => EmitSummary {
+            fresh: true,
+            ..EmitSummary::default()
+        },
+    };
+
+    let mut out = String::new();
+    out.push_str(HEADER);
+    out.push_str("\n\n");
+    write_summary(&mut out, &summary, doc.sections.len());
+    out.push_str(&doc.overview);
+    out.push('\n');
+    if !doc.sections.is_empty() {
+        let _ = writeln!(out, "\n{}\n", FILES_HEADING);
+        for (path, section) in &doc.sections {
+            let _ = writeln!(out, "<a id=\"{}\"></a>", anchor(path));
+            out.push_str(section);
+        }
+    }
+    (out, summary)
+}
+
+fn compare(previous: &MapDoc, current: &MapDoc) -> EmitSummary {
+    let before: HashMap<&str, &str> = previous
+        .sections
+        .iter()
+        .map(|(path, text)| (path.as_str(), text.as_str()))
+        .collect();
+    let now: HashSet<&str> = current.sections.iter().map(|(p, _)| p.as_str()).collect();
+
+    let mut summary = EmitSummary::default();
+    for (path, text) in &current.sections {
+        match before.get(path.as_str()) {
+            None => summary.added.push(path.clone()),
+            Some(old) if old.trim_end() != text.trim_end() => summary.changed.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    summary.removed = previous
+        .sections
+        .iter()
+        .filter(|(path, _)| !now.contains(path.as_str()))
+        .map(|(path, _)| path.clone())
+        .collect();
+    summary
+}
+
+fn write_summary(out: &mut String, summary: &EmitSummary, files: usize) {
+    let _ = writeln!(out, "{}\n", SUMMARY_HEADING);
+    let link = |path: &String| format!("[{}](#{})", path, anchor(path));
+    if summary.fresh {
+        let _ = writeln!(
+            out,
+            "- Full emit: {} files, no previous map compared",
+            files
+        );
+    } else if summary.is_empty() {
+        let _ = writeln!(out, "- No changes");
+    }
+    for (label, paths, linked) in [
+        ("Added", &summary.added, true),
+        ("Changed", &summary.changed, true),
+        ("Removed", &summary.removed, false),
+    ] {
+        if paths.is_empty() {
+            continue;
+        }
+        let list: Vec<String> = paths
+            .iter()
+            .map(|path| {
+                if linked {
+                    link(path)
+                } else {
+                    format!("`{}`", path)
+                }
+            })
+            .collect();
+        let _ = writeln!(out, "- {} ({}): {}", label, paths.len(), list.join(", "));
+    }
+    out.push('\n');
+}
+
+/// Write `map` to `target`; with `update`, compare with what is there. A file not written
+/// by `--emit` is never overwritten.
+pub fn emit(target: &Path, map: &str, update: bool) -> Result<EmitSummary> {
+    let previous = match fs::read_to_string(target) {
+        Ok(text) if !text.starts_with(HEADER) => anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "{} was not written by `cm map --emit`; refusing to overwrite it",
+                target.display_slash()
+            )
+        )),
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", target.display_slash()))
+        }
+    };
+    let (text, summary) = render(map, previous.as_deref().filter(|_| update));
+    if let Some(dir) = target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display_slash()))?;
+    }
+    fs::write(target, text)
+        .with_context(|| format!("Failed to write {}", target.display_slash()))?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "# Project Overview\n\n## Statistics\n- Total files: 2\n\n## Files\n\n### src/a.rs\n- Language: rust\n- Symbols: 1\n\n### src/b.rs\n- Language: rust\n    \"### not a section\"\n\n";
+
+    #[test]
+    fn test_render_anchors_sections() {
+        let (text, summary) = render(MAP, None);
+        assert!(summary.fresh);
+        assert!(text.starts_with(HEADER));
+        assert!(text.contains("- Full emit: 2 files"));
+        assert!(text.contains("<a id=\"file-src-a-rs\"></a>\n### src/a.rs\n- Language: rust\n"));
+
+        let doc = MapDoc::from_emitted(&text);
+        assert_eq!(doc.sections, MapDoc::from_map(MAP).sections);
+        assert_eq!(anchor("src/My Module.py"), "file-src-my-module-py");
+    }
+
+    #[test]
+    fn test_update_reports_changed_sections() {
+        let (first, _) = render(MAP, None);
+        let next = MAP.replace("- Symbols: 1", "- Symbols: 2").replace(
+            "### src/b.rs\n- Language: rust\n    \"### not a section\"\n",
+            "### src/c.rs\n- Language: rust\n",
+        );
+        let (text, summary) = render(&next, Some(&first));
+        assert_eq!(summary.added, ["src/c.rs"]);
+        assert_eq!(summary.changed, ["src/a.rs"]);
+        assert_eq!(summary.removed, ["src/b.rs"]);
+        assert!(text.contains("- Changed (1): [src/a.rs](#file-src-a-rs)"));
+        assert!(text.contains("- Removed (1): `src/b.rs`"));
+
+        let (again, summary) = render(&next, Some(&text));
+        assert!(summary.is_empty());
+        assert!(again.contains("- No changes"));
+    }
+}
@@ -0,0 +1,230 @@
+//! `cm envvars`: the environment variables a project reads or sets, found from each
+//! language's accessors (`os.environ`, `process.env.X`, `std::env::var`, clap's
+//! `env = "X"`, `os.Getenv`, `System.getenv`, Scala's `sys.env`, Swift's `environment["X"]`,
+//! `getenv`, Lua's `os.getenv`, Zig's `std.posix.getenv`), grouped by variable name.
+
+use anyhow::Result;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::callgraph;
+use crate::index::CodeIndex;
+use crate::models::Language;
+use crate::scan::{self, regex};
+use crate::value_refs::enclosing_symbol;
+
+#[derive(Debug, Clone)]
+pub struct EnvUsage {
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// The source line, trimmed
+    pub context: String,
+    /// Innermost symbol containing the usage
+    pub enclosing: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvVar {
+    pub name: String,
+    pub usages: Vec<EnvUsage>,
+}
+
+/// Every variable named by a string literal in a non-test file, sorted by name. Names built
+/// at runtime (`env::var(key)`) cannot be resolved and are not reported.
+pub fn find_env_vars(index: &CodeIndex) -> Result<Vec<EnvVar>> {
+    let mut vars: BTreeMap<String, Vec<EnvUsage>> = BTreeMap::new();
+
+    for file in index.files() {
+        if callgraph::is_test_file(&file.path, file.language) {
+            continue;
+        }
+        let content = scan::non_test_source(&file.path, file.language);
+        for (name, line, context) in env_vars_in(&content, file.language) {
+            vars.entry(name).or_default().push(EnvUsage {
+                file_path: file.path.clone(),
+                line,
+                context,
+                enclosing: enclosing_symbol(index, &file.path, line).map(|s| s.name.to_string()),
+            });
+        }
+    }
+
+    Ok(vars
+        .into_iter()
+        .map(|(name, mut usages)| {
+            usages.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+            EnvVar { name, usages }
+        })
+        .collect())
+}
+
+const NAME: &str = r"[A-Za-z_][A-Za-z0-9_]*";
+
+fn patterns(language: Language) -> Vec<Regex> {
+    let quoted = format!(r#"["'`]({})["'`]"#, NAME);
+    let sources = match language {
+        Language::Python => vec![
+            format!(
+                r"\benviron(?:\.get|\.setdefault|\.pop)?\s*[\[(]\s*{}",
+                quoted
+            ),
+            format!(r"\b(?:getenv|putenv|unsetenv)\s*\(\s*{}", quoted),
+        ],
+        Language::JavaScript | Language::TypeScript => vec![
+            format!(r"\b(?:process|import\.meta)\.env\.({})", NAME),
+            format!(r"\b(?:process|import\.meta)\.env\s*\[\s*{}", quoted),
+            format!(r"\bDeno\.env\.(?:get|set|has|delete)\s*\(\s*{}", quoted),
+        ],
+        Language::Rust => vec![
+            format!(
+                r#"\benv::(?:var|var_os|set_var|remove_var)\s*\(\s*"({})""#,
+                NAME
+            ),
+            format!(r#"\b(?:option_)?env!\s*\(\s*"({})""#, NAME),
+            // clap's `#[arg(long, env = "CM_CACHE_DIR")]`
+            format!(r#"[(,]\s*env\s*=\s*"({})""#, NAME),
+        ],
+        Language::Go => vec![format!(
+            r#"\bos\.(?:Getenv|LookupEnv|Setenv|Unsetenv)\s*\(\s*"({})""#,
+            NAME
+        )],
+        Language::Java | Language::Kotlin => {
+            vec![format!(r#"\bSystem\.getenv\s*\(\s*"({})""#, NAME)]
+        }
+        // `sys.env("HOME")`, `sys.env.get("HOME")`, `sys.env.getOrElse("HOME", ...)`
+        Language::Scala => vec![
+            format!(
+                r#"\bsys\.env(?:\.get|\.getOrElse|\.contains)?\s*[(\[]\s*"({})""#,
+                NAME
+            ),
+            format!(r#"\bSystem\.getenv\s*\(\s*"({})""#, NAME),
+        ],
+        // `ProcessInfo.processInfo.environment["API_KEY"]`, and C's `getenv` from Foundation
+        Language::Swift => vec![
+            format!(r#"\benvironment\s*\[\s*"({})""#, NAME),
+            format!(r#"\b(?:getenv|setenv|unsetenv)\s*\(\s*"({})""#, NAME),
+        ],
+        // `os.getenv("HOME")` and `os.getenv "HOME"`
+        Language::Lua => vec![format!(r"\bos\.getenv\s*\(?\s*{}", quoted)],
+        // `std.posix.getenv("HOME")` and `std.process.getEnvVarOwned(allocator, "HOME")`
+        Language::Zig => vec![
+            format!(r#"\b(?:posix|os)\.getenv\s*\(\s*"({})""#, NAME),
+            format!(
+                r#"\b(?:getEnvVarOwned|hasEnvVar|hasEnvVarConstant)\s*\((?:[^,()]*,)?\s*"({})""#,
+                NAME
+            ),
+        ],
+        Language::CSharp => vec![format!(
+            r#"\bEnvironment\.(?:GetEnvironmentVariable|SetEnvironmentVariable)\s*\(\s*"({})""#,
+            NAME
+        )],
+        // Laravel's `env('APP_KEY')` and `$_ENV['APP_KEY']` too
+        Language::Php => vec![
+            format!(r#"\b(?:getenv|putenv|env)\s*\(\s*['"]({})['"=]"#, NAME),
+            format!(r#"\$_(?:ENV|SERVER)\s*\[\s*['"]({})['"]"#, NAME),
+        ],
+        Language::C | Language::Cpp => vec![format!(
+            r#"\b(?:secure_getenv|getenv|setenv|unsetenv)\s*\(\s*"({})""#,
+            NAME
+        )],
+        _ => Vec::new(),
+    };
+    sources.iter().map(|s| regex(s)).collect()
+}
+
+/// (name, 1-based line, trimmed source line) of every variable used in `content`
+pub fn env_vars_in(content: &str, language: Language) -> Vec<(String, usize, String)> {
+    let patterns = patterns(language);
+    if patterns.is_empty() {
+        return Vec::new();
+    }
+    // `const { API_KEY, PORT = 80 } = process.env`
+    let destructure = regex(r"\{([^}]*)\}\s*=\s*process\.env\b");
+
+    let mut found = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if scan::is_comment(line, language) {
+            continue;
+        }
+        let mut names: Vec<(usize, String)> = Vec::new();
+        for pattern in &patterns {
+            for caps in pattern.captures_iter(line) {
+                if let Some(m) = caps.get(1) {
+                    names.push((m.start(), m.as_str().to_string()));
+                }
+            }
+        }
+        if matches!(language, Language::JavaScript | Language::TypeScript) {
+            for caps in destructure.captures_iter(line) {
+                let Some(fields) = caps.get(1) else {
+                    continue;
+                };
+                for field in fields.as_str().split(',') {
+                    let name = field.split([':', '=']).next().unwrap_or("").trim();
+                    if !name.is_empty() && !name.starts_with("...") {
+                        names.push((fields.start(), name.to_string()));
+                    }
+                }
+            }
+        }
+        names.sort();
+        names.dedup_by(|a, b| a.1 == b.1);
+        for (_, name) in names {
+            found.push((name, i + 1, line.trim().to_string()));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(content: &str, language: Language) -> Vec<(String, usize)> {
+        env_vars_in(content, language)
+            .into_iter()
+            .map(|(name, line, _)| (name, line))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_vars_per_language() {
+        let python = "import os\n# os.environ['COMMENTED']\nurl = os.environ['DATABASE_URL']\ndebug = os.getenv(\"DEBUG\", \"0\")\nos.environ.setdefault('TZ', 'UTC')\n";
+        assert_eq!(
+            names(python, Language::Python),
+            vec![
+                ("DATABASE_URL".to_string(), 3),
+                ("DEBUG".to_string(), 4),
+                ("TZ".to_string(), 5)
+            ]
+        );
+
+        let js = "const port = process.env.PORT || 3000;\nconst { API_KEY, REGION: region = 'us' } = process.env;\nconst key = process.env['SECRET'];\n";
+        assert_eq!(
+            names(js, Language::TypeScript),
+            vec![
+                ("PORT".to_string(), 1),
+                ("API_KEY".to_string(), 2),
+                ("REGION".to_string(), 2),
+                ("SECRET".to_string(), 3)
+            ]
+        );
+
+        let rust = "#[arg(long, env = \"CM_CACHE_DIR\")]\nlet home = std::env::var(\"HOME\")?;\nlet v = env!(\"CARGO_PKG_VERSION\");\nlet dynamic = env::var(key);\n";
+        assert_eq!(
+            names(rust, Language::Rust),
+            vec![
+                ("CM_CACHE_DIR".to_string(), 1),
+                ("HOME".to_string(), 2),
+                ("CARGO_PKG_VERSION".to_string(), 3)
+            ]
+        );
+
+        let go = "addr := os.Getenv(\"ADDR\")\nif v, ok := os.LookupEnv(\"TOKEN\"); ok {}\n";
+        assert_eq!(
+            names(go, Language::Go),
+            vec![("ADDR".to_string(), 1), ("TOKEN".to_string(), 2)]
+        );
+    }
+}
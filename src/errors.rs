@@ -0,0 +1,388 @@
+//! `cm errors`: the failure modes of a function. Error types it raises, throws or returns
+//! (`raise`, `throw new`, `Err(...)`, `errors.New`), the ones its signature declares
+//! (`Result<_, E>`, `throws`, Go's `error`), panics, and the same for its direct callees.
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::callgraph;
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+use crate::scan::{self, regex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorKind {
+    /// In the signature: `Result<_, E>`, `throws E`, a Go `error` result
+    Declared,
+    /// `raise`, `throw`
+    Raised,
+    /// `Err(...)`, `bail!`, `errors.New`, sentinel errors
+    Returned,
+    /// `panic!`, `unwrap()`, `expect()`
+    Panic,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Declared => "declared",
+            ErrorKind::Raised => "raised",
+            ErrorKind::Returned => "returned",
+            ErrorKind::Panic => "panic",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorSite {
+    pub error: String,
+    pub kind: ErrorKind,
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// The direct callee the error comes from; `None` for the function itself
+    pub via: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorReport {
+    pub name: String,
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// Own errors first, then each callee's, in source order
+    pub sites: Vec<ErrorSite>,
+}
+
+fn is_callable(symbol: &Symbol) -> bool {
+    matches!(
+        symbol.symbol_type,
+        SymbolType::Function | SymbolType::Method
+    )
+}
+
+/// Failure modes of each function in `symbols`, plus those of its direct callees unless
+/// `direct` is set
+pub fn analyze(index: &CodeIndex, symbols: &[&Symbol], direct: bool) -> Result<Vec<ErrorReport>> {
+    let mut reports = Vec::new();
+    for &symbol in symbols.iter().filter(|s| is_callable(s)) {
+        let mut sites = own_errors(symbol, None);
+
+        if !direct {
+            let mut seen = HashSet::new();
+            for callee in callgraph::find_callees(index, &symbol.name, false)? {
+                if callee.file_path == "<external>" || !seen.insert(callee.caller_name.clone()) {
+                    continue;
+                }
+                // Same-file definitions first (`Self::load` over another module's `load`),
+                // then the one the call graph resolved
+                let targets = index.query_symbol(&callee.caller_name);
+                let Some(target) = targets
+                    .iter()
+                    .filter(|t| is_callable(t))
+                    .find(|t| t.file_path == symbol.file_path)
+                    .or_else(|| {
+                        targets.iter().find(|t| {
+                            is_callable(t)
+                                && t.line_start == callee.line
+                                && *t.file_path == *Path::new(&callee.file_path)
+                        })
+                    })
+                else {
+                    continue;
+                };
+                if target.file_path == symbol.file_path && target.line_start == symbol.line_start {
+                    continue;
+                }
+                sites.extend(own_errors(target, Some(&callee.caller_name)));
+            }
+        }
+
+        reports.push(ErrorReport {
+            name: symbol.name.to_string(),
+            file_path: symbol.file_path.to_path_buf(),
+            line: symbol.line_start,
+            sites,
+        });
+    }
+    Ok(reports)
+}
+
+fn own_errors(symbol: &Symbol, via: Option<&str>) -> Vec<ErrorSite> {
+    let Ok(content) = fs::read_to_string(&symbol.file_path) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let start = symbol.line_start.saturating_sub(1);
+    let end = symbol.line_end.min(lines.len());
+    if start >= end {
+        return Vec::new();
+    }
+    let language = Language::from_extension(
+        symbol
+            .file_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or(""),
+    );
+
+    let alias_error = if language == Language::Rust {
+        result_alias_error(&content)
+    } else {
+        None
+    };
+
+    errors_in(&lines[start..end], language)
+        .into_iter()
+        .map(|(error, kind, offset)| ErrorSite {
+            error: match &alias_error {
+                Some(alias) if error == "Error" && kind == ErrorKind::Declared => alias.clone(),
+                _ => error,
+            },
+            kind,
+            file_path: symbol.file_path.to_path_buf(),
+            line: symbol.line_start + offset,
+            via: via.map(str::to_string),
+        })
+        .collect()
+}
+
+/// `Result<Config, ConfigError>` -> `ConfigError`; `io::Result<()>` -> `io::Error`
+fn result_error(return_type: &str) -> Option<String> {
+    let caps = regex(r"((?:\w+::)*)Result\s*<").captures(return_type)?;
+    let open = caps.get(0)?.end();
+    let mut depth = 1;
+    let mut split = None;
+    for (i, c) in return_type[open..].char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    let inner = &return_type[open..open + i];
+                    return Some(match split {
+                        Some(at) => inner[at + 1..].trim().to_string(),
+                        None => format!("{}Error", caps.get(1).map_or("", |m| m.as_str())),
+                    });
+                }
+            }
+            ',' if depth == 1 => split = Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Error type behind a bare `Result<T>` in a file importing `anyhow::Result` or
+/// `std::io::Result` (`anyhow::Error`, `io::Error`)
+fn result_alias_error(content: &str) -> Option<String> {
+    let caps = regex(r"(?m)^\s*(?:pub\s+)?use\s+((?:\w+::)*\w+)::(?:Result\b|\{[^}]*\bResult\b)")
+        .captures(content)?;
+    let module = caps.get(1)?.as_str();
+    let module = module.strip_prefix("std::").unwrap_or(module);
+    Some(format!("{}::Error", module))
+}
+
+/// Declaration text up to the body
+fn header(lines: &[&str]) -> String {
+    let mut text = String::new();
+    for line in lines.iter().take(12) {
+        match line.find('{') {
+            Some(brace) => {
+                text.push_str(&line[..brace]);
+                break;
+            }
+            None => {
+                text.push_str(line);
+                text.push(' ');
+            }
+        }
+    }
+    text
+}
+
+/// (error, kind, line offset within `lines`) found in one function's source, first
+/// occurrence of each error and kind only
+pub fn errors_in(lines: &[&str], language: Language) -> Vec<(String, ErrorKind, usize)> {
+    let mut found: Vec<(String, ErrorKind, usize)> = Vec::new();
+    let mut push = |error: String, kind: ErrorKind, offset: usize| {
+        if !found.iter().any(|(e, k, _)| *e == error && *k == kind) {
+            found.push((error, kind, offset));
+        }
+    };
+
+    let header = header(lines);
+    match language {
+        Language::Rust => {
+            if let Some(error) = header
+                .split_once("->")
+                .and_then(|(_, ret)| result_error(ret))
+            {
+                push(error, ErrorKind::Declared, 0);
+            }
+        }
+        Language::Java => {
+            if let Some(caps) = regex(r"\)\s*throws\s+([\w.,\s]+)$").captures(header.trim_end()) {
+                for error in caps[1].split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                    push(error.to_string(), ErrorKind::Declared, 0);
+                }
+            }
+        }
+        // `func f() error` or `func f() (T, error)`
+        Language::Go
+            if regex(r"\)\s*(?:\([^)]*\berror\s*\)|error)\s*$").is_match(header.trim_end()) =>
+        {
+            push("error".to_string(), ErrorKind::Declared, 0);
+        }
+        _ => {}
+    }
+
+    let patterns: Vec<(regex::Regex, ErrorKind)> = match language {
+        Language::Rust => vec![
+            (regex(r"\bErr\s*\(\s*([A-Z][\w:]*)"), ErrorKind::Returned),
+            (
+                regex(r"\b(?:bail|ensure|anyhow)!\s*\(()"),
+                ErrorKind::Returned,
+            ),
+            (
+                regex(r"\b(panic|unreachable|todo|unimplemented)!\s*\("),
+                ErrorKind::Panic,
+            ),
+            (regex(r"\.(unwrap|expect)\s*\("), ErrorKind::Panic),
+        ],
+        Language::Python => vec![(regex(r"^\s*raise\s+([A-Za-z_][\w.]*)"), ErrorKind::Raised)],
+        Language::JavaScript | Language::TypeScript | Language::Java => vec![
+            (
+                regex(r"\bthrow\s+new\s+([A-Za-z_$][\w.$]*)"),
+                ErrorKind::Raised,
+            ),
+            (
+                regex(r"\bPromise\.reject\s*\(\s*new\s+([A-Za-z_$][\w.$]*)"),
+                ErrorKind::Returned,
+            ),
+        ],
+        Language::Go => vec![
+            (
+                regex(r"\b(errors\.New|fmt\.Errorf)\s*\("),
+                ErrorKind::Returned,
+            ),
+            (
+                regex(r"\breturn\b.*?\b(Err[A-Z]\w*)\b"),
+                ErrorKind::Returned,
+            ),
+            (regex(r"&?\b([A-Z]\w*Error)\s*\{"), ErrorKind::Returned),
+            (regex(r"\b(panic)\s*\("), ErrorKind::Panic),
+        ],
+        _ => Vec::new(),
+    };
+
+    for (offset, line) in lines.iter().enumerate() {
+        if scan::is_comment(line, language) {
+            continue;
+        }
+        for (pattern, kind) in &patterns {
+            for caps in pattern.captures_iter(line) {
+                let captured = caps.get(1).map_or("", |m| m.as_str());
+                let error = match (language, captured) {
+                    // `bail!("...")` and friends build an `anyhow::Error`
+                    (Language::Rust, "") => "anyhow::Error".to_string(),
+                    (Language::Rust, "unwrap" | "expect") => format!("{}()", captured),
+                    (Language::Rust, name) if *kind == ErrorKind::Panic => format!("{}!", name),
+                    _ => captured.to_string(),
+                };
+                push(error, *kind, offset);
+            }
+        }
+        // A bare `raise` re-raises the exception being handled
+        if language == Language::Python && line.trim() == "raise" {
+            push("re-raise".to_string(), ErrorKind::Raised, offset);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+    use std::path::Path;
+
+    fn errors(source: &str, language: Language) -> Vec<(String, ErrorKind)> {
+        let lines: Vec<&str> = source.lines().collect();
+        errors_in(&lines, language)
+            .into_iter()
+            .map(|(error, kind, _)| (error, kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_errors_per_language() {
+        let rust = "pub fn load(path: &Path) -> Result<Config, ConfigError> {\n    if bad { return Err(ConfigError::Missing(path.into())); }\n    // Err(Commented)\n    let x = parse(path).unwrap();\n    bail!(\"nope\")\n}";
+        assert_eq!(
+            errors(rust, Language::Rust),
+            vec![
+                ("ConfigError".to_string(), ErrorKind::Declared),
+                ("ConfigError::Missing".to_string(), ErrorKind::Returned),
+                ("unwrap()".to_string(), ErrorKind::Panic),
+                ("anyhow::Error".to_string(), ErrorKind::Returned),
+            ]
+        );
+        assert_eq!(
+            result_error(" io::Result<Vec<u8>>").as_deref(),
+            Some("io::Error")
+        );
+
+        let python = "def charge(card):\n    try:\n        gateway.charge(card)\n    except Timeout:\n        raise\n    raise PaymentError('declined')";
+        assert_eq!(
+            errors(python, Language::Python),
+            vec![
+                ("re-raise".to_string(), ErrorKind::Raised),
+                ("PaymentError".to_string(), ErrorKind::Raised),
+            ]
+        );
+
+        let java = "public User find(long id) throws NotFoundException, IOException {\n    throw new NotFoundException(id);\n}";
+        assert_eq!(
+            errors(java, Language::Java),
+            vec![
+                ("NotFoundException".to_string(), ErrorKind::Declared),
+                ("IOException".to_string(), ErrorKind::Declared),
+                ("NotFoundException".to_string(), ErrorKind::Raised),
+            ]
+        );
+
+        let go = "func Get(id string) (*Item, error) {\n\tif id == \"\" {\n\t\treturn nil, ErrNotFound\n\t}\n\treturn nil, fmt.Errorf(\"get %s: %w\", id, err)\n}";
+        assert_eq!(
+            errors(go, Language::Go),
+            vec![
+                ("error".to_string(), ErrorKind::Declared),
+                ("ErrNotFound".to_string(), ErrorKind::Returned),
+                ("fmt.Errorf".to_string(), ErrorKind::Returned),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_includes_direct_callees() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = "def save(order):\n    validate(order)\n    raise StorageError()\n\ndef validate(order):\n    if not order:\n        raise ValueError('empty')\n";
+        let index = index_of(dir.path(), &[("app.py", source, Language::Python)]);
+
+        let symbols = index.query_symbol("save");
+        let reports = analyze(&index, &symbols, false).unwrap();
+        let sites: Vec<(&str, Option<&str>, usize)> = reports[0]
+            .sites
+            .iter()
+            .map(|s| (s.error.as_str(), s.via.as_deref(), s.line))
+            .collect();
+        assert_eq!(
+            sites,
+            vec![
+                ("StorageError", None, 3),
+                ("ValueError", Some("validate"), 7)
+            ]
+        );
+        assert_eq!(analyze(&index, &symbols, true).unwrap()[0].sites.len(), 1);
+        assert!(Path::new(&reports[0].file_path).ends_with("app.py"));
+    }
+}
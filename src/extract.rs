@@ -0,0 +1,140 @@
+//! `cm extract`: a symbol's source as a standalone snippet for an issue, a test or a prompt.
+//! With imports, the snippet starts with the import statements of its file that bind a name
+//! the body uses, one per name, and lists the symbols of the same file it relies on but
+//! does not carry along.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::index::CodeIndex;
+use crate::indexer::detect_language;
+use crate::models::{Language, Symbol, SymbolType};
+use crate::paths::DisplaySlash;
+use crate::scan::regex;
+use crate::symbol_deps::{self, DepKind};
+
+#[derive(Debug, Clone)]
+pub struct Snippet {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: PathBuf,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub language: Language,
+    /// Import statements the body needs, in file order
+    pub imports: Vec<String>,
+    /// Symbols of the same file the body calls or names, which the snippet leaves out
+    pub local: Vec<String>,
+    /// The symbol's lines, common indentation removed
+    pub body: String,
+}
+
+impl Snippet {
+    /// Imports, a blank line, then the body
+    pub fn code(&self) -> String {
+        if self.imports.is_empty() {
+            return self.body.clone();
+        }
+        format!("{}\n\n{}", self.imports.join("\n"), self.body)
+    }
+}
+
+/// Remove the indentation every non-blank line shares, so a method reads as top-level code
+fn dedent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `symbol` as a snippet; with `with_imports`, the imports and same-file symbols it needs
+pub fn extract(index: &CodeIndex, symbol: &Symbol, with_imports: bool) -> Result<Snippet> {
+    let content = fs::read_to_string(&symbol.file_path)
+        .with_context(|| format!("Failed to read {}", symbol.file_path.display_slash()))?;
+    let lines: Vec<&str> = content.lines().collect();
+    let start = symbol.line_start.max(1) - 1;
+    let end = symbol.line_end.min(lines.len()).max(start);
+    let language = detect_language(&symbol.file_path);
+
+    let mut snippet = Snippet {
+        name: symbol.name.to_string(),
+        symbol_type: symbol.symbol_type,
+        file_path: symbol.file_path.to_path_buf(),
+        line_start: symbol.line_start,
+        line_end: symbol.line_end,
+        language,
+        imports: Vec::new(),
+        local: Vec::new(),
+        body: dedent(&lines[start..end]),
+    };
+    if !with_imports {
+        return Ok(snippet);
+    }
+
+    let identifiers: HashSet<&str> = regex(r"[A-Za-z_$][A-Za-z0-9_$]*")
+        .find_iter(&snippet.body)
+        .map(|m| m.as_str())
+        .collect();
+    for import in symbol_deps::imports(&content, language) {
+        if identifiers.contains(import.bound.as_str())
+            && !snippet.imports.contains(&import.statement)
+        {
+            snippet.imports.push(import.statement);
+        }
+    }
+
+    let own_file = format!("{}:", symbol.file_path.display_slash());
+    for dep in symbol_deps::symbol_dependencies(index, symbol)? {
+        let in_file = dep.target.starts_with(&own_file);
+        if dep.kind != DepKind::Import && in_file && !snippet.local.contains(&dep.name) {
+            snippet.local.push(dep.name);
+        }
+    }
+    Ok(snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+
+    #[test]
+    fn test_extract_with_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = r#"import json
+import os.path
+from decimal import Decimal as D, ROUND_UP
+
+def total(order):
+    return D(order.amount)
+
+class Cart:
+    def checkout(self, order):
+        data = json.dumps({"total": str(total(order))})
+        return data
+"#;
+        let index = index_of(dir.path(), &[("shop.py", source, Language::Python)]);
+
+        let checkout = index.query_symbol("checkout")[0].clone();
+        let snippet = extract(&index, &checkout, true).unwrap();
+        assert_eq!(snippet.imports, ["import json"]);
+        assert_eq!(snippet.local, ["total"]);
+        assert!(snippet
+            .code()
+            .starts_with("import json\n\ndef checkout(self, order):\n    data ="));
+
+        let total = index.query_symbol("total")[0].clone();
+        let snippet = extract(&index, &total, true).unwrap();
+        assert_eq!(snippet.imports, ["from decimal import Decimal as D"]);
+        assert!(extract(&index, &total, false).unwrap().imports.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! Failures `cm` reports to its caller. Each one carries a stable code (`PATH_NOT_FOUND`,
+//! `CACHE_CORRUPT`, ...) that `--format json` and `--format ai` print, and maps to one of the
+//! documented exit statuses, so wrappers can branch without parsing messages.
+
+use std::fmt;
+use std::io;
+
+/// Exit status for failures caused by the environment: I/O, git, cache, external commands,
+/// a path outside a git repository
+pub const EXIT_ERROR: i32 = 1;
+/// Exit status for a command line cm cannot act on (clap uses it for parse errors too)
+pub const EXIT_USAGE: i32 = 2;
+/// Exit status for a failed CI assertion (`--expect-min`, `--fail-under`, ...)
+pub const EXIT_ASSERTION_FAILED: i32 = 3;
+/// Exit status for a file, directory, snapshot, symbol or history that does not exist
+pub const EXIT_NOT_FOUND: i32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Bad flag value or combination
+    InvalidArgument,
+    /// A file or directory named on the command line does not exist
+    PathNotFound,
+    /// A named snapshot, symbol or git history does not exist
+    NotFound,
+    /// No parser for the file's language
+    UnsupportedLanguage,
+    /// The command needs a `.codemapper.toml` section that is missing (embeddings, summarizer)
+    NotConfigured,
+    /// The command needs git and the path is not in a repository
+    NotAGitRepo,
+    /// A git command failed
+    GitFailed,
+    /// The on-disk index cache could not be read back
+    CacheCorrupt,
+    /// An embedding backend, summarizer or parser plugin failed
+    ExternalCommandFailed,
+    /// Reading or writing a file failed
+    Io,
+    /// A CI assertion flag or check did not hold
+    AssertionFailed,
+    /// Anything not classified above
+    Other,
+}
+
+impl ErrorCode {
+    /// Every code, in the order above
+    pub const ALL: [ErrorCode; 12] = [
+        ErrorCode::InvalidArgument,
+        ErrorCode::PathNotFound,
+        ErrorCode::NotFound,
+        ErrorCode::UnsupportedLanguage,
+        ErrorCode::NotConfigured,
+        ErrorCode::NotAGitRepo,
+        ErrorCode::GitFailed,
+        ErrorCode::CacheCorrupt,
+        ErrorCode::ExternalCommandFailed,
+        ErrorCode::Io,
+        ErrorCode::AssertionFailed,
+        ErrorCode::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidArgument => "INVALID_ARGUMENT",
+            ErrorCode::PathNotFound => "PATH_NOT_FOUND",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::UnsupportedLanguage => "UNSUPPORTED_LANGUAGE",
+            ErrorCode::NotConfigured => "NOT_CONFIGURED",
+            ErrorCode::NotAGitRepo => "NOT_A_GIT_REPO",
+            ErrorCode::GitFailed => "GIT_FAILED",
+            ErrorCode::CacheCorrupt => "CACHE_CORRUPT",
+            ErrorCode::ExternalCommandFailed => "EXTERNAL_COMMAND_FAILED",
+            ErrorCode::Io => "IO_ERROR",
+            ErrorCode::AssertionFailed => "ASSERTION_FAILED",
+            ErrorCode::Other => "ERROR",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorCode::InvalidArgument
+            | ErrorCode::UnsupportedLanguage
+            | ErrorCode::NotConfigured => EXIT_USAGE,
+            ErrorCode::PathNotFound | ErrorCode::NotFound => EXIT_NOT_FOUND,
+            ErrorCode::AssertionFailed => EXIT_ASSERTION_FAILED,
+            ErrorCode::NotAGitRepo
+            | ErrorCode::GitFailed
+            | ErrorCode::CacheCorrupt
+            | ErrorCode::ExternalCommandFailed
+            | ErrorCode::Io
+            | ErrorCode::Other => EXIT_ERROR,
+        }
+    }
+}
+
+/// An error with a code, raised with `bail!(Failure::new(...))` and recovered from the
+/// `anyhow::Error` chain by `code_of`
+#[derive(Debug)]
+pub struct Failure {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// The code of the outermost `Failure` in `err` (raised or attached with `.context`);
+/// untagged I/O errors are classified by kind, anything else is `Other`
+pub fn code_of(err: &anyhow::Error) -> ErrorCode {
+    if let Some(failure) = err.downcast_ref::<Failure>() {
+        return failure.code;
+    }
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<io::Error>())
+        .map_or(ErrorCode::Other, |io| match io.kind() {
+            io::ErrorKind::NotFound => ErrorCode::PathNotFound,
+            _ => ErrorCode::Io,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{bail, Context, Result};
+
+    #[test]
+    fn test_code_of_tagged_and_untagged_errors() {
+        let tagged = || -> Result<()> {
+            bail!(Failure::new(
+                ErrorCode::NotAGitRepo,
+                "Not a git repository: /tmp"
+            ));
+        };
+        let err = tagged().context("cm diff").unwrap_err();
+        assert_eq!(code_of(&err), ErrorCode::NotAGitRepo);
+        assert_eq!(code_of(&err).exit_code(), EXIT_ERROR);
+        assert_eq!(format!("{:#}", err), "cm diff: Not a git repository: /tmp");
+
+        let corrupt = Err::<(), _>(io::Error::other("bad bincode")).context(Failure::new(
+            ErrorCode::CacheCorrupt,
+            "Failed to deserialize index",
+        ));
+        assert_eq!(code_of(&corrupt.unwrap_err()), ErrorCode::CacheCorrupt);
+
+        let missing = std::fs::read_to_string("/nonexistent/cm/file").context("reading");
+        assert_eq!(code_of(&missing.unwrap_err()), ErrorCode::PathNotFound);
+        assert_eq!(code_of(&anyhow::anyhow!("boom")), ErrorCode::Other);
+    }
+}
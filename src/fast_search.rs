@@ -2,17 +2,24 @@ use anyhow::{Context, Result};
 use grep::regex::RegexMatcher;
 use grep::searcher::{BinaryDetection, Searcher, SearcherBuilder, Sink, SinkMatch};
 use ignore::WalkBuilder;
+use rayon::prelude::*;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::deadline;
+use crate::index::NamePattern;
 use crate::indexer::{detect_language, index_file};
 use crate::models::Symbol;
+use crate::paths;
 
 /// Fast text search using ripgrep-style grep for prefiltering candidate files
 pub struct GrepFilter {
     pattern: String,
     case_sensitive: bool,
     extensions: Vec<String>,
+    /// Wildcard query; the grep stage searches for its literal part
+    name_pattern: Option<NamePattern>,
 }
 
 /// Collects file paths that match the grep pattern
@@ -54,20 +61,24 @@ impl GrepFilter {
             pattern: pattern.to_string(),
             case_sensitive,
             extensions,
+            name_pattern: None,
+        }
+    }
+
+    /// Prefilter on the pattern's literal part and validate names against the whole pattern
+    pub fn for_name_pattern(pattern: NamePattern, extensions: Vec<String>) -> Self {
+        Self {
+            pattern: pattern.literal().to_string(),
+            case_sensitive: pattern.case_sensitive(),
+            extensions,
+            name_pattern: Some(pattern),
         }
     }
 
     /// Stage 1: Fast text search to find candidate files
     /// Returns list of files that contain the pattern
     pub fn prefilter(&self, root: &Path) -> Result<Vec<PathBuf>> {
-        // Build regex pattern with case sensitivity
-        let pattern = if self.case_sensitive {
-            self.pattern.clone()
-        } else {
-            format!("(?i){}", regex::escape(&self.pattern))
-        };
-
-        let matcher = RegexMatcher::new(&pattern).context("Failed to create regex matcher")?;
+        let matcher = self.matcher()?;
 
         let mut collector = CandidateCollector::new();
         let mut searcher = SearcherBuilder::new()
@@ -84,6 +95,9 @@ impl GrepFilter {
             .build();
 
         for entry in walker {
+            if deadline::expired() {
+                break;
+            }
             let entry = entry.context("Failed to read directory entry")?;
 
             if !entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
@@ -105,56 +119,101 @@ impl GrepFilter {
         Ok(collector.files)
     }
 
+    /// Stage 1 over an already-discovered file list, skipping the directory walk
+    pub fn prefilter_files(&self, files: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let matcher = self.matcher()?;
+
+        let mut collector = CandidateCollector::new();
+        let mut searcher = SearcherBuilder::new()
+            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .line_number(false)
+            .build();
+
+        for path in files.iter().filter(|p| self.matches_extension(p)) {
+            if deadline::expired() {
+                break;
+            }
+            collector.set_path(path.clone());
+            let _ = searcher.search_path(&matcher, path, &mut collector);
+        }
+
+        Ok(collector.files)
+    }
+
+    fn matcher(&self) -> Result<RegexMatcher> {
+        let pattern = if self.case_sensitive {
+            self.pattern.clone()
+        } else {
+            format!("(?i){}", regex::escape(&self.pattern))
+        };
+
+        RegexMatcher::new(&pattern).context("Failed to create regex matcher")
+    }
+
     /// Check if file extension matches our filter
     fn matches_extension(&self, path: &Path) -> bool {
         if self.extensions.is_empty() {
             return true;
         }
 
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| self.extensions.contains(&ext.to_string()))
-            .unwrap_or(false)
+        paths::has_extension(path, &self.extensions)
     }
 
     /// Stage 2: AST validation of candidate files
-    /// Parse only candidate files and extract matching symbols
+    /// Parses candidates in parallel (each file once, even if listed twice) and returns
+    /// matching symbols in candidate order
     pub fn validate(
         &self,
         candidates: Vec<PathBuf>,
         query: &str,
         fuzzy: bool,
     ) -> Result<Vec<Symbol>> {
-        let mut all_symbols = Vec::new();
-
-        for path in candidates {
-            // Read file content
-            let content = match fs::read_to_string(&path) {
-                Ok(c) => c,
-                Err(_) => continue, // Skip files we can't read
-            };
-
-            // Detect language and parse
-            let language = detect_language(&path);
-            let file_info = match index_file(&path, &content, language, None) {
-                Ok(info) => info,
-                Err(_) => continue, // Skip files we can't parse
-            };
-
-            // Filter symbols matching query
-            for symbol in file_info.symbols {
-                if self.symbol_matches(&symbol.name, query, fuzzy) {
-                    all_symbols.push(symbol);
-                }
-            }
+        let mut seen = HashSet::new();
+        let candidates: Vec<PathBuf> = candidates
+            .into_iter()
+            .filter(|path| seen.insert(path.clone()))
+            .collect();
+
+        let per_file: Vec<Vec<Symbol>> = candidates
+            .par_iter()
+            .map(|path| self.validate_file(path, query, fuzzy))
+            .collect();
+
+        Ok(per_file.into_iter().flatten().collect())
+    }
+
+    /// Matching symbols in one file; files that cannot be read or parsed yield none
+    fn validate_file(&self, path: &Path, query: &str, fuzzy: bool) -> Vec<Symbol> {
+        if deadline::expired() {
+            return Vec::new();
+        }
+        let Ok(content) = fs::read_to_string(path) else {
+            return Vec::new();
+        };
+
+        // The grep stage may have matched case-insensitively; an exact query that is not
+        // in the text cannot name a symbol here, so skip the parse
+        if !fuzzy && self.name_pattern.is_none() && !content.contains(query) {
+            return Vec::new();
         }
 
-        Ok(all_symbols)
+        let language = detect_language(path);
+        let Ok(file_info) = index_file(path, &content, language, None) else {
+            return Vec::new();
+        };
+
+        file_info
+            .symbols
+            .into_iter()
+            .filter(|symbol| self.symbol_matches(&symbol.name, query, fuzzy))
+            .collect()
     }
 
     /// Check if a symbol name matches the query
     fn symbol_matches(&self, name: &str, query: &str, fuzzy: bool) -> bool {
-        if fuzzy {
+        if let Some(pattern) = &self.name_pattern {
+            pattern.matches(name)
+        } else if fuzzy {
             // Case-insensitive substring match for fuzzy search
             name.to_lowercase().contains(&query.to_lowercase())
         } else {
@@ -188,4 +247,30 @@ mod tests {
         assert!(filter.matches_extension(path_py));
         assert!(!filter.matches_extension(path_js));
     }
+
+    #[test]
+    fn test_validate_parses_each_candidate_once() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let lib = dir.path().join("lib.rs");
+        let other = dir.path().join("other.rs");
+        fs::write(&lib, "fn retry() {}\nfn retry_later() {}\n").unwrap();
+        fs::write(&other, "// mentions Retry only in a comment\n").unwrap();
+
+        let filter = GrepFilter::new("retry", false, vec!["rs".to_string()]);
+        let candidates = vec![lib.clone(), other, lib];
+
+        let exact = filter.validate(candidates.clone(), "retry", false).unwrap();
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].name, "retry");
+
+        let fuzzy = filter.validate(candidates.clone(), "retry", true).unwrap();
+        assert_eq!(fuzzy.len(), 2);
+
+        let pattern = NamePattern::parse("*_later", true).unwrap();
+        let filter = GrepFilter::for_name_pattern(pattern, vec!["rs".to_string()]);
+        assert_eq!(filter.pattern, "_later");
+        let globbed = filter.validate(candidates, "*_later", false).unwrap();
+        assert_eq!(globbed.len(), 1);
+        assert_eq!(globbed[0].name, "retry_later");
+    }
 }
@@ -0,0 +1,467 @@
+//! `--where` expressions: one composable filter over symbol metadata for query, map and
+//! untested, e.g. `kind=function && exported && lines>40 && file~"src/**"`.
+//!
+//! Conditions compare a field with a value (`=`, `!=`, `>`, `>=`, `<`, `<=`, and `~` / `!~`
+//! for glob matches) or name a yes/no field on its own (`exported`, `documented`). They
+//! combine with `&&`, `||`, `!` and parentheses; `&&` binds tighter than `||`. Values are
+//! bare words or double-quoted strings.
+
+use regex::Regex;
+use std::path::Path;
+
+use crate::index::NamePattern;
+use crate::indexer::detect_language;
+use crate::models::{Language, Symbol, SymbolType};
+use crate::paths::DisplaySlash;
+
+const FIELDS: &str = "kind, name, file, lang, lines, line, exported, documented";
+
+/// A parsed `--where` expression
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cond(Cond),
+}
+
+#[derive(Debug, Clone)]
+enum Cond {
+    Kind(SymbolType),
+    Lang(Language),
+    /// `name=...`
+    Name(String),
+    /// `name~...`: a wildcard pattern, or a case-insensitive substring without wildcards
+    NameLike(Option<NamePattern>, String),
+    /// `file=...`: the path, or its trailing components
+    File(String),
+    /// `file~...`: a glob from any directory boundary; a directory matches what is under it
+    FileLike(Regex),
+    Lines(Cmp, usize),
+    Line(Cmp, usize),
+    Exported,
+    Documented,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Like,
+    NotLike,
+}
+
+impl Cmp {
+    fn holds(self, left: usize, right: usize) -> bool {
+        match self {
+            Cmp::Eq => left == right,
+            Cmp::Ne => left != right,
+            Cmp::Gt => left > right,
+            Cmp::Ge => left >= right,
+            Cmp::Lt => left < right,
+            Cmp::Le => left <= right,
+            Cmp::Like | Cmp::NotLike => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Cmp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Filter { expr }),
+            Some(token) => Err(format!(
+                "unexpected {} after a complete expression",
+                describe(token)
+            )),
+        }
+    }
+
+    pub fn matches(&self, symbol: &Symbol) -> bool {
+        self.expr.matches(symbol)
+    }
+}
+
+impl Expr {
+    fn matches(&self, symbol: &Symbol) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(symbol) && right.matches(symbol),
+            Expr::Or(left, right) => left.matches(symbol) || right.matches(symbol),
+            Expr::Not(inner) => !inner.matches(symbol),
+            Expr::Cond(cond) => cond.matches(symbol),
+        }
+    }
+}
+
+impl Cond {
+    fn matches(&self, symbol: &Symbol) -> bool {
+        match self {
+            Cond::Kind(kind) => symbol.symbol_type == *kind,
+            Cond::Lang(language) => detect_language(&symbol.file_path) == *language,
+            Cond::Name(name) => *symbol.name == **name,
+            Cond::NameLike(Some(pattern), _) => pattern.matches(&symbol.name),
+            Cond::NameLike(None, text) => symbol.name.to_lowercase().contains(text.as_str()),
+            Cond::File(file) => {
+                let path = relative_path(&symbol.file_path);
+                path == *file || path.ends_with(&format!("/{}", file))
+            }
+            Cond::FileLike(regex) => regex.is_match(&relative_path(&symbol.file_path)),
+            Cond::Lines(cmp, n) => cmp.holds(symbol.line_end + 1 - symbol.line_start, *n),
+            Cond::Line(cmp, n) => cmp.holds(symbol.line_start, *n),
+            Cond::Exported => symbol.is_exported,
+            Cond::Documented => symbol
+                .docstring
+                .as_ref()
+                .is_some_and(|doc| !doc.trim().is_empty()),
+        }
+    }
+}
+
+/// `/`-separated, without a leading `./`
+fn relative_path(path: &Path) -> String {
+    let path = path.display_slash().to_string();
+    match path.strip_prefix("./") {
+        Some(rest) => rest.to_string(),
+        None => path,
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('!', Some('=')) => (Token::Op(Cmp::Ne), 2),
+            ('!', Some('~')) => (Token::Op(Cmp::NotLike), 2),
+            ('=', Some('=')) => (Token::Op(Cmp::Eq), 2),
+            ('>', Some('=')) => (Token::Op(Cmp::Ge), 2),
+            ('<', Some('=')) => (Token::Op(Cmp::Le), 2),
+            ('!', _) => (Token::Not, 1),
+            ('=', _) => (Token::Op(Cmp::Eq), 1),
+            ('>', _) => (Token::Op(Cmp::Gt), 1),
+            ('<', _) => (Token::Op(Cmp::Lt), 1),
+            ('~', _) => (Token::Op(Cmp::Like), 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('"', _) => {
+                let Some(end) = chars[i + 1..].iter().position(|c| *c == '"') else {
+                    return Err("unterminated string".to_string());
+                };
+                let value: String = chars[i + 1..i + 1 + end].iter().collect();
+                (Token::Quoted(value), end + 2)
+            }
+            _ => {
+                let len = chars[i..]
+                    .iter()
+                    .position(|c| c.is_whitespace() || "&|!=<>~()\"".contains(*c))
+                    .unwrap_or(chars.len() - i);
+                if len == 0 {
+                    return Err(format!("unexpected '{}'", c));
+                }
+                (Token::Word(chars[i..i + len].iter().collect()), len)
+            }
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) => format!("'{}'", word),
+        Token::Quoted(text) => format!("\"{}\"", text),
+        Token::Op(_) => "comparison".to_string(),
+        Token::And => "'&&'".to_string(),
+        Token::Or => "'||'".to_string(),
+        Token::Not => "'!'".to_string(),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.tokens.get(self.pos) == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                if !self.eat(&Token::Close) {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(expr)
+            }
+            Some(Token::Word(field)) => self.condition(&field),
+            Some(token) => Err(format!("expected a field, found {}", describe(&token))),
+            None => Err(format!("expected a field ({})", FIELDS)),
+        }
+    }
+
+    fn condition(&mut self, field: &str) -> Result<Expr, String> {
+        let field = field.to_lowercase();
+        let cmp = match self.tokens.get(self.pos) {
+            Some(Token::Op(cmp)) => {
+                let cmp = *cmp;
+                self.pos += 1;
+                Some(cmp)
+            }
+            _ => None,
+        };
+        let value = match cmp {
+            None => None,
+            Some(_) => match self.next() {
+                Some(Token::Word(value)) | Some(Token::Quoted(value)) => Some(value),
+                _ => return Err(format!("expected a value after '{}' comparison", field)),
+            },
+        };
+        let op_error = || format!("'{}' does not support that comparison", field);
+
+        let cond = match (field.as_str(), cmp, value) {
+            ("exported" | "documented", None, _) => {
+                if field == "exported" {
+                    Cond::Exported
+                } else {
+                    Cond::Documented
+                }
+            }
+            ("exported" | "documented", Some(Cmp::Eq | Cmp::Ne), Some(value)) => {
+                let yes = match value.to_lowercase().as_str() {
+                    "true" | "yes" => true,
+                    "false" | "no" => false,
+                    _ => return Err(format!("'{}' is true or false, not '{}'", field, value)),
+                };
+                let cond = if field == "exported" {
+                    Cond::Exported
+                } else {
+                    Cond::Documented
+                };
+                // `exported=false` and `exported!=true` both mean "not exported"
+                return Ok(if yes == (cmp == Some(Cmp::Eq)) {
+                    Expr::Cond(cond)
+                } else {
+                    Expr::Not(Box::new(Expr::Cond(cond)))
+                });
+            }
+            (_, None, _) if FIELDS.split(", ").any(|f| f == field) => {
+                return Err(format!(
+                    "'{}' needs a comparison, e.g. {}=...",
+                    field, field
+                ));
+            }
+            ("kind" | "type", Some(cmp @ (Cmp::Eq | Cmp::Ne)), Some(value)) => {
+                let kind = SymbolType::from_str(&value)
+                    .ok_or_else(|| format!("unknown kind '{}' (see cm kinds)", value))?;
+                return Ok(negate_if(cmp == Cmp::Ne, Cond::Kind(kind)));
+            }
+            ("lang" | "language", Some(cmp @ (Cmp::Eq | Cmp::Ne)), Some(value)) => {
+                let language = match Language::from_name(&value) {
+                    Language::Unknown => return Err(format!("unknown language '{}'", value)),
+                    language => language,
+                };
+                return Ok(negate_if(cmp == Cmp::Ne, Cond::Lang(language)));
+            }
+            ("name", Some(cmp), Some(value)) => match cmp {
+                Cmp::Eq | Cmp::Ne => return Ok(negate_if(cmp == Cmp::Ne, Cond::Name(value))),
+                Cmp::Like | Cmp::NotLike => {
+                    let pattern = NamePattern::parse(&value, false);
+                    let cond = Cond::NameLike(pattern, value.to_lowercase());
+                    return Ok(negate_if(cmp == Cmp::NotLike, cond));
+                }
+                _ => return Err(op_error()),
+            },
+            ("file" | "path", Some(cmp), Some(value)) => {
+                let value = value
+                    .trim_start_matches("./")
+                    .trim_end_matches('/')
+                    .to_string();
+                match cmp {
+                    Cmp::Eq | Cmp::Ne => return Ok(negate_if(cmp == Cmp::Ne, Cond::File(value))),
+                    Cmp::Like | Cmp::NotLike => {
+                        let cond = Cond::FileLike(glob_regex(&value)?);
+                        return Ok(negate_if(cmp == Cmp::NotLike, cond));
+                    }
+                    _ => return Err(op_error()),
+                }
+            }
+            ("lines" | "line", Some(cmp), Some(value)) => {
+                if matches!(cmp, Cmp::Like | Cmp::NotLike) {
+                    return Err(op_error());
+                }
+                let n: usize = value
+                    .parse()
+                    .map_err(|_| format!("'{}' compares with a number, not '{}'", field, value))?;
+                if field == "lines" {
+                    Cond::Lines(cmp, n)
+                } else {
+                    Cond::Line(cmp, n)
+                }
+            }
+            (_, Some(_), _) if FIELDS.split(", ").any(|f| f == field) => return Err(op_error()),
+            _ => return Err(format!("unknown field '{}' (fields: {})", field, FIELDS)),
+        };
+        Ok(Expr::Cond(cond))
+    }
+}
+
+fn negate_if(negate: bool, cond: Cond) -> Expr {
+    if negate {
+        Expr::Not(Box::new(Expr::Cond(cond)))
+    } else {
+        Expr::Cond(cond)
+    }
+}
+
+/// `**` crosses directories, `*` and `?` stay within one; anchored at a path component,
+/// and a match on a directory takes in everything under it
+pub(crate) fn glob_regex(glob: &str) -> Result<Regex, String> {
+    let mut pattern = String::from("(^|/)");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `src/**/x.rs` also matches `src/x.rs`
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push_str("(/.*)?$");
+    Regex::new(&pattern).map_err(|e| format!("invalid glob '{}': {}", glob, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+
+    #[test]
+    fn test_filter_matches_symbols() {
+        let source = "def short():\n    pass\n\ndef _private_helper():\n    \"\"\"Helps.\"\"\"\n    a = 1\n    b = 2\n    return a + b\n\nclass Parser:\n    def parse(self):\n        pass\n";
+        let file = index_file(
+            Path::new("./src/api/parse.py"),
+            source,
+            Language::Python,
+            None,
+        )
+        .unwrap();
+        let names = |expr: &str| -> Vec<String> {
+            let filter = Filter::parse(expr).unwrap();
+            file.symbols
+                .iter()
+                .filter(|s| filter.matches(s))
+                .map(|s| s.name.to_string())
+                .collect()
+        };
+
+        assert_eq!(names("kind=function && lines>3"), ["_private_helper"]);
+        assert_eq!(names("documented"), ["_private_helper"]);
+        assert_eq!(
+            names("kind=method || name~\"*er\""),
+            ["Parser", "_private_helper", "parse"]
+        );
+        assert_eq!(
+            names("!(kind=function) && file~\"src/**\""),
+            ["Parser", "parse"]
+        );
+        assert_eq!(
+            names("name~PARS && file~api && lang=py"),
+            ["Parser", "parse"]
+        );
+        assert_eq!(names("exported=false || line<=1"), ["short", "parse"]);
+        assert!(names("file~\"lib/**\" || file=other/parse.py").is_empty());
+        assert_eq!(names("file=api/parse.py && name=short"), ["short"]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (expr, error) in [
+            ("size>3", "unknown field 'size'"),
+            ("lines>many", "compares with a number"),
+            ("kind=gizmo", "unknown kind 'gizmo'"),
+            ("kind", "needs a comparison"),
+            ("lines~3", "does not support"),
+            ("(exported", "missing ')'"),
+            ("exported exported", "unexpected 'exported'"),
+            ("name=\"open", "unterminated string"),
+            ("exported &&", "expected a field"),
+        ] {
+            let err = Filter::parse(expr).unwrap_err();
+            assert!(err.contains(error), "{}: {}", expr, err);
+        }
+    }
+}
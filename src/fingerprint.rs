@@ -0,0 +1,202 @@
+//! Structural fingerprints: a hash of each file's sorted symbol signatures, and one over all
+//! files, so two checkouts with the same declarations fingerprint the same however their
+//! bodies, comments or formatting differ. `cm verify --fingerprint` records a baseline and
+//! later reports the files whose structure drifted from it (generated code that was not
+//! regenerated, a build that is not reproducible). The cache keeps the project hash too.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::failure::{ErrorCode, Failure};
+use crate::index::CodeIndex;
+use crate::models::Symbol;
+use crate::paths::DisplaySlash;
+
+/// Bumped when what goes into a file hash changes, so old baselines are not misread
+const FINGERPRINT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub version: u32,
+    /// Over every file path and file hash
+    pub hash: String,
+    pub symbol_count: usize,
+    /// File hashes by path relative to the root, `/`-separated
+    pub files: BTreeMap<String, String>,
+}
+
+/// Files whose structure differs from a baseline
+#[derive(Debug, Clone, Default)]
+pub struct Drift {
+    pub baseline: String,
+    pub current: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl Drift {
+    pub fn is_clean(&self) -> bool {
+        self.baseline == self.current
+    }
+}
+
+/// One line per symbol: kind, name with its parent, signature and visibility. Line numbers
+/// are left out, so moving code inside a file changes nothing.
+fn symbol_line(symbol: &Symbol, symbols: &[&Symbol]) -> String {
+    let parent = symbol
+        .parent_id
+        .and_then(|id| symbols.get(id))
+        .map_or("", |parent| parent.name.as_str());
+    format!(
+        "{}|{}::{}|{}|{}",
+        symbol.symbol_type.as_str(),
+        parent,
+        symbol.name,
+        symbol.signature.as_deref().unwrap_or(""),
+        if symbol.is_exported { "pub" } else { "" }
+    )
+}
+
+/// Hash of a file's symbol signatures, in sorted order
+pub fn file_hash(symbols: &[&Symbol]) -> String {
+    let mut lines: Vec<String> = symbols
+        .iter()
+        .map(|symbol| symbol_line(symbol, symbols))
+        .collect();
+    lines.sort_unstable();
+    let mut hasher = blake3::Hasher::new();
+    for line in &lines {
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("blake3:{}", hasher.finalize().to_hex())
+}
+
+/// Fingerprint of every file in `index`, paths taken relative to `root`
+pub fn compute(index: &CodeIndex, root: &Path) -> Fingerprint {
+    let files: BTreeMap<String, String> = index
+        .files()
+        .map(|file| {
+            let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+            (
+                relative.display_slash().to_string(),
+                file_hash(&index.get_file_symbols(&file.path)),
+            )
+        })
+        .collect();
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&FINGERPRINT_VERSION.to_le_bytes());
+    for (path, hash) in &files {
+        hasher.update(path.as_bytes());
+        hasher.update(b"\t");
+        hasher.update(hash.as_bytes());
+        hasher.update(b"\n");
+    }
+    Fingerprint {
+        version: FINGERPRINT_VERSION,
+        hash: format!("blake3:{}", hasher.finalize().to_hex()),
+        symbol_count: index.total_symbols(),
+        files,
+    }
+}
+
+pub fn save(path: &Path, fingerprint: &Fingerprint) -> Result<()> {
+    let json = serde_json::to_string_pretty(fingerprint)?;
+    fs::write(path, json + "\n")
+        .with_context(|| format!("Failed to write fingerprint {}", path.display_slash()))
+}
+
+pub fn load(path: &Path) -> Result<Fingerprint> {
+    if !path.exists() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!(
+                "No fingerprint at {}; record one with --record",
+                path.display_slash()
+            )
+        ));
+    }
+    let json = fs::read_to_string(path).context("Failed to read fingerprint")?;
+    let fingerprint: Fingerprint =
+        serde_json::from_str(&json).context("Failed to parse fingerprint")?;
+    if fingerprint.version != FINGERPRINT_VERSION {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "Fingerprint {} has version {}, this cm writes version {}; record it again",
+                path.display_slash(),
+                fingerprint.version,
+                FINGERPRINT_VERSION
+            )
+        ));
+    }
+    Ok(fingerprint)
+}
+
+pub fn compare(baseline: &Fingerprint, current: &Fingerprint) -> Drift {
+    let mut drift = Drift {
+        baseline: baseline.hash.clone(),
+        current: current.hash.clone(),
+        ..Drift::default()
+    };
+    for (path, hash) in &current.files {
+        match baseline.files.get(path) {
+            None => drift.added.push(path.clone()),
+            Some(old) if old != hash => drift.changed.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    drift.removed = baseline
+        .files
+        .keys()
+        .filter(|path| !current.files.contains_key(*path))
+        .cloned()
+        .collect();
+    drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+    use crate::models::Language;
+
+    fn fingerprint_of(files: &[(&str, &str)]) -> Fingerprint {
+        let root = Path::new("/project");
+        let mut index = CodeIndex::new();
+        for (name, source) in files {
+            let path = root.join(name);
+            index.add_file(index_file(&path, source, Language::Python, None).unwrap());
+        }
+        compute(&index, root)
+    }
+
+    #[test]
+    fn test_fingerprint_drift() {
+        let baseline = fingerprint_of(&[
+            ("api.py", "def load(path):\n    return 1\n\nclass Store:\n    def get(self, key):\n        pass\n"),
+            ("util.py", "def helper():\n    pass\n"),
+        ]);
+        // Bodies, order and comments are not structure
+        let same = fingerprint_of(&[
+            ("util.py", "# helpers\ndef helper():\n    return None\n"),
+            ("api.py", "class Store:\n    def get(self, key):\n        return key\n\ndef load(path):\n    return 2\n"),
+        ]);
+        assert_eq!(same.hash, baseline.hash);
+        assert!(compare(&baseline, &same).is_clean());
+
+        let current = fingerprint_of(&[
+            ("api.py", "def load(path, mode):\n    return 1\n\nclass Store:\n    def get(self, key):\n        pass\n"),
+            ("gen.py", "def generated():\n    pass\n"),
+        ]);
+        let drift = compare(&baseline, &current);
+        assert!(!drift.is_clean());
+        assert_eq!(drift.changed, ["api.py"]);
+        assert_eq!(drift.added, ["gen.py"]);
+        assert_eq!(drift.removed, ["util.py"]);
+    }
+}
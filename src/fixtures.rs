@@ -0,0 +1,532 @@
+//! `cm test-deps --fixtures`: the shared test infrastructure tests lean on, and which tests use
+//! each piece. Covers pytest fixtures (requested by parameter, `usefixtures` or `autouse`),
+//! mocks (`jest.mock`, `vi.mock`, `jest.spyOn`, `patch`, `patch.object`, `monkeypatch.setattr`)
+//! and Go test helpers (non-test functions in `_test.go` files taking a `*testing.T` or calling
+//! `t.Helper()`).
+
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::callgraph::{is_test_file, list_tests, TestCase};
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+use crate::paths::{self, DisplaySlash};
+use crate::scan::regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FixtureKind {
+    /// pytest fixture
+    Fixture,
+    /// A module, function or method replaced for the test's duration
+    Mock,
+    /// Go test helper
+    Helper,
+}
+
+impl FixtureKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FixtureKind::Fixture => "fixture",
+            FixtureKind::Mock => "mock",
+            FixtureKind::Helper => "helper",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FixtureUse {
+    pub test: TestCase,
+    /// Where the test asks for it: the test's own line for fixtures, the call for mocks and
+    /// helpers
+    pub line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    /// Module path for module mocks, `object.attribute` for method mocks
+    pub name: String,
+    pub kind: FixtureKind,
+    /// None for mocks, which are set up where they are used
+    pub defined_at: Option<(String, usize)>,
+    pub uses: Vec<FixtureUse>,
+}
+
+/// A test file's tests with their symbols, which carry the ranges and signatures
+struct FileTests<'a> {
+    path: &'a Path,
+    language: Language,
+    lines: Vec<String>,
+    symbols: Vec<&'a Symbol>,
+    tests: Vec<(TestCase, &'a Symbol)>,
+}
+
+impl FileTests<'_> {
+    /// Tests set up by a line: the test it is in or decorates, or else every test in the
+    /// innermost `describe` block or test class around it, or else every test in the file
+    fn tests_at(&self, line: usize) -> Vec<&TestCase> {
+        let mut owners: Vec<&Symbol> = self
+            .symbols
+            .iter()
+            .filter(|s| {
+                (s.line_start <= line && s.line_end >= line) || self.decorates(line, s.line_start)
+            })
+            .copied()
+            .collect();
+        owners.sort_by_key(|s| std::cmp::Reverse(s.line_start));
+
+        for owner in owners {
+            if let Some((test, _)) = self
+                .tests
+                .iter()
+                .find(|(_, symbol)| std::ptr::eq(*symbol, owner))
+            {
+                return vec![test];
+            }
+            if owner.name.starts_with("describe:") || owner.symbol_type == SymbolType::Class {
+                return self.tests_within(owner.line_start, owner.line_end);
+            }
+        }
+        self.tests.iter().map(|(test, _)| test).collect()
+    }
+
+    fn tests_within(&self, start: usize, end: usize) -> Vec<&TestCase> {
+        self.tests
+            .iter()
+            .filter(|(_, symbol)| symbol.line_start >= start && symbol.line_end <= end)
+            .map(|(test, _)| test)
+            .collect()
+    }
+
+    /// Whether `line` is among the Python decorators above the declaration on `start`
+    fn decorates(&self, line: usize, start: usize) -> bool {
+        self.language == Language::Python
+            && line < start
+            && decorators(&self.lines, start - 1).0 < line
+    }
+}
+
+/// Decorators above the declaration at `index` (0-based): the first line they start on and
+/// their text. Arguments may run over several lines.
+pub(crate) fn decorators(lines: &[String], index: usize) -> (usize, String) {
+    let mut first = index;
+    let mut depth = 0i32;
+    for i in (0..index).rev() {
+        let line = lines[i].trim();
+        depth += line.matches([')', ']']).count() as i32 - line.matches(['(', '[']).count() as i32;
+        if depth <= 0 && !line.starts_with('@') && !line.starts_with('#') && !line.is_empty() {
+            break;
+        }
+        first = i;
+    }
+    while first < index && !lines[first].trim_start().starts_with('@') {
+        first += 1;
+    }
+    (first, lines[first..index].join("\n"))
+}
+
+/// The names a pytest test or fixture requests through its parameters
+fn parameters(symbol: &Symbol) -> Vec<&str> {
+    let Some(signature) = symbol.signature.as_deref() else {
+        return Vec::new();
+    };
+    let inner = signature
+        .trim()
+        .trim_start_matches('(')
+        .split(')')
+        .next()
+        .unwrap_or_default();
+    inner
+        .split(',')
+        .map(|param| param.split([':', '=']).next().unwrap_or_default().trim())
+        .filter(|param| !param.is_empty() && *param != "self" && !param.starts_with('*'))
+        .collect()
+}
+
+/// `patch`, `mock.patch`, `mocker.patch`, but not `requests.patch`
+const PATCH: &str = r"(?:^|[^\w.])(?:(?:unittest\.mock|mock|mocker)\.)?patch";
+
+/// What a test mocks: group 1 is a module path, class or `object.attribute`, or with group 2
+/// the object whose attribute it is
+pub(crate) fn mock_patterns(language: Language) -> Vec<Regex> {
+    match language {
+        Language::JavaScript | Language::TypeScript => vec![
+            // `jest.mock('./api')`, `vi.mock("axios")`
+            regex(r#"\b(?:jest|vi)\.(?:mock|doMock|unstable_mockModule)\(\s*['"`]([^'"`]+)"#),
+            // `jest.spyOn(api, 'fetch')`
+            regex(r#"\b(?:jest|vi)\.spyOn\(\s*([\w.$]+)\s*,\s*['"`]([\w$]+)"#),
+        ],
+        Language::Python => vec![
+            // `@patch("app.api.fetch")`, `mocker.patch('app.clock')`, `mock.patch.dict(...)`
+            regex(&format!(r#"{}(?:\.dict)?\(\s*['"]([^'"]+)['"]"#, PATCH)),
+            // `patch.object(Client, "send")`
+            regex(&format!(
+                r#"{}\.object\(\s*([\w.]+)\s*,\s*['"](\w+)['"]"#,
+                PATCH
+            )),
+            // `monkeypatch.setattr("app.clock.now", v)`, `monkeypatch.setattr(time, "sleep", f)`
+            regex(r#"\bmonkeypatch\.setattr\(\s*['"]([\w.]+)['"]"#),
+            regex(r#"\bmonkeypatch\.setattr\(\s*([\w.]+)\s*,\s*['"](\w+)['"]"#),
+        ],
+        Language::Java | Language::Kotlin => vec![
+            // `mock(Client.class)`, `spy(Client::class.java)`
+            regex(r"\b(?:mock|spy)\(\s*([\w.]+?)(?:\.class|::class(?:\.java)?)\b"),
+            // `@Mock private Client client;`, `@MockBean lateinit var client: Client`
+            regex(
+                r"@(?:Mock|Spy|MockBean|SpyBean|InjectMocks)\b(?:\([^)]*\))?\s+(?:(?:private|protected|public|final)\s+)*([A-Z][\w.]*)(?:<[^>]*>)?\s+\w+\s*;",
+            ),
+            regex(
+                r"@(?:Mock|Spy|MockBean|SpyBean|MockK|RelaxedMockK|SpyK)\b(?:\([^)]*\))?\s+(?:(?:private|protected|internal|lateinit)\s+)*va[rl]\s+\w+\s*:\s*([A-Z][\w.]*)",
+            ),
+            // MockK's `mockk<Client>()` and `mockkObject(Clock)`
+            regex(r"\b(?:mockk|spyk|mockkClass)\s*[<(]\s*([A-Z][\w.]*?)\s*(?:>|::class)"),
+            regex(r"\bmockk(?:Object|Static)\(\s*([\w.]+)"),
+            // Stubs and verifications: `when(repo.find(1))`, `given(...)`, `verify(repo).save(x)`,
+            // `doReturn(x).when(repo).find(1)`, `every { repo.find(1) }`
+            regex(r"\b(?:when|given)\(\s*([\w.]+)\.(\w+)\s*\("),
+            regex(r"(?:\bverify|\.when)\(\s*(\w+)\s*(?:,[^)]*)?\)\s*\.(\w+)\s*\("),
+            regex(
+                r"\b(?:every|coEvery|verify|coVerify)\s*(?:\([^)]*\))?\s*\{\s*([\w.]+)\.(\w+)\s*\(",
+            ),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn is_conftest(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "conftest.py")
+}
+
+type Found = BTreeMap<(FixtureKind, String), Fixture>;
+
+fn entry<'a>(found: &'a mut Found, kind: FixtureKind, name: &str) -> &'a mut Fixture {
+    found
+        .entry((kind, name.to_string()))
+        .or_insert_with(|| Fixture {
+            name: name.to_string(),
+            kind,
+            defined_at: None,
+            uses: Vec::new(),
+        })
+}
+
+/// Whether `path` is `scope` or inside it; no scope takes in everything
+fn in_scope(path: &str, scope: Option<&Path>, cache: &mut HashMap<String, bool>) -> bool {
+    let Some(scope) = scope else {
+        return true;
+    };
+    *cache.entry(path.to_string()).or_insert_with(|| {
+        paths::canonicalize(Path::new(path)).is_ok_and(|path| path.starts_with(scope))
+    })
+}
+
+/// Fixtures, mocks and test helpers with the tests using them. With `scope` (a canonical
+/// file or directory), only uses from tests under it count, and only what they use or what is
+/// defined there is reported.
+pub fn find_fixtures(index: &CodeIndex, scope: Option<&Path>) -> Vec<Fixture> {
+    let mut by_file: HashMap<String, Vec<TestCase>> = HashMap::new();
+    for test in list_tests(index) {
+        by_file
+            .entry(test.file_path.clone())
+            .or_default()
+            .push(test);
+    }
+
+    let mut files = Vec::new();
+    for file in index.files() {
+        let language = file.language;
+        if !matches!(
+            language,
+            Language::Python | Language::JavaScript | Language::TypeScript | Language::Go
+        ) || !(is_test_file(&file.path, language) || is_conftest(&file.path))
+        {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&file.path) else {
+            continue;
+        };
+        let symbols = index.get_file_symbols(&file.path);
+        let key = file.path.display_slash().to_string();
+        let tests = by_file
+            .remove(&key)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|test| {
+                let symbol = symbols.iter().find(|s| {
+                    s.line_start == test.line
+                        && matches!(s.symbol_type, SymbolType::Function | SymbolType::Method)
+                })?;
+                Some((test, *symbol))
+            })
+            .collect();
+        files.push(FileTests {
+            path: &file.path,
+            language,
+            lines: content.lines().map(str::to_string).collect(),
+            symbols,
+            tests,
+        });
+    }
+
+    let mut found: Found = BTreeMap::new();
+    let fixture_name = regex(r#"\bname\s*=\s*['"](\w+)['"]"#);
+    let use_fixtures = regex(r#"usefixtures\(([^)]*)\)"#);
+    let quoted = regex(r#"['"](\w+)['"]"#);
+    let mut mocks: HashMap<Language, Vec<Regex>> = HashMap::new();
+
+    for file in &files {
+        let path = file.path.display_slash().to_string();
+
+        // pytest fixtures, and the autouse ones with the tests they apply to
+        let mut autouse: Vec<(String, Vec<&TestCase>)> = Vec::new();
+        if file.language == Language::Python {
+            for symbol in &file.symbols {
+                if symbol.symbol_type != SymbolType::Function
+                    && symbol.symbol_type != SymbolType::Method
+                {
+                    continue;
+                }
+                let (_, decorators) = decorators(&file.lines, symbol.line_start - 1);
+                if !decorators.contains("@pytest.fixture") && !decorators.contains("@fixture") {
+                    continue;
+                }
+                let name = fixture_name
+                    .captures(&decorators)
+                    .and_then(|caps| caps.get(1))
+                    .map_or(symbol.name.as_str(), |m| m.as_str());
+                entry(&mut found, FixtureKind::Fixture, name).defined_at =
+                    Some((path.clone(), symbol.line_start));
+                if decorators.contains("autouse=True") {
+                    let class = file.symbols.iter().find(|s| {
+                        s.symbol_type == SymbolType::Class
+                            && s.line_start < symbol.line_start
+                            && s.line_end >= symbol.line_end
+                    });
+                    let tests = match class {
+                        Some(class) => file.tests_within(class.line_start, class.line_end),
+                        None => Vec::new(),
+                    };
+                    autouse.push((name.to_string(), tests));
+                }
+            }
+        }
+        for (name, mut tests) in autouse {
+            // Outside a class an autouse fixture covers its module, or a conftest's directory
+            if tests.is_empty() {
+                let dir = file.path.parent().unwrap_or(Path::new(""));
+                for other in &files {
+                    let covered = if is_conftest(file.path) {
+                        other.language == Language::Python && other.path.starts_with(dir)
+                    } else {
+                        std::ptr::eq(other, file)
+                    };
+                    if covered {
+                        tests.extend(other.tests.iter().map(|(test, _)| test));
+                    }
+                }
+            }
+            let target = entry(&mut found, FixtureKind::Fixture, &name);
+            target.uses.extend(tests.into_iter().map(|test| FixtureUse {
+                test: test.clone(),
+                line: test.line,
+            }));
+        }
+
+        // Go test helpers
+        if file.language == Language::Go {
+            for symbol in &file.symbols {
+                if symbol.symbol_type != SymbolType::Function
+                    || file.tests.iter().any(|(_, s)| std::ptr::eq(*s, *symbol))
+                {
+                    continue;
+                }
+                let signature = symbol.signature.as_deref().unwrap_or_default();
+                let body =
+                    &file.lines[symbol.line_start - 1..symbol.line_end.min(file.lines.len())];
+                if signature.contains("testing.T")
+                    || signature.contains("testing.B")
+                    || body.iter().any(|line| line.contains(".Helper()"))
+                {
+                    entry(&mut found, FixtureKind::Helper, &symbol.name).defined_at =
+                        Some((path.clone(), symbol.line_start));
+                }
+            }
+        }
+
+        // Mocks
+        let patterns = mocks
+            .entry(file.language)
+            .or_insert_with(|| mock_patterns(file.language));
+        for (i, line) in file.lines.iter().enumerate() {
+            let trimmed = line.trim_start();
+            if patterns.is_empty() || trimmed.starts_with("//") || trimmed.starts_with('#') {
+                continue;
+            }
+            for pattern in patterns.iter() {
+                for caps in pattern.captures_iter(line) {
+                    let name = match (caps.get(1), caps.get(2)) {
+                        (Some(object), Some(attribute)) => {
+                            format!("{}.{}", object.as_str(), attribute.as_str())
+                        }
+                        (Some(target), None) => target.as_str().to_string(),
+                        _ => continue,
+                    };
+                    let target = entry(&mut found, FixtureKind::Mock, &name);
+                    target
+                        .uses
+                        .extend(file.tests_at(i + 1).into_iter().map(|test| FixtureUse {
+                            test: test.clone(),
+                            line: i + 1,
+                        }));
+                }
+            }
+        }
+    }
+
+    // Fixtures by parameter and `usefixtures`, helpers by call
+    let helper_call = regex(r"\b([A-Za-z_]\w*)\s*\(");
+    for file in &files {
+        for (test, symbol) in &file.tests {
+            match file.language {
+                Language::Python => {
+                    let mut requested: Vec<String> =
+                        parameters(symbol).into_iter().map(str::to_string).collect();
+                    let mut starts = vec![symbol.line_start];
+                    starts.extend(test.suites.iter().filter_map(|suite| {
+                        file.symbols
+                            .iter()
+                            .find(|s| {
+                                s.name == suite.as_str() && s.symbol_type == SymbolType::Class
+                            })
+                            .map(|class| class.line_start)
+                    }));
+                    for start in starts {
+                        let (_, decorators) = decorators(&file.lines, start - 1);
+                        for group in use_fixtures.captures_iter(&decorators) {
+                            requested.extend(
+                                quoted
+                                    .captures_iter(&group[1])
+                                    .map(|caps| caps[1].to_string()),
+                            );
+                        }
+                    }
+                    for name in requested {
+                        if let Some(target) = found.get_mut(&(FixtureKind::Fixture, name)) {
+                            target.uses.push(FixtureUse {
+                                test: test.clone(),
+                                line: test.line,
+                            });
+                        }
+                    }
+                }
+                Language::Go => {
+                    let end = symbol.line_end.min(file.lines.len());
+                    for (i, line) in file.lines[symbol.line_start..end].iter().enumerate() {
+                        for caps in helper_call.captures_iter(line) {
+                            let key = (FixtureKind::Helper, caps[1].to_string());
+                            if let Some(target) = found.get_mut(&key) {
+                                target.uses.push(FixtureUse {
+                                    test: test.clone(),
+                                    line: symbol.line_start + i + 1,
+                                });
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut cache = HashMap::new();
+    let mut fixtures = Vec::new();
+    for (_, mut fixture) in found {
+        let defined_here = fixture
+            .defined_at
+            .as_ref()
+            .is_some_and(|(path, _)| in_scope(path, scope, &mut cache));
+        fixture
+            .uses
+            .retain(|u| in_scope(&u.test.file_path, scope, &mut cache));
+        fixture.uses.sort_by(|a, b| {
+            (&a.test.file_path, a.test.line, a.line).cmp(&(&b.test.file_path, b.test.line, b.line))
+        });
+        fixture
+            .uses
+            .dedup_by(|a, b| a.test.file_path == b.test.file_path && a.test.line == b.test.line);
+        if defined_here || !fixture.uses.is_empty() {
+            fixtures.push(fixture);
+        }
+    }
+    fixtures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+
+    #[test]
+    fn test_fixtures_mocks_and_helpers_map_to_tests() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "conftest.py",
+                "import pytest\n\n@pytest.fixture(\n    scope=\"session\",\n)\ndef db():\n    yield 1\n\n@pytest.fixture(autouse=True)\ndef clean():\n    pass\n\n@pytest.fixture(name=\"api\")\ndef api_client(db):\n    return 2\n\n@pytest.fixture\ndef unused():\n    pass\n",
+                Language::Python,
+            ),
+            (
+                "test_pay.py",
+                "from unittest import mock\n\n@mock.patch(\"app.gateway.send\")\ndef test_charge(send, db, api):\n    pass\n\nclass TestRefund:\n    @pytest.mark.usefixtures(\"db\")\n    def test_refund(self):\n        pass\n",
+                Language::Python,
+            ),
+            (
+                "cart.test.ts",
+                "jest.mock('./api');\n\ndescribe('Cart', () => {\n  it('adds', () => {\n    jest.spyOn(store, 'save');\n  });\n  it('empties', () => {});\n});\n",
+                Language::TypeScript,
+            ),
+            (
+                "pay_test.go",
+                "package pay\n\nfunc newServer(t *testing.T) *Server {\n\tt.Helper()\n\treturn nil\n}\n\nfunc TestCharge(t *testing.T) {\n\ts := newServer(t)\n\t_ = s\n}\n",
+                Language::Go,
+            ),
+        ];
+        let index = index_of(dir.path(), &files);
+
+        let fixtures = find_fixtures(&index, None);
+        let users = |kind: FixtureKind, name: &str| -> Vec<String> {
+            let fixture = fixtures
+                .iter()
+                .find(|f| f.kind == kind && f.name == name)
+                .unwrap_or_else(|| panic!("{} not found", name));
+            fixture.uses.iter().map(|u| u.test.name.clone()).collect()
+        };
+
+        assert_eq!(
+            users(FixtureKind::Fixture, "db"),
+            ["test_charge", "test_refund"]
+        );
+        assert_eq!(users(FixtureKind::Fixture, "api"), ["test_charge"]);
+        assert_eq!(
+            users(FixtureKind::Fixture, "clean"),
+            ["test_charge", "test_refund"]
+        );
+        assert!(users(FixtureKind::Fixture, "unused").is_empty());
+        assert_eq!(
+            users(FixtureKind::Mock, "app.gateway.send"),
+            ["test_charge"]
+        );
+        assert_eq!(users(FixtureKind::Mock, "./api"), ["adds", "empties"]);
+        assert_eq!(users(FixtureKind::Mock, "store.save"), ["adds"]);
+        assert_eq!(users(FixtureKind::Helper, "newServer"), ["TestCharge"]);
+        let helper = fixtures.iter().find(|f| f.name == "newServer").unwrap();
+        assert_eq!(helper.uses[0].line, 9);
+
+        // Scoped to one test file, fixtures it does not use and cannot define drop out
+        let scope = paths::canonicalize(&dir.path().join("cart.test.ts")).unwrap();
+        let scoped = find_fixtures(&index, Some(&scope));
+        assert!(scoped.iter().all(|f| f.kind == FixtureKind::Mock));
+        assert_eq!(scoped.len(), 2);
+    }
+}
@@ -0,0 +1,162 @@
+//! `cm flags`: feature flag keys and every place that checks them. A check is a call
+//! matching one of the detection patterns (`isEnabled(`, `variation(`, ...) whose first
+//! argument is a string literal; that literal is the flag key.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::callgraph;
+use crate::index::CodeIndex;
+use crate::models::Language;
+use crate::routes::{args, first_string, group_text, split_args};
+use crate::value_refs::enclosing_symbol;
+
+/// Used when neither `--pattern` nor `[flags] patterns` is given: LaunchDarkly, Unleash,
+/// PostHog, Flipt/OpenFeature-style and home-grown `feature("x")` helpers
+pub const DEFAULT_PATTERNS: &[&str] = &[
+    r"\bis_?[Ee]nabled\s*\(",
+    r"\bis_?[Ff]eature_?[Ee]nabled\s*\(",
+    r"\b[Ff]eature_?[Ee]nabled\s*\(",
+    r"[Vv]ariation(?:Detail)?\s*\(",
+    r"\b(?:get|use)_?[Ff]eature_?[Ff]lag(?:_?[Pp]ayload)?\s*\(",
+    r"\buse_?[Ff]lag\s*\(",
+    r"\b[Gg]et(?:Boolean|String|Integer|Number|Object)Value\s*\(",
+    r"\bfeature\s*\(",
+];
+
+#[derive(Debug, Clone)]
+pub struct FlagCheck {
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// The source line, trimmed
+    pub context: String,
+    /// Innermost symbol containing the check
+    pub enclosing: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Flag {
+    pub key: String,
+    pub checks: Vec<FlagCheck>,
+}
+
+/// Compile detection patterns, naming the one that is not a valid regex
+pub fn compile_patterns<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|p| {
+            Regex::new(p.as_ref()).with_context(|| format!("Invalid flag pattern: {}", p.as_ref()))
+        })
+        .collect()
+}
+
+/// Every flag checked in a non-test file, sorted by key
+pub fn find_flags(index: &CodeIndex, patterns: &[Regex]) -> Result<Vec<Flag>> {
+    let mut flags: BTreeMap<String, Vec<FlagCheck>> = BTreeMap::new();
+
+    for file in index.files() {
+        if file.language == Language::Markdown || callgraph::is_test_file(&file.path, file.language)
+        {
+            continue;
+        }
+        let content = fs::read_to_string(&file.path).unwrap_or_default();
+        for (key, line, context) in flags_in(&content, patterns) {
+            flags.entry(key).or_default().push(FlagCheck {
+                file_path: file.path.clone(),
+                line,
+                context,
+                enclosing: enclosing_symbol(index, &file.path, line).map(|s| s.name.to_string()),
+            });
+        }
+    }
+
+    Ok(flags
+        .into_iter()
+        .map(|(key, mut checks)| {
+            checks.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+            Flag { key, checks }
+        })
+        .collect())
+}
+
+/// `"new-checkout"` or `flag = "new-checkout"` -> `new-checkout`
+fn literal_arg(arg: &str) -> Option<String> {
+    let value = match arg.split_once(['=', ':']) {
+        Some((name, value)) if name.trim().chars().all(|c| c.is_alphanumeric() || c == '_') => {
+            value.trim()
+        }
+        _ => arg,
+    };
+    let quote = value.chars().next()?;
+    if !matches!(quote, '"' | '\'' | '`') || !value.ends_with(quote) || value.len() < 2 {
+        return None;
+    }
+    first_string(value).filter(|key| !key.is_empty() && !key.contains("${"))
+}
+
+/// (key, 1-based line, trimmed source line) of every flag check in `content`
+pub fn flags_in(content: &str, patterns: &[Regex]) -> Vec<(String, usize, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut found = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("//") || trimmed.starts_with('#') && !trimmed.starts_with("#[") {
+            continue;
+        }
+        let mut keys: Vec<(usize, String)> = Vec::new();
+        for pattern in patterns {
+            for m in pattern.find_iter(line) {
+                let call = group_text(&lines, i, m.start(), false);
+                if let Some(key) = split_args(args(&call)).first().and_then(|a| literal_arg(a)) {
+                    keys.push((m.start(), key));
+                }
+            }
+        }
+        // Overlapping patterns (`isFeatureEnabled(` and `FeatureEnabled(`) find one call twice
+        keys.sort();
+        keys.dedup_by(|a, b| a.1 == b.1);
+        for (_, key) in keys {
+            found.push((key, i + 1, line.trim().to_string()));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_default_and_custom_patterns() {
+        let defaults = compile_patterns(DEFAULT_PATTERNS).unwrap();
+        let content = r#"if (client.isEnabled("new-checkout")) {
+  const v = ld.variation('pricing-v2', user, false);
+}
+// isEnabled("commented-out")
+if flags.is_feature_enabled(flag="dark_mode", user=u):
+    pass
+if (settings.isEnabled()) {}
+toggle.isEnabled(name)
+"#;
+        let keys: Vec<(String, usize)> = flags_in(content, &defaults)
+            .into_iter()
+            .map(|(key, line, _)| (key, line))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("new-checkout".to_string(), 1),
+                ("pricing-v2".to_string(), 2),
+                ("dark_mode".to_string(), 5)
+            ]
+        );
+
+        let custom = compile_patterns(&[r"cfg!?\(\s*feature"]).unwrap();
+        let keys = flags_in("#[cfg(feature = \"wasm\")]\npub mod wasm;\n", &custom);
+        assert_eq!(keys[0].0, "wasm");
+        assert!(compile_patterns(&["isEnabled("]).is_err());
+    }
+}
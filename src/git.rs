@@ -1,4 +1,7 @@
+use crate::failure::{ErrorCode, Failure};
+use crate::paths::DisplaySlash;
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -30,7 +33,10 @@ pub fn get_repo_root(path: &Path) -> Result<PathBuf> {
         .context("Failed to execute git command")?;
 
     if !output.status.success() {
-        anyhow::bail!("Not a git repository: {}", path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("Not a git repository: {}", path.display_slash())
+        ));
     }
 
     let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -52,11 +58,14 @@ pub fn resolve_commit(path: &Path, commit_ref: &str) -> Result<String> {
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!(
-            "Invalid commit reference '{}': {}",
-            commit_ref,
-            stderr.trim()
-        );
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "Invalid commit reference '{}': {}",
+                commit_ref,
+                stderr.trim()
+            )
+        ));
     }
 
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
@@ -75,7 +84,7 @@ pub fn get_file_at_commit(
         file_path
     };
 
-    let git_path = format!("{}:{}", commit, relative_path.display());
+    let git_path = format!("{}:{}", commit, relative_path.display_slash());
 
     let output = Command::new("git")
         .args([
@@ -92,7 +101,10 @@ pub fn get_file_at_commit(
         if stderr.contains("does not exist") || stderr.contains("exists on disk, but not in") {
             return Ok(None);
         }
-        anyhow::bail!("git show failed: {}", stderr.trim());
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git show failed: {}", stderr.trim())
+        ));
     }
 
     Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
@@ -103,28 +115,139 @@ pub fn get_changed_files(
     commit: &str,
     subpath: Option<&Path>,
 ) -> Result<ChangedFiles> {
+    diff_name_status(repo_path, &[commit, "HEAD"], subpath)
+}
+
+/// Files that differ between `commit` and the working tree, including uncommitted edits
+pub fn get_worktree_changed_files(
+    repo_path: &Path,
+    commit: &str,
+    subpath: Option<&Path>,
+) -> Result<ChangedFiles> {
+    diff_name_status(repo_path, &[commit], subpath)
+}
+
+/// `-- <subpath>` relative to the repository root, or nothing for the whole repository
+fn pathspec(repo_root: &Path, subpath: Option<&Path>) -> Vec<String> {
+    let Some(sp) = subpath else {
+        return Vec::new();
+    };
+    let relative = if sp.is_absolute() {
+        sp.strip_prefix(repo_root).unwrap_or(sp)
+    } else {
+        sp
+    };
+    if relative == Path::new(".") || relative == Path::new("") {
+        return Vec::new();
+    }
+    vec!["--".to_string(), relative.to_string_lossy().to_string()]
+}
+
+/// Line ranges `git diff -U0` reports changed between `from` and `to`, or between `from` and
+/// the working tree (uncommitted edits included) when `to` is None. Renames show up as a
+/// deletion and an addition.
+pub fn diff_hunks(
+    repo_path: &Path,
+    from: &str,
+    to: Option<&str>,
+    subpath: Option<&Path>,
+) -> Result<Vec<FileHunks>> {
     let repo_root = get_repo_root(repo_path)?;
 
     let mut args = vec![
         "-C".to_string(),
         repo_root.to_string_lossy().to_string(),
         "diff".to_string(),
-        "--name-status".to_string(),
-        commit.to_string(),
-        "HEAD".to_string(),
+        "-U0".to_string(),
+        "--no-color".to_string(),
+        "--no-ext-diff".to_string(),
+        "--no-renames".to_string(),
+        from.to_string(),
     ];
+    args.extend(to.map(str::to_string));
+    args.extend(pathspec(&repo_root, subpath));
+
+    let output = Command::new("git")
+        .args(&args)
+        .output()
+        .context("Failed to execute git diff")?;
 
-    if let Some(sp) = subpath {
-        let relative = if sp.is_absolute() {
-            sp.strip_prefix(&repo_root).unwrap_or(sp)
-        } else {
-            sp
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git diff failed: {}", stderr.trim())
+        ));
+    }
+
+    Ok(parse_hunks(
+        &String::from_utf8_lossy(&output.stdout),
+        &repo_root,
+    ))
+}
+
+fn parse_hunks(diff: &str, repo_root: &Path) -> Vec<FileHunks> {
+    // `12` or `12,3`; a missing count is 1
+    let range = |text: &str| -> Option<(usize, usize)> {
+        let (start, count) = text.split_once(',').unwrap_or((text, "1"));
+        Some((start.parse().ok()?, count.parse().ok()?))
+    };
+    let side = |text: &str, prefix: &str| -> Option<PathBuf> {
+        text.strip_prefix(prefix).map(|path| repo_root.join(path))
+    };
+
+    let mut files: Vec<FileHunks> = Vec::new();
+    let mut in_header = false;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            files.push(FileHunks::default());
+            in_header = true;
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
         };
-        if relative != Path::new(".") && relative != Path::new("") {
-            args.push("--".to_string());
-            args.push(relative.to_string_lossy().to_string());
+        if in_header {
+            if let Some(old) = line.strip_prefix("--- ") {
+                file.old_path = side(old, "a/");
+            } else if let Some(new) = line.strip_prefix("+++ ") {
+                file.new_path = side(new, "b/");
+            }
+        }
+        // `@@ -12,3 +12,4 @@ context`
+        let Some(header) = line.strip_prefix("@@ -") else {
+            continue;
+        };
+        in_header = false;
+        let mut parts = header.split(' ');
+        let old = parts.next().and_then(range);
+        let new = parts
+            .next()
+            .and_then(|new| new.strip_prefix('+'))
+            .and_then(range);
+        if let (Some(old), Some(new)) = (old, new) {
+            file.old_ranges.push(old);
+            file.new_ranges.push(new);
         }
     }
+    files
+}
+
+fn diff_name_status(
+    repo_path: &Path,
+    revisions: &[&str],
+    subpath: Option<&Path>,
+) -> Result<ChangedFiles> {
+    let repo_root = get_repo_root(repo_path)?;
+
+    let mut args = vec![
+        "-C".to_string(),
+        repo_root.to_string_lossy().to_string(),
+        "diff".to_string(),
+        "--name-status".to_string(),
+    ];
+    args.extend(revisions.iter().map(|r| r.to_string()));
+    args.extend(pathspec(&repo_root, subpath));
 
     let output = Command::new("git")
         .args(&args)
@@ -133,7 +256,10 @@ pub fn get_changed_files(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("git diff failed: {}", stderr.trim());
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git diff failed: {}", stderr.trim())
+        ));
     }
 
     let mut changed = ChangedFiles {
@@ -163,6 +289,147 @@ pub fn get_changed_files(
     Ok(changed)
 }
 
+/// Unix time of the last commit touching each file within the `limit` most recent commits,
+/// keyed by absolute path
+pub fn last_commit_times(repo_path: &Path, limit: usize) -> Result<HashMap<PathBuf, i64>> {
+    let repo_root = get_repo_root(repo_path)?;
+
+    let output = Command::new("git")
+        .args([
+            "-C",
+            repo_root.to_string_lossy().as_ref(),
+            "log",
+            &format!("-{}", limit),
+            "--format=%x00%ct",
+            "--name-only",
+        ])
+        .output()
+        .context("Failed to execute git log")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git log failed: {}", stderr.trim())
+        ));
+    }
+
+    // Newest commit first, so the first time a file shows up is its last change
+    let mut times = HashMap::new();
+    let mut commit_time = 0;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(time) = line.strip_prefix('\0') {
+            commit_time = time.parse().unwrap_or(0);
+        } else if !line.is_empty() {
+            times.entry(repo_root.join(line)).or_insert(commit_time);
+        }
+    }
+
+    Ok(times)
+}
+
+/// Newest commit on HEAD at least `days` days old, or None when the whole history is younger
+pub fn commit_before_days(repo_path: &Path, days: u64) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args([
+            "-C",
+            repo_path.to_string_lossy().as_ref(),
+            "rev-list",
+            "-1",
+            &format!("--before={} days ago", days),
+            "HEAD",
+        ])
+        .output()
+        .context("Failed to execute git rev-list")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git rev-list failed: {}", stderr.trim())
+        ));
+    }
+
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!commit.is_empty()).then_some(commit))
+}
+
+/// Commits since `base` (all of HEAD's history when None) touching each file, with the Unix
+/// time of the newest one, keyed by absolute path
+pub fn commits_since(
+    repo_path: &Path,
+    base: Option<&str>,
+) -> Result<HashMap<PathBuf, (usize, i64)>> {
+    let repo_root = get_repo_root(repo_path)?;
+    let range = match base {
+        Some(base) => format!("{}..HEAD", base),
+        None => "HEAD".to_string(),
+    };
+
+    let output = Command::new("git")
+        .args([
+            "-C",
+            repo_root.to_string_lossy().as_ref(),
+            "log",
+            "--format=%x00%ct",
+            "--name-only",
+            &range,
+        ])
+        .output()
+        .context("Failed to execute git log")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git log failed: {}", stderr.trim())
+        ));
+    }
+
+    let mut commits: HashMap<PathBuf, (usize, i64)> = HashMap::new();
+    let mut commit_time = 0;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(time) = line.strip_prefix('\0') {
+            commit_time = time.parse().unwrap_or(0);
+        } else if !line.is_empty() {
+            let (count, newest) = commits
+                .entry(repo_root.join(line))
+                .or_insert((0, commit_time));
+            *count += 1;
+            *newest = (*newest).max(commit_time);
+        }
+    }
+
+    Ok(commits)
+}
+
+/// Number of commits in a `git rev-list` range such as `base..HEAD`
+pub fn commit_count(repo_path: &Path, range: &str) -> Result<usize> {
+    let output = Command::new("git")
+        .args([
+            "-C",
+            repo_path.to_string_lossy().as_ref(),
+            "rev-list",
+            "--count",
+            range,
+        ])
+        .output()
+        .context("Failed to execute git rev-list")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git rev-list failed: {}", stderr.trim())
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .unwrap_or(0))
+}
+
 #[derive(Debug, Clone)]
 pub struct ChangedFiles {
     pub added: Vec<PathBuf>,
@@ -170,6 +437,18 @@ pub struct ChangedFiles {
     pub modified: Vec<PathBuf>,
 }
 
+/// Changed lines of one file, as (first line, line count) ranges on each side; a count of 0
+/// is a pure insertion or deletion after that line
+#[derive(Debug, Clone, Default)]
+pub struct FileHunks {
+    /// None when the file is new
+    pub old_path: Option<PathBuf>,
+    /// None when the file was deleted
+    pub new_path: Option<PathBuf>,
+    pub old_ranges: Vec<(usize, usize)>,
+    pub new_ranges: Vec<(usize, usize)>,
+}
+
 #[derive(Debug, Clone)]
 pub struct CommitInfo {
     pub hash: String,
@@ -198,7 +477,7 @@ pub fn get_commits_for_file(
         "-C".to_string(),
         repo_root.to_string_lossy().to_string(),
         "log".to_string(),
-        "--format=%H|%h|%an|%ai|%s".to_string(),
+        COMMIT_FORMAT.to_string(),
         "--follow".to_string(),
     ];
 
@@ -216,30 +495,135 @@ pub fn get_commits_for_file(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("git log failed: {}", stderr.trim());
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git log failed: {}", stderr.trim())
+        ));
     }
 
     let commits: Vec<CommitInfo> = String::from_utf8_lossy(&output.stdout)
         .lines()
-        .filter_map(|line| {
-            let parts: Vec<&str> = line.splitn(5, '|').collect();
-            if parts.len() >= 5 {
-                Some(CommitInfo {
-                    hash: parts[0].to_string(),
-                    short_hash: parts[1].to_string(),
-                    author: parts[2].to_string(),
-                    date: parts[3].to_string(),
-                    message: parts[4].to_string(),
-                })
-            } else {
-                None
-            }
-        })
+        .filter_map(parse_commit)
         .collect();
 
     Ok(commits)
 }
 
+/// Log format read by `parse_commit`
+const COMMIT_FORMAT: &str = "--format=%H|%h|%an|%ai|%s";
+
+fn parse_commit(line: &str) -> Option<CommitInfo> {
+    let parts: Vec<&str> = line.splitn(5, '|').collect();
+    if parts.len() >= 5 {
+        Some(CommitInfo {
+            hash: parts[0].to_string(),
+            short_hash: parts[1].to_string(),
+            author: parts[2].to_string(),
+            date: parts[3].to_string(),
+            message: parts[4].to_string(),
+        })
+    } else {
+        None
+    }
+}
+
+/// Hash of the commit that last touched each line of the file as it is in the working tree,
+/// with that commit's Unix time. Lines not committed yet have an all-zero hash. None when the
+/// file is not tracked.
+pub fn blame_lines(repo_path: &Path, file_path: &Path) -> Result<Option<Vec<(String, i64)>>> {
+    let repo_root = get_repo_root(repo_path)?;
+
+    let relative_path = if file_path.is_absolute() {
+        file_path.strip_prefix(&repo_root).unwrap_or(file_path)
+    } else {
+        file_path
+    };
+
+    let output = Command::new("git")
+        .args([
+            "-C",
+            repo_root.to_string_lossy().as_ref(),
+            "blame",
+            "--porcelain",
+            "--",
+            relative_path.to_string_lossy().as_ref(),
+        ])
+        .output()
+        .context("Failed to execute git blame")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("no such path") {
+            return Ok(None);
+        }
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git blame failed: {}", stderr.trim())
+        ));
+    }
+
+    Ok(Some(parse_blame(&String::from_utf8_lossy(&output.stdout))))
+}
+
+/// Per-line commits out of `git blame --porcelain`, which gives a commit's details only the
+/// first time one of its lines comes up
+fn parse_blame(porcelain: &str) -> Vec<(String, i64)> {
+    let mut hashes: Vec<&str> = Vec::new();
+    let mut times: HashMap<&str, i64> = HashMap::new();
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            continue;
+        }
+        if let Some(time) = line.strip_prefix("committer-time ") {
+            if let Some(hash) = hashes.last() {
+                times.insert(hash, time.parse().unwrap_or(0));
+            }
+            continue;
+        }
+        let hash = line.split(' ').next().unwrap_or("");
+        if hash.len() == 40 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            hashes.push(hash);
+        }
+    }
+    hashes
+        .into_iter()
+        .map(|hash| (hash.to_string(), times.get(hash).copied().unwrap_or(0)))
+        .collect()
+}
+
+/// Details of each of `hashes`, in that order
+pub fn commit_infos(repo_path: &Path, hashes: &[&str]) -> Result<Vec<CommitInfo>> {
+    if hashes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let output = Command::new("git")
+        .args([
+            "-C",
+            repo_path.to_string_lossy().as_ref(),
+            "show",
+            "-s",
+            "--no-walk=unsorted",
+            COMMIT_FORMAT,
+        ])
+        .args(hashes)
+        .output()
+        .context("Failed to execute git show")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(Failure::new(
+            ErrorCode::GitFailed,
+            format!("git show failed: {}", stderr.trim())
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_commit)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,4 +633,30 @@ mod tests {
         let current_dir = std::env::current_dir().unwrap_or_default();
         assert!(is_git_repo(&current_dir));
     }
+
+    #[test]
+    fn test_parse_hunks() {
+        let diff = "diff --git a/src/a.rs b/src/a.rs\nindex 1..2 100644\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -3 +3,2 @@ fn a() {\n--- removed line that looks like a header\n+x\n+y\n@@ -10,2 +11,0 @@\n-a\n-b\ndiff --git a/new.py b/new.py\nnew file mode 100644\n--- /dev/null\n+++ b/new.py\n@@ -0,0 +1,2 @@\n+a\n+b\n";
+        let files = parse_hunks(diff, Path::new("/repo"));
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].old_path, Some(PathBuf::from("/repo/src/a.rs")));
+        assert_eq!(files[0].old_ranges, [(3, 1), (10, 2)]);
+        assert_eq!(files[0].new_ranges, [(3, 2), (11, 0)]);
+        assert_eq!(files[1].old_path, None);
+        assert_eq!(files[1].new_path, Some(PathBuf::from("/repo/new.py")));
+        assert_eq!(files[1].new_ranges, [(1, 2)]);
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let porcelain = format!(
+            "{a} 1 1 2\nauthor Ann\ncommitter-time 200\nsummary first\n\tfn main() {{\n{a} 2 2\n\t{b} 1 1\n{b} 3 3 1\ncommitter-time 300\nprevious {a} src/a.rs\n\t}}\n"
+        );
+        assert_eq!(
+            parse_blame(&porcelain),
+            [(a.clone(), 200), (a.clone(), 200), (b.clone(), 300)]
+        );
+    }
 }
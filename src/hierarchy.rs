@@ -0,0 +1,247 @@
+//! `cm hierarchy`: the ancestor and descendant trees of a class, interface or trait,
+//! assembled from the inheritance edges `cm implements` finds (extends, implements,
+//! trait impls and supertraits, Python base classes, Go embedding).
+
+use crate::implements::{Implementation, ImplementsKind};
+use crate::index::CodeIndex;
+use crate::models::SymbolType;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub struct HierarchyNode {
+    pub name: String,
+    /// Relation between this node and the one above it in the tree
+    pub kind: ImplementsKind,
+    /// The type's definition, or the declaring `class`/`impl` line when the type has no
+    /// definition of its own in the index; `None` for types from outside the project
+    pub location: Option<(PathBuf, usize)>,
+    pub children: Vec<HierarchyNode>,
+    /// The type already appears on this branch, so its subtree is not repeated
+    pub cycle: bool,
+}
+
+pub struct Hierarchy {
+    pub name: String,
+    pub location: Option<(PathBuf, usize)>,
+    /// Supertypes, each with its own supertypes as children
+    pub ancestors: Vec<HierarchyNode>,
+    /// Subtypes and implementors, each with its own subtypes as children
+    pub descendants: Vec<HierarchyNode>,
+}
+
+impl HierarchyNode {
+    fn count(nodes: &[HierarchyNode]) -> usize {
+        nodes.iter().map(|n| 1 + Self::count(&n.children)).sum()
+    }
+}
+
+impl Hierarchy {
+    pub fn ancestor_count(&self) -> usize {
+        HierarchyNode::count(&self.ancestors)
+    }
+
+    pub fn descendant_count(&self) -> usize {
+        HierarchyNode::count(&self.descendants)
+    }
+}
+
+/// Where each named type is defined: classes, interfaces, enums and type aliases
+fn definitions(index: &CodeIndex) -> HashMap<String, Vec<(PathBuf, usize)>> {
+    let mut definitions: HashMap<String, Vec<(PathBuf, usize)>> = HashMap::new();
+    for file in index.files() {
+        for symbol in index.get_file_symbols(&file.path) {
+            let is_type = matches!(
+                symbol.symbol_type,
+                SymbolType::Class
+                    | SymbolType::Interface
+                    | SymbolType::Enum
+                    | SymbolType::TypeAlias
+            );
+            // Rust impl blocks are indexed as classes named `impl Type`
+            if is_type && !symbol.name.contains(' ') {
+                definitions
+                    .entry(symbol.name.to_string())
+                    .or_default()
+                    .push((file.path.clone(), symbol.line_start));
+            }
+        }
+    }
+    definitions
+}
+
+struct Edges<'a> {
+    /// implementor -> its supertypes
+    up: HashMap<&'a str, Vec<&'a Implementation>>,
+    /// supertype -> its implementors
+    down: HashMap<&'a str, Vec<&'a Implementation>>,
+    definitions: HashMap<String, Vec<(PathBuf, usize)>>,
+}
+
+impl Edges<'_> {
+    /// The definition of `name` in `near` (the file declaring the edge), else the only one
+    fn locate(&self, name: &str, near: Option<&Path>) -> Option<(PathBuf, usize)> {
+        let definitions = self.definitions.get(name)?;
+        let same_file = near.and_then(|file| definitions.iter().find(|(path, _)| path == file));
+        match (same_file, definitions.as_slice()) {
+            (Some(found), _) | (None, [found]) => Some(found.clone()),
+            _ => None,
+        }
+    }
+
+    fn grow(
+        &self,
+        name: &str,
+        upward: bool,
+        path: &mut Vec<String>,
+        depth: usize,
+    ) -> Vec<HierarchyNode> {
+        let edges = if upward { &self.up } else { &self.down };
+        let Some(edges) = edges.get(name) else {
+            return Vec::new();
+        };
+        if depth == 0 {
+            return Vec::new();
+        }
+
+        path.push(name.to_string());
+        let mut nodes: Vec<HierarchyNode> = Vec::new();
+        for edge in edges {
+            let (next, location) = if upward {
+                // The edge sits in the subtype's declaration, which says nothing about
+                // where an external supertype lives
+                let name = &edge.interface_name;
+                let location = self
+                    .locate(name, Some(&edge.file_path))
+                    .or_else(|| self.definitions.get(name.as_str())?.first().cloned());
+                (name, location)
+            } else {
+                // Same-named subtypes in different files (`Iter`) stay separate nodes
+                let name = &edge.implementor_name;
+                let location = self
+                    .locate(name, Some(&edge.file_path))
+                    .unwrap_or_else(|| (edge.file_path.clone(), edge.line));
+                (name, Some(location))
+            };
+            if nodes
+                .iter()
+                .any(|n| &n.name == next && (upward || n.location == location))
+            {
+                continue;
+            }
+
+            let cycle = path.contains(next);
+            nodes.push(HierarchyNode {
+                name: next.clone(),
+                kind: edge.kind.clone(),
+                location,
+                children: if cycle {
+                    Vec::new()
+                } else {
+                    self.grow(next, upward, path, depth - 1)
+                },
+                cycle,
+            });
+        }
+        path.pop();
+
+        nodes.sort_by(|a, b| a.name.cmp(&b.name).then(a.location.cmp(&b.location)));
+        nodes
+    }
+}
+
+/// Trees of at most `max_depth` levels above and below `name`
+pub fn build(
+    index: &CodeIndex,
+    edges: &[Implementation],
+    name: &str,
+    max_depth: usize,
+) -> Hierarchy {
+    let mut up: HashMap<&str, Vec<&Implementation>> = HashMap::new();
+    let mut down: HashMap<&str, Vec<&Implementation>> = HashMap::new();
+    for edge in edges {
+        if edge.implementor_name == edge.interface_name {
+            continue;
+        }
+        up.entry(&edge.implementor_name).or_default().push(edge);
+        down.entry(&edge.interface_name).or_default().push(edge);
+    }
+
+    // Declarations found by the edge scan stand in for types the parsers do not index,
+    // such as Rust traits (`trait Name: Supertrait`)
+    let mut definitions = definitions(index);
+    for edge in edges {
+        if edge.kind != ImplementsKind::Impl {
+            definitions
+                .entry(edge.implementor_name.clone())
+                .or_insert_with(|| vec![(edge.file_path.clone(), edge.line)]);
+        }
+    }
+
+    let edges = Edges {
+        up,
+        down,
+        definitions,
+    };
+    Hierarchy {
+        name: name.to_string(),
+        location: edges.locate(name, None),
+        ancestors: edges.grow(name, true, &mut Vec::new(), max_depth),
+        descendants: edges.grow(name, false, &mut Vec::new(), max_depth),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::confidence::Confidence;
+    use crate::indexer::index_file;
+    use crate::models::Language;
+
+    #[test]
+    fn test_hierarchy_walks_both_directions() {
+        let source = "class Base(Protocol):\n    pass\n\nclass Mid(Base):\n    pass\n\nclass Leaf(Mid):\n    pass\n\nclass Other(Base):\n    pass\n";
+        let mut index = CodeIndex::new();
+        index.add_file(index_file(Path::new("a.py"), source, Language::Python, None).unwrap());
+
+        let edge = |implementor: &str, interface: &str, line| Implementation {
+            implementor_name: implementor.to_string(),
+            interface_name: interface.to_string(),
+            file_path: PathBuf::from("a.py"),
+            line,
+            kind: ImplementsKind::Inherits,
+            language: Language::Python,
+            confidence: Confidence::ExactAst,
+        };
+        let mut edges = vec![
+            edge("Base", "Protocol", 1),
+            edge("Mid", "Base", 4),
+            edge("Leaf", "Mid", 7),
+            edge("Other", "Base", 10),
+        ];
+        // A cycle must not recurse forever. As an `impl` edge it does not count as a
+        // declaration of Protocol, which stays external.
+        edges.push(Implementation {
+            kind: ImplementsKind::Impl,
+            ..edge("Protocol", "Leaf", 12)
+        });
+
+        let mid = build(&index, &edges, "Mid", 10);
+        assert_eq!(mid.location, Some((PathBuf::from("a.py"), 4)));
+        assert_eq!(mid.ancestors.len(), 1);
+        assert_eq!(mid.ancestors[0].name, "Base");
+        assert_eq!(mid.ancestors[0].children[0].name, "Protocol");
+        // Protocol is external: no definition, and the edge is in Base's declaration
+        assert_eq!(mid.ancestors[0].children[0].location, None);
+        assert_eq!(mid.descendants[0].name, "Leaf");
+
+        let base = build(&index, &edges, "Base", 10);
+        let children: Vec<&str> = base.descendants.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(children, vec!["Mid", "Other"]);
+        assert_eq!(base.descendants[0].children[0].name, "Leaf");
+        let protocol = &base.descendants[0].children[0].children[0];
+        assert_eq!(protocol.name, "Protocol");
+        assert!(protocol.children[0].cycle && protocol.children[0].name == "Base");
+
+        assert_eq!(build(&index, &edges, "Base", 1).descendant_count(), 2);
+    }
+}
@@ -0,0 +1,252 @@
+//! Command history for `cm last`: each command's arguments, exit code and timing, plus what it
+//! printed to stdout, kept in `.codemapper/history/` so a result that scrolled away or was
+//! lost by an agent's tool call can be printed again without rerunning the command. The
+//! binary tees its stdout into the recorder while a command runs and saves it on exit.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::failure::{ErrorCode, Failure};
+
+/// History directory, inside the cache base directory (`.codemapper/`)
+pub const HISTORY_DIR: &str = "history";
+/// One JSON object per line, oldest first
+const ENTRIES_FILE: &str = "entries.jsonl";
+/// Set to any value to stop recording
+pub const ENV_NO_HISTORY: &str = "CM_NO_HISTORY";
+/// Older entries and their output are dropped
+const MAX_ENTRIES: usize = 50;
+/// Output past this is not kept; the entry is marked truncated
+const MAX_OUTPUT_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRecord {
+    pub id: u64,
+    /// Arguments after `cm`, as given
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    /// Seconds since the Unix epoch
+    pub started: u64,
+    pub duration_ms: u64,
+    pub exit_code: i32,
+    /// Bytes printed to stdout, including any not kept
+    pub output_bytes: usize,
+    pub output_lines: usize,
+    /// Output went past `MAX_OUTPUT_BYTES` and only its start was kept
+    pub truncated: bool,
+}
+
+impl CommandRecord {
+    /// The command line, for display
+    pub fn command(&self) -> String {
+        std::iter::once("cm")
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// How long ago the command ran, e.g. `3m ago`
+    pub fn ago(&self) -> String {
+        ago(self.started)
+    }
+}
+
+/// Time since `secs` (since the Unix epoch), e.g. `3m ago`
+pub fn ago(secs: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    match now.saturating_sub(secs) {
+        secs if secs < 60 => format!("{}s ago", secs),
+        secs if secs < 3600 => format!("{}m ago", secs / 60),
+        secs if secs < 86400 => format!("{}h ago", secs / 3600),
+        secs => format!("{}d ago", secs / 86400),
+    }
+}
+
+struct Recorder {
+    dir: PathBuf,
+    args: Vec<String>,
+    started: SystemTime,
+    output: Vec<u8>,
+    /// Bytes seen, including those past the cap
+    seen: usize,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+fn recorder() -> std::sync::MutexGuard<'static, Option<Recorder>> {
+    RECORDER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start recording the command run with `args`, to be saved under `base_dir` by `finish`.
+/// Does nothing when `CM_NO_HISTORY` is set.
+pub fn start(base_dir: &Path, args: Vec<String>) {
+    if std::env::var_os(ENV_NO_HISTORY).is_some() {
+        return;
+    }
+    *recorder() = Some(Recorder {
+        dir: base_dir.join(HISTORY_DIR),
+        args,
+        started: SystemTime::now(),
+        output: Vec::new(),
+        seen: 0,
+    });
+}
+
+/// Add bytes printed to stdout to the recording, if one is running, and to the result being
+/// recorded for the result cache
+pub fn capture(bytes: &[u8]) {
+    crate::results::capture(bytes);
+    if let Some(recording) = recorder().as_mut() {
+        let room = MAX_OUTPUT_BYTES.saturating_sub(recording.output.len());
+        recording
+            .output
+            .extend_from_slice(&bytes[..bytes.len().min(room)]);
+        recording.seen += bytes.len();
+    }
+}
+
+/// A writer that passes everything through to `W` and records it
+pub struct Tee<W>(pub W);
+
+impl<W: Write> Write for Tee<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.0.write(buf)?;
+        capture(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Save the running recording as a history entry. Failing to save never fails the command,
+/// so errors are returned for the caller to report or ignore.
+pub fn finish(exit_code: i32) -> Result<Option<u64>> {
+    let Some(recording) = recorder().take() else {
+        return Ok(None);
+    };
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let mut entries = load_from(&recording.dir);
+    let id = entries.last().map_or(1, |entry| entry.id + 1);
+    let entry = CommandRecord {
+        id,
+        args: recording.args,
+        cwd,
+        started: recording
+            .started
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs()),
+        duration_ms: recording
+            .started
+            .elapsed()
+            .map_or(0, |elapsed| elapsed.as_millis() as u64),
+        exit_code,
+        output_bytes: recording.seen,
+        output_lines: recording.output.iter().filter(|b| **b == b'\n').count(),
+        truncated: recording.seen > recording.output.len(),
+    };
+
+    fs::create_dir_all(&recording.dir).context("Failed to create history directory")?;
+    fs::write(output_path(&recording.dir, id), &recording.output)
+        .context("Failed to write command output to history")?;
+    entries.push(entry);
+    let dropped = entries.len().saturating_sub(MAX_ENTRIES);
+    for old in entries.drain(..dropped) {
+        let _ = fs::remove_file(output_path(&recording.dir, old.id));
+    }
+
+    let mut lines = String::new();
+    for entry in &entries {
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+    fs::write(recording.dir.join(ENTRIES_FILE), lines).context("Failed to write history")?;
+    Ok(Some(id))
+}
+
+fn output_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{}.out", id))
+}
+
+fn load_from(dir: &Path) -> Vec<CommandRecord> {
+    fs::read_to_string(dir.join(ENTRIES_FILE))
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Recorded commands under `base_dir`, oldest first
+pub fn load(base_dir: &Path) -> Vec<CommandRecord> {
+    load_from(&base_dir.join(HISTORY_DIR))
+}
+
+/// The entry `back` commands ago (0 is the latest), or the one with `id`
+pub fn find(base_dir: &Path, back: usize, id: Option<u64>) -> Result<CommandRecord> {
+    let entries = load(base_dir);
+    let found = match id {
+        Some(id) => entries.into_iter().find(|entry| entry.id == id),
+        None => entries.into_iter().rev().nth(back),
+    };
+    found.ok_or_else(|| {
+        let what = match id {
+            Some(id) => format!("No command #{} in history", id),
+            None if back == 0 => "No commands in history yet".to_string(),
+            None => format!("History has fewer than {} commands", back + 1),
+        };
+        Failure::new(ErrorCode::NotFound, what).into()
+    })
+}
+
+/// What `entry` printed to stdout
+pub fn output(base_dir: &Path, entry: &CommandRecord) -> Result<String> {
+    let bytes = fs::read(output_path(&base_dir.join(HISTORY_DIR), entry.id)).map_err(|_| {
+        Failure::new(
+            ErrorCode::NotFound,
+            format!("Output of command #{} is no longer in history", entry.id),
+        )
+    })?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = |name: &str| vec!["query".to_string(), name.to_string()];
+        // Recording is process-wide, so both runs happen in this one test
+        for name in ["first", "second"] {
+            start(dir.path(), args(name));
+            Tee(Vec::new())
+                .write_all(format!("[RESULTS:1]\n{}\n", name).as_bytes())
+                .unwrap();
+            finish(0).unwrap();
+        }
+        assert_eq!(finish(0).unwrap(), None);
+
+        let latest = find(dir.path(), 0, None).unwrap();
+        assert_eq!(latest.command(), "cm query second");
+        assert_eq!((latest.id, latest.output_lines), (2, 2));
+        assert_eq!(
+            output(dir.path(), &latest).unwrap(),
+            "[RESULTS:1]\nsecond\n"
+        );
+        assert_eq!(find(dir.path(), 1, None).unwrap().id, 1);
+        assert_eq!(find(dir.path(), 0, Some(1)).unwrap().args, args("first"));
+        assert!(find(dir.path(), 2, None).is_err());
+    }
+}
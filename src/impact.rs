@@ -1,7 +1,11 @@
 use crate::callgraph;
 use crate::models::SymbolType;
 use crate::output::{OutputFormat, OutputFormatter};
+use crate::test_select::{self, Runner};
 use anyhow::Result;
+use codemapper::index::CodeIndex;
+use codemapper::paths::DisplaySlash;
+use colored::*;
 use std::path::{Path, PathBuf};
 
 pub fn cmd_impact(
@@ -11,6 +15,7 @@ pub fn cmd_impact(
     include_docs: bool,
     limit: Option<usize>,
     all: bool,
+    emit: Option<Runner>,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
@@ -23,6 +28,11 @@ pub fn cmd_impact(
     let original_symbol = symbol;
     let symbol = normalize_qualified_name(&original_symbol);
 
+    // Stdout carries only the command, so it can go straight to a shell
+    if let Some(runner) = emit {
+        return emit_tests(&index, &original_symbol, !exact, runner);
+    }
+
     let raw_matches = if exact {
         index.query_symbol(&symbol)
     } else {
@@ -77,7 +87,7 @@ pub fn cmd_impact(
                 "{}|{}|{}:{}\n",
                 i + 1,
                 s.symbol_type.as_str(),
-                s.file_path.display(),
+                s.file_path.display_slash(),
                 s.line_start
             ));
         }
@@ -88,7 +98,7 @@ pub fn cmd_impact(
     out.push_str(&format!("- Type: {}\n", target.symbol_type.as_str()));
     out.push_str(&format!(
         "- Location: {}:{}\n",
-        target.file_path.display(),
+        target.file_path.display_slash(),
         target.line_start
     ));
     if let Some(sig) = &target.signature {
@@ -125,6 +135,39 @@ pub fn cmd_impact(
     Ok(())
 }
 
+/// `--emit`: the runner command for every test of the symbol, untruncated, on stdout
+fn emit_tests(index: &CodeIndex, symbol: &str, fuzzy: bool, runner: Runner) -> Result<()> {
+    let tests = callgraph::find_tests(index, symbol, fuzzy)?;
+    let selection = test_select::select(index, &tests, runner);
+
+    if selection.skipped > 0 {
+        eprintln!(
+            "{} {} test(s) of '{}' are not {} tests, left out",
+            "⚠".yellow(),
+            selection.skipped,
+            symbol,
+            runner.as_str()
+        );
+    }
+    if selection.is_empty() {
+        eprintln!(
+            "{} No {} tests found for '{}'",
+            "⚠".yellow(),
+            runner.as_str(),
+            symbol
+        );
+        return Ok(());
+    }
+    eprintln!(
+        "{} {} test(s) selected by name, {} run with their whole file or module",
+        "✓".green(),
+        selection.selected,
+        selection.widened
+    );
+    println!("{}", selection.command());
+    Ok(())
+}
+
 fn normalize_qualified_name(name: &str) -> String {
     let trimmed = name.trim();
 
@@ -3,6 +3,7 @@ use regex::Regex;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::confidence::Confidence;
 use crate::index::CodeIndex;
 use crate::models::Language;
 
@@ -33,6 +34,8 @@ pub struct Implementation {
     pub line: usize,
     pub kind: ImplementsKind,
     pub language: Language,
+    /// `text-match` unless the parser indexed the implementor's declaration on `line`
+    pub confidence: Confidence,
 }
 
 pub fn find_implementations(
@@ -43,6 +46,7 @@ pub fn find_implementations(
 ) -> Result<Vec<Implementation>> {
     let mut results = Vec::new();
     let interface_lower = interface.to_lowercase();
+    let definitions = index.query_symbol(interface).len();
 
     for file in index.files() {
         let content = fs::read_to_string(&file.path).unwrap_or_default();
@@ -75,6 +79,18 @@ pub fn find_implementations(
                 continue;
             }
 
+            // The patterns run over raw lines, which may be comments or strings. Rust impl
+            // blocks are indexed as `impl Type`.
+            let declared = index
+                .get_file_symbols(&file.path)
+                .iter()
+                .any(|s| s.line_start == line && s.name.trim_start_matches("impl ") == implementor);
+            let confidence = if declared {
+                Confidence::of_ast_match(&iface, interface, definitions)
+            } else {
+                Confidence::TextMatch
+            };
+
             results.push(Implementation {
                 implementor_name: implementor,
                 interface_name: iface,
@@ -82,6 +98,7 @@ pub fn find_implementations(
                 line,
                 kind,
                 language: file.language,
+                confidence,
             });
         }
     }
@@ -95,6 +112,12 @@ pub fn find_implementations(
     Ok(results)
 }
 
+/// Every inheritance edge in the index, inherent `impl Type` blocks excluded
+pub fn all_edges(index: &CodeIndex) -> Result<Vec<Implementation>> {
+    // An empty fuzzy pattern is contained in every name
+    find_implementations(index, "", true, true)
+}
+
 fn matches_interface(name: &str, interface: &str, fuzzy: bool, interface_lower: &str) -> bool {
     if fuzzy {
         name.to_lowercase().contains(interface_lower)
@@ -112,16 +135,47 @@ fn find_rust_implementations(
     let mut results = Vec::new();
 
     // Pattern: impl Trait for Type
-    let impl_for_re = Regex::new(r"impl\s+(?:<[^>]*>\s*)?(\w+)(?:<[^>]*>)?\s+for\s+(\w+)")
-        .unwrap_or_else(|_| Regex::new(r"^$").expect("fallback regex"));
+    let impl_for_re =
+        Regex::new(r"impl(?:\s*<[^>]*>)?\s+(?:\w+::)*(\w+)(?:<[^>]*>)?\s+for\s+(\w+)")
+            .unwrap_or_else(|_| Regex::new(r"^$").expect("fallback regex"));
 
     // Pattern: impl Type (inherent impl - type implements its own methods)
-    let impl_self_re = Regex::new(r"impl\s+(?:<[^>]*>\s*)?(\w+)(?:<[^>]*>)?\s*\{")
+    let impl_self_re = Regex::new(r"impl(?:\s*<[^>]*>)?\s+(\w+)(?:<[^>]*>)?\s*\{")
         .unwrap_or_else(|_| Regex::new(r"^$").expect("fallback regex"));
 
+    // Pattern: trait Name: Supertrait + Other
+    let supertrait_re =
+        Regex::new(r"^(?:pub(?:\([^)]*\))?\s+)?(?:unsafe\s+)?trait\s+(\w+)(?:<[^>]*>)?\s*:([^{]+)")
+            .unwrap_or_else(|_| Regex::new(r"^$").expect("fallback regex"));
+
     for (line_num, line) in content.lines().enumerate() {
         let trimmed = line.trim();
 
+        // Check trait Name: Supertrait (bounds end at a where clause)
+        if let Some(caps) = supertrait_re.captures(trimmed) {
+            let trait_name = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
+            let bounds = caps.get(2).map(|m| m.as_str()).unwrap_or_default();
+            let bounds = bounds.split(" where ").next().unwrap_or(bounds);
+
+            for bound in bounds.split('+') {
+                let bound = bound.trim().split('<').next().unwrap_or("").trim();
+                let supertrait = bound.rsplit("::").next().unwrap_or(bound);
+                if supertrait.is_empty() || supertrait.starts_with(['\'', '?']) {
+                    continue;
+                }
+                if matches_interface(supertrait, interface, fuzzy, interface_lower)
+                    || matches_interface(trait_name, interface, fuzzy, interface_lower)
+                {
+                    results.push((
+                        trait_name.to_string(),
+                        supertrait.to_string(),
+                        line_num + 1,
+                        ImplementsKind::Extends,
+                    ));
+                }
+            }
+        }
+
         // Check impl Trait for Type
         if let Some(caps) = impl_for_re.captures(trimmed) {
             let trait_name = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
@@ -430,6 +484,47 @@ impl Display for MyType {
         assert_eq!(results[0].1, "Display");
     }
 
+    #[test]
+    fn test_rust_generic_impls() {
+        let content = r#"
+impl<T: Send> IndexedParallelIterator for IntoIter<T> {}
+impl<'a> fmt::Display for Wrapper<'a> {}
+impl<T> Wrapper<T> {
+}
+"#;
+        let results = find_rust_implementations(content, "", true, "");
+        let edges: Vec<(&str, &str)> = results
+            .iter()
+            .map(|r| (r.0.as_str(), r.1.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("IntoIter", "IndexedParallelIterator"),
+                ("Wrapper", "Display"),
+                ("Wrapper", "Wrapper"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_supertraits() {
+        let content = r#"
+pub trait Shape: Debug + std::fmt::Display + 'static {
+    fn area(&self) -> f64;
+}
+trait Plain {}
+"#;
+        let results = find_rust_implementations(content, "Display", false, "display");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "Shape");
+        assert_eq!(results[0].3, ImplementsKind::Extends);
+
+        let all = find_rust_implementations(content, "", true, "");
+        let supertraits: Vec<&str> = all.iter().map(|r| r.1.as_str()).collect();
+        assert_eq!(supertraits, vec!["Debug", "Display"]);
+    }
+
     #[test]
     fn test_python_inheritance() {
         let content = r#"
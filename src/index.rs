@@ -1,188 +1,270 @@
-use crate::models::{FileInfo, Symbol, SymbolType};
-use serde::{Deserialize, Serialize};
+use crate::annotations::Annotation;
+use crate::models::{
+    Dependency, FileInfo, Language, LineCounts, Name, Reference, Symbol, SymbolType,
+};
+use crate::paths;
+use rayon::prelude::*;
+use serde::de::{self, Error as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
-
-#[derive(Serialize, Deserialize)]
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Symbols share their file's path and equal names share one allocation, so memory grows
+/// with distinct paths and names rather than with the symbol count.
+///
+/// An index loaded from the cache only materializes each file's symbols the first time
+/// that file is asked for; names are known up front so lookups know which files to decode.
 pub struct CodeIndex {
-    files: HashMap<PathBuf, FileInfo>,
-    symbols: Vec<Symbol>,
-    symbol_index: HashMap<String, Vec<usize>>,
-    file_symbols: HashMap<PathBuf, Vec<usize>>,
-    dependencies: HashMap<PathBuf, Vec<String>>,
+    /// Insertion order, so iteration is stable across runs
+    order: Vec<Arc<Path>>,
+    files: HashMap<Arc<Path>, FileEntry>,
+    /// Name -> (file, position within that file's symbols)
+    symbol_index: HashMap<Name, Vec<(Arc<Path>, usize)>>,
+    /// Unqualified referenced name -> (file, position within that file's references)
+    reference_index: HashMap<Box<str>, Vec<(Arc<Path>, usize)>>,
+    /// Files indexed without recording their references
+    unreferenced: usize,
+    dependencies: HashMap<Arc<Path>, Vec<String>>,
+}
+
+struct FileEntry {
+    info: FileInfo,
+    names: Vec<Name>,
+    symbols: OnceLock<Vec<Symbol>>,
+    /// Encoded symbol records from the cache, taken when `symbols` is first initialized
+    encoded: Mutex<Option<Vec<u8>>>,
+}
+
+impl FileEntry {
+    fn symbols(&self) -> &[Symbol] {
+        self.symbols.get_or_init(|| {
+            let encoded = self
+                .encoded
+                .lock()
+                .map(|mut e| e.take())
+                .unwrap_or_default();
+            encoded
+                .and_then(|bytes| decode_symbols(&self.info.path, &self.names, &bytes))
+                .unwrap_or_default()
+        })
+    }
 }
 
 impl CodeIndex {
     pub fn new() -> Self {
         Self {
+            order: Vec::new(),
             files: HashMap::new(),
-            symbols: Vec::new(),
             symbol_index: HashMap::new(),
-            file_symbols: HashMap::new(),
+            reference_index: HashMap::new(),
+            unreferenced: 0,
             dependencies: HashMap::new(),
         }
     }
 
     pub fn add_file(&mut self, mut file_info: FileInfo) {
-        let file_path = file_info.path.clone();
-        let _symbol_start_idx = self.symbols.len();
-
-        let mut symbol_indices = Vec::new();
-        for symbol in file_info.symbols.drain(..) {
-            let idx = self.symbols.len();
-            symbol_indices.push(idx);
-
-            self.symbol_index
-                .entry(symbol.name.clone())
-                .or_insert_with(Vec::new)
-                .push(idx);
+        let file_path: Arc<Path> = Arc::from(file_info.path.as_path());
 
-            self.symbols.push(symbol);
+        let mut symbols: Vec<Symbol> = file_info.symbols.drain(..).collect();
+        for symbol in &mut symbols {
+            symbol.file_path = file_path.clone();
+            symbol.name = self.intern_name(symbol.name.clone());
         }
-
-        self.file_symbols.insert(file_path.clone(), symbol_indices);
+        let names = symbols.iter().map(|s| s.name.clone()).collect();
 
         let deps: Vec<String> = file_info
             .dependencies
             .iter()
             .map(|d| d.import_name.clone())
             .collect();
-        self.dependencies.insert(file_path.clone(), deps);
 
-        self.files.insert(file_path, file_info);
+        self.insert_entry(
+            file_path,
+            FileEntry {
+                info: file_info,
+                names,
+                symbols: OnceLock::from(symbols),
+                encoded: Mutex::new(None),
+            },
+            deps,
+        );
     }
 
-    /// Remove a file from the index (for incremental updates)
-    pub fn remove_file(&mut self, path: &Path) {
-        // Get symbol indices for this file
-        let symbol_indices = match self.file_symbols.get(path) {
-            Some(indices) => indices.clone(),
-            None => return, // File not in index
-        };
+    fn insert_entry(&mut self, file_path: Arc<Path>, entry: FileEntry, deps: Vec<String>) {
+        self.remove_file(&file_path);
 
-        // Remove from symbol_index: for each symbol from this file,
-        // remove its index from the symbol_index map
-        for &idx in &symbol_indices {
-            if let Some(symbol) = self.symbols.get(idx) {
-                let name = symbol.name.clone();
-                if let Some(indices) = self.symbol_index.get_mut(&name) {
-                    indices.retain(|&i| i != idx);
-                    // Remove entry if empty
-                    if indices.is_empty() {
-                        self.symbol_index.remove(&name);
-                    }
-                }
-            }
+        for (slot, name) in entry.names.iter().enumerate() {
+            self.symbol_index
+                .entry(name.clone())
+                .or_default()
+                .push((file_path.clone(), slot));
         }
-
-        // Mark symbols as deleted (set empty placeholder)
-        // We can't remove from Vec without invalidating indices
-        // This is cleaned up when cache is saved/compacted
-        for &idx in &symbol_indices {
-            if idx < self.symbols.len() {
-                // Clear the symbol but keep the slot
-                // (compaction happens when saving cache)
-                self.symbols[idx].name = String::new();
+        match &entry.info.references {
+            Some(references) => {
+                for (slot, reference) in references.iter().enumerate() {
+                    self.reference_index
+                        .entry(reference.target().into())
+                        .or_default()
+                        .push((file_path.clone(), slot));
+                }
             }
+            None => self.unreferenced += 1,
         }
 
-        // Remove from other maps
-        self.file_symbols.remove(path);
-        self.dependencies.remove(path);
-        self.files.remove(path);
+        self.dependencies.insert(file_path.clone(), deps);
+        self.order.push(file_path.clone());
+        self.files.insert(file_path, entry);
     }
 
-    /// Compact the index by removing deleted symbols and rebuilding indices
-    /// Call this after incremental updates to reclaim memory
-    pub fn compact(&mut self) {
-        // Collect all non-deleted symbols
-        let mut new_symbols = Vec::new();
-        let mut old_to_new_idx: HashMap<usize, usize> = HashMap::new();
-
-        for (old_idx, symbol) in self.symbols.iter().enumerate() {
-            if !symbol.name.is_empty() {
-                let new_idx = new_symbols.len();
-                old_to_new_idx.insert(old_idx, new_idx);
-                new_symbols.push(symbol.clone());
-            }
+    /// Reuse the allocation of an equal name already in the index
+    fn intern_name(&self, name: Name) -> Name {
+        match self.symbol_index.get_key_value(name.as_str()) {
+            Some((existing, _)) => existing.clone(),
+            None => name,
         }
+    }
 
-        // Rebuild symbol_index with new indices
-        let mut new_symbol_index: HashMap<String, Vec<usize>> = HashMap::new();
-        for (old_idx, new_idx) in &old_to_new_idx {
-            if let Some(symbol) = self.symbols.get(*old_idx) {
-                new_symbol_index
-                    .entry(symbol.name.clone())
-                    .or_insert_with(Vec::new)
-                    .push(*new_idx);
+    /// Remove a file from the index (for incremental updates)
+    pub fn remove_file(&mut self, path: &Path) {
+        let Some(entry) = self.files.remove(path) else {
+            return;
+        };
+
+        for name in &entry.names {
+            if let Some(locations) = self.symbol_index.get_mut(name) {
+                locations.retain(|(p, _)| **p != *path);
+                if locations.is_empty() {
+                    self.symbol_index.remove(name);
+                }
             }
         }
-
-        // Rebuild file_symbols with new indices
-        let mut new_file_symbols: HashMap<PathBuf, Vec<usize>> = HashMap::new();
-        for (path, old_indices) in &self.file_symbols {
-            let new_indices: Vec<usize> = old_indices
-                .iter()
-                .filter_map(|old_idx| old_to_new_idx.get(old_idx))
-                .copied()
-                .collect();
-            if !new_indices.is_empty() {
-                new_file_symbols.insert(path.clone(), new_indices);
+        match &entry.info.references {
+            Some(references) => {
+                for reference in references {
+                    let target = reference.target();
+                    if let Some(locations) = self.reference_index.get_mut(target) {
+                        locations.retain(|(p, _)| **p != *path);
+                        if locations.is_empty() {
+                            self.reference_index.remove(target);
+                        }
+                    }
+                }
             }
+            None => self.unreferenced -= 1,
         }
 
-        // Replace with compacted versions
-        self.symbols = new_symbols;
-        self.symbol_index = new_symbol_index;
-        self.file_symbols = new_file_symbols;
+        self.dependencies.remove(path);
+        self.order.retain(|p| **p != *path);
     }
 
+    /// Used to reclaim the slots `remove_file` left behind. Removal now frees a file's
+    /// symbols right away, so there is nothing left to compact.
+    #[deprecated(note = "remove_file frees symbols immediately; drop the call")]
+    pub fn compact(&mut self) {}
+
     pub fn query_symbol(&self, name: &str) -> Vec<&Symbol> {
         self.symbol_index
             .get(name)
-            .map(|indices| indices.iter().map(|&idx| &self.symbols[idx]).collect())
+            .map(|locations| {
+                locations
+                    .iter()
+                    .filter_map(|(path, slot)| self.files.get(path)?.symbols().get(*slot))
+                    .collect()
+            })
             .unwrap_or_default()
     }
 
     pub fn fuzzy_search(&self, pattern: &str) -> Vec<&Symbol> {
         let pattern_lower = pattern.to_lowercase();
-        let mut results: Vec<(&Symbol, i32)> = self
-            .symbols
-            .iter()
-            .filter_map(|symbol| {
-                let name_lower = symbol.name.to_lowercase();
-                if name_lower.contains(&pattern_lower) {
-                    let score = if name_lower == pattern_lower {
-                        100
-                    } else if name_lower.starts_with(&pattern_lower) {
-                        50
-                    } else {
-                        levenshtein_distance(&name_lower, &pattern_lower)
-                    };
-                    Some((symbol, score))
+        let mut results: Vec<(&Symbol, i32)> = Vec::new();
+
+        // Match on names first so only files with a hit get their symbols decoded
+        for entry in self.entries() {
+            for (slot, name) in entry.names.iter().enumerate() {
+                let name_lower = name.to_lowercase();
+                if !name_lower.contains(&pattern_lower) {
+                    continue;
+                }
+                let score = if name_lower == pattern_lower {
+                    100
+                } else if name_lower.starts_with(&pattern_lower) {
+                    50
                 } else {
-                    None
+                    levenshtein_distance(&name_lower, &pattern_lower)
+                };
+                if let Some(symbol) = entry.symbols().get(slot) {
+                    results.push((symbol, score));
                 }
-            })
-            .collect();
+            }
+        }
 
         results.sort_by(|a, b| b.1.cmp(&a.1));
         results.into_iter().map(|(s, _)| s).collect()
     }
 
+    /// Symbols whose name matches a wildcard pattern, grouped by name in name order. Matches
+    /// against the name index, so only files with a hit get their symbols decoded
+    pub fn glob_search(&self, pattern: &NamePattern) -> Vec<&Symbol> {
+        let mut names: Vec<&Name> = self
+            .symbol_index
+            .keys()
+            .filter(|name| pattern.matches(name))
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .flat_map(|name| self.query_symbol(name))
+            .collect()
+    }
+
     pub fn get_file_symbols(&self, path: &Path) -> Vec<&Symbol> {
-        self.file_symbols
+        self.files
             .get(path)
-            .map(|indices| indices.iter().map(|&idx| &self.symbols[idx]).collect())
+            .map(|entry| entry.symbols().iter().collect())
             .unwrap_or_default()
     }
 
+    /// Distinct symbol names, without decoding any file's symbols
+    pub fn names(&self) -> impl Iterator<Item = &Name> {
+        self.symbol_index.keys()
+    }
+
+    /// Whether every file's references were recorded, so `references_to` sees them all
+    pub fn has_references(&self) -> bool {
+        self.unreferenced == 0
+    }
+
+    /// Recorded references to `name`, matched without qualifiers, in no particular order
+    pub fn references_to(&self, name: &str) -> Vec<(&Path, &Reference)> {
+        self.reference_index
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter_map(|(path, slot)| {
+                let references = self.file_references(path)?;
+                Some((&**path, references.get(*slot)?))
+            })
+            .collect()
+    }
+
+    /// The references recorded for the file at `path`, if they were recorded
+    pub fn file_references(&self, path: &Path) -> Option<&[Reference]> {
+        self.files.get(path)?.info.references.as_deref()
+    }
+
     pub fn get_dependencies(&self, path: &Path) -> Option<&Vec<String>> {
         self.dependencies.get(path)
     }
 
     pub fn files(&self) -> impl Iterator<Item = &FileInfo> {
-        self.files.values()
+        self.entries().map(|entry| &entry.info)
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &FileEntry> {
+        self.order.iter().filter_map(|path| self.files.get(path))
     }
 
     pub fn total_files(&self) -> usize {
@@ -190,19 +272,267 @@ impl CodeIndex {
     }
 
     pub fn total_symbols(&self) -> usize {
-        self.symbols.len()
+        self.files.values().map(|entry| entry.names.len()).sum()
     }
 
     pub fn symbols_by_type(&self, symbol_type: SymbolType) -> usize {
-        self.symbols
-            .iter()
+        self.all_symbols()
+            .into_iter()
             .filter(|s| s.symbol_type == symbol_type)
             .count()
     }
 
-    /// Get all symbols (for use with type filtering)
+    /// Get all symbols (for use with type filtering); decodes every file not yet loaded
     pub fn all_symbols(&self) -> Vec<&Symbol> {
-        self.symbols.iter().collect()
+        self.files.par_iter().for_each(|(_, entry)| {
+            entry.symbols();
+        });
+        self.entries().flat_map(|entry| entry.symbols()).collect()
+    }
+
+    /// A copy holding only the symbols `keep` accepts, and only files left with any
+    pub fn filtered(&self, keep: impl Fn(&Symbol) -> bool) -> CodeIndex {
+        let mut filtered = CodeIndex::new();
+        for file in self.files() {
+            let symbols: Vec<Symbol> = self
+                .get_file_symbols(&file.path)
+                .into_iter()
+                .filter(|s| keep(s))
+                .map(|s| Symbol {
+                    // Parents may be filtered out, so the ids would point at the wrong symbols
+                    parent_id: None,
+                    ..s.clone()
+                })
+                .collect();
+            if symbols.is_empty() {
+                continue;
+            }
+            filtered.add_file(FileInfo {
+                symbols,
+                ..file.clone()
+            });
+        }
+        filtered
+    }
+}
+
+/// On-disk layout: each path is written once with its symbols' name ids, and names are
+/// written once in a table. The rest of each file's symbols is a separate encoded blob so
+/// loading the cache only reads it into memory; it is decoded when the file is first used.
+#[derive(Serialize, Deserialize)]
+struct IndexRecord<'a> {
+    names: Vec<Cow<'a, str>>,
+    files: Vec<FileRecord<'a>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileRecord<'a> {
+    path: Cow<'a, Path>,
+    language: Language,
+    size: u64,
+    hash: Cow<'a, str>,
+    lines: LineCounts,
+    dependencies: Cow<'a, [Dependency]>,
+    references: Option<Cow<'a, [Reference]>>,
+    names: Vec<u32>,
+    symbols: Blob<'a>,
+}
+
+/// Everything but the name, which lives in `FileRecord::names`
+#[derive(Serialize, Deserialize)]
+struct SymbolRecord<'a> {
+    symbol_type: SymbolType,
+    signature: Option<Cow<'a, str>>,
+    docstring: Option<Cow<'a, str>>,
+    line_start: usize,
+    line_end: usize,
+    parent_id: Option<usize>,
+    is_exported: bool,
+    byte_range: Option<(usize, usize)>,
+    annotations: Cow<'a, [Annotation]>,
+}
+
+/// Bytes written with `serialize_bytes`, so bincode reads them in one copy
+struct Blob<'a>(Cow<'a, [u8]>);
+
+impl Serialize for Blob<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Blob<'_> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BlobVisitor;
+
+        impl<'de> de::Visitor<'de> for BlobVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a byte array")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(b) = seq.next_element()? {
+                    bytes.push(b);
+                }
+                Ok(bytes)
+            }
+        }
+
+        let bytes = deserializer.deserialize_byte_buf(BlobVisitor)?;
+        Ok(Blob(Cow::Owned(bytes)))
+    }
+}
+
+fn encode_symbols(symbols: &[Symbol]) -> Vec<u8> {
+    let records: Vec<SymbolRecord> = symbols
+        .iter()
+        .map(|symbol| SymbolRecord {
+            symbol_type: symbol.symbol_type,
+            signature: symbol.signature.as_deref().map(Cow::Borrowed),
+            docstring: symbol.docstring.as_deref().map(Cow::Borrowed),
+            line_start: symbol.line_start,
+            line_end: symbol.line_end,
+            parent_id: symbol.parent_id,
+            is_exported: symbol.is_exported,
+            byte_range: symbol.byte_range,
+            annotations: Cow::Borrowed(&symbol.annotations),
+        })
+        .collect();
+    bincode::serialize(&records).unwrap_or_default()
+}
+
+/// `None` if the blob is unreadable or does not match the name list
+fn decode_symbols(path: &Path, names: &[Name], bytes: &[u8]) -> Option<Vec<Symbol>> {
+    let records: Vec<SymbolRecord> = bincode::deserialize(bytes).ok()?;
+    if records.len() != names.len() {
+        return None;
+    }
+
+    let file_path: Arc<Path> = Arc::from(path);
+    let symbols = records
+        .into_iter()
+        .zip(names)
+        .map(|(s, name)| Symbol {
+            name: name.clone(),
+            symbol_type: s.symbol_type,
+            signature: s.signature.map(Cow::into_owned),
+            docstring: s.docstring.map(Cow::into_owned),
+            line_start: s.line_start,
+            line_end: s.line_end,
+            parent_id: s.parent_id,
+            file_path: file_path.clone(),
+            is_exported: s.is_exported,
+            byte_range: s.byte_range,
+            annotations: s.annotations.into_owned(),
+        })
+        .collect();
+    Some(symbols)
+}
+
+impl Serialize for CodeIndex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut names: Vec<Cow<str>> = Vec::new();
+        let mut name_ids: HashMap<&str, u32> = HashMap::new();
+        let mut files = Vec::with_capacity(self.files.len());
+
+        for path in &self.order {
+            let Some(entry) = self.files.get(path) else {
+                continue;
+            };
+            let name_list = entry
+                .names
+                .iter()
+                .map(|name| {
+                    *name_ids.entry(name.as_str()).or_insert_with(|| {
+                        names.push(Cow::Borrowed(name.as_str()));
+                        (names.len() - 1) as u32
+                    })
+                })
+                .collect();
+
+            // Files that were never opened keep their encoded symbols as they are
+            let encoded = entry.encoded.lock().ok().and_then(|e| e.clone());
+            let symbols = match (entry.symbols.get(), encoded) {
+                (Some(symbols), _) => encode_symbols(symbols),
+                (None, Some(encoded)) => encoded,
+                (None, None) => encode_symbols(entry.symbols()),
+            };
+
+            files.push(FileRecord {
+                path: Cow::Borrowed(&**path),
+                language: entry.info.language,
+                size: entry.info.size,
+                hash: Cow::Borrowed(&entry.info.hash),
+                lines: entry.info.lines,
+                dependencies: Cow::Borrowed(&entry.info.dependencies),
+                references: entry.info.references.as_deref().map(Cow::Borrowed),
+                names: name_list,
+                symbols: Blob(Cow::Owned(symbols)),
+            });
+        }
+
+        IndexRecord { names, files }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CodeIndex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let record = IndexRecord::deserialize(deserializer)?;
+        let names: Vec<Name> = record
+            .names
+            .into_iter()
+            .map(|name| Name::from(name.as_ref()))
+            .collect();
+
+        let mut index = CodeIndex::new();
+        for file in record.files {
+            let path: Arc<Path> = Arc::from(file.path.as_ref());
+            let file_names = file
+                .names
+                .iter()
+                .map(|&id| {
+                    names
+                        .get(id as usize)
+                        .cloned()
+                        .ok_or_else(|| D::Error::custom("symbol name id out of range"))
+                })
+                .collect::<Result<Vec<_>, D::Error>>()?;
+            let deps = file
+                .dependencies
+                .iter()
+                .map(|d| d.import_name.clone())
+                .collect();
+
+            let entry = FileEntry {
+                info: FileInfo {
+                    path: file.path.into_owned(),
+                    language: file.language,
+                    size: file.size,
+                    hash: file.hash.into_owned(),
+                    lines: file.lines,
+                    symbols: Vec::new(),
+                    dependencies: file.dependencies.into_owned(),
+                    references: file.references.map(Cow::into_owned),
+                },
+                names: file_names,
+                symbols: OnceLock::new(),
+                encoded: Mutex::new(Some(file.symbols.0.into_owned())),
+            };
+            index.insert_entry(path, entry, deps);
+        }
+
+        Ok(index)
     }
 }
 
@@ -245,8 +575,256 @@ fn levenshtein_distance(s1: &str, s2: &str) -> i32 {
     -(matrix[len1][len2] as i32)
 }
 
+/// A `cm query` name with `*` (any run of characters) or `?` (one character) wildcards.
+/// Anchored at both ends, so `parse_*` is a prefix query and `*Handler` a suffix query
+#[derive(Debug, Clone)]
+pub struct NamePattern {
+    chars: Vec<char>,
+    case_sensitive: bool,
+    literal: String,
+}
+
+impl NamePattern {
+    /// `None` when `query` has no wildcard
+    pub fn parse(query: &str, case_sensitive: bool) -> Option<Self> {
+        if !query.contains(['*', '?']) {
+            return None;
+        }
+        let literal = query
+            .split(['*', '?'])
+            .max_by_key(|part| part.len())
+            .unwrap_or_default()
+            .to_string();
+        let chars = if case_sensitive {
+            query.chars().collect()
+        } else {
+            query.to_lowercase().chars().collect()
+        };
+        Some(Self {
+            chars,
+            case_sensitive,
+            literal,
+        })
+    }
+
+    /// Longest wildcard-free part, which every matching name contains
+    pub fn literal(&self) -> &str {
+        &self.literal
+    }
+
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// Whether `name`, or its last segment for `Type::name` / `Type.name`, matches
+    pub fn matches(&self, name: &str) -> bool {
+        let last = name.rsplit(['.', ':']).next().unwrap_or(name);
+        self.matches_whole(name) || (last.len() < name.len() && self.matches_whole(last))
+    }
+
+    fn matches_whole(&self, name: &str) -> bool {
+        let name: Vec<char> = if self.case_sensitive {
+            name.chars().collect()
+        } else {
+            name.to_lowercase().chars().collect()
+        };
+        let (mut p, mut n) = (0, 0);
+        // Position of the last `*` and the name index it is currently absorbing up to
+        let mut star: Option<(usize, usize)> = None;
+        while n < name.len() {
+            match self.chars.get(p) {
+                Some('*') => {
+                    star = Some((p, n));
+                    p += 1;
+                }
+                Some(&c) if c == '?' || c == name[n] => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => match star {
+                    Some((star_p, star_n)) => {
+                        star = Some((star_p, star_n + 1));
+                        p = star_p + 1;
+                        n = star_n + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.chars[p..].iter().all(|&c| c == '*')
+    }
+}
+
+/// Parse an inclusive line range like `100-250` (or a single line `42`)
+pub fn parse_line_range(value: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("expected a line range like '100-250', got '{}'", value);
+    let (start, end) = value.split_once('-').unwrap_or((value, value));
+    let start: usize = start.trim().parse().map_err(|_| invalid())?;
+    let end: usize = end.trim().parse().map_err(|_| invalid())?;
+    if start == 0 || end < start {
+        return Err(invalid());
+    }
+    Ok((start, end))
+}
+
+/// Whether `path` is `scope` or lies beneath it, ignoring `.` components so `./src/a.rs` is
+/// within `src` (and, on Windows, case)
+pub fn path_within(path: &Path, scope: &Path) -> bool {
+    let lexical = |p: &Path| -> PathBuf {
+        let path: PathBuf = p
+            .components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .collect();
+        paths::fold_case(&path)
+    };
+    lexical(path).starts_with(lexical(scope))
+}
+
+/// Symbols of one file that overlap the inclusive line range `start..=end`
+pub fn symbols_in_lines(symbols: Vec<&Symbol>, (start, end): (usize, usize)) -> Vec<&Symbol> {
+    symbols
+        .into_iter()
+        .filter(|s| s.line_start <= end && s.line_end >= start)
+        .collect()
+}
+
+/// Symbols of one file named `name` (bare or as the last segment of `Type::name` /
+/// `Type.name`), each followed by the symbols nested inside it
+pub fn symbol_with_children<'a>(symbols: Vec<&'a Symbol>, name: &str) -> Vec<&'a Symbol> {
+    let is_match = |s: &Symbol| {
+        s.name == name
+            || s.name
+                .strip_suffix(name)
+                .is_some_and(|rest| rest.ends_with("::") || rest.ends_with('.'))
+    };
+    let roots: Vec<(usize, usize)> = symbols
+        .iter()
+        .filter(|s| is_match(s))
+        .map(|s| (s.line_start, s.line_end))
+        .collect();
+
+    symbols
+        .into_iter()
+        .filter(|s| {
+            roots
+                .iter()
+                .any(|&(start, end)| s.line_start >= start && s.line_end <= end)
+        })
+        .collect()
+}
+
 impl Default for CodeIndex {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+
+    #[test]
+    fn test_round_trip_shares_paths_and_names() {
+        let source = "struct A;\nimpl A { fn new() -> A { A } }\nstruct B;\nimpl B { fn new() -> B { B } }\n";
+        let file = index_file(Path::new("src/lib.rs"), source, Language::Rust, None).unwrap();
+
+        let mut index = CodeIndex::new();
+        index.add_file(file);
+
+        let bytes = bincode::serialize(&index).unwrap();
+        let index: CodeIndex = bincode::deserialize(&bytes).unwrap();
+
+        let news = index.query_symbol("new");
+        assert_eq!(news.len(), 2);
+        assert!(std::ptr::eq(news[0].name.as_str(), news[1].name.as_str()));
+        assert!(Arc::ptr_eq(&news[0].file_path, &news[1].file_path));
+        assert_eq!(&*news[0].file_path, Path::new("src/lib.rs"));
+        assert_eq!(
+            index.get_file_symbols(Path::new("src/lib.rs")).len(),
+            index.total_symbols()
+        );
+    }
+
+    #[test]
+    fn test_line_range_and_symbol_filters() {
+        let source = "class Config:\n    def load(self):\n        pass\n\n    def save(self):\n        pass\n\ndef load():\n    pass\n";
+        let file = index_file(Path::new("c.py"), source, Language::Python, None).unwrap();
+        let symbols: Vec<&Symbol> = file.symbols.iter().collect();
+        let names = |found: Vec<&Symbol>| -> Vec<(String, usize)> {
+            found
+                .into_iter()
+                .map(|s| (s.name.to_string(), s.line_start))
+                .collect()
+        };
+
+        assert_eq!(parse_line_range("100-250"), Ok((100, 250)));
+        assert_eq!(parse_line_range("42"), Ok((42, 42)));
+        assert!(parse_line_range("250-100").is_err());
+
+        let in_lines = names(symbols_in_lines(symbols.clone(), (5, 8)));
+        assert!(in_lines.contains(&("save".to_string(), 5)));
+        assert!(in_lines.contains(&("load".to_string(), 8)));
+        assert!(!in_lines.contains(&("load".to_string(), 2)));
+
+        let config = names(symbol_with_children(symbols.clone(), "Config"));
+        assert_eq!(config.len(), 3);
+        assert_eq!(config[0], ("Config".to_string(), 1));
+        assert_eq!(names(symbol_with_children(symbols, "load")).len(), 2);
+
+        let within = |path: &str, scope: &str| path_within(Path::new(path), Path::new(scope));
+        assert!(within("./src/api/a.rs", "src/api"));
+        assert!(within("src/api/a.rs", "./src/api/a.rs"));
+        assert!(!within("./src/api_v2/a.rs", "./src/api"));
+    }
+
+    #[test]
+    fn test_name_pattern() {
+        let pattern =
+            |query: &str, case_sensitive: bool| NamePattern::parse(query, case_sensitive).unwrap();
+        assert!(NamePattern::parse("parse", false).is_none());
+
+        let prefix = pattern("parse_*", true);
+        assert!(prefix.matches("parse_args"));
+        assert!(prefix.matches("Parser::parse_file"));
+        assert!(!prefix.matches("reparse_args"));
+        assert!(!prefix.matches("Parse_args"));
+        assert!(pattern("Parse_*", false).matches("parse_args"));
+
+        let suffix = pattern("*Handler", true);
+        assert!(suffix.matches("RequestHandler"));
+        assert!(!suffix.matches("RequestHandlers"));
+        assert_eq!(suffix.literal(), "Handler");
+
+        assert!(pattern("get_?d", true).matches("get_id"));
+        assert!(pattern("a*b*c", true).matches("axxbyybzc"));
+        assert!(!pattern("a*b*c", true).matches("axxbyybz"));
+    }
+
+    #[test]
+    fn test_loaded_index_decodes_files_on_demand() {
+        let mut index = CodeIndex::new();
+        for (path, source) in [("a.rs", "fn alpha() {}\n"), ("b.rs", "fn beta() {}\n")] {
+            let file = index_file(Path::new(path), source, Language::Rust, None).unwrap();
+            index.add_file(file);
+        }
+
+        let bytes = bincode::serialize(&index).unwrap();
+        let index: CodeIndex = bincode::deserialize(&bytes).unwrap();
+        let decoded = |path: &str| index.files[Path::new(path)].symbols.get().is_some();
+
+        assert_eq!(index.total_symbols(), 2);
+        assert!(!decoded("a.rs") && !decoded("b.rs"));
+
+        assert_eq!(index.query_symbol("beta").len(), 1);
+        assert!(!decoded("a.rs") && decoded("b.rs"));
+
+        let pattern = NamePattern::parse("al*", true).unwrap();
+        assert_eq!(index.glob_search(&pattern)[0].name, "alpha");
+        assert!(decoded("a.rs"));
+
+        // Untouched files are written back from their encoded form
+        let again: CodeIndex = bincode::deserialize(&bincode::serialize(&index).unwrap()).unwrap();
+        assert_eq!(again.query_symbol("alpha")[0].line_start, 1);
+    }
+}
@@ -1,19 +1,28 @@
+use crate::annotations;
+use crate::callgraph;
+use crate::deadline;
+use crate::failure::{ErrorCode, Failure};
 use crate::index::CodeIndex;
-use crate::models::{FileInfo, Language};
+use crate::models::{FileInfo, Language, LineCounts, Reference, ReferenceKind, Symbol};
 use crate::parser::{
-    c::CParser, go::GoParser, java::JavaParser, javascript::JavaScriptParser,
-    markdown::MarkdownParser, python::PythonParser, rust::RustParser, typescript::TypeScriptParser,
-    Parser,
+    c::CParser, cpp::CppParser, csharp::CSharpParser, go::GoParser, java::JavaParser,
+    javascript::JavaScriptParser, kotlin::KotlinParser, lua::LuaParser, markdown::MarkdownParser,
+    php::PhpParser, python::PythonParser, registry, rust::RustParser, scala::ScalaParser,
+    swift::SwiftParser, typescript::TypeScriptParser, zig::ZigParser, Parser,
 };
+use crate::paths::{self, DisplaySlash};
+use crate::symbol_deps;
 use anyhow::{Context, Result};
 use indicatif::ProgressBar;
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use walkdir::WalkDir;
+use ignore::{WalkBuilder, WalkState};
 
-const IGNORED_DIRS: &[&str] = &[
+/// Directories never descended into when discovering files
+pub(crate) const IGNORED_DIRS: &[&str] = &[
     ".codemapper",
     ".git",
     "node_modules",
@@ -23,15 +32,106 @@ const IGNORED_DIRS: &[&str] = &[
     "build",
 ];
 
+/// Directories holding third-party code checked into the tree. `node_modules` is normally
+/// in `IGNORED_DIRS` and only shows up when it is the indexed path itself
+pub const VENDOR_DIRS: &[&str] = &[
+    "vendor",
+    "vendored",
+    "third_party",
+    "third-party",
+    "thirdparty",
+    "node_modules",
+    "bower_components",
+];
+
+/// Larger files are not indexed: generated parsers, bundles and data dumps only slow parsing
+pub const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Extensions with a built-in parser; `--extensions auto` picks from these and plugin ones
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "py", "js", "ts", "jsx", "tsx", "rs", "java", "go", "c", "h", "cpp", "cc", "cxx", "hpp", "hh",
+    "hxx", "cs", "php", "kt", "kts", "swift", "scala", "sc", "lua", "zig", "md",
+];
+
+/// Common source extensions without a parser, so `auto` can say what it left out
+const UNSUPPORTED_LANGUAGES: &[(&str, &str)] = &[("rb", "Ruby")];
+
+/// What `--extensions auto` resolved to for a directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DetectedExtensions {
+    /// Supported and plugin extensions with at least one file, in `SUPPORTED_EXTENSIONS` order
+    pub extensions: Vec<String>,
+    /// Languages present without a parser, with their file counts, most files first
+    pub unsupported: Vec<(&'static str, usize)>,
+}
+
+/// Why a file under the indexed path is not in the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SkipReason {
+    /// A parser exists, but the extension is not in `--extensions`
+    Excluded,
+    /// No parser (built-in or plugin) for the extension
+    Unsupported,
+    /// Over `MAX_FILE_BYTES`
+    TooLarge,
+    /// Not valid UTF-8, or could not be read
+    Unreadable,
+    /// Not parsed before the `--timeout` budget ran out
+    TimedOut,
+    /// Under one of `VENDOR_DIRS`, left out by `--no-vendor`
+    Vendored,
+    /// One of `IGNORED_DIRS`, not walked (the path is the directory)
+    Ignored,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::Excluded => "excluded",
+            SkipReason::Unsupported => "unsupported",
+            SkipReason::TooLarge => "too-large",
+            SkipReason::Unreadable => "unreadable",
+            SkipReason::TimedOut => "timed-out",
+            SkipReason::Vendored => "vendored",
+            SkipReason::Ignored => "ignored",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SkipReason::Excluded => "extension not in --extensions",
+            SkipReason::Unsupported => "no parser for the extension",
+            SkipReason::TooLarge => "larger than 2 MiB",
+            SkipReason::Unreadable => "not UTF-8 or unreadable",
+            SkipReason::TimedOut => "not reached before --timeout",
+            SkipReason::Vendored => "third-party directory, left out by --no-vendor",
+            SkipReason::Ignored => "ignored directory, not walked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+    /// File size in bytes (0 for ignored directories)
+    pub size: u64,
+}
+
 pub fn detect_language(path: &Path) -> Language {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(Language::from_extension)
-        .unwrap_or(Language::Unknown)
+    let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+        return Language::Unknown;
+    };
+
+    match Language::from_extension(ext) {
+        Language::Unknown if registry::parser_for_extension(ext).is_some() => Language::Plugin,
+        language => language,
+    }
 }
 
 fn read_file_content(path: &Path) -> Result<String> {
-    fs::read_to_string(path).with_context(|| format!("Failed to read file: {}", path.display()))
+    fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file: {}", path.display_slash()))
 }
 
 fn hash_content_blake3(content: &str) -> String {
@@ -40,6 +140,91 @@ fn hash_content_blake3(content: &str) -> String {
     format!("blake3:{}", hasher.finalize().to_hex())
 }
 
+/// Code, comment and blank lines of `content`. Comments are `#` (Python, which also counts
+/// docstrings), `--` and `--[[ ]]` (Lua), `<!-- -->` (Markdown) or `//` and `/* */`
+/// (everything else); a `"` string on a line is skipped so a comment marker inside it doesn't
+/// count.
+pub fn count_lines(content: &str, language: Language) -> LineCounts {
+    let (line_comment, block): (Option<&str>, Option<(&str, &str)>) = match language {
+        Language::Python => (Some("#"), None),
+        Language::Lua => (Some("--"), Some(("--[[", "]]"))),
+        Language::Markdown => (None, Some(("<!--", "-->"))),
+        _ => (Some("//"), Some(("/*", "*/"))),
+    };
+
+    let mut counts = LineCounts::default();
+    // Closing delimiter of the block comment or docstring the scan is in
+    let mut open: Option<&str> = None;
+    for line in content.lines() {
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            counts.blank += 1;
+            continue;
+        }
+        let (mut code, mut comment) = (false, false);
+        while !rest.is_empty() {
+            if let Some(close) = open {
+                comment = true;
+                let Some(end) = rest.find(close) else {
+                    break;
+                };
+                rest = rest[end + close.len()..].trim_start();
+                open = None;
+                continue;
+            }
+            // A string standing alone as a statement is a docstring
+            if language == Language::Python && !code {
+                if let Some(quotes) = ["\"\"\"", "'''"].into_iter().find(|q| rest.starts_with(q)) {
+                    comment = true;
+                    open = Some(quotes);
+                    rest = &rest[3..];
+                    continue;
+                }
+            }
+            // Before line comments, which Lua's block comments start like
+            if let Some((start, close)) = block.filter(|(start, _)| rest.starts_with(start)) {
+                comment = true;
+                open = Some(close);
+                rest = &rest[start.len()..];
+                continue;
+            }
+            if line_comment.is_some_and(|marker| rest.starts_with(marker)) {
+                comment = true;
+                break;
+            }
+
+            code = true;
+            let mut chars = rest.char_indices();
+            let Some((_, c)) = chars.next() else {
+                break;
+            };
+            let mut end = c.len_utf8();
+            if c == '"' && language != Language::Markdown {
+                let mut escaped = false;
+                end = rest.len();
+                for (i, c) in chars {
+                    match c {
+                        '"' if !escaped => {
+                            end = i + 1;
+                            break;
+                        }
+                        '\\' => escaped = !escaped,
+                        _ => escaped = false,
+                    }
+                }
+            }
+            rest = &rest[end..];
+        }
+
+        if code {
+            counts.code += 1;
+        } else if comment {
+            counts.comment += 1;
+        }
+    }
+    counts
+}
+
 pub fn index_file(
     path: &Path,
     content: &str,
@@ -51,6 +236,7 @@ pub fn index_file(
         .map(|h| h.to_string())
         .unwrap_or_else(|| hash_content_blake3(content));
     let mut file_info = FileInfo::new(path.to_path_buf(), language, size, hash);
+    file_info.lines = count_lines(content, language);
 
     match language {
         Language::Python => {
@@ -109,6 +295,70 @@ pub fn index_file(
                 }
             }
         }
+        Language::Cpp => {
+            if let Ok(parser) = CppParser::new() {
+                if let Ok(parsed) = parser.parse(content, path) {
+                    file_info.symbols = parsed.symbols;
+                    file_info.dependencies = parsed.dependencies;
+                }
+            }
+        }
+        Language::CSharp => {
+            if let Ok(parser) = CSharpParser::new() {
+                if let Ok(parsed) = parser.parse(content, path) {
+                    file_info.symbols = parsed.symbols;
+                    file_info.dependencies = parsed.dependencies;
+                }
+            }
+        }
+        Language::Php => {
+            if let Ok(parser) = PhpParser::new() {
+                if let Ok(parsed) = parser.parse(content, path) {
+                    file_info.symbols = parsed.symbols;
+                    file_info.dependencies = parsed.dependencies;
+                }
+            }
+        }
+        Language::Kotlin => {
+            if let Ok(parser) = KotlinParser::new() {
+                if let Ok(parsed) = parser.parse(content, path) {
+                    file_info.symbols = parsed.symbols;
+                    file_info.dependencies = parsed.dependencies;
+                }
+            }
+        }
+        Language::Swift => {
+            if let Ok(parser) = SwiftParser::new() {
+                if let Ok(parsed) = parser.parse(content, path) {
+                    file_info.symbols = parsed.symbols;
+                    file_info.dependencies = parsed.dependencies;
+                }
+            }
+        }
+        Language::Scala => {
+            if let Ok(parser) = ScalaParser::new() {
+                if let Ok(parsed) = parser.parse(content, path) {
+                    file_info.symbols = parsed.symbols;
+                    file_info.dependencies = parsed.dependencies;
+                }
+            }
+        }
+        Language::Lua => {
+            if let Ok(parser) = LuaParser::new() {
+                if let Ok(parsed) = parser.parse(content, path) {
+                    file_info.symbols = parsed.symbols;
+                    file_info.dependencies = parsed.dependencies;
+                }
+            }
+        }
+        Language::Zig => {
+            if let Ok(parser) = ZigParser::new() {
+                if let Ok(parsed) = parser.parse(content, path) {
+                    file_info.symbols = parsed.symbols;
+                    file_info.dependencies = parsed.dependencies;
+                }
+            }
+        }
         Language::Markdown => {
             if let Ok(parser) = MarkdownParser::new() {
                 if let Ok(parsed) = parser.parse(content, path) {
@@ -117,12 +367,276 @@ pub fn index_file(
                 }
             }
         }
+        Language::Plugin => {
+            let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            if let Some(parser) = registry::parser_for_extension(ext) {
+                match parser.parse(content, path) {
+                    Ok(parsed) => {
+                        file_info.symbols = parsed.symbols;
+                        file_info.dependencies = parsed.dependencies;
+                    }
+                    Err(e) => eprintln!("Warning: {:#}", e),
+                }
+            }
+        }
         Language::Unknown => {}
     }
 
+    assign_byte_ranges(&mut file_info.symbols, content);
+    annotations::attach(&mut file_info.symbols, content, language);
+
     Ok(file_info)
 }
 
+/// Record the calls and imported names in `content`, so callers and usages can be looked up
+/// in the index (and its cache) without parsing every file again. Left unrecorded when the
+/// calls cannot be extracted, and lookups parse the file instead.
+pub fn record_references(file_info: &mut FileInfo, content: &str) {
+    let Ok(calls) = callgraph::extract_calls_from_source(content, file_info.language) else {
+        return;
+    };
+    let calls = calls.into_iter().map(|(name, line, _)| Reference {
+        name,
+        kind: ReferenceKind::Call,
+        line,
+    });
+    let imports = symbol_deps::imports(content, file_info.language)
+        .into_iter()
+        .map(|import| Reference {
+            name: import.bound,
+            kind: ReferenceKind::Import,
+            line: import.line,
+        });
+    file_info.references = Some(calls.chain(imports).collect());
+}
+
+/// Record where each symbol's lines start and end so bodies can be read with a seek
+fn assign_byte_ranges(symbols: &mut [Symbol], content: &str) {
+    let mut line_starts = vec![0];
+    line_starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+
+    for symbol in symbols {
+        if symbol.line_start == 0 || symbol.line_start > symbol.line_end {
+            continue;
+        }
+        let Some(&start) = line_starts.get(symbol.line_start - 1) else {
+            continue;
+        };
+        let end = line_starts
+            .get(symbol.line_end)
+            .copied()
+            .unwrap_or(content.len());
+        symbol.byte_range = Some((start, end));
+    }
+}
+
+/// Files under `path` with one of `extensions` (all files if empty), skipping ignored directories
+/// and files over `MAX_FILE_BYTES`. Walks directories on all cores; the result is sorted so
+/// callers see a stable order.
+pub fn discover_files(path: &Path, extensions: &[&str]) -> Vec<PathBuf> {
+    let files = Mutex::new(Vec::new());
+
+    // Standard filters off: .gitignore and hidden files are not special here, only IGNORED_DIRS
+    WalkBuilder::new(path)
+        .standard_filters(false)
+        .filter_entry(|e| {
+            let is_dir = e.file_type().is_some_and(|ft| ft.is_dir());
+            e.depth() == 0
+                || !is_dir
+                || !IGNORED_DIRS.contains(&e.file_name().to_string_lossy().as_ref())
+        })
+        .build_parallel()
+        .run(|| {
+            let files = &files;
+            Box::new(move |entry| {
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                    return WalkState::Continue;
+                }
+
+                let matches =
+                    extensions.is_empty() || paths::has_extension(entry.path(), extensions);
+                let too_large = || entry.metadata().is_ok_and(|m| m.len() > MAX_FILE_BYTES);
+                if matches && !too_large() {
+                    if let Ok(mut files) = files.lock() {
+                        files.push(entry.into_path());
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+
+    let mut files = files.into_inner().unwrap_or_else(|e| e.into_inner());
+    files.sort();
+    files
+}
+
+/// The parseable languages present under `path` (a file counts for its own extension).
+/// Falls back to every supported extension when nothing parseable is found, so a typo'd
+/// or empty path still reports its usual errors.
+pub fn detect_extensions(path: &Path) -> DetectedExtensions {
+    let files = if path.is_file() {
+        vec![path.to_path_buf()]
+    } else {
+        discover_files(path, &[])
+    };
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for file in &files {
+        if let Some(ext) = file.extension().and_then(|ext| ext.to_str()) {
+            *counts.entry(ext.to_ascii_lowercase()).or_default() += 1;
+        }
+    }
+
+    let mut candidates: Vec<String> = SUPPORTED_EXTENSIONS.iter().map(|e| e.to_string()).collect();
+    for ext in registry::registered_extensions() {
+        if !candidates.contains(&ext) {
+            candidates.push(ext);
+        }
+    }
+    let present: Vec<String> = candidates
+        .iter()
+        .filter(|ext| counts.contains_key(*ext))
+        .cloned()
+        .collect();
+    let extensions = if present.is_empty() {
+        candidates
+    } else {
+        present
+    };
+
+    let mut unsupported: Vec<(&'static str, usize)> = Vec::new();
+    for (ext, language) in UNSUPPORTED_LANGUAGES {
+        let Some(&count) = counts.get(*ext) else {
+            continue;
+        };
+        if registry::parser_for_extension(ext).is_some() {
+            continue;
+        }
+        match unsupported.iter_mut().find(|(name, _)| name == language) {
+            Some((_, total)) => *total += count,
+            None => unsupported.push((language, count)),
+        }
+    }
+    unsupported.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    DetectedExtensions {
+        extensions,
+        unsupported,
+    }
+}
+
+/// Every file under `path` that `index` does not contain, with the reason, sorted by reason
+/// then path. Directories in `IGNORED_DIRS` are reported once each instead of per file.
+pub fn skipped_files(path: &Path, extensions: &[&str], index: &CodeIndex) -> Vec<SkippedFile> {
+    let indexed: HashSet<&Path> = index.files().map(|file| file.path.as_path()).collect();
+    let ignored_dirs = Arc::new(Mutex::new(Vec::new()));
+    let pruned = Arc::clone(&ignored_dirs);
+    let walker = WalkBuilder::new(path)
+        .standard_filters(false)
+        .filter_entry(move |e| {
+            let is_dir = e.file_type().is_some_and(|ft| ft.is_dir());
+            let ignored = e.depth() > 0
+                && is_dir
+                && IGNORED_DIRS.contains(&e.file_name().to_string_lossy().as_ref());
+            if ignored {
+                if let Ok(mut dirs) = pruned.lock() {
+                    dirs.push(e.path().to_path_buf());
+                }
+            }
+            !ignored
+        })
+        .build();
+
+    let mut skipped = Vec::new();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) || indexed.contains(entry.path()) {
+            continue;
+        }
+        let size = entry.metadata().map_or(0, |m| m.len());
+        let ext = entry.path().extension().and_then(|ext| ext.to_str());
+        let reason = match ext {
+            Some(_) if paths::has_extension(entry.path(), extensions) => {
+                if vendor_root(entry.path(), path).is_some() {
+                    SkipReason::Vendored
+                } else if detect_language(entry.path()) == Language::Unknown {
+                    SkipReason::Unsupported
+                } else if size > MAX_FILE_BYTES {
+                    SkipReason::TooLarge
+                } else if deadline::truncated() {
+                    SkipReason::TimedOut
+                } else {
+                    SkipReason::Unreadable
+                }
+            }
+            Some(_) if detect_language(entry.path()) != Language::Unknown => SkipReason::Excluded,
+            _ => SkipReason::Unsupported,
+        };
+        skipped.push(SkippedFile {
+            path: entry.into_path(),
+            reason,
+            size,
+        });
+    }
+
+    let dirs = ignored_dirs.lock().map(|d| d.clone()).unwrap_or_default();
+    skipped.extend(dirs.into_iter().map(|path| SkippedFile {
+        path,
+        reason: SkipReason::Ignored,
+        size: 0,
+    }));
+    skipped.sort_by(|a, b| a.reason.cmp(&b.reason).then(a.path.cmp(&b.path)));
+    skipped
+}
+
+/// Vendored files removed from an index by `remove_vendored`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VendoredStats {
+    pub files: usize,
+    pub symbols: usize,
+    pub lines: LineCounts,
+    /// Each vendored package (`vendor/github.com`, `third_party/zlib`) with its file count,
+    /// sorted by path
+    pub roots: Vec<(PathBuf, usize)>,
+}
+
+/// The vendored package `path` belongs to: the `VENDOR_DIRS` directory below `root` plus the
+/// directory under it, if any. `None` for first-party files.
+pub fn vendor_root(path: &Path, root: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let components: Vec<_> = relative.components().collect();
+    let position = components
+        .iter()
+        .position(|c| VENDOR_DIRS.contains(&c.as_os_str().to_string_lossy().as_ref()))?;
+    // The package directory, unless the file sits directly in the vendor directory
+    let end = (position + 2).min(components.len() - 1).max(position + 1);
+    Some(root.join(components[..end].iter().collect::<PathBuf>()))
+}
+
+/// Take vendored files (see `VENDOR_DIRS`) under `root` out of `index` and count them
+pub fn remove_vendored(index: &mut CodeIndex, root: &Path) -> VendoredStats {
+    let vendored: Vec<(PathBuf, PathBuf, LineCounts)> = index
+        .files()
+        .filter_map(|file| {
+            vendor_root(&file.path, root).map(|dir| (file.path.clone(), dir, file.lines))
+        })
+        .collect();
+
+    let mut stats = VendoredStats::default();
+    let mut roots: HashMap<PathBuf, usize> = HashMap::new();
+    for (path, dir, lines) in vendored {
+        stats.files += 1;
+        stats.lines.add(lines);
+        stats.symbols += index.get_file_symbols(&path).len();
+        *roots.entry(dir).or_default() += 1;
+        index.remove_file(&path);
+    }
+    stats.roots = roots.into_iter().collect();
+    stats.roots.sort();
+    stats
+}
+
 pub fn index_directory(path: &Path, extensions: &[&str]) -> Result<CodeIndex> {
     index_directory_with_progress(path, extensions, None)
 }
@@ -133,39 +647,27 @@ pub fn index_directory_with_progress(
     progress: Option<ProgressBar>,
 ) -> Result<CodeIndex> {
     if !path.exists() {
-        anyhow::bail!("Directory does not exist: {}", path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::PathNotFound,
+            format!("Directory does not exist: {}", path.display_slash())
+        ));
     }
 
     if !path.is_dir() {
-        anyhow::bail!("Path is not a directory: {}", path.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!("Path is not a directory: {}", path.display_slash())
+        ));
     }
 
-    let entries: Vec<PathBuf> = WalkDir::new(path)
-        .into_iter()
-        .filter_entry(|e| {
-            if e.file_type().is_dir() {
-                let dir_name = e.file_name().to_string_lossy();
-                !IGNORED_DIRS.contains(&dir_name.as_ref())
-            } else {
-                true
-            }
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| {
-            if extensions.is_empty() {
-                true
-            } else {
-                e.path()
-                    .extension()
-                    .and_then(|ext| ext.to_str())
-                    .map(|ext| extensions.contains(&ext))
-                    .unwrap_or(false)
-            }
-        })
-        .map(|e| e.into_path())
-        .collect();
+    index_files_with_progress(&discover_files(path, extensions), progress)
+}
 
+/// Index an already-discovered file list (see `discover_files`)
+pub fn index_files_with_progress(
+    entries: &[PathBuf],
+    progress: Option<ProgressBar>,
+) -> Result<CodeIndex> {
     let total_files = entries.len();
     let progress_wrapper = progress.map(|pb| {
         pb.set_length(total_files as u64);
@@ -175,6 +677,11 @@ pub fn index_directory_with_progress(
     let file_infos: Vec<FileInfo> = entries
         .par_iter()
         .filter_map(|file_path| {
+            // Out of `--timeout` budget: leave the remaining files out of the index
+            if deadline::expired() {
+                return None;
+            }
+
             let language = detect_language(file_path);
 
             if language == Language::Unknown {
@@ -199,7 +706,10 @@ pub fn index_directory_with_progress(
             };
 
             let result = match index_file(file_path, &content, language, None) {
-                Ok(info) => Some(info),
+                Ok(mut info) => {
+                    record_references(&mut info, &content);
+                    Some(info)
+                }
                 Err(_) => None,
             };
 
@@ -247,4 +757,155 @@ mod tests {
         assert!(IGNORED_DIRS.contains(&"node_modules"));
         assert!(IGNORED_DIRS.contains(&"__pycache__"));
     }
+
+    #[test]
+    fn test_byte_ranges_cover_symbol_lines() {
+        let source = "import os\n\ndef load():\n    return 1\n";
+        let file = index_file(Path::new("m.py"), source, Language::Python, None).unwrap();
+
+        let load = file.symbols.iter().find(|s| s.name == "load").unwrap();
+        let (start, end) = load.byte_range.unwrap();
+        assert_eq!(&source[start..end], "def load():\n    return 1\n");
+    }
+
+    #[test]
+    fn test_discover_files_sorted_and_filtered() {
+        let dir = std::env::temp_dir().join(format!("cm-discover-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("node_modules/pkg")).unwrap();
+        fs::create_dir_all(dir.join(".hidden")).unwrap();
+        fs::write(dir.join("src/b.py"), "").unwrap();
+        fs::write(dir.join("a.py"), "").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        fs::write(dir.join("node_modules/pkg/index.py"), "").unwrap();
+        fs::write(dir.join(".hidden/c.py"), "").unwrap();
+
+        let files = discover_files(&dir, &["py"]);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            files,
+            vec![dir.join(".hidden/c.py"), dir.join("a.py"), dir.join("src/b.py")]
+        );
+    }
+
+    #[test]
+    fn test_detect_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("src/app.tsx"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+        fs::write(dir.path().join("app.rb"), "").unwrap();
+        fs::write(dir.path().join("model.rb"), "").unwrap();
+        fs::write(dir.path().join("node_modules/pkg/index.js"), "").unwrap();
+
+        let detected = detect_extensions(dir.path());
+        assert_eq!(detected.extensions, vec!["tsx", "rs", "md"]);
+        assert_eq!(detected.unsupported, vec![("Ruby", 2)]);
+
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(
+            detect_extensions(empty.path()).extensions.len(),
+            SUPPORTED_EXTENSIONS.len()
+        );
+    }
+
+    #[test]
+    fn test_skipped_files_reasons() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(root.join("node_modules/pkg/index.py"), "").unwrap();
+        fs::write(root.join("app.py"), "def main():\n    pass\n").unwrap();
+        fs::write(root.join("lib.rs"), "fn f() {}\n").unwrap();
+        fs::write(root.join("notes.txt"), "").unwrap();
+        fs::write(root.join("latin1.py"), b"s = '\xe9'\n").unwrap();
+        fs::write(root.join("huge.py"), vec![b'#'; MAX_FILE_BYTES as usize + 1]).unwrap();
+
+        let index = index_directory(root, &["py"]).unwrap();
+        assert_eq!(index.total_files(), 1);
+        let reasons: Vec<(SkipReason, PathBuf)> = skipped_files(root, &["py"], &index)
+            .into_iter()
+            .map(|file| (file.reason, file.path))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (SkipReason::Excluded, root.join("lib.rs")),
+                (SkipReason::Unsupported, root.join("notes.txt")),
+                (SkipReason::TooLarge, root.join("huge.py")),
+                (SkipReason::Unreadable, root.join("latin1.py")),
+                (SkipReason::Ignored, root.join("node_modules")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_lines() {
+        let rust = "//! Crate docs\n\nfn main() { // entry\n    let s = \"/* not a comment\";\n    /* one\n\n       two */ run();\n}\n/* three\n*/\n";
+        assert_eq!(
+            count_lines(rust, Language::Rust),
+            LineCounts {
+                code: 4,
+                comment: 4,
+                blank: 2
+            }
+        );
+
+        let python = "#!/usr/bin/env python\ndef f():\n    \"\"\"Docs.\n\n    More.\"\"\"\n    return '#'  # trailing\n";
+        assert_eq!(
+            count_lines(python, Language::Python),
+            LineCounts {
+                code: 2,
+                comment: 3,
+                blank: 1
+            }
+        );
+
+        let lua = "-- Config\nlocal x = 1 -- trailing\n--[[ block\nstill ]]\n\nreturn x\n";
+        assert_eq!(
+            count_lines(lua, Language::Lua),
+            LineCounts {
+                code: 2,
+                comment: 3,
+                blank: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_remove_vendored() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("vendor/github.com/pkg/errors")).unwrap();
+        fs::create_dir_all(root.join("src/third_party/zlib")).unwrap();
+        fs::write(root.join("main.go"), "package main\n\nfunc main() {}\n").unwrap();
+        fs::write(
+            root.join("vendor/github.com/pkg/errors/errors.go"),
+            "package errors\n\nfunc New() {}\nfunc Wrap() {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("vendor/modules.go"), "package vendor\n").unwrap();
+        fs::write(
+            root.join("src/third_party/zlib/zlib.c"),
+            "int inflate(void) { return 0; }\n",
+        )
+        .unwrap();
+
+        let mut index = index_directory(root, &["go", "c"]).unwrap();
+        let stats = remove_vendored(&mut index, root);
+
+        assert_eq!(index.total_files(), 1);
+        assert_eq!((stats.files, stats.symbols), (3, 3));
+        assert_eq!(
+            stats.roots,
+            vec![
+                (root.join("src/third_party/zlib"), 1),
+                (root.join("vendor"), 1),
+                (root.join("vendor/github.com"), 1),
+            ]
+        );
+    }
 }
@@ -0,0 +1,235 @@
+//! `cm map --mode files-of-interest`: a shortlist of the files worth opening first. Each
+//! non-test source file is scored on how much it defines, how much of that is exported, how
+//! central it is (files importing it, calls into its functions and types), and how recently
+//! it changed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::callgraph;
+use crate::dep_tree;
+use crate::git;
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+use crate::paths;
+
+/// Share of the score taken by symbols, exports, importing files, incoming calls and recency
+const WEIGHTS: [f64; 5] = [0.15, 0.2, 0.25, 0.2, 0.2];
+
+/// Commits read to date files; older files count as not recently changed
+const HISTORY_COMMITS: usize = 1000;
+
+pub struct FileOfInterest<'a> {
+    pub path: &'a Path,
+    pub language: Language,
+    pub symbols: usize,
+    pub exports: usize,
+    /// Other files importing this one
+    pub importers: usize,
+    /// Calls into the file's functions and types, split between definitions sharing a name.
+    /// Methods are left out: `get` or `new` calls mostly land elsewhere.
+    pub calls: usize,
+    /// Days since the last commit touching the file (its mtime outside git)
+    pub age_days: Option<u64>,
+    /// 0 to 1
+    pub score: f64,
+    /// Exported functions and types first, most-called first; at most three
+    pub top_symbols: Vec<&'a str>,
+}
+
+fn ranks(symbol: &Symbol) -> bool {
+    !symbol.name.is_empty() && symbol.name != "anonymous" && symbol.symbol_type != SymbolType::Field
+}
+
+/// Unix time each file last changed: its last commit in git, its mtime otherwise
+fn last_changed<'a>(index: &'a CodeIndex, root: &Path) -> HashMap<&'a Path, i64> {
+    let commits = if git::is_git_repo(root) {
+        git::last_commit_times(root, HISTORY_COMMITS).unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
+    index
+        .files()
+        .filter_map(|file| {
+            let time = if commits.is_empty() {
+                let modified = fs::metadata(&file.path).and_then(|m| m.modified()).ok()?;
+                modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64
+            } else {
+                *commits.get(&paths::canonicalize(&file.path).ok()?)?
+            };
+            Some((file.path.as_path(), time))
+        })
+        .collect()
+}
+
+/// `ln(1 + value) / ln(1 + max)`, so one huge file doesn't flatten everything else
+fn scaled(value: f64, max: f64) -> f64 {
+    if max <= 0.0 {
+        0.0
+    } else {
+        value.ln_1p() / max.ln_1p()
+    }
+}
+
+/// The `limit` highest-scoring non-test source files under `root`, best first
+pub fn files_of_interest<'a>(
+    index: &'a CodeIndex,
+    root: &Path,
+    limit: usize,
+) -> Vec<FileOfInterest<'a>> {
+    let calls = callgraph::call_counts(index);
+    let importers = dep_tree::importers(index);
+    let changed = last_changed(index, root);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+
+    let mut files: Vec<(&Path, Language, Vec<&Symbol>)> = Vec::new();
+    let mut definitions: HashMap<&str, usize> = HashMap::new();
+    for file in index.files() {
+        if file.language == Language::Markdown || callgraph::is_test_file(&file.path, file.language)
+        {
+            continue;
+        }
+        let symbols: Vec<&Symbol> = index
+            .get_file_symbols(&file.path)
+            .into_iter()
+            .filter(|s| ranks(s))
+            .collect();
+        if symbols.is_empty() {
+            continue;
+        }
+        for symbol in &symbols {
+            *definitions.entry(symbol.name.as_str()).or_default() += 1;
+        }
+        files.push((&file.path, file.language, symbols));
+    }
+    let share = |symbol: &Symbol| -> f64 {
+        if symbol.symbol_type == SymbolType::Method {
+            return 0.0;
+        }
+        match (
+            calls.get(symbol.name.as_str()),
+            definitions.get(symbol.name.as_str()),
+        ) {
+            (Some(&calls), Some(&count)) => calls as f64 / count as f64,
+            _ => 0.0,
+        }
+    };
+
+    let mut ranked: Vec<(FileOfInterest, f64)> = files
+        .into_iter()
+        .map(|(path, language, mut symbols)| {
+            // Parsers index enum variants as `Enum`s signed "variant of <enum>"
+            let variant = |s: &Symbol| {
+                s.signature
+                    .as_deref()
+                    .is_some_and(|sig| sig.starts_with("variant of"))
+            };
+            let describes = |s: &Symbol| {
+                s.is_exported
+                    && !variant(s)
+                    && matches!(
+                        s.symbol_type,
+                        SymbolType::Function
+                            | SymbolType::Class
+                            | SymbolType::Enum
+                            | SymbolType::Interface
+                            | SymbolType::TypeAlias
+                    )
+            };
+            symbols.sort_by(|a, b| {
+                describes(b)
+                    .cmp(&describes(a))
+                    .then(share(b).total_cmp(&share(a)))
+                    .then(a.line_start.cmp(&b.line_start))
+            });
+            let incoming: f64 = symbols.iter().map(|s| share(s)).sum();
+            let file = FileOfInterest {
+                path,
+                language,
+                symbols: symbols.len(),
+                exports: symbols.iter().filter(|s| s.is_exported).count(),
+                importers: importers.get(path).copied().unwrap_or(0),
+                calls: incoming.round() as usize,
+                age_days: changed
+                    .get(path)
+                    .map(|&time| (now - time).max(0) as u64 / 86_400),
+                score: 0.0,
+                top_symbols: symbols.iter().take(3).map(|s| s.name.as_str()).collect(),
+            };
+            (file, incoming)
+        })
+        .collect();
+
+    let max = |value: &dyn Fn(&(FileOfInterest, f64)) -> f64| {
+        ranked.iter().map(value).fold(0.0, f64::max)
+    };
+    let max_symbols = max(&|(f, _)| f.symbols as f64);
+    let max_exports = max(&|(f, _)| f.exports as f64);
+    let max_importers = max(&|(f, _)| f.importers as f64);
+    let max_calls = max(&|(_, calls)| *calls);
+    let max_age = max(&|(f, _)| f.age_days.unwrap_or(0) as f64);
+
+    for (file, incoming) in &mut ranked {
+        let recency = match file.age_days {
+            Some(age) if max_age > 0.0 => 1.0 - age as f64 / max_age,
+            Some(_) => 1.0,
+            None => 0.0,
+        };
+        let parts = [
+            scaled(file.symbols as f64, max_symbols),
+            scaled(file.exports as f64, max_exports),
+            scaled(file.importers as f64, max_importers),
+            scaled(*incoming, max_calls),
+            recency,
+        ];
+        file.score = parts.iter().zip(WEIGHTS).map(|(part, w)| part * w).sum();
+    }
+
+    let mut ranked: Vec<FileOfInterest> = ranked.into_iter().map(|(f, _)| f).collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.path.cmp(b.path)));
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_directory;
+
+    #[test]
+    fn test_files_of_interest_ranks_central_files_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write(
+            "core.py",
+            "class Config:\n    def get(self):\n        pass\n\ndef parse():\n    pass\n\ndef render():\n    pass\n",
+        );
+        write(
+            "app.py",
+            "from core import parse, render\n\ndef main():\n    parse()\n    render()\n    parse()\n    cache.get()\n",
+        );
+        write("util.py", "def unused():\n    pass\n");
+        write(
+            "test_core.py",
+            "from core import parse\n\ndef test_parse():\n    parse()\n",
+        );
+
+        let index = index_directory(dir.path(), &["py"]).unwrap();
+        let ranked = files_of_interest(&index, dir.path(), 2);
+
+        let names: Vec<&str> = ranked
+            .iter()
+            .map(|f| f.path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["core.py", "app.py"]);
+        assert_eq!(ranked[0].importers, 2);
+        // `cache.get()` is not a call into `Config.get`
+        assert_eq!(ranked[0].calls, 4);
+        assert_eq!(ranked[0].top_symbols, vec!["parse", "render", "Config"]);
+        assert_eq!(ranked[0].age_days, Some(0));
+    }
+}
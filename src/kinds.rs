@@ -0,0 +1,296 @@
+//! Symbol kinds beyond the built-in ones (`component`, `route`, `migration`, `job`, ...),
+//! declared as `[[kinds]]` in config or by a parser plugin. A kind has a name, used wherever
+//! kinds are spelled out (`--type`, plugin output, JSON), and a short code for the ai format.
+//! Like the parser registry the kinds are process-wide, and once registered they stay.
+
+use anyhow::{bail, Result};
+use std::sync::{OnceLock, RwLock};
+
+use crate::config::KindConfig;
+use crate::models::{Language, SymbolType};
+
+/// Longest code a kind may use in the ai format
+const MAX_CODE_LEN: usize = 3;
+
+/// Languages `cm kinds` reports on, plugins last
+const LANGUAGES: [Language; 17] = [
+    Language::Python,
+    Language::JavaScript,
+    Language::TypeScript,
+    Language::Rust,
+    Language::Java,
+    Language::Go,
+    Language::C,
+    Language::Cpp,
+    Language::CSharp,
+    Language::Php,
+    Language::Kotlin,
+    Language::Swift,
+    Language::Scala,
+    Language::Lua,
+    Language::Zig,
+    Language::Markdown,
+    Language::Plugin,
+];
+
+/// A registered kind. `SymbolType` serializes it by name, so caches and JSON do not depend
+/// on the order kinds were registered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KindId(u16);
+
+struct Kind {
+    name: &'static str,
+    code: &'static str,
+    plural: &'static str,
+}
+
+fn registry() -> &'static RwLock<Vec<Kind>> {
+    static REGISTRY: OnceLock<RwLock<Vec<Kind>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+impl KindId {
+    fn with<T>(self, f: impl FnOnce(&Kind) -> T) -> T {
+        let kinds = registry().read().unwrap_or_else(|e| e.into_inner());
+        f(&kinds[self.0 as usize])
+    }
+
+    pub fn name(self) -> &'static str {
+        self.with(|kind| kind.name)
+    }
+
+    pub fn code(self) -> &'static str {
+        self.with(|kind| kind.code)
+    }
+
+    pub fn plural(self) -> &'static str {
+        self.with(|kind| kind.plural)
+    }
+}
+
+/// Strings live as long as the registry, which is the whole process
+fn leak(text: String) -> &'static str {
+    Box::leak(text.into_boxed_str())
+}
+
+/// Register a kind. Registering the same name and code again returns the existing kind, so
+/// user and project config may both declare it.
+pub fn register(config: &KindConfig) -> Result<SymbolType> {
+    let name = config.name.trim().to_lowercase();
+    let code = config.code.trim().to_lowercase();
+    let plural = config
+        .plural
+        .as_deref()
+        .map(|plural| plural.trim().to_lowercase())
+        .unwrap_or_else(|| format!("{}s", name));
+
+    let is_word = |text: &str| {
+        !text.is_empty()
+            && text
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    if !is_word(&name) || !is_word(&plural) {
+        bail!(
+            "Invalid kind name '{}': use letters, digits, '_' and '-'",
+            config.name
+        );
+    }
+    if code.is_empty()
+        || code.len() > MAX_CODE_LEN
+        || !code.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        bail!(
+            "Invalid code '{}' for kind '{}': use 1-{} letters or digits",
+            config.code,
+            name,
+            MAX_CODE_LEN
+        );
+    }
+    if SymbolType::builtin_from_str(&name).is_some()
+        || SymbolType::builtin_from_plural(&plural).is_some()
+    {
+        bail!("Kind '{}' is built in and cannot be redeclared", name);
+    }
+    if let Some(builtin) = SymbolType::BUILTIN.iter().find(|t| t.code() == code) {
+        bail!(
+            "Code '{}' for kind '{}' is taken by the built-in kind '{}'",
+            code,
+            name,
+            builtin.as_str()
+        );
+    }
+
+    let mut kinds = registry().write().unwrap_or_else(|e| e.into_inner());
+    if let Some(id) = kinds.iter().position(|kind| kind.name == name) {
+        if kinds[id].code != code {
+            bail!(
+                "Kind '{}' is already registered with code '{}'",
+                name,
+                kinds[id].code
+            );
+        }
+        return Ok(SymbolType::Custom(KindId(id as u16)));
+    }
+    if let Some(other) = kinds.iter().find(|kind| kind.code == code) {
+        bail!(
+            "Code '{}' for kind '{}' is taken by the kind '{}'",
+            code,
+            name,
+            other.name
+        );
+    }
+    kinds.push(Kind {
+        name: leak(name),
+        code: leak(code),
+        plural: leak(plural),
+    });
+    Ok(SymbolType::Custom(KindId((kinds.len() - 1) as u16)))
+}
+
+/// Register every kind in `kinds`; returns how many there were
+pub fn register_kinds(kinds: &[KindConfig]) -> Result<usize> {
+    for kind in kinds {
+        register(kind)?;
+    }
+    Ok(kinds.len())
+}
+
+/// A registered kind by name (`plural: false`) or plural, case-insensitively
+pub fn lookup(name: &str, plural: bool) -> Option<KindId> {
+    let kinds = registry().read().unwrap_or_else(|e| e.into_inner());
+    kinds
+        .iter()
+        .position(|kind| {
+            let spelled = if plural { kind.plural } else { kind.name };
+            spelled.eq_ignore_ascii_case(name)
+        })
+        .map(|id| KindId(id as u16))
+}
+
+/// Every registered kind, in registration order
+pub fn custom_kinds() -> Vec<SymbolType> {
+    let count = registry().read().unwrap_or_else(|e| e.into_inner()).len();
+    (0..count)
+        .map(|id| SymbolType::Custom(KindId(id as u16)))
+        .collect()
+}
+
+/// The kind called `name`, registering it with its name as its code when it is not declared
+/// (read back from a cache written under an older config)
+pub fn lookup_or_register(name: &str) -> KindId {
+    if let Some(id) = lookup(name, false) {
+        return id;
+    }
+    let mut kinds = registry().write().unwrap_or_else(|e| e.into_inner());
+    kinds.push(Kind {
+        name: leak(name.to_string()),
+        code: leak(name.to_string()),
+        plural: leak(format!("{}s", name)),
+    });
+    KindId((kinds.len() - 1) as u16)
+}
+
+/// One row of `cm kinds`
+#[derive(Debug, Clone)]
+pub struct KindInfo {
+    pub kind: SymbolType,
+    /// Declared in config or by a plugin rather than built in
+    pub custom: bool,
+    /// Languages whose parser produces the kind
+    pub languages: Vec<Language>,
+}
+
+/// Built-in kinds, then custom ones; with `language`, only the kinds it produces
+pub fn catalog(language: Option<Language>) -> Vec<KindInfo> {
+    let builtin = SymbolType::BUILTIN.iter().map(|kind| KindInfo {
+        kind: *kind,
+        custom: false,
+        languages: LANGUAGES
+            .into_iter()
+            .filter(|language| language.symbol_types().contains(kind))
+            .collect(),
+    });
+    let custom = custom_kinds().into_iter().map(|kind| KindInfo {
+        kind,
+        custom: true,
+        languages: vec![Language::Plugin],
+    });
+    builtin
+        .chain(custom)
+        .filter(|info| language.is_none_or(|language| info.languages.contains(&language)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(name: &str, code: &str) -> KindConfig {
+        KindConfig {
+            name: name.to_string(),
+            code: code.to_string(),
+            plural: None,
+        }
+    }
+
+    #[test]
+    fn test_register_kinds() {
+        let component = register(&kind("Component", "co")).unwrap();
+        assert_eq!(component.as_str(), "component");
+        assert_eq!(component.code(), "co");
+        assert_eq!(register(&kind("component", "co")).unwrap(), component);
+        assert_eq!(SymbolType::from_str("COMPONENT"), Some(component));
+        assert_eq!(SymbolType::from_plural("components"), Some(component));
+
+        // Built-in names and codes, and codes of other kinds, are taken
+        assert!(register(&kind("class", "zz")).is_err());
+        assert!(register(&kind("widget", "f")).is_err());
+        assert!(register(&kind("widget", "co")).is_err());
+        assert!(register(&kind("component", "cp")).is_err());
+        assert!(register(&kind("widget", "wdgt")).is_err());
+
+        let json = serde_json::to_string(&component).unwrap();
+        assert_eq!(json, r#""component""#);
+        assert_eq!(
+            serde_json::from_str::<SymbolType>(&json).unwrap(),
+            component
+        );
+        let forgotten: SymbolType = serde_json::from_str(r#""cron_job""#).unwrap();
+        assert_eq!(forgotten.code(), "cron_job");
+        let legacy: SymbolType = serde_json::from_str(r#""StaticField""#).unwrap();
+        assert_eq!(legacy, SymbolType::StaticField);
+
+        let plugin = catalog(Some(Language::Plugin));
+        assert!(plugin
+            .iter()
+            .any(|info| info.custom && info.kind == component));
+        assert!(catalog(Some(Language::Rust))
+            .iter()
+            .all(|info| !info.custom));
+    }
+
+    /// The per-language table `cm kinds` prints covers what the parsers really produce
+    #[test]
+    fn test_fixture_kinds_are_listed() {
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+        for entry in std::fs::read_dir(fixtures).unwrap() {
+            let path = entry.unwrap().path();
+            if !path.is_file() {
+                continue;
+            }
+            let language = crate::indexer::detect_language(&path);
+            let content = std::fs::read_to_string(&path).unwrap();
+            let file = crate::indexer::index_file(&path, &content, language, None).unwrap();
+            for symbol in &file.symbols {
+                assert!(
+                    language.symbol_types().contains(&symbol.symbol_type),
+                    "{} produces {} symbols ({}), missing from Language::symbol_types",
+                    language.as_str(),
+                    symbol.symbol_type.as_str(),
+                    symbol.name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! `cm layers`: architectural layering checked against the import graph. Layers are declared
+//! top first (`ui -> service -> data`); a file may import its own layer and the layers below
+//! it, and an import reaching up into a higher layer is a violation. Files outside every
+//! layer are not checked.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::config::LayersConfig;
+use crate::dep_tree;
+use crate::failure::{ErrorCode, Failure};
+use crate::filter_expr;
+use crate::index::CodeIndex;
+use crate::models::Dependency;
+use crate::paths::DisplaySlash;
+
+pub struct Layer {
+    pub name: String,
+    globs: Vec<Regex>,
+}
+
+/// Declared layers, top first
+pub struct Layers {
+    layers: Vec<Layer>,
+}
+
+impl Layers {
+    /// `order` as `ui -> service -> data`, with each layer's files matched by its globs in
+    /// `paths`, or by a directory named after the layer
+    pub fn parse(order: &str, paths: &HashMap<String, Vec<String>>) -> Result<Self> {
+        let names: Vec<&str> = order.split("->").map(str::trim).collect();
+        if names.len() < 2 || names.iter().any(|name| name.is_empty()) {
+            anyhow::bail!(Failure::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "Invalid layer order '{}': expected at least two layers, e.g. 'ui -> service -> data'",
+                    order
+                )
+            ));
+        }
+        if let Some(unknown) = paths.keys().find(|key| !names.contains(&key.as_str())) {
+            anyhow::bail!(Failure::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "[layers.paths] names '{}', which is not in the layer order",
+                    unknown
+                )
+            ));
+        }
+
+        let mut layers = Vec::new();
+        for name in names {
+            if layers.iter().any(|layer: &Layer| layer.name == name) {
+                anyhow::bail!(Failure::new(
+                    ErrorCode::InvalidArgument,
+                    format!("Layer '{}' is listed twice in '{}'", name, order)
+                ));
+            }
+            let globs = match paths.get(name) {
+                Some(globs) => globs.clone(),
+                None => vec![name.to_string()],
+            };
+            let globs = globs
+                .iter()
+                .map(|glob| filter_expr::glob_regex(glob))
+                .collect::<Result<Vec<_>, String>>()
+                .map_err(|e| Failure::new(ErrorCode::InvalidArgument, e))?;
+            layers.push(Layer {
+                name: name.to_string(),
+                globs,
+            });
+        }
+        Ok(Layers { layers })
+    }
+
+    /// Layers from `--order`, else from `[layers]` in the config
+    pub fn resolve(order: Option<&str>, config: &LayersConfig) -> Result<Self> {
+        match order.or(config.order.as_deref()) {
+            Some(order) => Self::parse(order, &config.paths),
+            None => anyhow::bail!(Failure::new(
+                ErrorCode::NotConfigured,
+                "No layers declared: pass --order 'ui -> service -> data' or set [layers] order in .codemapper.toml"
+            )),
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(|layer| layer.name.as_str())
+    }
+
+    /// Position of the first layer matching `path` (relative to the checked root), top = 0
+    pub fn layer_of(&self, path: &Path) -> Option<usize> {
+        let path = path.display_slash().to_string();
+        let path = path.strip_prefix("./").unwrap_or(&path);
+        self.layers
+            .iter()
+            .position(|layer| layer.globs.iter().any(|glob| glob.is_match(path)))
+    }
+}
+
+/// An import from a lower layer into a higher one
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub file: PathBuf,
+    /// Line of the import statement, when it could be found again in the source
+    pub line: Option<usize>,
+    /// The import as written
+    pub import: String,
+    pub target: PathBuf,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LayerReport {
+    /// Layers top first, with their file counts
+    pub layers: Vec<(String, usize)>,
+    /// Files in no layer
+    pub unlayered: usize,
+    /// Imports between layers as (from layer, to layer, count), allowed or not
+    pub edges: Vec<(String, String, usize)>,
+    pub violations: Vec<Violation>,
+}
+
+/// Classify every resolved import between two layered files under `root`
+pub fn check(index: &CodeIndex, root: &Path, layers: &Layers) -> LayerReport {
+    let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+    let mut file_layers: HashMap<&Path, usize> = HashMap::new();
+    let mut counts = vec![0; layers.layers.len()];
+    let mut unlayered = 0;
+    for file in index.files() {
+        match layers.layer_of(&relative(&file.path)) {
+            Some(layer) => {
+                file_layers.insert(&file.path, layer);
+                counts[layer] += 1;
+            }
+            None => unlayered += 1,
+        }
+    }
+
+    let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut violations = Vec::new();
+    for (from, dep, target) in dep_tree::resolved_imports(index) {
+        let (Some(&from_layer), Some(&to_layer)) =
+            (file_layers.get(from), file_layers.get(target.as_path()))
+        else {
+            continue;
+        };
+        if from_layer != to_layer {
+            *edges.entry((from_layer, to_layer)).or_default() += 1;
+        }
+        if to_layer < from_layer {
+            violations.push(Violation {
+                from_layer: layers.layers[from_layer].name.clone(),
+                to_layer: layers.layers[to_layer].name.clone(),
+                file: from.to_path_buf(),
+                line: import_line(from, dep),
+                import: dep.import_name.clone(),
+                target,
+            });
+        }
+    }
+    violations.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+
+    let mut edges: Vec<((usize, usize), usize)> = edges.into_iter().collect();
+    edges.sort();
+    LayerReport {
+        layers: layers.names().map(str::to_string).zip(counts).collect(),
+        unlayered,
+        edges: edges
+            .into_iter()
+            .map(|((from, to), count)| {
+                (
+                    layers.layers[from].name.clone(),
+                    layers.layers[to].name.clone(),
+                    count,
+                )
+            })
+            .collect(),
+        violations,
+    }
+}
+
+/// First line of `file` mentioning the import (its module too, for `from x import y`); for
+/// a Rust use tree split over lines, the line holding the path before `{`
+fn import_line(file: &Path, dep: &Dependency) -> Option<usize> {
+    let content = fs::read_to_string(file).ok()?;
+    let module = dep.from_file.as_deref().unwrap_or("");
+    let find = |needle: &str| {
+        content
+            .lines()
+            .position(|line| line.contains(needle) && line.contains(module))
+            .map(|i| i + 1)
+    };
+    find(&dep.import_name).or_else(|| {
+        let prefix = dep.import_name.split('{').next()?.trim_end_matches(':');
+        find(prefix).filter(|_| !prefix.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_directory;
+
+    #[test]
+    fn test_parse_order() {
+        let layers = Layers::parse("ui -> service -> data", &HashMap::new()).unwrap();
+        assert_eq!(
+            layers.names().collect::<Vec<_>>(),
+            ["ui", "service", "data"]
+        );
+        assert_eq!(
+            layers.layer_of(Path::new("./src/service/billing.py")),
+            Some(1)
+        );
+        assert_eq!(layers.layer_of(Path::new("src/services/billing.py")), None);
+        assert!(Layers::parse("ui", &HashMap::new()).is_err());
+        assert!(Layers::parse("ui -> ui", &HashMap::new()).is_err());
+        let unknown = HashMap::from([("db".to_string(), vec!["src/db/**".to_string()])]);
+        assert!(Layers::parse("ui -> data", &unknown).is_err());
+    }
+
+    #[test]
+    fn test_check_reports_upward_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["ui", "service", "data"] {
+            fs::create_dir(dir.path().join(sub)).unwrap();
+        }
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write("ui/view.js", "import { load } from '../service/orders';\n");
+        write(
+            "service/orders.js",
+            "import { query } from '../data/db';\nexport function load() { return query(); }\n",
+        );
+        write(
+            "data/db.js",
+            "import { render } from '../ui/view';\nexport function query() {}\n",
+        );
+
+        let index = index_directory(dir.path(), &["js"]).unwrap();
+        let paths = HashMap::from([("data".to_string(), vec!["data/**".to_string()])]);
+        let layers = Layers::parse("ui -> service -> data", &paths).unwrap();
+        let report = check(&index, dir.path(), &layers);
+
+        assert_eq!(report.layers.iter().map(|(_, n)| n).sum::<usize>(), 3);
+        assert_eq!(report.edges.len(), 3);
+        assert_eq!(report.violations.len(), 1);
+        let violation = &report.violations[0];
+        assert_eq!(
+            (violation.from_layer.as_str(), violation.to_layer.as_str()),
+            ("data", "ui")
+        );
+        assert!(violation.file.ends_with("data/db.js"));
+        assert_eq!(violation.line, Some(1));
+    }
+}
@@ -0,0 +1,128 @@
+//! CodeMapper: fast, in-memory code indexing built on tree-sitter.
+//!
+//! This crate powers the `cm` command line tool and can be embedded directly by
+//! editors, CI bots, and other Rust tools that need symbol-level views of a codebase
+//! without spawning the CLI.
+//!
+//! # Overview
+//!
+//! - [`indexer`] parses files and directories into a [`CodeIndex`]
+//! - [`CodeIndex`] answers symbol lookups (exact and fuzzy) and per-file queries
+//! - [`CacheManager`] persists an index on disk and detects changed files
+//! - [`GrepFilter`] is the fast-mode text prefilter plus AST validation used on large repos
+//! - [`parser`] holds the per-language [`Parser`] implementations
+//! - [`callgraph`], [`diff`], [`blame`], [`types`], [`implements`], [`schema`] build the
+//!   higher level analyses on top of the index
+//!
+//! # Example
+//!
+//! ```
+//! use codemapper::{indexer, CodeIndex, Language, SymbolType};
+//! use std::path::Path;
+//!
+//! let source = "pub fn add(a: i32, b: i32) -> i32 { a + b }\n";
+//! let file = indexer::index_file(Path::new("lib.rs"), source, Language::Rust, None)?;
+//!
+//! let mut index = CodeIndex::new();
+//! index.add_file(file);
+//!
+//! let found = index.query_symbol("add");
+//! assert_eq!(found.len(), 1);
+//! assert_eq!(found[0].symbol_type, SymbolType::Function);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! # WebAssembly
+//!
+//! With the `wasm` feature, [`wasm`] exposes `parseFile(content, lang)` to JavaScript so web
+//! tools can extract symbols and dependencies client-side.
+//!
+//! # Stability
+//!
+//! The crate is pre-1.0 and its API still changes with what the `cm` binary needs; pin an
+//! exact version when embedding it. The [`output`] module only exists to share formatting
+//! with the binary.
+
+pub mod annotations;
+pub mod api_lock;
+pub mod audit;
+pub mod bench;
+pub mod blame;
+pub mod breaking;
+pub mod cache;
+pub mod callgraph;
+pub mod cli_map;
+pub mod completion;
+pub mod confidence;
+pub mod config;
+pub mod deadline;
+pub mod dedupe;
+pub mod deprecated;
+pub mod dep_tree;
+pub mod diff;
+pub mod embed;
+pub mod emit;
+pub mod envvars;
+pub mod errors;
+pub mod extract;
+pub mod failure;
+pub mod fast_search;
+pub mod filter_expr;
+pub mod fingerprint;
+pub mod fixtures;
+pub mod flags;
+pub mod git;
+pub mod hierarchy;
+pub mod history;
+pub mod implements;
+pub mod index;
+pub mod indexer;
+pub mod interest;
+pub mod kinds;
+pub mod layers;
+pub mod logs;
+pub mod metrics;
+pub mod mocks;
+pub mod models;
+#[doc(hidden)]
+pub mod output;
+pub mod output_schema;
+#[cfg(feature = "cli")]
+pub mod package;
+pub mod parse_dump;
+pub mod parser;
+pub mod paths;
+#[doc(hidden)]
+#[cfg(feature = "cli")]
+pub mod picker;
+pub mod process;
+pub mod prompt;
+pub mod recency;
+#[cfg(feature = "cli")]
+pub mod redact;
+pub mod results;
+pub mod routes;
+pub mod scan;
+pub mod schema;
+pub mod semver;
+pub mod sets;
+pub mod shadows;
+pub mod snapshot;
+pub mod sql;
+pub mod stats;
+pub mod strings;
+pub mod summary;
+pub mod symbol_deps;
+pub mod test_select;
+#[cfg(test)]
+mod test_support;
+pub mod types;
+pub mod value_refs;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use cache::CacheManager;
+pub use fast_search::GrepFilter;
+pub use index::CodeIndex;
+pub use models::{Dependency, FileInfo, Language, Name, Symbol, SymbolType};
+pub use parser::{ParseResult, Parser};
@@ -0,0 +1,198 @@
+//! `cm logs`: every logging call with its level, message and enclosing symbol. Covers the
+//! `log`/`tracing` macros, Python `logging`, `console.*`, and `logger.*`/`log.*`/`slog.*`
+//! style loggers in every language.
+
+use anyhow::Result;
+use regex::Regex;
+use std::path::PathBuf;
+
+use crate::callgraph;
+use crate::index::CodeIndex;
+use crate::models::Language;
+use crate::routes::{args, first_string, group_text};
+use crate::scan::{self, regex};
+use crate::value_refs::enclosing_symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    /// `critical`, `fatal`, Go's `log.Fatal`/`log.Panic`
+    Fatal,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+            LogLevel::Fatal => "fatal",
+        }
+    }
+
+    /// Level of a logging method or macro name (`warning`, `Errorf`, `exception`, `Println`)
+    pub fn from_method(method: &str) -> Option<LogLevel> {
+        let method = method.to_lowercase();
+        let level = if method.starts_with("trace") {
+            LogLevel::Trace
+        } else if method.starts_with("debug") {
+            LogLevel::Debug
+        } else if method.starts_with("info") || method.starts_with("print") || method == "log" {
+            LogLevel::Info
+        } else if method.starts_with("warn") {
+            LogLevel::Warn
+        } else if method.starts_with("error") || method == "exception" {
+            LogLevel::Error
+        } else if ["fatal", "critical", "panic"]
+            .iter()
+            .any(|p| method.starts_with(p))
+        {
+            LogLevel::Fatal
+        } else {
+            return None;
+        };
+        Some(level)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogCall {
+    pub level: LogLevel,
+    /// The logger as written: `log::`, `logging`, `console`, `self.logger`, ...
+    pub logger: String,
+    /// First string literal of the call, usually the message or format string
+    pub message: Option<String>,
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// Innermost symbol containing the call
+    pub enclosing: Option<String>,
+}
+
+fn patterns(language: Language) -> Vec<Regex> {
+    // `logger.info(`, `self.log.warning(`, `console.error(`, `slog.Warn(`, `log.Printf(`
+    let method = regex(
+        r"\b((?:self\.|this\.)?_?(?:[Ll]ogger|[Ll]og|LOG|LOGGER|logging|console|slog))\.([a-zA-Z]+)\s*\(",
+    );
+    match language {
+        Language::Rust => vec![regex(
+            r"\b((?:log|tracing)::)?(trace|debug|info|warn|error)!\s*\(",
+        )],
+        Language::Python
+        | Language::JavaScript
+        | Language::TypeScript
+        | Language::Java
+        | Language::Go => vec![method],
+        _ => Vec::new(),
+    }
+}
+
+/// Every logging call in non-test files, in file and line order
+pub fn find_logs(index: &CodeIndex) -> Result<Vec<LogCall>> {
+    let mut calls = Vec::new();
+    for file in index.files() {
+        if callgraph::is_test_file(&file.path, file.language) {
+            continue;
+        }
+        let content = scan::non_test_source(&file.path, file.language);
+        for (level, logger, message, line) in logs_in(&content, file.language) {
+            calls.push(LogCall {
+                level,
+                logger,
+                message,
+                file_path: file.path.clone(),
+                line,
+                enclosing: enclosing_symbol(index, &file.path, line).map(|s| s.name.to_string()),
+            });
+        }
+    }
+    calls.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+    Ok(calls)
+}
+
+/// (level, logger, message, 1-based line) of every logging call in `content`
+pub fn logs_in(
+    content: &str,
+    language: Language,
+) -> Vec<(LogLevel, String, Option<String>, usize)> {
+    let patterns = patterns(language);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut found = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with('*') {
+            continue;
+        }
+        for pattern in &patterns {
+            for caps in pattern.captures_iter(line) {
+                let Some(level) = caps.get(2).and_then(|m| LogLevel::from_method(m.as_str()))
+                else {
+                    continue;
+                };
+                let logger = match caps.get(1) {
+                    Some(m) => m.as_str().to_string(),
+                    // A bare `info!` is the `log` crate (or `tracing`) imported by name
+                    None => "log::".to_string(),
+                };
+                let start = caps.get(0).map_or(0, |m| m.start());
+                let call = group_text(&lines, i, start, false);
+                found.push((level, logger, first_string(args(&call)), i + 1));
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logs_per_language() {
+        let python = "import logging\nlogger = logging.getLogger(__name__)\ndef pay():\n    logger.warning(\"retrying %s\", n)\n    self.log.exception('failed')\n    math.log(2)\n";
+        let found: Vec<(LogLevel, String, Option<String>)> = logs_in(python, Language::Python)
+            .into_iter()
+            .map(|(level, logger, message, _)| (level, logger, message))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    LogLevel::Warn,
+                    "logger".to_string(),
+                    Some("retrying %s".to_string())
+                ),
+                (
+                    LogLevel::Error,
+                    "self.log".to_string(),
+                    Some("failed".to_string())
+                ),
+            ]
+        );
+
+        let rust = "fn run() {\n    info!(user = %id, \"logged in\");\n    log::error!(\n        \"boom: {}\",\n        e\n    );\n    println!(\"not a log\");\n}\n";
+        let found: Vec<(LogLevel, Option<String>, usize)> = logs_in(rust, Language::Rust)
+            .into_iter()
+            .map(|(level, _, message, line)| (level, message, line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (LogLevel::Info, Some("logged in".to_string()), 2),
+                (LogLevel::Error, Some("boom: {}".to_string()), 3),
+            ]
+        );
+
+        let go = "log.Fatalf(\"listen: %v\", err)\nslog.Info(\"started\", \"port\", port)\nlog.SetFlags(0)\n";
+        let levels: Vec<LogLevel> = logs_in(go, Language::Go)
+            .into_iter()
+            .map(|(level, ..)| level)
+            .collect();
+        assert_eq!(levels, vec![LogLevel::Fatal, LogLevel::Info]);
+    }
+}
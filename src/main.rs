@@ -1,30 +1,56 @@
-mod blame;
-mod cache;
-mod callgraph;
-mod diff;
-mod fast_search;
-mod git;
+// Everything printed to stdout is also recorded for `cm last`, so `println!` and `print!`
+// tee into the history recorder for this file and the modules declared below
+macro_rules! println {
+    () => {{
+        std::println!();
+        codemapper::history::capture(b"\n");
+    }};
+    ($($arg:tt)*) => {{
+        let text = format!("{}\n", format_args!($($arg)*));
+        std::print!("{}", text);
+        codemapper::history::capture(text.as_bytes());
+    }};
+}
+
+macro_rules! print {
+    ($($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        std::print!("{}", text);
+        codemapper::history::capture(text.as_bytes());
+    }};
+}
+
 mod impact;
-mod implements;
-mod index;
-mod indexer;
-mod models;
-mod output;
-mod parser;
-mod schema;
-mod snapshot;
-mod types;
-
-use anyhow::Result;
-use cache::FileChangeKind;
-use clap::{Parser, Subcommand};
+
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser, Subcommand};
+use codemapper::cache::FileChangeKind;
+use codemapper::confidence::Confidence;
+use codemapper::failure::{ErrorCode, Failure};
+use codemapper::models::Symbol;
+use codemapper::output::{OutputFormat, OutputFormatter};
+use codemapper::paths::{self, DisplaySlash};
+use codemapper::{
+    annotations, api_lock, audit, bench, blame, breaking, cache, callgraph, cli_map, completion,
+    config, deadline, dedupe, dep_tree, deprecated, diff, embed, emit, envvars, errors, extract,
+    failure, fast_search, filter_expr, fingerprint, fixtures, flags, hierarchy, history,
+    implements, index, indexer, interest, kinds, layers, logs, metrics, mocks, models, output,
+    output_schema, package, parse_dump, parser, picker, prompt, recency, redact, results, routes,
+    schema, semver, sets, shadows, snapshot, sql, stats, strings, summary, symbol_deps,
+    test_select, types, value_refs,
+};
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
-use models::Symbol;
-use output::{OutputFormat, OutputFormatter};
 use rayon::prelude::*;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// File count at which query, callers, tests, and deps switch to grep-prefiltered fast mode
+const FAST_MODE_THRESHOLD: usize = 1000;
 
 #[derive(clap::Parser)]
 #[command(name = "cm")]
@@ -108,10 +134,22 @@ COMMANDS (organized by task)
   query        → Find symbols by name (main search tool)
   inspect      → List all symbols in one file
   deps         → Track imports and usage
+  prompt       → Orientation block for LLM agents (workflow 1 in one step)
+  routes       → HTTP routes: METHOD path → handler (API map)
+  cli-map      → CLI subcommands and flags → handler functions
+  envvars      → Environment variables and where they are read
+  sql          → SQL in string literals: type, tables, enclosing symbol
+  flags        → Feature flag keys and the symbols that check them
+  logs         → Logging calls by level and enclosing symbol
+  audit        → unsafe, eval, shell and SQL built from interpolated strings
+  shadows      → Names defined in several files: copies, drift, ambiguity
+  layers       → Declared architectural layers and imports crossing them upward
 
 [CALL GRAPH - Understand code flow]
   callers      → WHO calls this function? (reverse dependencies)
   callees      → What DOES this function call? (forward dependencies)
+  value-refs   → Where is this constant/enum variant used? (not strings)
+  errors       → How can this function fail? (own + direct callees)
   trace        → CALL PATH from A → B (shortest route)
   entrypoints  → Public APIs with no internal callers (dead code?)
   tests        → Which tests call this symbol?
@@ -119,13 +157,19 @@ COMMANDS (organized by task)
 
 [GIT HISTORY - Blame and timeline]
   diff         → Symbol-level changes vs a commit (what changed?)
+  stash-compare → Symbol-level diff vs a stash entry or another worktree
   since        → Breaking changes since commit (what broke?)
+  breaking     → CI gate: fail on breaking changes to exported symbols
+  semver       → Recommend major/minor/patch bump since a release tag
+  api-diff     → Public API vs a published crates.io/npm/PyPI release
+  api          → Freeze exported signatures to api.lock; fail when they drift
   blame        → Who last touched this symbol? (when, commit, author)
-  history      → Full evolution of a symbol (all commits touching it)
+  history      → Full evolution of a symbol (or a file's API with --file)
 
 [TYPE ANALYSIS - Understand data flow]
   types        → Parameter types and return type (where are they defined?)
   implements   → Find all implementations of an interface
+  hierarchy    → Full ancestor/descendant tree of a type
   schema       → Field structure (structs, classes, dataclasses)
 
 [SNAPSHOTS - Compare over time]
@@ -140,6 +184,8 @@ OUTPUT FORMATS:
   --format default  → Markdown (documentation, readable)
   --format human    → Tables (terminal viewing, pretty)
   --format ai       → Compact (LLM context, token-efficient) ← RECOMMENDED
+  --format compact  → file:line:col lines (editor problem matchers, vim quickfix)
+  --profile agent   → Defaults from [profile.agent] in .codemapper.toml
 
 PERFORMANCE:
   Small repos (< 100 files)    → < 20ms instant
@@ -183,7 +229,7 @@ GIT REQUIREMENTS:
 COMMON FLAGS
 
 --exact              → Strict matching (default is fuzzy)
---format <format>    → Output style: default (markdown), human (tables), ai (compact)
+--format <format>    → Output style: default (markdown), human (tables), ai (compact), json
 --show-body          → Include actual code (not just signatures)
 --exports-only       → Public symbols only (functions with export, pub, etc.)
 --full               → Include anonymous/lambda functions (normally hidden)
@@ -191,7 +237,18 @@ COMMON FLAGS
 --context full       → Include docstrings and metadata
 --no-cache           → Skip cache, always reindex (troubleshooting)
 --rebuild-cache      → Force cache rebuild
---extensions py,rs   → Comma-separated file types to include
+--extensions py,rs   → Comma-separated file types to include (default: auto, every
+                       supported language present)
+
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+EXIT CODES
+
+0  → Success
+1  → Error (bad arguments, unreadable files, git failures)
+2  → Invalid command line (unknown flag, missing argument)
+3  → Assertion failed (--expect-min, --fail-under, --fail-if-any, --max-calls, breaking
+     --check, layers --check, bench --fail-on-regression)
 
 ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
@@ -199,7 +256,7 @@ TROUBLESHOOTING
 
 NO SYMBOLS FOUND?
   ✓ Fuzzy matching by default (matches more)
-  ✓ Check --extensions py,js,ts (default: py,js,ts,jsx,tsx,rs,java,go,c,h,md)
+  ✓ Check --extensions py,js,ts (default: auto, the supported languages found)
   ✓ Verify file encoding is UTF-8
   ✓ Run: cm stats . (to see what's indexed)
 
@@ -254,21 +311,41 @@ EXAMPLES:
   
   # Health check
   cm untested .                        # What's not tested?
+  cm deprecated .                      # What's left to migrate?
   cm entrypoints .                     # Public API surface
 
 For detailed help on any command: cm <command> --help
 "
 )]
 struct Cli {
-    /// Output format: 'default' (markdown), 'human' (tables), 'ai' (token-efficient)
-    #[arg(short, long, global = true, default_value = "default")]
+    /// Output format: 'default' (markdown), 'human' (tables), 'ai' (token-efficient),
+    /// 'compact' (file:line:col lines for editor problem matchers / quickfix), 'json'
+    #[arg(short, long, global = true, default_value = "default", env = "CM_FORMAT")]
     format: String,
 
     /// Override cache directory location (default: .codemapper in project root)
-    /// Can also be set via CODEMAPPER_CACHE_DIR environment variable
+    /// Can also be set via CM_CACHE_DIR or CODEMAPPER_CACHE_DIR environment variables
     #[arg(long, global = true, env = "CODEMAPPER_CACHE_DIR")]
     cache_dir: Option<PathBuf>,
 
+    /// Apply a named set of defaults from config, e.g. [profile.agent] in .codemapper.toml
+    #[arg(long, global = true, env = "CM_PROFILE")]
+    profile: Option<String>,
+
+    /// Leave vendored code (vendor/, third_party/, node_modules/, ...) out of every command
+    #[arg(long, global = true, env = "CM_NO_VENDOR")]
+    no_vendor: bool,
+
+    /// Stop long operations (cold indexing, repo-wide scans) after this long and print the
+    /// partial results, marked as truncated: '5s', '500ms', '2m'
+    #[arg(long, global = true, env = "CM_TIMEOUT", value_parser = deadline::parse_duration)]
+    timeout: Option<Duration>,
+
+    /// Also use the parser plugins, embedding backend and summarizer declared in the
+    /// project's .codemapper.toml (those in the user config always apply)
+    #[arg(long, global = true, env = "CM_ALLOW_PLUGINS")]
+    allow_plugins: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -281,7 +358,10 @@ enum Commands {
         long_about = "USE CASE: Start here when exploring a new codebase
   • See how many files and what languages are present
   • Understand symbol distribution (functions vs classes vs methods)
-  • Verify that files are being indexed correctly
+  • Verify that files are being indexed correctly (skipped files are counted:
+    excluded or unsupported extension, too large, unreadable, ignored directory)
+  • Vendored trees (vendor/, third_party/, node_modules/, ...) are reported in their
+    own section instead of the totals; --no-vendor drops them from every command
   • Results are cached ONLY if indexing takes ≥ 300ms (automatic)
 
 SMART CACHE BEHAVIOR:
@@ -300,6 +380,10 @@ TIP: Fastest way to understand codebase size and composition"
   cm stats . --extensions py,rs      # Only Python and Rust files
   cm stats . --rebuild-cache         # Force fresh rebuild (may skip cache if fast)
   cm stats . --no-cache              # Skip cache, always reindex (benchmarking)
+  cm stats . --top                   # Plus the 10 largest files, longest functions, ...
+  cm stats . --top 25 --format ai    # Top 25 of each, token-efficient
+  cm stats . --skipped               # Which files were not indexed, and why
+  cm stats . --no-vendor --skipped   # Vendored files show up as skipped
 
 TYPICAL WORKFLOW:
   1. Run 'cm stats .' first to understand the codebase
@@ -313,16 +397,25 @@ TYPICAL WORKFLOW:
         path: PathBuf,
 
         /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache (invalidate and reindex)
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
+
+        /// Also list the N largest files, longest functions, classes with the most methods,
+        /// and files with the most symbols (default N: 10)
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+        top: Option<usize>,
+
+        /// Also list every file that was not indexed, and why
+        #[arg(long, default_value_t = false)]
+        skipped: bool,
     },
 
     /// [DISCOVERY] Hierarchical project structure - from overview to detailed symbol listings
@@ -338,6 +431,11 @@ WHEN TO USE WHICH LEVEL:
   Level 1 → Getting oriented in a new project
   Level 2 → Finding which files contain what you need
   Level 3 → Comprehensive reference (warning: verbose for large projects)
+  --group-by dir → Per-directory rollups between level 1 and 2 (monorepos)
+  --mode files-of-interest → Short ranked list of where to look first
+  --mode sizes → Directory/file/symbol line counts (treemap JSON, bar tree in human)
+  --since <ref|Nd> → Mark files and symbols changed since a ref or in the last N days
+  --metrics → Complexity, longest function and maintainability per file (levels 2-3)
 
 TIP: Use --format human for best terminal readability"
     )]
@@ -347,6 +445,19 @@ TIP: Use --format human for best terminal readability"
   cm map . --level 3                    # Full symbol signatures (verbose)
   cm map ./src --level 2 --format human # Pretty tables for src/ directory
   cm map . --level 2 --format ai        # Token-efficient for LLM context
+  cm summarize . && cm map . --level 2  # Include cached file summaries
+  cm map . --level 3 --redact           # Structure only, names pseudonymized for sharing
+  cm map . --level 3 --annotated entrypoint   # Only symbols tagged // @cm:entrypoint
+  cm map . --level 3 --where 'exported && file~\"src/api/**\"'   # Public API of src/api
+  cm map . --group-by dir --depth 2     # Symbol counts rolled up per directory
+  cm map . --mode files-of-interest     # The 20 files to read first, with why
+  cm map . --mode files-of-interest --limit 5 --format ai
+  cm map . --mode sizes --format human  # Where the lines are, as a bar tree
+  cm map . --mode sizes --format json > sizes.json  # Treemap data (d3, etc.)
+  cm map . --level 2 --since 14d        # Which files moved in the last two weeks
+  cm map . --level 3 --since v1.4 --format ai  # Symbols touched since a release
+  cm map . --level 2 --metrics --format human  # Health dashboard: complexity, maintainability
+  cm map . --level 2 --emit .codemapper/MAP.md --update  # Standing map, changed files on top
 
 TYPICAL WORKFLOW:
   1. Start with level 1 to see the big picture
@@ -362,16 +473,145 @@ TYPICAL WORKFLOW:
         level: u8,
 
         /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache (invalidate and reindex)
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
+
+        /// Pseudonymize file names, identifiers, string literals and docstrings, keeping
+        /// kinds, counts and line ranges, so the output can be shared externally
+        #[arg(long, default_value_t = false)]
+        redact: bool,
+
+        /// Salt for --redact; the same salt gives the same pseudonyms (default: random per run)
+        #[arg(long, env = "CM_REDACT_SALT", hide_env_values = true)]
+        redact_salt: Option<String>,
+
+        /// Roll files up into their directories, with symbol counts per kind (replaces --level)
+        #[arg(long, value_parser = ["dir"])]
+        group_by: Option<String>,
+
+        /// Directory levels below the mapped path to keep with --group-by dir
+        #[arg(long, default_value_t = 1, requires = "group_by")]
+        depth: usize,
+
+        /// files-of-interest: the files to read first, ranked by symbols, exports, incoming
+        /// calls and recent changes; sizes: lines and symbols per directory, file and symbol
+        /// (replaces --level)
+        #[arg(
+            long,
+            value_parser = ["files-of-interest", "sizes"],
+            conflicts_with_all = ["group_by", "redact"]
+        )]
+        mode: Option<String>,
+
+        /// Files to list with --mode files-of-interest; entries per directory with --mode sizes
+        /// (except in JSON, which holds the whole tree)
+        #[arg(long, default_value_t = 20, requires = "mode")]
+        limit: usize,
+
+        /// Map only symbols tagged with this `@cm:` comment, e.g. 'entrypoint' or 'owner=ops'
+        #[arg(
+            long,
+            value_name = "TAG[=VALUE]",
+            value_parser = annotations::AnnotationFilter::parse
+        )]
+        annotated: Option<annotations::AnnotationFilter>,
+
+        /// Only map symbols matching this filter expression, e.g. 'kind=function && exported && lines>40'
+        #[arg(
+            long = "where",
+            value_name = "EXPR",
+            value_parser = filter_expr::Filter::parse
+        )]
+        r#where: Option<filter_expr::Filter>,
+
+        /// Mark files and symbols changed since a git ref, or in the last N days with e.g. '14d'
+        /// (uncommitted edits count as changed)
+        #[arg(
+            long,
+            value_name = "REF|Nd",
+            value_parser = recency::Since::parse,
+            conflicts_with_all = ["group_by", "mode", "redact"]
+        )]
+        since: Option<recency::Since>,
+
+        /// Add cyclomatic complexity (sum and max), longest function and a 0-100
+        /// maintainability index to each file (--level 2 or 3)
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with_all = ["group_by", "mode", "redact"]
+        )]
+        metrics: bool,
+
+        /// Write the markdown map to this file instead of stdout, one anchored section per
+        /// file, e.g. '.codemapper/MAP.md'
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["group_by", "mode"])]
+        emit: Option<PathBuf>,
+
+        /// With --emit, compare with the map already in the file: unchanged sections stay as
+        /// they are and a summary of the changed files goes on top
+        #[arg(long, default_value_t = false, requires = "emit")]
+        update: bool,
+    },
+
+    /// [DISCOVERY] Orientation preamble for LLM agents - paste it before the task
+    #[command(
+        about = "Generate a ready-to-paste orientation block for an LLM agent, within a token budget",
+        long_about = "USE CASE: Give an agent its bearings before it touches the code
+  • Automates the EXPLORING UNKNOWN CODE workflow (cm --help) in one command
+  • Project size: files per language, symbols per kind
+  • Entry points: main-style functions and API handlers nothing else calls
+  • Most-called symbols: ranked by call sites (shared names split the count)
+  • Central files: where the most-called symbols live
+  • Next steps: concrete cm commands to run from here
+
+TOKEN BUDGET:
+  • Estimated at ~4 characters per token
+  • Over budget? The longest list is trimmed first, one entry at a time
+  • Overview and next steps are always kept
+
+Test files are left out of the rankings.
+
+TIP: Use --format ai for the most compact block, default markdown for readability"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm prompt                             # Orientation for the current directory
+  cm prompt ./backend --budget 800      # Tighter block for a small context window
+  cm prompt . --format ai               # Token-efficient key:value lines
+  cm prompt . > ORIENTATION.md          # Save it for the agent's system prompt
+
+TYPICAL WORKFLOW:
+  1. cm prompt . | pbcopy (or save to a file)
+  2. Paste it at the top of the agent's task
+  3. The agent follows the suggested next steps (inspect, query, callers, trace)")]
+    Prompt {
+        /// Directory path to describe
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Approximate maximum size of the block, in tokens
+        #[arg(long, default_value_t = 2000)]
+        budget: usize,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
     },
 
     /// [SEARCH] Find symbols by name - the main workhorse for code exploration
@@ -387,12 +627,18 @@ SEARCH MODES:
   Exact   → cm query MyClass              (case-sensitive, precise)
   Fuzzy   → cm query myclass              (DEFAULT: case-insensitive, flexible)
 Exact   → cm query myclass --exact      (strict matching)
+  Pattern → cm query 'parse_*'            (* and ? wildcards, whole name; --exact for case)
 
 CONTEXT OPTIONS:
   --context minimal → Signatures only (default, fast)
   --context full    → Includes docstrings and metadata
   --show-body       → Show actual code implementation
 
+DUPLICATES:
+  A symbol that also appears in a .d.ts declaration, in JS compiled beside its TS
+  source, or as an identical copy elsewhere is listed once with \"Also at\" locations
+  (--no-dedupe lists each one)
+
 PERFORMANCE (Fast Mode):
   • 18,457 files: 76s → 1.2s (63x faster)
   • 17,005 files: 122s → 9.6s (12x faster)
@@ -405,6 +651,20 @@ TIP: Start with fuzzy search, it's more forgiving"
   # Basic searches
   cm query authenticate                      # Exact match (case-sensitive)
   cm query auth                              # Fuzzy search (default)
+  cm query 'parse_*'                         # Names starting with parse_
+  cm query '*Handler' --exact                # Names ending in Handler (case-sensitive)
+
+  # Scoped to part of the tree (same cache as the whole repo)
+  cm query handle --in src/server/           # Only symbols under src/server
+  cm query Config --in src/config.rs         # Only symbols in one file
+
+  # By @cm: annotation comments (# @cm:deprecated, // @cm:owner team-payments)
+  cm query '' --annotated deprecated         # Every symbol tagged @cm:deprecated
+  cm query charge --annotated owner=team-payments
+
+  # Filter expressions over kind, name, file, lang, lines, line, exported, documented
+  cm query '' --where 'kind=function && exported && lines>40 && file~\"src/**\"'
+  cm query parse --where '(kind=method || kind=function) && !documented'
 
   # With context
   cm query process_payment --context full    # Include docstrings
@@ -417,6 +677,22 @@ TIP: Start with fuzzy search, it's more forgiving"
   # Output formats
   cm query Parser --format human             # Pretty tables
   cm query CodeIndex --format ai             # Token-efficient for LLMs
+  cm query auth --format compact             # file:line:col lines for editors
+
+  # Interactive
+  cm query auth --pick --show-body           # Fuzzy-pick one result, print its body
+
+  # Duplicates (.d.ts declarations, compiled JS, copies) are shown once by default
+  cm query Config --no-dedupe                # List every location separately
+
+  # CI gate (exit code 3 when fewer results are found)
+  cm query PaymentGateway --exact --expect-min 1
+
+  # Pin the results for later steps
+  cm query auth --save-as auth-hits          # Then: cm show auth-hits
+
+  # Semantic search (run 'cm embed' first)
+  cm query --semantic 'retry logic with backoff'
 
 TYPICAL WORKFLOW:
   1. Quick fuzzy search: cm query auth --fuzzy
@@ -430,18 +706,22 @@ WHEN TO USE:
   ✓ \"What methods does the User class have?\"
   ✓ \"Show me the validate_input implementation\"")]
     Query {
-        /// Symbol name to search for (function, class, or method name)
+        /// Symbol name to search for (function, class, or method name); `*` and `?` are wildcards
         symbol: String,
 
         /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
+        /// Only report symbols from this file or directory, reusing the index of PATH
+        #[arg(long = "in", value_name = "FILE_OR_DIR", conflicts_with = "semantic")]
+        within: Option<PathBuf>,
+
         /// Use exact matching instead of fuzzy matching (default is fuzzy)
         #[arg(long, default_value = "false")]
         exact: bool,
 
-        /// Filter by symbol type: 'function', 'class', 'method', 'enum', 'static', 'heading', 'code_block'
+        /// Filter by symbol type: 'function', 'class', 'method', 'enum', 'static', 'field', 'heading', 'code_block', or a custom kind from config
         #[arg(long)]
         r#type: Option<String>,
 
@@ -450,7 +730,7 @@ WHEN TO USE:
         context: String,
 
         /// Show the actual code implementation in results
-        #[arg(long, default_value = "false")]
+        #[arg(long, default_value = "false", env = "CM_SHOW_BODY")]
         show_body: bool,
 
         /// Enable fast mode explicitly (auto-enabled for 1000+ files)
@@ -458,11 +738,11 @@ WHEN TO USE:
         fast: bool,
 
         /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache (invalidate and reindex)
@@ -474,22 +754,62 @@ WHEN TO USE:
         full: bool,
 
         /// Show only exported/public symbols (functions/classes with export keyword, pub visibility, etc.)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_EXPORTS_ONLY")]
         exports_only: bool,
 
+        /// Show only symbols tagged with this `@cm:` comment, e.g. 'deprecated' or 'owner=ops'
+        #[arg(
+            long,
+            value_name = "TAG[=VALUE]",
+            value_parser = annotations::AnnotationFilter::parse
+        )]
+        annotated: Option<annotations::AnnotationFilter>,
+
+        /// Only show symbols matching this filter expression, e.g. 'kind=function && exported && lines>40'
+        #[arg(
+            long = "where",
+            value_name = "EXPR",
+            value_parser = filter_expr::Filter::parse
+        )]
+        r#where: Option<filter_expr::Filter>,
+
         /// Maximum number of results to return (prevents overwhelming output)
         #[arg(long)]
         limit: Option<usize>,
+
+        /// Treat the query as natural language and search embeddings from 'cm embed'
+        #[arg(long, default_value_t = false)]
+        semantic: bool,
+
+        /// Choose one result in an inline fuzzy picker and print only that one
+        #[arg(long, default_value_t = false, conflicts_with = "semantic")]
+        pick: bool,
+
+        /// Exit with code 3 when fewer than N symbols are found (for CI)
+        #[arg(long, value_name = "N", conflicts_with = "semantic")]
+        expect_min: Option<usize>,
+
+        /// Keep duplicates: `.d.ts` declarations, JS compiled beside its TS source and
+        /// identical copies are otherwise shown once, with their other locations
+        #[arg(long, default_value_t = false, conflicts_with = "semantic")]
+        no_dedupe: bool,
+
+        /// Pin the results as a named set for 'cm show NAME' and 'cm callers --from-set NAME'
+        #[arg(long, value_name = "NAME", conflicts_with = "semantic")]
+        save_as: Option<String>,
     },
 
-    /// [SEARCH] Explore a single file in detail - see all symbols with their signatures
+    /// [SEARCH] Explore files in detail - see all symbols with their signatures
     #[command(
-        about = "Analyze one file and list all functions, classes, and methods it contains",
-        long_about = "USE CASE: Deep dive into a specific file
-  • See all symbols defined in a single file
+        about = "Analyze files or directories and list the functions, classes, and methods they contain",
+        long_about = "USE CASE: Deep dive into specific files
+  • See all symbols defined in a file, several files, or a directory
   • Understand file organization and structure
   • Review function signatures and documentation
 
+Only the named files are parsed, never the tree around them; several files or a
+directory are grouped per file.
+
 WHEN TO USE:
   → You know the file but want to see what's inside
   → Reviewing a file before making changes
@@ -504,6 +824,12 @@ TIP: Combine with --show-body to see implementations"
   cm inspect ./auth.py --show-body         # Show implementations
   cm inspect ./parser.rs --format human    # Pretty table format
   cm inspect ./utils.js --format ai        # Token-efficient output
+  git show HEAD~1:src/app.py | cm inspect - --lang python   # Parse stdin
+  cm inspect src/parsers/                  # Every file in a directory, per file
+  cm inspect a.py b.py --format ai         # Several files in one run
+  cm inspect ./src/config.py --lines 100-250   # Only symbols overlapping lines 100-250
+  cm inspect ./src/config.py --symbol Config   # One definition and everything nested in it
+  cm inspect src/parsers/ --redact         # Structure only, names pseudonymized for sharing
 
 TYPICAL WORKFLOW:
   1. Use 'cm map --level 2' to find interesting files
@@ -515,11 +841,23 @@ WHEN TO USE:
   ✓ \"Show me everything in this module\"
   ✓ \"What's the structure of parser.rs?\"")]
     Inspect {
-        /// Path to the file to analyze
-        file_path: PathBuf,
+        /// Files or directories to analyze, or '-' to read source from stdin (requires --lang)
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Language of the source (name or extension, e.g. 'python' or 'py'); overrides detection
+        #[arg(long, value_name = "LANG")]
+        lang: Option<String>,
 
         /// Show the actual code implementation for each symbol
-        #[arg(long, default_value = "false")]
+        #[arg(
+            long,
+            default_value_t = false,
+            num_args = 0..=1,
+            default_missing_value = "true",
+            action = clap::ArgAction::Set,
+            env = "CM_SHOW_BODY"
+        )]
         show_body: bool,
 
         /// Show anonymous/lambda functions (default: filtered out)
@@ -527,8 +865,211 @@ WHEN TO USE:
         full: bool,
 
         /// Show only exported/public symbols (functions/classes with export keyword, pub visibility, etc.)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_EXPORTS_ONLY")]
         exports_only: bool,
+
+        /// Only show symbols overlapping this line range, e.g. 100-250 (single file only)
+        #[arg(long, value_parser = index::parse_line_range, value_name = "START-END")]
+        lines: Option<(usize, usize)>,
+
+        /// Only show symbols with this name, each with the symbols nested inside it
+        #[arg(long)]
+        symbol: Option<String>,
+
+        /// Comma-separated file extensions to include when inspecting directories
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Pseudonymize file names, identifiers, string literals and docstrings, keeping
+        /// kinds, counts and line ranges, so the output can be shared externally
+        #[arg(long, default_value_t = false)]
+        redact: bool,
+
+        /// Salt for --redact; the same salt gives the same pseudonyms (default: random per run)
+        #[arg(long, env = "CM_REDACT_SALT", hide_env_values = true)]
+        redact_salt: Option<String>,
+    },
+
+    /// [SEARCH] Print a result set pinned with 'cm query --save-as'
+    #[command(
+        about = "Print a named result set saved with 'cm query --save-as', or list saved sets",
+        long_about = "USE CASE: Keep working on the same symbols across steps
+  • 'cm query auth --save-as auth-hits' pins what the query found
+  • 'cm show auth-hits' prints exactly those symbols again, without searching
+  • 'cm callers --from-set auth-hits' finds the callers of every symbol in it
+
+Sets are stored in .codemapper/sets/ under the working directory (or
+--cache-dir). They keep the symbols as found; when a file has changed since
+the set was saved, a warning says so, as lines may have moved.
+
+Without a NAME, lists the saved sets."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm show                          # Saved sets, their size and query
+  cm show auth-hits                # The pinned symbols
+  cm show auth-hits --show-body    # With their implementations
+  cm show auth-hits --delete       # Remove the set")]
+    Show {
+        /// Name given with --save-as
+        name: Option<String>,
+
+        /// Context level: 'minimal' (signatures only) or 'full' (includes docstrings)
+        #[arg(long, default_value = "minimal")]
+        context: String,
+
+        /// Show the actual code implementation in results
+        #[arg(long, default_value = "false", env = "CM_SHOW_BODY")]
+        show_body: bool,
+
+        /// Delete the set instead of printing it
+        #[arg(long, default_value_t = false, requires = "name")]
+        delete: bool,
+    },
+
+    /// [SEARCH] Export a symbol's source as a standalone snippet
+    #[command(
+        about = "Print a symbol's body as a standalone snippet, optionally with the imports it needs",
+        long_about = "USE CASE: Paste one function into an issue, a test or an LLM prompt
+  • Prints the symbol's source lines, with the indentation of methods removed
+  • --with-imports puts the import statements of its file that bind a name the body
+    uses on top, one statement per name, and notes the symbols of the same file it
+    calls or names but does not include
+
+Imports are found for Python, JavaScript/TypeScript, Rust, Go and Java. Every
+definition of the name is printed; pass a file path to pick one.
+
+TIP: --format compact prints just the code, ready for a clipboard"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm extract parse_config                       # The body
+  cm extract parse_config --with-imports        # Plus the imports it needs
+  cm extract checkout ./src/cart.py --with-imports --format compact | pbcopy")]
+    Extract {
+        /// Symbol name
+        symbol: String,
+
+        /// Directory to search, or the file that defines the symbol
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Prepend the import statements the body needs
+        #[arg(long, default_value_t = false)]
+        with_imports: bool,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache (invalidate and reindex)
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [SEARCH] Build symbol embeddings for semantic search
+    #[command(
+        about = "Chunk symbols for embedding and store vectors for 'cm query --semantic'",
+        long_about = "USE CASE: Find code by meaning instead of by name
+  • Splits the codebase into symbol-aligned chunks (name + signature + docstring + body)
+  • Sends chunks to a local embedding model and stores the vectors in .codemapper/
+  • Only chunks whose text changed are re-embedded on later runs
+  • Or writes the chunks as JSON lines (--output) for an external embedding pipeline
+
+CONFIGURATION (.codemapper.toml or ~/.config/codemapper/config.toml):
+  [embeddings]
+  endpoint = \"http://localhost:11434/api/embed\"   # Ollama, or an OpenAI-compatible /v1/embeddings
+  model = \"nomic-embed-text\"
+  # command = \"my-embedder\"   # alternative: JSON array of strings on stdin → JSON array of vectors on stdout"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm embed                                  # Embed the current project
+  cm embed ./src --max-chars 1000           # Smaller chunks
+  cm embed --output chunks.jsonl            # Export chunks, no model needed
+  cm query --semantic 'retry logic with backoff'
+
+TYPICAL WORKFLOW:
+  1. Configure [embeddings] once
+  2. cm embed (re-run after large changes; unchanged chunks are reused)
+  3. cm query --semantic '<what the code does>'")]
+    Embed {
+        /// Directory path to embed
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Write chunks as JSON lines to this file ('-' for stdout) instead of embedding them
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Maximum characters per chunk; longer symbols are split
+        #[arg(long, default_value_t = embed::DEFAULT_MAX_CHUNK_CHARS)]
+        max_chars: usize,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache (invalidate and reindex)
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [SEARCH] Generate cached prose summaries of files and symbols
+    #[command(
+        about = "Summarize files (and optionally symbols) with an external command, cached for map/inspect",
+        long_about = "USE CASE: Give agents prose context without re-deriving it every session
+  • Runs a configured command (e.g. an LLM CLI) once per file, or per symbol with --symbols
+  • Summaries are cached in .codemapper/summaries.json, keyed by content hash
+  • Only new or changed files/symbols are summarized on later runs
+  • 'cm map --level 2' shows file summaries; 'cm inspect' shows file and symbol summaries
+
+CONFIGURATION (.codemapper.toml or ~/.config/codemapper/config.toml):
+  [summarizer]
+  command = \"llm\"
+  args = [\"-s\", \"Summarize this code in one sentence\"]
+
+The command receives the source on stdin and CM_SUMMARY_KIND (file|symbol),
+CM_SUMMARY_PATH, and CM_SUMMARY_NAME in its environment; stdout is the summary."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm summarize                          # Summarize every file
+  cm summarize ./src --symbols          # Files plus each function/class/method
+  cm summarize --force                  # Regenerate even unchanged summaries
+
+TYPICAL WORKFLOW:
+  1. Configure [summarizer] once
+  2. cm summarize (re-run after changes; only stale entries are regenerated)
+  3. cm map . --level 2 --format ai / cm inspect ./file.py")]
+    Summarize {
+        /// Directory path to summarize
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Also summarize individual functions, classes, and methods
+        #[arg(long, default_value_t = false)]
+        symbols: bool,
+
+        /// Regenerate summaries even if the cached ones are up to date
+        #[arg(long, default_value_t = false)]
+        force: bool,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache (invalidate and reindex)
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
     },
 
     /// [ANALYSIS] Track dependencies - see what imports what, or find all usages
@@ -536,7 +1077,7 @@ WHEN TO USE:
         about = "Analyze import relationships and symbol usage across the codebase",
         long_about = "USE CASE: Understand code relationships and dependencies
   • For files: See imports and reverse dependencies
-  • For symbols: Find all places where they're used
+  • For symbols: Find all places where they're used, or what their body depends on
 
 TWO MODES:
   imports  → What does this file/symbol import?
@@ -544,9 +1085,11 @@ TWO MODES:
 
 FILE ANALYSIS:
   cm deps ./auth.py                       → Shows auth.py's imports
+  cm deps ./auth.py --depth 3             → Imports of imports, as a tree
   cm deps ./auth.py --direction used-by   → Shows files importing auth.py
 
 SYMBOL ANALYSIS:
+  cm deps authenticate                    → Calls, types and imports authenticate() uses
   cm deps authenticate --direction used-by → Find all authenticate() calls
   cm deps User --direction used-by        → Find where User class is used
 
@@ -559,17 +1102,27 @@ TIP: Great for impact analysis before refactoring"
     #[command(after_help = "EXAMPLES:
   # File dependencies
   cm deps ./src/auth.py                           # What does auth.py import?
+  cm deps ./src/auth.py --depth 0                 # Full import tree, cycles marked
+  cm deps ./src/app.ts --depth 2 --format json    # Nested dependencies arrays
   cm deps ./utils.js --direction used-by          # What imports utils.js?
 
-  # Symbol usage (requires --direction used-by)
+  # Symbol dependencies: calls, types and imports used in the body
+  cm deps authenticate                            # What does authenticate() depend on?
+  cm deps checkout --format ai                    # call:/type:/import: lines
+
+  # Symbol usage
   cm deps authenticate --direction used-by        # Find all authenticate() calls
   cm deps User --direction used-by                # Where is User class used?
   cm deps process_payment --direction used-by     # Track payment processing usage
+  cm deps User --direction used-by --fast         # Grep-prefilter (auto at 1000+ files)
 
   # Output formats
   cm deps CodeIndex --direction used-by --format human  # Pretty tables
   cm deps ./main.rs --format ai                         # Token-efficient
 
+  # CI gate (exit code 3 when anything is found)
+  cm deps legacy_auth --direction used-by --fail-if-any # Fail while callers remain
+
 TYPICAL WORKFLOW:
   1. Find symbol: cm query MyClass --fuzzy
   2. Check usage: cm deps MyClass --direction used-by
@@ -580,7 +1133,8 @@ WHEN TO USE:
   ✓ \"What files import this module?\"
   ✓ \"Where is this function called?\"
   ✓ \"Safe to refactor this class?\" (check used-by first)
-  ✓ \"What does this file depend on?\"")]
+  ✓ \"What does this file depend on?\"
+  ✓ \"What does this function need?\" (symbol imports)")]
     Deps {
         /// File path (./src/auth.py) or symbol name (authenticate)
         target: String,
@@ -593,260 +1147,274 @@ WHEN TO USE:
         #[arg(long, default_value = "imports")]
         direction: String,
 
+        /// Levels of a file's imports to expand into a tree (0 = all)
+        #[arg(long, default_value_t = 1)]
+        depth: usize,
+
+        /// Grep-prefilter files before parsing (auto-enabled for 1000+ files)
+        #[arg(long, default_value = "false")]
+        fast: bool,
+
         /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache (invalidate and reindex)
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
+
+        /// Exit with code 3 when any dependency or usage is found (for CI)
+        #[arg(long, default_value_t = false)]
+        fail_if_any: bool,
     },
 
-    /// [UTILITY] Validate indexing - mostly for testing and debugging
+    /// [DISCOVERY] List the HTTP routes a service declares
     #[command(
-        about = "Test that files can be indexed correctly (reports file count and timing)",
-        long_about = "USE CASE: Verify CodeMapper can parse your files
-  • Validate indexing works on your codebase
-  • Check parsing performance
-  • Debug file detection issues
-
-NOTE: This command doesn't cache results yet (future feature)
-
-RARELY NEEDED: Most users should use 'stats', 'map', or 'query' instead
-
-TIP: Use this to verify file extensions are being detected"
+        about = "List HTTP routes: method, path, handler and location",
+        long_about = "USE CASE: Get an instant API map of a web service
+  • One line per route: METHOD path -> handler symbol (file:line)
+  • Prefixes from routers, blueprints, controllers and groups are applied
+  • Routes handled by inline closures show no handler
+
+FRAMEWORKS:
+  Python   → FastAPI/Flask decorators (@app.get, @router.post, @bp.route)
+  TS/JS    → Express-style app.get('/path', handler), NestJS @Get()
+  Rust     → axum .route(\"/path\", get(handler)), actix/rocket #[get(\"/path\")]
+  Java     → Spring @GetMapping, @RequestMapping
+  Go       → net/http HandleFunc, gorilla/mux, gin, echo, chi
+
+TIP: Pipe --format ai output to an agent as the service's API surface"
     )]
     #[command(after_help = "EXAMPLES:
-  cm index                          # Index current directory
-  cm index /path/to/project         # Index specific project
-  cm index . --extensions py,rs     # Only Python and Rust files
-
-WHEN TO USE:
-  ✓ Testing CodeMapper on a new language/extension
-  ✓ Debugging why files aren't being found
-  ✓ Benchmarking parse performance
+  cm routes                                # Every route in the project
+  cm routes ./services/api                 # One service
+  cm routes --method POST                  # Only POST routes
+  cm routes --prefix /api/users            # Routes under a path
+  cm routes --format ai                    # Token-efficient output
 
-NOTE: For normal usage, use 'cm stats' or 'cm map' instead")]
-    Index {
-        /// Directory path to index
+TYPICAL WORKFLOW:
+  1. Map the API: cm routes
+  2. Read a handler: cm query <handler> --show-body
+  3. Follow it down: cm callees <handler>")]
+    Routes {
+        /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
-        extensions: String,
-    },
+        /// Only show routes for this HTTP method (routes accepting any method always match)
+        #[arg(long)]
+        method: Option<String>,
 
-    /// [ANALYSIS] Symbol-level diff between current code and a git commit
-    #[command(
-        about = "Show symbol-level changes between current code and a git commit",
-        long_about = "USE CASE: Understand what symbols changed between commits
-  • See which functions/classes were added, deleted, or modified
-  • Detect signature changes (parameter/return type modifications)
-  • Review changes at symbol granularity instead of line-by-line
+        /// Only show routes whose path starts with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
 
-CHANGE TYPES:
-  ADDED            → New symbols that didn't exist in the commit
-  DELETED          → Symbols that were removed since the commit
-  MODIFIED         → Symbols with body/line changes (same signature)
-  SIGNATURE_CHANGED → Symbols with parameter or return type changes
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
 
-REQUIREMENTS:
-  • Must be run inside a git repository
-  • Commit reference can be: HEAD~1, abc123, branch-name, tag-name
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
 
-TIP: Great for code review and understanding PR impact"
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [DISCOVERY] List the command-line interface a project defines
+    #[command(
+        about = "List CLI subcommands and flags with their handler functions",
+        long_about = "USE CASE: Understand (or document) a command-line tool
+  • One entry per command: full invocation, description, flags and positionals
+  • Maps each subcommand to the function that runs it
+  • Nested subcommands are listed with their full path (`tool cache clear`)
+
+FRAMEWORKS:
+  Rust     → clap derive (Parser/Subcommand/Args) and builder (Command::new)
+  Python   → argparse (add_parser/add_argument/set_defaults), click, typer
+  Go       → cobra (cobra.Command, AddCommand, Flags())
+
+TIP: Run it on cm itself to see how the commands map to cmd_* functions"
     )]
     #[command(after_help = "EXAMPLES:
-  cm diff HEAD~1                        # Compare to previous commit
-  cm diff HEAD~3 ./src                  # Compare src/ to 3 commits ago
-  cm diff main                          # Compare to main branch
-  cm diff abc1234 --format human        # Pretty table output
-  cm diff HEAD~5 --extensions py,rs     # Only Python and Rust files
+  cm cli-map                               # Every command in the project
+  cm cli-map ./tools/deploy                # One tool
+  cm cli-map --format ai                   # Token-efficient output
+  cm cli-map --format human                # Pretty table
 
 TYPICAL WORKFLOW:
-  1. Before PR review: cm diff main --format human
-  2. Check recent changes: cm diff HEAD~1
-  3. Impact analysis: cm diff release-v1.0 ./src
-
-WHEN TO USE:
-  ✓ \"What functions changed in this PR?\"
-  ✓ \"Did any signatures change since last release?\"
-  ✓ \"What was added/removed in the last 5 commits?\"")]
-    Diff {
-        /// Git commit reference (e.g., HEAD~1, abc123, main, v1.0)
-        commit: String,
-
-        /// Directory or file path to analyze (optional, defaults to entire repo)
+  1. List the commands: cm cli-map
+  2. Read a handler: cm query <handler> --show-body
+  3. Follow it down: cm callees <handler>")]
+    CliMap {
+        /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
-        /// Show anonymous/lambda functions (default: filtered out)
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
         #[arg(long, default_value_t = false)]
-        full: bool,
+        rebuild_cache: bool,
     },
 
-    /// [ANALYSIS] Find all call sites of a function (reverse call graph)
+    /// [DISCOVERY] List the environment variables a project reads
     #[command(
-        about = "Find all places where a function/method is called",
-        long_about = "USE CASE: Understand who calls a function
-  • Find all call sites of a specific function
-  • Useful for impact analysis before refactoring
-  • Shows the enclosing function/method making each call
-  • Uses AST-based call detection (not text search)
-  • Tip: use qualified names (e.g. `Foo::new`) to reduce noise for common method names
-
-SUPPORTED LANGUAGES:
-  Python, JavaScript, TypeScript, Rust, Go, Java, C
-
-TIP: Great for understanding function usage patterns"
+        about = "List environment variables with their usage sites",
+        long_about = "USE CASE: Derive deployment requirements from the code
+  • One entry per variable, with every file:line that reads or sets it
+  • Each usage shows the function or type it occurs in
+  • Test files and Rust #[cfg(test)] modules are skipped
+
+DETECTS:
+  Python   → os.environ[...], os.environ.get(...), os.getenv(...)
+  JS/TS    → process.env.X, process.env[\"X\"], const { X } = process.env, Deno.env.get
+  Rust     → std::env::var(...), env!(...), clap's env = \"X\"
+  Go       → os.Getenv, os.LookupEnv
+  Java/C   → System.getenv(...), getenv(...)
+
+NOTE: Only literal names are reported; env::var(key) with a computed key is not"
     )]
     #[command(after_help = "EXAMPLES:
-  cm callers parse_file                    # Find all callers of parse_file
-  cm callers parse ./src --fuzzy           # Fuzzy match 'parse' in src/
-  cm callers Foo::new                      # Disambiguate common method names
-  cm callers validate --format human       # Pretty table output
-  cm callers process_data . --format ai    # Token-efficient output
+  cm envvars                               # Every variable in the project
+  cm envvars ./services/api                # One service
+  cm envvars --format ai                   # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Find the function: cm query my_func --fuzzy
-  2. See who calls it: cm callers my_func
-  3. Understand the call chain before refactoring")]
-    Callers {
-        /// Symbol name to find callers for
-        symbol: String,
-
+  1. List the variables: cm envvars
+  2. Check one in context: cm query <enclosing> --show-body")]
+    Envvars {
         /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Enable fuzzy matching for symbol lookup
-        #[arg(long, default_value = "false")]
-        fuzzy: bool,
-
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
-
-        /// Maximum number of results to return (prevents overwhelming output)
-        #[arg(long)]
-        limit: Option<usize>,
     },
 
-    /// [ANALYSIS] Find all functions called by a symbol (forward call graph)
+    /// [DISCOVERY] Find SQL statements embedded in string literals
     #[command(
-        about = "Find all functions/methods that a symbol calls",
-        long_about = "USE CASE: Understand what a function depends on
-  • See all functions called within a symbol's body
-  • Useful for understanding code dependencies
-  • Links to definitions when found in codebase
-  • Marks external/built-in functions separately
-
-SUPPORTED LANGUAGES:
-  Python, JavaScript, TypeScript, Rust, Go, Java, C
-
-TIP: Great for understanding function complexity and dependencies"
+        about = "Find SQL queries in string literals with their tables",
+        long_about = "USE CASE: Connect code to the database schema
+  • Which functions read or write a table?
+  • What statements does the data layer run?
+
+HOW IT WORKS:
+  String literals starting with SELECT/INSERT/UPDATE/DELETE/WITH/CREATE/ALTER/DROP/...
+  are parsed with sqlparser (generic, PostgreSQL, MySQL and SQLite dialects). Only the
+  ones that parse are reported, with the statement type, referenced tables and the
+  enclosing symbol.
+
+  • Adjacent and `+`-concatenated literals are joined into one statement
+  • f-string/template interpolations, %s and {} holes are treated as placeholders
+  • Statements assembled at runtime (query builders, loops) are not found"
     )]
     #[command(after_help = "EXAMPLES:
-  cm callees main                          # What does main() call?
-  cm callees process_data --fuzzy          # Fuzzy match
-  cm callees cmd_query --format human      # Pretty table output
-  cm callees validate . --format ai        # Token-efficient output
+  cm sql                                   # Every embedded statement
+  cm sql --table users                     # Statements touching the users table
+  cm sql --kind delete                     # Only DELETE statements
+  cm sql --format ai                       # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Find the function: cm query my_func --fuzzy
-  2. See what it calls: cm callees my_func
-  3. Understand the dependency graph")]
-    Callees {
-        /// Symbol name to find callees for
-        symbol: String,
-
+  1. Find who writes a table: cm sql --table orders --kind insert
+  2. Read the function: cm query <enclosing> --show-body
+  3. See who calls it: cm callers <enclosing>")]
+    Sql {
         /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Enable fuzzy matching for symbol lookup
-        #[arg(long, default_value = "false")]
-        fuzzy: bool,
+        /// Only statements referencing this table (case-insensitive, schema optional)
+        #[arg(long)]
+        table: Option<String>,
+
+        /// Only this statement type: select, insert, update, delete, create, ...
+        #[arg(long)]
+        kind: Option<String>,
 
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
-
-        /// Maximum number of results to return (prevents overwhelming output)
-        #[arg(long)]
-        limit: Option<usize>,
     },
 
-    /// [ANALYSIS] Find tests that call a symbol
+    /// [DISCOVERY] Search string literals and report the symbol each one is in
     #[command(
-        about = "Find test functions that call a given symbol",
-        long_about = "USE CASE: Find tests covering a specific function or method
-  • Identifies test files by naming convention (_test.rs, test_*.py, *.test.js)
-  • Detects test functions by attributes (#[test], @Test) or naming (test_*, Test*)
-  • Shows where in the test the symbol is called
-
-TEST DETECTION:
-  Rust     → #[test] attribute, _test.rs files, tests/ directory
-  Python   → test_*.py files, functions starting with test_
-  Go       → *_test.go files, functions starting with Test
-  JS/TS    → *.test.js, *.spec.ts, __tests__/ directory
-  Java     → @Test annotation, *Test.java files
-
-TIP: Use before refactoring to understand test coverage"
+        about = "Search string literals (messages, error strings) and show the enclosing symbol",
+        long_about = "USE CASE: Go from an error message in a bug report to the code behind it
+  • Paste the message as reported: values filled into {}, %s or ${...} still match
+  • Each hit shows the literal, its enclosing symbol and file:line
+  • Best matches first: the more of the message a literal accounts for, the higher
+
+MATCHING:
+  contains  → the literal contains the pattern (case-insensitive)
+  template  → the pattern is the literal with its placeholders filled in
+  regex     → with --regex, the pattern as a regular expression
+
+INDEX:
+  String literals are indexed separately, in .codemapper/strings.bin, and only once
+  cm strings is first run. Later runs re-read just the files that changed.
+  Rust, Python, JavaScript, TypeScript, Go, Java and C are covered."
     )]
     #[command(after_help = "EXAMPLES:
-  cm tests parse_file                     # Find tests calling parse_file
-  cm tests authenticate --fuzzy           # Fuzzy match test discovery
-  cm tests validate ./src --format human  # Pretty table output
-  cm tests process_payment --format ai    # Token-efficient for LLMs
+  cm strings \"permission denied\"                          # Literals containing the text
+  cm strings \"failed to open config /etc/app.toml: EACCES\" # A message with values filled in
+  cm strings '^E[0-9]{4}' --regex                           # Error codes
+  cm strings timeout ./src --limit 20 --format ai
 
 TYPICAL WORKFLOW:
-  1. Identify function to refactor: cm query my_function
-  2. Find tests: cm tests my_function
-  3. Run tests, make changes, verify")]
-    Tests {
-        /// Symbol name to find tests for
-        symbol: String,
+  1. Find the origin: cm strings \"<message from the bug report>\"
+  2. Read the function: cm query <enclosing> --show-body
+  3. Walk back to the entry point: cm callers <enclosing>")]
+    Strings {
+        /// Text to look for, or a full message as printed
+        pattern: String,
 
         /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Enable fuzzy matching for flexible search
-        #[arg(long, default_value = "false")]
-        fuzzy: bool,
+        /// Treat the pattern as a regular expression
+        #[arg(long, default_value_t = false)]
+        regex: bool,
+
+        /// Maximum number of results to return
+        #[arg(long)]
+        limit: Option<usize>,
 
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
@@ -854,45 +1422,50 @@ TYPICAL WORKFLOW:
         rebuild_cache: bool,
     },
 
-    /// [ANALYSIS] Find symbols with no test coverage
+    /// [DISCOVERY] List feature flag keys and the symbols that check them
     #[command(
-        about = "Find functions and methods that are not called by any test",
-        long_about = "USE CASE: Identify code without test coverage
-  • Finds symbols not called from any test file or test function
-  • Excludes test functions themselves from the output
-  • Excludes private/internal helpers (leading underscore in Python)
-  • Shows coverage percentage and untested symbol count
-
-TEST DETECTION:
-  Rust     → #[test] attribute, _test.rs files, tests/ directory
-  Python   → test_*.py files, functions starting with test_
-  Go       → *_test.go files, functions starting with Test
-  JS/TS    → *.test.js, *.spec.ts, __tests__/ directory
-  Java     → @Test annotation, *Test.java files
-
-TIP: Use to identify areas needing test coverage"
+        about = "List feature flags and every symbol that checks them",
+        long_about = "USE CASE: Retire stale feature flags
+  • One entry per flag key, with every check site and its enclosing symbol
+  • Flags checked in a single place are usually the easiest to remove
+
+DETECTION:
+  A check is a call matching a pattern whose first argument is a string literal
+  (or a keyword argument like flag=\"x\"); that literal is the flag key.
+
+  Default patterns cover isEnabled/is_enabled, isFeatureEnabled, LaunchDarkly
+  variation(...), PostHog getFeatureFlag/useFeatureFlag, Unleash useFlag,
+  OpenFeature getBooleanValue(...) and feature(...).
+
+  Patterns come from --pattern, else [flags] patterns in .codemapper.toml:
+    [flags]
+    patterns = ['flags\\.on\\(', 'Toggles\\.active\\(']"
     )]
     #[command(after_help = "EXAMPLES:
-  cm untested                           # Find untested symbols in current dir
-  cm untested ./src                     # Check specific directory
-  cm untested . --format human          # Pretty table output
-  cm untested . --format ai             # Token-efficient for LLMs
+  cm flags                                     # Default patterns
+  cm flags --pattern 'isEnabled\\(' --pattern 'feature\\('
+  cm flags --pattern 'cfg!?\\(\\s*feature'     # Cargo features in #[cfg(...)]
+  cm flags --format ai                         # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Check coverage: cm untested .
-  2. Pick symbol to test: identify critical untested code
-  3. Write tests for high-priority symbols")]
-    Untested {
-        /// Directory path to analyze
+  1. List flags: cm flags
+  2. Read a check site: cm query <symbol> --show-body
+  3. Remove the flag, then: cm since main --breaking")]
+    Flags {
+        /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
+        /// Regex matching a flag check up to its call (repeatable; replaces the defaults)
+        #[arg(long = "pattern")]
+        patterns: Vec<String>,
+
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
@@ -900,111 +1473,100 @@ TYPICAL WORKFLOW:
         rebuild_cache: bool,
     },
 
-    /// [ANALYSIS] List breaking changes since a known-good commit
+    /// [DISCOVERY] Inventory logging calls by level and enclosing symbol
     #[command(
-        about = "Show breaking API changes since a git commit (removed symbols, signature changes)",
-        long_about = "USE CASE: Identify breaking changes that could affect callers
-  • Removed symbols (DELETED) - definitely breaks callers
-  • Signature changes (params/return type modified) - breaks callers
-  • Filters out non-breaking changes (internal body modifications)
-
-BREAKING CHANGES:
-  DELETED          → Symbol was removed (callers will fail)
-  SIGNATURE_CHANGED → Function signature modified (callers may need updates)
-
-NON-BREAKING (filtered out with --breaking):
-  ADDED            → New symbols (safe)
-  MODIFIED         → Body changes only (internal, safe for callers)
-
-REQUIREMENTS:
-  • Must be run inside a git repository
-  • Commit reference can be: HEAD~1, abc123, branch-name, tag-name
-
-TIP: Use before releases to identify API-breaking changes"
+        about = "List logging calls grouped by level and enclosing symbol",
+        long_about = "USE CASE: Audit observability coverage and search log messages
+  • Which functions log errors, and which never log at all?
+  • Where does a message seen in production come from?
+
+DETECTS:
+  Rust     → log/tracing macros: info!, warn!, log::error!, tracing::debug!
+  Python   → logging.*, logger.*, self.log.* (debug/info/warning/error/exception/critical)
+  JS/TS    → console.*, logger.*, this.logger.*
+  Java     → log.*, logger.*, LOG.* (trace/debug/info/warn/error)
+  Go       → log.Print*/Fatal*/Panic*, slog.*, logger.Infof/Errorw (zap, logrus)
+
+Test files and Rust #[cfg(test)] modules are skipped."
     )]
     #[command(after_help = "EXAMPLES:
-  cm since HEAD~10 --breaking              # Breaking changes in last 10 commits
-  cm since main --breaking                 # Breaking changes since main branch
-  cm since v1.0 --breaking --format human  # Breaking changes since v1.0 tag
-  cm since HEAD~5 src/ --breaking          # Breaking changes in src/ directory
-  cm since abc1234                         # All changes (not just breaking)
+  cm logs                                  # Every logging call
+  cm logs --level warn                     # Warnings and worse
+  cm logs --grep 'connection refused'      # Where a message is logged
+  cm logs --format ai                      # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Before release: cm since last-release --breaking
-  2. Check PR impact: cm since main --breaking --format human
-  3. Full changelog: cm since v1.0 (without --breaking)
-
-WHEN TO USE:
-  ✓ \"What breaks if I upgrade from v1.0?\"
-  ✓ \"Did this PR introduce breaking changes?\"
-  ✓ \"Is it safe to merge this into main?\"")]
-    Since {
-        /// Git commit reference (e.g., HEAD~1, abc123, main, v1.0)
-        commit: String,
-
-        /// Directory or file path to analyze (optional, defaults to entire repo)
+  1. Error paths without logging: cm errors <function>, then cm logs --level error
+  2. Find a production message: cm logs --grep '<text>'
+  3. Read the function: cm query <enclosing> --show-body")]
+    Logs {
+        /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        /// Minimum level: trace, debug, info, warn, error, fatal
+        #[arg(long)]
+        level: Option<String>,
+
+        /// Only calls whose message contains this text (case-insensitive)
+        #[arg(long)]
+        grep: Option<String>,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
-        /// Show only breaking changes (deleted symbols, signature changes)
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
         #[arg(long, default_value_t = false)]
-        breaking: bool,
+        rebuild_cache: bool,
     },
 
-    /// [ANALYSIS] Find exported/public symbols with no internal callers (API surface)
+    /// [DISCOVERY] Flag unsafe and injection-prone constructs
     #[command(
-        about = "Find entrypoints: exported symbols that are not called internally",
-        long_about = "USE CASE: Identify API surface and potentially unused code
-  • Finds public/exported functions that have no callers within the codebase
-  • Useful for identifying: main entry points, exported APIs, dead code
-  • Groups by: Main Entrypoints, API Functions, Possibly Unused
-
-EXPORT DETECTION BY LANGUAGE:
-  Rust     → pub fn, pub struct (excluding pub(crate), pub(super))
-  Python   → No leading underscore, or in __all__
-  JS/TS    → export function, export default, module.exports
-  Go       → Capitalized function names
-  Java     → public modifier
-  C        → No leading underscore
-
-CATEGORIES:
-  Main Entrypoint → main, run, start, init, execute, cli, app
-  API Function    → get*, post*, handle*, create*, process*, classes, enums
-  Possibly Unused → Other exported symbols with no callers
-
-TIP: Use to identify dead code or document your public API"
+        about = "Flag unsafe code, eval, shell and SQL built from interpolated strings",
+        long_about = "USE CASE: Lightweight security sweep before a review or release
+  • Which functions use unsafe, and are they the ones we expect?
+  • Is user input spliced into a shell command or a SQL string anywhere?
+
+RULES:
+  unsafe      → Rust unsafe blocks, fns, impls and traits
+  eval        → eval/exec (Python), eval/new Function (JS/TS)
+  shell       → os.system, subprocess(..., shell=True), child_process.exec,
+                sh -c via Command/exec.Command, Runtime.exec with interpolated args
+  sql-concat  → SQL literals joined with +, f-strings, %, .format, format!, Sprintf
+
+Findings are line-based heuristics mapped to their enclosing symbol. Test files and
+Rust #[cfg(test)] modules are skipped."
     )]
     #[command(after_help = "EXAMPLES:
-  cm entrypoints                         # Find entrypoints in current dir
-  cm entrypoints ./src                   # Check specific directory
-  cm entrypoints . --format human        # Pretty table output
-  cm entrypoints . --format ai           # Token-efficient for LLMs
+  cm audit                                 # All rules
+  cm audit --rule shell --rule sql-concat  # Injection candidates only
+  cm audit src/ffi --rule unsafe           # Where unsafe lives
+  cm audit --format ai                     # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Check entrypoints: cm entrypoints .
-  2. Review 'Possibly Unused' - candidates for removal
-  3. Document 'API Functions' as your public interface
-
-WHEN TO USE:
-  ✓ \"What's our public API surface?\"
-  ✓ \"Is this function used anywhere?\"
-  ✓ \"Find dead code candidates\"
-  ✓ \"What are the entry points to this codebase?\"")]
-    Entrypoints {
-        /// Directory path to analyze
+  1. Sweep: cm audit
+  2. Who reaches a finding: cm callers <enclosing>
+  3. Does input flow there: cm trace <entrypoint> <enclosing>")]
+    Audit {
+        /// Directory path to audit
         #[arg(default_value = ".")]
         path: PathBuf,
 
+        /// Only report these rules (repeatable)
+        #[arg(long = "rule", value_enum)]
+        rules: Vec<audit::Rule>,
+
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
@@ -1012,62 +1574,45 @@ WHEN TO USE:
         rebuild_cache: bool,
     },
 
-    /// [ANALYSIS] Trace the call path between two symbols
+    /// [DISCOVERY] Names defined in more than one file
     #[command(
-        about = "Show the shortest call path from symbol A to symbol B",
-        long_about = "USE CASE: Understand how code flows between two symbols
-  • Find the call chain: A calls X, X calls Y, Y calls B
-  • Uses BFS to find the shortest path
-  • Supports fuzzy matching for flexible symbol lookup
-
-ALGORITHM:
-  • Breadth-first search from source symbol
-  • Follows call edges (function/method calls)
-  • Maximum depth: 10 levels to avoid infinite loops
-  • Returns shortest path found
-
-OUTPUT:
-  • Shows each step in the call chain
-  • Includes symbol type and file location for each step
-
-TIP: Use --fuzzy if you're not sure of exact symbol names"
+        about = "List symbol names defined as the same kind in multiple files",
+        long_about = "USE CASE: Find copy-paste drift and ambiguous names
+  • Which helpers were copied between modules, and have the copies diverged?
+  • Why does cm callers/query return several definitions for one name?
+
+Functions, classes, enums, interfaces, type aliases and statics defined under the same
+name in two or more non-test files are grouped and classified:
+  identical  → Bodies match (ignoring whitespace): a copy to consolidate
+  drifted    → Same signature, bodies differ: copies that diverged
+  different  → Unrelated definitions that share a name
+
+Methods and fields are left out; the same method name across types is normal."
     )]
     #[command(after_help = "EXAMPLES:
-  cm trace main parse_file                   # Trace from main to parse_file
-  cm trace authenticate validate --fuzzy    # Fuzzy match both symbols
-  cm trace handler response ./src           # Trace within specific path
-  cm trace cmd_query format_output --format human  # Pretty table
+  cm shadows                         # All collisions, copies first
+  cm shadows --exported-only         # Only public names that collide
+  cm shadows src/ --format ai        # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Find symbols: cm query func_a --fuzzy
-  2. Trace path: cm trace func_a func_b
-  3. Review the call chain to understand code flow
-
-WHEN TO USE:
-  ✓ \"How does data flow from A to B?\"
-  ✓ \"What's the call path from main to this function?\"
-  ✓ \"Understanding control flow in unfamiliar code\"")]
-    Trace {
-        /// Source symbol name (start of the path)
-        from: String,
-
-        /// Target symbol name (end of the path)
-        to: String,
-
-        /// Directory path to search in
+  1. Find copies: cm shadows
+  2. Compare them: cm query <name> --exact --show-body
+  3. Check who uses each: cm callers <name>")]
+    Shadows {
+        /// Directory path to analyze
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Enable fuzzy matching for symbol names
-        #[arg(long, default_value = "false")]
-        fuzzy: bool,
+        /// Only names whose definitions are exported (pub, export, top-level)
+        #[arg(long)]
+        exported_only: bool,
 
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
@@ -1075,1330 +1620,6177 @@ WHEN TO USE:
         rebuild_cache: bool,
     },
 
-    /// [ANALYSIS] Quick breakage report for a symbol (definition + callers + tests)
+    /// [UTILITY] Validate indexing - mostly for testing and debugging
     #[command(
-        about = "Quick breakage report for a symbol (definition + callers + tests)",
-        long_about = "USE CASE: Tight edit loop safety check
-  • After you edit a function, run this to see what you likely broke
-  • Shows definition + signature, all callers, and tests that touch it
-  • Intended to be fast enough to run repeatedly during refactors
+        about = "Test that files can be indexed correctly (reports file count and timing)",
+        long_about = "USE CASE: Verify CodeMapper can parse your files
+  • Validate indexing works on your codebase
+  • Check parsing performance
+  • Debug file detection issues
+
+NOTE: This command doesn't cache results yet (future feature)
 
-TIP: Run this after changing a function signature"
+RARELY NEEDED: Most users should use 'stats', 'map', or 'query' instead
+
+TIP: Use this to verify file extensions are being detected"
     )]
     #[command(after_help = "EXAMPLES:
-  cm impact symbols_by_type               # quick: counts + top callsites/tests
-  cm impact parse_file ./src --exact      # restrict scope + exact match
-  cm impact auth . --format ai            # token-efficient output
-  cm impact output --include-docs         # allow matching headings/code blocks
-  cm impact big_function --all            # print full lists (no truncation)")]
-    Impact {
-        /// Symbol name to analyze
-        symbol: String,
+  cm index                          # Index current directory
+  cm index /path/to/project         # Index specific project
+  cm index . --extensions py,rs     # Only Python and Rust files
 
-        /// Directory path to search in
+WHEN TO USE:
+  ✓ Testing CodeMapper on a new language/extension
+  ✓ Debugging why files aren't being found
+  ✓ Benchmarking parse performance
+
+NOTE: For normal usage, use 'cm stats' or 'cm map' instead")]
+    Index {
+        /// Directory path to index
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Use exact matching (default is fuzzy)
-        #[arg(long, default_value = "false")]
-        exact: bool,
-
-        /// Include markdown headings/code blocks as candidates (default: code symbols only)
-        #[arg(long, default_value_t = false)]
-        include_docs: bool,
-
-        /// Maximum number of callers/tests to show (default: 10 each)
-        #[arg(long)]
-        limit: Option<usize>,
-
-        /// Show full callers/tests lists (ignores --limit)
-        #[arg(long, default_value_t = false)]
-        all: bool,
-
-        /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
-
-        /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
-        no_cache: bool,
-
-        /// Force rebuild cache
-        #[arg(long, default_value_t = false)]
-        rebuild_cache: bool,
     },
 
-    /// [ANALYSIS] Show what production symbols a test file calls
+    /// [UTILITY] Benchmark indexing stages and compare against a baseline
     #[command(
-        about = "List production (non-test) symbols called by a test file",
-        long_about = "USE CASE: Understand test scope and coverage
-  • Parse a test file to find all function/method calls
-  • Filter to only show production code (not test helpers)
-  • Useful for understanding what a test actually tests
-
-FILTERS OUT:
-  • Calls to other test functions/files
-  • Calls to symbols in the same test file
-  • External/built-in functions not in codebase
-
-TIP: Great for reviewing test scope before refactoring"
+        about = "Time walk, parse, cache save/load, and lookups; compare against a saved baseline",
+        long_about = "USE CASE: Keep performance honest across releases and refactors
+  • Times each stage on your codebase: walk, parse, cache_save, cache_load, query_exact, query_fuzzy
+  • Reports the median of several iterations as JSON on stdout
+  • Compares against a previous report and can fail CI on slowdowns
+
+The cache is written to a temporary directory, so .codemapper/ is not touched.
+Lookup metrics are the total for 50 names sampled from the index."
     )]
     #[command(after_help = "EXAMPLES:
-  cm test-deps ./tests/test_auth.py              # Show production deps
-  cm test-deps ./src/parser_test.rs --format ai  # Token-efficient output
-  cm test-deps ./auth.test.ts --format human     # Pretty table
+  cm bench                                          # Benchmark the current directory
+  cm bench /large/repo --iterations 5               # More iterations, steadier medians
+  cm bench --output bench.json                      # Save a baseline
+  cm bench --baseline bench.json --fail-on-regression 10%   # Exit 3 if any stage is >10% slower
 
 TYPICAL WORKFLOW:
-  1. Find test file: cm map . --level 2 (look for test files)
-  2. Check test scope: cm test-deps ./path/to/test_file.py
-  3. Ensure test covers intended functionality")]
-    TestDeps {
-        /// Path to the test file to analyze
-        test_file: PathBuf,
-
-        /// Directory path to search for production symbols
+  1. On main: cm bench --output bench.json
+  2. On a branch: cm bench --baseline bench.json --fail-on-regression 10%")]
+    Bench {
+        /// Directory path to benchmark
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
-        extensions: String,
+        /// How many times to run each stage (the median is reported)
+        #[arg(long, default_value_t = 3)]
+        iterations: usize,
 
-        /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
-        no_cache: bool,
+        /// Also write the JSON report to this file
+        #[arg(long)]
+        output: Option<PathBuf>,
 
-        /// Force rebuild cache
-        #[arg(long, default_value_t = false)]
-        rebuild_cache: bool,
+        /// Previous report to compare against
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+
+        /// With --baseline, exit with status 3 if any stage is slower by more than this
+        /// (e.g. '10%')
+        #[arg(long, value_parser = bench::parse_percent, requires = "baseline")]
+        fail_on_regression: Option<f64>,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
     },
 
-    /// [GIT] Find when a symbol was last modified
+    /// [UTILITY] Shell completion scripts
     #[command(
-        about = "Show who last modified a symbol and when",
-        long_about = "USE CASE: Git blame for symbols, not lines
-  • Find the commit that last modified a specific function/class/method
-  • See author, date, and commit message
-  • Compare old vs new signature if it changed
-
-REQUIREMENTS:
-  • Must be run inside a git repository
-  • File must have git history
+        about = "Print a completion script for bash, zsh, or fish",
+        long_about = "USE CASE: Tab-complete commands, flags, symbol names, and file paths
+  • Subcommands and their flags complete everywhere
+  • Symbol arguments (query, callers, callees, tests, trace, ...) complete from the cache
+  • File arguments (inspect, test-deps, blame) complete from the indexed file list
+
+Symbol and file completion reads the cache of the current directory, so run any
+cached command (e.g. 'cm stats') once first. Without a cache the shell falls back
+to completing file names."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm completions bash > ~/.local/share/bash-completion/completions/cm
+  cm completions zsh > ~/.zfunc/_cm               # with fpath+=(~/.zfunc) in .zshrc
+  cm completions fish > ~/.config/fish/completions/cm.fish
+
+THEN:
+  cm callers parse_<TAB>     → parse_config  parse_args  parse_file
+  cm inspect src/pa<TAB>     → src/parser/  src/parsers.rs")]
+    Completions {
+        /// Shell to generate the script for
+        #[arg(value_enum)]
+        shell: completion::Shell,
+    },
 
-TIP: Use with 'cm history' to see full evolution of a symbol"
+    /// [UTILITY] JSON Schemas of the JSON documents cm writes
+    #[command(
+        name = "output-schema",
+        about = "Print the JSON Schema of cm's JSON outputs (--format json payloads, errors, saved files)",
+        long_about = "USE CASE: Generate typed bindings or validate cm output in downstream tooling
+  • bench              → cm bench report (stdout, --output, --baseline files)
+  • embed-chunk        → each line of cm embed --output
+  • snapshot           → .codemapper/snapshots/<name>.json written by cm snapshot
+  • error              → any command's --format json output when it fails
+  • deps, used-by      → cm deps <symbol>, cm deps --direction used-by
+  • dep-tree           → cm deps <file>
+  • size-tree          → cm map --mode sizes
+  • parse-dump         → cm parse-dump
+  • symbol-diff        → cm diff --symbol
+  • kinds              → cm kinds
+  • command-history    → cm last --list
+  • sets               → cm show without a set name
+  • fingerprint        → the file written by cm verify --record
+  • fingerprint-drift  → cm verify
+  • snippets           → cm extract
+
+Commands not listed print markdown under --format json.
+
+Schemas follow JSON Schema draft 2020-12 and are checked against the serialized
+structs in the test suite, so they stay in step with what cm actually writes.
+Without a name, every schema is printed under $defs."
     )]
     #[command(after_help = "EXAMPLES:
-  cm blame parse_file ./src/parser.rs          # Blame for parse_file function
-  cm blame MyClass ./src/models.py --format ai # Token-efficient output
-  cm blame validate ./utils.go --format human  # Pretty table
+  cm output-schema                       # All schemas in one document
+  cm output-schema bench > bench.schema.json
+  cm output-schema --list                # Names and the commands that produce them")]
+    OutputSchema {
+        /// Schema to print (see --list); all when omitted
+        name: Option<String>,
 
-TYPICAL WORKFLOW:
-  1. Find symbol: cm query my_func --fuzzy
-  2. See last change: cm blame my_func ./path/to/file.rs
-  3. See full history: cm history my_func ./path/to/file.rs")]
-    Blame {
-        /// Symbol name to blame
-        symbol: String,
+        /// List the available schemas instead of printing them
+        #[arg(long, default_value_t = false, conflicts_with = "name")]
+        list: bool,
+    },
 
-        /// Path to the file containing the symbol
+    /// [UTILITY] Canonical dump of what the parser extracts from one file
+    #[command(
+        name = "parse-dump",
+        about = "Dump a file's parsed symbols and dependencies as canonical JSON for golden tests",
+        long_about = "USE CASE: Golden-file tests of cm's parsers, in your own project or for a new language
+  • Symbols in source order: name, kind, lines, exported, signature, docstring
+  • Dependencies sorted by import name
+  • No index ids, byte offsets or file paths, so the dump is the same on every machine
+
+The output is always pretty-printed JSON (--format is ignored) and is byte-for-byte
+stable for the same parser output: commit it next to the fixture and diff it in CI.
+The top-level \"version\" changes whenever the dump's shape does."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm parse-dump test_files/sample.py > test_files/golden/sample.py.json
+  cm parse-dump src/lib.rs | diff golden/lib.rs.json -   # Check against a golden file
+  git show HEAD:app.py | cm parse-dump - --lang python  # Parse stdin")]
+    ParseDump {
+        /// File to parse, or '-' to read source from stdin (requires --lang)
         file: PathBuf,
+
+        /// Language of the source (name or extension, e.g. 'python' or 'py'); overrides detection
+        #[arg(long, value_name = "LANG")]
+        lang: Option<String>,
     },
 
-    /// [GIT] Show all commits that touched a symbol
+    /// [UTILITY] Symbol kinds, their ai-format codes and the languages that produce them
     #[command(
-        about = "Show the evolution of a symbol across git history",
-        long_about = "USE CASE: Track how a symbol evolved over time
-  • See all commits where a symbol was added, modified, or deleted
-  • Track signature changes across versions
-  • Understand when and why a function changed
+        about = "List symbol kinds with their --format ai codes and the languages that produce them",
+        long_about = "USE CASE: Decode --format ai output, or pick a --type filter
+  • Every built-in kind with the one- or two-letter code ai output uses for it
+  • Custom kinds declared in config ([[kinds]]) or by parser plugins
+  • Which languages' parsers produce each kind
+
+--format json spells every kind out in full, the same names --type takes and
+JSON outputs (snapshots, embed chunks) use."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm kinds                      # All kinds and their codes
+  cm kinds --lang typescript    # Only what the TypeScript parser produces
+  cm kinds --format json        # For tools decoding ai output")]
+    Kinds {
+        /// Only kinds this language produces (name or extension, e.g. 'python' or 'py')
+        #[arg(long, value_name = "LANG")]
+        lang: Option<String>,
+    },
 
-REQUIREMENTS:
-  • Must be run inside a git repository
-  • File must have git history
+    /// [UTILITY] Print a previous command's output again, or list recent commands
+    #[command(
+        about = "Print the output of a previous command again without rerunning it",
+        long_about = "USE CASE: Get back a result that scrolled away or was cut off
+  • Replays what an earlier cm command printed to stdout, exactly as printed
+  • No re-indexing or re-parsing: the output comes from the history
+  • --list shows recent commands with their exit codes and output sizes
 
-CHANGE TRACKING:
-  • Detects signature changes (parameter/return type modifications)
-  • Detects body size changes (function grew or shrunk)
-  • Shows when symbol was created or deleted
+Every command records its arguments, exit code, timing and stdout in
+.codemapper/history/ under the working directory (or --cache-dir), keeping
+the last 50. Set CM_NO_HISTORY=1 to stop recording.
 
-TIP: Combine with 'cm blame' for quick last-change info"
+Replays keep the format the command ran with. --format json instead wraps the
+output in an object with the command's metadata; output that was itself JSON
+is embedded as JSON.
+
+`cm history` is a different command: the git history of a symbol."
     )]
     #[command(after_help = "EXAMPLES:
-  cm history parse_file ./src/parser.rs           # Full history
-  cm history authenticate ./auth.py --format ai   # Token-efficient
-  cm history MyClass ./models.go --format human   # Pretty table
+  cm last                       # Output of the latest command
+  cm last 2                     # The command before that
+  cm last --id 12               # Command #12 from the list
+  cm last --format json         # Output plus command, exit code and timing
+  cm last --list                # Recent commands, newest first")]
+    Last {
+        /// Which command: 1 is the latest, 2 the one before it, and so on
+        #[arg(default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+        back: u64,
+
+        /// Replay the command with this id (shown by --list) instead
+        #[arg(long, conflicts_with = "back")]
+        id: Option<u64>,
+
+        /// List recent commands instead of replaying one
+        #[arg(long, conflicts_with = "id")]
+        list: bool,
 
-TYPICAL WORKFLOW:
-  1. Find symbol: cm query my_func --fuzzy
-  2. See history: cm history my_func ./path/to/file.rs
-  3. Compare specific versions using git diff")]
-    History {
-        /// Symbol name to track
-        symbol: String,
+        /// With --list, how many commands to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
 
-        /// Path to the file containing the symbol
-        file: PathBuf,
+    /// Completion candidates for the generated scripts, one per line
+    #[command(hide = true)]
+    Complete {
+        /// What to complete: symbols, files, or targets (symbols and files)
+        #[arg(value_parser = ["symbols", "files", "targets"])]
+        kind: String,
+
+        /// Text typed so far
+        #[arg(default_value = "")]
+        prefix: String,
+
+        /// Project root whose cache is consulted
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
     },
 
-    /// [ANALYSIS] Find all implementations of an interface/trait/protocol
+    /// [ANALYSIS] Symbol-level diff between current code and a git commit
     #[command(
-        about = "Find all classes/structs that implement a given interface or trait",
-        long_about = "USE CASE: Discover all implementors of an interface
-  • Rust: Find `impl Trait for Type` patterns
-  • Python: Find class inheritance `class Foo(Interface):`
-  • TypeScript/JavaScript: Find `class Foo implements Interface`
-  • Java: Find `implements` and `extends` clauses
-  • Go: Find structs that embed interfaces
+        about = "Show symbol-level changes between current code and a git commit",
+        long_about = "USE CASE: Understand what symbols changed between commits
+  • See which functions/classes were added, deleted, or modified
+  • Detect signature changes (parameter/return type modifications)
+  • Review changes at symbol granularity instead of line-by-line
 
-LANGUAGE PATTERNS:
-  Rust     → impl Trait for Type, impl Type
-  Python   → class Name(Interface):
-  TS/JS    → class Name implements Interface, extends Parent
-  Java     → class Name implements Interface, extends Parent
-  Go       → type Name struct { Interface }
+CHANGE TYPES:
+  ADDED            → New symbols that didn't exist in the commit
+  DELETED          → Symbols that were removed since the commit
+  MODIFIED         → Symbols with body/line changes (same signature)
+  SIGNATURE_CHANGED → Symbols with parameter or return type changes
+  MOVED            → Same body, same name, now in another file
+  RENAMED          → Same body under a new name (possibly in another file)
 
-TIP: Use --fuzzy to find partial matches"
+REQUIREMENTS:
+  • Must be run inside a git repository
+  • Commit reference can be: HEAD~1, abc123, branch-name, tag-name
+
+TIP: Great for code review and understanding PR impact"
     )]
     #[command(after_help = "EXAMPLES:
-  cm implements Iterator                    # Find all Iterator implementations
-  cm implements Repository --fuzzy          # Fuzzy match implementations
-  cm implements Handler ./src --format ai   # Search in src/, AI format
-  cm implements Serializable --format human # Pretty table output
+  cm diff HEAD~1                        # Compare to previous commit
+  cm diff HEAD~3 ./src                  # Compare src/ to 3 commits ago
+  cm diff main                          # Compare to main branch
+  cm diff abc1234 --format human        # Pretty table output
+  cm diff HEAD~5 --extensions py,rs     # Only Python and Rust files
+  cm diff main --symbol parse_config    # Line diff of one function only
+  cm diff v1.0 --symbol Auth::login --to v1.1 -U 1
 
 TYPICAL WORKFLOW:
-  1. cm implements MyInterface --fuzzy
-  2. Review which types implement the interface
-  3. cm inspect <file> to see the implementation details
+  1. Before PR review: cm diff main --format human
+  2. Check recent changes: cm diff HEAD~1
+  3. Impact analysis: cm diff release-v1.0 ./src
 
 WHEN TO USE:
-  ✓ \"What types implement this trait?\"
-  ✓ \"Find all subclasses of this base class\"
-  ✓ \"Which structs satisfy this interface?\"")]
-    Implements {
-        /// Interface, trait, or protocol name to search for
-        interface: String,
+  ✓ \"What functions changed in this PR?\"
+  ✓ \"Did any signatures change since last release?\"
+  ✓ \"What was added/removed in the last 5 commits?\"
+  ✓ \"How exactly did this function change?\" (--symbol)")]
+    Diff {
+        /// Git commit reference (e.g., HEAD~1, abc123, main, v1.0)
+        commit: String,
 
-        /// Directory path to search in
+        /// Directory or file path to analyze (optional, defaults to entire repo)
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Enable fuzzy matching for interface name
-        #[arg(long, default_value = "false")]
-        fuzzy: bool,
-
-        /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
-        /// Disable cache (always reindex)
+        /// Show anonymous/lambda functions (default: filtered out)
         #[arg(long, default_value_t = false)]
-        no_cache: bool,
+        full: bool,
 
-        /// Force rebuild cache
-        #[arg(long, default_value_t = false)]
-        rebuild_cache: bool,
+        /// Show the unified diff of this symbol's lines only (`name`, or `Type::name`)
+        #[arg(long, value_name = "NAME")]
+        symbol: Option<String>,
 
-        /// Only show trait implementations (filter out inherent impls)
-        #[arg(long, default_value_t = false)]
-        trait_only: bool,
+        /// With --symbol, the newer side of the diff (default: the working tree)
+        #[arg(long, value_name = "REF", requires = "symbol")]
+        to: Option<String>,
+
+        /// With --symbol, lines of context around each change
+        #[arg(short = 'U', long = "unified", value_name = "N", default_value_t = 3)]
+        unified: usize,
     },
 
-    /// [ANALYSIS] Show type information for a symbol's parameters and return type
+    /// [GIT] Symbol-level diff of the working tree against a stash entry or another worktree
     #[command(
-        about = "Analyze types used in a symbol's signature and locate their definitions",
-        long_about = "USE CASE: Understand types flowing through a function/method
-  • Extract parameter types and return type from signature
-  • Locate where each custom type is defined in the codebase
-  • Support for Rust, Python, TypeScript, Go, Java, and C
+        name = "stash-compare",
+        about = "Compare the working tree against a stash entry or another worktree (symbol-level)",
+        long_about = "USE CASE: Structural diff of work-in-progress
+  • Compare uncommitted edits against a stash entry (stash@{0}, stash@{2}, ...)
+  • Compare this checkout against another worktree directory of the same repo
+  • Same change types as 'cm diff', including MOVED and RENAMED
+
+TARGET:
+  stash@{N} / any ref → Compared against the working tree, uncommitted edits included
+  directory path      → Files paired by relative path with this checkout
+
+TIP: 'git diff stash@{0}' shows lines, this shows symbols"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm stash-compare                             # Working tree vs stash@{0}
+  cm stash-compare stash@{2} ./src             # Only src/, against an older stash
+  cm stash-compare ../myrepo-feature           # Against another worktree
+  cm stash-compare ../myrepo-feature --format ai
 
-LANGUAGE SUPPORT:
-  Rust     → fn name(x: Type) -> RetType
-  Python   → def name(x: Type) -> RetType (type hints)
-  TS/JS    → function name(x: Type): RetType
-  Go       → func name(x Type) RetType
-  Java     → RetType name(Type x)
-  C        → RetType name(Type x)
+TYPICAL WORKFLOW:
+  1. Park experiment: git stash
+  2. Try another approach in the working tree
+  3. Compare approaches: cm stash-compare")]
+    StashCompare {
+        /// Stash entry / git ref, or path to another worktree
+        #[arg(default_value = "stash@{0}")]
+        target: String,
 
-OUTPUT:
-  • Symbol signature
-  • Table of parameter names, types, and where each type is defined
-  • Return type with its definition location
+        /// Directory or file path to analyze (optional, defaults to entire repo)
+        #[arg(default_value = ".")]
+        path: PathBuf,
 
-TIP: Useful for understanding API boundaries and type dependencies"
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Show anonymous/lambda functions (default: filtered out)
+        #[arg(long, default_value_t = false)]
+        full: bool,
+    },
+
+    /// [GIT] Names, kinds and files of the symbols changed between two refs
+    #[command(
+        name = "changed-symbols",
+        about = "List the symbols changed between two refs (names, kinds, files only)",
+        long_about = "USE CASE: Select affected tests and checks in CI
+  • A thin, fast variant of 'cm diff': which symbols changed, nothing more
+  • Reads the changed line ranges from git and parses only the changed files
+  • Each changed line counts toward the innermost symbol around it, so an edit
+    in a method lists the method, not its class; blank lines are ignored
+  • No signature comparison, move or rename detection (use 'cm diff' for that)
+
+CHANGE TYPES:
+  ADDED     → Only in the head
+  DELETED   → Only in the base
+  MODIFIED  → In both, with changed lines
+
+REFS:
+  --between BASE HEAD  → Commits, branches or tags
+  --between BASE       → BASE against the working tree, uncommitted edits included
+  (default)            → origin/main against the working tree"
     )]
     #[command(after_help = "EXAMPLES:
-  cm types process_payment                    # Analyze types in process_payment
-  cm types authenticate --fuzzy               # Fuzzy search for symbol
-  cm types parse ./src --format human         # Pretty table output
-  cm types validate_input --format ai         # Token-efficient for LLMs
+  cm changed-symbols                                    # Since origin/main
+  cm changed-symbols --between origin/main HEAD --format ai
+  cm changed-symbols --between v1.2.0 v1.3.0 ./src      # Between releases, src/ only
+  cm changed-symbols --between HEAD --format compact    # Uncommitted edits
+
+TYPICAL WORKFLOW (CI):
+  1. cm changed-symbols --between origin/main HEAD --format ai > changed.txt
+  2. Pick the tests for each name: cut -d'|' -f1 changed.txt | xargs -n1 cm tests --format compact
+  3. Run only those tests")]
+    ChangedSymbols {
+        /// Directory or file path to limit the comparison to (optional, defaults to entire repo)
+        #[arg(default_value = ".")]
+        path: PathBuf,
 
-TYPICAL WORKFLOW:
-  1. Find function: cm query my_func --fuzzy
-  2. Analyze types: cm types my_func
-  3. Inspect type definition: cm query TypeName to see implementation
+        /// Base and head refs; with only a base, the working tree is the head
+        #[arg(
+            long,
+            num_args = 1..=2,
+            value_names = ["BASE", "HEAD"],
+            default_value = "origin/main"
+        )]
+        between: Vec<String>,
 
-WHEN TO USE:
-  ✓ \"What types does this function accept?\"
-  ✓ \"Where is this parameter type defined?\"
-  ✓ \"What's the return type and its definition?\"")]
-    Types {
-        /// Symbol name to analyze
-        symbol: String,
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+    },
+
+    /// [ANALYSIS] Find all call sites of a function (reverse call graph)
+    #[command(
+        about = "Find all places where a function/method is called",
+        long_about = "USE CASE: Understand who calls a function
+  • Find all call sites of a specific function
+  • Useful for impact analysis before refactoring
+  • Shows the enclosing function/method making each call
+  • Uses AST-based call detection (not text search)
+  • Tip: use qualified names (e.g. `Foo::new`) to reduce noise for common method names
+
+SUPPORTED LANGUAGES:
+  Python, JavaScript, TypeScript, Rust, Go, Java, C
+
+TIP: Great for understanding function usage patterns"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm callers parse_file                    # Find all callers of parse_file
+  cm callers parse ./src --fuzzy           # Fuzzy match 'parse' in src/
+  cm callers parse /monorepo --fast        # Grep-prefilter before parsing
+  cm callers Foo::new                      # Disambiguate common method names
+  cm callers validate --format human       # Pretty table output
+  cm callers process_data . --format ai    # Token-efficient output
+  cm callers --from-set auth-hits          # Every symbol pinned by 'cm query --save-as'
+
+TYPICAL WORKFLOW:
+  1. Find the function: cm query my_func --fuzzy
+  2. See who calls it: cm callers my_func
+  3. Understand the call chain before refactoring")]
+    Callers {
+        /// Symbol name to find callers for; with --from-set, the directory path to search in
+        #[arg(required_unless_present = "from_set")]
+        symbol: Option<String>,
 
         /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Enable fuzzy matching for symbol name
+        /// Find callers of every symbol in a set saved with 'cm query --save-as'
+        #[arg(long, value_name = "NAME", conflicts_with = "fuzzy")]
+        from_set: Option<String>,
+
+        /// Enable fuzzy matching for symbol lookup
         #[arg(long, default_value = "false")]
         fuzzy: bool,
 
+        /// Grep-prefilter files before parsing (auto-enabled for 1000+ files)
+        #[arg(long, default_value = "false")]
+        fast: bool,
+
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
+
+        /// Maximum number of results to return (prevents overwhelming output)
+        #[arg(long)]
+        limit: Option<usize>,
     },
 
-    /// [ANALYSIS] Show field structure for structs, classes, dataclasses, etc.
+    /// [ANALYSIS] Find all functions called by a symbol (forward call graph)
     #[command(
-        about = "Display field schema for data structures (structs, classes, dataclasses, etc.)",
-        long_about = "USE CASE: Understand the field structure of data types
-  • Parse struct/class bodies to extract fields with types
-  • Works with Rust structs, Python dataclasses/Pydantic, TypeScript interfaces, Java classes, Go structs
-  • Shows field names, types, optionality, and default values
+        about = "Find all functions/methods that a symbol calls",
+        long_about = "USE CASE: Understand what a function depends on
+  • See all functions called within a symbol's body
+  • Useful for understanding code dependencies
+  • Links to definitions when found in codebase
+  • Marks external/built-in functions separately
 
-LANGUAGE SUPPORT:
-  Rust       → struct fields (name: Type)
-  Python     → dataclass, TypedDict, Pydantic BaseModel fields
-  TypeScript → interface/class properties
-  Java       → class fields
-  Go         → struct fields
+SUPPORTED LANGUAGES:
+  Python, JavaScript, TypeScript, Rust, Go, Java, C
 
-TIP: Use --fuzzy for flexible symbol matching"
+TIP: Great for understanding function complexity and dependencies"
     )]
     #[command(after_help = "EXAMPLES:
-  cm schema User                           # Show fields of User struct/class
-  cm schema Config --fuzzy                 # Fuzzy search for Config
-  cm schema UserRequest ./src --format ai  # Token-efficient output
-  cm schema MyModel --format human         # Pretty table format
+  cm callees main                          # What does main() call?
+  cm callees process_data --fuzzy          # Fuzzy match
+  cm callees cmd_query --format human      # Pretty table output
+  cm callees validate . --format ai        # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Find class: cm query MyClass --fuzzy
-  2. Show schema: cm schema MyClass
-  3. Understand types: cm types related_function
-
-WHEN TO USE:
-  ✓ \"What fields does this struct have?\"
-  ✓ \"What's the shape of this dataclass?\"
-  ✓ \"Which fields are optional in this model?\"")]
-    Schema {
-        /// Symbol name (struct/class/interface name)
+  1. Find the function: cm query my_func --fuzzy
+  2. See what it calls: cm callees my_func
+  3. Understand the dependency graph")]
+    Callees {
+        /// Symbol name to find callees for
         symbol: String,
 
         /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Enable fuzzy matching for symbol name
+        /// Enable fuzzy matching for symbol lookup
         #[arg(long, default_value = "false")]
         fuzzy: bool,
 
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
+
+        /// Maximum number of results to return (prevents overwhelming output)
+        #[arg(long)]
+        limit: Option<usize>,
     },
 
-    /// [UTILITY] Save current symbol state as a named snapshot
+    /// [ANALYSIS] Find where a constant or enum variant is used
     #[command(
-        about = "Save a snapshot of current codebase symbols for later comparison",
-        long_about = "USE CASE: Create reference points for tracking code evolution
-  • Save current symbol state as a named reference
-  • Includes all symbols with signatures and locations
-  • Records git commit hash if in a git repository
-  • Stored in .codemapper/snapshots/<name>.json (override with --cache-dir or CODEMAPPER_CACHE_DIR)
+        about = "Find definitions and uses of a constant or enum variant",
+        long_about = "USE CASE: Change a widely-used flag, limit or enum variant safely
+  • Lists the definition and every use of the value in code
+  • Separates matches inside strings and comments (config keys, log messages)
+    that only share the name, so a rename does not touch them by accident
+  • Qualified names (`Status::NotFound`, `Color.RED`) skip same-named variants
+    of other enums; a bare variant name matches every enum defining it
+  • Uses the syntax tree (not text search) to tell code from strings and comments
 
-COMMON USES:
-  • Save baseline before major refactoring
-  • Track API surface changes over time
-  • Compare feature branches to main
+SUPPORTED LANGUAGES:
+  Python, JavaScript, TypeScript, Rust, Go, Java, C
 
-TIP: Use 'cm compare <name>' to see changes since the snapshot"
+TIP: Run before renaming or changing the value of a shared constant"
     )]
     #[command(after_help = "EXAMPLES:
-  cm snapshot baseline                      # Save snapshot named 'baseline'
-  cm snapshot v1.0                          # Save snapshot named 'v1.0'
-  cm snapshot pre-refactor --format human   # Save with confirmation table
-  cm snapshot --list                        # List all saved snapshots
-  cm snapshot --delete old-snap             # Delete a snapshot
+  cm value-refs MAX_RETRIES                # Definition, uses and textual matches
+  cm value-refs Status::NotFound ./src     # One variant of one enum
+  cm value-refs Color.RED --code-only      # Skip strings and comments
+  cm value-refs DEFAULT_PORT --format ai   # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Save baseline: cm snapshot before-changes
-  2. Make code changes
-  3. Compare: cm compare before-changes
-  4. Review added/deleted/modified symbols")]
-    Snapshot {
-        /// Snapshot name (required unless --list or --delete)
-        #[arg(required_unless_present_any = ["list", "delete"])]
-        name: Option<String>,
+  1. Find the constant: cm query TIMEOUT --type static
+  2. See every use: cm value-refs TIMEOUT
+  3. Check the textual matches before renaming")]
+    ValueRefs {
+        /// Constant or enum variant (`MAX`, `Enum::Variant`, `Enum.MEMBER`)
+        name: String,
 
-        /// Directory path to analyze
+        /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// List all saved snapshots
+        /// Only report uses in code, not matches inside strings and comments
         #[arg(long, default_value_t = false)]
-        list: bool,
+        code_only: bool,
 
-        /// Delete a snapshot by name
-        #[arg(long)]
-        delete: Option<String>,
+        /// Grep-prefilter files before parsing (auto-enabled for 1000+ files)
+        #[arg(long, default_value = "false")]
+        fast: bool,
 
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
+
+        /// Maximum number of results to return (prevents overwhelming output)
+        #[arg(long)]
+        limit: Option<usize>,
     },
 
-    /// [ANALYSIS] Compare current codebase to a saved snapshot
+    /// [ANALYSIS] Summarize how a function can fail
     #[command(
-        about = "Show symbol-level changes between current code and a saved snapshot",
-        long_about = "USE CASE: Track code evolution since a snapshot was taken
-  • Compares current symbols against a previously saved snapshot
-  • Shows ADDED, DELETED, MODIFIED, and SIGNATURE_CHANGED symbols
-  • Like 'cm diff' but against a saved state instead of a git commit
-
-CHANGE TYPES:
-  ADDED            → New symbols that didn't exist in the snapshot
-  DELETED          → Symbols that were removed since the snapshot
-  MODIFIED         → Symbols with body/line changes (same signature)
-  SIGNATURE_CHANGED → Symbols with parameter or return type changes
-
-TIP: Save snapshots at key milestones for easy comparison"
+        about = "List the error types a function (and its direct callees) can raise or return",
+        long_about = "USE CASE: Review failure modes before calling or changing a function
+  • Errors the function declares, raises/throws, returns or panics with
+  • The same for each function it calls directly, marked with the callee name
+  • Each entry points at the line it comes from
+
+DETECTS:
+  Rust     → Result<_, E> / io::Result in the signature, Err(Type::..), bail!/anyhow!,
+             panic!/unreachable!/todo!, .unwrap()/.expect()
+  Python   → raise Type(...), bare raise (re-raise)
+  JS/TS    → throw new Type(...), Promise.reject(new Type(...))
+  Java     → throws clause, throw new Type(...)
+  Go       → error results, errors.New, fmt.Errorf, ErrXxx sentinels, XxxError{...}, panic
+
+TIP: Errors from deeper calls surface through the callee that propagates them"
     )]
     #[command(after_help = "EXAMPLES:
-  cm compare baseline                     # Compare to 'baseline' snapshot
-  cm compare v1.0 --format human          # Pretty table output
-  cm compare pre-refactor --format ai     # Token-efficient for LLMs
-  cm compare release --extensions py,rs   # Only Python and Rust files
+  cm errors load_config                    # Own and direct-callee errors
+  cm errors Parser::parse                  # Qualified method name
+  cm errors save --direct                  # Only the function itself
+  cm errors save --format ai               # Token-efficient output
 
 TYPICAL WORKFLOW:
-  1. Saved snapshot earlier: cm snapshot milestone
-  2. Made code changes
-  3. Review changes: cm compare milestone
-  4. See what was added/deleted/modified")]
-    Compare {
-        /// Snapshot name to compare against
-        snapshot: String,
+  1. What can fail: cm errors <function>
+  2. Who has to handle it: cm callers <function>
+  3. Is it tested: cm tests <function>")]
+    Errors {
+        /// Function or method name (`name`, `Type::method`, `Type.method`)
+        symbol: String,
 
-        /// Directory path to analyze
+        /// Directory path to search in
         #[arg(default_value = ".")]
         path: PathBuf,
 
+        /// Skip errors coming from direct callees
+        #[arg(long, default_value_t = false)]
+        direct: bool,
+
         /// Comma-separated file extensions to include
-        #[arg(long, default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,md")]
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
         extensions: String,
 
         /// Disable cache (always reindex)
-        #[arg(long, default_value_t = false)]
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
         no_cache: bool,
 
         /// Force rebuild cache
         #[arg(long, default_value_t = false)]
         rebuild_cache: bool,
     },
-}
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// [ANALYSIS] Find tests that call a symbol
+    #[command(
+        about = "Find test functions that call a given symbol",
+        long_about = "USE CASE: Find tests covering a specific function or method
+  • Identifies test files by naming convention (_test.rs, test_*.py, *.test.js)
+  • Detects test functions by attributes (#[test], @Test) or naming (test_*, Test*)
+  • Shows where in the test the symbol is called
+  • Also links tests named after the symbol that never call it directly
+    (test_parse_config, TestParseConfig, ParseConfigTest → parse_config / ParseConfig)
 
-    let format = OutputFormat::from_str(&cli.format).unwrap_or_else(|err| {
-        eprintln!("{}", err);
-        std::process::exit(1);
-    });
+TEST DETECTION:
+  Rust     → #[test] attribute, _test.rs files, tests/ directory
+  Python   → test_*.py files, functions starting with test_
+  Go       → *_test.go files, functions starting with Test
+  JS/TS    → *.test.js, *.spec.ts, __tests__/ directory
+  Java     → @Test annotation, *Test.java files
 
-    let cache_dir = cli.cache_dir.as_deref();
+LISTING (--list):
+  Lists every test the index knows of instead, with file, line and the suites
+  it is nested in (describe blocks, test classes, Rust test modules), without
+  running the test framework's own collector
 
-    match cli.command {
-        Commands::Stats {
-            path,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_stats(path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
-        }
-        Commands::Map {
-            path,
-            level,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_map(path, level, extensions, no_cache, rebuild_cache, format, cache_dir)?;
-        }
-        Commands::Query {
-            symbol,
-            path,
-            exact,
-            r#type,
-            context,
-            show_body,
-            fast,
-            extensions,
-            no_cache,
-            rebuild_cache,
-            full,
-            exports_only,
-            limit,
-        } => {
-            cmd_query(
+ORPHANS (--orphans):
+  Lists tests named after a symbol (test_parse_config, UserServiceTest,
+  describe('parseConfig')) that still call it while the index defines it
+  nowhere: the function was deleted or renamed and its tests left behind.
+  Shorter readings count as defined (test_parse_config_twice → parse_config),
+  and descriptive names that call no such symbol are never flagged
+
+TIP: Use before refactoring to understand test coverage"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm tests parse_file                     # Find tests calling parse_file
+  cm tests authenticate --fuzzy           # Fuzzy match test discovery
+  cm tests validate ./src --format human  # Pretty table output
+  cm tests process_payment --format ai    # Token-efficient for LLMs
+  cm tests parse_file /monorepo --fast    # Grep-prefilter before parsing
+  cm tests --list                         # Inventory of every test
+  cm tests --list ./tests --format ai     # Tests under one directory
+  cm tests --orphans                      # Tests named after deleted symbols
+
+TYPICAL WORKFLOW:
+  1. Identify function to refactor: cm query my_function
+  2. Find tests: cm tests my_function
+  3. Run tests, make changes, verify")]
+    Tests {
+        /// Symbol name to find tests for (with --list or --orphans: the path to look under)
+        #[arg(required_unless_present_any = ["list", "orphans"])]
+        symbol: Option<String>,
+
+        /// Directory path to search in
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Enable fuzzy matching for flexible search
+        #[arg(long, default_value = "false")]
+        fuzzy: bool,
+
+        /// Grep-prefilter files before parsing (auto-enabled for 1000+ files)
+        #[arg(long, default_value = "false")]
+        fast: bool,
+
+        /// List all tests with their suites instead of finding tests for a symbol
+        #[arg(long, default_value = "false")]
+        list: bool,
+
+        /// List tests named after a symbol that no longer exists
+        #[arg(long, default_value = "false", conflicts_with = "list")]
+        orphans: bool,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Find where tests mock or patch a symbol
+    #[command(
+        about = "Find tests that mock or patch a symbol, its class or its module",
+        long_about = "USE CASE: Know which tests break when a symbol's interface changes
+  • A mock keeps the old signature alive after a refactor, so the tests
+    mocking a symbol need updating along with its callers
+  • Lists every mock in test files with the test doing it
+  • Reports how each matched: by name (app.api.fetch, Client.send),
+    by class (mock(Client.class) for Client.send) or by module
+    (jest.mock('./api') for a function in api.ts)
+
+DETECTS:
+  Python   → patch(\"a.b.f\"), patch.object(C, \"f\"), mocker.patch,
+             monkeypatch.setattr
+  JS/TS    → jest.mock / vi.mock module paths, jest.spyOn(obj, \"f\")
+  Java     → mock(C.class), @Mock C c, when(x.f(...)), verify(x).f(...)
+  Kotlin   → mockk<C>(), @MockK c: C, every { x.f(...) }
+
+NOTE: Targets match by name, as patch targets the name a module looks up,
+  not where it is defined; qualify as Class.method to narrow it down"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm mocks fetch_user                     # Tests mocking fetch_user
+  cm mocks Client.send ./tests            # Only Client's send, under tests/
+  cm mocks OrderRepository --format ai    # Token-efficient output
+
+TYPICAL WORKFLOW:
+  1. Change a signature: cm query fetch_user --show-body
+  2. Find callers: cm callers fetch_user
+  3. Find the mocks to update: cm mocks fetch_user")]
+    Mocks {
+        /// Symbol name, or Class.method
+        symbol: String,
+
+        /// Directory path to search in
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Find symbols with no test coverage
+    #[command(
+        about = "Find functions and methods that are not called by any test",
+        long_about = "USE CASE: Identify code without test coverage
+  • Finds symbols not called from any test file or test function
+  • A test named after a symbol (test_parse_config, ConfigTest) covers it too
+  • Excludes test functions themselves from the output
+  • Excludes private/internal helpers (leading underscore in Python)
+  • Shows coverage percentage and untested symbol count
+
+TEST DETECTION:
+  Rust     → #[test] attribute, _test.rs files, tests/ directory
+  Python   → test_*.py files, functions starting with test_
+  Go       → *_test.go files, functions starting with Test
+  JS/TS    → *.test.js, *.spec.ts, __tests__/ directory
+  Java     → @Test annotation, *Test.java files
+
+TIP: Use to identify areas needing test coverage"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm untested                           # Find untested symbols in current dir
+  cm untested ./src                     # Check specific directory
+  cm untested . --format human          # Pretty table output
+  cm untested . --format ai             # Token-efficient for LLMs
+  cm untested . --fail-under 70         # CI gate: exit 3 below 70% coverage
+  cm untested . --where 'exported && kind=function'   # Coverage of the public functions
+
+TYPICAL WORKFLOW:
+  1. Check coverage: cm untested .
+  2. Pick symbol to test: identify critical untested code
+  3. Write tests for high-priority symbols")]
+    Untested {
+        /// Directory path to analyze
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+
+        /// Exit with code 3 when coverage is below this percentage, e.g. 70 or 70%
+        #[arg(long, value_parser = bench::parse_percent, value_name = "PERCENT")]
+        fail_under: Option<f64>,
+
+        /// Only report and count symbols matching this filter expression, e.g. 'kind=function && exported && lines>40'
+        #[arg(
+            long = "where",
+            value_name = "EXPR",
+            value_parser = filter_expr::Filter::parse
+        )]
+        r#where: Option<filter_expr::Filter>,
+    },
+
+    /// [ANALYSIS] List deprecated symbols and the calls that still reach them
+    #[command(
+        about = "List deprecated symbols with their remaining callers and migration progress",
+        long_about = "USE CASE: Track a deprecation through to deleting the old code
+  • Finds symbols marked deprecated the way their language does it
+  • Lists every call still reaching each one (calls from inside the symbol don't count)
+  • Shows how many are migrated: deprecated with no callers left, ready to delete
+
+MARKERS:
+  Rust     → #[deprecated], #[deprecated(note = \"...\")]
+  Python   → @deprecated(\"...\"), warnings.warn(..., DeprecationWarning), .. deprecated::
+  JS/TS    → @deprecated in a JSDoc comment
+  Java     → @Deprecated, @deprecated in Javadoc
+  Go       → // Deprecated: doc comment line
+  C        → [[deprecated]], __attribute__((deprecated))
+  Any      → a @cm:deprecated comment marker
+
+A DeprecationWarning raised in __init__ deprecates the class.
+
+TIP: Callers are matched by name, so check their confidence before editing"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm deprecated                          # Deprecated symbols in current dir
+  cm deprecated ./src --format human     # Table per symbol with its callers
+  cm deprecated . --format compact       # One line per remaining call, for vim -q
+  cm deprecated . --max-calls 40         # CI ratchet: exit 3 above 40 remaining calls
+
+TYPICAL WORKFLOW:
+  1. See what is left: cm deprecated .
+  2. Migrate callers: cm deprecated . --format compact
+  3. Delete symbols with no callers left, then lower --max-calls in CI")]
+    Deprecated {
+        /// Directory path to analyze
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+
+        /// Exit with code 3 when more calls than this still reach deprecated symbols
+        #[arg(long, value_name = "N")]
+        max_calls: Option<usize>,
+    },
+
+    /// [ANALYSIS] List breaking changes since a known-good commit
+    #[command(
+        about = "Show breaking API changes since a git commit (removed symbols, signature changes)",
+        long_about = "USE CASE: Identify breaking changes that could affect callers
+  • Removed symbols (DELETED) - definitely breaks callers
+  • Signature changes (params/return type modified) - breaks callers
+  • Filters out non-breaking changes (internal body modifications)
+
+BREAKING CHANGES:
+  DELETED          → Symbol was removed (callers will fail)
+  SIGNATURE_CHANGED → Function signature modified (callers may need updates)
+  MOVED / RENAMED  → Symbol relocated or renamed (references need updating)
+
+NON-BREAKING (filtered out with --breaking):
+  ADDED            → New symbols (safe)
+  MODIFIED         → Body changes only (internal, safe for callers)
+
+REQUIREMENTS:
+  • Must be run inside a git repository
+  • Commit reference can be: HEAD~1, abc123, branch-name, tag-name
+
+TIP: Use before releases to identify API-breaking changes"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm since HEAD~10 --breaking              # Breaking changes in last 10 commits
+  cm since main --breaking                 # Breaking changes since main branch
+  cm since v1.0 --breaking --format human  # Breaking changes since v1.0 tag
+  cm since HEAD~5 src/ --breaking          # Breaking changes in src/ directory
+  cm since abc1234                         # All changes (not just breaking)
+
+TYPICAL WORKFLOW:
+  1. Before release: cm since last-release --breaking
+  2. Check PR impact: cm since main --breaking --format human
+  3. Full changelog: cm since v1.0 (without --breaking)
+
+WHEN TO USE:
+  ✓ \"What breaks if I upgrade from v1.0?\"
+  ✓ \"Did this PR introduce breaking changes?\"
+  ✓ \"Is it safe to merge this into main?\"")]
+    Since {
+        /// Git commit reference (e.g., HEAD~1, abc123, main, v1.0)
+        commit: String,
+
+        /// Directory or file path to analyze (optional, defaults to entire repo)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Show only breaking changes (deleted symbols, signature changes)
+        #[arg(long, default_value_t = false)]
+        breaking: bool,
+    },
+
+    /// [ANALYSIS] CI gate: fail when exported symbols break against a base ref
+    #[command(
+        about = "Check for breaking changes to exported symbols (pre-commit / CI gate)",
+        long_about = "USE CASE: Block accidental API breaks before they land
+  • Compares current code against a base ref (default: origin/main)
+  • Only exported/public symbols are considered
+  • Reports DELETED, SIGNATURE_CHANGED, MOVED and RENAMED symbols
+  • With --check, exits with status 3 when unapproved breaks are found
+
+ALLOWLIST:
+  Intentional breaks can be listed in .cm-allow-breaking (or --allowlist <file>)
+  One entry per line, '#' starts a comment:
+    old_function              → allow by name anywhere
+    src/api.rs:Client::send   → allow only in a specific file
+
+REQUIREMENTS:
+  • Must be run inside a git repository
+  • Base ref must exist locally (run 'git fetch' first in CI)
+
+TIP: Add 'cm breaking --check' to a pre-commit hook or CI step"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm breaking --check                              # Gate against origin/main
+  cm breaking --check --against main               # Gate against local main
+  cm breaking --against v1.0 --format ai           # Report only, no exit code
+  cm breaking --check --allowlist ci/allowed.txt   # Custom allowlist file
+
+TYPICAL WORKFLOW:
+  1. CI step: cm breaking --check --against origin/main
+  2. Break is intentional? Add it to .cm-allow-breaking
+  3. Re-run: the allowlisted change no longer fails the check")]
+    Breaking {
+        /// Directory or file path to analyze (optional, defaults to entire repo)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Git reference to compare against (e.g., origin/main, main, v1.0)
+        #[arg(long, default_value = "origin/main")]
+        against: String,
+
+        /// Exit with status 3 if any non-allowlisted breaking change is found
+        #[arg(long, default_value_t = false)]
+        check: bool,
+
+        /// Allowlist file of intentional breaks (default: .cm-allow-breaking if present)
+        #[arg(long)]
+        allowlist: Option<PathBuf>,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+    },
+
+    /// [ANALYSIS] Detect structural drift against a recorded fingerprint
+    #[command(
+        about = "Check the project's structural fingerprint against a recorded baseline",
+        long_about = "USE CASE: Catch code whose declarations changed when they should not have
+  • Generated code: was it regenerated after the schema/IDL changed?
+  • Reproducible builds: do two checkouts or build outputs declare the same symbols?
+  • Vendored code: has anyone edited the copy?
+
+FINGERPRINT:
+  Each file hashes its symbols' kinds, names, parents, signatures and visibility,
+  sorted; the project hash covers every file path and file hash. Bodies, comments,
+  formatting and the order of definitions do not count.
+
+With --record, writes the current fingerprint to the file. Otherwise compares against
+it and exits with status 3 when files were changed, added or removed.
+
+TIP: Commit the fingerprint file next to the generated code it guards"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm verify --fingerprint gen.fingerprint --record src/gen   # Record a baseline
+  cm verify --fingerprint gen.fingerprint src/gen            # Fail on drift
+  cm verify --fingerprint gen.fingerprint --format ai        # Drifted files only
+
+TYPICAL WORKFLOW:
+  1. After generating code: cm verify --fingerprint gen.fingerprint --record src/gen
+  2. CI step: regenerate, then cm verify --fingerprint gen.fingerprint src/gen
+  3. Intentional change? Record again and commit the new fingerprint")]
+    Verify {
+        /// Directory to fingerprint
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Fingerprint file to compare against (or write, with --record)
+        #[arg(long, value_name = "FILE")]
+        fingerprint: PathBuf,
+
+        /// Write the current fingerprint to the file instead of comparing
+        #[arg(long, default_value_t = false)]
+        record: bool,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache (invalidate and reindex)
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Architectural linting: imports that cross declared layers the wrong way
+    #[command(
+        about = "Check imports against declared architectural layers (e.g. ui -> service -> data)",
+        long_about = "USE CASE: Keep a layered architecture layered
+  • Layers are declared top first; each may import itself and the layers below it
+  • An import reaching up into a higher layer is a violation, reported with its import site
+  • Shows how many files each layer holds and the import counts between layers
+  • With --check, exits with status 3 when there are violations
+
+CONFIG (.codemapper.toml):
+  [layers]
+  order = \"ui -> service -> data\"
+
+  [layers.paths]                     # optional; default: a directory named after the layer
+  ui = [\"src/components/**\", \"src/pages/**\"]
+  data = [\"src/db/**\"]
+
+A file belongs to the first layer whose globs match it; files in no layer are not checked.
+Imports are resolved the same way as 'cm deps <file> --depth', so external packages and
+unresolved imports never count."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm layers                                        # Layers from .codemapper.toml
+  cm layers --order 'ui -> service -> data'        # Ad hoc, directories named after layers
+  cm layers --check                                # CI gate
+  cm layers --check --format compact               # file:line list for editors
+
+TYPICAL WORKFLOW:
+  1. Declare the layers in .codemapper.toml
+  2. Review the violations: cm layers
+  3. Gate CI on it: cm layers --check")]
+    Layers {
+        /// Directory to check
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Layer order, top first, e.g. 'ui -> service -> data' (overrides [layers] order)
+        #[arg(long)]
+        order: Option<String>,
+
+        /// Exit with status 3 if any import violates the layer order
+        #[arg(long, default_value_t = false)]
+        check: bool,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache (invalidate and reindex)
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [GIT] Recommend a major/minor/patch release bump from public API changes
+    #[command(
+        about = "Semver bump advisor: recommend major/minor/patch since a release tag",
+        long_about = "USE CASE: Decide the version number for the next release of a library
+  • Compares public (exported) symbols between a release tag and the working tree
+  • Removed or signature-changed public symbol → MAJOR
+  • Added public symbol                         → MINOR
+  • Only internal changes                       → PATCH
+  • Lists every change that justifies the recommendation
+
+VERSION SUGGESTION:
+  When --from looks like a version (1.2.0 or v1.2.0) the next version is suggested.
+  Pre-1.0 versions follow the cargo convention: breaking changes bump the minor.
+
+MACHINE-READABLE:
+  --format ai prints one `bump|change|symbol|type|file` line per justification."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm semver --from v1.2.0                   # Recommend bump since v1.2.0
+  cm semver --from v1.2.0 --format ai       # Machine-readable justification list
+  cm semver --from v0.9.3 src/              # Only consider the src/ directory
+
+TYPICAL WORKFLOW:
+  1. Before tagging: cm semver --from <last-tag>
+  2. Review the justifications for unintended API changes
+  3. Tag the suggested version")]
+    Semver {
+        /// Directory or file path to analyze (optional, defaults to entire repo)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Release tag or git reference of the previous release (e.g., v1.2.0)
+        #[arg(long)]
+        from: String,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h')
+        #[arg(
+            long,
+            default_value = "py,js,ts,jsx,tsx,rs,java,go,c,h,cpp,cc,cxx,hpp,hh,hxx",
+            env = "CM_EXTENSIONS"
+        )]
+        extensions: String,
+    },
+
+    /// [GIT] Diff exported symbols against the version of the package users actually have
+    #[command(
+        name = "api-diff",
+        about = "Compare the public API of the working tree with a published package release",
+        long_about = "USE CASE: Verify what changed relative to the release on crates.io, npm or PyPI
+  • Downloads the published source archive (what users actually have installed)
+  • Diffs exported symbols against the local checkout: added, deleted, signature changes
+  • Catches drift that git tags miss: unpushed tags, publish from a dirty tree, vendored edits
+
+PACKAGE SPEC:
+  crates:NAME[@VERSION]   → crates.io (yanked releases are skipped)
+  npm:NAME[@VERSION]      → registry.npmjs.org (scoped names like @scope/pkg work)
+  pypi:NAME[@VERSION]     → PyPI source distribution (sdist)
+  VERSION may be a prefix: @0.3 picks the newest 0.3.x; omitted means latest
+  A directory or a downloaded .crate/.tgz/.tar.gz/.zip path works as well
+
+Compared extensions default to the registry's language (crates: rs, npm: js,ts,jsx,tsx, pypi: py)."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm api-diff --against-registry crates:codemapper@0.3    # Newest published 0.3.x
+  cm api-diff --against-registry npm:@acme/sdk             # Latest npm release
+  cm api-diff --against-registry pypi:requests@2.31.0 src/ # Compare against src/
+  cm api-diff --against-registry ./mylib-0.3.1.crate       # Already-downloaded archive
+  cm api-diff --against-registry crates:serde --format ai  # Token-efficient diff
+
+TYPICAL WORKFLOW:
+  1. Before a release: cm api-diff --against-registry crates:<name>
+  2. Review DELETED and SIGNATURE_CHANGED entries - they break users
+  3. Pick the version with 'cm semver' or by hand")]
+    ApiDiff {
+        /// Local package root to compare (where Cargo.toml, package.json or pyproject.toml lives)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Published release (crates:NAME@VERSION, npm:..., pypi:...) or a local archive/directory
+        #[arg(long, value_name = "PACKAGE")]
+        against_registry: String,
+
+        /// Comma-separated file extensions to include (default: the registry's language)
+        #[arg(long)]
+        extensions: Option<String>,
+
+        /// Skip anonymous/lambda functions
+        #[arg(long, default_value_t = false)]
+        skip_anonymous: bool,
+    },
+
+    /// [ANALYSIS] CI gate: exported symbols must match a committed API lock file
+    #[command(
+        about = "Freeze the exported API to a lock file, or check the code still matches it",
+        long_about = "USE CASE: Make every public API change a deliberate, reviewed edit
+  • freeze → prints one line per exported symbol: file, kind, name and signature
+  • check  → compares current exports against the lock (default: api.lock)
+  • Test files are left out; bodies, comments and line numbers never count
+  • Works for every indexed language, like cargo-public-api does for Rust
+
+LOCK FILE:
+  Sorted, tab-separated lines under a '# cm api lock v1' header, so a change to the
+  API shows up as a readable diff of the lock in code review.
+
+With check, reports ADDED, DELETED and SIGNATURE_CHANGED symbols and exits with
+status 3 when the exports deviate from the lock in any way, additions included.
+
+TIP: Commit api.lock and run 'cm api check' in CI"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm api freeze > api.lock                  # Record the current public API
+  cm api check                              # Fail if exports drifted from api.lock
+  cm api check src/ --lock ci/api.lock      # Another directory and lock file
+  cm api check --format ai                  # One line per deviation
+
+TYPICAL WORKFLOW:
+  1. Record and commit: cm api freeze > api.lock
+  2. CI step: cm api check
+  3. Intended change? cm api freeze > api.lock, and the lock diff goes into review")]
+    Api {
+        /// freeze: print the lock of the current exports; check: compare against the lock
+        #[arg(value_enum)]
+        action: api_lock::ApiAction,
+
+        /// Directory to freeze or check; lock paths are relative to it
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Lock file to check against
+        #[arg(long, default_value = "api.lock", value_name = "FILE")]
+        lock: PathBuf,
+
+        /// Comma-separated file extensions to include (e.g., 'py,js,rs,go,c,h,md')
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache (invalidate and reindex)
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Find exported/public symbols with no internal callers (API surface)
+    #[command(
+        about = "Find entrypoints: exported symbols that are not called internally",
+        long_about = "USE CASE: Identify API surface and potentially unused code
+  • Finds public/exported functions that have no callers within the codebase
+  • Useful for identifying: main entry points, exported APIs, dead code
+  • Groups by: Main Entrypoints, API Functions, Possibly Unused
+
+EXPORT DETECTION BY LANGUAGE:
+  Rust     → pub fn, pub struct (excluding pub(crate), pub(super))
+  Python   → No leading underscore, or in __all__
+  JS/TS    → export function, export default, module.exports
+  Go       → Capitalized function names
+  Java     → public modifier
+  C        → No leading underscore
+
+CATEGORIES:
+  Main Entrypoint → main, run, start, init, execute, cli, app
+  API Function    → get*, post*, handle*, create*, process*, classes, enums
+  Possibly Unused → Other exported symbols with no callers
+
+TIP: Use to identify dead code or document your public API"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm entrypoints                         # Find entrypoints in current dir
+  cm entrypoints ./src                   # Check specific directory
+  cm entrypoints . --format human        # Pretty table output
+  cm entrypoints . --format ai           # Token-efficient for LLMs
+
+TYPICAL WORKFLOW:
+  1. Check entrypoints: cm entrypoints .
+  2. Review 'Possibly Unused' - candidates for removal
+  3. Document 'API Functions' as your public interface
+
+WHEN TO USE:
+  ✓ \"What's our public API surface?\"
+  ✓ \"Is this function used anywhere?\"
+  ✓ \"Find dead code candidates\"
+  ✓ \"What are the entry points to this codebase?\"")]
+    Entrypoints {
+        /// Directory path to analyze
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Trace the call path between two symbols
+    #[command(
+        about = "Show the shortest call path from symbol A to symbol B",
+        long_about = "USE CASE: Understand how code flows between two symbols
+  • Find the call chain: A calls X, X calls Y, Y calls B
+  • Uses BFS to find the shortest path
+  • Supports fuzzy matching for flexible symbol lookup
+
+ALGORITHM:
+  • Breadth-first search from source symbol
+  • Follows call edges (function/method calls)
+  • Maximum depth: 10 levels to avoid infinite loops
+  • Returns shortest path found
+
+OUTPUT:
+  • Shows each step in the call chain
+  • Includes symbol type and file location for each step
+
+TIP: Use --fuzzy if you're not sure of exact symbol names"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm trace main parse_file                   # Trace from main to parse_file
+  cm trace authenticate validate --fuzzy    # Fuzzy match both symbols
+  cm trace handler response ./src           # Trace within specific path
+  cm trace cmd_query format_output --format human  # Pretty table
+
+TYPICAL WORKFLOW:
+  1. Find symbols: cm query func_a --fuzzy
+  2. Trace path: cm trace func_a func_b
+  3. Review the call chain to understand code flow
+
+WHEN TO USE:
+  ✓ \"How does data flow from A to B?\"
+  ✓ \"What's the call path from main to this function?\"
+  ✓ \"Understanding control flow in unfamiliar code\"")]
+    Trace {
+        /// Source symbol name (start of the path)
+        from: String,
+
+        /// Target symbol name (end of the path)
+        to: String,
+
+        /// Directory path to search in
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Enable fuzzy matching for symbol names
+        #[arg(long, default_value = "false")]
+        fuzzy: bool,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Quick breakage report for a symbol (definition + callers + tests)
+    #[command(
+        about = "Quick breakage report for a symbol (definition + callers + tests)",
+        long_about = "USE CASE: Tight edit loop safety check
+  • After you edit a function, run this to see what you likely broke
+  • Shows definition + signature, all callers, and tests that touch it
+  • Intended to be fast enough to run repeatedly during refactors
+
+TIP: Run this after changing a function signature
+
+RUNNING THE TESTS:
+  --emit prints only a test command that runs the impacted tests, for that runner:
+  • pytest  pytest path::Class::test_name ...
+  • jest    jest path ... -t '^(suite test name|...)$'
+  • cargo   cargo test -- module::tests::test_name ...
+  A test reached only through a helper or a file-level call runs its whole file
+  (pytest, jest) or module (cargo). Nothing is printed when no test was found."
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm impact symbols_by_type               # quick: counts + top callsites/tests
+  cm impact parse_file ./src --exact      # restrict scope + exact match
+  cm impact auth . --format ai            # token-efficient output
+  cm impact output --include-docs         # allow matching headings/code blocks
+  cm impact big_function --all            # print full lists (no truncation)
+  cm impact parse_total --emit pytest     # pytest tests/test_price.py::test_total
+  eval \"$(cm impact total --emit cargo)\"  # run just the impacted Rust tests")]
+    Impact {
+        /// Symbol name to analyze
+        symbol: String,
+
+        /// Directory path to search in
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Use exact matching (default is fuzzy)
+        #[arg(long, default_value = "false")]
+        exact: bool,
+
+        /// Include markdown headings/code blocks as candidates (default: code symbols only)
+        #[arg(long, default_value_t = false)]
+        include_docs: bool,
+
+        /// Maximum number of callers/tests to show (default: 10 each)
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Show full callers/tests lists (ignores --limit)
+        #[arg(long, default_value_t = false)]
+        all: bool,
+
+        /// Print only a command that runs the impacted tests with this runner
+        #[arg(long, value_enum, value_name = "RUNNER")]
+        emit: Option<test_select::Runner>,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Show what production symbols a test file calls
+    #[command(
+        about = "List production (non-test) symbols called by a test file",
+        long_about = "USE CASE: Understand test scope and coverage
+  • Parse a test file to find all function/method calls
+  • Filter to only show production code (not test helpers)
+  • Useful for understanding what a test actually tests
+
+FILTERS OUT:
+  • Calls to other test functions/files
+  • Calls to symbols in the same test file
+  • External/built-in functions not in codebase
+
+FIXTURES (--fixtures):
+  Maps shared test infrastructure to the tests using it instead, for a test
+  file or a whole directory:
+  pytest   → @pytest.fixture by parameter name, usefixtures, autouse
+  mocks    → jest.mock, vi.mock, jest.spyOn, patch, patch.object, monkeypatch
+  Go       → helpers in _test.go files taking *testing.T or calling t.Helper()
+
+TIP: Great for reviewing test scope before refactoring"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm test-deps ./tests/test_auth.py              # Show production deps
+  cm test-deps ./src/parser_test.rs --format ai  # Token-efficient output
+  cm test-deps ./auth.test.ts --format human     # Pretty table
+  cm test-deps ./tests/test_auth.py --fixtures   # Fixtures and mocks it uses
+  cm test-deps . --fixtures --format ai          # Every fixture and its tests
+
+TYPICAL WORKFLOW:
+  1. Find test file: cm map . --level 2 (look for test files)
+  2. Check test scope: cm test-deps ./path/to/test_file.py
+  3. Ensure test covers intended functionality")]
+    TestDeps {
+        /// Path to the test file to analyze (with --fixtures, a directory works too)
+        test_file: PathBuf,
+
+        /// Directory path to search for production symbols
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Report fixtures, mocks and test helpers and the tests using them instead
+        #[arg(long, default_value = "false")]
+        fixtures: bool,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [GIT] Find when a symbol was last modified
+    #[command(
+        about = "Show who last modified a symbol and when",
+        long_about = "USE CASE: Git blame for symbols, not lines
+  • Find the commit that last modified a specific function/class/method
+  • See author, date, and commit message
+  • Compare old vs new signature if it changed
+
+REQUIREMENTS:
+  • Must be run inside a git repository
+  • File must have git history
+
+FILE MODE (--file):
+  • One table of every symbol in the file with the commit, author and date
+    that last touched any of its lines
+  • A single `git blame` of the file, so a whole file costs about as much as one symbol
+  • Symbols with uncommitted edits show as uncommitted
+
+BATCH MODE (--batch):
+  • Reads one `SYMBOL FILE` pair per line (`-` for stdin; blank lines and # comments skipped)
+  • Entries are grouped by file: each file's history is read and parsed once for all of its symbols
+  • A symbol that cannot be blamed is reported inline without stopping the rest
+
+TIP: Use with 'cm history' to see full evolution of a symbol"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm blame parse_file ./src/parser.rs          # Blame for parse_file function
+  cm blame MyClass ./src/models.py --format ai # Token-efficient output
+  cm blame validate ./utils.go --format human  # Pretty table
+  cm blame --file ./src/parser.rs              # Every symbol of the file at once
+  cm blame --batch audit.txt --format ai       # Many symbols, one git pass per file
+
+TYPICAL WORKFLOW:
+  1. Find symbol: cm query my_func --fuzzy
+  2. See last change: cm blame my_func ./path/to/file.rs
+  3. See full history: cm history my_func ./path/to/file.rs")]
+    Blame {
+        /// Symbol name to blame
+        #[arg(required_unless_present_any = ["blame_file", "batch"])]
+        symbol: Option<String>,
+
+        /// Path to the file containing the symbol
+        #[arg(required_unless_present_any = ["blame_file", "batch"])]
+        file: Option<PathBuf>,
+
+        /// Blame every symbol of a file in one table instead of one symbol
+        #[arg(long = "file", value_name = "FILE", conflicts_with_all = ["symbol", "file"])]
+        blame_file: Option<PathBuf>,
+
+        /// Blame every `SYMBOL FILE` line of a list (`-` for stdin)
+        #[arg(long, value_name = "LIST", conflicts_with_all = ["symbol", "file", "blame_file"])]
+        batch: Option<PathBuf>,
+    },
+
+    /// [GIT] Show all commits that touched a symbol
+    #[command(
+        about = "Show the evolution of a symbol across git history",
+        long_about = "USE CASE: Track how a symbol evolved over time
+  • See all commits where a symbol was added, modified, or deleted
+  • Track signature changes across versions
+  • Understand when and why a function changed
+
+REQUIREMENTS:
+  • Must be run inside a git repository
+  • File must have git history
+
+CHANGE TRACKING:
+  • Detects signature changes (parameter/return type modifications)
+  • Detects body size changes (function grew or shrunk)
+  • Shows when symbol was created or deleted
+
+FILE MODE (--file):
+  • Tracks the file's exported API instead of a single symbol
+  • One entry per commit that added, removed, or re-signed an exported symbol
+  • Useful when reviewing how a module's public surface evolved
+
+BATCH MODE (--batch):
+  • Reads one `SYMBOL FILE` pair per line (`-` for stdin; blank lines and # comments skipped)
+  • Each file's history is walked once for all of its symbols
+
+TIP: Combine with 'cm blame' for quick last-change info"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm history parse_file ./src/parser.rs           # Full history
+  cm history authenticate ./auth.py --format ai   # Token-efficient
+  cm history MyClass ./models.go --format human   # Pretty table
+  cm history --file ./src/parser.rs               # Evolution of the file's API
+  cm history --batch audit.txt --format ai        # Many symbols, one git pass per file
+
+TYPICAL WORKFLOW:
+  1. Find symbol: cm query my_func --fuzzy
+  2. See history: cm history my_func ./path/to/file.rs
+  3. Compare specific versions using git diff")]
+    History {
+        /// Symbol name to track
+        #[arg(required_unless_present_any = ["api_file", "batch"])]
+        symbol: Option<String>,
+
+        /// Path to the file containing the symbol
+        #[arg(required_unless_present_any = ["api_file", "batch"])]
+        file: Option<PathBuf>,
+
+        /// Show the evolution of a whole file's exported API instead of one symbol
+        #[arg(long = "file", value_name = "FILE", conflicts_with_all = ["symbol", "file"])]
+        api_file: Option<PathBuf>,
+
+        /// Trace every `SYMBOL FILE` line of a list (`-` for stdin)
+        #[arg(long, value_name = "LIST", conflicts_with_all = ["symbol", "file", "api_file"])]
+        batch: Option<PathBuf>,
+    },
+
+    /// [ANALYSIS] Find all implementations of an interface/trait/protocol
+    #[command(
+        about = "Find all classes/structs that implement a given interface or trait",
+        long_about = "USE CASE: Discover all implementors of an interface
+  • Rust: Find `impl Trait for Type` patterns
+  • Python: Find class inheritance `class Foo(Interface):`
+  • TypeScript/JavaScript: Find `class Foo implements Interface`
+  • Java: Find `implements` and `extends` clauses
+  • Go: Find structs that embed interfaces
+
+LANGUAGE PATTERNS:
+  Rust     → impl Trait for Type, impl Type
+  Python   → class Name(Interface):
+  TS/JS    → class Name implements Interface, extends Parent
+  Java     → class Name implements Interface, extends Parent
+  Go       → type Name struct { Interface }
+
+TIP: Use --fuzzy to find partial matches"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm implements Iterator                    # Find all Iterator implementations
+  cm implements Repository --fuzzy          # Fuzzy match implementations
+  cm implements Handler ./src --format ai   # Search in src/, AI format
+  cm implements Serializable --format human # Pretty table output
+
+TYPICAL WORKFLOW:
+  1. cm implements MyInterface --fuzzy
+  2. Review which types implement the interface
+  3. cm inspect <file> to see the implementation details
+
+WHEN TO USE:
+  ✓ \"What types implement this trait?\"
+  ✓ \"Find all subclasses of this base class\"
+  ✓ \"Which structs satisfy this interface?\"")]
+    Implements {
+        /// Interface, trait, or protocol name to search for
+        interface: String,
+
+        /// Directory path to search in
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Enable fuzzy matching for interface name
+        #[arg(long, default_value = "false")]
+        fuzzy: bool,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+
+        /// Only show trait implementations (filter out inherent impls)
+        #[arg(long, default_value_t = false)]
+        trait_only: bool,
+    },
+
+    /// [ANALYSIS] Full ancestor and descendant tree of a class, interface, or trait
+    #[command(
+        about = "Show the type hierarchy of a class/interface/trait: every ancestor and descendant",
+        long_about = "USE CASE: See where a type sits in the inheritance graph, not just its direct implementors
+  • Ancestors: base classes, implemented interfaces, supertraits - and theirs
+  • Descendants: subclasses, implementors, trait impls - and theirs
+  • Each node shows the relation (extends, implements, impl, inherits) and file:line
+
+EDGES (same patterns as 'cm implements'):
+  Rust     → impl Trait for Type, trait Name: Supertrait
+  Python   → class Name(Base):
+  TS/JS    → class Name extends Parent implements Interface
+  Java     → extends / implements, interface extends
+  Go       → embedded interfaces in structs
+
+Types defined outside the project are shown as (external). A type that appears
+again on its own branch is marked as a cycle and not expanded twice.
+
+TIP: --depth 1 gives only the direct parents and children"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm hierarchy BaseHandler                  # Full tree above and below BaseHandler
+  cm hierarchy Repository ./src --depth 2   # Two levels each way, src/ only
+  cm hierarchy Shape --format human         # Box-drawing tree
+  cm hierarchy Shape --format ai            # UP:/DOWN:<depth> lines
+
+TYPICAL WORKFLOW:
+  1. cm hierarchy <Type> to see the whole family
+  2. cm implements <Type> for the flat list of direct implementors
+  3. cm inspect <file> for the class you need to change")]
+    Hierarchy {
+        /// Class, interface, or trait name
+        r#type: String,
+
+        /// Directory path to search in
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Levels to follow in each direction
+        #[arg(long, default_value_t = 10)]
+        depth: usize,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Show type information for a symbol's parameters and return type
+    #[command(
+        about = "Analyze types used in a symbol's signature and locate their definitions",
+        long_about = "USE CASE: Understand types flowing through a function/method
+  • Extract parameter types and return type from signature
+  • Locate where each custom type is defined in the codebase
+  • Support for Rust, Python, TypeScript, Go, Java, and C
+
+LANGUAGE SUPPORT:
+  Rust     → fn name(x: Type) -> RetType
+  Python   → def name(x: Type) -> RetType (type hints)
+  TS/JS    → function name(x: Type): RetType
+  Go       → func name(x Type) RetType
+  Java     → RetType name(Type x)
+  C        → RetType name(Type x)
+
+OUTPUT:
+  • Symbol signature
+  • Table of parameter names, types, and where each type is defined
+  • Return type with its definition location
+
+TIP: Useful for understanding API boundaries and type dependencies"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm types process_payment                    # Analyze types in process_payment
+  cm types authenticate --fuzzy               # Fuzzy search for symbol
+  cm types parse ./src --format human         # Pretty table output
+  cm types validate_input --format ai         # Token-efficient for LLMs
+
+TYPICAL WORKFLOW:
+  1. Find function: cm query my_func --fuzzy
+  2. Analyze types: cm types my_func
+  3. Inspect type definition: cm query TypeName to see implementation
+
+WHEN TO USE:
+  ✓ \"What types does this function accept?\"
+  ✓ \"Where is this parameter type defined?\"
+  ✓ \"What's the return type and its definition?\"")]
+    Types {
+        /// Symbol name to analyze
+        symbol: String,
+
+        /// Directory path to search in
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Enable fuzzy matching for symbol name
+        #[arg(long, default_value = "false")]
+        fuzzy: bool,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Show field structure for structs, classes, dataclasses, etc.
+    #[command(
+        about = "Display field schema for data structures (structs, classes, dataclasses, etc.)",
+        long_about = "USE CASE: Understand the field structure of data types
+  • Parse struct/class bodies to extract fields with types
+  • Works with Rust structs, Python dataclasses/Pydantic, TypeScript interfaces, Java classes, Go structs
+  • Shows field names, types, optionality, and default values
+
+LANGUAGE SUPPORT:
+  Rust       → struct fields (name: Type)
+  Python     → dataclass, TypedDict, Pydantic BaseModel fields
+  TypeScript → interface/class properties
+  Java       → class fields
+  Go         → struct fields
+
+TIP: Use --fuzzy for flexible symbol matching"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm schema User                           # Show fields of User struct/class
+  cm schema Config --fuzzy                 # Fuzzy search for Config
+  cm schema UserRequest ./src --format ai  # Token-efficient output
+  cm schema MyModel --format human         # Pretty table format
+
+TYPICAL WORKFLOW:
+  1. Find class: cm query MyClass --fuzzy
+  2. Show schema: cm schema MyClass
+  3. Understand types: cm types related_function
+
+WHEN TO USE:
+  ✓ \"What fields does this struct have?\"
+  ✓ \"What's the shape of this dataclass?\"
+  ✓ \"Which fields are optional in this model?\"")]
+    Schema {
+        /// Symbol name (struct/class/interface name)
+        symbol: String,
+
+        /// Directory path to search in
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Enable fuzzy matching for symbol name
+        #[arg(long, default_value = "false")]
+        fuzzy: bool,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [UTILITY] Save current symbol state as a named snapshot
+    #[command(
+        about = "Save a snapshot of current codebase symbols for later comparison",
+        long_about = "USE CASE: Create reference points for tracking code evolution
+  • Save current symbol state as a named reference
+  • Includes all symbols with signatures and locations
+  • Records git commit hash if in a git repository
+  • Stored in .codemapper/snapshots/<name>.json (override with --cache-dir or CODEMAPPER_CACHE_DIR)
+
+COMMON USES:
+  • Save baseline before major refactoring
+  • Track API surface changes over time
+  • Compare feature branches to main
+
+TIP: Use 'cm compare <name>' to see changes since the snapshot"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm snapshot baseline                      # Save snapshot named 'baseline'
+  cm snapshot v1.0                          # Save snapshot named 'v1.0'
+  cm snapshot pre-refactor --format human   # Save with confirmation table
+  cm snapshot --list                        # List all saved snapshots
+  cm snapshot --delete old-snap             # Delete a snapshot
+
+TYPICAL WORKFLOW:
+  1. Save baseline: cm snapshot before-changes
+  2. Make code changes
+  3. Compare: cm compare before-changes
+  4. Review added/deleted/modified symbols")]
+    Snapshot {
+        /// Snapshot name (required unless --list or --delete)
+        #[arg(required_unless_present_any = ["list", "delete"])]
+        name: Option<String>,
+
+        /// Directory path to analyze
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// List all saved snapshots
+        #[arg(long, default_value_t = false)]
+        list: bool,
+
+        /// Delete a snapshot by name
+        #[arg(long)]
+        delete: Option<String>,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+
+    /// [ANALYSIS] Compare current codebase to a saved snapshot
+    #[command(
+        about = "Show symbol-level changes between current code and a saved snapshot",
+        long_about = "USE CASE: Track code evolution since a snapshot was taken
+  • Compares current symbols against a previously saved snapshot
+  • Shows ADDED, DELETED, MODIFIED, and SIGNATURE_CHANGED symbols
+  • Like 'cm diff' but against a saved state instead of a git commit
+
+CHANGE TYPES:
+  ADDED            → New symbols that didn't exist in the snapshot
+  DELETED          → Symbols that were removed since the snapshot
+  MODIFIED         → Symbols with body/line changes (same signature)
+  SIGNATURE_CHANGED → Symbols with parameter or return type changes
+
+TIP: Save snapshots at key milestones for easy comparison"
+    )]
+    #[command(after_help = "EXAMPLES:
+  cm compare baseline                     # Compare to 'baseline' snapshot
+  cm compare v1.0 --format human          # Pretty table output
+  cm compare pre-refactor --format ai     # Token-efficient for LLMs
+  cm compare release --extensions py,rs   # Only Python and Rust files
+
+TYPICAL WORKFLOW:
+  1. Saved snapshot earlier: cm snapshot milestone
+  2. Made code changes
+  3. Review changes: cm compare milestone
+  4. See what was added/deleted/modified")]
+    Compare {
+        /// Snapshot name to compare against
+        snapshot: String,
+
+        /// Directory path to analyze
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Comma-separated file extensions to include
+        #[arg(long, default_value = "auto", env = "CM_EXTENSIONS")]
+        extensions: String,
+
+        /// Disable cache (always reindex)
+        #[arg(long, default_value_t = false, env = "CM_NO_CACHE")]
+        no_cache: bool,
+
+        /// Force rebuild cache
+        #[arg(long, default_value_t = false)]
+        rebuild_cache: bool,
+    },
+}
+
+/// Arguments read ahead of the real parse: `--profile` and the subcommand decide which
+/// config values become flag defaults, the command's path which project config applies,
+/// `--allow-plugins` which plugins and backends load
+#[derive(Default)]
+struct EarlyArgs {
+    profile: Option<String>,
+    command: Option<String>,
+    path: Option<PathBuf>,
+    allow_plugins: bool,
+}
+
+/// The project file sets a backend `--allow-plugins` would have used
+fn warn_untrusted(section: &str) {
+    eprintln!(
+        "{} Project [{}] backend not used (pass --allow-plugins to use it)",
+        "⚠".yellow(),
+        section
+    );
+}
+
+fn early_args() -> EarlyArgs {
+    match Cli::command().ignore_errors(true).try_get_matches() {
+        Ok(matches) => EarlyArgs {
+            profile: matches.get_one::<String>("profile").cloned(),
+            command: matches.subcommand_name().map(str::to_string),
+            path: matches
+                .subcommand()
+                .and_then(|(_, sub)| sub.try_get_one::<PathBuf>("path").ok().flatten().cloned()),
+            allow_plugins: matches!(matches.try_get_one::<bool>("allow_plugins"), Ok(Some(true))),
+        },
+        Err(_) => EarlyArgs::default(),
+    }
+}
+
+/// Arguments for the real parse. When the command's extensions are `auto` (the default),
+/// the parseable languages found under its path are exported as `CM_EXTENSIONS`, and an
+/// explicit `--extensions auto` is rewritten, so commands always see a concrete list.
+fn resolve_auto_extensions(cwd: &Path) -> Vec<OsString> {
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    // Help keeps showing `auto` as the default
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        return args;
+    }
+    let Ok(matches) = Cli::command()
+        .ignore_errors(true)
+        .try_get_matches_from(&args)
+    else {
+        return args;
+    };
+    let Some((command, sub)) = matches.subcommand() else {
+        return args;
+    };
+    if !matches!(sub.try_get_one::<String>("extensions"), Ok(Some(ext)) if ext == "auto") {
+        return args;
+    }
+
+    let root = match sub.try_get_one::<PathBuf>("path") {
+        Ok(Some(path)) if path.exists() => path.clone(),
+        _ => cwd.to_path_buf(),
+    };
+    let detected = indexer::detect_extensions(&root);
+    // Completion scripts run `cm complete` on every TAB; warnings would garble the prompt
+    if command != "complete" {
+        for (language, count) in &detected.unsupported {
+            eprintln!(
+                "{} Skipping {} {} file(s): {} is not supported",
+                "⚠".yellow(),
+                count,
+                language,
+                language
+            );
+        }
+    }
+
+    let list = detected.extensions.join(",");
+    std::env::set_var(config::ENV_EXTENSIONS, &list);
+    // A command-line value outranks the variable
+    for i in 1..args.len() {
+        if args[i] == "--extensions=auto" {
+            args[i] = format!("--extensions={}", list).into();
+        } else if args[i] == "auto" && args[i - 1] == "--extensions" {
+            args[i] = list.clone().into();
+        }
+    }
+    args
+}
+
+fn main() {
+    if let Err(err) = run() {
+        let code = failure::code_of(&err);
+        let format = requested_format();
+        let rendered = OutputFormatter::new(format).format_error(code, &format!("{:#}", err));
+        // A JSON error object replaces the output on stdout; other formats keep stdout clean
+        if format == OutputFormat::Json {
+            println!("{}", rendered);
+        } else {
+            eprintln!("{}", rendered);
+        }
+        exit(code.exit_code());
+    }
+    exit(0);
+}
+
+/// Save the command to the history and its result to the result cache, then exit with `code`
+fn exit(code: i32) -> ! {
+    if let Err(e) = history::finish(code) {
+        eprintln!("{} Command not saved to history: {:#}", "⚠".yellow(), e);
+    }
+    if let Err(e) = results::finish(code) {
+        eprintln!("{} Result not cached: {:#}", "⚠".yellow(), e);
+    }
+    std::process::exit(code);
+}
+
+/// `--format` read straight from the arguments, for reporting errors: the command line may
+/// not parse. The last value wins, as with clap; `CM_FORMAT` (set by a config profile too)
+/// applies when none is given.
+fn requested_format() -> OutputFormat {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut format = None;
+    for (i, arg) in args.iter().enumerate() {
+        if arg == "--" {
+            break;
+        }
+        let value = match arg.as_str() {
+            "--format" | "-f" => args.get(i + 1).cloned(),
+            _ => arg
+                .strip_prefix("--format=")
+                .or_else(|| arg.strip_prefix("-f").filter(|v| !v.is_empty()))
+                .map(|v| v.trim_start_matches('=').to_string()),
+        };
+        if value.is_some() {
+            format = value;
+        }
+    }
+    format
+        .or_else(|| std::env::var("CM_FORMAT").ok())
+        .and_then(|format| OutputFormat::from_str(&format).ok())
+        .unwrap_or(OutputFormat::Default)
+}
+
+fn run() -> Result<()> {
+    // Config files feed clap through CM_* variables, so flags and real env vars still win
+    let cwd = std::env::current_dir()?;
+    let early = early_args();
+    let config_root =
+        config::project_root(&cwd.join(early.path.as_deref().unwrap_or(Path::new("."))));
+    let config = config::load_merged(&config_root, early.allow_plugins).unwrap_or_else(|e| {
+        eprintln!("{} Config not loaded: {:#}", "⚠".yellow(), e);
+        config::ProjectConfig::default()
+    });
+    config
+        .resolve_defaults(early.profile.as_deref(), early.command.as_deref())?
+        .export_to_env();
+
+    // Before parsing, so `--type` takes custom kinds and plugin symbols get them
+    if let Err(e) = kinds::register_kinds(&config.kinds) {
+        eprintln!("{} Custom kinds not loaded: {:#}", "⚠".yellow(), e);
+    }
+    // Before parsing, so `--extensions auto` picks up plugin extensions too
+    if let Err(e) = parser::registry::register_plugins(config.plugins) {
+        eprintln!("{} Parser plugins not loaded: {:#}", "⚠".yellow(), e);
+    }
+    if !config.untrusted_plugins.is_empty() && early.command.as_deref() != Some("complete") {
+        let names: Vec<&str> = config
+            .untrusted_plugins
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        eprintln!(
+            "{} Project parser plugins not run: {} (pass --allow-plugins to run them)",
+            "⚠".yellow(),
+            names.join(", ")
+        );
+    }
+
+    let cli = match Cli::try_parse_from(resolve_auto_extensions(&cwd)) {
+        Ok(cli) => cli,
+        // Help, version, and usage errors for people print the way clap renders them
+        Err(e)
+            if !e.use_stderr()
+                || !matches!(requested_format(), OutputFormat::Json | OutputFormat::AI) =>
+        {
+            e.exit()
+        }
+        Err(e) => {
+            let rendered = e.render().to_string();
+            let message = rendered.lines().next().unwrap_or_default();
+            anyhow::bail!(Failure::new(
+                ErrorCode::InvalidArgument,
+                message.trim_start_matches("error: ")
+            ));
+        }
+    };
+
+    let format = OutputFormat::from_str(&cli.format)
+        .map_err(|err| Failure::new(ErrorCode::InvalidArgument, err))?;
+
+    let cache_dir = cli.cache_dir.as_deref();
+    // Command history and saved sets live in the working directory's `.codemapper/`
+    let base_dir = cache::CacheManager::base_dir(&cwd, cache_dir);
+    // Replays and tab completion would only push real commands out of the history
+    if !matches!(
+        cli.command,
+        Commands::Last { .. } | Commands::Complete { .. }
+    ) {
+        history::start(&base_dir, std::env::args().skip(1).collect());
+    }
+    NO_VENDOR.store(cli.no_vendor, Ordering::Relaxed);
+    if let Some(budget) = cli.timeout {
+        deadline::start(budget);
+    }
+
+    match cli.command {
+        Commands::Stats {
+            path,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            top,
+            skipped,
+        } => {
+            cmd_stats(
+                path,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                top,
+                skipped,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Map {
+            path,
+            level,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            redact,
+            redact_salt,
+            group_by,
+            depth,
+            mode,
+            limit,
+            annotated,
+            r#where,
+            since,
+            metrics,
+            emit,
+            update,
+        } => {
+            let redactor = redact
+                .then(|| redact::Redactor::new(redact_salt.as_deref()))
+                .transpose()?;
+            cmd_map(
+                path,
+                level,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                redactor,
+                group_by.map(|_| depth),
+                mode.map(|mode| (mode, limit)),
+                annotated,
+                r#where,
+                since,
+                metrics,
+                emit.map(|target| (target, update)),
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Query {
+            symbol,
+            path,
+            within,
+            exact,
+            r#type,
+            context,
+            show_body,
+            fast,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            full,
+            exports_only,
+            annotated,
+            r#where,
+            limit,
+            semantic,
+            pick,
+            expect_min,
+            no_dedupe,
+            save_as,
+        } => {
+            let cacheable = !(no_cache || rebuild_cache || pick || save_as.is_some());
+            if !semantic
+                && cacheable
+                && replay_result(&base_dir, &cwd, &path, &extensions, cache_dir)
+            {
+                return Ok(());
+            }
+            if semantic {
+                if config.untrusted_embeddings {
+                    warn_untrusted("embeddings");
+                }
+                cmd_semantic_query(
+                    symbol,
+                    path,
+                    limit,
+                    config.embeddings.clone(),
+                    format,
+                    cache_dir,
+                )?;
+                return Ok(());
+            }
+            let found = cmd_query(
+                symbol.clone(),
+                path,
+                within,
+                context,
+                !exact, // Invert: default is fuzzy, --exact disables it
+                fast,
+                show_body,
+                r#type,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                !full,
+                exports_only,
+                annotated.as_ref(),
+                r#where.as_ref(),
+                format,
+                limit,
+                pick,
+                no_dedupe,
+                save_as.as_deref().map(|name| (base_dir.as_path(), name)),
+                cache_dir,
+            )?;
+            if let Some(min) = expect_min {
+                if found < min {
+                    assertion_failed(format!(
+                        "Expected at least {} symbol(s) matching '{}', found {}",
+                        min, symbol, found
+                    ));
+                }
+            }
+        }
+        Commands::Show {
+            name,
+            context,
+            show_body,
+            delete,
+        } => {
+            cmd_show(name, &context, show_body, delete, &base_dir, format)?;
+        }
+        Commands::Extract {
+            symbol,
+            path,
+            with_imports,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_extract(
+                symbol,
+                path,
+                with_imports,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Embed {
+            path,
+            output,
+            max_chars,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            if config.untrusted_embeddings {
+                warn_untrusted("embeddings");
+            }
+            cmd_embed(
+                path,
+                output,
+                max_chars,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                config.embeddings.clone(),
+                cache_dir,
+            )?;
+        }
+        Commands::Summarize {
+            path,
+            symbols,
+            force,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            if config.untrusted_summarizer {
+                warn_untrusted("summarizer");
+            }
+            cmd_summarize(
+                path,
+                symbols,
+                force,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                config.summarizer.clone(),
+                cache_dir,
+            )?;
+        }
+        Commands::Inspect {
+            paths,
+            lang,
+            show_body,
+            full,
+            exports_only,
+            lines,
+            symbol,
+            extensions,
+            redact,
+            redact_salt,
+        } => {
+            let filter = InspectFilter {
+                skip_anonymous: !full,
+                exports_only,
+                lines,
+                symbol,
+            };
+            if redact && show_body {
+                anyhow::bail!(Failure::new(
+                    ErrorCode::InvalidArgument,
+                    "--show-body cannot be combined with --redact"
+                ));
+            }
+            let redactor = redact
+                .then(|| redact::Redactor::new(redact_salt.as_deref()))
+                .transpose()?;
+            if let [file_path] = paths.as_slice() {
+                if file_path.as_os_str() == "-" || !file_path.is_dir() {
+                    cmd_inspect(
+                        file_path.clone(),
+                        lang,
+                        show_body,
+                        &filter,
+                        redactor.as_ref(),
+                        format,
+                        cache_dir,
+                    )?;
+                    return Ok(());
+                }
+            }
+            if lang.is_some() {
+                anyhow::bail!(Failure::new(
+                    ErrorCode::InvalidArgument,
+                    "--lang only applies when inspecting a single file or stdin"
+                ));
+            }
+            if lines.is_some() {
+                anyhow::bail!(Failure::new(
+                    ErrorCode::InvalidArgument,
+                    "--lines only applies when inspecting a single file or stdin"
+                ));
+            }
+            cmd_inspect_many(
+                paths,
+                show_body,
+                &filter,
+                redactor.as_ref(),
+                extensions,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Deps {
+            target,
+            path,
+            direction,
+            depth,
+            fast,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            fail_if_any,
+        } => {
+            if !(no_cache || rebuild_cache)
+                && replay_result(&base_dir, &cwd, &path, &extensions, cache_dir)
+            {
+                return Ok(());
+            }
+            let found = cmd_deps(
+                target.clone(),
+                path,
+                direction,
+                depth,
+                fast,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+            if fail_if_any && found > 0 {
+                assertion_failed(format!("Found {} result(s) for '{}'", found, target));
+            }
+        }
+        Commands::Index { path, extensions } => {
+            cmd_index(path, extensions)?;
+        }
+        Commands::Bench {
+            path,
+            iterations,
+            output,
+            baseline,
+            fail_on_regression,
+            extensions,
+        } => {
+            cmd_bench(
+                path,
+                iterations,
+                output,
+                baseline,
+                fail_on_regression,
+                extensions,
+            )?;
+        }
+        Commands::Completions { shell } => {
+            print!("{}", completion::script(shell, &Cli::command()));
+        }
+        Commands::OutputSchema { name, list } => {
+            cmd_output_schema(name, list)?;
+        }
+        Commands::ParseDump { file, lang } => {
+            cmd_parse_dump(file, lang)?;
+        }
+        Commands::Kinds { lang } => {
+            cmd_kinds(lang, format)?;
+        }
+        Commands::Last {
+            back,
+            id,
+            list,
+            limit,
+        } => {
+            cmd_last(back, id, list, limit, &base_dir, format)?;
+        }
+        Commands::Complete {
+            kind,
+            prefix,
+            path,
+            extensions,
+        } => {
+            cmd_complete(&kind, &prefix, &path, &extensions, cache_dir);
+        }
+        Commands::Diff {
+            commit,
+            path,
+            extensions,
+            full,
+            symbol,
+            to,
+            unified,
+        } => match symbol {
+            Some(symbol) => cmd_diff_symbol(symbol, commit, to, path, extensions, unified, format)?,
+            None => cmd_diff(commit, path, extensions, !full, format)?,
+        },
+        Commands::StashCompare {
+            target,
+            path,
+            extensions,
+            full,
+        } => {
+            cmd_stash_compare(target, path, extensions, !full, format)?;
+        }
+        Commands::ChangedSymbols {
+            path,
+            between,
+            extensions,
+        } => {
+            cmd_changed_symbols(between, path, extensions, format)?;
+        }
+        Commands::Callers {
+            symbol,
+            path,
+            from_set,
+            fuzzy,
+            fast,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            limit,
+        } => {
+            // With --from-set the only positional given is the path
+            let (symbols, path) = match from_set {
+                Some(name) => (
+                    sets::load_set(&base_dir, &name)?.names(),
+                    symbol.map(PathBuf::from).unwrap_or(path),
+                ),
+                None => (symbol.into_iter().collect(), path),
+            };
+            if !(no_cache || rebuild_cache)
+                && replay_result(&base_dir, &cwd, &path, &extensions, cache_dir)
+            {
+                return Ok(());
+            }
+            cmd_callers(
+                symbols,
+                path,
+                fuzzy,
+                fast,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                limit,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Callees {
+            symbol,
+            path,
+            fuzzy,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            limit,
+        } => {
+            cmd_callees(
+                symbol,
+                path,
+                fuzzy,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                limit,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Routes {
+            path,
+            method,
+            prefix,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_routes(
+                path,
+                method,
+                prefix,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::CliMap {
+            path,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_cli_map(path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+        }
+        Commands::Envvars {
+            path,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_envvars(path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+        }
+        Commands::Sql {
+            path,
+            table,
+            kind,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_sql(
+                path,
+                table,
+                kind,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Strings {
+            pattern,
+            path,
+            regex,
+            limit,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_strings(
+                pattern,
+                path,
+                regex,
+                limit,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Flags {
+            path,
+            patterns,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_flags(
+                path,
+                patterns,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                config.flags.clone(),
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Errors {
+            symbol,
+            path,
+            direct,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_errors(
+                symbol,
+                path,
+                direct,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Logs {
+            path,
+            level,
+            grep,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_logs(
+                path,
+                level,
+                grep,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Audit {
+            path,
+            rules,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_audit(path, rules, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+        }
+        Commands::Shadows {
+            path,
+            exported_only,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_shadows(
+                path,
+                exported_only,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::ValueRefs {
+            name,
+            path,
+            code_only,
+            fast,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            limit,
+        } => {
+            cmd_value_refs(
+                name,
+                path,
+                code_only,
+                fast,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                limit,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Tests {
+            symbol,
+            path,
+            fuzzy,
+            fast,
+            list,
+            orphans,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_tests(
+                symbol,
+                path,
+                list,
+                orphans,
+                fuzzy,
+                fast,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Mocks {
+            symbol,
+            path,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_mocks(
+                symbol,
+                path,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Untested {
+            path,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            fail_under,
+            r#where,
+        } => {
+            cmd_untested(
+                path,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                fail_under,
+                r#where.as_ref(),
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Deprecated {
+            path,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            max_calls,
+        } => {
+            cmd_deprecated(
+                path,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                max_calls,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Since {
+            commit,
+            path,
+            extensions,
+            breaking,
+        } => {
+            cmd_since(commit, path, extensions, breaking, format)?;
+        }
+        Commands::Breaking {
+            path,
+            against,
+            check,
+            allowlist,
+            extensions,
+        } => {
+            cmd_breaking(path, against, check, allowlist, extensions, format)?;
+        }
+        Commands::Verify {
+            path,
+            fingerprint,
+            record,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_verify(
+                path,
+                fingerprint,
+                record,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Layers {
+            path,
+            order,
+            check,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            let layers = layers::Layers::resolve(order.as_deref(), &config.layers)?;
+            cmd_layers(
+                path,
+                &layers,
+                check,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Semver {
+            path,
+            from,
+            extensions,
+        } => {
+            cmd_semver(path, from, extensions, format)?;
+        }
+        Commands::ApiDiff {
+            path,
+            against_registry,
+            extensions,
+            skip_anonymous,
+        } => {
+            cmd_api_diff(path, against_registry, extensions, skip_anonymous, format)?;
+        }
+        Commands::Api {
+            action,
+            path,
+            lock,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_api(
+                action,
+                path,
+                lock,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Prompt {
+            path,
+            budget,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_prompt(
+                path,
+                budget,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Entrypoints {
+            path,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_entrypoints(path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+        }
+        Commands::Trace {
+            from,
+            to,
+            path,
+            fuzzy,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_trace(
+                from,
+                to,
+                path,
+                fuzzy,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Impact {
+            symbol,
+            path,
+            exact,
+            include_docs,
+            limit,
+            all,
+            emit,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            impact::cmd_impact(
+                symbol,
+                path,
+                exact,
+                include_docs,
+                limit,
+                all,
+                emit,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::TestDeps {
+            test_file,
+            path,
+            fixtures,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_test_deps(
+                test_file,
+                path,
+                fixtures,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Blame {
+            symbol,
+            file,
+            blame_file,
+            batch,
+        } => match (blame_file, batch, symbol, file) {
+            (Some(blame_file), _, _, _) => cmd_blame_file(blame_file, format)?,
+            (None, Some(batch), _, _) => cmd_blame_batch(batch, format)?,
+            (None, None, Some(symbol), Some(file)) => cmd_blame(symbol, file, format)?,
+            _ => anyhow::bail!(Failure::new(
+                ErrorCode::InvalidArgument,
+                "blame requires <SYMBOL> <FILE>, --file <FILE> or --batch <LIST>"
+            )),
+        },
+        Commands::History {
+            symbol,
+            file,
+            api_file,
+            batch,
+        } => match (api_file, batch, symbol, file) {
+            (Some(api_file), _, _, _) => cmd_history_file(api_file, format)?,
+            (None, Some(batch), _, _) => cmd_history_batch(batch, format)?,
+            (None, None, Some(symbol), Some(file)) => cmd_history(symbol, file, format)?,
+            _ => anyhow::bail!(Failure::new(
+                ErrorCode::InvalidArgument,
+                "history requires <SYMBOL> <FILE>, --file <FILE> or --batch <LIST>"
+            )),
+        },
+        Commands::Hierarchy {
+            r#type,
+            path,
+            depth,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_hierarchy(
+                r#type,
+                path,
+                depth,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Implements {
+            interface,
+            path,
+            fuzzy,
+            extensions,
+            no_cache,
+            rebuild_cache,
+            trait_only,
+        } => {
+            cmd_implements(
+                interface,
+                path,
+                fuzzy,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                trait_only,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Types {
+            symbol,
+            path,
+            fuzzy,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_types(
+                symbol,
+                path,
+                fuzzy,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Schema {
+            symbol,
+            path,
+            fuzzy,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_schema(
                 symbol,
                 path,
-                context,
-                !exact, // Invert: default is fuzzy, --exact disables it
-                fast,
-                show_body,
-                r#type,
+                fuzzy,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Snapshot {
+            name,
+            path,
+            list,
+            delete,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_snapshot(
+                name,
+                path,
+                list,
+                delete,
+                extensions,
+                no_cache,
+                rebuild_cache,
+                format,
+                cache_dir,
+            )?;
+        }
+        Commands::Compare {
+            snapshot,
+            path,
+            extensions,
+            no_cache,
+            rebuild_cache,
+        } => {
+            cmd_compare(snapshot, path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+        }
+    }
+
+    if deadline::truncated() {
+        report_timeout(format);
+    }
+
+    Ok(())
+}
+
+/// `--timeout` ran out: mark the output as partial where an agent reads it, on stderr for
+/// formats other tools parse
+fn report_timeout(format: OutputFormat) {
+    let budget = deadline::budget().unwrap_or_default();
+    match format {
+        OutputFormat::AI => println!("[TRUNCATED:timeout={:?}]", budget),
+        OutputFormat::Default | OutputFormat::Human => println!(
+            "{} TRUNCATED: stopped after --timeout {:?}, results are partial",
+            "⚠".yellow(),
+            budget
+        ),
+        OutputFormat::Compact | OutputFormat::Json => eprintln!(
+            "{} Stopped after --timeout {:?}, results are partial",
+            "⚠".yellow(),
+            budget
+        ),
+    }
+}
+
+/// Auto-rebuild wrapper: Try cache first, rebuild if needed
+/// `--no-vendor`: drop vendored files from every index once it is loaded (the cache keeps
+/// them, so toggling the flag does not reindex)
+static NO_VENDOR: AtomicBool = AtomicBool::new(false);
+
+fn try_load_or_rebuild(
+    path: &PathBuf,
+    extensions: &[&str],
+    no_cache: bool,
+    rebuild_cache: bool,
+    cache_dir: Option<&Path>,
+) -> Result<index::CodeIndex> {
+    load_or_index(path, extensions, no_cache, rebuild_cache, cache_dir, None)
+}
+
+/// `try_load_or_rebuild` for callers that already walked the tree: a fresh index is
+/// built from `files` instead of walking `path` again.
+fn load_or_index(
+    path: &Path,
+    extensions: &[&str],
+    no_cache: bool,
+    rebuild_cache: bool,
+    cache_dir: Option<&Path>,
+    files: Option<Vec<PathBuf>>,
+) -> Result<index::CodeIndex> {
+    let mut index = load_or_index_all(path, extensions, no_cache, rebuild_cache, cache_dir, files)?;
+    if NO_VENDOR.load(Ordering::Relaxed) {
+        indexer::remove_vendored(&mut index, path);
+    }
+    Ok(index)
+}
+
+/// `load_or_index` before the `--no-vendor` filter
+fn load_or_index_all(
+    path: &Path,
+    extensions: &[&str],
+    no_cache: bool,
+    rebuild_cache: bool,
+    cache_dir: Option<&Path>,
+    files: Option<Vec<PathBuf>>,
+) -> Result<index::CodeIndex> {
+    use cache::CacheManager;
+
+    let index_fresh = |progress: Option<ProgressBar>| match files {
+        Some(ref files) => indexer::index_files_with_progress(files, progress),
+        None => indexer::index_directory_with_progress(path, extensions, progress),
+    };
+
+    // Skip cache if flags set
+    if no_cache || rebuild_cache {
+        if rebuild_cache {
+            eprintln!("{} Rebuilding cache (--rebuild-cache)", "→".cyan());
+            CacheManager::invalidate(path, extensions, cache_dir).ok(); // Ignore errors
+        }
+        let start = Instant::now();
+        let index = index_fresh(None)?;
+        let elapsed_ms = start.elapsed().as_millis();
+
+        // Save to cache only if indexing took >= 300ms (unless --no-cache), and never a
+        // partial index cut short by --timeout
+        if !no_cache && elapsed_ms >= 300 && !deadline::truncated() {
+            match CacheManager::save(&index, path, extensions, cache_dir) {
+                Ok(_) => eprintln!(
+                    "{} Cached index for future use ({}ms)",
+                    "✓".green(),
+                    elapsed_ms
+                ),
+                Err(e) => eprintln!("{} Warning: Failed to save cache: {}", "⚠".yellow(), e),
+            }
+        } else if !no_cache && elapsed_ms < 300 {
+            eprintln!(
+                "{} Indexed in {}ms (no cache needed for small repos)",
+                "✓".green(),
+                elapsed_ms
+            );
+        }
+
+        return Ok(index);
+    }
+
+    // Try to load from cache
+    match CacheManager::load(path, extensions, cache_dir) {
+        Ok(Some((index, metadata, changed_files))) if changed_files.is_empty() => {
+            // Cache hit - no changes
+            eprintln!(
+                "{} Loaded from cache - {} files, {} symbols",
+                "✓".green(),
+                metadata.file_count.to_string().bold(),
+                metadata.symbol_count.to_string().bold()
+            );
+            Ok(index)
+        }
+        Ok(Some((mut index, metadata, changed_files))) => {
+            // Incremental update needed
+            eprintln!("{} Indexing, changes detected", "→".cyan());
+
+            // Create progress bar
+            let pb = ProgressBar::new(changed_files.len() as u64);
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template("{spinner:.cyan} [{bar:40.cyan/blue}] {percent}% ({pos}/{len} files)")
+                    .unwrap()
+                    .progress_chars("=>-"),
+            );
+
+            let start = Instant::now();
+
+            for change in &changed_files {
+                index.remove_file(&change.path);
+            }
+
+            use std::sync::{Arc, Mutex};
+            let pb_wrapper = Arc::new(Mutex::new(pb));
+
+            let new_file_infos: Vec<_> = changed_files
+                .par_iter()
+                .filter(|change| change.kind != FileChangeKind::Deleted)
+                .filter_map(|change| {
+                    if deadline::expired() {
+                        return None;
+                    }
+                    let path = change.path.clone();
+                    let result = match std::fs::read_to_string(&path) {
+                        Ok(content) => {
+                            let language = models::Language::from_extension(
+                                path.extension().and_then(|e| e.to_str()).unwrap_or(""),
+                            );
+
+                            match indexer::index_file(
+                                &path,
+                                &content,
+                                language,
+                                change.hash.as_deref(),
+                            ) {
+                                Ok(mut file_info) => {
+                                    indexer::record_references(&mut file_info, &content);
+                                    Some((change.clone(), file_info))
+                                }
+                                Err(e) => {
+                                    eprintln!(
+                                        "{} Warning: Failed to parse {}: {}",
+                                        "⚠".yellow(),
+                                        path.display_slash(),
+                                        e
+                                    );
+                                    None
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!(
+                                "{} Warning: Failed to read {}: {}",
+                                "⚠".yellow(),
+                                path.display_slash(),
+                                e
+                            );
+                            None
+                        }
+                    };
+
+                    if let Ok(pb) = pb_wrapper.lock() {
+                        pb.inc(1);
+                    }
+
+                    result
+                })
+                .collect();
+
+            if let Ok(pb) = pb_wrapper.lock() {
+                pb.finish_with_message("Done");
+                eprintln!(); // Add newline after progress bar
+            }
+
+            for (change, file_info) in new_file_infos {
+                index.remove_file(&change.path);
+                index.add_file(file_info);
+            }
+
+            let elapsed_ms = start.elapsed().as_millis();
+
+            // Always save updated cache for incremental updates (cache already exists),
+            // unless --timeout left changes unparsed: the next run picks them up again
+            if deadline::truncated() {
+                eprintln!("{} Cache not updated: --timeout reached", "⚠".yellow());
+                return Ok(index);
+            }
+            match CacheManager::save_with_changes(
+                &index,
+                path,
                 extensions,
+                &metadata,
+                &changed_files,
+                cache_dir,
+            ) {
+                Ok(_) => eprintln!("{} Cache updated ({}ms)", "✓".green(), elapsed_ms),
+                Err(e) => eprintln!("{} Warning: Failed to save cache: {}", "⚠".yellow(), e),
+            }
+
+            Ok(index)
+        }
+        Ok(None) => {
+            // Cache miss or invalid - index from scratch
+            eprintln!("{} Indexing...", "→".cyan());
+
+            // Create progress bar
+            let pb = ProgressBar::new(0);
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template("{spinner:.cyan} [{bar:40.cyan/blue}] {percent}% ({pos}/{len} files)")
+                    .unwrap()
+                    .progress_chars("=>-"),
+            );
+
+            let start = Instant::now();
+            let index = index_fresh(Some(pb))?;
+            let elapsed_ms = start.elapsed().as_millis();
+
+            // Save to cache only if indexing took >= 300ms and was not cut short
+            if elapsed_ms >= 300 && !deadline::truncated() {
+                match CacheManager::save(&index, path, extensions, cache_dir) {
+                    Ok(_) => eprintln!(
+                        "{} Cache not found, created new cache ({} files, {}ms)",
+                        "✓".green(),
+                        index.total_files().to_string().bold(),
+                        elapsed_ms
+                    ),
+                    Err(e) => eprintln!("{} Warning: Failed to save cache: {}", "⚠".yellow(), e),
+                }
+            } else {
+                // Small repo - simple completion message
+                eprintln!(
+                    "{} Indexed ({} files, {}ms)",
+                    "✓".green(),
+                    index.total_files().to_string().bold(),
+                    elapsed_ms
+                );
+            }
+
+            Ok(index)
+        }
+        Err(e) => {
+            // Cache error - fallback to rebuild
+            eprintln!("{} Cache error: {}. Rebuilding...", "⚠".yellow(), e);
+            let start = Instant::now();
+            let index = index_fresh(None)?;
+            let elapsed_ms = start.elapsed().as_millis();
+
+            eprintln!(
+                "{} Indexed ({} files, {}ms)",
+                "✓".green(),
+                index.total_files().to_string().bold(),
+                elapsed_ms
+            );
+
+            Ok(index)
+        }
+    }
+}
+
+fn cmd_index(path: PathBuf, extensions: String) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+
+    println!("{} Indexing directory: {}", "→".cyan(), path.display_slash());
+
+    let start = Instant::now();
+    let index = indexer::index_directory(&path, &ext_list)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    println!(
+        "{} Indexed {} files in {}ms",
+        "✓".green(),
+        index.total_files().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+    println!(
+        "{} Total symbols: {}",
+        "→".cyan(),
+        index.total_symbols().to_string().bold()
+    );
+
+    Ok(())
+}
+
+fn cmd_bench(
+    path: PathBuf,
+    iterations: usize,
+    output: Option<PathBuf>,
+    baseline: Option<PathBuf>,
+    fail_on_regression: Option<f64>,
+    extensions: String,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+
+    // Read the baseline first so a bad path fails before the benchmark runs
+    let baseline = baseline
+        .map(|p| bench::BenchReport::load(&p))
+        .transpose()?;
+
+    eprintln!(
+        "{} Benchmarking {} ({} iterations)...",
+        "→".cyan(),
+        path.display_slash(),
+        iterations.max(1)
+    );
+    let report = bench::run(&path, &ext_list, iterations)?;
+
+    let json = serde_json::to_string_pretty(&report)?;
+    if let Some(ref output) = output {
+        std::fs::write(output, format!("{}\n", json))
+            .with_context(|| format!("Failed to write {}", output.display_slash()))?;
+        eprintln!("{} Saved report to {}", "✓".green(), output.display_slash());
+    }
+    println!("{}", json);
+
+    let Some(baseline) = baseline else {
+        return Ok(());
+    };
+
+    let threshold = fail_on_regression.unwrap_or(f64::INFINITY);
+    let changes = bench::compare(&baseline, &report);
+    let regressions = changes.iter().filter(|c| c.is_regression(threshold)).count();
+
+    eprintln!(
+        "\n{} Compared with baseline ({} files then, {} now):",
+        "→".cyan(),
+        baseline.files,
+        report.files
+    );
+    for change in &changes {
+        let mark = if change.is_regression(threshold) {
+            "✗".red()
+        } else if change.change_pct > 0.0 {
+            "⚠".yellow()
+        } else {
+            "✓".green()
+        };
+        eprintln!(
+            "  {} {:<12} {:>10.3}ms → {:>10.3}ms  ({:+.1}%)",
+            mark, change.metric, change.baseline_ms, change.current_ms, change.change_pct
+        );
+    }
+
+    if regressions > 0 {
+        assertion_failed(format!(
+            "{} stage(s) regressed by more than {}%",
+            regressions, threshold
+        ));
+    }
+
+    Ok(())
+}
+
+fn cmd_output_schema(name: Option<String>, list: bool) -> Result<()> {
+    if list {
+        let schemas = output_schema::all();
+        let width = schemas.iter().map(|s| s.name.len()).max().unwrap_or(0);
+        for schema in schemas {
+            println!("{:<width$} {}", schema.name, schema.produced_by);
+        }
+        return Ok(());
+    }
+
+    let document = match name {
+        Some(name) => {
+            let schema = output_schema::find(&name).with_context(|| {
+                let names: Vec<&str> = output_schema::all().iter().map(|s| s.name).collect();
+                format!("Unknown schema '{}' (available: {})", name, names.join(", "))
+            })?;
+            output_schema::document(&schema)
+        }
+        None => output_schema::bundle(),
+    };
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
+
+fn cmd_kinds(lang: Option<String>, format: OutputFormat) -> Result<()> {
+    let language = match lang {
+        Some(lang) => match models::Language::from_name(&lang) {
+            models::Language::Unknown => anyhow::bail!(Failure::new(
+                ErrorCode::InvalidArgument,
+                format!("Unknown language '{}'", lang)
+            )),
+            language => Some(language),
+        },
+        None => None,
+    };
+
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_kinds(&kinds::catalog(language));
+    println!("{}", output.trim_end());
+    Ok(())
+}
+
+fn cmd_last(
+    back: u64,
+    id: Option<u64>,
+    list: bool,
+    limit: usize,
+    base_dir: &Path,
+    format: OutputFormat,
+) -> Result<()> {
+    if list {
+        let records: Vec<_> = history::load(base_dir)
+            .into_iter()
+            .rev()
+            .take(limit)
+            .collect();
+        let output = OutputFormatter::new(format).format_command_history(&records);
+        println!("{}", output.trim_end());
+        return Ok(());
+    }
+
+    let record = history::find(base_dir, (back - 1) as usize, id)?;
+    let output = history::output(base_dir, &record)?;
+    if record.truncated {
+        eprintln!(
+            "{} Only the first {} of {} bytes were kept",
+            "⚠".yellow(),
+            output.len(),
+            record.output_bytes
+        );
+    }
+
+    if format == OutputFormat::Json {
+        let output = serde_json::from_str::<serde_json::Value>(&output)
+            .unwrap_or(serde_json::Value::String(output));
+        let mut replay = serde_json::to_value(&record)?;
+        replay["command"] = record.command().into();
+        replay["output"] = output;
+        println!("{}", replay);
+    } else {
+        eprintln!(
+            "{} #{} {} ({}, exit {})",
+            "→".cyan(),
+            record.id,
+            record.command().bold(),
+            record.ago(),
+            record.exit_code
+        );
+        print!("{}", output);
+    }
+    Ok(())
+}
+
+fn cmd_parse_dump(file_path: PathBuf, lang: Option<String>) -> Result<()> {
+    use std::io::Read;
+
+    let from_stdin = file_path.as_os_str() == "-";
+    if from_stdin && lang.is_none() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            "Reading from stdin requires --lang (e.g. cm parse-dump - --lang python)"
+        ));
+    }
+    if !from_stdin && !file_path.is_file() {
+        let code = if file_path.exists() {
+            ErrorCode::InvalidArgument
+        } else {
+            ErrorCode::PathNotFound
+        };
+        anyhow::bail!(Failure::new(
+            code,
+            format!("Not a readable file: {}", file_path.display_slash())
+        ));
+    }
+
+    let (language, file_path) = match lang {
+        Some(ref lang) => resolve_lang(lang, file_path, from_stdin)?,
+        None => (indexer::detect_language(&file_path), file_path),
+    };
+    if language == models::Language::Unknown {
+        anyhow::bail!(Failure::new(
+            ErrorCode::UnsupportedLanguage,
+            format!("Unknown or unsupported file type: {}", file_path.display_slash())
+        ));
+    }
+
+    let content = if from_stdin {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        std::fs::read_to_string(&file_path)?
+    };
+    let dump = parse_dump::dump(&file_path, &content, language)?;
+    print!("{}", dump.to_json());
+    Ok(())
+}
+
+/// Prints nothing on any failure: a completion helper must never disturb the prompt
+fn cmd_complete(
+    kind: &str,
+    prefix: &str,
+    path: &Path,
+    extensions: &str,
+    cache_dir: Option<&Path>,
+) {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let Some(index) = cache::CacheManager::load_unchecked(path, &ext_list, cache_dir) else {
+        return;
+    };
+
+    let mut candidates = Vec::new();
+    if kind != "files" {
+        candidates.extend(completion::symbol_candidates(&index, prefix));
+    }
+    if kind != "symbols" {
+        candidates.extend(completion::file_candidates(&index, prefix));
+    }
+    for candidate in candidates {
+        println!("{}", candidate);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_map(
+    path: PathBuf,
+    level: u8,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    redactor: Option<redact::Redactor>,
+    group_depth: Option<usize>,
+    mode: Option<(String, usize)>,
+    annotated: Option<annotations::AnnotationFilter>,
+    where_filter: Option<filter_expr::Filter>,
+    since: Option<recency::Since>,
+    metrics: bool,
+    emit: Option<(PathBuf, bool)>,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    if level < 1 || level > 3 {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            "Level must be between 1 and 3"
+        ));
+    }
+    if metrics && level < 2 {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            "--metrics is shown per file: use --level 2 or 3"
+        ));
+    }
+    if emit.is_some() && format != OutputFormat::Default {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            "--emit writes the markdown map: drop --format"
+        ));
+    }
+
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+    let index = match annotated {
+        Some(ref filter) => annotations::filter_index(&index, filter),
+        None => index,
+    };
+    let index = match where_filter {
+        Some(ref filter) => index.filtered(|s| filter.matches(s)),
+        None => index,
+    };
+
+    // Summaries are prose about the code, so a redacted map leaves them out
+    let (index, root, formatter) = match redactor {
+        Some(redactor) => (
+            redactor.index(&index),
+            redactor.path(&path),
+            OutputFormatter::new(format),
+        ),
+        None => {
+            let (summary_root, summaries) =
+                summary::SummaryCache::find(&path, cache_dir).unwrap_or_default();
+            let mut formatter = OutputFormatter::new(format)
+                .with_summaries(summaries.fresh_for_index(&summary_root, &index));
+            if let Some(since) = &since {
+                formatter = formatter.with_recency(recency::since(&index, &path, since)?);
+            }
+            if metrics {
+                formatter = formatter.with_metrics(metrics::compute(&index));
+            }
+            (index, path, formatter)
+        }
+    };
+
+    match mode {
+        Some((mode, limit)) if mode == "sizes" => {
+            let tree = stats::size_tree(&index, &root);
+            println!("{}", formatter.format_size_tree(&tree, limit));
+            return Ok(());
+        }
+        Some((_, limit)) => {
+            let files = interest::files_of_interest(&index, &root, limit);
+            println!("{}", formatter.format_files_of_interest(&files));
+            return Ok(());
+        }
+        None => {}
+    }
+
+    if let Some(depth) = group_depth {
+        let rollups = stats::dir_rollups(&index, &root, depth);
+        return stream_stdout(|out| {
+            formatter.write_dir_map(out, &index, &rollups, depth)?;
+            writeln!(out)
+        });
+    }
+
+    if let Some((target, update)) = emit {
+        let mut map = Vec::new();
+        formatter.write_map(&mut map, &index, level)?;
+        let summary = emit::emit(&target, &String::from_utf8_lossy(&map), update)?;
+        let changes = if summary.fresh {
+            "full map".to_string()
+        } else {
+            format!(
+                "{} added, {} changed, {} removed",
+                summary.added.len(),
+                summary.changed.len(),
+                summary.removed.len()
+            )
+        };
+        eprintln!(
+            "{} Wrote {} ({})",
+            "✓".green(),
+            target.display_slash(),
+            changes
+        );
+        return Ok(());
+    }
+
+    stream_stdout(|out| {
+        formatter.write_map(out, &index, level)?;
+        writeln!(out)
+    })
+}
+
+/// Print the stored result of this same command line when no file indexed under `path` has
+/// changed since it was stored, and return `true`; otherwise start recording this run's
+/// output for the result cache. A query gated by `--expect-min` or `--fail-if-any` is only
+/// stored when it passed, so a replay never hides a failure.
+fn replay_result(
+    base_dir: &Path,
+    cwd: &Path,
+    path: &Path,
+    extensions: &str,
+    cache_dir: Option<&Path>,
+) -> bool {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let Some(generation) = cache::CacheManager::generation(path, &ext_list, cache_dir) else {
+        return false;
+    };
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let key = results::key(&args, cwd, extensions, &generation);
+    match results::lookup(base_dir, &key) {
+        Some(output) => {
+            eprintln!(
+                "{} Index unchanged, replaying the stored result",
+                "✓".green()
+            );
+            print!("{}", String::from_utf8_lossy(&output));
+            true
+        }
+        None => {
+            results::start(base_dir, &key);
+            false
+        }
+    }
+}
+
+/// Report a failed CI assertion and exit with status 3 (`ASSERTION_FAILED`). The results are
+/// already on stdout, so the message goes to stderr in every format
+fn assertion_failed(message: impl std::fmt::Display) -> ! {
+    eprintln!("{} {}", "✗".red(), message);
+    exit(ErrorCode::AssertionFailed.exit_code());
+}
+
+/// Write command output to stdout as it is produced; a closed pipe (`cm map | head`)
+/// ends the output quietly
+fn stream_stdout(
+    write: impl FnOnce(&mut BufWriter<history::Tee<io::StdoutLock<'static>>>) -> io::Result<()>,
+) -> Result<()> {
+    let mut out = BufWriter::new(history::Tee(io::stdout().lock()));
+    match write(&mut out).and_then(|_| out.flush()) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+fn cmd_query(
+    symbol: String,
+    path: PathBuf,
+    within: Option<PathBuf>,
+    context: String,
+    fuzzy: bool,
+    fast: bool,
+    show_body: bool,
+    symbol_type_filter: Option<String>,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    skip_anonymous: bool,
+    exports_only: bool,
+    annotated: Option<&annotations::AnnotationFilter>,
+    where_filter: Option<&filter_expr::Filter>,
+    format: OutputFormat,
+    limit: Option<usize>,
+    pick: bool,
+    no_dedupe: bool,
+    save_as: Option<(&Path, &str)>,
+    cache_dir: Option<&Path>,
+) -> Result<usize> {
+    use fast_search::GrepFilter;
+    use models::SymbolType;
+
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+
+    // Check if symbol is a plural form of a symbol type (e.g., "functions", "classes")
+    let (symbol, symbol_type_filter) = if symbol_type_filter.is_none() {
+        if let Some(plural_type) = SymbolType::from_plural(&symbol) {
+            // Convert plural form to empty symbol + type filter
+            (String::new(), Some(plural_type.as_str().to_string()))
+        } else {
+            (symbol, symbol_type_filter)
+        }
+    } else {
+        (symbol, symbol_type_filter)
+    };
+
+    // Parse symbol type filter if provided
+    let type_filter = if let Some(ref type_str) = symbol_type_filter {
+        match SymbolType::from_str(type_str) {
+            Some(t) => Some(t),
+            None => {
+                let valid: Vec<&str> = SymbolType::BUILTIN
+                    .iter()
+                    .copied()
+                    .chain(kinds::custom_kinds())
+                    .map(|t| t.as_str())
+                    .collect();
+                anyhow::bail!(Failure::new(
+                    ErrorCode::InvalidArgument,
+                    format!(
+                        "Invalid symbol type '{}', valid types: {}",
+                        type_str,
+                        valid.join(", ")
+                    )
+                ));
+            }
+        }
+    } else {
+        None
+    };
+
+    // Validate context level
+    let context_lower = context.to_lowercase();
+    if context_lower != "minimal" && context_lower != "full" {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!("Invalid context '{}'. Valid options: minimal, full", context)
+        ));
+    }
+
+    // `--in` narrows the results, not the index: the cache for PATH is reused as is
+    let within = match within {
+        Some(ref scope) => Some(query_scope(&path, scope)?),
+        None => None,
+    };
+    let in_scope = |file: &Path| {
+        within
+            .as_ref()
+            .is_none_or(|scope| index::path_within(file, scope))
+    };
+
+    // `parse_*` / `*Handler`: anchored wildcard match, case-sensitive only with --exact
+    let name_pattern = index::NamePattern::parse(&symbol, !fuzzy);
+
+    // Check if user wants all symbols of a specific type or annotation (empty symbol name with
+    // a filter)
+    let search_all = symbol.trim().is_empty()
+        && (type_filter.is_some() || annotated.is_some() || where_filter.is_some());
+
+    // Size up the repo from the cache metadata when there is a cache; otherwise walk once
+    // and hand the file list to whichever path runs next
+    let cached_count = if no_cache || rebuild_cache {
+        None
+    } else {
+        cache::CacheManager::cached_file_count(&path, &ext_list, cache_dir)
+    };
+    let discovered = match cached_count {
+        Some(_) => None,
+        None => Some(indexer::discover_files(&path, &ext_list)),
+    };
+    let file_count = cached_count
+        .or_else(|| discovered.as_ref().map(|files| files.len()))
+        .unwrap_or(0);
+
+    // Auto-enable fast mode for large codebases (1000+ files), but not when searching for all symbols
+    let use_fast_mode = !search_all && (fast || file_count >= FAST_MODE_THRESHOLD);
+
+    if use_fast_mode {
+        if fast {
+            eprintln!(
+                "{} Fast mode enabled by --fast flag ({} files)",
+                "→".cyan(),
+                file_count
+            );
+        } else {
+            eprintln!(
+                "{} Fast mode auto-enabled ({} files detected)",
+                "→".cyan(),
+                file_count
+            );
+        }
+
+        // Stage 1: Ripgrep prefilter
+        let extensions_vec: Vec<String> = ext_list.iter().map(|s| s.to_string()).collect();
+        let filter = match name_pattern {
+            Some(ref pattern) => GrepFilter::for_name_pattern(pattern.clone(), extensions_vec),
+            None => GrepFilter::new(&symbol, !fuzzy, extensions_vec),
+        };
+
+        let mut candidates = match discovered {
+            Some(ref files) => filter.prefilter_files(files)?,
+            None => filter.prefilter(&path)?,
+        };
+        candidates.retain(|file| in_scope(file));
+
+        if candidates.is_empty() {
+            eprintln!(
+                "{} No text matches found, falling back to full AST scan",
+                "→".yellow()
+            );
+            // Fallback: Use normal mode with cache
+            let index = load_or_index(
+                &path,
+                &ext_list,
                 no_cache,
                 rebuild_cache,
-                !full,
-                exports_only,
-                format,
-                limit,
                 cache_dir,
+                discovered,
+            )?;
+            let mut symbols = if search_all {
+                index.all_symbols()
+            } else if let Some(ref pattern) = name_pattern {
+                index.glob_search(pattern)
+            } else if fuzzy {
+                index.fuzzy_search(&symbol)
+            } else {
+                index.query_symbol(&symbol)
+            };
+
+            // Keep only symbols under --in
+            symbols.retain(|s| in_scope(&s.file_path));
+
+            // Apply type filter if specified
+            if let Some(filter_type) = type_filter {
+                symbols.retain(|s| s.symbol_type == filter_type);
+            }
+
+            // Filter anonymous if requested
+            if skip_anonymous {
+                symbols.retain(|s| s.name != "anonymous");
+            }
+
+            // Filter to exports only if requested
+            if exports_only {
+                symbols.retain(|s| s.is_exported);
+            }
+
+            if let Some(annotated) = annotated {
+                symbols.retain(|s| annotated.matches(s));
+            }
+
+            if let Some(filter) = where_filter {
+                symbols.retain(|s| filter.matches(s));
+            }
+
+            let (mut symbols, alternates) = dedupe_results(symbols, no_dedupe);
+
+            // Apply limit if specified
+            if let Some(n) = limit {
+                symbols.truncate(n);
+            }
+
+            if symbols.is_empty() {
+                println!(
+                    "{} No symbols found matching '{}'",
+                    "✗".red(),
+                    symbol.bold()
+                );
+                return Ok(0);
+            }
+
+            let show_context = context.to_lowercase() == "full";
+            let formatter = OutputFormatter::new(format).with_alternates(alternates);
+            let found = symbols.len();
+            print_query_results(&formatter, symbols, show_context, show_body, pick, save_as)?;
+            Ok(found)
+        } else {
+            eprintln!(
+                "{} Found {} candidate files, validating with AST...",
+                "→".cyan(),
+                candidates.len()
+            );
+
+            // Stage 2: AST validation
+            let mut owned_symbols = filter.validate(candidates, &symbol, fuzzy)?;
+
+            // Keep only symbols under --in
+            owned_symbols.retain(|s| in_scope(&s.file_path));
+
+            // Apply type filter if specified
+            if let Some(filter_type) = type_filter {
+                owned_symbols.retain(|s| s.symbol_type == filter_type);
+            }
+
+            // Filter anonymous if requested
+            if skip_anonymous {
+                owned_symbols.retain(|s| s.name != "anonymous");
+            }
+
+            // Filter to exports only if requested
+            if exports_only {
+                owned_symbols.retain(|s| s.is_exported);
+            }
+
+            if let Some(annotated) = annotated {
+                owned_symbols.retain(|s| annotated.matches(s));
+            }
+
+            if let Some(filter) = where_filter {
+                owned_symbols.retain(|s| filter.matches(s));
+            }
+
+            // Convert owned symbols to references for formatter
+            let symbol_refs: Vec<&Symbol> = owned_symbols.iter().collect();
+            let (mut symbol_refs, alternates) = dedupe_results(symbol_refs, no_dedupe);
+
+            // Apply limit if specified
+            if let Some(n) = limit {
+                symbol_refs.truncate(n);
+            }
+
+            if symbol_refs.is_empty() {
+                println!(
+                    "{} No symbols found matching '{}'",
+                    "✗".red(),
+                    symbol.bold()
+                );
+                return Ok(0);
+            }
+
+            let show_context = context.to_lowercase() == "full";
+            let formatter = OutputFormatter::new(format).with_alternates(alternates);
+            let found = symbol_refs.len();
+            print_query_results(
+                &formatter,
+                symbol_refs,
+                show_context,
+                show_body,
+                pick,
+                save_as,
             )?;
+            Ok(found)
+        }
+    } else {
+        // Normal mode for small codebases with cache
+        let index = load_or_index(
+            &path,
+            &ext_list,
+            no_cache,
+            rebuild_cache,
+            cache_dir,
+            discovered,
+        )?;
+        let mut symbols = if search_all {
+            // Get all symbols when searching for all of a specific type
+            index.all_symbols()
+        } else if let Some(ref pattern) = name_pattern {
+            index.glob_search(pattern)
+        } else if fuzzy {
+            index.fuzzy_search(&symbol)
+        } else {
+            index.query_symbol(&symbol)
+        };
+
+        // Keep only symbols under --in
+        symbols.retain(|s| in_scope(&s.file_path));
+
+        // Apply type filter if specified
+        if let Some(filter_type) = type_filter {
+            symbols.retain(|s| s.symbol_type == filter_type);
         }
-        Commands::Inspect {
-            file_path,
-            show_body,
-            full,
-            exports_only,
-        } => {
-            cmd_inspect(file_path, show_body, !full, exports_only, format)?;
+
+        // Filter anonymous if requested
+        if skip_anonymous {
+            symbols.retain(|s| s.name != "anonymous");
         }
-        Commands::Deps {
-            target,
-            path,
-            direction,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_deps(
-                target,
-                path,
-                direction,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                format,
-                cache_dir,
-            )?;
+
+        // Filter to exports only if requested
+        if exports_only {
+            symbols.retain(|s| s.is_exported);
         }
-        Commands::Index { path, extensions } => {
-            cmd_index(path, extensions)?;
+
+        if let Some(annotated) = annotated {
+            symbols.retain(|s| annotated.matches(s));
         }
-        Commands::Diff {
-            commit,
-            path,
-            extensions,
-            full,
-        } => {
-            cmd_diff(commit, path, extensions, !full, format)?;
+
+        if let Some(filter) = where_filter {
+            symbols.retain(|s| filter.matches(s));
         }
-        Commands::Callers {
-            symbol,
-            path,
-            fuzzy,
-            extensions,
-            no_cache,
-            rebuild_cache,
-            limit,
-        } => {
-            cmd_callers(
-                symbol,
-                path,
-                fuzzy,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                limit,
-                format,
-                cache_dir,
-            )?;
+
+        let (mut symbols, alternates) = dedupe_results(symbols, no_dedupe);
+
+        // Apply limit if specified
+        if let Some(n) = limit {
+            symbols.truncate(n);
         }
-        Commands::Callees {
-            symbol,
-            path,
-            fuzzy,
-            extensions,
-            no_cache,
-            rebuild_cache,
-            limit,
-        } => {
-            cmd_callees(
-                symbol,
-                path,
-                fuzzy,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                limit,
-                format,
-                cache_dir,
-            )?;
+
+        if symbols.is_empty() {
+            println!(
+                "{} No symbols found matching '{}'",
+                "✗".red(),
+                symbol.bold()
+            );
+            return Ok(0);
+        }
+
+        let show_context = context.to_lowercase() == "full";
+        let formatter = OutputFormatter::new(format).with_alternates(alternates);
+        let found = symbols.len();
+        print_query_results(&formatter, symbols, show_context, show_body, pick, save_as)?;
+        Ok(found)
+    }
+}
+
+/// `--in` as the index stores paths: `scope` (relative to the working directory) re-rooted
+/// under `root`, so `cm query x --in src/api` matches `./src/api/...`
+fn query_scope(root: &Path, scope: &Path) -> Result<PathBuf> {
+    let canonical_root = paths::canonicalize(root)
+        .with_context(|| format!("Cannot access {}", root.display_slash()))?;
+    let canonical_scope = paths::canonicalize(scope)
+        .with_context(|| format!("Invalid --in path {}", scope.display_slash()))?;
+    match canonical_scope.strip_prefix(&canonical_root) {
+        Ok(relative) => Ok(root.join(relative)),
+        Err(_) => anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "--in {} is outside the searched path {}",
+                scope.display_slash(),
+                root.display_slash()
+            )
+        )),
+    }
+}
+
+/// Query results with duplicates collapsed (see `dedupe`), or untouched under `--no-dedupe`
+fn dedupe_results(symbols: Vec<&Symbol>, no_dedupe: bool) -> (Vec<&Symbol>, dedupe::Alternates) {
+    if no_dedupe {
+        (symbols, dedupe::Alternates::new())
+    } else {
+        dedupe::dedupe(symbols)
+    }
+}
+
+/// Print query results; with `pick`, only the one chosen in the fuzzy picker
+/// (a single result is taken without asking)
+fn print_query_results(
+    formatter: &OutputFormatter,
+    symbols: Vec<&Symbol>,
+    show_context: bool,
+    show_body: bool,
+    pick: bool,
+    save_as: Option<(&Path, &str)>,
+) -> Result<()> {
+    let symbols = if pick && symbols.len() > 1 {
+        let items: Vec<String> = symbols
+            .iter()
+            .map(|s| {
+                format!(
+                    "{}  {}  {}:{}",
+                    s.name,
+                    s.symbol_type.as_str(),
+                    s.file_path.display_slash(),
+                    s.line_start
+                )
+            })
+            .collect();
+        match picker::pick(&items, "")? {
+            Some(chosen) => vec![symbols[chosen]],
+            None => return Ok(()),
+        }
+    } else {
+        symbols
+    };
+
+    if let Some((base_dir, name)) = save_as {
+        let command = std::iter::once("cm".to_string())
+            .chain(std::env::args().skip(1))
+            .collect::<Vec<_>>()
+            .join(" ");
+        sets::save_set(base_dir, name, command, &symbols)?;
+        eprintln!(
+            "{} Saved {} symbol(s) as '{}'",
+            "✓".green(),
+            symbols.len().to_string().bold(),
+            name
+        );
+    }
+
+    stream_stdout(|out| {
+        formatter.write_query(out, symbols, show_context, show_body)?;
+        writeln!(out)
+    })
+}
+
+fn cmd_show(
+    name: Option<String>,
+    context: &str,
+    show_body: bool,
+    delete: bool,
+    base_dir: &Path,
+    format: OutputFormat,
+) -> Result<()> {
+    let formatter = OutputFormatter::new(format);
+    let Some(name) = name else {
+        let output = formatter.format_sets(&sets::list_sets(base_dir)?);
+        println!("{}", output.trim_end());
+        return Ok(());
+    };
+    if delete {
+        sets::delete_set(base_dir, &name)?;
+        eprintln!("{} Deleted set '{}'", "✓".green(), name);
+        return Ok(());
+    }
+
+    let set = sets::load_set(base_dir, &name)?;
+    eprintln!(
+        "{} Set '{}': {} symbol(s) from {} ({})",
+        "→".cyan(),
+        name,
+        set.symbols.len().to_string().bold(),
+        set.command.bold(),
+        history::ago(set.saved)
+    );
+    let changed = set.changed_files();
+    if !changed.is_empty() {
+        eprintln!(
+            "{} {} file(s) changed since the set was saved, lines may have moved: {}",
+            "⚠".yellow(),
+            changed.len(),
+            changed
+                .iter()
+                .map(|path| path.display_slash().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let show_context = context.eq_ignore_ascii_case("full");
+    stream_stdout(|out| {
+        formatter.write_query(out, set.symbols.iter().collect(), show_context, show_body)?;
+        writeln!(out)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_extract(
+    symbol_name: String,
+    path: PathBuf,
+    with_imports: bool,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    // A file narrows the search to its own definitions
+    let (root, file) = if path.is_file() {
+        let parent = path.parent().filter(|p| !p.as_os_str().is_empty());
+        let file = paths::canonicalize(&path)?;
+        (parent.unwrap_or(Path::new(".")).to_path_buf(), Some(file))
+    } else {
+        (path, None)
+    };
+    let index = try_load_or_rebuild(&root, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    let symbols: Vec<&Symbol> = index
+        .query_symbol(&symbol_name)
+        .into_iter()
+        .filter(|s| {
+            file.as_ref()
+                .is_none_or(|f| paths::canonicalize(&s.file_path).is_ok_and(|p| p == *f))
+        })
+        .collect();
+    if symbols.is_empty() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("Symbol '{}' not found", symbol_name)
+        ));
+    }
+    if symbols.len() > 1 {
+        eprintln!(
+            "{} {} definitions of '{}'; pass a file path to pick one",
+            "⚠".yellow(),
+            symbols.len(),
+            symbol_name
+        );
+    }
+
+    let snippets = symbols
+        .into_iter()
+        .map(|symbol| extract::extract(&index, symbol, with_imports))
+        .collect::<Result<Vec<_>>>()?;
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_snippets(&snippets).trim_end());
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_deps(
+    target: String,
+    path: PathBuf,
+    direction: String,
+    depth: usize,
+    fast: bool,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<usize> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    // Detect if target is a file path or symbol name
+    let target_path = Path::new(&target);
+    let is_file = target_path.exists() || target.contains('/') || target.contains('\\');
+
+    if is_file {
+        // Original file-based dependency tracking
+        cmd_deps_file(target, index, direction, depth, format)
+    } else {
+        // New symbol-based usage tracking
+        cmd_deps_symbol(target, index, direction, fast, format)
+    }
+}
+
+fn cmd_deps_file(
+    target: String,
+    index: index::CodeIndex,
+    direction: String,
+    depth: usize,
+    format: OutputFormat,
+) -> Result<usize> {
+    use std::path::PathBuf;
+
+    let target_path = PathBuf::from(&target);
+
+    let usages = if direction.to_lowercase() == "imports" {
+        let Some(file) = dep_tree::find_file(&index, &target_path) else {
+            println!("{} No dependencies found for {}", "✗".yellow(), target);
+            return Ok(0);
+        };
+        let tree = dep_tree::dependency_tree(&index, &file, depth);
+        if tree.is_empty() {
+            println!("{} No dependencies found for {}", "✗".yellow(), target);
+            return Ok(0);
+        }
+
+        let formatter = OutputFormatter::new(format);
+        println!("{}", formatter.format_dep_tree(&target, &tree, depth));
+        return Ok(tree.len());
+    } else if direction.to_lowercase() == "used-by" {
+        // Matched on resolved imports, not on path text, so `./a.py`, `a.py` and an absolute
+        // path (any case on Windows) all find the same importers. Each is a parsed import
+        // statement naming this file.
+        match dep_tree::find_file(&index, &target_path) {
+            Some(file) => dep_tree::importing_files(&index, &file)
+                .iter()
+                .map(|path| (path.display_slash().to_string(), Confidence::ExactAst))
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        }
+    } else {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!("Invalid direction '{}', use 'imports' or 'used-by'", direction)
+        ));
+    };
+
+    if usages.is_empty() {
+        println!("{} No dependencies found for {}", "✗".yellow(), target);
+        return Ok(0);
+    }
+
+    let found = usages.len();
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_used_by(&target, &usages);
+
+    println!("{}", output);
+
+    Ok(found)
+}
+
+fn cmd_deps_symbol(
+    symbol_name: String,
+    index: index::CodeIndex,
+    direction: String,
+    fast: bool,
+    format: OutputFormat,
+) -> Result<usize> {
+    use std::fs;
+
+    let direction = direction.to_lowercase();
+    if direction != "used-by" && direction != "imports" {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!("Invalid direction '{}'. Use 'imports' or 'used-by'", direction)
+        ));
+    }
+
+    // Find the symbol definition
+    let symbols = index.query_symbol(&symbol_name);
+
+    if symbols.is_empty() {
+        println!(
+            "{} Symbol '{}' not found in codebase",
+            "✗".yellow(),
+            symbol_name.bold()
+        );
+        return Ok(0);
+    }
+
+    if direction == "imports" {
+        // What the symbol's body calls, names and imports, across every definition
+        let mut deps: Vec<String> = Vec::new();
+        for symbol in &symbols {
+            for dep in symbol_deps::symbol_dependencies(&index, symbol)? {
+                let entry = format!("{}: {} ({})", dep.kind.as_str(), dep.name, dep.target);
+                if !deps.contains(&entry) {
+                    deps.push(entry);
+                }
+            }
+        }
+
+        if deps.is_empty() {
+            println!(
+                "{} No dependencies found for symbol '{}'",
+                "✗".yellow(),
+                symbol_name.bold()
+            );
+            return Ok(0);
+        }
+
+        println!(
+            "{} Found {} dependencies of '{}'\n",
+            "✓".green(),
+            deps.len().to_string().bold(),
+            symbol_name.bold()
+        );
+
+        let found = deps.len();
+        let formatter = OutputFormatter::new(format);
+        println!("{}", formatter.format_deps(&symbol_name, deps, "imports"));
+        return Ok(found);
+    }
+
+    // Every line naming the symbol, graded by whether the syntax tree sees a call there
+    let mut usages: Vec<(String, Confidence)> = Vec::new();
+    let candidates = prefilter_indexed_files(&index, &symbol_name, fast)?;
+
+    for file in index.files() {
+        if candidates.as_ref().is_some_and(|c| !c.contains(&file.path)) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&file.path) else {
+            continue;
+        };
+        if !content.contains(&symbol_name) {
+            continue;
+        }
+        let call_lines = callgraph::lines_calling(&index, file, &content, &symbol_name)?;
+
+        for (line_num, line) in content.lines().enumerate() {
+            if line.contains(&symbol_name) {
+                // Skip the definition itself
+                let is_definition = symbols.iter().any(|s| {
+                    *s.file_path == *file.path
+                        && (line_num + 1) >= s.line_start
+                        && (line_num + 1) <= s.line_end
+                });
+
+                if !is_definition {
+                    let confidence = if call_lines.contains(&(line_num + 1)) {
+                        Confidence::of_ast_match(&symbol_name, &symbol_name, symbols.len())
+                    } else {
+                        Confidence::of_text_match(line, &symbol_name)
+                    };
+                    usages.push((
+                        format!("{}:{}", file.path.display_slash(), line_num + 1),
+                        confidence,
+                    ));
+                }
+            }
         }
-        Commands::Tests {
-            symbol,
-            path,
-            fuzzy,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_tests(
-                symbol,
-                path,
-                fuzzy,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                format,
-                cache_dir,
-            )?;
+    }
+
+    if usages.is_empty() {
+        println!(
+            "{} No usages found for symbol '{}'",
+            "✗".yellow(),
+            symbol_name.bold()
+        );
+        return Ok(0);
+    }
+
+    // Show summary first
+    println!(
+        "{} Found {} usage(s) of '{}'\n",
+        "✓".green(),
+        usages.len().to_string().bold(),
+        symbol_name.bold()
+    );
+
+    let found = usages.len();
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_used_by(&symbol_name, &usages);
+
+    println!("{}", output);
+
+    Ok(found)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_stats(
+    path: PathBuf,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    top: Option<usize>,
+    list_skipped: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+
+    let mut index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+    let skipped = indexer::skipped_files(&path, &ext_list, &index);
+    // First-party numbers only; vendored code gets its own section
+    let vendored = indexer::remove_vendored(&mut index, &path);
+
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_stats(&index, &skipped, &vendored);
+
+    println!("{}", output);
+
+    if list_skipped {
+        println!("{}", formatter.format_skipped(&skipped));
+    }
+
+    if let Some(limit) = top {
+        let report = stats::TopReport::compute(&index, limit);
+        println!("{}", formatter.format_top(&report));
+    }
+
+    Ok(())
+}
+
+/// Which of a file's symbols `cm inspect` shows
+struct InspectFilter {
+    skip_anonymous: bool,
+    exports_only: bool,
+    lines: Option<(usize, usize)>,
+    symbol: Option<String>,
+}
+
+impl InspectFilter {
+    fn apply<'a>(&self, symbols: Vec<&'a models::Symbol>) -> Vec<&'a models::Symbol> {
+        let mut symbols: Vec<&models::Symbol> = symbols
+            .into_iter()
+            .filter(|s| !self.skip_anonymous || s.name != "anonymous")
+            .filter(|s| !self.exports_only || s.is_exported)
+            .collect();
+        if let Some(range) = self.lines {
+            symbols = index::symbols_in_lines(symbols, range);
         }
-        Commands::Untested {
-            path,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_untested(path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+        if let Some(ref name) = self.symbol {
+            symbols = index::symbol_with_children(symbols, name);
         }
-        Commands::Since {
-            commit,
-            path,
-            extensions,
-            breaking,
-        } => {
-            cmd_since(commit, path, extensions, breaking, format)?;
+        symbols
+    }
+}
+
+fn cmd_inspect(
+    file_path: PathBuf,
+    lang: Option<String>,
+    show_body: bool,
+    filter: &InspectFilter,
+    redactor: Option<&redact::Redactor>,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    use std::fs;
+    use std::io::Read;
+
+    let from_stdin = file_path.as_os_str() == "-";
+
+    if from_stdin && lang.is_none() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            "Reading from stdin requires --lang (e.g. cm inspect - --lang python)"
+        ));
+    }
+
+    if !from_stdin && !file_path.exists() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::PathNotFound,
+            format!("File does not exist: {}", file_path.display_slash())
+        ));
+    }
+
+    if !from_stdin && !file_path.is_file() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!("Path is not a file: {}", file_path.display_slash())
+        ));
+    }
+
+    let (language, file_path) = match lang {
+        Some(ref lang) => resolve_lang(lang, file_path, from_stdin)?,
+        None => (indexer::detect_language(&file_path), file_path),
+    };
+    if language == models::Language::Unknown {
+        anyhow::bail!(Failure::new(
+            ErrorCode::UnsupportedLanguage,
+            format!("Unknown or unsupported file type: {}", file_path.display_slash())
+        ));
+    }
+
+    let content = if from_stdin {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        fs::read_to_string(&file_path)?
+    };
+    let start = Instant::now();
+    let file_info = indexer::index_file(&file_path, &content, language, None)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    let symbol_refs = filter.apply(file_info.symbols.iter().collect());
+    if symbol_refs.is_empty() {
+        println!(
+            "{} No symbols found in {}",
+            "✗".yellow(),
+            file_path.display_slash()
+        );
+        return Ok(());
+    }
+
+    let (summary_root, summaries) =
+        summary::SummaryCache::find(&file_path, cache_dir).unwrap_or_default();
+    let summaries = summaries.fresh_for_file(&summary_root, &file_info, &content);
+    print_inspected_file(
+        summaries,
+        &file_path,
+        language,
+        file_info.size,
+        symbol_refs,
+        show_body,
+        redactor,
+        format,
+    )?;
+    if format == OutputFormat::Compact {
+        return Ok(());
+    }
+
+    println!(
+        "\n{} Parse time: {}ms",
+        "→".cyan(),
+        elapsed_ms.to_string().bold()
+    );
+
+    Ok(())
+}
+
+/// One file's block of `cm inspect` output
+#[allow(clippy::too_many_arguments)]
+fn print_inspected_file(
+    summaries: std::collections::HashMap<String, String>,
+    file_path: &Path,
+    language: models::Language,
+    size: u64,
+    symbols: Vec<&models::Symbol>,
+    show_body: bool,
+    redactor: Option<&redact::Redactor>,
+    format: OutputFormat,
+) -> Result<()> {
+    // Redacted output shows pseudonymized paths and symbols, and no summaries
+    let redacted: Option<(PathBuf, Vec<models::Symbol>)> = redactor.map(|redactor| {
+        let path = redactor.path(file_path);
+        let symbols = symbols.iter().map(|s| redactor.symbol(s, &path)).collect();
+        (path, symbols)
+    });
+    let (file_path, symbols, summaries) = match redacted {
+        Some((ref path, ref redacted_symbols)) => (
+            path.as_path(),
+            redacted_symbols.iter().collect(),
+            Default::default(),
+        ),
+        None => (file_path, symbols, summaries),
+    };
+
+    let file_summary = summaries.get(&summary::file_key(file_path)).cloned();
+    let formatter = OutputFormatter::new(format).with_summaries(summaries.clone());
+
+    match format {
+        OutputFormat::Compact => {
+            stream_stdout(|out| formatter.write_query(out, symbols, false, false))?;
         }
-        Commands::Entrypoints {
-            path,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_entrypoints(path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+        OutputFormat::AI => {
+            println!("[FILE:{}]", file_path.display_slash());
+            println!(
+                "LANG:{} SIZE:{} SYMS:{}",
+                language.as_str(),
+                size,
+                symbols.len()
+            );
+            if let Some(ref summary) = file_summary {
+                println!("SUM:{}", summary);
+            }
+            for symbol in &symbols {
+                print!(
+                    "{}|{}|{}-{}",
+                    symbol.name,
+                    symbol.symbol_type.as_str().chars().next().unwrap(),
+                    symbol.line_start,
+                    symbol.line_end
+                );
+                if let Some(ref sig) = symbol.signature {
+                    print!("|sig:{}", sig);
+                }
+                if let Some(summary) = summaries.get(&summary::symbol_key(symbol)) {
+                    print!("|sum:{}", summary);
+                }
+                println!();
+            }
         }
-        Commands::Trace {
-            from,
-            to,
-            path,
-            fuzzy,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_trace(
-                from,
-                to,
-                path,
-                fuzzy,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                format,
-                cache_dir,
-            )?;
+        _ => {
+            println!(
+                "{} Inspecting: {}\n",
+                "→".cyan(),
+                file_path.display_slash().to_string().bold()
+            );
+            println!("Language: {}", language.as_str());
+            println!("Size: {} bytes", size);
+            if let Some(ref summary) = file_summary {
+                println!("Summary: {}", summary);
+            }
+            println!("Symbols: {}\n", symbols.len());
+
+            stream_stdout(|out| {
+                formatter.write_query(out, symbols, false, show_body)?;
+                writeln!(out)
+            })?;
         }
-        Commands::Impact {
-            symbol,
-            path,
-            exact,
-            include_docs,
-            limit,
-            all,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            impact::cmd_impact(
-                symbol,
-                path,
-                exact,
-                include_docs,
-                limit,
-                all,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                format,
-                cache_dir,
-            )?;
+    }
+
+    Ok(())
+}
+
+/// `cm inspect` over several files and/or directories. Only the named files are parsed, so
+/// inspecting a few files never indexes the tree around them; returns how many were shown.
+fn cmd_inspect_many(
+    paths: Vec<PathBuf>,
+    show_body: bool,
+    filter: &InspectFilter,
+    redactor: Option<&redact::Redactor>,
+    extensions: String,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<usize> {
+    use std::collections::HashMap;
+
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in &paths {
+        if path.is_dir() {
+            files.extend(indexer::discover_files(path, &ext_list));
+        } else if path.is_file() {
+            files.push(path.clone());
+        } else {
+            anyhow::bail!(Failure::new(
+                ErrorCode::PathNotFound,
+                format!("File does not exist: {}", path.display_slash())
+            ));
         }
-        Commands::TestDeps {
-            test_file,
-            path,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_test_deps(test_file, path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+    }
+    let mut seen = HashSet::new();
+    files.retain(|f| seen.insert(paths::canonicalize(f).unwrap_or_else(|_| f.clone())));
+
+    if files.is_empty() {
+        println!("{} No files to inspect", "✗".yellow());
+        return Ok(0);
+    }
+
+    let index = indexer::index_files_with_progress(&files, None)?;
+    let indexed: HashMap<&Path, &models::FileInfo> =
+        index.files().map(|f| (f.path.as_path(), f)).collect();
+
+    let (summary_root, summary_cache) =
+        summary::SummaryCache::find(&paths[0], cache_dir).unwrap_or_default();
+
+    let mut shown = 0;
+    for file in &files {
+        // Unknown languages and unreadable files are not in the index
+        let Some(&info) = indexed.get(file.as_path()) else {
+            continue;
+        };
+
+        let symbols = filter.apply(index.get_file_symbols(&info.path));
+        if symbols.is_empty() {
+            continue;
+        }
+
+        let summaries = if summary_cache.entries.is_empty() {
+            HashMap::new()
+        } else {
+            let content = std::fs::read_to_string(file).unwrap_or_default();
+            let mut with_symbols = info.clone();
+            with_symbols.symbols = symbols.iter().map(|&s| s.clone()).collect();
+            summary_cache.fresh_for_file(&summary_root, &with_symbols, &content)
+        };
+        if shown > 0 && format != OutputFormat::Compact {
+            println!();
         }
-        Commands::Blame { symbol, file } => {
-            cmd_blame(symbol, file, format)?;
+        print_inspected_file(
+            summaries,
+            file,
+            info.language,
+            info.size,
+            symbols,
+            show_body,
+            redactor,
+            format,
+        )?;
+        shown += 1;
+    }
+
+    if shown == 0 {
+        println!("{} No symbols found in the given paths", "✗".yellow());
+    } else {
+        eprintln!(
+            "{} Inspected {} of {} files",
+            "✓".green(),
+            shown,
+            files.len()
+        );
+    }
+
+    Ok(shown)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_embed(
+    path: PathBuf,
+    output: Option<PathBuf>,
+    max_chars: usize,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    embeddings: config::EmbeddingsConfig,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    use std::io::Write;
+
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    let chunks = embed::build_chunks(&index, max_chars);
+    eprintln!(
+        "{} Built {} chunks from {} symbols",
+        "→".cyan(),
+        chunks.len().to_string().bold(),
+        index.total_symbols()
+    );
+
+    if let Some(output) = output {
+        let mut writer: Box<dyn Write> = if output.as_os_str() == "-" {
+            Box::new(history::Tee(std::io::stdout().lock()))
+        } else {
+            Box::new(std::io::BufWriter::new(std::fs::File::create(&output)?))
+        };
+        for chunk in &chunks {
+            writeln!(writer, "{}", serde_json::to_string(chunk)?)?;
         }
-        Commands::History { symbol, file } => {
-            cmd_history(symbol, file, format)?;
+        writer.flush()?;
+
+        if output.as_os_str() != "-" {
+            eprintln!(
+                "{} Wrote {} chunks to {}",
+                "✓".green(),
+                chunks.len(),
+                output.display_slash()
+            );
         }
-        Commands::Implements {
-            interface,
-            path,
-            fuzzy,
-            extensions,
-            no_cache,
-            rebuild_cache,
-            trait_only,
-        } => {
-            cmd_implements(
-                interface,
-                path,
-                fuzzy,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                trait_only,
-                format,
-                cache_dir,
-            )?;
+        return Ok(());
+    }
+
+    let embedder = embed::Embedder::new(embeddings)?;
+    let base_dir = cache::CacheManager::base_dir(&path, cache_dir);
+    let previous = embed::VectorStore::load(&base_dir)?.unwrap_or_default();
+    let reusable = previous.reusable_vectors(embedder.model());
+
+    let start = Instant::now();
+    let mut vectors: Vec<Option<Vec<f32>>> = chunks
+        .iter()
+        .map(|c| reusable.get(c.hash.as_str()).map(|v| (*v).clone()))
+        .collect();
+    let missing: Vec<usize> = (0..chunks.len()).filter(|&i| vectors[i].is_none()).collect();
+    let reused = chunks.len() - missing.len();
+
+    for (batch_num, batch) in missing.chunks(embedder.batch_size()).enumerate() {
+        let texts: Vec<String> = batch.iter().map(|&i| chunks[i].text.clone()).collect();
+        let embedded = embedder.embed(&texts)?;
+        for (&i, vector) in batch.iter().zip(embedded) {
+            vectors[i] = Some(vector);
         }
-        Commands::Types {
-            symbol,
-            path,
-            fuzzy,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_types(
-                symbol,
-                path,
-                fuzzy,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                format,
-                cache_dir,
-            )?;
+        eprintln!(
+            "{} Embedded {}/{} chunks",
+            "→".cyan(),
+            ((batch_num + 1) * embedder.batch_size()).min(missing.len()),
+            missing.len()
+        );
+    }
+
+    let store = embed::VectorStore {
+        model: embedder.model().map(|m| m.to_string()),
+        chunks,
+        vectors: vectors.into_iter().flatten().collect(),
+    };
+    store.save(&base_dir)?;
+
+    eprintln!(
+        "{} Stored {} vectors ({} new, {} reused) in {}ms",
+        "✓".green(),
+        store.vectors.len().to_string().bold(),
+        missing.len(),
+        reused,
+        start.elapsed().as_millis()
+    );
+
+    Ok(())
+}
+
+fn cmd_semantic_query(
+    query: String,
+    path: PathBuf,
+    limit: Option<usize>,
+    embeddings: config::EmbeddingsConfig,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let base_dir = cache::CacheManager::base_dir(&path, cache_dir);
+    let store = embed::VectorStore::load(&base_dir)?.ok_or_else(|| {
+        Failure::new(
+            ErrorCode::NotFound,
+            format!(
+                "No embeddings found in {}. Run 'cm embed' first",
+                base_dir.display_slash()
+            ),
+        )
+    })?;
+
+    let embedder = embed::Embedder::new(embeddings)?;
+    if store.model.as_deref() != embedder.model() {
+        eprintln!(
+            "{} Embeddings were built with model {}, re-run 'cm embed' for comparable scores",
+            "⚠".yellow(),
+            store.model.as_deref().unwrap_or("(unnamed)")
+        );
+    }
+
+    let start = Instant::now();
+    let query_vector = embedder
+        .embed(std::slice::from_ref(&query))?
+        .pop()
+        .unwrap_or_default();
+    let matches = store.search(&query_vector, limit.unwrap_or(10));
+
+    eprintln!(
+        "{} Searched {} chunks in {}ms\n",
+        "✓".green(),
+        store.chunks.len().to_string().bold(),
+        start.elapsed().as_millis()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_semantic(&matches, &query));
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_summarize(
+    path: PathBuf,
+    include_symbols: bool,
+    force: bool,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    summarizer: config::SummarizerConfig,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    use models::SymbolType;
+
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    let summarizer = summary::Summarizer::new(summarizer)?;
+    let base_dir = cache::CacheManager::base_dir(&path, cache_dir);
+    let mut summaries = summary::SummaryCache::load(&base_dir)?;
+    summaries.root = Some(std::path::absolute(&path)?);
+
+    let mut files: Vec<&models::FileInfo> = index.files().collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let start = Instant::now();
+    let mut generated = 0;
+    let mut up_to_date = 0;
+
+    for file in files {
+        let content = match std::fs::read_to_string(&file.path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+
+        let mut pending = vec![(
+            "file",
+            summary::stored_file_key(&path, &file.path),
+            file.hash.clone(),
+            file.path.display_slash().to_string(),
+            content.clone(),
+        )];
+
+        if include_symbols {
+            let lines: Vec<&str> = content.lines().collect();
+            for symbol in index.get_file_symbols(&file.path) {
+                let summarizable = matches!(
+                    symbol.symbol_type,
+                    SymbolType::Function
+                        | SymbolType::Class
+                        | SymbolType::Method
+                        | SymbolType::Interface
+                        | SymbolType::Enum
+                );
+                if !summarizable || symbol.name == "anonymous" {
+                    continue;
+                }
+                let text = summary::symbol_text(symbol, &lines);
+                pending.push((
+                    "symbol",
+                    summary::stored_symbol_key(&path, symbol),
+                    summary::text_hash(&text),
+                    symbol.name.to_string(),
+                    text,
+                ));
+            }
         }
-        Commands::Schema {
-            symbol,
-            path,
-            fuzzy,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_schema(
-                symbol,
-                path,
-                fuzzy,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                format,
-                cache_dir,
-            )?;
+
+        for (kind, key, hash, name, text) in pending {
+            if !force && summaries.get(&key, &hash).is_some() {
+                up_to_date += 1;
+                continue;
+            }
+
+            eprintln!("{} Summarizing {}", "→".cyan(), key);
+            let result = summarizer.summarize(kind, &file.path, &name, &text);
+            let text_summary = match result {
+                Ok(text_summary) => text_summary,
+                Err(e) => {
+                    // Keep what was generated so far; the next run resumes from here
+                    summaries.save(&base_dir)?;
+                    return Err(e);
+                }
+            };
+            summaries.insert(key, hash, text_summary);
+            generated += 1;
         }
-        Commands::Snapshot {
-            name,
-            path,
-            list,
-            delete,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_snapshot(
-                name,
-                path,
-                list,
-                delete,
-                extensions,
-                no_cache,
-                rebuild_cache,
-                format,
-                cache_dir,
-            )?;
+    }
+
+    summaries.save(&base_dir)?;
+
+    eprintln!(
+        "{} {} summaries generated, {} up to date ({}ms)",
+        "✓".green(),
+        generated.to_string().bold(),
+        up_to_date,
+        start.elapsed().as_millis()
+    );
+
+    Ok(())
+}
+
+/// Map `--lang` to a language and the path symbols are reported under.
+/// Stdin gets a virtual `<stdin>` path; plugin languages keep the extension so
+/// the registry can find their parser (files must already have it).
+fn resolve_lang(
+    lang: &str,
+    file_path: PathBuf,
+    from_stdin: bool,
+) -> Result<(models::Language, PathBuf)> {
+    let ext = lang.trim().trim_start_matches('.');
+    let language = match models::Language::from_name(ext) {
+        models::Language::Unknown if parser::registry::parser_for_extension(ext).is_some() => {
+            models::Language::Plugin
         }
-        Commands::Compare {
-            snapshot,
-            path,
-            extensions,
-            no_cache,
-            rebuild_cache,
-        } => {
-            cmd_compare(snapshot, path, extensions, no_cache, rebuild_cache, format, cache_dir)?;
+        models::Language::Unknown => {
+            let mut expected: Vec<String> = models::Language::BUILTIN
+                .iter()
+                .map(|l| l.as_str().to_string())
+                .collect();
+            expected.extend(parser::registry::registered_extensions());
+            anyhow::bail!(Failure::new(
+                ErrorCode::UnsupportedLanguage,
+                format!(
+                    "Unsupported language: {} (expected one of {})",
+                    lang,
+                    expected.join(", ")
+                )
+            ))
         }
+        language => language,
+    };
+
+    let path = match (from_stdin, language) {
+        (true, models::Language::Plugin) => PathBuf::from(format!("<stdin>.{}", ext)),
+        (true, _) => PathBuf::from("<stdin>"),
+        (false, _) => file_path,
+    };
+
+    Ok((language, path))
+}
+
+fn cmd_diff(
+    commit: String,
+    path: PathBuf,
+    extensions: String,
+    skip_anonymous: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    use std::time::Instant;
+
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+
+    eprintln!(
+        "{} Computing symbol diff against {}...",
+        "→".cyan(),
+        commit.bold()
+    );
+
+    let start = Instant::now();
+
+    let subpath = if path == PathBuf::from(".") {
+        None
+    } else {
+        Some(path.as_path())
+    };
+
+    let mut result = diff::compute_diff(&std::env::current_dir()?, &commit, subpath, &ext_list)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    // Filter anonymous if requested
+    if skip_anonymous {
+        result.symbols.retain(|s| s.name != "anonymous");
+    }
+
+    eprintln!(
+        "{} Analyzed {} files in {}ms\n",
+        "✓".green(),
+        result.files_analyzed.to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_diff(&result);
+    println!("{}", output);
+
+    Ok(())
+}
+
+fn cmd_diff_symbol(
+    symbol: String,
+    from: String,
+    to: Option<String>,
+    path: PathBuf,
+    extensions: String,
+    context: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let range = format!("{}..{}", from, to.as_deref().unwrap_or("worktree"));
+
+    eprintln!(
+        "{} Diffing {} between {}...",
+        "→".cyan(),
+        symbol.bold(),
+        range.bold()
+    );
+
+    let subpath = if path == Path::new(".") {
+        None
+    } else {
+        Some(path.as_path())
+    };
+    let changes = diff::symbol_changes(
+        &std::env::current_dir()?,
+        &symbol,
+        &from,
+        to.as_deref(),
+        subpath,
+        &ext_list,
+        context,
+    )?;
+
+    if changes.is_empty() {
+        eprintln!("{} No changes to '{}' between {}", "✗".red(), symbol, range);
+    } else {
+        eprintln!(
+            "{} {} changed definition(s)\n",
+            "✓".green(),
+            changes.len().to_string().bold()
+        );
     }
 
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_symbol_changes(&changes, &range));
+
     Ok(())
 }
 
-/// Auto-rebuild wrapper: Try cache first, rebuild if needed
-fn try_load_or_rebuild(
-    path: &PathBuf,
-    extensions: &[&str],
-    no_cache: bool,
-    rebuild_cache: bool,
-    cache_dir: Option<&Path>,
-) -> Result<index::CodeIndex> {
-    use cache::CacheManager;
+fn cmd_changed_symbols(
+    between: Vec<String>,
+    path: PathBuf,
+    extensions: String,
+    format: OutputFormat,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let base = &between[0];
+    let head = between.get(1).map(String::as_str);
 
-    // Skip cache if flags set
-    if no_cache || rebuild_cache {
-        if rebuild_cache {
-            eprintln!("{} Rebuilding cache (--rebuild-cache)", "→".cyan());
-            CacheManager::invalidate(path, extensions, cache_dir).ok(); // Ignore errors
-        }
-        let start = Instant::now();
-        let index = indexer::index_directory(path, extensions)?;
-        let elapsed_ms = start.elapsed().as_millis();
+    eprintln!(
+        "{} Listing symbols changed between {} and {}...",
+        "→".cyan(),
+        base.bold(),
+        head.unwrap_or("the working tree").bold()
+    );
 
-        // Save to cache only if indexing took >= 300ms (unless --no-cache)
-        if !no_cache && elapsed_ms >= 300 {
-            match CacheManager::save(&index, path, extensions, cache_dir) {
-                Ok(_) => eprintln!(
-                    "{} Cached index for future use ({}ms)",
-                    "✓".green(),
-                    elapsed_ms
-                ),
-                Err(e) => eprintln!("{} Warning: Failed to save cache: {}", "⚠".yellow(), e),
-            }
-        } else if !no_cache && elapsed_ms < 300 {
-            eprintln!(
-                "{} Indexed in {}ms (no cache needed for small repos)",
-                "✓".green(),
-                elapsed_ms
-            );
-        }
+    let start = Instant::now();
+    let subpath = if path == Path::new(".") {
+        None
+    } else {
+        Some(path.as_path())
+    };
+    let changed = diff::changed_symbols(&std::env::current_dir()?, base, head, subpath, &ext_list)?;
+    let elapsed_ms = start.elapsed().as_millis();
 
-        return Ok(index);
-    }
+    eprintln!(
+        "{} Found {} changed symbol(s) in {}ms\n",
+        "✓".green(),
+        changed.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
 
-    // Try to load from cache
-    match CacheManager::load(path, extensions, cache_dir) {
-        Ok(Some((index, metadata, changed_files))) if changed_files.is_empty() => {
-            // Cache hit - no changes
-            eprintln!(
-                "{} Loaded from cache - {} files, {} symbols",
-                "✓".green(),
-                metadata.file_count.to_string().bold(),
-                metadata.symbol_count.to_string().bold()
-            );
-            Ok(index)
-        }
-        Ok(Some((mut index, metadata, changed_files))) => {
-            // Incremental update needed
-            eprintln!("{} Indexing, changes detected", "→".cyan());
+    let formatter = OutputFormatter::new(format);
+    let range = format!("{}..{}", base, head.unwrap_or("worktree"));
+    println!("{}", formatter.format_changed_symbols(&changed, &range));
 
-            // Create progress bar
-            let pb = ProgressBar::new(changed_files.len() as u64);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("{spinner:.cyan} [{bar:40.cyan/blue}] {percent}% ({pos}/{len} files)")
-                    .unwrap()
-                    .progress_chars("=>-"),
-            );
+    Ok(())
+}
 
-            let start = Instant::now();
+fn cmd_stash_compare(
+    target: String,
+    path: PathBuf,
+    extensions: String,
+    skip_anonymous: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
 
-            for change in &changed_files {
-                index.remove_file(&change.path);
-            }
+    eprintln!(
+        "{} Comparing working tree against {}...",
+        "→".cyan(),
+        target.bold()
+    );
 
-            use std::sync::{Arc, Mutex};
-            let pb_wrapper = Arc::new(Mutex::new(pb));
+    let start = Instant::now();
+    let cwd = std::env::current_dir()?;
 
-            let new_file_infos: Vec<_> = changed_files
-                .par_iter()
-                .filter(|change| change.kind != FileChangeKind::Deleted)
-                .filter_map(|change| {
-                    let path = change.path.clone();
-                    let result = match std::fs::read_to_string(&path) {
-                        Ok(content) => {
-                            let language = models::Language::from_extension(
-                                path.extension().and_then(|e| e.to_str()).unwrap_or(""),
-                            );
+    let subpath = if path == Path::new(".") {
+        None
+    } else {
+        Some(path.as_path())
+    };
 
-                            match indexer::index_file(
-                                &path,
-                                &content,
-                                language,
-                                change.hash.as_deref(),
-                            ) {
-                                Ok(file_info) => Some((change.clone(), file_info)),
-                                Err(e) => {
-                                    eprintln!(
-                                        "{} Warning: Failed to parse {}: {}",
-                                        "⚠".yellow(),
-                                        path.display(),
-                                        e
-                                    );
-                                    None
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "{} Warning: Failed to read {}: {}",
-                                "⚠".yellow(),
-                                path.display(),
-                                e
-                            );
-                            None
-                        }
-                    };
+    let other = Path::new(&target);
+    let mut result = if other.is_dir() {
+        let other_root = paths::canonicalize(other)?;
+        diff::compute_directory_diff(&cwd, &other_root, subpath, &ext_list)?
+    } else {
+        diff::compute_worktree_diff(&cwd, &target, subpath, &ext_list)?
+    };
+    let elapsed_ms = start.elapsed().as_millis();
 
-                    if let Ok(pb) = pb_wrapper.lock() {
-                        pb.inc(1);
-                    }
+    if skip_anonymous {
+        result.symbols.retain(|s| s.name != "anonymous");
+    }
 
-                    result
-                })
-                .collect();
+    eprintln!(
+        "{} Analyzed {} files in {}ms\n",
+        "✓".green(),
+        result.files_analyzed.to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
 
-            if let Ok(pb) = pb_wrapper.lock() {
-                pb.finish_with_message("Done");
-                eprintln!(); // Add newline after progress bar
-            }
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_diff(&result));
 
-            for (change, file_info) in new_file_infos {
-                index.remove_file(&change.path);
-                index.add_file(file_info);
-            }
+    Ok(())
+}
 
-            // Compact the index to remove deleted symbols
-            index.compact();
+fn cmd_since(
+    commit: String,
+    path: PathBuf,
+    extensions: String,
+    breaking: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    use diff::ChangeType;
+    use std::time::Instant;
 
-            let elapsed_ms = start.elapsed().as_millis();
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
 
-            // Always save updated cache for incremental updates (cache already exists)
-            match CacheManager::save_with_changes(
-                &index,
-                path,
-                extensions,
-                &metadata,
-                &changed_files,
-                cache_dir,
-            ) {
-                Ok(_) => eprintln!("{} Cache updated ({}ms)", "✓".green(), elapsed_ms),
-                Err(e) => eprintln!("{} Warning: Failed to save cache: {}", "⚠".yellow(), e),
-            }
+    let label = if breaking {
+        "breaking changes"
+    } else {
+        "changes"
+    };
+    eprintln!(
+        "{} Finding {} since {}...",
+        "→".cyan(),
+        label,
+        commit.bold()
+    );
 
-            Ok(index)
-        }
-        Ok(None) => {
-            // Cache miss or invalid - index from scratch
-            eprintln!("{} Indexing...", "→".cyan());
+    let start = Instant::now();
 
-            // Create progress bar
-            let pb = ProgressBar::new(0);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("{spinner:.cyan} [{bar:40.cyan/blue}] {percent}% ({pos}/{len} files)")
-                    .unwrap()
-                    .progress_chars("=>-"),
-            );
+    let subpath = if path == PathBuf::from(".") {
+        None
+    } else {
+        Some(path.as_path())
+    };
 
-            let start = Instant::now();
-            let index = indexer::index_directory_with_progress(path, extensions, Some(pb))?;
-            let elapsed_ms = start.elapsed().as_millis();
+    let mut result = diff::compute_diff(&std::env::current_dir()?, &commit, subpath, &ext_list)?;
+    let elapsed_ms = start.elapsed().as_millis();
 
-            // Save to cache only if indexing took >= 300ms
-            if elapsed_ms >= 300 {
-                match CacheManager::save(&index, path, extensions, cache_dir) {
-                    Ok(_) => eprintln!(
-                        "{} Cache not found, created new cache ({} files, {}ms)",
-                        "✓".green(),
-                        index.total_files().to_string().bold(),
-                        elapsed_ms
-                    ),
-                    Err(e) => eprintln!("{} Warning: Failed to save cache: {}", "⚠".yellow(), e),
-                }
-            } else {
-                // Small repo - simple completion message
-                eprintln!(
-                    "{} Indexed ({} files, {}ms)",
-                    "✓".green(),
-                    index.total_files().to_string().bold(),
-                    elapsed_ms
-                );
-            }
+    if breaking {
+        result.symbols.retain(|s| {
+            matches!(
+                s.change_type,
+                ChangeType::Deleted
+                    | ChangeType::SignatureChanged
+                    | ChangeType::Moved
+                    | ChangeType::Renamed
+            )
+        });
+    }
 
-            Ok(index)
-        }
-        Err(e) => {
-            // Cache error - fallback to rebuild
-            eprintln!("{} Cache error: {}. Rebuilding...", "⚠".yellow(), e);
-            let start = Instant::now();
-            let index = indexer::index_directory(path, extensions)?;
-            let elapsed_ms = start.elapsed().as_millis();
+    eprintln!(
+        "{} Analyzed {} files in {}ms\n",
+        "✓".green(),
+        result.files_analyzed.to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
 
-            eprintln!(
-                "{} Indexed ({} files, {}ms)",
-                "✓".green(),
-                index.total_files().to_string().bold(),
-                elapsed_ms
-            );
+    let formatter = OutputFormatter::new(format);
+    let output = if breaking {
+        formatter.format_breaking(&result)
+    } else {
+        formatter.format_diff(&result)
+    };
+    println!("{}", output);
 
-            Ok(index)
-        }
-    }
+    Ok(())
 }
 
-fn cmd_index(path: PathBuf, extensions: String) -> Result<()> {
+fn cmd_breaking(
+    path: PathBuf,
+    against: String,
+    check: bool,
+    allowlist_path: Option<PathBuf>,
+    extensions: String,
+    format: OutputFormat,
+) -> Result<()> {
+    use breaking::Allowlist;
+
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
 
-    println!("{} Indexing directory: {}", "→".cyan(), path.display());
+    let allowlist = match allowlist_path {
+        Some(ref p) => Allowlist::load(p)?,
+        None => {
+            let default_path = Path::new(breaking::DEFAULT_ALLOWLIST_FILE);
+            if default_path.exists() {
+                Allowlist::load(default_path)?
+            } else {
+                Allowlist::default()
+            }
+        }
+    };
+
+    eprintln!(
+        "{} Checking breaking changes against {}...",
+        "→".cyan(),
+        against.bold()
+    );
 
     let start = Instant::now();
-    let index = indexer::index_directory(&path, &ext_list)?;
+
+    let subpath = if path == Path::new(".") {
+        None
+    } else {
+        Some(path.as_path())
+    };
+
+    let mut result = diff::compute_diff(&std::env::current_dir()?, &against, subpath, &ext_list)?;
+    let allowed = breaking::filter_breaking(&mut result, &allowlist);
     let elapsed_ms = start.elapsed().as_millis();
 
-    println!(
-        "{} Indexed {} files in {}ms",
+    eprintln!(
+        "{} Analyzed {} files in {}ms\n",
         "✓".green(),
-        index.total_files().to_string().bold(),
+        result.files_analyzed.to_string().bold(),
         elapsed_ms.to_string().bold()
     );
+
+    if !allowed.is_empty() {
+        eprintln!(
+            "{} {} breaking change(s) allowlisted: {}",
+            "→".cyan(),
+            allowed.len(),
+            allowed
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_breaking(&result));
+
+    if check && !result.symbols.is_empty() {
+        assertion_failed(format!(
+            "{} unapproved breaking change(s) to exported symbols",
+            result.symbols.len()
+        ));
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_verify(
+    path: PathBuf,
+    fingerprint_file: PathBuf,
+    record: bool,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+    let current = fingerprint::compute(&index, &path);
+
+    if record {
+        fingerprint::save(&fingerprint_file, &current)?;
+        eprintln!(
+            "{} Recorded fingerprint of {} files to {}",
+            "✓".green(),
+            current.files.len().to_string().bold(),
+            fingerprint_file.display_slash()
+        );
+        println!("{}", current.hash);
+        return Ok(());
+    }
+
+    let baseline = fingerprint::load(&fingerprint_file)?;
+    let drift = fingerprint::compare(&baseline, &current);
+
+    let formatter = OutputFormatter::new(format);
     println!(
-        "{} Total symbols: {}",
-        "→".cyan(),
-        index.total_symbols().to_string().bold()
+        "{}",
+        formatter.format_fingerprint_drift(&drift, &fingerprint_file)
     );
 
+    if !drift.is_clean() {
+        assertion_failed(format!(
+            "Structure drifted from {}: {} changed, {} added, {} removed",
+            fingerprint_file.display_slash(),
+            drift.changed.len(),
+            drift.added.len(),
+            drift.removed.len()
+        ));
+    }
+
     Ok(())
 }
 
-fn cmd_map(
+#[allow(clippy::too_many_arguments)]
+fn cmd_layers(
     path: PathBuf,
-    level: u8,
+    layers: &layers::Layers,
+    check: bool,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
     format: OutputFormat,
     cache_dir: Option<&Path>,
 ) -> Result<()> {
-    if level < 1 || level > 3 {
-        eprintln!("{} Level must be between 1 and 3", "Error:".red());
-        std::process::exit(1);
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+    let report = layers::check(&index, &path, layers);
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_layers(&report));
+
+    if check && !report.violations.is_empty() {
+        assertion_failed(format!(
+            "{} import(s) violate the layer order",
+            report.violations.len()
+        ));
     }
 
+    Ok(())
+}
+
+fn cmd_api_diff(
+    path: PathBuf,
+    against: String,
+    extensions: Option<String>,
+    skip_anonymous: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let current_root = paths::canonicalize(&path)
+        .with_context(|| format!("Failed to resolve path: {}", path.display_slash()))?;
+    // Removed when dropped, on errors too
+    let work_dir = tempfile::Builder::new()
+        .prefix("cm-api-diff-")
+        .tempdir()
+        .context("Failed to create a temporary directory")?;
+
+    let local = Path::new(&against);
+    let (label, registry_extensions, published_root) = if local.exists() {
+        eprintln!("{} Comparing against {}...", "→".cyan(), against.bold());
+        let root = if local.is_dir() {
+            local.to_path_buf()
+        } else {
+            package::unpack(local, work_dir.path())?
+        };
+        (against.clone(), None, root)
+    } else {
+        let spec = package::PackageSpec::parse(&against)?;
+        eprintln!(
+            "{} Downloading {} {} from {}...",
+            "→".cyan(),
+            spec.name.bold(),
+            spec.version.as_deref().unwrap_or("(latest)"),
+            spec.registry.as_str()
+        );
+        let (version, root) = package::fetch(&spec, work_dir.path())?;
+        let label = format!("{}:{}@{}", spec.registry.as_str(), spec.name, version);
+        (label, Some(spec.registry.extensions()), root)
+    };
+
+    let extensions = extensions
+        .or(registry_extensions.map(str::to_string))
+        .unwrap_or_else(|| "py,js,ts,jsx,tsx,rs,java,go,c,h,cpp,cc,cxx,hpp,hh,hxx".to_string());
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
 
-    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+    let start = Instant::now();
+    let published_root = paths::canonicalize(&published_root)?;
+    let mut result = diff::compute_directory_diff(&current_root, &published_root, None, &ext_list)?;
+    let elapsed_ms = start.elapsed().as_millis();
 
-    let formatter = OutputFormatter::new(format);
-    let output = formatter.format_map(&index, level);
+    // Body-only edits leave the API as it was
+    result.commit = label;
+    result.symbols.retain(|s| {
+        s.is_exported
+            && s.change_type != diff::ChangeType::Modified
+            && !(skip_anonymous && s.name == "anonymous")
+    });
+    for symbol in &mut result.symbols {
+        if let Ok(relative) = symbol.file_path.strip_prefix(&current_root) {
+            symbol.file_path = relative.to_path_buf();
+        }
+    }
 
-    println!("{}", output);
+    eprintln!(
+        "{} Analyzed {} files in {}ms ({} public API changes)\n",
+        "✓".green(),
+        result.files_analyzed.to_string().bold(),
+        elapsed_ms.to_string().bold(),
+        result.symbols.len()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_diff(&result));
 
     Ok(())
 }
 
-fn cmd_query(
-    symbol: String,
+#[allow(clippy::too_many_arguments)]
+fn cmd_api(
+    action: api_lock::ApiAction,
     path: PathBuf,
-    context: String,
-    fuzzy: bool,
-    fast: bool,
-    show_body: bool,
-    symbol_type_filter: Option<String>,
+    lock: PathBuf,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
-    skip_anonymous: bool,
-    exports_only: bool,
     format: OutputFormat,
-    limit: Option<usize>,
     cache_dir: Option<&Path>,
 ) -> Result<()> {
-    use fast_search::GrepFilter;
-    use models::SymbolType;
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+    let current = api_lock::freeze(&index, &path);
+
+    if action == api_lock::ApiAction::Freeze {
+        print!("{}", api_lock::render(&current));
+        eprintln!(
+            "{} Froze {} exported symbols",
+            "✓".green(),
+            current.len().to_string().bold()
+        );
+        return Ok(());
+    }
+
+    let locked = api_lock::load(&lock)?;
+    let result = diff::DiffResult {
+        commit: lock.display_slash().to_string(),
+        against_worktree: true,
+        symbols: api_lock::check(&locked, &current),
+        files_analyzed: index.total_files(),
+    };
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_diff(&result));
+
+    if !result.symbols.is_empty() {
+        assertion_failed(format!(
+            "Exports deviate from {} in {} place(s); if intended, run 'cm api freeze > {}'",
+            lock.display_slash(),
+            result.symbols.len(),
+            lock.display_slash()
+        ));
+    }
+    eprintln!(
+        "{} {} exported symbols match {}",
+        "✓".green(),
+        current.len().to_string().bold(),
+        lock.display_slash()
+    );
+
+    Ok(())
+}
 
+fn cmd_semver(path: PathBuf, from: String, extensions: String, format: OutputFormat) -> Result<()> {
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
 
-    // Check if symbol is a plural form of a symbol type (e.g., "functions", "classes")
-    let (symbol, symbol_type_filter) = if symbol_type_filter.is_none() {
-        if let Some(plural_type) = SymbolType::from_plural(&symbol) {
-            // Convert plural form to empty symbol + type filter
-            (String::new(), Some(plural_type.as_str().to_string()))
-        } else {
-            (symbol, symbol_type_filter)
-        }
+    eprintln!(
+        "{} Comparing public API against {}...",
+        "→".cyan(),
+        from.bold()
+    );
+
+    let start = Instant::now();
+
+    let subpath = if path == Path::new(".") {
+        None
     } else {
-        (symbol, symbol_type_filter)
+        Some(path.as_path())
     };
 
-    // Parse symbol type filter if provided
-    let type_filter = if let Some(ref type_str) = symbol_type_filter {
-        match SymbolType::from_str(type_str) {
-            Some(t) => Some(t),
-            None => {
-                eprintln!("{} Invalid symbol type '{}', valid types: function, class, method, enum, static, heading, code_block", "Error:".red(), type_str);
-                return Ok(());
-            }
-        }
+    let result = diff::compute_diff(&std::env::current_dir()?, &from, subpath, &ext_list)?;
+    let advice = semver::advise(&result, &from);
+    let elapsed_ms = start.elapsed().as_millis();
+
+    eprintln!(
+        "{} Analyzed {} files in {}ms\n",
+        "✓".green(),
+        result.files_analyzed.to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_semver(&advice));
+
+    Ok(())
+}
+
+/// Stage 1 of fast mode for commands that scan every indexed file: grep the indexed files
+/// for `needle` and return the ones that mention it (`None` when fast mode is off)
+fn prefilter_indexed_files(
+    index: &index::CodeIndex,
+    needle: &str,
+    fast: bool,
+) -> Result<Option<HashSet<PathBuf>>> {
+    use fast_search::GrepFilter;
+
+    let file_count = index.total_files();
+    if !fast && file_count < FAST_MODE_THRESHOLD {
+        return Ok(None);
+    }
+
+    if fast {
+        eprintln!(
+            "{} Fast mode enabled by --fast flag ({} files)",
+            "→".cyan(),
+            file_count
+        );
     } else {
-        None
-    };
+        eprintln!(
+            "{} Fast mode auto-enabled ({} files detected)",
+            "→".cyan(),
+            file_count
+        );
+    }
+
+    // Candidates only need to be a superset of the files the scan matches, so grep for the
+    // needle as a literal in any case
+    let files: Vec<PathBuf> = index.files().map(|f| f.path.clone()).collect();
+    let candidates = GrepFilter::new(needle, false, Vec::new()).prefilter_files(&files)?;
+
+    eprintln!(
+        "{} Found {} candidate files, validating with AST...",
+        "→".cyan(),
+        candidates.len()
+    );
+
+    Ok(Some(candidates.into_iter().collect()))
+}
+
+fn normalize_qualified_name(name: &str) -> String {
+    let trimmed = name.trim();
+
+    if let Some((_, tail)) = trimmed.rsplit_once("::") {
+        return tail.to_string();
+    }
+    if let Some((_, tail)) = trimmed.rsplit_once('.') {
+        return tail.to_string();
+    }
+
+    trimmed.to_string()
+}
+
+fn qualifier_pattern(name: &str) -> Option<String> {
+    let trimmed = name.trim();
+
+    if let Some((prefix, tail)) = trimmed.rsplit_once("::") {
+        if !prefix.is_empty() && !tail.is_empty() {
+            return Some(trimmed.to_string());
+        }
+    }
+
+    if let Some((prefix, tail)) = trimmed.rsplit_once('.') {
+        if !prefix.is_empty() && !tail.is_empty() {
+            return Some(trimmed.to_string());
+        }
+    }
+
+    None
+}
+
+fn cmd_callers(
+    symbols: Vec<String>,
+    path: PathBuf,
+    fuzzy: bool,
+    fast: bool,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    limit: Option<usize>,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+    let formatter = OutputFormatter::new(format);
+
+    // Several symbols come from a saved set: one report each, over the same index
+    for original_symbol in symbols {
+        let symbol = normalize_qualified_name(&original_symbol);
 
-    // Validate context level
-    let context_lower = context.to_lowercase();
-    if context_lower != "minimal" && context_lower != "full" {
         eprintln!(
-            "Invalid context '{}'. Valid options: minimal, full",
-            context
+            "{} Finding callers of '{}'...",
+            "→".cyan(),
+            original_symbol.bold()
         );
-        std::process::exit(1);
-    }
 
-    // Check if user wants all symbols of a specific type (empty symbol name with type filter)
-    let search_all = symbol.trim().is_empty() && type_filter.is_some();
-
-    // Count files for auto-detection
-    let file_count = count_indexable_files(&path, &ext_list)?;
+        let mut symbols = if fuzzy {
+            index.fuzzy_search(&symbol)
+        } else {
+            index.query_symbol(&symbol)
+        };
 
-    // Auto-enable fast mode for large codebases (1000+ files), but not when searching for all symbols
-    let use_fast_mode = !search_all && (fast || file_count >= 1000);
+        if symbols.is_empty() {
+            // allow Enum::Variant lookup via our variant indexing
+            if let Some(pattern) = qualifier_pattern(&original_symbol) {
+                symbols = if fuzzy {
+                    index.fuzzy_search(&pattern)
+                } else {
+                    index.query_symbol(&pattern)
+                };
+            }
+        }
 
-    if use_fast_mode {
-        if fast {
-            eprintln!(
-                "{} Fast mode enabled by --fast flag ({} files)",
-                "→".cyan(),
-                file_count
-            );
-        } else {
-            eprintln!(
-                "{} Fast mode auto-enabled ({} files detected)",
-                "→".cyan(),
-                file_count
+        if symbols.is_empty() {
+            println!(
+                "{} Symbol '{}' not found in codebase",
+                "✗".yellow(),
+                symbol.bold()
             );
+            continue;
         }
 
-        // Stage 1: Ripgrep prefilter
-        let extensions_vec: Vec<String> = ext_list.iter().map(|s| s.to_string()).collect();
-        let filter = GrepFilter::new(&symbol, !fuzzy, extensions_vec);
+        let start = Instant::now();
+        let candidates = prefilter_indexed_files(&index, &symbol, fast)?;
+        let mut callers =
+            callgraph::find_callers_in(&index, &original_symbol, fuzzy, candidates.as_ref())?;
+        let elapsed_ms = start.elapsed().as_millis();
 
-        let candidates = filter.prefilter(&path)?;
+        if callers.is_empty() {
+            println!("{} No callers found for '{}'", "✗".yellow(), symbol.bold());
+            continue;
+        }
 
-        if candidates.is_empty() {
-            eprintln!(
-                "{} No text matches found, falling back to full AST scan",
-                "→".yellow()
-            );
-            // Fallback: Use normal mode with cache
-            let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
-            let mut symbols = if search_all {
-                index.all_symbols()
-            } else if fuzzy {
-                index.fuzzy_search(&symbol)
+        let total_count = callers.len();
+        let truncated = if let Some(lim) = limit {
+            if callers.len() > lim {
+                callers.truncate(lim);
+                true
             } else {
-                index.query_symbol(&symbol)
-            };
-
-            // Apply type filter if specified
-            if let Some(filter_type) = type_filter {
-                symbols.retain(|s| s.symbol_type == filter_type);
+                false
             }
+        } else {
+            false
+        };
 
-            // Filter anonymous if requested
-            if skip_anonymous {
-                symbols.retain(|s| s.name != "anonymous");
+        eprintln!(
+            "{} Found {} call site(s) in {}ms{}\n",
+            "✓".green(),
+            total_count.to_string().bold(),
+            elapsed_ms.to_string().bold(),
+            if truncated {
+                format!(" (showing first {})", limit.unwrap())
+            } else {
+                String::new()
             }
+        );
 
-            // Filter to exports only if requested
-            if exports_only {
-                symbols.retain(|s| s.is_exported);
-            }
+        let output = formatter.format_callers(&callers, &original_symbol);
+        println!("{}", output);
+    }
 
-            // Apply limit if specified
-            if let Some(n) = limit {
-                symbols.truncate(n);
-            }
+    Ok(())
+}
 
-            if symbols.is_empty() {
-                println!(
-                    "{} No symbols found matching '{}'",
-                    "✗".red(),
-                    symbol.bold()
-                );
-                return Ok(());
-            }
+fn cmd_callees(
+    symbol: String,
+    path: PathBuf,
+    fuzzy: bool,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    limit: Option<usize>,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-            let show_context = context.to_lowercase() == "full";
-            let formatter = OutputFormatter::new(format);
-            let output = formatter.format_query(symbols, show_context, show_body);
-            println!("{}", output);
-        } else {
-            eprintln!(
-                "{} Found {} candidate files, validating with AST...",
-                "→".cyan(),
-                candidates.len()
-            );
+    let original_symbol = symbol;
+    let symbol = normalize_qualified_name(&original_symbol);
 
-            // Stage 2: AST validation
-            let mut owned_symbols = filter.validate(candidates, &symbol, fuzzy)?;
+    eprintln!(
+        "{} Finding callees of '{}'...",
+        "→".cyan(),
+        original_symbol.bold()
+    );
 
-            // Apply type filter if specified
-            if let Some(filter_type) = type_filter {
-                owned_symbols.retain(|s| s.symbol_type == filter_type);
-            }
+    let mut symbols = if fuzzy {
+        index.fuzzy_search(&symbol)
+    } else {
+        index.query_symbol(&symbol)
+    };
 
-            // Filter anonymous if requested
-            if skip_anonymous {
-                owned_symbols.retain(|s| s.name != "anonymous");
-            }
+    if symbols.is_empty() {
+        if let Some(pattern) = qualifier_pattern(&original_symbol) {
+            symbols = if fuzzy {
+                index.fuzzy_search(&pattern)
+            } else {
+                index.query_symbol(&pattern)
+            };
+        }
+    }
 
-            // Filter to exports only if requested
-            if exports_only {
-                owned_symbols.retain(|s| s.is_exported);
-            }
+    if symbols.is_empty() {
+        println!(
+            "{} Symbol '{}' not found in codebase",
+            "✗".yellow(),
+            symbol.bold()
+        );
+        return Ok(());
+    }
 
-            // Apply limit if specified
-            if let Some(n) = limit {
-                owned_symbols.truncate(n);
-            }
+    let start = Instant::now();
+    let mut callees = callgraph::find_callees(&index, &symbol, fuzzy)?;
+    let elapsed_ms = start.elapsed().as_millis();
 
-            if owned_symbols.is_empty() {
-                println!(
-                    "{} No symbols found matching '{}'",
-                    "✗".red(),
-                    symbol.bold()
-                );
-                return Ok(());
-            }
+    if callees.is_empty() {
+        println!("{} No callees found for '{}'", "✗".yellow(), symbol.bold());
+        return Ok(());
+    }
 
-            let show_context = context.to_lowercase() == "full";
-            let formatter = OutputFormatter::new(format);
-            // Convert owned symbols to references for formatter
-            let symbol_refs: Vec<&Symbol> = owned_symbols.iter().collect();
-            let output = formatter.format_query(symbol_refs, show_context, show_body);
-            println!("{}", output);
+    let total_count = callees.len();
+    let truncated = if let Some(lim) = limit {
+        if callees.len() > lim {
+            callees.truncate(lim);
+            true
+        } else {
+            false
         }
     } else {
-        // Normal mode for small codebases with cache
-        let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
-        let mut symbols = if search_all {
-            // Get all symbols when searching for all of a specific type
-            index.all_symbols()
-        } else if fuzzy {
-            index.fuzzy_search(&symbol)
-        } else {
-            index.query_symbol(&symbol)
-        };
+        false
+    };
 
-        // Apply type filter if specified
-        if let Some(filter_type) = type_filter {
-            symbols.retain(|s| s.symbol_type == filter_type);
+    eprintln!(
+        "{} Found {} callee(s) in {}ms{}\n",
+        "✓".green(),
+        total_count.to_string().bold(),
+        elapsed_ms.to_string().bold(),
+        if truncated {
+            format!(" (showing first {})", limit.unwrap())
+        } else {
+            String::new()
         }
+    );
 
-        // Filter anonymous if requested
-        if skip_anonymous {
-            symbols.retain(|s| s.name != "anonymous");
-        }
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_callees(&callees, &original_symbol);
+    println!("{}", output);
 
-        // Filter to exports only if requested
-        if exports_only {
-            symbols.retain(|s| s.is_exported);
-        }
+    Ok(())
+}
 
-        // Apply limit if specified
-        if let Some(n) = limit {
-            symbols.truncate(n);
-        }
+#[allow(clippy::too_many_arguments)]
+fn cmd_routes(
+    path: PathBuf,
+    method: Option<String>,
+    prefix: Option<String>,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-        if symbols.is_empty() {
-            println!(
-                "{} No symbols found matching '{}'",
-                "✗".red(),
-                symbol.bold()
-            );
-            return Ok(());
-        }
+    eprintln!("{} Finding HTTP routes...", "→".cyan());
 
-        let show_context = context.to_lowercase() == "full";
-        let formatter = OutputFormatter::new(format);
-        let output = formatter.format_query(symbols, show_context, show_body);
-        println!("{}", output);
+    let start = Instant::now();
+    let mut found = routes::find_routes(&index)?;
+    if let Some(method) = &method {
+        let method = method.to_uppercase();
+        found.retain(|r| r.method == method || r.method == "ANY");
+    }
+    if let Some(prefix) = &prefix {
+        found.retain(|r| r.path.starts_with(prefix.as_str()));
+    }
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if found.is_empty() {
+        println!("{} No HTTP routes found", "✗".yellow());
+        return Ok(());
     }
 
-    Ok(())
-}
+    eprintln!(
+        "{} Found {} route(s) in {}ms\n",
+        "✓".green(),
+        found.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_routes(&found);
+    println!("{}", output);
+
+    Ok(())
+}
+
+fn cmd_cli_map(
+    path: PathBuf,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    eprintln!("{} Finding CLI definitions...", "→".cyan());
 
-/// Count indexable files in directory for auto-detection logic
-fn count_indexable_files(path: &PathBuf, extensions: &[&str]) -> Result<usize> {
-    use ignore::WalkBuilder;
+    let start = Instant::now();
+    let commands = cli_map::find_cli_commands(&index)?;
+    let elapsed_ms = start.elapsed().as_millis();
 
-    let mut count = 0;
-    let walker = WalkBuilder::new(path)
-        .hidden(false)
-        .git_ignore(true)
-        .build();
+    if commands.is_empty() {
+        println!("{} No CLI definitions found", "✗".yellow());
+        return Ok(());
+    }
 
-    for entry in walker.filter_map(|e| e.ok()) {
-        if !entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
-            continue;
-        }
+    eprintln!(
+        "{} Found {} command(s) in {}ms\n",
+        "✓".green(),
+        commands.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
 
-        if extensions.is_empty() {
-            count += 1;
-        } else {
-            if entry
-                .path()
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| extensions.contains(&ext))
-                .unwrap_or(false)
-            {
-                count += 1;
-            }
-        }
-    }
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_cli_map(&commands);
+    println!("{}", output);
 
-    Ok(count)
+    Ok(())
 }
 
-fn cmd_deps(
-    target: String,
+fn cmd_envvars(
     path: PathBuf,
-    direction: String,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
@@ -2408,414 +7800,424 @@ fn cmd_deps(
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
     let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    // Detect if target is a file path or symbol name
-    let target_path = Path::new(&target);
-    let is_file = target_path.exists() || target.contains('/') || target.contains('\\');
+    eprintln!("{} Finding environment variables...", "→".cyan());
 
-    if is_file {
-        // Original file-based dependency tracking
-        cmd_deps_file(target, index, direction, format)
-    } else {
-        // New symbol-based usage tracking
-        cmd_deps_symbol(target, index, direction, format)
+    let start = Instant::now();
+    let vars = envvars::find_env_vars(&index)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if vars.is_empty() {
+        println!("{} No environment variables found", "✗".yellow());
+        return Ok(());
     }
+
+    eprintln!(
+        "{} Found {} variable(s) in {}ms\n",
+        "✓".green(),
+        vars.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_envvars(&vars);
+    println!("{}", output);
+
+    Ok(())
 }
 
-fn cmd_deps_file(
-    target: String,
-    index: index::CodeIndex,
-    direction: String,
+#[allow(clippy::too_many_arguments)]
+fn cmd_sql(
+    path: PathBuf,
+    table: Option<String>,
+    kind: Option<String>,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
     format: OutputFormat,
+    cache_dir: Option<&Path>,
 ) -> Result<()> {
-    use std::path::PathBuf;
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    let target_path = PathBuf::from(&target);
-    let target_canonical = std::fs::canonicalize(&target_path).unwrap_or(target_path.clone());
-
-    let deps = if direction.to_lowercase() == "imports" {
-        // Try both relative and canonical paths
-        index
-            .get_dependencies(&target_path)
-            .or_else(|| index.get_dependencies(&target_canonical))
-            .map(|d| d.clone())
-            .unwrap_or_default()
-    } else if direction.to_lowercase() == "used-by" {
-        let mut used_by = Vec::new();
-        for file in index.files() {
-            if let Some(file_deps) = index.get_dependencies(&file.path) {
-                let target_name = target_canonical
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("");
-
-                for dep in file_deps {
-                    if dep.contains(target_name) {
-                        used_by.push(file.path.display().to_string());
-                        break;
-                    }
-                }
-            }
-        }
-        used_by
-    } else {
-        eprintln!(
-            "{} Invalid direction '{}', use 'imports' or 'used-by'",
-            "Error:".red(),
-            direction
-        );
-        std::process::exit(1);
-    };
+    eprintln!("{} Finding SQL in string literals...", "→".cyan());
 
-    if deps.is_empty() {
-        println!("{} No dependencies found for {}", "✗".yellow(), target);
+    let start = Instant::now();
+    let mut queries = sql::find_sql(&index)?;
+    if let Some(table) = &table {
+        let table = table.to_lowercase();
+        queries.retain(|q| {
+            q.tables.iter().any(|t| {
+                let t = t.to_lowercase();
+                t == table || t.rsplit('.').next() == Some(table.as_str())
+            })
+        });
+    }
+    if let Some(kind) = &kind {
+        let kind = kind.to_uppercase();
+        queries.retain(|q| q.kind.starts_with(kind.as_str()));
+    }
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if queries.is_empty() {
+        println!("{} No SQL statements found", "✗".yellow());
         return Ok(());
     }
 
-    let formatter = OutputFormatter::new(format);
-    let output = formatter.format_deps(&target, deps, &direction);
+    eprintln!(
+        "{} Found {} statement(s) in {}ms\n",
+        "✓".green(),
+        queries.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
 
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_sql(&queries);
     println!("{}", output);
 
     Ok(())
 }
 
-fn cmd_deps_symbol(
-    symbol_name: String,
-    index: index::CodeIndex,
-    direction: String,
+#[allow(clippy::too_many_arguments)]
+fn cmd_strings(
+    pattern: String,
+    path: PathBuf,
+    regex: bool,
+    limit: Option<usize>,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
     format: OutputFormat,
+    cache_dir: Option<&Path>,
 ) -> Result<()> {
-    use std::fs;
-
-    if direction.to_lowercase() != "used-by" {
-        eprintln!(
-            "{} For symbols, only '--direction used-by' is supported",
-            "Error:".red()
-        );
-        eprintln!("{} Use a file path to see imports", "Hint:".cyan());
-        std::process::exit(1);
-    }
+    let matcher = if regex {
+        strings::StringPattern::regex(&pattern)?
+    } else {
+        strings::StringPattern::text(&pattern)
+    };
 
-    // Find the symbol definition
-    let symbols = index.query_symbol(&symbol_name);
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    if symbols.is_empty() {
-        println!(
-            "{} Symbol '{}' not found in codebase",
-            "✗".yellow(),
-            symbol_name.bold()
+    let base_dir = cache::CacheManager::base_dir(&path, cache_dir);
+    let mut literals = if no_cache || rebuild_cache {
+        strings::StringIndex::default()
+    } else {
+        strings::StringIndex::load(&base_dir)
+    };
+    let start = Instant::now();
+    let read = literals.refresh(&index);
+    if read > 0 {
+        eprintln!(
+            "{} Indexed string literals of {} file(s) in {}ms",
+            "→".cyan(),
+            read,
+            start.elapsed().as_millis()
         );
-        return Ok(());
+        if !no_cache {
+            literals.save(&base_dir)?;
+        }
     }
 
-    // For now, use simple string search to find usages
-    // Future: can be upgraded to AST-based call detection
-    let mut usages: Vec<String> = Vec::new();
+    eprintln!(
+        "{} Searching {} string literals for '{}'...",
+        "→".cyan(),
+        literals.total_literals(),
+        pattern.bold()
+    );
 
-    for file in index.files() {
-        if let Ok(content) = fs::read_to_string(&file.path) {
-            for (line_num, line) in content.lines().enumerate() {
-                if line.contains(&symbol_name) {
-                    // Skip the definition itself
-                    let is_definition = symbols.iter().any(|s| {
-                        s.file_path == file.path
-                            && (line_num + 1) >= s.line_start
-                            && (line_num + 1) <= s.line_end
-                    });
-
-                    if !is_definition {
-                        usages.push(format!("{}:{}", file.path.display(), line_num + 1));
-                    }
-                }
-            }
-        }
-    }
+    let start = Instant::now();
+    let mut matches = literals.search(&index, &matcher);
+    let elapsed_ms = start.elapsed().as_millis();
 
-    if usages.is_empty() {
+    if matches.is_empty() {
         println!(
-            "{} No usages found for symbol '{}'",
+            "{} No string literals match '{}'",
             "✗".yellow(),
-            symbol_name.bold()
+            pattern.bold()
         );
         return Ok(());
     }
 
-    // Show summary first
-    println!(
-        "{} Found {} usage(s) of '{}'\n",
+    eprintln!(
+        "{} Found {} literal(s) in {}ms\n",
         "✓".green(),
-        usages.len().to_string().bold(),
-        symbol_name.bold()
+        matches.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
     );
 
-    let formatter = OutputFormatter::new(format);
-    let output = formatter.format_deps(&symbol_name, usages, "used-by");
+    if let Some(limit) = limit {
+        matches.truncate(limit);
+    }
 
-    println!("{}", output);
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_strings(&matches, &pattern));
 
     Ok(())
 }
 
-fn cmd_stats(
+#[allow(clippy::too_many_arguments)]
+fn cmd_flags(
     path: PathBuf,
+    patterns: Vec<String>,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
+    flags_config: config::FlagsConfig,
     format: OutputFormat,
     cache_dir: Option<&Path>,
 ) -> Result<()> {
-    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let patterns = if !patterns.is_empty() {
+        flags::compile_patterns(&patterns)?
+    } else if !flags_config.patterns.is_empty() {
+        flags::compile_patterns(&flags_config.patterns)?
+    } else {
+        flags::compile_patterns(flags::DEFAULT_PATTERNS)?
+    };
 
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
     let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    let formatter = OutputFormatter::new(format);
-    let output = formatter.format_stats(&index);
+    eprintln!("{} Finding feature flag checks...", "→".cyan());
+
+    let start = Instant::now();
+    let found = flags::find_flags(&index, &patterns)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if found.is_empty() {
+        println!("{} No feature flags found", "✗".yellow());
+        return Ok(());
+    }
+
+    eprintln!(
+        "{} Found {} flag(s) in {}ms\n",
+        "✓".green(),
+        found.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
 
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_flags(&found);
     println!("{}", output);
 
     Ok(())
 }
 
-fn cmd_inspect(
-    file_path: PathBuf,
-    show_body: bool,
-    skip_anonymous: bool,
-    exports_only: bool,
+#[allow(clippy::too_many_arguments)]
+fn cmd_errors(
+    symbol: String,
+    path: PathBuf,
+    direct: bool,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
     format: OutputFormat,
+    cache_dir: Option<&Path>,
 ) -> Result<()> {
-    use std::fs;
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    if !file_path.exists() {
-        anyhow::bail!("File does not exist: {}", file_path.display());
-    }
+    let original_symbol = symbol;
+    let symbol = normalize_qualified_name(&original_symbol);
+
+    eprintln!(
+        "{} Finding errors of '{}'...",
+        "→".cyan(),
+        original_symbol.bold()
+    );
 
-    if !file_path.is_file() {
-        anyhow::bail!("Path is not a file: {}", file_path.display());
+    let mut symbols = index.query_symbol(&symbol);
+    if symbols.is_empty() {
+        if let Some(pattern) = qualifier_pattern(&original_symbol) {
+            symbols = index.query_symbol(&pattern);
+        }
     }
+    symbols.retain(|s| {
+        matches!(
+            s.symbol_type,
+            models::SymbolType::Function | models::SymbolType::Method
+        )
+    });
 
-    let language = indexer::detect_language(&file_path);
-    if language == models::Language::Unknown {
-        anyhow::bail!("Unknown or unsupported file type: {}", file_path.display());
+    if symbols.is_empty() {
+        println!(
+            "{} Function '{}' not found in codebase",
+            "✗".yellow(),
+            symbol.bold()
+        );
+        return Ok(());
     }
 
-    let content = fs::read_to_string(&file_path)?;
     let start = Instant::now();
-    let mut file_info = indexer::index_file(&file_path, &content, language, None)?;
+    let reports = errors::analyze(&index, &symbols, direct)?;
     let elapsed_ms = start.elapsed().as_millis();
 
-    // Filter anonymous if requested
-    if skip_anonymous {
-        file_info.symbols.retain(|s| s.name != "anonymous");
-    }
-
-    // Filter to exports only if requested
-    if exports_only {
-        file_info.symbols.retain(|s| s.is_exported);
-    }
-
-    if file_info.symbols.is_empty() {
+    let total: usize = reports.iter().map(|r| r.sites.len()).sum();
+    if total == 0 {
         println!(
-            "{} No symbols found in {}",
+            "{} No error sources found for '{}'",
             "✗".yellow(),
-            file_path.display()
+            symbol.bold()
         );
         return Ok(());
     }
 
+    eprintln!(
+        "{} Found {} error source(s) in {}ms\n",
+        "✓".green(),
+        total.to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
     let formatter = OutputFormatter::new(format);
+    let output = formatter.format_errors(&reports);
+    println!("{}", output);
 
-    match format {
-        OutputFormat::AI => {
-            println!("[FILE:{}]", file_path.display());
-            println!(
-                "LANG:{} SIZE:{} SYMS:{}",
-                language.as_str(),
-                file_info.size,
-                file_info.symbols.len()
-            );
-            for symbol in &file_info.symbols {
-                print!(
-                    "{}|{}|{}-{}",
-                    symbol.name,
-                    symbol.symbol_type.as_str().chars().next().unwrap(),
-                    symbol.line_start,
-                    symbol.line_end
-                );
-                if let Some(ref sig) = symbol.signature {
-                    print!("|sig:{}", sig);
-                }
-                println!();
-            }
-        }
-        _ => {
-            println!(
-                "{} Inspecting: {}\n",
-                "→".cyan(),
-                file_path.display().to_string().bold()
-            );
-            println!("Language: {}", language.as_str());
-            println!("Size: {} bytes", file_info.size);
-            println!("Symbols: {}\n", file_info.symbols.len());
+    Ok(())
+}
 
-            let symbol_refs: Vec<&models::Symbol> = file_info.symbols.iter().collect();
-            let output = formatter.format_query(symbol_refs, false, show_body);
-            println!("{}", output);
-        }
+#[allow(clippy::too_many_arguments)]
+fn cmd_logs(
+    path: PathBuf,
+    level: Option<String>,
+    grep: Option<String>,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let min_level = match &level {
+        Some(level) => Some(logs::LogLevel::from_method(level).with_context(|| {
+            format!(
+                "Unknown log level '{}' (use trace, debug, info, warn, error or fatal)",
+                level
+            )
+        })?),
+        None => None,
+    };
+
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    eprintln!("{} Finding logging calls...", "→".cyan());
+
+    let start = Instant::now();
+    let mut calls = logs::find_logs(&index)?;
+    if let Some(min_level) = min_level {
+        calls.retain(|c| c.level >= min_level);
+    }
+    if let Some(grep) = &grep {
+        let grep = grep.to_lowercase();
+        calls.retain(|c| {
+            c.message
+                .as_ref()
+                .is_some_and(|m| m.to_lowercase().contains(&grep))
+        });
+    }
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if calls.is_empty() {
+        println!("{} No logging calls found", "✗".yellow());
+        return Ok(());
     }
 
-    println!(
-        "\n{} Parse time: {}ms",
-        "→".cyan(),
+    eprintln!(
+        "{} Found {} logging call(s) in {}ms\n",
+        "✓".green(),
+        calls.len().to_string().bold(),
         elapsed_ms.to_string().bold()
     );
 
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_logs(&calls);
+    println!("{}", output);
+
     Ok(())
 }
 
-fn cmd_diff(
-    commit: String,
+fn cmd_audit(
     path: PathBuf,
+    rules: Vec<audit::Rule>,
     extensions: String,
-    skip_anonymous: bool,
+    no_cache: bool,
+    rebuild_cache: bool,
     format: OutputFormat,
+    cache_dir: Option<&Path>,
 ) -> Result<()> {
-    use std::time::Instant;
-
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    eprintln!(
-        "{} Computing symbol diff against {}...",
-        "→".cyan(),
-        commit.bold()
-    );
+    eprintln!("{} Auditing...", "→".cyan());
 
     let start = Instant::now();
-
-    let subpath = if path == PathBuf::from(".") {
-        None
-    } else {
-        Some(path.as_path())
-    };
-
-    let mut result = diff::compute_diff(&std::env::current_dir()?, &commit, subpath, &ext_list)?;
+    let mut findings = audit::audit(&index)?;
+    if !rules.is_empty() {
+        findings.retain(|f| rules.contains(&f.rule));
+    }
     let elapsed_ms = start.elapsed().as_millis();
 
-    // Filter anonymous if requested
-    if skip_anonymous {
-        result.symbols.retain(|s| s.name != "anonymous");
+    if findings.is_empty() {
+        println!("{} No findings", "✓".green());
+        return Ok(());
     }
 
     eprintln!(
-        "{} Analyzed {} files in {}ms\n",
-        "✓".green(),
-        result.files_analyzed.to_string().bold(),
+        "{} Found {} finding(s) in {}ms\n",
+        "✗".yellow(),
+        findings.len().to_string().bold(),
         elapsed_ms.to_string().bold()
     );
 
     let formatter = OutputFormatter::new(format);
-    let output = formatter.format_diff(&result);
+    let output = formatter.format_audit(&findings);
     println!("{}", output);
 
     Ok(())
 }
 
-fn cmd_since(
-    commit: String,
+fn cmd_shadows(
     path: PathBuf,
+    exported_only: bool,
     extensions: String,
-    breaking: bool,
+    no_cache: bool,
+    rebuild_cache: bool,
     format: OutputFormat,
+    cache_dir: Option<&Path>,
 ) -> Result<()> {
-    use diff::ChangeType;
-    use std::time::Instant;
-
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    let label = if breaking {
-        "breaking changes"
-    } else {
-        "changes"
-    };
-    eprintln!(
-        "{} Finding {} since {}...",
-        "→".cyan(),
-        label,
-        commit.bold()
-    );
+    eprintln!("{} Comparing definitions...", "→".cyan());
 
     let start = Instant::now();
-
-    let subpath = if path == PathBuf::from(".") {
-        None
-    } else {
-        Some(path.as_path())
-    };
-
-    let mut result = diff::compute_diff(&std::env::current_dir()?, &commit, subpath, &ext_list)?;
+    let shadows = shadows::find_shadows(&index, exported_only)?;
     let elapsed_ms = start.elapsed().as_millis();
 
-    if breaking {
-        result.symbols.retain(|s| {
-            matches!(
-                s.change_type,
-                ChangeType::Deleted | ChangeType::SignatureChanged
-            )
-        });
+    if shadows.is_empty() {
+        println!("{} No name is defined in more than one file", "✓".green());
+        return Ok(());
     }
 
     eprintln!(
-        "{} Analyzed {} files in {}ms\n",
-        "✓".green(),
-        result.files_analyzed.to_string().bold(),
+        "{} Found {} shadowed name(s) in {}ms\n",
+        "✗".yellow(),
+        shadows.len().to_string().bold(),
         elapsed_ms.to_string().bold()
     );
 
     let formatter = OutputFormatter::new(format);
-    let output = if breaking {
-        formatter.format_breaking(&result)
-    } else {
-        formatter.format_diff(&result)
-    };
+    let output = formatter.format_shadows(&shadows);
     println!("{}", output);
 
     Ok(())
 }
 
-fn normalize_qualified_name(name: &str) -> String {
-    let trimmed = name.trim();
-
-    if let Some((_, tail)) = trimmed.rsplit_once("::") {
-        return tail.to_string();
-    }
-    if let Some((_, tail)) = trimmed.rsplit_once('.') {
-        return tail.to_string();
-    }
-
-    trimmed.to_string()
-}
-
-fn qualifier_pattern(name: &str) -> Option<String> {
-    let trimmed = name.trim();
-
-    if let Some((prefix, tail)) = trimmed.rsplit_once("::") {
-        if !prefix.is_empty() && !tail.is_empty() {
-            return Some(trimmed.to_string());
-        }
-    }
-
-    if let Some((prefix, tail)) = trimmed.rsplit_once('.') {
-        if !prefix.is_empty() && !tail.is_empty() {
-            return Some(trimmed.to_string());
-        }
-    }
-
-    None
-}
-
-fn cmd_callers(
-    symbol: String,
+#[allow(clippy::too_many_arguments)]
+fn cmd_value_refs(
+    name: String,
     path: PathBuf,
-    fuzzy: bool,
+    code_only: bool,
+    fast: bool,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
@@ -2826,64 +8228,37 @@ fn cmd_callers(
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
     let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    let original_symbol = symbol;
-    let symbol = normalize_qualified_name(&original_symbol);
-
-    eprintln!(
-        "{} Finding callers of '{}'...",
-        "→".cyan(),
-        original_symbol.bold()
-    );
-
-    let mut symbols = if fuzzy {
-        index.fuzzy_search(&symbol)
-    } else {
-        index.query_symbol(&symbol)
-    };
-
-    if symbols.is_empty() {
-        // allow Enum::Variant lookup via our variant indexing
-        if let Some(pattern) = qualifier_pattern(&original_symbol) {
-            symbols = if fuzzy {
-                index.fuzzy_search(&pattern)
-            } else {
-                index.query_symbol(&pattern)
-            };
-        }
-    }
+    eprintln!("{} Finding references to '{}'...", "→".cyan(), name.bold());
 
-    if symbols.is_empty() {
+    let definitions = value_refs::definitions(&index, &name);
+    if definitions.is_empty() {
         println!(
-            "{} Symbol '{}' not found in codebase",
+            "{} No constant or enum variant named '{}' in codebase",
             "✗".yellow(),
-            symbol.bold()
+            name.bold()
         );
         return Ok(());
     }
 
     let start = Instant::now();
-    let mut callers = callgraph::find_callers(&index, &original_symbol, fuzzy)?;
-    let elapsed_ms = start.elapsed().as_millis();
-
-    if callers.is_empty() {
-        println!("{} No callers found for '{}'", "✗".yellow(), symbol.bold());
-        return Ok(());
+    let candidates = prefilter_indexed_files(&index, &normalize_qualified_name(&name), fast)?;
+    let mut refs = value_refs::find_value_refs(&index, &name, &definitions, candidates.as_ref())?;
+    if code_only {
+        refs.retain(|r| r.kind != value_refs::RefKind::Text);
     }
+    let elapsed_ms = start.elapsed().as_millis();
 
-    let total_count = callers.len();
-    let truncated = if let Some(lim) = limit {
-        if callers.len() > lim {
-            callers.truncate(lim);
+    let total_count = refs.len();
+    let truncated = match limit {
+        Some(lim) if refs.len() > lim => {
+            refs.truncate(lim);
             true
-        } else {
-            false
         }
-    } else {
-        false
+        _ => false,
     };
 
     eprintln!(
-        "{} Found {} call site(s) in {}ms{}\n",
+        "{} Found {} reference(s) in {}ms{}\n",
         "✓".green(),
         total_count.to_string().bold(),
         elapsed_ms.to_string().bold(),
@@ -2895,104 +8270,126 @@ fn cmd_callers(
     );
 
     let formatter = OutputFormatter::new(format);
-    let output = formatter.format_callers(&callers, &original_symbol);
+    let output = formatter.format_value_refs(&refs, &name);
     println!("{}", output);
 
     Ok(())
 }
 
-fn cmd_callees(
-    symbol: String,
+fn cmd_tests(
+    symbol: Option<String>,
     path: PathBuf,
+    list: bool,
+    orphans: bool,
     fuzzy: bool,
+    fast: bool,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
-    limit: Option<usize>,
     format: OutputFormat,
     cache_dir: Option<&Path>,
 ) -> Result<()> {
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    // `cm tests --list ./tests`: the one positional is the path
+    let path = match (&symbol, list || orphans) {
+        (Some(dir), true) if path == Path::new(".") => PathBuf::from(dir),
+        _ => path,
+    };
     let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
-    let original_symbol = symbol;
+    if list {
+        return cmd_list_tests(&index, format);
+    }
+    if orphans {
+        return cmd_orphan_tests(&index, format);
+    }
+
+    let original_symbol = symbol.unwrap_or_default();
     let symbol = normalize_qualified_name(&original_symbol);
 
     eprintln!(
-        "{} Finding callees of '{}'...",
+        "{} Finding tests for '{}'...",
         "→".cyan(),
         original_symbol.bold()
     );
 
-    let mut symbols = if fuzzy {
-        index.fuzzy_search(&symbol)
-    } else {
-        index.query_symbol(&symbol)
-    };
+    let start = Instant::now();
+    let candidates = prefilter_indexed_files(&index, &symbol, fast)?;
+    let tests = callgraph::find_tests_in(&index, &original_symbol, fuzzy, candidates.as_ref())?;
+    let elapsed_ms = start.elapsed().as_millis();
 
-    if symbols.is_empty() {
-        if let Some(pattern) = qualifier_pattern(&original_symbol) {
-            symbols = if fuzzy {
-                index.fuzzy_search(&pattern)
-            } else {
-                index.query_symbol(&pattern)
-            };
-        }
+    if tests.is_empty() {
+        println!("{} No tests found for '{}'", "✗".yellow(), symbol.bold());
+        return Ok(());
     }
 
-    if symbols.is_empty() {
-        println!(
-            "{} Symbol '{}' not found in codebase",
-            "✗".yellow(),
-            symbol.bold()
-        );
+    eprintln!(
+        "{} Found {} test(s) in {}ms\n",
+        "✓".green(),
+        tests.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_tests(&tests, &original_symbol);
+    println!("{}", output);
+
+    Ok(())
+}
+
+fn cmd_list_tests(index: &index::CodeIndex, format: OutputFormat) -> Result<()> {
+    eprintln!("{} Listing tests...", "→".cyan());
+
+    let start = Instant::now();
+    let tests = callgraph::list_tests(index);
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if tests.is_empty() {
+        println!("{} No tests found", "✗".yellow());
         return Ok(());
     }
 
+    eprintln!(
+        "{} Found {} test(s) in {}ms\n",
+        "✓".green(),
+        tests.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_test_list(&tests));
+
+    Ok(())
+}
+
+fn cmd_orphan_tests(index: &index::CodeIndex, format: OutputFormat) -> Result<()> {
+    eprintln!("{} Finding orphaned tests...", "→".cyan());
+
     let start = Instant::now();
-    let mut callees = callgraph::find_callees(&index, &symbol, fuzzy)?;
+    let orphans = callgraph::find_orphan_tests(index);
     let elapsed_ms = start.elapsed().as_millis();
 
-    if callees.is_empty() {
-        println!("{} No callees found for '{}'", "✗".yellow(), symbol.bold());
+    if orphans.is_empty() {
+        println!("{} No orphaned tests found", "✓".green());
         return Ok(());
     }
 
-    let total_count = callees.len();
-    let truncated = if let Some(lim) = limit {
-        if callees.len() > lim {
-            callees.truncate(lim);
-            true
-        } else {
-            false
-        }
-    } else {
-        false
-    };
-
     eprintln!(
-        "{} Found {} callee(s) in {}ms{}\n",
+        "{} Found {} orphaned test(s) in {}ms\n",
         "✓".green(),
-        total_count.to_string().bold(),
-        elapsed_ms.to_string().bold(),
-        if truncated {
-            format!(" (showing first {})", limit.unwrap())
-        } else {
-            String::new()
-        }
+        orphans.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
     );
 
     let formatter = OutputFormatter::new(format);
-    let output = formatter.format_callees(&callees, &original_symbol);
-    println!("{}", output);
+    println!("{}", formatter.format_orphan_tests(&orphans));
 
     Ok(())
 }
 
-fn cmd_tests(
+fn cmd_mocks(
     symbol: String,
     path: PathBuf,
-    fuzzy: bool,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
@@ -3000,45 +8397,40 @@ fn cmd_tests(
     cache_dir: Option<&Path>,
 ) -> Result<()> {
     let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
-    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
-
-    let original_symbol = symbol;
-    let symbol = normalize_qualified_name(&original_symbol);
-
-    eprintln!(
-        "{} Finding tests for '{}'...",
-        "→".cyan(),
-        original_symbol.bold()
-    );
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    eprintln!("{} Finding mocks of '{}'...", "→".cyan(), symbol.bold());
 
     let start = Instant::now();
-    let tests = callgraph::find_tests(&index, &original_symbol, fuzzy)?;
+    let sites = mocks::find_mocks(&index, &symbol);
     let elapsed_ms = start.elapsed().as_millis();
 
-    if tests.is_empty() {
-        println!("{} No tests found for '{}'", "✗".yellow(), symbol.bold());
+    if sites.is_empty() {
+        println!("{} No mocks found for '{}'", "✗".yellow(), symbol.bold());
         return Ok(());
     }
 
     eprintln!(
-        "{} Found {} test(s) in {}ms\n",
+        "{} Found {} mock(s) in {}ms\n",
         "✓".green(),
-        tests.len().to_string().bold(),
+        sites.len().to_string().bold(),
         elapsed_ms.to_string().bold()
     );
 
     let formatter = OutputFormatter::new(format);
-    let output = formatter.format_tests(&tests, &original_symbol);
-    println!("{}", output);
+    println!("{}", formatter.format_mocks(&sites, &symbol));
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_untested(
     path: PathBuf,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
+    fail_under: Option<f64>,
+    where_filter: Option<&filter_expr::Filter>,
     format: OutputFormat,
     cache_dir: Option<&Path>,
 ) -> Result<()> {
@@ -3048,10 +8440,21 @@ fn cmd_untested(
     eprintln!("{} Finding untested symbols...", "→".cyan());
 
     let start = Instant::now();
-    let untested = callgraph::find_untested(&index)?;
+    let mut untested = callgraph::find_untested(&index)?;
     let elapsed_ms = start.elapsed().as_millis();
 
-    let total_symbols = index.total_symbols();
+    let mut total_symbols = index.total_symbols();
+    // Tests are found in the whole index; coverage counts only the symbols that match
+    if let Some(filter) = where_filter {
+        let matching: HashSet<(String, usize)> = index
+            .all_symbols()
+            .into_iter()
+            .filter(|s| filter.matches(s))
+            .map(|s| (s.file_path.display_slash().to_string(), s.line_start))
+            .collect();
+        untested.retain(|info| matching.contains(&(info.file_path.clone(), info.line)));
+        total_symbols = matching.len();
+    }
     let tested_count = total_symbols.saturating_sub(untested.len());
     let coverage_pct = if total_symbols > 0 {
         (tested_count as f64 / total_symbols as f64) * 100.0
@@ -3071,6 +8474,96 @@ fn cmd_untested(
     let output = formatter.format_untested(&untested, total_symbols);
     println!("{}", output);
 
+    if let Some(minimum) = fail_under {
+        if coverage_pct < minimum {
+            assertion_failed(format!(
+                "Coverage {:.1}% is below the required {}%",
+                coverage_pct, minimum
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_deprecated(
+    path: PathBuf,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    max_calls: Option<usize>,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    eprintln!("{} Finding deprecated symbols...", "→".cyan());
+
+    let start = Instant::now();
+    let report = deprecated::find_deprecated(&index)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    eprintln!(
+        "{} Found {} deprecated symbol(s) with {} remaining call(s) in {}ms\n",
+        "✓".green(),
+        report.deprecations.len().to_string().bold(),
+        report.remaining_calls().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_deprecated(&report));
+
+    if let Some(maximum) = max_calls {
+        let remaining = report.remaining_calls();
+        if remaining > maximum {
+            assertion_failed(format!(
+                "{} calls still reach deprecated symbols, more than the allowed {}",
+                remaining, maximum
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_prompt(
+    path: PathBuf,
+    budget: usize,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    eprintln!("{} Ranking symbols by call sites...", "→".cyan());
+    let calls = callgraph::call_counts(&index);
+    let mut orientation = prompt::Orientation::build(&index, &path, &calls, 15);
+
+    let formatter = OutputFormatter::new(format);
+    let output = orientation.fit(budget, |o| formatter.format_prompt(o));
+    let tokens = prompt::estimate_tokens(&output);
+    if tokens > budget {
+        eprintln!(
+            "{} Overview alone is ~{} tokens, over the budget of {}",
+            "⚠".yellow(),
+            tokens,
+            budget
+        );
+    } else {
+        eprintln!(
+            "{} Orientation ready: ~{} tokens (budget {})",
+            "✓".green(),
+            tokens,
+            budget
+        );
+    }
+
+    print!("{}", output);
     Ok(())
 }
 
@@ -3183,9 +8676,11 @@ fn cmd_trace(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_test_deps(
     test_file: PathBuf,
     path: PathBuf,
+    fixtures: bool,
     extensions: String,
     no_cache: bool,
     rebuild_cache: bool,
@@ -3201,15 +8696,22 @@ fn cmd_test_deps(
     };
 
     if !abs_test_file.exists() {
-        anyhow::bail!("Test file not found: {}", test_file.display());
+        anyhow::bail!(Failure::new(
+            ErrorCode::PathNotFound,
+            format!("Test file not found: {}", test_file.display_slash())
+        ));
     }
 
     let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
 
+    if fixtures {
+        return cmd_fixtures(&index, &test_file, &abs_test_file, format);
+    }
+
     eprintln!(
         "{} Analyzing test file '{}'...",
         "→".cyan(),
-        test_file.display().to_string().bold()
+        test_file.display_slash().to_string().bold()
     );
 
     let start = Instant::now();
@@ -3220,7 +8722,7 @@ fn cmd_test_deps(
         println!(
             "{} No production symbols found in '{}'",
             "✗".yellow(),
-            test_file.display().to_string().bold()
+            test_file.display_slash().to_string().bold()
         );
         return Ok(());
     }
@@ -3233,12 +8735,51 @@ fn cmd_test_deps(
     );
 
     let formatter = OutputFormatter::new(format);
-    let output = formatter.format_test_deps(&deps, &test_file.display().to_string());
+    let output = formatter.format_test_deps(&deps, &test_file.display_slash().to_string());
     println!("{}", output);
 
     Ok(())
 }
 
+fn cmd_fixtures(
+    index: &index::CodeIndex,
+    test_path: &Path,
+    abs_test_path: &Path,
+    format: OutputFormat,
+) -> Result<()> {
+    eprintln!(
+        "{} Mapping fixtures and mocks used under '{}'...",
+        "→".cyan(),
+        test_path.display_slash().to_string().bold()
+    );
+
+    let start = Instant::now();
+    let scope = paths::canonicalize(abs_test_path)?;
+    let fixtures = fixtures::find_fixtures(index, Some(&scope));
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if fixtures.is_empty() {
+        println!(
+            "{} No fixtures, mocks or test helpers found under '{}'",
+            "✗".yellow(),
+            test_path.display_slash().to_string().bold()
+        );
+        return Ok(());
+    }
+
+    eprintln!(
+        "{} Found {} fixture(s), mock(s) and helper(s) in {}ms\n",
+        "✓".green(),
+        fixtures.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_fixtures(&fixtures));
+
+    Ok(())
+}
+
 fn cmd_blame(symbol: String, file: PathBuf, format: OutputFormat) -> Result<()> {
     eprintln!(
         "{} Finding last modification of '{}'...",
@@ -3264,6 +8805,159 @@ fn cmd_blame(symbol: String, file: PathBuf, format: OutputFormat) -> Result<()>
     Ok(())
 }
 
+fn cmd_blame_file(file: PathBuf, format: OutputFormat) -> Result<()> {
+    eprintln!(
+        "{} Blaming symbols of {}...",
+        "→".cyan(),
+        file.display_slash().to_string().bold()
+    );
+
+    let start = Instant::now();
+    let cwd = std::env::current_dir()?;
+    let blame = blame::blame_file(&cwd, &file)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if blame.is_empty() {
+        println!(
+            "{} No symbols found in {}",
+            "✗".yellow(),
+            file.display_slash().to_string().bold()
+        );
+        return Ok(());
+    }
+
+    eprintln!(
+        "{} Blamed {} symbol(s) in {}ms\n",
+        "✓".green(),
+        blame.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_file_blame(&blame, &file.display_slash().to_string());
+    println!("{}", output);
+
+    Ok(())
+}
+
+fn cmd_blame_batch(list: PathBuf, format: OutputFormat) -> Result<()> {
+    let entries = read_batch_list(&list)?;
+    eprintln!(
+        "{} Finding last modification of {} symbol(s)...",
+        "→".cyan(),
+        entries.len().to_string().bold()
+    );
+
+    let start = Instant::now();
+    let cwd = std::env::current_dir()?;
+    let results = blame::blame_batch(&cwd, &entries)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    let formatter = OutputFormatter::new(format);
+    let mut found = 0;
+    for (entry, result) in entries.iter().zip(&results) {
+        match result {
+            Ok(result) => {
+                found += 1;
+                println!("{}", formatter.format_blame(result));
+            }
+            Err(e) => println!("{}", format_batch_miss(&formatter, entry, e)),
+        }
+    }
+
+    finish_batch(found, entries.len(), elapsed_ms)
+}
+
+fn cmd_history_batch(list: PathBuf, format: OutputFormat) -> Result<()> {
+    let entries = read_batch_list(&list)?;
+    eprintln!(
+        "{} Tracing history of {} symbol(s)...",
+        "→".cyan(),
+        entries.len().to_string().bold()
+    );
+
+    let start = Instant::now();
+    let cwd = std::env::current_dir()?;
+    let results = blame::history_batch(&cwd, &entries)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    let formatter = OutputFormatter::new(format);
+    let mut found = 0;
+    for (entry, result) in entries.iter().zip(&results) {
+        match result {
+            Ok(history) if history.is_empty() => println!(
+                "{} No history found for '{}'",
+                "✗".yellow(),
+                entry.symbol.bold()
+            ),
+            Ok(history) => {
+                found += 1;
+                println!("{}", formatter.format_history(history, &entry.symbol));
+            }
+            Err(e) => println!("{}", format_batch_miss(&formatter, entry, e)),
+        }
+    }
+
+    finish_batch(found, entries.len(), elapsed_ms)
+}
+
+/// The `SYMBOL FILE` entries of a `--batch` list, read from stdin for `-`
+fn read_batch_list(list: &Path) -> Result<Vec<blame::BatchEntry>> {
+    use std::io::Read;
+
+    let text = if list.as_os_str() == "-" {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        std::fs::read_to_string(list)
+            .with_context(|| format!("Failed to read batch list {}", list.display_slash()))?
+    };
+    let entries = blame::parse_batch(&text)?;
+    if entries.is_empty() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!("No `SYMBOL FILE` entries in {}", list.display_slash())
+        ));
+    }
+    Ok(entries)
+}
+
+/// A batch entry that failed, printed in place of its result
+fn format_batch_miss(
+    formatter: &OutputFormatter,
+    entry: &blame::BatchEntry,
+    err: &anyhow::Error,
+) -> String {
+    formatter.format_error(
+        failure::code_of(err),
+        &format!(
+            "{} in {}: {:#}",
+            entry.symbol,
+            entry.file.display_slash(),
+            err
+        ),
+    )
+}
+
+/// Report how much of a batch resolved; fails only when nothing did
+fn finish_batch(found: usize, total: usize, elapsed_ms: u128) -> Result<()> {
+    if found == 0 {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("None of the {} batch entries could be resolved", total)
+        ));
+    }
+    eprintln!(
+        "{} Resolved {}/{} symbol(s) in {}ms",
+        "✓".green(),
+        found.to_string().bold(),
+        total,
+        elapsed_ms.to_string().bold()
+    );
+    Ok(())
+}
+
 fn cmd_history(symbol: String, file: PathBuf, format: OutputFormat) -> Result<()> {
     eprintln!("{} Tracing history of '{}'...", "→".cyan(), symbol.bold());
 
@@ -3291,6 +8985,41 @@ fn cmd_history(symbol: String, file: PathBuf, format: OutputFormat) -> Result<()
     Ok(())
 }
 
+fn cmd_history_file(file: PathBuf, format: OutputFormat) -> Result<()> {
+    eprintln!(
+        "{} Tracing API history of {}...",
+        "→".cyan(),
+        file.display_slash().to_string().bold()
+    );
+
+    let start = Instant::now();
+    let cwd = std::env::current_dir()?;
+    let history = blame::history_file_api(&cwd, &file)?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if history.is_empty() {
+        println!(
+            "{} No exported API history found for {}",
+            "✗".yellow(),
+            file.display_slash().to_string().bold()
+        );
+        return Ok(());
+    }
+
+    eprintln!(
+        "{} Found {} API-changing commit(s) in {}ms\n",
+        "✓".green(),
+        history.len().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    let output = formatter.format_file_history(&history, &file.display_slash().to_string());
+    println!("{}", output);
+
+    Ok(())
+}
+
 fn cmd_implements(
     interface: String,
     path: PathBuf,
@@ -3340,6 +9069,57 @@ fn cmd_implements(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
+fn cmd_hierarchy(
+    type_name: String,
+    path: PathBuf,
+    depth: usize,
+    extensions: String,
+    no_cache: bool,
+    rebuild_cache: bool,
+    format: OutputFormat,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let ext_list: Vec<&str> = extensions.split(',').map(|s| s.trim()).collect();
+    let index = try_load_or_rebuild(&path, &ext_list, no_cache, rebuild_cache, cache_dir)?;
+
+    eprintln!(
+        "{} Building type hierarchy of '{}'...",
+        "→".cyan(),
+        type_name.bold()
+    );
+
+    let start = Instant::now();
+    let edges = implements::all_edges(&index)?;
+    let hierarchy = hierarchy::build(&index, &edges, &type_name, depth);
+    let elapsed_ms = start.elapsed().as_millis();
+
+    if hierarchy.location.is_none()
+        && hierarchy.ancestors.is_empty()
+        && hierarchy.descendants.is_empty()
+    {
+        println!(
+            "{} No type or inheritance edges found for '{}'",
+            "✗".yellow(),
+            type_name.bold()
+        );
+        return Ok(());
+    }
+
+    eprintln!(
+        "{} Found {} ancestor(s) and {} descendant(s) in {}ms\n",
+        "✓".green(),
+        hierarchy.ancestor_count().to_string().bold(),
+        hierarchy.descendant_count().to_string().bold(),
+        elapsed_ms.to_string().bold()
+    );
+
+    let formatter = OutputFormatter::new(format);
+    println!("{}", formatter.format_hierarchy(&hierarchy));
+
+    Ok(())
+}
+
 fn cmd_types(
     symbol: String,
     path: PathBuf,
@@ -3534,3 +9314,188 @@ fn cmd_compare(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn query(path: &Path, symbol: &str, kind: Option<&str>) -> Result<usize> {
+        query_in(path, symbol, kind, None, None)
+    }
+
+    fn query_in(
+        path: &Path,
+        symbol: &str,
+        kind: Option<&str>,
+        within: Option<&Path>,
+        cache_dir: Option<&Path>,
+    ) -> Result<usize> {
+        cmd_query(
+            symbol.to_string(),
+            path.to_path_buf(),
+            within.map(Path::to_path_buf),
+            "minimal".to_string(),
+            false,
+            false,
+            false,
+            kind.map(str::to_string),
+            "py".to_string(),
+            cache_dir.is_none(),
+            false,
+            false,
+            false,
+            None,
+            None,
+            OutputFormat::Default,
+            None,
+            false,
+            false,
+            None,
+            cache_dir,
+        )
+    }
+
+    #[test]
+    fn test_query_rejects_unknown_type() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.py"), "def handler():\n    pass\n").unwrap();
+
+        assert_eq!(query(dir.path(), "handler", Some("function")).unwrap(), 1);
+        let err = query(dir.path(), "handler", Some("widget")).unwrap_err();
+        assert_eq!(failure::code_of(&err), ErrorCode::InvalidArgument);
+        assert!(err.to_string().contains("Invalid symbol type 'widget'"));
+    }
+
+    #[test]
+    fn test_query_in_filters_the_parent_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let (root, cache_dir) = (dir.path().join("repo"), dir.path().join("cache"));
+        for sub in ["api", "cli"] {
+            fs::create_dir_all(root.join(sub)).unwrap();
+            fs::write(root.join(sub).join("main.py"), "def handle():\n    pass\n").unwrap();
+        }
+
+        // A cache whose files also define `cached`, which is only found if the cache is read
+        let mut index = index::CodeIndex::new();
+        for sub in ["api", "cli"] {
+            let path = root.join(sub).join("main.py");
+            let source = "def handle():\n    pass\n\ndef cached():\n    pass\n";
+            index.add_file(
+                indexer::index_file(&path, source, models::Language::Python, None).unwrap(),
+            );
+        }
+        cache::CacheManager::save(&index, &root, &["py"], Some(&cache_dir)).unwrap();
+
+        assert_eq!(
+            query_in(&root, "cached", None, None, Some(&cache_dir)).unwrap(),
+            2
+        );
+        let api = root.join("api");
+        assert_eq!(
+            query_in(&root, "cached", None, Some(&api), Some(&cache_dir)).unwrap(),
+            1
+        );
+        assert_eq!(
+            query_in(&root, "handle", None, Some(&api), Some(&cache_dir)).unwrap(),
+            1
+        );
+
+        let outside = dir.path().join("cache");
+        let err = query_in(&root, "handle", None, Some(&outside), Some(&cache_dir)).unwrap_err();
+        assert_eq!(failure::code_of(&err), ErrorCode::InvalidArgument);
+    }
+
+    #[test]
+    fn test_inspect_many_parses_only_the_given_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("a.py"), "def a():\n    pass\n").unwrap();
+        fs::write(root.join("sub").join("b.py"), "def b():\n    pass\n").unwrap();
+        fs::write(root.join("sub").join("notes.txt"), "not code\n").unwrap();
+        fs::write(root.join("other.py"), "def other():\n    pass\n").unwrap();
+
+        let filter = InspectFilter {
+            skip_anonymous: false,
+            exports_only: false,
+            lines: None,
+            symbol: None,
+        };
+        let inspect = |paths: Vec<PathBuf>| {
+            cmd_inspect_many(
+                paths,
+                false,
+                &filter,
+                None,
+                "py".to_string(),
+                OutputFormat::AI,
+                None,
+            )
+        };
+
+        // A file named twice, once through `.`, is shown once
+        let paths = vec![
+            root.join("a.py"),
+            root.join("sub"),
+            root.join(".").join("a.py"),
+        ];
+        assert_eq!(inspect(paths).unwrap(), 2);
+        assert!(!root.join(".codemapper").exists());
+
+        let err = inspect(vec![root.join("a.py"), root.join("missing.py")]).unwrap_err();
+        assert_eq!(failure::code_of(&err), ErrorCode::PathNotFound);
+    }
+
+    #[test]
+    fn test_prefilter_indexed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, source) in [
+            (
+                "cart.py",
+                "class Cart:\n    def add_item(self):\n        pass\n",
+            ),
+            ("shop.py", "def buy(cart):\n    cart.ADD_ITEM()\n"),
+            ("other.py", "def unrelated():\n    pass\n"),
+        ] {
+            fs::write(dir.path().join(name), source).unwrap();
+        }
+        let index = indexer::index_directory(dir.path(), &["py"]).unwrap();
+        let names = |files: HashSet<PathBuf>| {
+            let mut names: Vec<String> = files
+                .iter()
+                .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        };
+
+        // Off below the threshold unless asked for
+        assert!(prefilter_indexed_files(&index, "add_item", false)
+            .unwrap()
+            .is_none());
+
+        // A superset of the files the scan can match: any case, regex characters literal
+        let found = prefilter_indexed_files(&index, "add_item", true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(names(found), ["cart.py", "shop.py"]);
+        let found = prefilter_indexed_files(&index, "cart.ADD_ITEM(", true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(names(found), ["shop.py"]);
+    }
+
+    #[test]
+    fn test_resolve_lang_lists_every_builtin_language() {
+        let (language, _) = resolve_lang("c++", PathBuf::from("a.cc"), false).unwrap();
+        assert_eq!(language, models::Language::Cpp);
+
+        let err = resolve_lang("cobol", PathBuf::from("a.cob"), true).unwrap_err();
+        assert_eq!(failure::code_of(&err), ErrorCode::UnsupportedLanguage);
+        for language in models::Language::BUILTIN {
+            assert!(err.to_string().contains(language.as_str()));
+            assert_eq!(models::Language::from_name(language.as_str()), language);
+        }
+    }
+}
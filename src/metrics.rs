@@ -0,0 +1,384 @@
+//! `cm map --metrics`: per-file health numbers for the level 2 map. Complexity is McCabe's,
+//! counted from the branch keywords and operators in each function's own lines (strings and
+//! comments skipped), so it can be off by a macro or a TypeScript `x?: T`. The maintainability
+//! index is the 0-100 form of the classic formula over Halstead volume, complexity and code
+//! lines.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use rayon::prelude::*;
+
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FileMetrics {
+    /// Sum of the cyclomatic complexity of every function and method
+    pub complexity: usize,
+    /// Highest complexity of a single function
+    pub max_complexity: usize,
+    /// Lines of the longest function or method
+    pub longest_function: usize,
+    /// 0 (hard to maintain) to 100
+    pub maintainability: f64,
+}
+
+/// Metrics of every indexed file that can still be read
+pub fn compute(index: &CodeIndex) -> HashMap<PathBuf, FileMetrics> {
+    let files: Vec<_> = index.files().collect();
+    files
+        .par_iter()
+        .filter_map(|file| {
+            let content = fs::read_to_string(&file.path).ok()?;
+            let symbols = index.get_file_symbols(&file.path);
+            let metrics = file_metrics(&content, file.language, &symbols, file.lines.code);
+            Some((file.path.clone(), metrics))
+        })
+        .collect()
+}
+
+pub fn file_metrics(
+    content: &str,
+    language: Language,
+    symbols: &[&Symbol],
+    code_lines: usize,
+) -> FileMetrics {
+    let lines = tokens_by_line(content, language);
+
+    // Each line belongs to the innermost function around it, so a closure's branches are
+    // not counted again for the function holding it
+    let mut functions: Vec<&Symbol> = symbols
+        .iter()
+        .copied()
+        .filter(|s| matches!(s.symbol_type, SymbolType::Function | SymbolType::Method))
+        .collect();
+    functions.sort_by_key(|s| std::cmp::Reverse(s.line_end.saturating_sub(s.line_start)));
+    let mut owner: Vec<Option<usize>> = vec![None; lines.len()];
+    for (i, function) in functions.iter().enumerate() {
+        let start = function.line_start.saturating_sub(1).min(lines.len());
+        let end = function.line_end.min(lines.len());
+        for slot in &mut owner[start..end] {
+            *slot = Some(i);
+        }
+    }
+
+    let mut branches = vec![0i64; functions.len()];
+    for (tokens, owner) in lines.iter().zip(&owner) {
+        if let Some(i) = owner {
+            branches[*i] += tokens
+                .iter()
+                .map(|t| branch_weight(t, language))
+                .sum::<i64>();
+        }
+    }
+    let complexities: Vec<usize> = branches.iter().map(|b| 1 + (*b).max(0) as usize).collect();
+    let complexity: usize = complexities.iter().sum();
+
+    FileMetrics {
+        complexity,
+        max_complexity: complexities.iter().copied().max().unwrap_or(0),
+        longest_function: functions
+            .iter()
+            .map(|s| s.line_end.saturating_sub(s.line_start) + 1)
+            .max()
+            .unwrap_or(0),
+        maintainability: maintainability(&lines, complexity, code_lines),
+    }
+}
+
+/// `171 - 5.2 ln(V) - 0.23 CC - 16.2 ln(LOC)`, scaled to 0-100, with Halstead volume V over
+/// every code token
+fn maintainability(lines: &[Vec<&str>], complexity: usize, code_lines: usize) -> f64 {
+    if code_lines == 0 {
+        return 100.0;
+    }
+    let total: usize = lines.iter().map(Vec::len).sum();
+    let distinct = lines
+        .iter()
+        .flatten()
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+    let volume = total as f64 * (distinct.max(2) as f64).log2();
+    let index = 171.0
+        - 5.2 * volume.max(1.0).ln()
+        - 0.23 * complexity as f64
+        - 16.2 * (code_lines as f64).ln();
+    (index * 100.0 / 171.0).clamp(0.0, 100.0)
+}
+
+/// What a token adds to the complexity of the function it is in. A Rust `match` with n arms
+/// adds n - 1.
+fn branch_weight(token: &str, language: Language) -> i64 {
+    let branch = match language {
+        Language::Python => matches!(
+            token,
+            "if" | "elif" | "for" | "while" | "except" | "and" | "or" | "case"
+        ),
+        Language::Rust => match token {
+            "match" => return -1,
+            _ => matches!(token, "if" | "for" | "while" | "=>" | "&&" | "||"),
+        },
+        Language::Go => matches!(token, "if" | "for" | "case" | "&&" | "||"),
+        // A `when` with n arms adds n - 1; a lambda's `->` counts as an arm too
+        Language::Kotlin => match token {
+            "when" => return -1,
+            _ => matches!(token, "if" | "for" | "while" | "catch" | "->" | "&&" | "||"),
+        },
+        Language::Swift => matches!(
+            token,
+            "if" | "guard" | "for" | "while" | "case" | "catch" | "&&" | "||" | "?" | "??"
+        ),
+        // A `match` with n cases adds n - 1; a `catch` block's cases count on their own
+        Language::Scala => match token {
+            "match" => return -1,
+            _ => matches!(token, "if" | "for" | "while" | "case" | "&&" | "||"),
+        },
+        Language::Lua => matches!(
+            token,
+            "if" | "elseif" | "for" | "while" | "until" | "and" | "or"
+        ),
+        // A `switch` with n prongs adds n - 1
+        Language::Zig => match token {
+            "switch" => return -1,
+            _ => matches!(
+                token,
+                "if" | "for" | "while" | "=>" | "catch" | "orelse" | "and" | "or"
+            ),
+        },
+        Language::Php => matches!(
+            token,
+            "if" | "elseif"
+                | "for"
+                | "foreach"
+                | "while"
+                | "case"
+                | "catch"
+                | "&&"
+                | "||"
+                | "and"
+                | "or"
+                | "?"
+                | "??"
+        ),
+        Language::Markdown => false,
+        _ => matches!(
+            token,
+            "if" | "for" | "while" | "case" | "catch" | "&&" | "||" | "?" | "??"
+        ),
+    };
+    branch as i64
+}
+
+/// Operators read as one token
+const OPERATORS: [&str; 19] = [
+    "&&", "||", "=>", "??", "?.", "==", "!=", "<=", ">=", "->", "::", "+=", "-=", "*=", "/=", "<<",
+    ">>", "++", "--",
+];
+
+/// Code tokens of each line: words, numbers and operators. Comments are dropped and a string
+/// literal is a single `""` token.
+fn tokens_by_line(content: &str, language: Language) -> Vec<Vec<&str>> {
+    let python = language == Language::Python;
+    let php = language == Language::Php;
+    let lua = language == Language::Lua;
+    let backticks = matches!(
+        language,
+        Language::JavaScript | Language::TypeScript | Language::Go
+    );
+    let char_literals = matches!(
+        language,
+        Language::Rust
+            | Language::C
+            | Language::Cpp
+            | Language::Java
+            | Language::Kotlin
+            | Language::Scala
+            | Language::Go
+            | Language::Zig
+    );
+
+    let mut lines = Vec::new();
+    // Closing delimiter of the block comment or string the scan is in
+    let mut open: Option<&str> = None;
+    for line in content.lines() {
+        let mut tokens: Vec<&str> = Vec::new();
+        let mut rest = line;
+        loop {
+            if let Some(close) = open {
+                let Some(end) = rest.find(close) else {
+                    break;
+                };
+                rest = &rest[end + close.len()..];
+                open = None;
+            }
+            rest = rest.trim_start();
+            let Some(c) = rest.chars().next() else {
+                break;
+            };
+
+            // Lua's `--[[ ]]` comments and `[[ ]]` strings
+            if lua && rest.starts_with("--[[") {
+                open = Some("]]");
+                rest = &rest[4..];
+                continue;
+            }
+            if lua && rest.starts_with("[[") {
+                tokens.push("\"\"");
+                open = Some("]]");
+                rest = &rest[2..];
+                continue;
+            }
+            let comment = match language {
+                Language::Python => "#",
+                Language::Lua => "--",
+                _ => "//",
+            };
+            if rest.starts_with(comment)
+                || (php && rest.starts_with('#') && !rest.starts_with("#["))
+            {
+                break;
+            }
+            if !python && !lua && rest.starts_with("/*") {
+                open = Some("*/");
+                rest = &rest[2..];
+                continue;
+            }
+            if python {
+                if let Some(quotes) = ["\"\"\"", "'''"].into_iter().find(|q| rest.starts_with(q)) {
+                    tokens.push("\"\"");
+                    open = Some(quotes);
+                    rest = &rest[3..];
+                    continue;
+                }
+            }
+            let quote = match c {
+                '"' => Some('"'),
+                '`' if backticks => Some('`'),
+                '\'' if !char_literals => Some('\''),
+                '\'' => {
+                    // A char literal, or a Rust lifetime with no closing quote
+                    let body = &rest[1..];
+                    let len = match body.strip_prefix('\\') {
+                        Some(escape) => escape.get(1..).and_then(|e| e.find('\'')).map(|i| i + 3),
+                        None => {
+                            let mut chars = body.chars();
+                            let c = chars.next();
+                            c.filter(|_| chars.next() == Some('\''))
+                                .map(|c| c.len_utf8() + 1)
+                        }
+                    };
+                    match len {
+                        Some(len) => {
+                            tokens.push("\"\"");
+                            rest = &body[len..];
+                        }
+                        None => rest = body,
+                    }
+                    continue;
+                }
+                _ => None,
+            };
+            if let Some(quote) = quote {
+                tokens.push("\"\"");
+                match string_end(&rest[1..], quote) {
+                    Some(end) => rest = &rest[1 + end..],
+                    None => {
+                        // Only backtick strings and Python's go on past the line
+                        if quote == '`' {
+                            open = Some("`");
+                        }
+                        break;
+                    }
+                }
+                continue;
+            }
+
+            let len = if c.is_alphanumeric() || c == '_' {
+                rest.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len())
+            } else {
+                OPERATORS
+                    .iter()
+                    .find(|op| rest.starts_with(*op))
+                    .map_or(c.len_utf8(), |op| op.len())
+            };
+            tokens.push(&rest[..len]);
+            rest = &rest[len..];
+        }
+        lines.push(tokens);
+    }
+    lines
+}
+
+/// Byte offset just past the unescaped `quote` closing a string in `text`
+fn string_end(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '\\' => escaped = !escaped,
+            c if c == quote && !escaped => return Some(i + 1),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn function(line_start: usize, line_end: usize) -> Symbol {
+        Symbol {
+            name: "f".into(),
+            symbol_type: SymbolType::Function,
+            signature: None,
+            docstring: None,
+            line_start,
+            line_end,
+            parent_id: None,
+            file_path: Path::new("a.py").into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_complexity_counts_branches_per_function() {
+        let source = r#"def parse(text):
+    # if this were a comment it would not count
+    if not text or text == "if":
+        return None
+    for part in text.split(","):
+        def check(p):
+            return p if p else None
+        check(part)
+    return text
+"#;
+        let outer = function(1, 9);
+        let inner = function(6, 7);
+        let metrics = file_metrics(source, Language::Python, &[&outer, &inner], 8);
+        // parse: 1 + if, or, for; check: 1 + if
+        assert_eq!(metrics.complexity, 6);
+        assert_eq!(metrics.max_complexity, 4);
+        assert_eq!(metrics.longest_function, 9);
+        assert!(metrics.maintainability > 0.0 && metrics.maintainability < 100.0);
+    }
+
+    #[test]
+    fn test_rust_match_arms() {
+        let source = "fn f(x: Option<&'a str>) -> u8 {\n    match x {\n        Some(\"=>\") => 1,\n        Some(_) => 2,\n        None => 3,\n    }\n}\n";
+        let metrics = file_metrics(source, Language::Rust, &[&function(1, 7)], 7);
+        assert_eq!(metrics.complexity, 3);
+    }
+
+    #[test]
+    fn test_lua_comments_and_long_strings() {
+        let source = "function f(x)\n  -- if this were code\n  --[[ while\n  for ]]\n  local s = [[if\n  or]]\n  if x and x > 1 then return 1 end\n  return 0\nend\n";
+        let metrics = file_metrics(source, Language::Lua, &[&function(1, 8)], 8);
+        assert_eq!(metrics.complexity, 3);
+    }
+}
@@ -0,0 +1,362 @@
+//! `cm mocks`: where tests mock or patch a symbol, which are the tests to revisit when its
+//! interface changes. Reads the mocks `cm test-deps --fixtures` knows (`patch`, `patch.object`,
+//! `monkeypatch.setattr`, `jest.mock`, `vi.mock`, `jest.spyOn`) plus Mockito's and MockK's
+//! `mock(T.class)`, `@Mock T`, `mockk<T>()`, `when(x.f(...))`, `verify(x).f(...)` and
+//! `every { x.f(...) }`. Targets are matched by name, so `patch("app.views.fetch")` counts for
+//! a `fetch` defined in `app/api.py`, where the test patches the name it is looked up by.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::callgraph::is_test_file;
+use crate::fixtures::{decorators, mock_patterns};
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+use crate::value_refs::enclosing_symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockMatch {
+    /// The target ends with the symbol's name: `app.api.fetch`, `Client.send`, `repo.find`
+    Name,
+    /// The target is the class the method is defined in: `mock(Client.class)`
+    Class,
+    /// The target is the module the symbol is defined in: `jest.mock('./api')`
+    Module,
+}
+
+impl MockMatch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MockMatch::Name => "name",
+            MockMatch::Class => "class",
+            MockMatch::Module => "module",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockSite {
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// What is mocked, as written: a module path, class or `object.attribute`
+    pub target: String,
+    pub matched: MockMatch,
+    /// The test, fixture or test class doing it, or the function a decorator applies to
+    pub test: Option<String>,
+    /// The source line, trimmed
+    pub context: String,
+}
+
+/// Where `symbol` itself, its class or its module is defined, to match mock targets against
+struct Definitions {
+    name: String,
+    /// `Client` for `Client.send`
+    qualifier: Option<String>,
+    /// By language family, so a Python `api.py` is not what `jest.mock('./api')` mocks
+    classes: HashSet<(Language, String)>,
+    modules: HashSet<(Language, String)>,
+}
+
+impl Definitions {
+    fn new(index: &CodeIndex, symbol: &str) -> Self {
+        let (qualifier, name) = match symbol.rsplit_once('.') {
+            Some((qualifier, name)) => (Some(qualifier), name),
+            None => (None, symbol),
+        };
+        let qualifier = qualifier.map(|q| q.rsplit('.').next().unwrap_or(q).to_string());
+
+        let mut classes = HashSet::new();
+        let mut modules = HashSet::new();
+        for definition in index.query_symbol(name) {
+            if matches!(
+                definition.symbol_type,
+                SymbolType::Heading | SymbolType::CodeBlock
+            ) {
+                continue;
+            }
+            let class = enclosing_class(index, definition).map(|c| c.name.to_string());
+            if qualifier.is_some() && class != qualifier {
+                continue;
+            }
+            let extension = definition.file_path.extension().unwrap_or_default();
+            let family = family(Language::from_extension(&extension.to_string_lossy()));
+            classes.extend(class.map(|class| (family, class)));
+            modules.extend(module_name(&definition.file_path).map(|module| (family, module)));
+        }
+
+        Self {
+            name: name.to_string(),
+            qualifier,
+            classes,
+            modules,
+        }
+    }
+
+    /// How `target` mocks the symbol, if it does. `module` is whether the target can be a
+    /// module path rather than an attribute of an object.
+    fn matches(&self, target: &str, module: bool, language: Language) -> Option<MockMatch> {
+        let segments = segments(target);
+        let last = *segments.last()?;
+        let key = (family(language), last.to_string());
+        let owner = segments.len().checked_sub(2).map(|i| segments[i]);
+        if last == self.name
+            && self
+                .qualifier
+                .as_deref()
+                .is_none_or(|qualifier| owner == Some(qualifier))
+        {
+            return Some(MockMatch::Name);
+        }
+        if self.classes.contains(&key) {
+            return Some(MockMatch::Class);
+        }
+        (module && self.modules.contains(&key)).then_some(MockMatch::Module)
+    }
+}
+
+/// Languages that import each other's files and classes
+fn family(language: Language) -> Language {
+    match language {
+        Language::TypeScript => Language::JavaScript,
+        Language::Kotlin => Language::Java,
+        language => language,
+    }
+}
+
+/// The class, interface or enum a method is declared in
+fn enclosing_class<'a>(index: &'a CodeIndex, method: &Symbol) -> Option<&'a Symbol> {
+    if method.symbol_type != SymbolType::Method {
+        return None;
+    }
+    index
+        .get_file_symbols(&method.file_path)
+        .into_iter()
+        .filter(|s| {
+            matches!(
+                s.symbol_type,
+                SymbolType::Class | SymbolType::Interface | SymbolType::Enum
+            )
+        })
+        .filter(|s| s.line_start <= method.line_start && s.line_end >= method.line_end)
+        .filter(|s| !std::ptr::eq(*s, method))
+        .min_by_key(|s| s.line_end - s.line_start)
+}
+
+/// The name a file is imported by: its stem, or its directory's for `index.ts`,
+/// `__init__.py`, `mod.rs` and `init.lua`
+fn module_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let name = match stem {
+        "index" | "__init__" | "mod" | "init" => path.parent()?.file_name()?.to_str()?,
+        _ => stem,
+    };
+    Some(name.to_string())
+}
+
+/// `app.api.fetch` → `[app, api, fetch]`; `../services/api.js` → `[services, api]`
+fn segments(target: &str) -> Vec<&str> {
+    if target.contains('/') || target.starts_with('.') {
+        let mut segments: Vec<&str> = target
+            .split('/')
+            .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+            .collect();
+        if let Some(last) = segments.last_mut() {
+            *last = last.split('.').next().unwrap_or(last);
+        }
+        segments
+    } else {
+        target.split('.').filter(|s| !s.is_empty()).collect()
+    }
+}
+
+/// Every place a test file mocks `symbol` (a name, or `Class.method`), sorted by file and line
+pub fn find_mocks(index: &CodeIndex, symbol: &str) -> Vec<MockSite> {
+    let definitions = Definitions::new(index, symbol);
+    let mut sites = Vec::new();
+
+    for file in index.files() {
+        let conftest = file.path.file_name().is_some_and(|n| n == "conftest.py");
+        if !is_test_file(&file.path, file.language) && !conftest {
+            continue;
+        }
+        let patterns = mock_patterns(file.language);
+        if patterns.is_empty() {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&file.path) else {
+            continue;
+        };
+        if !content.contains(definitions.name.as_str())
+            && !definitions
+                .classes
+                .iter()
+                .chain(&definitions.modules)
+                .any(|(_, name)| content.contains(name.as_str()))
+        {
+            continue;
+        }
+        let lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+        // Annotations and stubs may span lines, so patterns run over the whole file
+        let mut seen = HashSet::new();
+        for pattern in &patterns {
+            for caps in pattern.captures_iter(&content) {
+                let (target, module) = match (caps.get(1), caps.get(2)) {
+                    (Some(object), Some(attribute)) => {
+                        (format!("{}.{}", object.as_str(), attribute.as_str()), false)
+                    }
+                    (Some(target), None) => (target.as_str().to_string(), true),
+                    _ => continue,
+                };
+                let Some(matched) = definitions.matches(&target, module, file.language) else {
+                    continue;
+                };
+                let start = caps.get(0).map_or(0, |m| m.start());
+                let line = content[..start].matches('\n').count() + 1;
+                let context = lines[line - 1].trim();
+                if context.starts_with("//") || context.starts_with('#') {
+                    continue;
+                }
+                if !seen.insert((line, target.clone())) {
+                    continue;
+                }
+                sites.push(MockSite {
+                    file_path: file.path.clone(),
+                    line,
+                    test: test_at(index, &file.path, &lines, line),
+                    context: context.to_string(),
+                    target,
+                    matched,
+                });
+            }
+        }
+    }
+
+    sites.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+    sites
+}
+
+/// The test a mock on `line` belongs to: the function a `@patch` decorator sits on, or else the
+/// innermost symbol around the line
+fn test_at(index: &CodeIndex, path: &Path, lines: &[String], line: usize) -> Option<String> {
+    if lines[line - 1].trim_start().starts_with('@') {
+        let decorated = index
+            .get_file_symbols(path)
+            .into_iter()
+            .filter(|s| s.line_start > line)
+            .filter(|s| matches!(s.symbol_type, SymbolType::Function | SymbolType::Method))
+            .min_by_key(|s| s.line_start)
+            .filter(|s| decorators(lines, s.line_start - 1).0 < line);
+        if let Some(decorated) = decorated {
+            return Some(decorated.name.to_string());
+        }
+    }
+    enclosing_symbol(index, path, line).map(|s| s.name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+
+    fn found(index: &CodeIndex, symbol: &str) -> Vec<(String, usize, String, &'static str)> {
+        find_mocks(index, symbol)
+            .into_iter()
+            .map(|site| {
+                (
+                    site.test.unwrap_or_default(),
+                    site.line,
+                    site.target,
+                    site.matched.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_python_and_jest_mocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = index_of(
+            dir.path(),
+            &[
+                (
+                    "app/api.py",
+                    "def fetch(url):\n    pass\n\nclass Client:\n    def send(self):\n        pass\n",
+                    Language::Python,
+                ),
+                (
+                    "tests/test_api.py",
+                    "from unittest.mock import patch\n\n@patch(\"app.views.fetch\")\ndef test_view(fetch):\n    pass\n\ndef test_send(mocker):\n    mocker.patch.object(Client, \"send\")\n    # patch(\"app.api.fetch\")\n    mocker.patch(\"app.api\")\n",
+                    Language::Python,
+                ),
+                (
+                    "src/api.ts",
+                    "export function fetchUser(id: string) {}\n",
+                    Language::TypeScript,
+                ),
+                (
+                    "src/user.test.ts",
+                    "jest.mock('./api');\njest.mock('axios');\n\ntest('loads', () => {\n  jest.spyOn(api, 'fetchUser');\n});\n",
+                    Language::TypeScript,
+                ),
+            ],
+        );
+
+        assert_eq!(
+            found(&index, "fetch"),
+            [
+                ("test_view".into(), 3, "app.views.fetch".into(), "name"),
+                ("test_send".into(), 10, "app.api".into(), "module"),
+            ]
+        );
+        assert_eq!(
+            found(&index, "Client.send"),
+            [
+                ("test_send".into(), 8, "Client.send".into(), "name"),
+                ("test_send".into(), 10, "app.api".into(), "module"),
+            ]
+        );
+        assert_eq!(
+            found(&index, "fetchUser"),
+            [
+                (String::new(), 1, "./api".into(), "module"),
+                ("test:loads".into(), 5, "api.fetchUser".into(), "name"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mockito_mocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = index_of(
+            dir.path(),
+            &[
+                (
+                    "src/main/java/OrderRepository.java",
+                    "public class OrderRepository {\n    public Order find(int id) { return null; }\n}\n",
+                    Language::Java,
+                ),
+                (
+                    "src/test/java/OrderServiceTest.java",
+                    "public class OrderServiceTest {\n    @Mock\n    private OrderRepository repo;\n\n    @Test\n    public void loads() {\n        when(repo.find(1)).thenReturn(order);\n        verify(repo).find(1);\n    }\n}\n",
+                    Language::Java,
+                ),
+            ],
+        );
+
+        assert_eq!(
+            found(&index, "find"),
+            [
+                (
+                    "OrderServiceTest".into(),
+                    2,
+                    "OrderRepository".into(),
+                    "class"
+                ),
+                ("loads".into(), 7, "repo.find".into(), "name"),
+                ("loads".into(), 8, "repo.find".into(), "name"),
+            ]
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use crate::annotations::Annotation;
+use crate::kinds::{self, KindId};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Language {
@@ -10,13 +13,43 @@ pub enum Language {
     Java,
     Go,
     C,
+    Cpp,
+    CSharp,
+    Php,
+    Kotlin,
+    Swift,
+    Scala,
+    Lua,
+    Zig,
     Markdown,
+    /// Parsed by a parser registered at runtime (see `parser::registry`)
+    Plugin,
     Unknown,
 }
 
 impl Language {
+    /// Every language with a built-in parser
+    pub const BUILTIN: [Language; 16] = [
+        Language::Python,
+        Language::JavaScript,
+        Language::TypeScript,
+        Language::Rust,
+        Language::Java,
+        Language::Go,
+        Language::C,
+        Language::Cpp,
+        Language::CSharp,
+        Language::Php,
+        Language::Kotlin,
+        Language::Swift,
+        Language::Scala,
+        Language::Lua,
+        Language::Zig,
+        Language::Markdown,
+    ];
+
     pub fn from_extension(ext: &str) -> Self {
-        match ext {
+        match ext.to_ascii_lowercase().as_str() {
             "py" => Language::Python,
             "js" | "jsx" => Language::JavaScript,
             "ts" | "tsx" => Language::TypeScript,
@@ -24,11 +57,43 @@ impl Language {
             "java" => Language::Java,
             "go" => Language::Go,
             "c" | "h" => Language::C,
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => Language::Cpp,
+            "cs" => Language::CSharp,
+            "php" => Language::Php,
+            "kt" | "kts" => Language::Kotlin,
+            "swift" => Language::Swift,
+            "scala" | "sc" => Language::Scala,
+            "lua" => Language::Lua,
+            "zig" => Language::Zig,
             "md" => Language::Markdown,
             _ => Language::Unknown,
         }
     }
 
+    /// Accepts a language name (`"python"`, `"golang"`) or a file extension (`"py"`, `".py"`)
+    pub fn from_name(name: &str) -> Self {
+        let name = name.trim().trim_start_matches('.').to_lowercase();
+        match name.as_str() {
+            "python" => Language::Python,
+            "javascript" => Language::JavaScript,
+            "typescript" => Language::TypeScript,
+            "rust" => Language::Rust,
+            "java" => Language::Java,
+            "go" | "golang" => Language::Go,
+            "c" => Language::C,
+            "c++" => Language::Cpp,
+            "csharp" | "c#" => Language::CSharp,
+            "php" => Language::Php,
+            "kotlin" => Language::Kotlin,
+            "swift" => Language::Swift,
+            "scala" => Language::Scala,
+            "lua" => Language::Lua,
+            "zig" => Language::Zig,
+            "markdown" => Language::Markdown,
+            ext => Language::from_extension(ext),
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Language::Python => "python",
@@ -38,13 +103,66 @@ impl Language {
             Language::Java => "java",
             Language::Go => "go",
             Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::CSharp => "csharp",
+            Language::Php => "php",
+            Language::Kotlin => "kotlin",
+            Language::Swift => "swift",
+            Language::Scala => "scala",
+            Language::Lua => "lua",
+            Language::Zig => "zig",
             Language::Markdown => "markdown",
+            Language::Plugin => "plugin",
             Language::Unknown => "unknown",
         }
     }
+
+    /// Kinds the language's parser produces. Plugins report kinds by name, so they can
+    /// produce any built-in kind and every custom one.
+    pub fn symbol_types(&self) -> &'static [SymbolType] {
+        use SymbolType::*;
+        match self {
+            Language::Python | Language::Rust | Language::Java => {
+                &[Function, Class, Method, Enum, StaticField, Field]
+            }
+            Language::JavaScript => &[Function, Class, Method, Field],
+            Language::TypeScript => &[
+                Function,
+                Class,
+                Method,
+                Enum,
+                StaticField,
+                Interface,
+                TypeAlias,
+                Field,
+            ],
+            Language::Go => &[Function, Class, Method, StaticField, Field],
+            Language::C => &[Function, Class],
+            Language::Cpp => &[Function, Class, Method, Enum],
+            Language::CSharp => &[Class, Method, Enum, StaticField, Interface, Field],
+            Language::Php => &[Function, Class, Method, Enum, StaticField, Interface, Field],
+            Language::Kotlin | Language::Swift | Language::Scala => &[
+                Function,
+                Class,
+                Method,
+                Enum,
+                StaticField,
+                Interface,
+                TypeAlias,
+                Field,
+            ],
+            Language::Lua => &[Function, Class, Method, StaticField],
+            Language::Zig => &[Function, Class, Method, Enum, StaticField, Field],
+            Language::Markdown => &[Heading, CodeBlock],
+            Language::Plugin => &SymbolType::BUILTIN,
+            Language::Unknown => &[],
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Serializes as its name (`"function"`, `"static"`, `"component"`), the same word the
+/// default format prints and `--type` takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SymbolType {
     Function,
     Class,
@@ -55,9 +173,26 @@ pub enum SymbolType {
     CodeBlock,
     Interface,
     TypeAlias,
+    Field,
+    /// Declared in config or by a plugin (see `kinds`)
+    Custom(KindId),
 }
 
 impl SymbolType {
+    /// Every kind the parsers know without configuration
+    pub const BUILTIN: [SymbolType; 10] = [
+        SymbolType::Function,
+        SymbolType::Class,
+        SymbolType::Method,
+        SymbolType::Enum,
+        SymbolType::StaticField,
+        SymbolType::Heading,
+        SymbolType::CodeBlock,
+        SymbolType::Interface,
+        SymbolType::TypeAlias,
+        SymbolType::Field,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             SymbolType::Function => "function",
@@ -69,10 +204,40 @@ impl SymbolType {
             SymbolType::CodeBlock => "code_block",
             SymbolType::Interface => "interface",
             SymbolType::TypeAlias => "type",
+            SymbolType::Field => "field",
+            SymbolType::Custom(kind) => kind.name(),
+        }
+    }
+
+    /// Short code used by the ai format
+    pub fn code(&self) -> &'static str {
+        match self {
+            SymbolType::Function => "f",
+            SymbolType::Class => "c",
+            SymbolType::Method => "m",
+            SymbolType::Enum => "e",
+            SymbolType::StaticField => "s",
+            SymbolType::Heading => "h",
+            SymbolType::CodeBlock => "cb",
+            SymbolType::Interface => "if",
+            SymbolType::TypeAlias => "ty",
+            SymbolType::Field => "fd",
+            SymbolType::Custom(kind) => kind.code(),
         }
     }
 
+    /// A built-in kind or a registered custom one, by name
     pub fn from_str(s: &str) -> Option<Self> {
+        Self::builtin_from_str(s).or_else(|| kinds::lookup(s, false).map(SymbolType::Custom))
+    }
+
+    /// A built-in kind or a registered custom one, by plural (`--type components`)
+    pub fn from_plural(s: &str) -> Option<Self> {
+        Self::builtin_from_plural(s).or_else(|| kinds::lookup(s, true).map(SymbolType::Custom))
+    }
+
+    /// A built-in kind, by name or one of its aliases
+    pub fn builtin_from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "function" | "func" | "fn" => Some(SymbolType::Function),
             "class" => Some(SymbolType::Class),
@@ -83,11 +248,12 @@ impl SymbolType {
             "code_block" | "codeblock" => Some(SymbolType::CodeBlock),
             "interface" => Some(SymbolType::Interface),
             "type" | "typealias" | "type_alias" => Some(SymbolType::TypeAlias),
+            "field" | "property" | "attribute" => Some(SymbolType::Field),
             _ => None,
         }
     }
 
-    pub fn from_plural(s: &str) -> Option<Self> {
+    pub fn builtin_from_plural(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "functions" | "funcs" | "fns" => Some(SymbolType::Function),
             "classes" => Some(SymbolType::Class),
@@ -98,22 +264,138 @@ impl SymbolType {
             "code_blocks" | "codeblocks" => Some(SymbolType::CodeBlock),
             "interfaces" => Some(SymbolType::Interface),
             "types" | "typealiases" | "type_aliases" => Some(SymbolType::TypeAlias),
+            "fields" => Some(SymbolType::Field),
             _ => None,
         }
     }
 }
 
+impl Serialize for SymbolType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SymbolType {
+    /// Also takes the variant names older snapshots were written with (`"StaticField"`), and
+    /// registers a kind no longer declared so what was saved under it still loads
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(SymbolType::from_str(&name)
+            .unwrap_or_else(|| SymbolType::Custom(kinds::lookup_or_register(&name))))
+    }
+}
+
+/// A symbol name. Clones share one allocation, so the index stores each distinct
+/// name (`new`, `fmt`, `__init__`, ...) once no matter how many symbols use it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Name(Arc<str>);
+
+impl Name {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::borrow::Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Name(name.into())
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Name(name.into())
+    }
+}
+
+impl From<Name> for String {
+    fn from(name: Name) -> Self {
+        name.0.to_string()
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<Name> for str {
+    fn eq(&self, other: &Name) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<Name> for &str {
+    fn eq(&self, other: &Name) -> bool {
+        *self == &*other.0
+    }
+}
+
+impl PartialEq<Name> for String {
+    fn eq(&self, other: &Name) -> bool {
+        **self == *other.0
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Symbol {
-    pub name: String,
+    pub name: Name,
     pub symbol_type: SymbolType,
     pub signature: Option<String>,
     pub docstring: Option<String>,
     pub line_start: usize,
     pub line_end: usize,
     pub parent_id: Option<usize>,
-    pub file_path: PathBuf,
+    pub file_path: Arc<Path>,
     pub is_exported: bool,
+    /// Byte offsets of lines `line_start..=line_end` in the file, set by the indexer so
+    /// bodies can be read without loading the whole file
+    #[serde(default)]
+    pub byte_range: Option<(usize, usize)>,
+    /// `@cm:` markers written for this symbol, set by the indexer
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,14 +404,83 @@ pub struct Dependency {
     pub from_file: Option<String>,
 }
 
+/// A name a file uses, recorded when the file is indexed so callers and usages are looked
+/// up in the index instead of by parsing every file again
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    /// As written at the use site
+    pub name: String,
+    pub kind: ReferenceKind,
+    pub line: usize,
+}
+
+impl Reference {
+    /// The name without a `Type::` or `receiver.` qualifier, as the index keys it
+    pub fn target(&self) -> &str {
+        unqualified(&self.name)
+    }
+}
+
+/// `load` for `Config::load` or `config.load`
+pub fn unqualified(name: &str) -> &str {
+    let name = name.trim();
+    name.rsplit_once("::")
+        .or_else(|| name.rsplit_once('.'))
+        .map_or(name, |(_, tail)| tail)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ReferenceKind {
+    /// A function, method or macro call
+    Call,
+    /// A name an import statement binds
+    Import,
+}
+
+impl ReferenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReferenceKind::Call => "call",
+            ReferenceKind::Import => "import",
+        }
+    }
+}
+
+/// Lines of a file by what they hold. A line with both code and a trailing comment is code;
+/// blank lines inside a block comment are blank.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineCounts {
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+}
+
+impl LineCounts {
+    pub fn total(&self) -> usize {
+        self.code + self.comment + self.blank
+    }
+
+    pub fn add(&mut self, other: LineCounts) {
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
     pub path: PathBuf,
     pub language: Language,
     pub size: u64,
     pub hash: String,
+    #[serde(default)]
+    pub lines: LineCounts,
     pub symbols: Vec<Symbol>,
     pub dependencies: Vec<Dependency>,
+    /// Calls and imported names, or `None` when they were not recorded. The index persists
+    /// them in its own layout.
+    #[serde(skip)]
+    pub references: Option<Vec<Reference>>,
 }
 
 impl FileInfo {
@@ -139,8 +490,10 @@ impl FileInfo {
             language,
             size,
             hash,
+            lines: LineCounts::default(),
             symbols: Vec::new(),
             dependencies: Vec::new(),
+            references: None,
         }
     }
 }
@@ -1,25 +1,69 @@
-use crate::blame::{BlameResult, HistoryEntry};
+use crate::annotations::Annotation;
+use crate::audit::{Finding, Rule};
+use crate::blame::{BlameResult, FileApiEntry, HistoryEntry, SymbolBlame};
 use crate::callgraph::{
-    CallInfo, EntrypointCategory, EntrypointInfo, TestDep, TestInfo, TracePath, UntestedInfo,
+    CallInfo, EntrypointCategory, EntrypointInfo, OrphanTest, TestCase, TestDep, TestInfo,
+    TracePath, UntestedInfo,
 };
-use crate::diff::{ChangeType, DiffResult, SymbolDiff};
+use crate::cli_map::CliCommand;
+use crate::confidence::Confidence;
+use crate::dedupe::Alternates;
+use crate::deprecated::DeprecationReport;
+use crate::dep_tree::DepNode;
+use crate::diff::{ChangeType, ChangedSymbol, DiffResult, HunkLine, SymbolChange, SymbolDiff};
+use crate::embed::SemanticMatch;
+use crate::envvars::EnvVar;
+use crate::errors::ErrorReport;
+use crate::extract::Snippet;
+use crate::failure::ErrorCode;
+use crate::fingerprint::Drift;
+use crate::fixtures::Fixture;
+use crate::flags::Flag;
+use crate::hierarchy::{Hierarchy, HierarchyNode};
+use crate::history::{self, CommandRecord};
 use crate::implements::Implementation;
 use crate::index::CodeIndex;
-use crate::models::{Symbol, SymbolType};
+use crate::indexer::{SkipReason, SkippedFile, VendoredStats};
+use crate::interest::FileOfInterest;
+use crate::kinds::KindInfo;
+use crate::layers::{LayerReport, Violation};
+use crate::logs::{LogCall, LogLevel};
+use crate::metrics::FileMetrics;
+use crate::mocks::MockSite;
+use crate::models::{LineCounts, Symbol, SymbolType};
+use crate::paths::DisplaySlash;
+use crate::prompt::Orientation;
+use crate::recency::{FileChange, Recency};
+use crate::routes::Route;
 use crate::schema::SchemaInfo;
+use crate::semver::{Bump, SemverAdvice};
+use crate::sets::SymbolSet;
+use crate::shadows::{Shadow, Similarity};
 use crate::snapshot::Snapshot;
+use crate::sql::SqlQuery;
+use crate::stats::{self, DirRollup, SizeNode, TopReport};
+use crate::strings::StringMatch;
+use crate::summary;
 use crate::types::SymbolTypes;
+use crate::value_refs::{RefKind, ValueRef};
 use colored::*;
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Table};
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OutputFormat {
     Default,
     Human,
     AI,
+    /// `file:line:col: kind name — signature`, one location per line for editors
+    Compact,
+    /// Structured JSON; commands without a JSON form print their default output
+    Json,
 }
 
 impl OutputFormat {
@@ -28,8 +72,10 @@ impl OutputFormat {
             "default" => Ok(Self::Default),
             "human" => Ok(Self::Human),
             "ai" => Ok(Self::AI),
+            "compact" => Ok(Self::Compact),
+            "json" => Ok(Self::Json),
             _ => Err(format!(
-                "Invalid format '{}'. Valid options: default, human, ai",
+                "Invalid format '{}'. Valid options: default, human, ai, compact, json",
                 s
             )),
         }
@@ -38,6 +84,183 @@ impl OutputFormat {
 
 pub struct OutputFormatter {
     format: OutputFormat,
+    /// Cached prose summaries keyed by `summary::file_key` / `summary::symbol_key`
+    summaries: HashMap<String, String>,
+    /// Locations `cm query` collapsed into each shown symbol
+    alternates: Alternates,
+    bodies: RefCell<BodyReader>,
+    /// Changes since `cm map --since`, marked on files and symbols
+    recency: Option<Recency>,
+    /// Complexity and maintainability per file for `cm map --metrics`
+    metrics: Option<HashMap<PathBuf, FileMetrics>>,
+}
+
+const OPEN_FILE_LIMIT: usize = 8;
+
+/// Logging calls per level, then per enclosing symbol
+type LogGroups<'a> = Vec<(LogLevel, Vec<(&'a str, Vec<&'a LogCall>)>)>;
+
+/// Reads symbol bodies by byte range, keeping the last few files open (most recent last)
+#[derive(Default)]
+struct BodyReader {
+    open: Vec<(Arc<Path>, File)>,
+}
+
+impl BodyReader {
+    fn read(&mut self, path: &Arc<Path>, start: usize, end: usize) -> Option<String> {
+        let entry = match self.open.iter().position(|(p, _)| p == path) {
+            Some(i) => self.open.remove(i),
+            None => (path.clone(), File::open(path).ok()?),
+        };
+        if self.open.len() >= OPEN_FILE_LIMIT {
+            self.open.remove(0);
+        }
+        self.open.push(entry);
+
+        let (_, file) = self.open.last_mut()?;
+        file.seek(SeekFrom::Start(start as u64)).ok()?;
+        let mut buf = vec![0; end.checked_sub(start)?];
+        file.read_exact(&mut buf).ok()?;
+        String::from_utf8(buf).ok()
+    }
+}
+
+/// Diff bases are usually commit hashes but may be a worktree path
+/// `old.rs:10-14 -> new.rs:12-17`, or just the side that exists
+fn symbol_change_location(change: &SymbolChange) -> String {
+    let side = |file: &Option<PathBuf>, lines: Option<(usize, usize)>| {
+        file.as_ref()
+            .zip(lines)
+            .map(|(file, (start, end))| format!("{}:{}-{}", file.display_slash(), start, end))
+    };
+    match (
+        side(&change.old_file, change.old_lines),
+        side(&change.new_file, change.new_lines),
+    ) {
+        (Some(old), Some(new)) if old == new => old,
+        (Some(old), Some(new)) => format!("{} -> {}", old, new),
+        (old, new) => old.or(new).unwrap_or_default(),
+    }
+}
+
+fn is_commit_hash(reference: &str) -> bool {
+    reference.len() >= 8 && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// One problem-matcher line (VS Code `$gcc`-style, vim `errorformat=%f:%l:%c:\ %m`).
+/// Columns are not tracked, so symbols always start at column 1.
+/// `file:line` wrapped in `quote`, or "(external)" for types defined outside the project
+fn hierarchy_location(location: Option<&(PathBuf, usize)>, quote: &str) -> String {
+    match location {
+        Some((file, line)) => format!("{}{}:{}{}", quote, file.display_slash(), line, quote),
+        None => "(external)".to_string(),
+    }
+}
+
+fn compact_line(
+    file: &str,
+    line: usize,
+    kind: &str,
+    name: &str,
+    detail: Option<&str>,
+) -> String {
+    match detail {
+        Some(detail) => format!("{}:{}:1: {} {} — {}\n", file, line, kind, name, detail),
+        None => format!("{}:{}:1: {} {}\n", file, line, kind, name),
+    }
+}
+
+/// `file:line` of a layer violation, or just the file when the import line wasn't found
+fn violation_site(violation: &Violation) -> String {
+    match violation.line {
+        Some(line) => format!("{}:{}", violation.file.display_slash(), line),
+        None => violation.file.display_slash().to_string(),
+    }
+}
+
+fn compact_violation(violation: &Violation) -> String {
+    compact_line(
+        &violation.file.display_slash().to_string(),
+        violation.line.unwrap_or(1),
+        "layer-violation",
+        &format!("{} -> {}", violation.from_layer, violation.to_layer),
+        Some(&violation.import),
+    )
+}
+
+/// A test with the suites it is nested in: `Cart > add > counts items`
+fn test_label(test: &TestCase) -> String {
+    let mut parts: Vec<&str> = test.suites.iter().map(String::as_str).collect();
+    parts.push(&test.name);
+    parts.join(" > ")
+}
+
+/// `@cm:` annotations in the `ai` format: `entrypoint;owner=team-payments`
+fn annotations_ai(annotations: &[Annotation]) -> String {
+    annotations
+        .iter()
+        .map(|a| match &a.value {
+            Some(value) => format!("{}={}", a.tag, value),
+            None => a.tag.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// `@cm:` annotations as written, space-separated
+fn annotations_text(annotations: &[Annotation]) -> String {
+    annotations
+        .iter()
+        .map(|a| a.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Confidence for a table cell; anything short of `exact-ast` is worth a look
+fn confidence_cell(confidence: Confidence) -> String {
+    match confidence {
+        Confidence::ExactAst => confidence.as_str().to_string(),
+        Confidence::NameMatch => confidence.as_str().yellow().to_string(),
+        Confidence::TextMatch => confidence.as_str().red().to_string(),
+    }
+}
+
+fn kind_languages(info: &KindInfo) -> Vec<&'static str> {
+    info.languages
+        .iter()
+        .map(|language| language.as_str())
+        .collect()
+}
+
+fn compact_diff_line(sym: &SymbolDiff) -> String {
+    let line = sym.new_lines.or(sym.old_lines).map(|(start, _)| start).unwrap_or(1);
+    let kind = format!(
+        "{} {}",
+        sym.change_type.as_str().to_lowercase(),
+        sym.symbol_type.as_str()
+    );
+    let detail = match (&sym.old_name, &sym.old_signature, &sym.new_signature) {
+        (Some(old_name), _, _) => Some(format!("was {}", old_name)),
+        (None, Some(old), Some(new)) if sym.change_type == ChangeType::SignatureChanged => {
+            Some(format!("{} -> {}", old, new))
+        }
+        (None, old, new) => new.clone().or_else(|| old.clone()),
+    };
+    compact_line(
+        &sym.file_path.display_slash().to_string(),
+        line,
+        &kind,
+        &sym.name,
+        detail.as_deref(),
+    )
+}
+
+fn short_ref(reference: &str) -> &str {
+    if is_commit_hash(reference) {
+        &reference[..8]
+    } else {
+        reference
+    }
 }
 
 /// Read specific lines from a file (1-indexed line numbers)
@@ -57,134 +280,765 @@ fn read_file_lines(path: &Path, start_line: usize, end_line: usize) -> Option<St
         return None;
     }
 
-    let selected_lines = &lines[start_idx..end_idx];
+    Some(number_lines(start_line, &lines[start_idx..end_idx]))
+}
+
+fn number_lines(start_line: usize, lines: &[&str]) -> String {
     let mut result = String::new();
 
-    for (i, line) in selected_lines.iter().enumerate() {
+    for (i, line) in lines.iter().enumerate() {
         let line_num = start_line + i;
         result.push_str(&format!("{:4} | {}\n", line_num, line));
     }
 
-    Some(result)
+    result
+}
+
+/// A directory or file line of `cm map --mode sizes`, or the siblings past `--limit` folded
+/// into one `… N more` line
+struct SizeRow {
+    depth: usize,
+    /// Name as listed under its parent: `src/`, `main.rs`, `… 12 more`
+    label: String,
+    /// From the mapped path: `./src/main.rs`
+    path: String,
+    kind: &'static str,
+    lines: usize,
+    symbols: usize,
+}
+
+/// Directories and files of `tree` depth first, at most `limit` per directory
+fn size_rows(tree: &SizeNode, limit: usize) -> Vec<SizeRow> {
+    fn walk(node: &SizeNode, path: &str, depth: usize, limit: usize, rows: &mut Vec<SizeRow>) {
+        let listed: Vec<&SizeNode> = node
+            .children
+            .iter()
+            .filter(|c| c.kind == "dir" || c.kind == "file")
+            .collect();
+        for child in listed.iter().take(limit) {
+            let child_path = format!("{}/{}", path, child.name);
+            let is_dir = child.kind == "dir";
+            rows.push(SizeRow {
+                depth,
+                label: format!("{}{}", child.name, if is_dir { "/" } else { "" }),
+                path: child_path.clone(),
+                kind: child.kind,
+                lines: child.lines,
+                symbols: child.symbols,
+            });
+            if is_dir {
+                walk(child, &child_path, depth + 1, limit, rows);
+            }
+        }
+        if listed.len() > limit {
+            let rest = &listed[limit..];
+            rows.push(SizeRow {
+                depth,
+                label: format!("… {} more", rest.len()),
+                path: format!("{}/…", path),
+                kind: "more",
+                lines: rest.iter().map(|c| c.lines).sum(),
+                symbols: rest.iter().map(|c| c.symbols).sum(),
+            });
+        }
+    }
+
+    let mut rows = Vec::new();
+    walk(tree, tree.name.trim_end_matches('/'), 0, limit, &mut rows);
+    rows
+}
+
+/// `new file` or `3 commits, 40 lines changed`, with the age of the newest commit
+fn recency_text(change: &FileChange) -> String {
+    let what = match (change.added, change.commits) {
+        (true, _) => "new file".to_string(),
+        (false, 0) => "uncommitted".to_string(),
+        (false, 1) => "1 commit".to_string(),
+        (false, n) => format!("{} commits", n),
+    };
+    let age = change.last_commit.map(|time| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        format!(", last {}d ago", (now - time).max(0) / 86_400)
+    });
+    format!(
+        "{}, {} lines changed{}",
+        what,
+        change.lines(),
+        age.unwrap_or_default()
+    )
+}
+
+/// `complexity 34 (max 9), longest function 52 lines, maintainability 61`
+fn metrics_text(metrics: FileMetrics) -> String {
+    format!(
+        "complexity {} (max {}), longest function {} lines, maintainability {:.0}",
+        metrics.complexity,
+        metrics.max_complexity,
+        metrics.longest_function,
+        metrics.maintainability
+    )
+}
+
+/// Commits a file blame attributes symbols to, counting uncommitted changes as one
+fn distinct_commits(blame: &[SymbolBlame]) -> usize {
+    blame
+        .iter()
+        .map(|b| b.commit.as_ref().map(|c| c.hash.as_str()))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Symbol counts per kind, e.g. `function: 12, method: 30`
+fn kind_breakdown(by_type: &[(SymbolType, usize)], separator: &str, joiner: &str) -> String {
+    by_type
+        .iter()
+        .map(|(kind, count)| format!("{}{}{}", kind.as_str(), separator, count))
+        .collect::<Vec<_>>()
+        .join(joiner)
 }
 
 impl OutputFormatter {
     pub fn new(format: OutputFormat) -> Self {
-        Self { format }
+        Self {
+            format,
+            summaries: HashMap::new(),
+            alternates: Alternates::new(),
+            bodies: RefCell::new(BodyReader::default()),
+            recency: None,
+            metrics: None,
+        }
     }
 
-    pub fn format_map(&self, index: &CodeIndex, level: u8) -> String {
+    pub fn with_summaries(mut self, summaries: HashMap<String, String>) -> Self {
+        self.summaries = summaries;
+        self
+    }
+
+    pub fn with_alternates(mut self, alternates: Alternates) -> Self {
+        self.alternates = alternates;
+        self
+    }
+
+    pub fn with_recency(mut self, recency: Recency) -> Self {
+        self.recency = Some(recency);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: HashMap<PathBuf, FileMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn file_change(&self, path: &Path) -> Option<&FileChange> {
+        self.recency.as_ref()?.file(path)
+    }
+
+    /// `+` for a symbol in a file added since the base, `~` for one whose lines changed
+    fn symbol_marker(&self, symbol: &Symbol) -> Option<&'static str> {
+        let change = self.file_change(&symbol.file_path)?;
+        match change.added {
+            true => Some("+"),
+            false => change.touches(symbol).then_some("~"),
+        }
+    }
+
+    /// `path:line (reason)` of each duplicate collapsed into `symbol`
+    fn alternate_locations(&self, symbol: &Symbol) -> Vec<String> {
+        self.alternates
+            .get(&(symbol.file_path.clone(), symbol.line_start))
+            .into_iter()
+            .flatten()
+            .map(|a| {
+                format!(
+                    "{}:{} ({})",
+                    a.file_path.display_slash(),
+                    a.line_start,
+                    a.reason.as_str()
+                )
+            })
+            .collect()
+    }
+
+    /// Numbered source lines of a symbol: a seek to its indexed byte range, or a full
+    /// read of the file if there is no range or the file no longer matches it
+    fn read_body(&self, symbol: &Symbol) -> Option<String> {
+        if let Some((start, end)) = symbol.byte_range {
+            let text = self.bodies.borrow_mut().read(&symbol.file_path, start, end);
+            if let Some(text) = text {
+                let lines: Vec<&str> = text.lines().collect();
+                if lines.len() == symbol.line_end + 1 - symbol.line_start {
+                    return Some(number_lines(symbol.line_start, &lines));
+                }
+            }
+        }
+
+        read_file_lines(&symbol.file_path, symbol.line_start, symbol.line_end)
+    }
+
+    fn file_summary(&self, path: &Path) -> Option<&str> {
+        if self.summaries.is_empty() {
+            return None;
+        }
+        self.summaries
+            .get(&summary::file_key(path))
+            .map(|s| s.as_str())
+    }
+
+    fn symbol_summary(&self, symbol: &Symbol) -> Option<&str> {
+        if self.summaries.is_empty() {
+            return None;
+        }
+        self.summaries
+            .get(&summary::symbol_key(symbol))
+            .map(|s| s.as_str())
+    }
+
+    /// Streams the map as it is produced, so `--level 3` on a large repository starts
+    /// printing right away instead of building the whole output first
+    pub fn write_map(&self, out: &mut impl Write, index: &CodeIndex, level: u8) -> io::Result<()> {
         match self.format {
-            OutputFormat::Default => self.format_map_default(index, level),
-            OutputFormat::Human => self.format_map_human(index, level),
-            OutputFormat::AI => self.format_map_ai(index, level),
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.write_map_default(out, index, level)
+            }
+            OutputFormat::Human => self.write_map_human(out, index, level),
+            OutputFormat::AI => self.write_map_ai(out, index, level),
         }
     }
 
-    fn format_map_default(&self, index: &CodeIndex, level: u8) -> String {
-        let mut output = String::new();
-        output.push_str("# Project Overview\n\n");
+    /// Level 1 overview followed by one rollup per directory (`cm map --group-by dir`)
+    pub fn write_dir_map(
+        &self,
+        out: &mut impl Write,
+        index: &CodeIndex,
+        rollups: &[DirRollup],
+        depth: usize,
+    ) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.write_map_default(out, index, 1)?;
+                self.write_dir_map_default(out, rollups, depth)
+            }
+            OutputFormat::Human => {
+                self.write_map_human(out, index, 1)?;
+                self.write_dir_map_human(out, rollups, depth)
+            }
+            OutputFormat::AI => {
+                self.write_map_ai(out, index, 1)?;
+                self.write_dir_map_ai(out, rollups, depth)
+            }
+        }
+    }
 
-        let mut lang_counts: HashMap<&str, usize> = HashMap::new();
-        for file in index.files() {
-            *lang_counts.entry(file.language.as_str()).or_insert(0) += 1;
+    fn write_dir_map_default(
+        &self,
+        out: &mut impl Write,
+        rollups: &[DirRollup],
+        depth: usize,
+    ) -> io::Result<()> {
+        writeln!(out, "\n## Directories (depth {})\n", depth)?;
+        for rollup in rollups {
+            writeln!(out, "### {}", rollup.dir.display_slash())?;
+            writeln!(
+                out,
+                "- Files: {} ({} code / {} comment / {} blank lines)",
+                rollup.files, rollup.lines.code, rollup.lines.comment, rollup.lines.blank
+            )?;
+            writeln!(
+                out,
+                "- Symbols: {} ({})",
+                rollup.symbols,
+                kind_breakdown(&rollup.by_type, ": ", ", ")
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_dir_map_human(
+        &self,
+        out: &mut impl Write,
+        rollups: &[DirRollup],
+        depth: usize,
+    ) -> io::Result<()> {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec![
+                "Directory",
+                "Files",
+                "Code",
+                "Comment",
+                "Blank",
+                "Symbols",
+                "By Kind",
+            ]);
+
+        for rollup in rollups {
+            table.add_row(vec![
+                rollup.dir.display_slash().to_string(),
+                rollup.files.to_string(),
+                rollup.lines.code.to_string(),
+                rollup.lines.comment.to_string(),
+                rollup.lines.blank.to_string(),
+                rollup.symbols.to_string(),
+                kind_breakdown(&rollup.by_type, " ", ", "),
+            ]);
+        }
+
+        writeln!(
+            out,
+            "\n{}\n",
+            format!("Directories (depth {})", depth).cyan()
+        )?;
+        writeln!(out, "{}", table)
+    }
+
+    fn write_dir_map_ai(
+        &self,
+        out: &mut impl Write,
+        rollups: &[DirRollup],
+        depth: usize,
+    ) -> io::Result<()> {
+        writeln!(out, "\n[DIRS:depth={}]", depth)?;
+        for rollup in rollups {
+            writeln!(
+                out,
+                "{}|files:{}|loc:{}/{}/{}|syms:{}|{}",
+                rollup.dir.display_slash(),
+                rollup.files,
+                rollup.lines.code,
+                rollup.lines.comment,
+                rollup.lines.blank,
+                rollup.symbols,
+                kind_breakdown(&rollup.by_type, ":", ",")
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn format_map(&self, index: &CodeIndex, level: u8) -> String {
+        let mut buf = Vec::new();
+        let _ = self.write_map(&mut buf, index, level);
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    /// Size tree for `cm map --mode sizes`: the whole tree, symbols included, as JSON for
+    /// treemap tools; directories and files, at most `limit` per directory, otherwise
+    pub fn format_size_tree(&self, tree: &SizeNode, limit: usize) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact => {
+                self.format_size_tree_default(tree, limit)
+            }
+            OutputFormat::Human => self.format_size_tree_human(tree, limit),
+            OutputFormat::AI => self.format_size_tree_ai(tree, limit),
+            OutputFormat::Json => serde_json::to_string(tree).unwrap_or_default(),
         }
+    }
 
-        output.push_str("## Languages\n");
-        for (lang, count) in &lang_counts {
-            output.push_str(&format!("- {}: {} files\n", lang, count));
+    fn share(lines: usize, total: usize) -> f64 {
+        if total == 0 {
+            0.0
+        } else {
+            lines as f64 * 100.0 / total as f64
         }
+    }
 
-        output.push_str(&format!("\n## Statistics\n"));
-        output.push_str(&format!("- Total files: {}\n", index.total_files()));
-        output.push_str(&format!("- Total symbols: {}\n", index.total_symbols()));
+    fn format_size_tree_default(&self, tree: &SizeNode, limit: usize) -> String {
+        let mut output = String::new();
+        output.push_str("# Sizes\n\n");
         output.push_str(&format!(
-            "  - Functions: {}\n",
-            index.symbols_by_type(SymbolType::Function)
+            "`{}`: {} lines, {} symbols\n\n",
+            tree.name, tree.lines, tree.symbols
         ));
+        for row in size_rows(tree, limit) {
+            let label = match row.kind {
+                "dir" => format!("**{}**", row.label),
+                _ => row.label,
+            };
+            output.push_str(&format!(
+                "{}- {} — {} lines ({:.1}%), {} symbols\n",
+                "  ".repeat(row.depth),
+                label,
+                row.lines,
+                Self::share(row.lines, tree.lines),
+                row.symbols
+            ));
+        }
+        output
+    }
+
+    /// An indented tree with one bar per directory and file, scaled to the whole tree
+    fn format_size_tree_human(&self, tree: &SizeNode, limit: usize) -> String {
+        const BAR_WIDTH: f64 = 30.0;
+        let rows = size_rows(tree, limit);
+        let width = rows
+            .iter()
+            .map(|row| row.depth * 2 + row.label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(48);
+
+        let mut output = String::new();
         output.push_str(&format!(
-            "  - Classes: {}\n",
-            index.symbols_by_type(SymbolType::Class)
+            "{} {} — {} lines, {} symbols\n\n",
+            "Sizes of".bold().green(),
+            tree.name.bold(),
+            tree.lines,
+            tree.symbols
         ));
+        for row in rows {
+            let share = Self::share(row.lines, tree.lines);
+            let cells = (share / 100.0 * BAR_WIDTH).round() as usize;
+            let bar = if cells == 0 && row.lines > 0 {
+                "▏".to_string()
+            } else {
+                "█".repeat(cells)
+            };
+            let label = format!("{}{}", "  ".repeat(row.depth), row.label);
+            let label = format!("{:<width$}", label, width = width);
+            let label = match row.kind {
+                "dir" => label.blue().bold().to_string(),
+                "more" => label.dimmed().to_string(),
+                _ => label,
+            };
+            output.push_str(&format!(
+                "{}  {}  {:>7}  {:>5.1}%  {} symbols\n",
+                label,
+                format!("{:<30}", bar).cyan(),
+                row.lines,
+                share,
+                row.symbols
+            ));
+        }
+        output
+    }
+
+    fn format_size_tree_ai(&self, tree: &SizeNode, limit: usize) -> String {
+        let mut output = String::new();
         output.push_str(&format!(
-            "  - Methods: {}\n",
-            index.symbols_by_type(SymbolType::Method)
+            "[SIZES:{}|lines={}|syms={}]\n",
+            tree.name, tree.lines, tree.symbols
         ));
-        output.push_str(&format!(
-            "  - Enums: {}\n",
+        output.push_str("path|kind|lines|syms|pct\n");
+        for row in size_rows(tree, limit) {
+            let kind = match row.kind {
+                "dir" => "d",
+                "file" => "f",
+                _ => "rest",
+            };
+            output.push_str(&format!(
+                "{}|{}|{}|{}|{:.1}\n",
+                row.path,
+                kind,
+                row.lines,
+                row.symbols,
+                Self::share(row.lines, tree.lines)
+            ));
+        }
+        output
+    }
+
+    /// Ranked shortlist for `cm map --mode files-of-interest`
+    pub fn format_files_of_interest(&self, files: &[FileOfInterest]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_files_of_interest_default(files)
+            }
+            OutputFormat::Human => self.format_files_of_interest_human(files),
+            OutputFormat::AI => self.format_files_of_interest_ai(files),
+        }
+    }
+
+    /// The file's cached summary, or its most-called symbols
+    fn interest_descriptor(&self, file: &FileOfInterest) -> String {
+        match self.file_summary(file.path) {
+            Some(summary) => summary.to_string(),
+            None => format!("defines {}", file.top_symbols.join(", ")),
+        }
+    }
+
+    fn age(days: Option<u64>) -> String {
+        match days {
+            Some(0) => "today".to_string(),
+            Some(days) => format!("{}d ago", days),
+            None => "-".to_string(),
+        }
+    }
+
+    fn format_files_of_interest_default(&self, files: &[FileOfInterest]) -> String {
+        let mut output = String::new();
+        output.push_str("# Files of Interest\n\n");
+        output.push_str(
+            "Ranked by symbols, exports, calls from the rest of the project and recent changes\n\n",
+        );
+
+        for (rank, file) in files.iter().enumerate() {
+            output.push_str(&format!(
+                "{}. {} ({}, score {:.2})\n",
+                rank + 1,
+                file.path.display_slash(),
+                file.language.as_str(),
+                file.score
+            ));
+            output.push_str(&format!(
+                "   - {} symbols, {} exported, imported by {}, {} calls in, changed {}\n",
+                file.symbols,
+                file.exports,
+                file.importers,
+                file.calls,
+                Self::age(file.age_days)
+            ));
+            output.push_str(&format!("   - {}\n", self.interest_descriptor(file)));
+        }
+
+        output
+    }
+
+    fn format_files_of_interest_human(&self, files: &[FileOfInterest]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{}\n\n", "Files of Interest".bold().green()));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec![
+                "#",
+                "File",
+                "Score",
+                "Symbols",
+                "Exported",
+                "Imported By",
+                "Calls In",
+                "Changed",
+                "About",
+            ]);
+
+        for (rank, file) in files.iter().enumerate() {
+            table.add_row(vec![
+                (rank + 1).to_string(),
+                file.path.display_slash().to_string(),
+                format!("{:.2}", file.score),
+                file.symbols.to_string(),
+                file.exports.to_string(),
+                file.importers.to_string(),
+                file.calls.to_string(),
+                Self::age(file.age_days),
+                self.interest_descriptor(file),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_files_of_interest_ai(&self, files: &[FileOfInterest]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[FILES_OF_INTEREST:{}]\n", files.len()));
+        output.push_str("file|score|syms|exp|imp_by|calls|age_d|about\n");
+        for file in files {
+            let age = file
+                .age_days
+                .map_or_else(|| "-".to_string(), |days| days.to_string());
+            output.push_str(&format!(
+                "{}|{:.2}|{}|{}|{}|{}|{}|{}\n",
+                file.path.display_slash(),
+                file.score,
+                file.symbols,
+                file.exports,
+                file.importers,
+                file.calls,
+                age,
+                self.interest_descriptor(file)
+            ));
+        }
+        output
+    }
+
+    fn write_map_default(
+        &self,
+        out: &mut impl Write,
+        index: &CodeIndex,
+        level: u8,
+    ) -> io::Result<()> {
+        writeln!(out, "# Project Overview\n")?;
+
+        let mut lang_counts: HashMap<&str, (usize, LineCounts)> = HashMap::new();
+        for file in index.files() {
+            let (files, lines) = lang_counts.entry(file.language.as_str()).or_default();
+            *files += 1;
+            lines.add(file.lines);
+        }
+
+        let mut lang_counts: Vec<_> = lang_counts.into_iter().collect();
+        lang_counts.sort_by_key(|(lang, _)| *lang);
+        writeln!(out, "## Languages")?;
+        for (lang, (count, lines)) in &lang_counts {
+            writeln!(
+                out,
+                "- {}: {} files, {} code / {} comment / {} blank lines",
+                lang, count, lines.code, lines.comment, lines.blank
+            )?;
+        }
+
+        writeln!(out, "\n## Statistics")?;
+        writeln!(out, "- Total files: {}", index.total_files())?;
+        writeln!(out, "- Total symbols: {}", index.total_symbols())?;
+        writeln!(
+            out,
+            "  - Functions: {}",
+            index.symbols_by_type(SymbolType::Function)
+        )?;
+        writeln!(
+            out,
+            "  - Classes: {}",
+            index.symbols_by_type(SymbolType::Class)
+        )?;
+        writeln!(
+            out,
+            "  - Methods: {}",
+            index.symbols_by_type(SymbolType::Method)
+        )?;
+        writeln!(
+            out,
+            "  - Enums: {}",
             index.symbols_by_type(SymbolType::Enum)
-        ));
-        output.push_str(&format!(
-            "  - Static Fields: {}\n",
+        )?;
+        writeln!(
+            out,
+            "  - Static Fields: {}",
             index.symbols_by_type(SymbolType::StaticField)
-        ));
-        output.push_str(&format!(
-            "  - Headings: {}\n",
+        )?;
+        writeln!(
+            out,
+            "  - Headings: {}",
             index.symbols_by_type(SymbolType::Heading)
-        ));
-        output.push_str(&format!(
-            "  - Code Blocks: {}\n",
+        )?;
+        writeln!(
+            out,
+            "  - Code Blocks: {}",
             index.symbols_by_type(SymbolType::CodeBlock)
-        ));
+        )?;
+
+        if let Some(recency) = &self.recency {
+            let (files, added) = recency.file_counts();
+            writeln!(out, "\n## Changed Since {}", recency.label)?;
+            if let Some(base) = &recency.base {
+                writeln!(out, "- Base commit: {}", base)?;
+            }
+            writeln!(out, "- Commits: {}", recency.commits)?;
+            writeln!(out, "- Files changed: {} ({} new)", files, added)?;
+            writeln!(out, "- Symbols changed: {}", recency.changed_symbols(index))?;
+        }
 
         if level >= 2 {
-            output.push_str("\n## Files\n\n");
+            writeln!(out, "\n## Files\n")?;
             for file in index.files() {
-                output.push_str(&format!("### {}\n", file.path.display()));
-                output.push_str(&format!("- Language: {}\n", file.language.as_str()));
-                output.push_str(&format!("- Size: {} bytes\n", file.size));
+                writeln!(out, "### {}", file.path.display_slash())?;
+                writeln!(out, "- Language: {}", file.language.as_str())?;
+                writeln!(
+                    out,
+                    "- Lines: {} code, {} comment, {} blank",
+                    file.lines.code, file.lines.comment, file.lines.blank
+                )?;
+                if let Some(change) = self.file_change(&file.path) {
+                    writeln!(out, "- Changed: {}", recency_text(change))?;
+                }
+                if let Some(metrics) = self.metrics.as_ref() {
+                    writeln!(
+                        out,
+                        "- Metrics: {}",
+                        metrics_text(metrics.get(&file.path).copied().unwrap_or_default())
+                    )?;
+                }
+                if let Some(summary) = self.file_summary(&file.path) {
+                    writeln!(out, "- Summary: {}", summary)?;
+                }
 
                 let symbols = index.get_file_symbols(&file.path);
                 if !symbols.is_empty() {
-                    output.push_str(&format!("- Symbols: {}\n", symbols.len()));
+                    writeln!(out, "- Symbols: {}", symbols.len())?;
 
                     if level >= 3 {
                         for symbol in symbols {
-                            output.push_str(&format!(
+                            // Fields nest under their type as `name: Type`
+                            if symbol.symbol_type == SymbolType::Field {
+                                writeln!(
+                                    out,
+                                    "    - {}: {} (line {})",
+                                    symbol.name,
+                                    symbol.signature.as_deref().unwrap_or("?"),
+                                    symbol.line_start
+                                )?;
+                                continue;
+                            }
+                            write!(
+                                out,
                                 "  - {} {} (lines {}-{})",
                                 symbol.symbol_type.as_str(),
                                 symbol.name,
                                 symbol.line_start,
                                 symbol.line_end
-                            ));
+                            )?;
                             if let Some(sig) = &symbol.signature {
-                                output.push_str(&format!("{}", sig));
+                                write!(out, "{}", sig)?;
                             }
-                            output.push('\n');
+                            if !symbol.annotations.is_empty() {
+                                write!(out, " {}", annotations_text(&symbol.annotations))?;
+                            }
+                            match self.symbol_marker(symbol) {
+                                Some("+") => write!(out, " [new]")?,
+                                Some(_) => write!(out, " [changed]")?,
+                                None => {}
+                            }
+                            writeln!(out)?;
                             if let Some(doc) = &symbol.docstring {
-                                output.push_str(&format!("    \"{}\"\n", doc));
+                                writeln!(out, "    \"{}\"", doc)?;
                             }
                         }
                     }
                 }
-                output.push('\n');
+                writeln!(out)?;
             }
         }
 
-        output
+        Ok(())
     }
 
-    fn format_map_human(&self, index: &CodeIndex, level: u8) -> String {
-        let mut output = String::new();
-
-        output.push_str(&format!("{}\n\n", "Project Overview".bold().green()));
+    fn write_map_human(
+        &self,
+        out: &mut impl Write,
+        index: &CodeIndex,
+        level: u8,
+    ) -> io::Result<()> {
+        writeln!(out, "{}\n", "Project Overview".bold().green())?;
 
         let mut lang_table = Table::new();
         lang_table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Language", "Files"]);
+            .set_header(vec!["Language", "Files", "Code", "Comment", "Blank"]);
 
-        let mut lang_counts: HashMap<&str, usize> = HashMap::new();
+        let mut lang_counts: HashMap<&str, (usize, LineCounts)> = HashMap::new();
         for file in index.files() {
-            *lang_counts.entry(file.language.as_str()).or_insert(0) += 1;
+            let (files, lines) = lang_counts.entry(file.language.as_str()).or_default();
+            *files += 1;
+            lines.add(file.lines);
         }
 
-        for (lang, count) in &lang_counts {
-            lang_table.add_row(vec![lang.to_string(), count.to_string()]);
+        for (lang, (count, lines)) in &lang_counts {
+            lang_table.add_row(vec![
+                lang.to_string(),
+                count.to_string(),
+                lines.code.to_string(),
+                lines.comment.to_string(),
+                lines.blank.to_string(),
+            ]);
         }
 
-        output.push_str(&format!("{}\n\n", lang_table));
+        writeln!(out, "{}\n", lang_table)?;
 
         let mut stats_table = Table::new();
         stats_table
@@ -222,65 +1076,133 @@ impl OutputFormatter {
             "Code Blocks",
             &index.symbols_by_type(SymbolType::CodeBlock).to_string(),
         ]);
+        if let Some(recency) = &self.recency {
+            let (files, added) = recency.file_counts();
+            let label = format!("Changed Since {}", recency.label);
+            stats_table.add_row(vec![
+                format!("{}: Commits", label),
+                recency.commits.to_string(),
+            ]);
+            stats_table.add_row(vec![
+                format!("{}: Files", label),
+                format!("{} ({} new)", files, added),
+            ]);
+            stats_table.add_row(vec![
+                format!("{}: Symbols", label),
+                recency.changed_symbols(index).to_string(),
+            ]);
+        }
 
-        output.push_str(&format!("{}\n", stats_table));
+        writeln!(out, "{}", stats_table)?;
 
         if level >= 2 {
-            output.push_str(&format!("\n{}\n\n", "Files".bold().green()));
+            writeln!(out, "\n{}\n", "Files".bold().green())?;
 
             let mut file_table = Table::new();
             file_table
                 .load_preset(UTF8_FULL)
                 .apply_modifier(UTF8_ROUND_CORNERS);
 
-            if level >= 3 {
-                file_table.set_header(vec!["File", "Language", "Size", "Symbols"]);
+            let mut header = if level >= 3 {
+                vec!["File", "Language", "Code Lines", "Symbols"]
             } else {
-                file_table.set_header(vec!["File", "Language", "Size", "Symbol Count"]);
+                vec!["File", "Language", "Code Lines", "Symbol Count"]
+            };
+            if self.recency.is_some() {
+                header.push("Changed");
+            }
+            if self.metrics.is_some() {
+                header.extend(["Complexity", "Longest Fn", "Maintainability"]);
+            }
+            let show_summaries = !self.summaries.is_empty();
+            if show_summaries {
+                header.push("Summary");
             }
+            file_table.set_header(header);
 
             for file in index.files() {
                 let symbols = index.get_file_symbols(&file.path);
                 let symbol_info = if level >= 3 {
                     symbols
                         .iter()
-                        .map(|s| format!("{}:{}", s.symbol_type.as_str(), s.name))
+                        .map(|s| {
+                            let mut item = format!("{}:{}", s.symbol_type.as_str(), s.name);
+                            if let Some(marker) = self.symbol_marker(s) {
+                                item.insert_str(0, marker);
+                            }
+                            if !s.annotations.is_empty() {
+                                item.push(' ');
+                                item.push_str(&annotations_text(&s.annotations));
+                            }
+                            item
+                        })
                         .collect::<Vec<_>>()
                         .join(", ")
                 } else {
                     symbols.len().to_string()
                 };
 
-                file_table.add_row(vec![
-                    file.path.display().to_string(),
+                let mut row = vec![
+                    file.path.display_slash().to_string(),
                     file.language.as_str().to_string(),
-                    format!("{} bytes", file.size),
+                    file.lines.code.to_string(),
                     symbol_info,
-                ]);
-            }
+                ];
+                if self.recency.is_some() {
+                    row.push(
+                        self.file_change(&file.path)
+                            .map_or("-".to_string(), recency_text),
+                    );
+                }
+                if let Some(metrics) = self.metrics.as_ref() {
+                    let metrics = metrics.get(&file.path).copied().unwrap_or_default();
+                    row.push(format!(
+                        "{} (max {})",
+                        metrics.complexity, metrics.max_complexity
+                    ));
+                    row.push(metrics.longest_function.to_string());
+                    row.push(format!("{:.0}", metrics.maintainability));
+                }
+                if show_summaries {
+                    row.push(self.file_summary(&file.path).unwrap_or("-").to_string());
+                }
+                file_table.add_row(row);
+            }
 
-            output.push_str(&format!("{}\n", file_table));
+            writeln!(out, "{}", file_table)?;
         }
 
-        output
+        Ok(())
     }
 
-    fn format_map_ai(&self, index: &CodeIndex, level: u8) -> String {
-        let mut output = String::new();
-        output.push_str("[PROJECT]\n");
+    fn write_map_ai(&self, out: &mut impl Write, index: &CodeIndex, level: u8) -> io::Result<()> {
+        writeln!(out, "[PROJECT]")?;
 
-        let mut lang_counts: HashMap<&str, usize> = HashMap::new();
+        let mut lang_counts: HashMap<&str, (usize, LineCounts)> = HashMap::new();
         for file in index.files() {
-            *lang_counts.entry(file.language.as_str()).or_insert(0) += 1;
+            let (files, lines) = lang_counts.entry(file.language.as_str()).or_default();
+            *files += 1;
+            lines.add(file.lines);
         }
 
-        output.push_str("LANGS:");
-        for (lang, count) in &lang_counts {
-            output.push_str(&format!(" {}:{}", lang, count));
+        write!(out, "LANGS:")?;
+        for (lang, (count, _)) in &lang_counts {
+            write!(out, " {}:{}", lang, count)?;
         }
-        output.push('\n');
+        writeln!(out)?;
+
+        // code/comment/blank
+        write!(out, "LOC:")?;
+        for (lang, (_, lines)) in &lang_counts {
+            write!(
+                out,
+                " {}:{}/{}/{}",
+                lang, lines.code, lines.comment, lines.blank
+            )?;
+        }
+        writeln!(out)?;
 
-        output.push_str(&format!("FILES:{} SYMBOLS:{} FUNCTIONS:{} CLASSES:{} METHODS:{} ENUMS:{} STATICS:{} HEADINGS:{} CODE BLOCKS:{}\n",
+        writeln!(out, "FILES:{} SYMBOLS:{} FUNCTIONS:{} CLASSES:{} METHODS:{} ENUMS:{} STATICS:{} HEADINGS:{} CODE BLOCKS:{}",
             index.total_files(),
             index.total_symbols(),
             index.symbols_by_type(SymbolType::Function),
@@ -290,67 +1212,116 @@ impl OutputFormatter {
             index.symbols_by_type(SymbolType::StaticField),
             index.symbols_by_type(SymbolType::Heading),
             index.symbols_by_type(SymbolType::CodeBlock)
-        ));
+        )?;
+
+        if let Some(recency) = &self.recency {
+            let (files, added) = recency.file_counts();
+            writeln!(
+                out,
+                "SINCE:{} BASE:{} COMMITS:{} CHANGED:{} NEW:{} SYMBOLS:{}",
+                recency.label,
+                recency.base.as_deref().unwrap_or("-"),
+                recency.commits,
+                files,
+                added,
+                recency.changed_symbols(index)
+            )?;
+        }
 
         if level >= 2 {
-            output.push_str("\n[FILES]\n");
+            writeln!(out, "\n[FILES]")?;
             for file in index.files() {
-                output.push_str(&format!(
-                    "{}|{}|{}",
-                    file.path.display(),
+                write!(
+                    out,
+                    "{}|{}|loc:{}/{}/{}",
+                    file.path.display_slash(),
                     file.language.as_str(),
-                    file.size
-                ));
+                    file.lines.code,
+                    file.lines.comment,
+                    file.lines.blank
+                )?;
+                if let Some(change) = self.file_change(&file.path) {
+                    match change.added {
+                        true => write!(out, "|new")?,
+                        false => write!(out, "|chg:{}c/{}l", change.commits, change.lines())?,
+                    }
+                }
+                if let Some(metrics) = self.metrics.as_ref() {
+                    let metrics = metrics.get(&file.path).copied().unwrap_or_default();
+                    write!(
+                        out,
+                        "|cc:{}/{}|maxfn:{}|mi:{:.0}",
+                        metrics.complexity,
+                        metrics.max_complexity,
+                        metrics.longest_function,
+                        metrics.maintainability
+                    )?;
+                }
+                if let Some(summary) = self.file_summary(&file.path) {
+                    write!(out, "|sum:{}", summary)?;
+                }
 
                 let symbols = index.get_file_symbols(&file.path);
                 if !symbols.is_empty() && level >= 3 {
-                    output.push_str("|");
+                    write!(out, "|")?;
                     for (i, symbol) in symbols.iter().enumerate() {
                         if i > 0 {
-                            output.push(',');
+                            write!(out, ",")?;
                         }
-                        output.push_str(&format!(
+                        write!(
+                            out,
                             "{}:{}@{}-{}",
-                            match symbol.symbol_type {
-                                SymbolType::Function => "f",
-                                SymbolType::Class => "c",
-                                SymbolType::Method => "m",
-                                SymbolType::Enum => "e",
-                                SymbolType::StaticField => "s",
-                                SymbolType::Heading => "h",
-                                SymbolType::CodeBlock => "cb",
-                                SymbolType::Interface => "if",
-                                SymbolType::TypeAlias => "ty",
-                            },
+                            symbol.symbol_type.code(),
                             symbol.name,
                             symbol.line_start,
                             symbol.line_end
-                        ));
+                        )?;
+                        if !symbol.annotations.is_empty() {
+                            write!(out, "[{}]", annotations_ai(&symbol.annotations))?;
+                        }
+                        if let Some(marker) = self.symbol_marker(symbol) {
+                            write!(out, "{}", marker)?;
+                        }
                     }
                 }
-                output.push('\n');
+                writeln!(out)?;
             }
         }
 
-        output
+        Ok(())
     }
 
-    pub fn format_query(&self, symbols: Vec<&Symbol>, context: bool, show_body: bool) -> String {
+    pub fn write_query(
+        &self,
+        out: &mut impl Write,
+        symbols: Vec<&Symbol>,
+        context: bool,
+        show_body: bool,
+    ) -> io::Result<()> {
         match self.format {
-            OutputFormat::Default => self.format_query_default(symbols, context, show_body),
-            OutputFormat::Human => self.format_query_human(symbols, context, show_body),
-            OutputFormat::AI => self.format_query_ai(symbols, context, show_body),
+            OutputFormat::Default | OutputFormat::Json => {
+                self.write_query_default(out, symbols, context, show_body)
+            }
+            OutputFormat::Human => self.write_query_human(out, symbols, context, show_body),
+            OutputFormat::AI => self.write_query_ai(out, symbols, context, show_body),
+            OutputFormat::Compact => self.write_query_compact(out, symbols),
         }
     }
 
-    fn format_query_default(
+    pub fn format_query(&self, symbols: Vec<&Symbol>, context: bool, show_body: bool) -> String {
+        let mut buf = Vec::new();
+        let _ = self.write_query(&mut buf, symbols, context, show_body);
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    fn write_query_default(
         &self,
+        out: &mut impl Write,
         symbols: Vec<&Symbol>,
         context: bool,
         show_body: bool,
-    ) -> String {
-        let mut output = String::new();
-        output.push_str(&format!("Found {} symbols\n\n", symbols.len()));
+    ) -> io::Result<()> {
+        writeln!(out, "Found {} symbols\n", symbols.len())?;
 
         for symbol in symbols {
             let export_marker = if symbol.is_exported {
@@ -358,25 +1329,39 @@ impl OutputFormatter {
             } else {
                 ""
             };
-            output.push_str(&format!("## {}\n", symbol.name));
-            output.push_str(&format!(
-                "- Type: {}{}\n",
+            writeln!(out, "## {}", symbol.name)?;
+            writeln!(
+                out,
+                "- Type: {}{}",
                 symbol.symbol_type.as_str(),
                 export_marker
-            ));
-            output.push_str(&format!("- File: {}\n", symbol.file_path.display()));
-            output.push_str(&format!(
-                "- Lines: {}-{}\n",
-                symbol.line_start, symbol.line_end
-            ));
+            )?;
+            writeln!(out, "- File: {}", symbol.file_path.display_slash())?;
+            writeln!(out, "- Lines: {}-{}", symbol.line_start, symbol.line_end)?;
+            let alternates = self.alternate_locations(symbol);
+            if !alternates.is_empty() {
+                writeln!(out, "- Also at: {}", alternates.join(", "))?;
+            }
 
             if let Some(sig) = &symbol.signature {
-                output.push_str(&format!("- Signature: {}\n", sig));
+                writeln!(out, "- Signature: {}", sig)?;
+            }
+
+            if !symbol.annotations.is_empty() {
+                writeln!(
+                    out,
+                    "- Annotations: {}",
+                    annotations_text(&symbol.annotations)
+                )?;
+            }
+
+            if let Some(summary) = self.symbol_summary(symbol) {
+                writeln!(out, "- Summary: {}", summary)?;
             }
 
             if context {
                 if let Some(doc) = &symbol.docstring {
-                    output.push_str(&format!("- Documentation: {}\n", doc));
+                    writeln!(out, "- Documentation: {}", doc)?;
                 }
             }
 
@@ -384,33 +1369,47 @@ impl OutputFormatter {
             if show_body {
                 let line_count = symbol.line_end - symbol.line_start + 1;
                 if line_count <= 50 {
-                    if let Some(body) =
-                        read_file_lines(&symbol.file_path, symbol.line_start, symbol.line_end)
-                    {
-                        output.push_str("\nCode:\n");
-                        output.push_str(&body);
+                    if let Some(body) = self.read_body(symbol) {
+                        writeln!(out, "\nCode:")?;
+                        out.write_all(body.as_bytes())?;
                     }
                 } else {
-                    output.push_str(&format!(
-                        "\n(Code body omitted: {} lines, use --context full to see more details)\n",
+                    writeln!(
+                        out,
+                        "\n(Code body omitted: {} lines, use --context full to see more details)",
                         line_count
-                    ));
+                    )?;
                 }
             }
 
-            output.push('\n');
+            writeln!(out)?;
         }
 
-        output
+        Ok(())
     }
 
-    fn format_query_human(&self, symbols: Vec<&Symbol>, context: bool, show_body: bool) -> String {
-        let mut output = String::new();
-        output.push_str(&format!(
-            "{} {}\n\n",
+    /// The symbol's file, with collapsed duplicates on the lines below
+    fn query_file_cell(&self, symbol: &Symbol) -> String {
+        let mut cell = symbol.file_path.display_slash().to_string();
+        for location in self.alternate_locations(symbol) {
+            cell.push_str(&format!("\n+ {}", location));
+        }
+        cell
+    }
+
+    fn write_query_human(
+        &self,
+        out: &mut impl Write,
+        symbols: Vec<&Symbol>,
+        context: bool,
+        show_body: bool,
+    ) -> io::Result<()> {
+        writeln!(
+            out,
+            "{} {}\n",
             "Found".green(),
             format!("{} symbols", symbols.len()).bold()
-        ));
+        )?;
 
         let mut table = Table::new();
         table
@@ -428,9 +1427,9 @@ impl OutputFormatter {
             ]);
             for symbol in &symbols {
                 table.add_row(vec![
-                    symbol.name.clone(),
+                    symbol.name.to_string(),
                     symbol.symbol_type.as_str().to_string(),
-                    symbol.file_path.display().to_string(),
+                    self.query_file_cell(symbol),
                     format!("{}-{}", symbol.line_start, symbol.line_end),
                     symbol.signature.as_deref().unwrap_or("-").to_string(),
                     symbol.docstring.as_deref().unwrap_or("-").to_string(),
@@ -440,97 +1439,267 @@ impl OutputFormatter {
             table.set_header(vec!["Name", "Type", "File", "Lines"]);
             for symbol in &symbols {
                 table.add_row(vec![
-                    symbol.name.clone(),
+                    symbol.name.to_string(),
                     symbol.symbol_type.as_str().to_string(),
-                    symbol.file_path.display().to_string(),
+                    self.query_file_cell(symbol),
                     format!("{}-{}", symbol.line_start, symbol.line_end),
                 ]);
             }
         }
 
-        output.push_str(&format!("{}\n", table));
+        writeln!(out, "{}", table)?;
+
+        let summarized: Vec<(&str, &str)> = symbols
+            .iter()
+            .filter_map(|s| {
+                self.symbol_summary(s)
+                    .map(|summary| (s.name.as_str(), summary))
+            })
+            .collect();
+        if !summarized.is_empty() {
+            writeln!(out, "\n{}", "Summaries".bold().green())?;
+            for (name, summary) in summarized {
+                writeln!(out, "  {} {}", format!("{}:", name).cyan(), summary)?;
+            }
+        }
+
+        let annotated: Vec<&&Symbol> = symbols
+            .iter()
+            .filter(|s| !s.annotations.is_empty())
+            .collect();
+        if !annotated.is_empty() {
+            writeln!(out, "\n{}", "Annotations".bold().green())?;
+            for symbol in annotated {
+                writeln!(
+                    out,
+                    "  {} {}",
+                    format!("{}:", symbol.name).cyan(),
+                    annotations_text(&symbol.annotations)
+                )?;
+            }
+        }
 
         // Show code bodies after the table if requested
         if show_body {
-            output.push_str("\n");
+            writeln!(out)?;
             for symbol in &symbols {
                 let line_count = symbol.line_end - symbol.line_start + 1;
                 if line_count <= 50 {
-                    if let Some(body) =
-                        read_file_lines(&symbol.file_path, symbol.line_start, symbol.line_end)
-                    {
-                        output.push_str(&format!("{} {}\n", "Code for".cyan(), symbol.name.bold()));
-                        output.push_str(&body);
-                        output.push_str("\n");
+                    if let Some(body) = self.read_body(symbol) {
+                        writeln!(out, "{} {}", "Code for".cyan(), symbol.name.bold())?;
+                        out.write_all(body.as_bytes())?;
+                        writeln!(out)?;
                     }
                 }
             }
         }
 
-        output
+        Ok(())
     }
 
-    fn format_query_ai(&self, symbols: Vec<&Symbol>, context: bool, show_body: bool) -> String {
-        let mut output = String::new();
-        output.push_str(&format!("[RESULTS:{}]\n", symbols.len()));
+    fn write_query_ai(
+        &self,
+        out: &mut impl Write,
+        symbols: Vec<&Symbol>,
+        context: bool,
+        show_body: bool,
+    ) -> io::Result<()> {
+        writeln!(out, "[RESULTS:{}]", symbols.len())?;
 
         for symbol in symbols {
             let export_tag = if symbol.is_exported { "|exp" } else { "" };
-            output.push_str(&format!(
+            write!(
+                out,
                 "{}|{}|{}|{}-{}{}",
                 symbol.name,
-                match symbol.symbol_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                symbol.file_path.display(),
+                symbol.symbol_type.code(),
+                symbol.file_path.display_slash(),
                 symbol.line_start,
                 symbol.line_end,
                 export_tag
-            ));
+            )?;
 
             if context {
                 if let Some(sig) = &symbol.signature {
-                    output.push_str(&format!("|sig:{}", sig));
+                    write!(out, "|sig:{}", sig)?;
                 }
                 if let Some(doc) = &symbol.docstring {
-                    output.push_str(&format!("|doc:{}", doc));
+                    write!(out, "|doc:{}", doc)?;
                 }
             }
 
+            let alternates: Vec<String> = self
+                .alternates
+                .get(&(symbol.file_path.clone(), symbol.line_start))
+                .into_iter()
+                .flatten()
+                .map(|a| format!("{}:{}", a.file_path.display_slash(), a.line_start))
+                .collect();
+            if !alternates.is_empty() {
+                write!(out, "|also:{}", alternates.join(","))?;
+            }
+
+            if !symbol.annotations.is_empty() {
+                write!(out, "|ann:{}", annotations_ai(&symbol.annotations))?;
+            }
+
+            if let Some(summary) = self.symbol_summary(symbol) {
+                write!(out, "|sum:{}", summary)?;
+            }
+
             if show_body {
                 let line_count = symbol.line_end - symbol.line_start + 1;
                 if line_count <= 50 {
-                    if let Some(body) =
-                        read_file_lines(&symbol.file_path, symbol.line_start, symbol.line_end)
-                    {
+                    if let Some(body) = self.read_body(symbol) {
                         // Compact format: include body on separate lines with indentation
-                        output.push_str("|body:");
+                        write!(out, "|body:")?;
                         for line in body.lines() {
-                            output.push_str(&format!("\n  {}", line));
+                            write!(out, "\n  {}", line)?;
                         }
                     }
                 }
             }
 
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_query_compact(&self, out: &mut impl Write, symbols: Vec<&Symbol>) -> io::Result<()> {
+        for s in symbols {
+            out.write_all(
+                compact_line(
+                    &s.file_path.display_slash().to_string(),
+                    s.line_start,
+                    s.symbol_type.as_str(),
+                    &s.name,
+                    s.signature.as_deref(),
+                )
+                .as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn format_semantic(&self, matches: &[SemanticMatch], query: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_semantic_default(matches, query),
+            OutputFormat::Human => self.format_semantic_human(matches, query),
+            OutputFormat::AI => self.format_semantic_ai(matches, query),
+            OutputFormat::Compact => self.format_semantic_compact(matches),
+        }
+    }
+
+    fn format_semantic_default(&self, matches: &[SemanticMatch], query: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Semantic matches for \"{}\"\n\n", query));
+
+        if matches.is_empty() {
+            output.push_str("No matches found\n");
+            return output;
+        }
+
+        for m in matches {
+            output.push_str(&format!(
+                "## {} ({}, score {:.3})\n",
+                m.chunk.name,
+                m.chunk.symbol_type.as_str(),
+                m.score
+            ));
+            if let Some(ref sig) = m.chunk.signature {
+                output.push_str(&format!("- Signature: `{}`\n", sig));
+            }
+            output.push_str(&format!(
+                "- Location: `{}:{}-{}`\n\n",
+                m.chunk.file_path.display_slash(),
+                m.chunk.line_start,
+                m.chunk.line_end
+            ));
+        }
+
+        output
+    }
+
+    fn format_semantic_human(&self, matches: &[SemanticMatch], query: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Semantic matches for".green(),
+            query.bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Score", "Name", "Type", "Location", "Signature"]);
+
+        for m in matches {
+            table.add_row(vec![
+                format!("{:.3}", m.score),
+                m.chunk.name.clone(),
+                m.chunk.symbol_type.as_str().to_string(),
+                format!("{}:{}", m.chunk.file_path.display_slash(), m.chunk.line_start),
+                m.chunk.signature.clone().unwrap_or_default(),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_semantic_ai(&self, matches: &[SemanticMatch], query: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[SEMANTIC:{}|{}]\n", query, matches.len()));
+
+        for m in matches {
+            output.push_str(&format!(
+                "{:.3}|{}|{}|{}:{}-{}",
+                m.score,
+                m.chunk.name,
+                m.chunk.symbol_type.as_str(),
+                m.chunk.file_path.display_slash(),
+                m.chunk.line_start,
+                m.chunk.line_end
+            ));
+            if let Some(ref sig) = m.chunk.signature {
+                output.push_str(&format!("|sig:{}", sig));
+            }
             output.push('\n');
         }
 
         output
     }
 
+    fn format_semantic_compact(&self, matches: &[SemanticMatch]) -> String {
+        matches
+            .iter()
+            .map(|m| {
+                compact_line(
+                    &m.chunk.file_path.display_slash().to_string(),
+                    m.chunk.line_start,
+                    m.chunk.symbol_type.as_str(),
+                    &m.chunk.name,
+                    m.chunk.signature.as_deref(),
+                )
+            })
+            .collect()
+    }
+
     pub fn format_deps(&self, target: &str, deps: Vec<String>, direction: &str) -> String {
         match self.format {
-            OutputFormat::Default => self.format_deps_default(target, deps, direction),
+            OutputFormat::Default | OutputFormat::Compact => {
+                self.format_deps_default(target, deps, direction)
+            }
             OutputFormat::Human => self.format_deps_human(target, deps, direction),
             OutputFormat::AI => self.format_deps_ai(target, deps, direction),
+            OutputFormat::Json => serde_json::json!({
+                "target": target,
+                "direction": direction,
+                "dependencies": deps,
+            })
+            .to_string(),
         }
     }
 
@@ -580,29 +1749,287 @@ impl OutputFormatter {
         output
     }
 
-    pub fn format_stats(&self, index: &CodeIndex) -> String {
+    /// Files or lines using `target`, each with how it was matched. JSON keeps the plain
+    /// `dependencies` list and adds `usages` with the confidence of each.
+    pub fn format_used_by(&self, target: &str, usages: &[(String, Confidence)]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_stats_default(index),
-            OutputFormat::Human => self.format_stats_human(index),
-            OutputFormat::AI => self.format_stats_ai(index),
+            OutputFormat::Default | OutputFormat::Compact => {
+                self.format_used_by_default(target, usages)
+            }
+            OutputFormat::Human => self.format_used_by_human(target, usages),
+            OutputFormat::AI => self.format_used_by_ai(target, usages),
+            OutputFormat::Json => serde_json::json!({
+                "target": target,
+                "direction": "used-by",
+                "dependencies": usages.iter().map(|(usage, _)| usage).collect::<Vec<_>>(),
+                "usages": usages
+                    .iter()
+                    .map(|(usage, confidence)| serde_json::json!({
+                        "location": usage,
+                        "confidence": confidence,
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+            .to_string(),
         }
     }
 
-    fn format_stats_default(&self, index: &CodeIndex) -> String {
+    fn format_used_by_default(&self, target: &str, usages: &[(String, Confidence)]) -> String {
         let mut output = String::new();
-        output.push_str("# Codebase Statistics\n\n");
+        output.push_str(&format!("# Dependencies for {}\n\n", target));
+        output.push_str("Direction: used-by\n\n");
+
+        for (usage, confidence) in usages {
+            output.push_str(&format!("- {} ({})\n", usage, confidence.as_str()));
+        }
 
-        let mut lang_counts: HashMap<&str, usize> = HashMap::new();
-        let mut total_loc = 0u64;
+        output
+    }
 
-        for file in index.files() {
-            *lang_counts.entry(file.language.as_str()).or_insert(0) += 1;
-            total_loc += file.size;
+    fn format_used_by_human(&self, target: &str, usages: &[(String, Confidence)]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n",
+            "Dependencies for".green(),
+            target.bold()
+        ));
+        output.push_str(&format!("{}: used-by\n\n", "Direction".cyan()));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Dependency", "Confidence"]);
+
+        for (usage, confidence) in usages {
+            table.add_row(vec![usage.clone(), confidence_cell(*confidence)]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_used_by_ai(&self, target: &str, usages: &[(String, Confidence)]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[DEPS:{}|used-by]\n", target));
+
+        for (usage, confidence) in usages {
+            output.push_str(&format!("{}|{}\n", usage, confidence.short()));
+        }
+
+        output
+    }
+
+    /// Imports of `target` as a tree, `depth` levels deep (0 = all)
+    pub fn format_dep_tree(&self, target: &str, tree: &[DepNode], depth: usize) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact => {
+                self.format_dep_tree_default(target, tree, depth)
+            }
+            OutputFormat::Human => self.format_dep_tree_human(target, tree),
+            OutputFormat::AI => self.format_dep_tree_ai(target, tree, depth),
+            OutputFormat::Json => serde_json::json!({
+                "target": target,
+                "direction": "imports",
+                "depth": depth,
+                "dependencies": tree,
+            })
+            .to_string(),
+        }
+    }
+
+    fn format_dep_tree_default(&self, target: &str, tree: &[DepNode], depth: usize) -> String {
+        fn push_nodes(output: &mut String, nodes: &[DepNode], level: usize) {
+            for node in nodes {
+                output.push_str(&format!("{}- `{}`", "  ".repeat(level), node.name));
+                if let Some(file) = &node.file {
+                    output.push_str(&format!(" → {}", file.display_slash()));
+                }
+                if node.child_count > 0 {
+                    output.push_str(&format!(" ({} imports)", node.child_count));
+                }
+                if node.cycle {
+                    output.push_str(" (cycle)");
+                } else if node.repeated {
+                    output.push_str(" (*)");
+                }
+                output.push('\n');
+                push_nodes(output, &node.children, level + 1);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str(&format!("# Dependencies for {}\n\n", target));
+        let levels = match depth {
+            0 => "all levels".to_string(),
+            n => format!("{} level(s)", n),
+        };
+        output.push_str(&format!("Direction: imports, {}\n\n", levels));
+        push_nodes(&mut output, tree, 0);
+        if Self::has_marker(tree, |n| n.repeated) {
+            output.push_str("\n(*) expanded above\n");
+        }
+        output
+    }
+
+    fn format_dep_tree_human(&self, target: &str, tree: &[DepNode]) -> String {
+        fn push_nodes(output: &mut String, nodes: &[DepNode], prefix: &str) {
+            for (i, node) in nodes.iter().enumerate() {
+                let last = i + 1 == nodes.len();
+                let file = match &node.file {
+                    Some(file) => format!(" {}", file.display_slash()).cyan().to_string(),
+                    None => String::new(),
+                };
+                let count = if node.child_count > 0 {
+                    format!(" ({})", node.child_count).dimmed().to_string()
+                } else {
+                    String::new()
+                };
+                let marker = if node.cycle {
+                    " ↺ cycle".yellow().to_string()
+                } else if node.repeated {
+                    " (*)".dimmed().to_string()
+                } else {
+                    String::new()
+                };
+                output.push_str(&format!(
+                    "{}{} {}{}{}{}\n",
+                    prefix,
+                    if last { "└──" } else { "├──" },
+                    node.name.bold(),
+                    file,
+                    count,
+                    marker
+                ));
+                let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                push_nodes(output, &node.children, &child_prefix);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n",
+            "Dependencies for".green(),
+            target.bold()
+        ));
+        push_nodes(&mut output, tree, "");
+        output
+    }
+
+    fn format_dep_tree_ai(&self, target: &str, tree: &[DepNode], depth: usize) -> String {
+        fn push_nodes(output: &mut String, nodes: &[DepNode], level: usize) {
+            for node in nodes {
+                output.push_str(&format!(
+                    "{}|{}|{}|{}{}\n",
+                    level,
+                    node.name,
+                    node.file
+                        .as_ref()
+                        .map_or("-".to_string(), |f| f.display_slash().to_string()),
+                    node.child_count,
+                    if node.cycle {
+                        "|cycle"
+                    } else if node.repeated {
+                        "|seen"
+                    } else {
+                        ""
+                    }
+                ));
+                push_nodes(output, &node.children, level + 1);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str(&format!(
+            "[DEPS:{}|imports|{}]\n",
+            target,
+            Self::depth_label(depth)
+        ));
+        push_nodes(&mut output, tree, 1);
+        output
+    }
+
+    fn depth_label(depth: usize) -> String {
+        match depth {
+            0 => "depth:all".to_string(),
+            n => format!("depth:{}", n),
+        }
+    }
+
+    fn has_marker(nodes: &[DepNode], marker: fn(&DepNode) -> bool) -> bool {
+        nodes
+            .iter()
+            .any(|n| marker(n) || Self::has_marker(&n.children, marker))
+    }
+
+    /// `index` holds first-party code only; `vendored` is what was taken out of it
+    pub fn format_stats(
+        &self,
+        index: &CodeIndex,
+        skipped: &[SkippedFile],
+        vendored: &VendoredStats,
+    ) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_stats_default(index, skipped, vendored)
+            }
+            OutputFormat::Human => self.format_stats_human(index, skipped, vendored),
+            OutputFormat::AI => self.format_stats_ai(index, skipped, vendored),
+        }
+    }
+
+    /// Skipped file count per reason, in `SkipReason` order
+    fn skip_counts(skipped: &[SkippedFile]) -> Vec<(SkipReason, usize)> {
+        let mut counts: Vec<(SkipReason, usize)> = Vec::new();
+        for file in skipped {
+            match counts.iter_mut().find(|(reason, _)| *reason == file.reason) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((file.reason, 1)),
+            }
+        }
+        counts.sort();
+        counts
+    }
+
+    fn format_stats_default(
+        &self,
+        index: &CodeIndex,
+        skipped: &[SkippedFile],
+        vendored: &VendoredStats,
+    ) -> String {
+        let mut output = String::new();
+        output.push_str("# Codebase Statistics\n\n");
+
+        let langs = stats::language_breakdown(index);
+        let mut total_lines = LineCounts::default();
+        for lang in &langs {
+            total_lines.add(lang.lines);
         }
 
         output.push_str("## Files by Language\n");
-        for (lang, count) in &lang_counts {
-            output.push_str(&format!("- {}: {}\n", lang, count));
+        for lang in &langs {
+            output.push_str(&format!(
+                "- {}: {} files ({} prod / {} test), {} code / {} comment / {} blank lines\n",
+                lang.language.as_str(),
+                lang.files,
+                lang.prod_files(),
+                lang.test_files,
+                lang.lines.code,
+                lang.lines.comment,
+                lang.lines.blank
+            ));
+        }
+
+        output.push_str("\n## Symbols by Language\n");
+        for lang in &langs {
+            output.push_str(&format!(
+                "- {}: {} functions, {} classes, {} methods, {:.1} symbols/file\n",
+                lang.language.as_str(),
+                lang.functions,
+                lang.classes,
+                lang.methods,
+                lang.avg_symbols_per_file()
+            ));
         }
 
         output.push_str("\n## Symbols by Type\n");
@@ -638,12 +2065,42 @@ impl OutputFormatter {
         output.push_str(&format!("\n## Totals\n"));
         output.push_str(&format!("- Total Files: {}\n", index.total_files()));
         output.push_str(&format!("- Total Symbols: {}\n", index.total_symbols()));
-        output.push_str(&format!("- Total Bytes: {}\n", total_loc));
+        output.push_str(&format!("- Code Lines: {}\n", total_lines.code));
+        output.push_str(&format!("- Comment Lines: {}\n", total_lines.comment));
+        output.push_str(&format!("- Blank Lines: {}\n", total_lines.blank));
+
+        if vendored.files > 0 {
+            output.push_str("\n## Vendored (not counted above; --no-vendor drops it)\n");
+            output.push_str(&format!(
+                "- {} files, {} symbols, {} lines of code\n",
+                vendored.files, vendored.symbols, vendored.lines.code
+            ));
+            for (dir, files) in &vendored.roots {
+                output.push_str(&format!("- {}/: {} files\n", dir.display_slash(), files));
+            }
+        }
+
+        if !skipped.is_empty() {
+            output.push_str("\n## Skipped (cm stats --skipped lists them)\n");
+            for (reason, count) in Self::skip_counts(skipped) {
+                output.push_str(&format!(
+                    "- {}: {} ({})\n",
+                    reason.as_str(),
+                    count,
+                    reason.description()
+                ));
+            }
+        }
 
         output
     }
 
-    fn format_stats_human(&self, index: &CodeIndex) -> String {
+    fn format_stats_human(
+        &self,
+        index: &CodeIndex,
+        skipped: &[SkippedFile],
+        vendored: &VendoredStats,
+    ) -> String {
         let mut output = String::new();
         output.push_str(&format!("{}\n\n", "Codebase Statistics".bold().green()));
 
@@ -651,22 +2108,53 @@ impl OutputFormatter {
         lang_table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Language", "Files"]);
-
-        let mut lang_counts: HashMap<&str, usize> = HashMap::new();
-        let mut total_loc = 0u64;
+            .set_header(vec![
+                "Language", "Files", "Prod", "Test", "Code", "Comment", "Blank",
+            ]);
 
-        for file in index.files() {
-            *lang_counts.entry(file.language.as_str()).or_insert(0) += 1;
-            total_loc += file.size;
+        let langs = stats::language_breakdown(index);
+        let mut total_lines = LineCounts::default();
+        for lang in &langs {
+            total_lines.add(lang.lines);
         }
 
-        for (lang, count) in &lang_counts {
-            lang_table.add_row(vec![lang.to_string(), count.to_string()]);
+        let mut matrix_table = Table::new();
+        matrix_table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec![
+                "Language",
+                "Functions",
+                "Classes",
+                "Methods",
+                "Symbols",
+                "Per File",
+            ]);
+
+        for lang in &langs {
+            lang_table.add_row(vec![
+                lang.language.as_str().to_string(),
+                lang.files.to_string(),
+                lang.prod_files().to_string(),
+                lang.test_files.to_string(),
+                lang.lines.code.to_string(),
+                lang.lines.comment.to_string(),
+                lang.lines.blank.to_string(),
+            ]);
+            matrix_table.add_row(vec![
+                lang.language.as_str().to_string(),
+                lang.functions.to_string(),
+                lang.classes.to_string(),
+                lang.methods.to_string(),
+                lang.symbols.to_string(),
+                format!("{:.1}", lang.avg_symbols_per_file()),
+            ]);
         }
 
         output.push_str(&format!("{}\n\n", "Files by Language".cyan()));
         output.push_str(&format!("{}\n\n", lang_table));
+        output.push_str(&format!("{}\n", "Symbols by Language".cyan()));
+        output.push_str(&format!("{}\n\n", matrix_table));
 
         let mut symbol_table = Table::new();
         symbol_table
@@ -714,511 +2202,4292 @@ impl OutputFormatter {
 
         totals_table.add_row(vec!["Total Files", &index.total_files().to_string()]);
         totals_table.add_row(vec!["Total Symbols", &index.total_symbols().to_string()]);
-        totals_table.add_row(vec!["Total Bytes", &total_loc.to_string()]);
+        totals_table.add_row(vec!["Code Lines", &total_lines.code.to_string()]);
+        totals_table.add_row(vec!["Comment Lines", &total_lines.comment.to_string()]);
+        totals_table.add_row(vec!["Blank Lines", &total_lines.blank.to_string()]);
 
         output.push_str(&format!("{}\n", "Totals".cyan()));
         output.push_str(&format!("{}\n", totals_table));
 
+        if vendored.files > 0 {
+            let mut vendored_table = Table::new();
+            vendored_table
+                .load_preset(UTF8_FULL)
+                .apply_modifier(UTF8_ROUND_CORNERS)
+                .set_header(vec!["Vendored Directory", "Files"]);
+            for (dir, files) in &vendored.roots {
+                vendored_table.add_row(vec![dir.display_slash().to_string(), files.to_string()]);
+            }
+            output.push_str(&format!(
+                "\n{} {}\n",
+                "Vendored".cyan(),
+                format!(
+                    "({} files, {} symbols, {} lines of code; not counted above)",
+                    vendored.files, vendored.symbols, vendored.lines.code
+                )
+                .dimmed()
+            ));
+            output.push_str(&format!("{}\n", vendored_table));
+        }
+
+        if !skipped.is_empty() {
+            let mut skipped_table = Table::new();
+            skipped_table
+                .load_preset(UTF8_FULL)
+                .apply_modifier(UTF8_ROUND_CORNERS)
+                .set_header(vec!["Reason", "Files", "Why"]);
+            for (reason, count) in Self::skip_counts(skipped) {
+                skipped_table.add_row(vec![
+                    reason.as_str(),
+                    &count.to_string(),
+                    reason.description(),
+                ]);
+            }
+            output.push_str(&format!("\n{}\n", "Skipped".cyan()));
+            output.push_str(&format!("{}\n", skipped_table));
+        }
+
         output
     }
 
-    fn format_stats_ai(&self, index: &CodeIndex) -> String {
+    fn format_stats_ai(
+        &self,
+        index: &CodeIndex,
+        skipped: &[SkippedFile],
+        vendored: &VendoredStats,
+    ) -> String {
         let mut output = String::new();
         output.push_str("[STATS]\n");
 
-        let mut lang_counts: HashMap<&str, usize> = HashMap::new();
-        let mut total_loc = 0u64;
-
-        for file in index.files() {
-            *lang_counts.entry(file.language.as_str()).or_insert(0) += 1;
-            total_loc += file.size;
+        let langs = stats::language_breakdown(index);
+        let mut total_lines = LineCounts::default();
+        for lang in &langs {
+            total_lines.add(lang.lines);
         }
 
         output.push_str("LANGS:");
-        for (lang, count) in &lang_counts {
-            output.push_str(&format!(" {}:{}", lang, count));
+        for lang in &langs {
+            output.push_str(&format!(" {}:{}", lang.language.as_str(), lang.files));
         }
         output.push('\n');
 
-        output.push_str(&format!(
-            "SYMS: f:{} c:{} m:{} e:{} s:{} h:{} cb:{}\n",
-            index.symbols_by_type(SymbolType::Function),
-            index.symbols_by_type(SymbolType::Class),
-            index.symbols_by_type(SymbolType::Method),
-            index.symbols_by_type(SymbolType::Enum),
-            index.symbols_by_type(SymbolType::StaticField),
-            index.symbols_by_type(SymbolType::Heading),
-            index.symbols_by_type(SymbolType::CodeBlock)
-        ));
+        // code/comment/blank
+        output.push_str("LOC:");
+        for lang in &langs {
+            output.push_str(&format!(
+                " {}:{}/{}/{}",
+                lang.language.as_str(),
+                lang.lines.code,
+                lang.lines.comment,
+                lang.lines.blank
+            ));
+        }
+        output.push('\n');
+
+        // functions/classes/methods, then average symbols per file
+        output.push_str("LANG_SYMS:");
+        for lang in &langs {
+            output.push_str(&format!(
+                " {}:{}/{}/{}~{:.1}",
+                lang.language.as_str(),
+                lang.functions,
+                lang.classes,
+                lang.methods,
+                lang.avg_symbols_per_file()
+            ));
+        }
+        output.push('\n');
+
+        // prod/test files
+        output.push_str("LANG_TESTS:");
+        for lang in &langs {
+            output.push_str(&format!(
+                " {}:{}/{}",
+                lang.language.as_str(),
+                lang.prod_files(),
+                lang.test_files
+            ));
+        }
+        output.push('\n');
+
+        output.push_str("SYMS:");
+        let counted = [
+            SymbolType::Function,
+            SymbolType::Class,
+            SymbolType::Method,
+            SymbolType::Enum,
+            SymbolType::StaticField,
+            SymbolType::Heading,
+            SymbolType::CodeBlock,
+        ];
+        for kind in counted {
+            output.push_str(&format!(" {}:{}", kind.code(), index.symbols_by_type(kind)));
+        }
+        // Custom kinds only when the index has some
+        for kind in crate::kinds::custom_kinds() {
+            let count = index.symbols_by_type(kind);
+            if count > 0 {
+                output.push_str(&format!(" {}:{}", kind.code(), count));
+            }
+        }
+        output.push('\n');
 
         output.push_str(&format!(
-            "TOTALS: files:{} syms:{} bytes:{}\n",
+            "TOTALS: files:{} syms:{} code:{} comment:{} blank:{}\n",
             index.total_files(),
             index.total_symbols(),
-            total_loc
+            total_lines.code,
+            total_lines.comment,
+            total_lines.blank
         ));
 
+        if vendored.files > 0 {
+            let roots: Vec<String> = vendored
+                .roots
+                .iter()
+                .map(|(dir, files)| format!("{}:{}", dir.display_slash(), files))
+                .collect();
+            output.push_str(&format!(
+                "VENDORED: files:{} syms:{} code:{} dirs:{}\n",
+                vendored.files,
+                vendored.symbols,
+                vendored.lines.code,
+                roots.join(",")
+            ));
+        }
+
+        if !skipped.is_empty() {
+            output.push_str("SKIPPED:");
+            for (reason, count) in Self::skip_counts(skipped) {
+                output.push_str(&format!(" {}:{}", reason.as_str(), count));
+            }
+            output.push('\n');
+        }
+
         output
     }
 
-    pub fn format_diff(&self, result: &DiffResult) -> String {
+    /// A failed command: `[ERROR:CODE]` for agents, an error object for JSON
+    pub fn format_error(&self, code: ErrorCode, message: &str) -> String {
         match self.format {
-            OutputFormat::Default => self.format_diff_default(result),
-            OutputFormat::Human => self.format_diff_human(result),
-            OutputFormat::AI => self.format_diff_ai(result),
+            OutputFormat::Default | OutputFormat::Compact => {
+                format!("{} {}", "Error:".red(), message)
+            }
+            OutputFormat::Human => format!(
+                "{} {} {}",
+                "✗".red(),
+                message,
+                format!("({})", code.as_str()).dimmed()
+            ),
+            OutputFormat::AI => format!("[ERROR:{}] {}", code.as_str(), message),
+            OutputFormat::Json => serde_json::json!({
+                "error": {
+                    "code": code.as_str(),
+                    "message": message,
+                    "exit_code": code.exit_code(),
+                }
+            })
+            .to_string(),
         }
     }
 
-    fn format_diff_default(&self, result: &DiffResult) -> String {
-        let mut output = String::new();
-        output.push_str(&format!("# Symbol Diff\n\n"));
-        output.push_str(&format!(
-            "Comparing HEAD to commit: `{}`\n\n",
-            &result.commit[..8.min(result.commit.len())]
-        ));
-        output.push_str(&format!("Files analyzed: {}\n", result.files_analyzed));
-        output.push_str(&format!("Symbol changes: {}\n\n", result.symbols.len()));
-
-        if result.symbols.is_empty() {
-            output.push_str("No symbol changes detected.\n");
-            return output;
+    pub fn format_skipped(&self, skipped: &[SkippedFile]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_skipped_default(skipped)
+            }
+            OutputFormat::Human => self.format_skipped_human(skipped),
+            OutputFormat::AI => self.format_skipped_ai(skipped),
         }
+    }
 
-        let mut by_type: HashMap<ChangeType, Vec<&SymbolDiff>> = HashMap::new();
-        for sym in &result.symbols {
-            by_type.entry(sym.change_type).or_default().push(sym);
+    fn format_skipped_default(&self, skipped: &[SkippedFile]) -> String {
+        let mut output = String::new();
+        output.push_str("# Skipped Files\n\n");
+        if skipped.is_empty() {
+            output.push_str("Every file was indexed\n");
+            return output;
         }
 
-        for change_type in [
-            ChangeType::Added,
-            ChangeType::Deleted,
-            ChangeType::Modified,
-            ChangeType::SignatureChanged,
-        ] {
-            if let Some(symbols) = by_type.get(&change_type) {
+        let mut current = None;
+        for file in skipped {
+            if current != Some(file.reason) {
+                let count = skipped.iter().filter(|f| f.reason == file.reason).count();
+                if current.is_some() {
+                    output.push('\n');
+                }
                 output.push_str(&format!(
-                    "## {} ({})\n\n",
-                    change_type.as_str(),
-                    symbols.len()
+                    "## {} — {} ({})\n",
+                    file.reason.as_str(),
+                    file.reason.description(),
+                    count
                 ));
-                for sym in symbols {
-                    output.push_str(&format!(
-                        "- **{}** ({}) in `{}`",
-                        sym.name,
-                        sym.symbol_type.as_str(),
-                        sym.file_path.display()
-                    ));
-                    if let Some((start, end)) = sym.new_lines {
-                        output.push_str(&format!(" @ lines {}-{}", start, end));
-                    } else if let Some((start, end)) = sym.old_lines {
-                        output.push_str(&format!(" @ lines {}-{} (deleted)", start, end));
-                    }
-                    output.push('\n');
-
-                    if change_type == ChangeType::SignatureChanged {
-                        if let Some(ref old_sig) = sym.old_signature {
-                            output.push_str(&format!("  - Old: `{}`\n", old_sig));
-                        }
-                        if let Some(ref new_sig) = sym.new_signature {
-                            output.push_str(&format!("  - New: `{}`\n", new_sig));
-                        }
-                    }
+                current = Some(file.reason);
+            }
+            match file.reason {
+                SkipReason::Ignored => {
+                    output.push_str(&format!("- {}/\n", file.path.display_slash()))
                 }
-                output.push('\n');
+                _ => output.push_str(&format!(
+                    "- {} ({} bytes)\n",
+                    file.path.display_slash(),
+                    file.size
+                )),
             }
         }
 
         output
     }
 
-    fn format_diff_human(&self, result: &DiffResult) -> String {
+    fn format_skipped_human(&self, skipped: &[SkippedFile]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("{}\n\n", "Symbol Diff".bold().green()));
-        output.push_str(&format!(
-            "Commit: {}\n",
-            result.commit[..8.min(result.commit.len())].cyan()
-        ));
-        output.push_str(&format!(
-            "Files analyzed: {}\n",
-            result.files_analyzed.to_string().bold()
-        ));
-        output.push_str(&format!(
-            "Symbol changes: {}\n\n",
-            result.symbols.len().to_string().bold()
-        ));
-
-        if result.symbols.is_empty() {
-            output.push_str(&format!("{}\n", "No symbol changes detected.".yellow()));
-            return output;
-        }
+        output.push_str(&format!("{}\n\n", "Skipped Files".bold().green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Change", "Name", "Type", "File", "Lines"]);
-
-        for sym in &result.symbols {
-            let change_str = match sym.change_type {
-                ChangeType::Added => format!("{}", "+".green()),
-                ChangeType::Deleted => format!("{}", "-".red()),
-                ChangeType::Modified => format!("{}", "~".yellow()),
-                ChangeType::SignatureChanged => format!("{}", "!".magenta()),
-            };
-
-            let lines = if let Some((start, end)) = sym.new_lines {
-                format!("{}-{}", start, end)
-            } else if let Some((start, end)) = sym.old_lines {
-                format!("{}-{} (del)", start, end)
-            } else {
-                "-".to_string()
+            .set_header(vec!["Reason", "Path", "Bytes"]);
+        for file in skipped {
+            let size = match file.reason {
+                SkipReason::Ignored => "-".to_string(),
+                _ => file.size.to_string(),
             };
-
             table.add_row(vec![
-                change_str,
-                sym.name.clone(),
-                sym.symbol_type.as_str().to_string(),
-                sym.file_path.display().to_string(),
-                lines,
+                file.reason.as_str().to_string(),
+                file.path.display_slash().to_string(),
+                size,
             ]);
         }
 
         output.push_str(&format!("{}\n", table));
+        output
+    }
 
-        let sig_changes: Vec<&SymbolDiff> = result
-            .symbols
-            .iter()
-            .filter(|s| s.change_type == ChangeType::SignatureChanged)
-            .collect();
+    fn format_skipped_ai(&self, skipped: &[SkippedFile]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[SKIPPED:{}]\n", skipped.len()));
+        for file in skipped {
+            output.push_str(&format!(
+                "{}|{}|{}\n",
+                file.reason.as_str(),
+                file.path.display_slash(),
+                file.size
+            ));
+        }
+        output
+    }
 
-        if !sig_changes.is_empty() {
-            output.push_str(&format!("\n{}\n", "Signature Changes:".cyan()));
-            for sym in sig_changes {
-                output.push_str(&format!("  {} {}\n", "→".cyan(), sym.name.bold()));
-                if let Some(ref old_sig) = sym.old_signature {
-                    output.push_str(&format!("    Old: {}\n", old_sig.red()));
-                }
-                if let Some(ref new_sig) = sym.new_signature {
-                    output.push_str(&format!("    New: {}\n", new_sig.green()));
-                }
+    pub fn format_top(&self, report: &TopReport) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_top_default(report)
             }
+            OutputFormat::Human => self.format_top_human(report),
+            OutputFormat::AI => self.format_top_ai(report),
         }
-
-        output.push_str(&format!(
-            "\n{}: {} {} {} {} {} {} {} {}\n",
-            "Legend".cyan(),
-            "+".green(),
-            "added".dimmed(),
-            "-".red(),
-            "deleted".dimmed(),
-            "~".yellow(),
-            "modified".dimmed(),
-            "!".magenta(),
-            "signature".dimmed()
-        ));
-
-        output
     }
 
-    fn format_diff_ai(&self, result: &DiffResult) -> String {
+    fn format_top_default(&self, report: &TopReport) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "[DIFF:{}]\n",
-            &result.commit[..8.min(result.commit.len())]
-        ));
-        output.push_str(&format!(
-            "FILES:{} CHANGES:{}\n",
-            result.files_analyzed,
-            result.symbols.len()
-        ));
 
-        if result.symbols.is_empty() {
-            output.push_str("NO_CHANGES\n");
-            return output;
+        output.push_str("## Largest Files\n");
+        for (path, code) in &report.largest_files {
+            output.push_str(&format!("- {} ({} lines of code)\n", path.display_slash(), code));
         }
 
-        for sym in &result.symbols {
+        output.push_str("\n## Longest Functions\n");
+        for (symbol, lines) in &report.longest_functions {
             output.push_str(&format!(
-                "{}|{}|{}|{}",
-                sym.change_type.short(),
-                sym.name,
-                match sym.symbol_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                sym.file_path.display()
+                "- {} ({} lines) - {}:{}\n",
+                symbol.name,
+                lines,
+                symbol.file_path.display_slash(),
+                symbol.line_start
             ));
+        }
 
-            if let Some((start, end)) = sym.new_lines {
-                output.push_str(&format!("|{}-{}", start, end));
-            } else if let Some((start, end)) = sym.old_lines {
-                output.push_str(&format!("|{}-{}(del)", start, end));
-            }
-
-            if sym.change_type == ChangeType::SignatureChanged {
-                if let Some(ref old_sig) = sym.old_signature {
-                    output.push_str(&format!("|old:{}", old_sig));
-                }
-                if let Some(ref new_sig) = sym.new_signature {
-                    output.push_str(&format!("|new:{}", new_sig));
-                }
-            }
+        output.push_str("\n## Classes with Most Methods\n");
+        for class in &report.biggest_classes {
+            output.push_str(&format!(
+                "- {} ({} methods) - {}:{}\n",
+                class.name,
+                class.methods,
+                class.class.file_path.display_slash(),
+                class.class.line_start
+            ));
+        }
 
-            output.push('\n');
+        output.push_str("\n## Files with Most Symbols\n");
+        for (path, count) in &report.densest_files {
+            output.push_str(&format!("- {} ({} symbols)\n", path.display_slash(), count));
         }
 
         output
     }
 
-    pub fn format_breaking(&self, result: &DiffResult) -> String {
-        match self.format {
-            OutputFormat::Default => self.format_breaking_default(result),
-            OutputFormat::Human => self.format_breaking_human(result),
-            OutputFormat::AI => self.format_breaking_ai(result),
-        }
-    }
-
-    fn format_breaking_default(&self, result: &DiffResult) -> String {
+    fn format_top_human(&self, report: &TopReport) -> String {
         let mut output = String::new();
-        output.push_str("# Breaking Changes\n\n");
+
+        let table = |header: Vec<&str>, rows: Vec<Vec<String>>| {
+            let mut table = Table::new();
+            table
+                .load_preset(UTF8_FULL)
+                .apply_modifier(UTF8_ROUND_CORNERS)
+                .set_header(header);
+            for row in rows {
+                table.add_row(row);
+            }
+            table
+        };
+
+        let rows = report
+            .largest_files
+            .iter()
+            .map(|(path, code)| vec![path.display_slash().to_string(), code.to_string()])
+            .collect();
+        output.push_str(&format!("{}\n", "Largest Files".cyan()));
+        output.push_str(&format!("{}\n\n", table(vec!["File", "Code Lines"], rows)));
+
+        let rows = report
+            .longest_functions
+            .iter()
+            .map(|(symbol, lines)| {
+                vec![
+                    symbol.name.to_string(),
+                    lines.to_string(),
+                    format!("{}:{}", symbol.file_path.display_slash(), symbol.line_start),
+                ]
+            })
+            .collect();
+        output.push_str(&format!("{}\n", "Longest Functions".cyan()));
         output.push_str(&format!(
-            "Since commit: `{}`\n\n",
-            &result.commit[..8.min(result.commit.len())]
+            "{}\n\n",
+            table(vec!["Function", "Lines", "Location"], rows)
         ));
 
-        if result.symbols.is_empty() {
-            output.push_str("No breaking changes detected.\n");
-            return output;
+        let rows = report
+            .biggest_classes
+            .iter()
+            .map(|class| {
+                vec![
+                    class.name.clone(),
+                    class.methods.to_string(),
+                    format!(
+                        "{}:{}",
+                        class.class.file_path.display_slash(),
+                        class.class.line_start
+                    ),
+                ]
+            })
+            .collect();
+        output.push_str(&format!("{}\n", "Classes with Most Methods".cyan()));
+        output.push_str(&format!(
+            "{}\n\n",
+            table(vec!["Class", "Methods", "Location"], rows)
+        ));
+
+        let rows = report
+            .densest_files
+            .iter()
+            .map(|(path, count)| vec![path.display_slash().to_string(), count.to_string()])
+            .collect();
+        output.push_str(&format!("{}\n", "Files with Most Symbols".cyan()));
+        output.push_str(&format!("{}\n", table(vec!["File", "Symbols"], rows)));
+
+        output
+    }
+
+    fn format_top_ai(&self, report: &TopReport) -> String {
+        let mut output = String::new();
+        output.push_str("[TOP]\n");
+
+        output.push_str("BIGGEST_FILES:");
+        for (path, code) in &report.largest_files {
+            output.push_str(&format!(" {}:{}loc", path.display_slash(), code));
+        }
+
+        output.push_str("\nLONGEST_FNS:");
+        for (symbol, lines) in &report.longest_functions {
+            output.push_str(&format!(
+                " {}@{}:{}:{}l",
+                symbol.name,
+                symbol.file_path.display_slash(),
+                symbol.line_start,
+                lines
+            ));
+        }
+
+        output.push_str("\nMOST_METHODS:");
+        for class in &report.biggest_classes {
+            output.push_str(&format!(
+                " {}@{}:{}:{}m",
+                class.name,
+                class.class.file_path.display_slash(),
+                class.class.line_start,
+                class.methods
+            ));
+        }
+
+        output.push_str("\nMOST_SYMBOLS:");
+        for (path, count) in &report.densest_files {
+            output.push_str(&format!(" {}:{}", path.display_slash(), count));
+        }
+        output.push('\n');
+
+        output
+    }
+
+    pub fn format_prompt(&self, orientation: &Orientation) -> String {
+        match self.format {
+            OutputFormat::AI => self.format_prompt_ai(orientation),
+            // Markdown is already what gets pasted into a prompt, so human and compact
+            // output share it
+            _ => self.format_prompt_default(orientation),
         }
+    }
 
+    fn format_prompt_default(&self, orientation: &Orientation) -> String {
+        let mut output = String::new();
         output.push_str(&format!(
-            "**{} breaking change(s) found**\n\n",
-            result.symbols.len()
+            "# Codebase Orientation: {}\n\n",
+            orientation.project
         ));
 
-        let deleted: Vec<&SymbolDiff> = result
-            .symbols
+        let languages: Vec<String> = orientation
+            .languages
             .iter()
-            .filter(|s| s.change_type == ChangeType::Deleted)
+            .map(|(language, count)| format!("{} {}", language, count))
             .collect();
-
-        let sig_changed: Vec<&SymbolDiff> = result
-            .symbols
+        let kinds: Vec<String> = orientation
+            .kinds
             .iter()
-            .filter(|s| s.change_type == ChangeType::SignatureChanged)
+            .map(|(kind, count)| format!("{} {}", kind.as_str(), count))
             .collect();
+        output.push_str(&format!(
+            "{} files ({}) with {} symbols ({}).\n",
+            orientation.files,
+            languages.join(", "),
+            orientation.symbols,
+            kinds.join(", ")
+        ));
 
-        if !deleted.is_empty() {
-            output.push_str("## REMOVED (callers will break)\n\n");
-            for sym in &deleted {
+        if !orientation.entrypoints.is_empty() {
+            output.push_str("\n## Entry Points\n");
+            for entry in &orientation.entrypoints {
                 output.push_str(&format!(
-                    "- **{}** ({}) in `{}`",
-                    sym.name,
-                    sym.symbol_type.as_str(),
-                    sym.file_path.display()
+                    "- `{}` ({}) - {}:{}\n",
+                    entry.name,
+                    entry.symbol_type.as_str(),
+                    entry.file_path,
+                    entry.line
                 ));
-                if let Some((start, end)) = sym.old_lines {
-                    output.push_str(&format!(" @ lines {}-{}", start, end));
+            }
+        }
+
+        if !orientation.top_symbols.is_empty() {
+            output.push_str("\n## Most-Called Symbols\n");
+            for ranked in &orientation.top_symbols {
+                let symbol = ranked.symbol;
+                output.push_str(&format!(
+                    "- `{}` ({}) - {}:{} - {} call sites",
+                    symbol.name,
+                    symbol.symbol_type.as_str(),
+                    symbol.file_path.display_slash(),
+                    symbol.line_start,
+                    ranked.calls
+                ));
+                if ranked.definitions > 1 {
+                    output.push_str(&format!(" across {} definitions", ranked.definitions));
                 }
                 output.push('\n');
-                if let Some(ref sig) = sym.old_signature {
-                    output.push_str(&format!("  Was: `{}`\n", sig));
-                }
             }
-            output.push('\n');
         }
 
-        if !sig_changed.is_empty() {
-            output.push_str("## SIGNATURE CHANGED (callers may need updates)\n\n");
-            for sym in &sig_changed {
+        if !orientation.top_files.is_empty() {
+            output.push_str("\n## Central Files\n");
+            for file in &orientation.top_files {
                 output.push_str(&format!(
-                    "- **{}** ({}) in `{}`\n",
-                    sym.name,
-                    sym.symbol_type.as_str(),
-                    sym.file_path.display()
+                    "- {} - {} symbols, ~{} incoming calls\n",
+                    file.path.display_slash(),
+                    file.symbols,
+                    file.calls
                 ));
-                if let Some(ref old_sig) = sym.old_signature {
-                    output.push_str(&format!("  Old: `{}`\n", old_sig));
-                }
-                if let Some(ref new_sig) = sym.new_signature {
-                    output.push_str(&format!("  New: `{}`\n", new_sig));
-                }
             }
-            output.push('\n');
+        }
+
+        output.push_str("\n## Next Steps\n");
+        for (command, why) in &orientation.next_steps {
+            output.push_str(&format!("- `{}` - {}\n", command, why));
         }
 
         output
     }
 
-    fn format_breaking_human(&self, result: &DiffResult) -> String {
+    fn format_prompt_ai(&self, orientation: &Orientation) -> String {
         let mut output = String::new();
-        output.push_str(&format!("{}\n\n", "Breaking Changes".bold().red()));
+        output.push_str(&format!("[ORIENTATION:{}]\n", orientation.project));
+
         output.push_str(&format!(
-            "Since commit: {}\n\n",
-            result.commit[..8.min(result.commit.len())].cyan()
+            "FILES:{} SYMS:{}\nLANGS:",
+            orientation.files, orientation.symbols
         ));
+        for (language, count) in &orientation.languages {
+            output.push_str(&format!(" {}:{}", language, count));
+        }
+        output.push_str("\nKINDS:");
+        for (kind, count) in &orientation.kinds {
+            output.push_str(&format!(" {}:{}", kind.as_str(), count));
+        }
+        output.push('\n');
+
+        for entry in &orientation.entrypoints {
+            output.push_str(&format!(
+                "ENTRY:{}|{}|{}:{}\n",
+                entry.name,
+                entry.symbol_type.as_str(),
+                entry.file_path,
+                entry.line
+            ));
+        }
+        for ranked in &orientation.top_symbols {
+            output.push_str(&format!(
+                "HOT:{}|{}|{}:{}|calls:{}|defs:{}\n",
+                ranked.symbol.name,
+                ranked.symbol.symbol_type.as_str(),
+                ranked.symbol.file_path.display_slash(),
+                ranked.symbol.line_start,
+                ranked.calls,
+                ranked.definitions
+            ));
+        }
+        for file in &orientation.top_files {
+            output.push_str(&format!(
+                "CENTRAL:{}|syms:{}|calls:{}\n",
+                file.path.display_slash(),
+                file.symbols,
+                file.calls
+            ));
+        }
+        for (command, _) in &orientation.next_steps {
+            output.push_str(&format!("NEXT:{}\n", command));
+        }
+
+        output
+    }
+
+    pub fn format_diff(&self, result: &DiffResult) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_diff_default(result),
+            OutputFormat::Human => self.format_diff_human(result),
+            OutputFormat::AI => self.format_diff_ai(result),
+            OutputFormat::Compact => self.format_diff_compact(result),
+        }
+    }
+
+    fn format_diff_default(&self, result: &DiffResult) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Symbol Diff\n\n"));
+        if result.against_worktree {
+            output.push_str(&format!(
+                "Comparing working tree to: `{}`\n\n",
+                short_ref(&result.commit)
+            ));
+        } else {
+            output.push_str(&format!(
+                "Comparing HEAD to commit: `{}`\n\n",
+                short_ref(&result.commit)
+            ));
+        }
+        output.push_str(&format!("Files analyzed: {}\n", result.files_analyzed));
+        output.push_str(&format!("Symbol changes: {}\n\n", result.symbols.len()));
 
         if result.symbols.is_empty() {
-            output.push_str(&format!("{}\n", "No breaking changes detected.".green()));
+            output.push_str("No symbol changes detected.\n");
             return output;
         }
 
+        let mut by_type: HashMap<ChangeType, Vec<&SymbolDiff>> = HashMap::new();
+        for sym in &result.symbols {
+            by_type.entry(sym.change_type).or_default().push(sym);
+        }
+
+        for change_type in [
+            ChangeType::Added,
+            ChangeType::Deleted,
+            ChangeType::Modified,
+            ChangeType::SignatureChanged,
+            ChangeType::Moved,
+            ChangeType::Renamed,
+        ] {
+            if let Some(symbols) = by_type.get(&change_type) {
+                output.push_str(&format!(
+                    "## {} ({})\n\n",
+                    change_type.as_str(),
+                    symbols.len()
+                ));
+                for sym in symbols {
+                    output.push_str(&format!(
+                        "- **{}** ({}) in `{}`",
+                        sym.name,
+                        sym.symbol_type.as_str(),
+                        sym.file_path.display_slash()
+                    ));
+                    if let Some((start, end)) = sym.new_lines {
+                        output.push_str(&format!(" @ lines {}-{}", start, end));
+                    } else if let Some((start, end)) = sym.old_lines {
+                        output.push_str(&format!(" @ lines {}-{} (deleted)", start, end));
+                    }
+                    output.push('\n');
+
+                    if let Some(ref old_name) = sym.old_name {
+                        output.push_str(&format!("  - Renamed from: `{}`\n", old_name));
+                    }
+                    if let Some(ref old_file) = sym.old_file_path {
+                        output.push_str(&format!(
+                            "  - Moved from: `{}`\n",
+                            old_file.display_slash()
+                        ));
+                    }
+
+                    if change_type == ChangeType::SignatureChanged {
+                        if let Some(ref old_sig) = sym.old_signature {
+                            output.push_str(&format!("  - Old: `{}`\n", old_sig));
+                        }
+                        if let Some(ref new_sig) = sym.new_signature {
+                            output.push_str(&format!("  - New: `{}`\n", new_sig));
+                        }
+                    }
+                }
+                output.push('\n');
+            }
+        }
+
+        output
+    }
+
+    fn format_diff_human(&self, result: &DiffResult) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{}\n\n", "Symbol Diff".bold().green()));
+        let label = if result.against_worktree {
+            "Working tree vs"
+        } else {
+            "Commit"
+        };
         output.push_str(&format!(
-            "{} breaking change(s) found\n\n",
-            result.symbols.len().to_string().bold().red()
+            "{}: {}\n",
+            label,
+            short_ref(&result.commit).cyan()
+        ));
+        output.push_str(&format!(
+            "Files analyzed: {}\n",
+            result.files_analyzed.to_string().bold()
+        ));
+        output.push_str(&format!(
+            "Symbol changes: {}\n\n",
+            result.symbols.len().to_string().bold()
         ));
 
-        let deleted: Vec<&SymbolDiff> = result
-            .symbols
-            .iter()
-            .filter(|s| s.change_type == ChangeType::Deleted)
-            .collect();
+        if result.symbols.is_empty() {
+            output.push_str(&format!("{}\n", "No symbol changes detected.".yellow()));
+            return output;
+        }
 
-        let sig_changed: Vec<&SymbolDiff> = result
-            .symbols
-            .iter()
-            .filter(|s| s.change_type == ChangeType::SignatureChanged)
-            .collect();
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Change", "Name", "Type", "File", "Lines"]);
 
-        if !deleted.is_empty() {
-            output.push_str(&format!(
-                "{}\n\n",
-                "REMOVED (callers will break)".bold().red()
-            ));
+        for sym in &result.symbols {
+            let change_str = match sym.change_type {
+                ChangeType::Added => format!("{}", "+".green()),
+                ChangeType::Deleted => format!("{}", "-".red()),
+                ChangeType::Modified => format!("{}", "~".yellow()),
+                ChangeType::SignatureChanged => format!("{}", "!".magenta()),
+                ChangeType::Moved => format!("{}", ">".cyan()),
+                ChangeType::Renamed => format!("{}", "=".cyan()),
+            };
 
-            let mut table = Table::new();
-            table
-                .load_preset(UTF8_FULL)
-                .apply_modifier(UTF8_ROUND_CORNERS)
-                .set_header(vec!["Symbol", "Type", "File", "Was"]);
+            let lines = if let Some((start, end)) = sym.new_lines {
+                format!("{}-{}", start, end)
+            } else if let Some((start, end)) = sym.old_lines {
+                format!("{}-{} (del)", start, end)
+            } else {
+                "-".to_string()
+            };
+
+            let name = match sym.old_name {
+                Some(ref old_name) => format!("{} ← {}", sym.name, old_name),
+                None => sym.name.clone(),
+            };
+            let file = match sym.old_file_path {
+                Some(ref old_file) => {
+                    format!("{} ← {}", sym.file_path.display_slash(), old_file.display_slash())
+                }
+                None => sym.file_path.display_slash().to_string(),
+            };
+
+            table.add_row(vec![
+                change_str,
+                name,
+                sym.symbol_type.as_str().to_string(),
+                file,
+                lines,
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+
+        let sig_changes: Vec<&SymbolDiff> = result
+            .symbols
+            .iter()
+            .filter(|s| s.change_type == ChangeType::SignatureChanged)
+            .collect();
+
+        if !sig_changes.is_empty() {
+            output.push_str(&format!("\n{}\n", "Signature Changes:".cyan()));
+            for sym in sig_changes {
+                output.push_str(&format!("  {} {}\n", "→".cyan(), sym.name.bold()));
+                if let Some(ref old_sig) = sym.old_signature {
+                    output.push_str(&format!("    Old: {}\n", old_sig.red()));
+                }
+                if let Some(ref new_sig) = sym.new_signature {
+                    output.push_str(&format!("    New: {}\n", new_sig.green()));
+                }
+            }
+        }
+
+        output.push_str(&format!(
+            "\n{}: {} {} {} {} {} {} {} {} {} {} {} {}\n",
+            "Legend".cyan(),
+            "+".green(),
+            "added".dimmed(),
+            "-".red(),
+            "deleted".dimmed(),
+            "~".yellow(),
+            "modified".dimmed(),
+            "!".magenta(),
+            "signature".dimmed(),
+            ">".cyan(),
+            "moved".dimmed(),
+            "=".cyan(),
+            "renamed".dimmed()
+        ));
+
+        output
+    }
+
+    fn format_diff_ai(&self, result: &DiffResult) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[DIFF:{}]\n", short_ref(&result.commit)));
+        output.push_str(&format!(
+            "FILES:{} CHANGES:{}\n",
+            result.files_analyzed,
+            result.symbols.len()
+        ));
+
+        if result.symbols.is_empty() {
+            output.push_str("NO_CHANGES\n");
+            return output;
+        }
+
+        for sym in &result.symbols {
+            output.push_str(&format!(
+                "{}|{}|{}|{}",
+                sym.change_type.short(),
+                sym.name,
+                sym.symbol_type.code(),
+                sym.file_path.display_slash()
+            ));
+
+            if let Some((start, end)) = sym.new_lines {
+                output.push_str(&format!("|{}-{}", start, end));
+            } else if let Some((start, end)) = sym.old_lines {
+                output.push_str(&format!("|{}-{}(del)", start, end));
+            }
+
+            if let Some(ref old_name) = sym.old_name {
+                output.push_str(&format!("|from_name:{}", old_name));
+            }
+            if let Some(ref old_file) = sym.old_file_path {
+                output.push_str(&format!("|from_file:{}", old_file.display_slash()));
+            }
+
+            if sym.change_type == ChangeType::SignatureChanged {
+                if let Some(ref old_sig) = sym.old_signature {
+                    output.push_str(&format!("|old:{}", old_sig));
+                }
+                if let Some(ref new_sig) = sym.new_signature {
+                    output.push_str(&format!("|new:{}", new_sig));
+                }
+            }
+
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_diff_compact(&self, result: &DiffResult) -> String {
+        result.symbols.iter().map(compact_diff_line).collect()
+    }
+
+    /// `range` is `base..head` as given, `worktree` for the working tree
+    pub fn format_changed_symbols(&self, changed: &[ChangedSymbol], range: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => {
+                self.format_changed_symbols_default(changed, range)
+            }
+            OutputFormat::Human => self.format_changed_symbols_human(changed, range),
+            OutputFormat::AI => self.format_changed_symbols_ai(changed, range),
+            OutputFormat::Compact => self.format_changed_symbols_compact(changed),
+        }
+    }
+
+    fn format_changed_symbols_default(&self, changed: &[ChangedSymbol], range: &str) -> String {
+        let mut output = String::new();
+        output.push_str("# Changed Symbols\n\n");
+        output.push_str(&format!("Between: `{}`\n", range));
+        output.push_str(&format!("Changed symbols: {}\n", changed.len()));
+
+        if changed.is_empty() {
+            output.push_str("\nNo symbol changes detected.\n");
+            return output;
+        }
+
+        let mut current_file = None;
+        for symbol in changed {
+            if current_file != Some(&symbol.file_path) {
+                current_file = Some(&symbol.file_path);
+                output.push_str(&format!("\n## {}\n\n", symbol.file_path.display_slash()));
+            }
+            output.push_str(&format!(
+                "- {} **{}** ({}) @ line {}\n",
+                symbol.change_type.as_str(),
+                symbol.name,
+                symbol.symbol_type.as_str(),
+                symbol.line
+            ));
+        }
+
+        output
+    }
+
+    fn format_changed_symbols_human(&self, changed: &[ChangedSymbol], range: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {} ({})\n\n",
+            "Changed symbols:".green(),
+            changed.len().to_string().bold(),
+            range
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Change", "Symbol", "Type", "Location"]);
+
+        for symbol in changed {
+            let change = match symbol.change_type {
+                ChangeType::Added => symbol.change_type.as_str().green(),
+                ChangeType::Deleted => symbol.change_type.as_str().red(),
+                _ => symbol.change_type.as_str().yellow(),
+            };
+            table.add_row(vec![
+                change.to_string(),
+                symbol.name.clone(),
+                symbol.symbol_type.as_str().to_string(),
+                format!("{}:{}", symbol.file_path.display_slash(), symbol.line),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    /// One `name|kind|file|change` row per symbol, names first so `cut -d'|' -f1` lists them
+    fn format_changed_symbols_ai(&self, changed: &[ChangedSymbol], range: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[CHANGED:{}|{}]\n", range, changed.len()));
+
+        for symbol in changed {
+            output.push_str(&format!(
+                "{}|{}|{}|{}\n",
+                symbol.name,
+                symbol.symbol_type.code(),
+                symbol.file_path.display_slash(),
+                symbol.change_type.short()
+            ));
+        }
+
+        output
+    }
+
+    fn format_changed_symbols_compact(&self, changed: &[ChangedSymbol]) -> String {
+        changed
+            .iter()
+            .map(|symbol| {
+                let kind = format!(
+                    "{} {}",
+                    symbol.change_type.as_str().to_lowercase(),
+                    symbol.symbol_type.as_str()
+                );
+                compact_line(
+                    &symbol.file_path.display_slash().to_string(),
+                    symbol.line.max(1),
+                    &kind,
+                    &symbol.name,
+                    None,
+                )
+            })
+            .collect()
+    }
+
+    /// `cm diff --symbol`: each changed definition of the symbol with its unified diff;
+    /// `range` is `from..to`
+    pub fn format_symbol_changes(&self, changes: &[SymbolChange], range: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact => {
+                self.format_symbol_changes_default(changes, range)
+            }
+            OutputFormat::Human => self.format_symbol_changes_human(changes, range),
+            OutputFormat::AI => self.format_symbol_changes_ai(changes, range),
+            OutputFormat::Json => {
+                let changes: Vec<serde_json::Value> = changes
+                    .iter()
+                    .map(|change| {
+                        let hunks: Vec<serde_json::Value> = change
+                            .hunks
+                            .iter()
+                            .map(|hunk| {
+                                serde_json::json!({
+                                    "old_start": hunk.old_start,
+                                    "old_count": hunk.old_count,
+                                    "new_start": hunk.new_start,
+                                    "new_count": hunk.new_count,
+                                    "lines": hunk.lines.iter().map(HunkLine::to_diff_line).collect::<Vec<_>>(),
+                                })
+                            })
+                            .collect();
+                        serde_json::json!({
+                            "name": change.name,
+                            "type": change.symbol_type.as_str(),
+                            "change": change.change_type.as_str(),
+                            "old_file": change.old_file.as_ref().map(|file| file.display_slash().to_string()),
+                            "new_file": change.new_file.as_ref().map(|file| file.display_slash().to_string()),
+                            "old_lines": change.old_lines.map(|(start, end)| [start, end]),
+                            "new_lines": change.new_lines.map(|(start, end)| [start, end]),
+                            "hunks": hunks,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({ "range": range, "changes": changes }).to_string()
+            }
+        }
+    }
+
+    fn format_symbol_changes_default(&self, changes: &[SymbolChange], range: &str) -> String {
+        let mut output = String::new();
+        output.push_str("# Symbol Diff\n\n");
+        output.push_str(&format!("Between: `{}`\n", range));
+        if changes.is_empty() {
+            output.push_str("\nNo changes to this symbol.\n");
+        }
+        for change in changes {
+            output.push_str(&format!(
+                "\n## {} {} ({})\n\n",
+                change.change_type.as_str(),
+                change.name,
+                change.symbol_type.as_str()
+            ));
+            output.push_str(&format!("{}\n\n```diff\n", symbol_change_location(change)));
+            for hunk in &change.hunks {
+                output.push_str(&format!("{}\n", hunk.header()));
+                for line in &hunk.lines {
+                    output.push_str(&format!("{}\n", line.to_diff_line()));
+                }
+            }
+            output.push_str("```\n");
+        }
+        output
+    }
+
+    fn format_symbol_changes_human(&self, changes: &[SymbolChange], range: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {} ({})\n",
+            "Symbol changes:".green(),
+            changes.len().to_string().bold(),
+            range
+        ));
+        for change in changes {
+            let kind = match change.change_type {
+                ChangeType::Added => change.change_type.as_str().green(),
+                ChangeType::Deleted => change.change_type.as_str().red(),
+                _ => change.change_type.as_str().yellow(),
+            };
+            output.push_str(&format!(
+                "\n{} {} ({})  {}\n",
+                kind,
+                change.name.bold(),
+                change.symbol_type.as_str(),
+                symbol_change_location(change).dimmed()
+            ));
+            for hunk in &change.hunks {
+                output.push_str(&format!("{}\n", hunk.header().cyan()));
+                for line in &hunk.lines {
+                    let text = line.to_diff_line();
+                    let text = match line {
+                        HunkLine::Context(_) => text.normal(),
+                        HunkLine::Removed(_) => text.red(),
+                        HunkLine::Added(_) => text.green(),
+                    };
+                    output.push_str(&format!("{}\n", text));
+                }
+            }
+        }
+        output
+    }
+
+    /// A `name|kind|change|location` row per definition, then its diff lines unchanged
+    fn format_symbol_changes_ai(&self, changes: &[SymbolChange], range: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[SYMDIFF:{}|{}]\n", range, changes.len()));
+        for change in changes {
+            output.push_str(&format!(
+                "{}|{}|{}|{}\n",
+                change.name,
+                change.symbol_type.code(),
+                change.change_type.short(),
+                symbol_change_location(change)
+            ));
+            for hunk in &change.hunks {
+                output.push_str(&format!("{}\n", hunk.header()));
+                for line in &hunk.lines {
+                    output.push_str(&format!("{}\n", line.to_diff_line()));
+                }
+            }
+        }
+        output
+    }
+
+    /// `cm verify --fingerprint`: files whose structure differs from the recorded baseline
+    pub fn format_fingerprint_drift(&self, drift: &Drift, baseline: &Path) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact => {
+                self.format_fingerprint_drift_default(drift, baseline)
+            }
+            OutputFormat::Human => self.format_fingerprint_drift_human(drift, baseline),
+            OutputFormat::AI => self.format_fingerprint_drift_ai(drift),
+            OutputFormat::Json => serde_json::json!({
+                "clean": drift.is_clean(),
+                "baseline": drift.baseline,
+                "current": drift.current,
+                "changed": drift.changed,
+                "added": drift.added,
+                "removed": drift.removed,
+            })
+            .to_string(),
+        }
+    }
+
+    fn format_fingerprint_drift_default(&self, drift: &Drift, baseline: &Path) -> String {
+        let mut output = String::new();
+        output.push_str("# Fingerprint Drift\n\n");
+        output.push_str(&format!(
+            "Baseline: `{}` ({})\n",
+            drift.baseline,
+            baseline.display_slash()
+        ));
+        output.push_str(&format!("Current:  `{}`\n", drift.current));
+        if drift.is_clean() {
+            output.push_str("\nNo structural drift.\n");
+            return output;
+        }
+        for (title, files) in [
+            ("Changed", &drift.changed),
+            ("Added", &drift.added),
+            ("Removed", &drift.removed),
+        ] {
+            if files.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\n## {} ({})\n\n", title, files.len()));
+            for file in files {
+                output.push_str(&format!("- {}\n", file));
+            }
+        }
+        output
+    }
+
+    fn format_fingerprint_drift_human(&self, drift: &Drift, baseline: &Path) -> String {
+        let mut output = String::new();
+        if drift.is_clean() {
+            output.push_str(&format!(
+                "{} matches {}\n",
+                "Fingerprint".green(),
+                baseline.display_slash().to_string().bold()
+            ));
+            return output;
+        }
+        output.push_str(&format!(
+            "{} from {}: {} changed, {} added, {} removed\n\n",
+            "Structure drifted".yellow(),
+            baseline.display_slash().to_string().bold(),
+            drift.changed.len(),
+            drift.added.len(),
+            drift.removed.len()
+        ));
+        for file in &drift.changed {
+            output.push_str(&format!("  {} {}\n", "~".yellow(), file));
+        }
+        for file in &drift.added {
+            output.push_str(&format!("  {} {}\n", "+".green(), file));
+        }
+        for file in &drift.removed {
+            output.push_str(&format!("  {} {}\n", "-".red(), file));
+        }
+        output
+    }
+
+    /// A `file|change` row per drifted file, with `diff`'s change codes
+    fn format_fingerprint_drift_ai(&self, drift: &Drift) -> String {
+        let mut output = String::new();
+        let total = drift.changed.len() + drift.added.len() + drift.removed.len();
+        output.push_str(&format!("[DRIFT:{}|{}]\n", total, drift.current));
+        for (change, files) in [
+            (ChangeType::Modified, &drift.changed),
+            (ChangeType::Added, &drift.added),
+            (ChangeType::Deleted, &drift.removed),
+        ] {
+            for file in files {
+                output.push_str(&format!("{}|{}\n", file, change.short()));
+            }
+        }
+        output
+    }
+
+    pub fn format_breaking(&self, result: &DiffResult) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_breaking_default(result),
+            OutputFormat::Human => self.format_breaking_human(result),
+            OutputFormat::AI => self.format_breaking_ai(result),
+            OutputFormat::Compact => self.format_breaking_compact(result),
+        }
+    }
+
+    fn format_breaking_default(&self, result: &DiffResult) -> String {
+        let mut output = String::new();
+        output.push_str("# Breaking Changes\n\n");
+        output.push_str(&format!(
+            "Since commit: `{}`\n\n",
+            &result.commit[..8.min(result.commit.len())]
+        ));
+
+        if result.symbols.is_empty() {
+            output.push_str("No breaking changes detected.\n");
+            return output;
+        }
+
+        output.push_str(&format!(
+            "**{} breaking change(s) found**\n\n",
+            result.symbols.len()
+        ));
+
+        let deleted: Vec<&SymbolDiff> = result
+            .symbols
+            .iter()
+            .filter(|s| s.change_type == ChangeType::Deleted)
+            .collect();
+
+        let sig_changed: Vec<&SymbolDiff> = result
+            .symbols
+            .iter()
+            .filter(|s| s.change_type == ChangeType::SignatureChanged)
+            .collect();
+
+        let relocated: Vec<&SymbolDiff> = result
+            .symbols
+            .iter()
+            .filter(|s| matches!(s.change_type, ChangeType::Moved | ChangeType::Renamed))
+            .collect();
+
+        if !deleted.is_empty() {
+            output.push_str("## REMOVED (callers will break)\n\n");
+            for sym in &deleted {
+                output.push_str(&format!(
+                    "- **{}** ({}) in `{}`",
+                    sym.name,
+                    sym.symbol_type.as_str(),
+                    sym.file_path.display_slash()
+                ));
+                if let Some((start, end)) = sym.old_lines {
+                    output.push_str(&format!(" @ lines {}-{}", start, end));
+                }
+                output.push('\n');
+                if let Some(ref sig) = sym.old_signature {
+                    output.push_str(&format!("  Was: `{}`\n", sig));
+                }
+            }
+            output.push('\n');
+        }
+
+        if !relocated.is_empty() {
+            output.push_str("## MOVED/RENAMED (references need updating)\n\n");
+            for sym in &relocated {
+                output.push_str(&format!(
+                    "- **{}** ({}) in `{}`\n",
+                    sym.name,
+                    sym.symbol_type.as_str(),
+                    sym.file_path.display_slash()
+                ));
+                if let Some(ref old_name) = sym.old_name {
+                    output.push_str(&format!("  Was: `{}`\n", old_name));
+                }
+                if let Some(ref old_file) = sym.old_file_path {
+                    output.push_str(&format!("  From: `{}`\n", old_file.display_slash()));
+                }
+            }
+            output.push('\n');
+        }
+
+        if !sig_changed.is_empty() {
+            output.push_str("## SIGNATURE CHANGED (callers may need updates)\n\n");
+            for sym in &sig_changed {
+                output.push_str(&format!(
+                    "- **{}** ({}) in `{}`\n",
+                    sym.name,
+                    sym.symbol_type.as_str(),
+                    sym.file_path.display_slash()
+                ));
+                if let Some(ref old_sig) = sym.old_signature {
+                    output.push_str(&format!("  Old: `{}`\n", old_sig));
+                }
+                if let Some(ref new_sig) = sym.new_signature {
+                    output.push_str(&format!("  New: `{}`\n", new_sig));
+                }
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_breaking_human(&self, result: &DiffResult) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{}\n\n", "Breaking Changes".bold().red()));
+        output.push_str(&format!(
+            "Since commit: {}\n\n",
+            result.commit[..8.min(result.commit.len())].cyan()
+        ));
+
+        if result.symbols.is_empty() {
+            output.push_str(&format!("{}\n", "No breaking changes detected.".green()));
+            return output;
+        }
+
+        output.push_str(&format!(
+            "{} breaking change(s) found\n\n",
+            result.symbols.len().to_string().bold().red()
+        ));
+
+        let deleted: Vec<&SymbolDiff> = result
+            .symbols
+            .iter()
+            .filter(|s| s.change_type == ChangeType::Deleted)
+            .collect();
+
+        let sig_changed: Vec<&SymbolDiff> = result
+            .symbols
+            .iter()
+            .filter(|s| s.change_type == ChangeType::SignatureChanged)
+            .collect();
+
+        let relocated: Vec<&SymbolDiff> = result
+            .symbols
+            .iter()
+            .filter(|s| matches!(s.change_type, ChangeType::Moved | ChangeType::Renamed))
+            .collect();
+
+        if !deleted.is_empty() {
+            output.push_str(&format!(
+                "{}\n\n",
+                "REMOVED (callers will break)".bold().red()
+            ));
+
+            let mut table = Table::new();
+            table
+                .load_preset(UTF8_FULL)
+                .apply_modifier(UTF8_ROUND_CORNERS)
+                .set_header(vec!["Symbol", "Type", "File", "Was"]);
+
+            for sym in &deleted {
+                let was_sig = sym.old_signature.as_deref().unwrap_or("-");
+                table.add_row(vec![
+                    sym.name.clone(),
+                    sym.symbol_type.as_str().to_string(),
+                    sym.file_path.display_slash().to_string(),
+                    was_sig.to_string(),
+                ]);
+            }
+            output.push_str(&format!("{}\n\n", table));
+        }
+
+        if !relocated.is_empty() {
+            output.push_str(&format!(
+                "{}\n\n",
+                "MOVED/RENAMED (references need updating)".bold().red()
+            ));
+
+            for sym in &relocated {
+                let from_name = sym.old_name.as_deref().unwrap_or(&sym.name);
+                let from_file = sym.old_file_path.as_deref().unwrap_or(&sym.file_path);
+                output.push_str(&format!(
+                    "  {} {} ({})\n",
+                    "→".cyan(),
+                    sym.name.bold(),
+                    sym.symbol_type.as_str()
+                ));
+                output.push_str(&format!(
+                    "    Was: {} in {}\n",
+                    from_name.red(),
+                    from_file.display_slash()
+                ));
+                output.push_str(&format!("    Now: {}\n\n", sym.file_path.display_slash()));
+            }
+        }
+
+        if !sig_changed.is_empty() {
+            output.push_str(&format!(
+                "{}\n\n",
+                "SIGNATURE CHANGED (callers may need updates)"
+                    .bold()
+                    .yellow()
+            ));
+
+            for sym in &sig_changed {
+                output.push_str(&format!(
+                    "  {} {} ({})\n",
+                    "→".cyan(),
+                    sym.name.bold(),
+                    sym.symbol_type.as_str()
+                ));
+                output.push_str(&format!("    File: {}\n", sym.file_path.display_slash()));
+                if let Some(ref old_sig) = sym.old_signature {
+                    output.push_str(&format!("    Old: {}\n", old_sig.red()));
+                }
+                if let Some(ref new_sig) = sym.new_signature {
+                    output.push_str(&format!("    New: {}\n", new_sig.green()));
+                }
+                output.push('\n');
+            }
+        }
+
+        output
+    }
+
+    fn format_breaking_ai(&self, result: &DiffResult) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "[BREAKING:{}]\n",
+            &result.commit[..8.min(result.commit.len())]
+        ));
+        output.push_str(&format!("COUNT:{}\n", result.symbols.len()));
+
+        if result.symbols.is_empty() {
+            output.push_str("NO_BREAKING_CHANGES\n");
+            return output;
+        }
+
+        for sym in &result.symbols {
+            let change_marker = match sym.change_type {
+                ChangeType::Deleted => "REMOVED",
+                ChangeType::SignatureChanged => "SIG_CHANGED",
+                ChangeType::Moved => "MOVED",
+                ChangeType::Renamed => "RENAMED",
+                _ => continue,
+            };
+
+            output.push_str(&format!(
+                "{}|{}|{}|{}",
+                change_marker,
+                sym.name,
+                sym.symbol_type.code(),
+                sym.file_path.display_slash()
+            ));
+
+            if sym.change_type == ChangeType::Deleted {
+                if let Some(ref old_sig) = sym.old_signature {
+                    output.push_str(&format!("|was:{}", old_sig));
+                }
+            } else if sym.change_type == ChangeType::SignatureChanged {
+                if let Some(ref old_sig) = sym.old_signature {
+                    output.push_str(&format!("|old:{}", old_sig));
+                }
+                if let Some(ref new_sig) = sym.new_signature {
+                    output.push_str(&format!("|new:{}", new_sig));
+                }
+            }
+
+            if let Some(ref old_name) = sym.old_name {
+                output.push_str(&format!("|from_name:{}", old_name));
+            }
+            if let Some(ref old_file) = sym.old_file_path {
+                output.push_str(&format!("|from_file:{}", old_file.display_slash()));
+            }
+
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_breaking_compact(&self, result: &DiffResult) -> String {
+        result
+            .symbols
+            .iter()
+            .filter(|s| s.change_type != ChangeType::Added && s.change_type != ChangeType::Modified)
+            .map(compact_diff_line)
+            .collect()
+    }
+
+    pub fn format_layers(&self, report: &LayerReport) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_layers_default(report),
+            OutputFormat::Human => self.format_layers_human(report),
+            OutputFormat::AI => self.format_layers_ai(report),
+            OutputFormat::Compact => report.violations.iter().map(compact_violation).collect(),
+        }
+    }
+
+    fn format_layers_default(&self, report: &LayerReport) -> String {
+        let mut output = String::new();
+        output.push_str("# Layers\n\n");
+        let order: Vec<&str> = report
+            .layers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        output.push_str(&format!("Order: `{}`\n\n", order.join(" -> ")));
+
+        for (name, files) in &report.layers {
+            output.push_str(&format!("- {}: {} file(s)\n", name, files));
+        }
+        output.push_str(&format!("- (no layer): {} file(s)\n", report.unlayered));
+
+        if !report.edges.is_empty() {
+            output.push_str("\n## Imports Between Layers\n\n");
+            for (from, to, count) in &report.edges {
+                let upward =
+                    order.iter().position(|l| l == to) < order.iter().position(|l| l == from);
+                output.push_str(&format!(
+                    "- {} -> {}: {}{}\n",
+                    from,
+                    to,
+                    count,
+                    if upward { " (violation)" } else { "" }
+                ));
+            }
+        }
+
+        if report.violations.is_empty() {
+            output.push_str("\nNo layer violations.\n");
+            return output;
+        }
+
+        output.push_str(&format!(
+            "\n## Violations ({})\n\n",
+            report.violations.len()
+        ));
+        for violation in &report.violations {
+            output.push_str(&format!(
+                "- {} `{}` ({} -> {}, imports {})\n",
+                violation_site(violation),
+                violation.import,
+                violation.from_layer,
+                violation.to_layer,
+                violation.target.display_slash()
+            ));
+        }
+        output
+    }
+
+    fn format_layers_human(&self, report: &LayerReport) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{}\n\n", "Layers".bold().green()));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Layer", "Files", "Imports Down", "Imports Up"]);
+        for (i, (name, files)) in report.layers.iter().enumerate() {
+            let count = |above: bool| -> usize {
+                report
+                    .edges
+                    .iter()
+                    .filter(|(from, to, _)| {
+                        from == name
+                            && report
+                                .layers
+                                .iter()
+                                .position(|(layer, _)| layer == to)
+                                .is_some_and(|j| (j < i) == above)
+                    })
+                    .map(|(_, _, count)| count)
+                    .sum()
+            };
+            let upward = count(true);
+            table.add_row(vec![
+                name.clone(),
+                files.to_string(),
+                count(false).to_string(),
+                if upward > 0 {
+                    upward.to_string().red().to_string()
+                } else {
+                    "0".to_string()
+                },
+            ]);
+        }
+        output.push_str(&format!("{}\n", table));
+        output.push_str(&format!(
+            "{} file(s) in no layer\n\n",
+            report.unlayered.to_string().dimmed()
+        ));
+
+        if report.violations.is_empty() {
+            output.push_str(&format!("{}\n", "No layer violations.".green()));
+            return output;
+        }
+
+        let mut violations = Table::new();
+        violations
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Location", "Layers", "Import", "Resolves To"]);
+        for violation in &report.violations {
+            violations.add_row(vec![
+                violation_site(violation),
+                format!("{} -> {}", violation.from_layer, violation.to_layer),
+                violation.import.clone(),
+                violation.target.display_slash().to_string(),
+            ]);
+        }
+        output.push_str(&format!(
+            "{}\n{}\n",
+            format!("{} violation(s)", report.violations.len())
+                .bold()
+                .red(),
+            violations
+        ));
+        output
+    }
+
+    fn format_layers_ai(&self, report: &LayerReport) -> String {
+        let mut output = String::new();
+        let order: Vec<&str> = report
+            .layers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        output.push_str(&format!(
+            "[LAYERS:{}|violations:{}]\n",
+            order.join(">"),
+            report.violations.len()
+        ));
+        let files: Vec<String> = report
+            .layers
+            .iter()
+            .map(|(name, files)| format!("{}:{}", name, files))
+            .chain(std::iter::once(format!("-:{}", report.unlayered)))
+            .collect();
+        output.push_str(&format!("FILES:{}\n", files.join(" ")));
+        let edges: Vec<String> = report
+            .edges
+            .iter()
+            .map(|(from, to, count)| format!("{}>{}:{}", from, to, count))
+            .collect();
+        output.push_str(&format!("EDGES:{}\n", edges.join(" ")));
+        for violation in &report.violations {
+            output.push_str(&format!(
+                "V|{}|{}>{}|{}|{}\n",
+                violation_site(violation),
+                violation.from_layer,
+                violation.to_layer,
+                violation.import,
+                violation.target.display_slash()
+            ));
+        }
+        output
+    }
+
+    pub fn format_semver(&self, advice: &SemverAdvice) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_semver_default(advice)
+            }
+            OutputFormat::Human => self.format_semver_human(advice),
+            OutputFormat::AI => self.format_semver_ai(advice),
+        }
+    }
+
+    fn format_semver_default(&self, advice: &SemverAdvice) -> String {
+        let mut output = String::new();
+        output.push_str("# Semver Advice\n\n");
+        output.push_str(&format!(
+            "From: `{}` (`{}`)\n\n",
+            advice.from,
+            &advice.commit[..8.min(advice.commit.len())]
+        ));
+        output.push_str(&format!(
+            "**Recommended bump: {}**",
+            advice.bump.as_str().to_uppercase()
+        ));
+        if let Some(ref next) = advice.next_version {
+            output.push_str(&format!(" → `{}`", next));
+        }
+        output.push_str("\n\n");
+
+        if advice.justifications.is_empty() {
+            output.push_str("No symbol changes detected.\n");
+            return output;
+        }
+
+        for bump in [Bump::Major, Bump::Minor, Bump::Patch] {
+            let items: Vec<_> = advice
+                .justifications
+                .iter()
+                .filter(|j| j.bump == bump)
+                .collect();
+            if items.is_empty() {
+                continue;
+            }
+
+            let heading = match bump {
+                Bump::Major => "MAJOR (public API removed or changed)",
+                Bump::Minor => "MINOR (public API added)",
+                _ => "PATCH (internal changes)",
+            };
+            output.push_str(&format!("## {}\n\n", heading));
+            for j in items {
+                output.push_str(&format!(
+                    "- {} **{}** ({}) in `{}`\n",
+                    j.change_type.short(),
+                    j.name,
+                    j.symbol_type.as_str(),
+                    j.file_path.display_slash()
+                ));
+                if j.change_type == ChangeType::SignatureChanged {
+                    if let Some(ref old_sig) = j.old_signature {
+                        output.push_str(&format!("  Old: `{}`\n", old_sig));
+                    }
+                    if let Some(ref new_sig) = j.new_signature {
+                        output.push_str(&format!("  New: `{}`\n", new_sig));
+                    }
+                }
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_semver_human(&self, advice: &SemverAdvice) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{}\n\n", "Semver Advice".bold().cyan()));
+        output.push_str(&format!(
+            "From: {} ({})\n",
+            advice.from.bold(),
+            advice.commit[..8.min(advice.commit.len())].cyan()
+        ));
+
+        let bump_label = advice.bump.as_str().to_uppercase();
+        let bump_colored = match advice.bump {
+            Bump::Major => bump_label.bold().red(),
+            Bump::Minor => bump_label.bold().yellow(),
+            _ => bump_label.bold().green(),
+        };
+        output.push_str(&format!("Recommended bump: {}", bump_colored));
+        if let Some(ref next) = advice.next_version {
+            output.push_str(&format!(" → {}", next.bold()));
+        }
+        output.push_str("\n\n");
+
+        if advice.justifications.is_empty() {
+            output.push_str(&format!("{}\n", "No symbol changes detected.".green()));
+            return output;
+        }
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Bump", "Change", "Symbol", "Type", "File"]);
+
+        for j in &advice.justifications {
+            table.add_row(vec![
+                j.bump.as_str().to_string(),
+                j.change_type.as_str().to_string(),
+                j.name.clone(),
+                j.symbol_type.as_str().to_string(),
+                j.file_path.display_slash().to_string(),
+            ]);
+        }
+        output.push_str(&format!("{}\n", table));
+
+        output
+    }
+
+    fn format_semver_ai(&self, advice: &SemverAdvice) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "[SEMVER:{}:{}]\n",
+            advice.from,
+            &advice.commit[..8.min(advice.commit.len())]
+        ));
+        output.push_str(&format!("BUMP:{}\n", advice.bump.as_str()));
+        if let Some(ref next) = advice.next_version {
+            output.push_str(&format!("NEXT:{}\n", next));
+        }
+        output.push_str(&format!("COUNT:{}\n", advice.justifications.len()));
+
+        for j in &advice.justifications {
+            output.push_str(&format!(
+                "{}|{}|{}|{}|{}",
+                j.bump.as_str(),
+                j.change_type.as_str(),
+                j.name,
+                j.symbol_type.code(),
+                j.file_path.display_slash()
+            ));
+            if j.change_type == ChangeType::SignatureChanged {
+                if let Some(ref old_sig) = j.old_signature {
+                    output.push_str(&format!("|old:{}", old_sig));
+                }
+                if let Some(ref new_sig) = j.new_signature {
+                    output.push_str(&format!("|new:{}", new_sig));
+                }
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    pub fn format_callers(&self, callers: &[CallInfo], symbol_name: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_callers_default(callers, symbol_name),
+            OutputFormat::Human => self.format_callers_human(callers, symbol_name),
+            OutputFormat::AI => self.format_callers_ai(callers, symbol_name),
+            OutputFormat::Compact => self.format_callers_compact(callers),
+        }
+    }
+
+    fn format_callers_default(&self, callers: &[CallInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Callers of `{}`\n\n", symbol_name));
+        output.push_str(&format!("Found {} call site(s)\n\n", callers.len()));
+
+        for caller in callers {
+            output.push_str(&format!(
+                "## {} ({})\n",
+                caller.caller_name,
+                caller.caller_type.as_str()
+            ));
+            output.push_str(&format!("- File: {}:{}\n", caller.file_path, caller.line));
+            output.push_str(&format!("- Confidence: {}\n", caller.confidence.as_str()));
+            if !caller.context.is_empty() {
+                output.push_str(&format!("- Context: `{}`\n", caller.context));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_callers_human(&self, callers: &[CallInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Callers of".green(),
+            symbol_name.bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Caller", "Type", "Location", "Confidence", "Context"]);
+
+        for caller in callers {
+            table.add_row(vec![
+                caller.caller_name.clone(),
+                caller.caller_type.as_str().to_string(),
+                format!("{}:{}", caller.file_path, caller.line),
+                confidence_cell(caller.confidence),
+                if caller.context.len() > 60 {
+                    format!("{}...", &caller.context[..57])
+                } else {
+                    caller.context.clone()
+                },
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_callers_ai(&self, callers: &[CallInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[CALLERS:{}|{}]\n", symbol_name, callers.len()));
+
+        for caller in callers {
+            output.push_str(&format!(
+                "{}|{}|{}:{}|{}",
+                caller.caller_name,
+                caller.caller_type.code(),
+                caller.file_path,
+                caller.line,
+                caller.confidence.short()
+            ));
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_callers_compact(&self, callers: &[CallInfo]) -> String {
+        callers
+            .iter()
+            .map(|c| {
+                compact_line(
+                    &c.file_path,
+                    c.line,
+                    c.caller_type.as_str(),
+                    &c.caller_name,
+                    Some(&format!("[{}] {}", c.confidence.as_str(), c.context.trim())),
+                )
+            })
+            .collect()
+    }
+
+    fn format_calls_compact(&self, calls: &[CallInfo]) -> String {
+        calls
+            .iter()
+            .map(|c| {
+                compact_line(
+                    &c.file_path,
+                    c.line,
+                    c.caller_type.as_str(),
+                    &c.caller_name,
+                    Some(c.context.trim()),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_value_refs(&self, refs: &[ValueRef], name: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_value_refs_default(refs, name),
+            OutputFormat::Human => self.format_value_refs_human(refs, name),
+            OutputFormat::AI => self.format_value_refs_ai(refs, name),
+            OutputFormat::Compact => self.format_value_refs_compact(refs),
+        }
+    }
+
+    fn format_value_refs_default(&self, refs: &[ValueRef], name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# References to `{}`\n\n", name));
+
+        let sections = [
+            (RefKind::Definition, "Definitions"),
+            (RefKind::Usage, "Usages"),
+            (RefKind::Text, "In strings and comments"),
+        ];
+        for (kind, title) in sections {
+            let matching: Vec<&ValueRef> = refs.iter().filter(|r| r.kind == kind).collect();
+            if matching.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("## {} ({})\n\n", title, matching.len()));
+            for r in matching {
+                output.push_str(&format!(
+                    "- {}:{}{}: `{}`\n",
+                    r.file_path.display_slash(),
+                    r.line,
+                    r.enclosing
+                        .as_ref()
+                        .map(|e| format!(" in {}", e))
+                        .unwrap_or_default(),
+                    r.context
+                ));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_value_refs_human(&self, refs: &[ValueRef], name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{} {}\n\n", "References to".green(), name.bold()));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Kind", "Location", "In", "Context"]);
+
+        for r in refs {
+            table.add_row(vec![
+                r.kind.as_str().to_string(),
+                format!("{}:{}", r.file_path.display_slash(), r.line),
+                r.enclosing.clone().unwrap_or_else(|| "-".to_string()),
+                if r.context.chars().count() > 60 {
+                    format!("{}...", r.context.chars().take(57).collect::<String>())
+                } else {
+                    r.context.clone()
+                },
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_value_refs_ai(&self, refs: &[ValueRef], name: &str) -> String {
+        let count = |kind| refs.iter().filter(|r| r.kind == kind).count();
+        let mut output = String::new();
+        output.push_str(&format!(
+            "[VALUE_REFS:{}|def:{}|use:{}|text:{}]\n",
+            name,
+            count(RefKind::Definition),
+            count(RefKind::Usage),
+            count(RefKind::Text)
+        ));
+
+        for r in refs {
+            output.push_str(&format!(
+                "{}|{}:{}|{}|{}\n",
+                match r.kind {
+                    RefKind::Definition => "D",
+                    RefKind::Usage => "U",
+                    RefKind::Text => "T",
+                },
+                r.file_path.display_slash(),
+                r.line,
+                r.enclosing.as_deref().unwrap_or("-"),
+                r.context
+            ));
+        }
+
+        output
+    }
+
+    fn format_value_refs_compact(&self, refs: &[ValueRef]) -> String {
+        refs.iter()
+            .map(|r| {
+                compact_line(
+                    &r.file_path.display_slash().to_string(),
+                    r.line,
+                    r.kind.as_str(),
+                    r.enclosing.as_deref().unwrap_or("-"),
+                    Some(&r.context),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_callees(&self, callees: &[CallInfo], symbol_name: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_callees_default(callees, symbol_name),
+            OutputFormat::Human => self.format_callees_human(callees, symbol_name),
+            OutputFormat::AI => self.format_callees_ai(callees, symbol_name),
+            OutputFormat::Compact => self.format_calls_compact(callees),
+        }
+    }
+
+    fn format_callees_default(&self, callees: &[CallInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Functions called by `{}`\n\n", symbol_name));
+        output.push_str(&format!("Found {} callee(s)\n\n", callees.len()));
+
+        for callee in callees {
+            output.push_str(&format!(
+                "## {} ({})\n",
+                callee.caller_name,
+                callee.caller_type.as_str()
+            ));
+            if callee.file_path != "<external>" {
+                output.push_str(&format!(
+                    "- Definition: {}:{}\n",
+                    callee.file_path, callee.line
+                ));
+            } else {
+                output.push_str("- External/built-in function\n");
+            }
+            if !callee.context.is_empty() {
+                output.push_str(&format!("- Signature: `{}`\n", callee.context));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_callees_human(&self, callees: &[CallInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Functions called by".green(),
+            symbol_name.bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Callee", "Type", "Definition", "Signature"]);
+
+        for callee in callees {
+            let location = if callee.file_path != "<external>" {
+                format!("{}:{}", callee.file_path, callee.line)
+            } else {
+                "<external>".to_string()
+            };
+
+            table.add_row(vec![
+                callee.caller_name.clone(),
+                callee.caller_type.as_str().to_string(),
+                location,
+                if callee.context.len() > 40 {
+                    format!("{}...", &callee.context[..37])
+                } else {
+                    callee.context.clone()
+                },
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_callees_ai(&self, callees: &[CallInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[CALLEES:{}|{}]\n", symbol_name, callees.len()));
+
+        for callee in callees {
+            output.push_str(&format!(
+                "{}|{}|{}:{}",
+                callee.caller_name,
+                callee.caller_type.code(),
+                callee.file_path,
+                callee.line
+            ));
+            if !callee.context.is_empty() {
+                output.push_str(&format!("|sig:{}", callee.context));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    pub fn format_tests(&self, tests: &[TestInfo], symbol_name: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_tests_default(tests, symbol_name),
+            OutputFormat::Human => self.format_tests_human(tests, symbol_name),
+            OutputFormat::AI => self.format_tests_ai(tests, symbol_name),
+            OutputFormat::Compact => self.format_tests_compact(tests),
+        }
+    }
+
+    fn format_tests_default(&self, tests: &[TestInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Tests covering `{}`\n\n", symbol_name));
+        output.push_str(&format!("Found {} test(s)\n\n", tests.len()));
+
+        for test in tests {
+            output.push_str(&format!(
+                "## {} ({})\n",
+                test.test_name,
+                test.test_type.as_str()
+            ));
+            output.push_str(&format!(
+                "- Test definition: {}:{}\n",
+                test.file_path, test.line
+            ));
+            if test.by_name {
+                output.push_str("- Linked by: test name\n");
+            } else {
+                output.push_str(&format!("- Calls symbol at: line {}\n", test.call_line));
+            }
+            output.push_str(&format!("- Confidence: {}\n", test.confidence.as_str()));
+            if !test.context.is_empty() {
+                output.push_str(&format!("- Context: `{}`\n", test.context));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_tests_human(&self, tests: &[TestInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Tests covering".green(),
+            symbol_name.bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec![
+                "Test",
+                "Type",
+                "Location",
+                "Call Line",
+                "Confidence",
+                "Context",
+            ]);
+
+        for test in tests {
+            table.add_row(vec![
+                test.test_name.clone(),
+                test.test_type.as_str().to_string(),
+                format!("{}:{}", test.file_path, test.line),
+                if test.by_name {
+                    "by name".to_string()
+                } else {
+                    test.call_line.to_string()
+                },
+                confidence_cell(test.confidence),
+                if test.context.len() > 50 {
+                    format!("{}...", &test.context[..47])
+                } else {
+                    test.context.clone()
+                },
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_tests_ai(&self, tests: &[TestInfo], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[TESTS:{}|{}]\n", symbol_name, tests.len()));
+
+        for test in tests {
+            let link = if test.by_name {
+                "by:name".to_string()
+            } else {
+                format!("call:{}", test.call_line)
+            };
+            output.push_str(&format!(
+                "{}|{}|{}:{}|{}|{}",
+                test.test_name,
+                test.test_type.code(),
+                test.file_path,
+                test.line,
+                link,
+                test.confidence.short()
+            ));
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_tests_compact(&self, tests: &[TestInfo]) -> String {
+        tests
+            .iter()
+            .map(|t| {
+                compact_line(
+                    &t.file_path,
+                    t.call_line,
+                    "test",
+                    &t.test_name,
+                    Some(&if t.by_name {
+                        format!("[{}] by name", t.confidence.as_str())
+                    } else {
+                        format!("[{}] {}", t.confidence.as_str(), t.context.trim())
+                    }),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_kinds(&self, kinds: &[KindInfo]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact => self.format_kinds_default(kinds),
+            OutputFormat::Human => self.format_kinds_human(kinds),
+            OutputFormat::AI => self.format_kinds_ai(kinds),
+            OutputFormat::Json => {
+                let kinds: Vec<serde_json::Value> = kinds
+                    .iter()
+                    .map(|info| {
+                        serde_json::json!({
+                            "name": info.kind.as_str(),
+                            "code": info.kind.code(),
+                            "custom": info.custom,
+                            "languages": kind_languages(info),
+                        })
+                    })
+                    .collect();
+                serde_json::json!({ "kinds": kinds }).to_string()
+            }
+        }
+    }
+
+    fn format_kinds_default(&self, kinds: &[KindInfo]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Symbol Kinds ({})\n\n", kinds.len()));
+        for info in kinds {
+            output.push_str(&format!(
+                "- **{}** (`{}`){}: {}\n",
+                info.kind.as_str(),
+                info.kind.code(),
+                if info.custom { ", custom" } else { "" },
+                kind_languages(info).join(", ")
+            ));
+        }
+        output
+    }
+
+    fn format_kinds_human(&self, kinds: &[KindInfo]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Symbol kinds:".green(),
+            kinds.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Kind", "Code", "Source", "Languages"]);
+
+        for info in kinds {
+            table.add_row(vec![
+                info.kind.as_str().to_string(),
+                info.kind.code().to_string(),
+                if info.custom { "config" } else { "built-in" }.to_string(),
+                kind_languages(info).join(", "),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_kinds_ai(&self, kinds: &[KindInfo]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[KINDS:{}]\n", kinds.len()));
+        output.push_str("name|code|langs\n");
+        for info in kinds {
+            output.push_str(&format!(
+                "{}|{}|{}{}\n",
+                info.kind.as_str(),
+                info.kind.code(),
+                kind_languages(info).join(","),
+                if info.custom { "|custom" } else { "" }
+            ));
+        }
+        output
+    }
+
+    /// Recorded commands for `cm last --list`, newest first
+    pub fn format_command_history(&self, records: &[CommandRecord]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact => {
+                self.format_command_history_default(records)
+            }
+            OutputFormat::Human => self.format_command_history_human(records),
+            OutputFormat::AI => self.format_command_history_ai(records),
+            OutputFormat::Json => serde_json::json!({ "commands": records }).to_string(),
+        }
+    }
+
+    fn format_command_history_default(&self, records: &[CommandRecord]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Recent Commands ({})\n\n", records.len()));
+        for record in records {
+            output.push_str(&format!(
+                "- #{} `{}`: {}, {} lines, exit {}{}\n",
+                record.id,
+                record.command(),
+                record.ago(),
+                record.output_lines,
+                record.exit_code,
+                if record.truncated { ", truncated" } else { "" }
+            ));
+        }
+        output
+    }
+
+    fn format_command_history_human(&self, records: &[CommandRecord]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Recent commands:".green(),
+            records.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["#", "When", "Command", "Exit", "Lines"]);
+
+        for record in records {
+            let exit = record.exit_code.to_string();
+            table.add_row(vec![
+                record.id.to_string(),
+                record.ago(),
+                record.command(),
+                if record.exit_code == 0 {
+                    exit
+                } else {
+                    exit.red().to_string()
+                },
+                record.output_lines.to_string(),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_command_history_ai(&self, records: &[CommandRecord]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[HISTORY:{}]\n", records.len()));
+        output.push_str("id|ago|exit|lines|cmd\n");
+        for record in records {
+            output.push_str(&format!(
+                "{}|{}|{}|{}{}|{}\n",
+                record.id,
+                record.ago(),
+                record.exit_code,
+                record.output_lines,
+                if record.truncated { "+" } else { "" },
+                record.command()
+            ));
+        }
+        output
+    }
+
+    /// `cm extract`: each definition's code; compact prints the code alone, for a clipboard
+    pub fn format_snippets(&self, snippets: &[Snippet]) -> String {
+        match self.format {
+            OutputFormat::Default => self.format_snippets_default(snippets),
+            OutputFormat::Human => self.format_snippets_human(snippets),
+            OutputFormat::AI => self.format_snippets_ai(snippets),
+            OutputFormat::Compact => self.format_snippets_compact(snippets),
+            OutputFormat::Json => {
+                let snippets: Vec<serde_json::Value> = snippets
+                    .iter()
+                    .map(|snippet| {
+                        serde_json::json!({
+                            "name": snippet.name,
+                            "type": snippet.symbol_type.as_str(),
+                            "file": snippet.file_path.display_slash().to_string(),
+                            "lines": [snippet.line_start, snippet.line_end],
+                            "language": snippet.language.as_str(),
+                            "imports": snippet.imports,
+                            "local": snippet.local,
+                            "code": snippet.code(),
+                        })
+                    })
+                    .collect();
+                serde_json::json!({ "snippets": snippets }).to_string()
+            }
+        }
+    }
+
+    fn format_snippets_compact(&self, snippets: &[Snippet]) -> String {
+        snippets
+            .iter()
+            .map(|snippet| format!("{}\n", snippet.code()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn format_snippets_default(&self, snippets: &[Snippet]) -> String {
+        let mut output = String::new();
+        for snippet in snippets {
+            output.push_str(&format!(
+                "# {} ({})\n\n`{}:{}-{}`\n\n```{}\n{}\n```\n",
+                snippet.name,
+                snippet.symbol_type.as_str(),
+                snippet.file_path.display_slash(),
+                snippet.line_start,
+                snippet.line_end,
+                snippet.language.as_str(),
+                snippet.code()
+            ));
+            if !snippet.local.is_empty() {
+                output.push_str(&format!(
+                    "\nAlso uses, from the same file (not included): {}\n",
+                    snippet.local.join(", ")
+                ));
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    fn format_snippets_human(&self, snippets: &[Snippet]) -> String {
+        let mut output = String::new();
+        for snippet in snippets {
+            output.push_str(&format!(
+                "{} {}\n\n{}\n",
+                snippet.name.bold(),
+                format!(
+                    "{}:{}-{}",
+                    snippet.file_path.display_slash(),
+                    snippet.line_start,
+                    snippet.line_end
+                )
+                .dimmed(),
+                snippet.code()
+            ));
+            if !snippet.local.is_empty() {
+                output.push_str(&format!(
+                    "\n{} {}\n",
+                    "Also uses, from the same file:".yellow(),
+                    snippet.local.join(", ")
+                ));
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    fn format_snippets_ai(&self, snippets: &[Snippet]) -> String {
+        let mut output = String::new();
+        for snippet in snippets {
+            output.push_str(&format!(
+                "[EXTRACT:{}|{}|{}|{}-{}]\n{}\n",
+                snippet.name,
+                snippet.symbol_type.code(),
+                snippet.file_path.display_slash(),
+                snippet.line_start,
+                snippet.line_end,
+                snippet.code()
+            ));
+            if !snippet.local.is_empty() {
+                output.push_str(&format!("[LOCAL:{}]\n", snippet.local.join(",")));
+            }
+        }
+        output
+    }
+
+    /// Saved result sets for `cm show`
+    pub fn format_sets(&self, sets: &[SymbolSet]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact => self.format_sets_default(sets),
+            OutputFormat::Human => self.format_sets_human(sets),
+            OutputFormat::AI => self.format_sets_ai(sets),
+            OutputFormat::Json => {
+                let sets: Vec<serde_json::Value> = sets
+                    .iter()
+                    .map(|set| {
+                        serde_json::json!({
+                            "name": set.name,
+                            "command": set.command,
+                            "saved": set.saved,
+                            "symbols": set.symbols.len(),
+                        })
+                    })
+                    .collect();
+                serde_json::json!({ "sets": sets }).to_string()
+            }
+        }
+    }
+
+    fn format_sets_default(&self, sets: &[SymbolSet]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Saved Sets ({})\n\n", sets.len()));
+        for set in sets {
+            output.push_str(&format!(
+                "- **{}**: {} symbols from `{}`, {}\n",
+                set.name,
+                set.symbols.len(),
+                set.command,
+                history::ago(set.saved)
+            ));
+        }
+        output
+    }
+
+    fn format_sets_human(&self, sets: &[SymbolSet]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Saved sets:".green(),
+            sets.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Set", "Symbols", "Saved", "Command"]);
+
+        for set in sets {
+            table.add_row(vec![
+                set.name.clone(),
+                set.symbols.len().to_string(),
+                history::ago(set.saved),
+                set.command.clone(),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_sets_ai(&self, sets: &[SymbolSet]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[SETS:{}]\n", sets.len()));
+        output.push_str("name|syms|saved|cmd\n");
+        for set in sets {
+            output.push_str(&format!(
+                "{}|{}|{}|{}\n",
+                set.name,
+                set.symbols.len(),
+                history::ago(set.saved),
+                set.command
+            ));
+        }
+        output
+    }
+
+    pub fn format_test_list(&self, tests: &[TestCase]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_test_list_default(tests),
+            OutputFormat::Human => self.format_test_list_human(tests),
+            OutputFormat::AI => self.format_test_list_ai(tests),
+            OutputFormat::Compact => self.format_test_list_compact(tests),
+        }
+    }
+
+    fn format_test_list_default(&self, tests: &[TestCase]) -> String {
+        let mut output = String::new();
+        output.push_str("# Tests\n\n");
+        let files: HashSet<&str> = tests.iter().map(|t| t.file_path.as_str()).collect();
+        output.push_str(&format!(
+            "Found {} test(s) in {} file(s)\n",
+            tests.len(),
+            files.len()
+        ));
+
+        let mut current_file = "";
+        let mut current_suites: &[String] = &[];
+        for test in tests {
+            if test.file_path != current_file {
+                current_file = &test.file_path;
+                current_suites = &[];
+                output.push_str(&format!("\n## {}\n\n", current_file));
+            }
+            // A suite heading wherever the nesting changes, indented by its depth
+            let shared = current_suites
+                .iter()
+                .zip(&test.suites)
+                .take_while(|(a, b)| a == b)
+                .count();
+            for (depth, suite) in test.suites.iter().enumerate().skip(shared) {
+                output.push_str(&format!("{}- **{}**\n", "  ".repeat(depth), suite));
+            }
+            current_suites = &test.suites;
+            output.push_str(&format!(
+                "{}- {} @ line {}\n",
+                "  ".repeat(test.suites.len()),
+                test.name,
+                test.line
+            ));
+        }
+
+        output
+    }
+
+    fn format_test_list_human(&self, tests: &[TestCase]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Tests:".green(),
+            tests.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Test", "Suite", "Location"]);
+
+        for test in tests {
+            table.add_row(vec![
+                test.name.clone(),
+                if test.suites.is_empty() {
+                    "-".to_string()
+                } else {
+                    test.suites.join(" > ")
+                },
+                format!("{}:{}", test.file_path, test.line),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_test_list_ai(&self, tests: &[TestCase]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[TEST_LIST:{}]\n", tests.len()));
+
+        for test in tests {
+            output.push_str(&format!("{}|{}:{}", test.name, test.file_path, test.line));
+            if !test.suites.is_empty() {
+                output.push_str(&format!("|in:{}", test.suites.join(">")));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_test_list_compact(&self, tests: &[TestCase]) -> String {
+        tests
+            .iter()
+            .map(|t| {
+                let suites = t.suites.join(" > ");
+                compact_line(
+                    &t.file_path,
+                    t.line,
+                    "test",
+                    &t.name,
+                    (!suites.is_empty()).then_some(suites.as_str()),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_orphan_tests(&self, orphans: &[OrphanTest]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_orphan_tests_default(orphans),
+            OutputFormat::Human => self.format_orphan_tests_human(orphans),
+            OutputFormat::AI => self.format_orphan_tests_ai(orphans),
+            OutputFormat::Compact => self.format_orphan_tests_compact(orphans),
+        }
+    }
+
+    fn format_orphan_tests_default(&self, orphans: &[OrphanTest]) -> String {
+        let mut output = String::new();
+        output.push_str("# Orphaned tests\n\n");
+        output.push_str(&format!(
+            "Found {} test(s) named after symbols that no longer exist\n\n",
+            orphans.len()
+        ));
+
+        for orphan in orphans {
+            output.push_str(&format!(
+                "- {} ({}) @ {}:{} → `{}`\n",
+                orphan.name,
+                orphan.test_type.as_str(),
+                orphan.file_path,
+                orphan.line,
+                orphan.target
+            ));
+        }
+
+        output
+    }
+
+    fn format_orphan_tests_human(&self, orphans: &[OrphanTest]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Orphaned tests:".yellow(),
+            orphans.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Test", "Type", "Location", "Missing Symbol"]);
+
+        for orphan in orphans {
+            table.add_row(vec![
+                orphan.name.clone(),
+                orphan.test_type.as_str().to_string(),
+                format!("{}:{}", orphan.file_path, orphan.line),
+                orphan.target.clone(),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_orphan_tests_ai(&self, orphans: &[OrphanTest]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[ORPHAN_TESTS:{}]\n", orphans.len()));
+
+        for orphan in orphans {
+            output.push_str(&format!(
+                "{}|{}|{}:{}|missing:{}\n",
+                orphan.name,
+                orphan.test_type.code(),
+                orphan.file_path,
+                orphan.line,
+                orphan.target
+            ));
+        }
+
+        output
+    }
+
+    fn format_orphan_tests_compact(&self, orphans: &[OrphanTest]) -> String {
+        orphans
+            .iter()
+            .map(|o| {
+                compact_line(
+                    &o.file_path,
+                    o.line,
+                    "test",
+                    &o.name,
+                    Some(&format!("missing {}", o.target)),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_test_deps(&self, deps: &[TestDep], test_file: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_test_deps_default(deps, test_file)
+            }
+            OutputFormat::Human => self.format_test_deps_human(deps, test_file),
+            OutputFormat::AI => self.format_test_deps_ai(deps, test_file),
+        }
+    }
+
+    fn format_test_deps_default(&self, deps: &[TestDep], test_file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Production dependencies of `{}`\n\n", test_file));
+        output.push_str(&format!(
+            "Found {} production symbol(s) called\n\n",
+            deps.len()
+        ));
+
+        let mut current_file = String::new();
+        for dep in deps {
+            if dep.file_path != current_file {
+                current_file = dep.file_path.clone();
+                output.push_str(&format!("\n## {}\n\n", current_file));
+            }
+
+            output.push_str(&format!(
+                "- **{}** ({}) @ line {} (called from test line {})\n",
+                dep.name,
+                dep.symbol_type.as_str(),
+                dep.line,
+                dep.called_from_line
+            ));
+        }
+
+        output
+    }
+
+    fn format_test_deps_human(&self, deps: &[TestDep], test_file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Production dependencies of".green(),
+            test_file.bold()
+        ));
+        output.push_str(&format!(
+            "Found {} production symbol(s)\n\n",
+            deps.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Symbol", "Type", "Location", "Called From"]);
+
+        for dep in deps {
+            table.add_row(vec![
+                dep.name.clone(),
+                dep.symbol_type.as_str().to_string(),
+                format!("{}:{}", dep.file_path, dep.line),
+                format!("line {}", dep.called_from_line),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_test_deps_ai(&self, deps: &[TestDep], test_file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[TEST_DEPS:{}|{}]\n", test_file, deps.len()));
+
+        for dep in deps {
+            output.push_str(&format!(
+                "{}|{}|{}:{}|from:{}",
+                dep.name,
+                dep.symbol_type.code(),
+                dep.file_path,
+                dep.line,
+                dep.called_from_line
+            ));
+            output.push('\n');
+        }
+
+        output
+    }
+
+    pub fn format_fixtures(&self, fixtures: &[Fixture]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_fixtures_default(fixtures),
+            OutputFormat::Human => self.format_fixtures_human(fixtures),
+            OutputFormat::AI => self.format_fixtures_ai(fixtures),
+            OutputFormat::Compact => self.format_fixtures_compact(fixtures),
+        }
+    }
+
+    fn format_fixtures_default(&self, fixtures: &[Fixture]) -> String {
+        let mut output = String::new();
+        output.push_str("# Fixtures and mocks\n\n");
+        let tests: HashSet<(&str, usize)> = fixtures
+            .iter()
+            .flat_map(|f| &f.uses)
+            .map(|u| (u.test.file_path.as_str(), u.test.line))
+            .collect();
+        output.push_str(&format!(
+            "Found {} fixture(s), mock(s) and helper(s) used by {} test(s)\n\n",
+            fixtures.len(),
+            tests.len()
+        ));
+
+        for fixture in fixtures {
+            output.push_str(&format!("## {} ({})\n", fixture.name, fixture.kind.as_str()));
+            if let Some((file, line)) = &fixture.defined_at {
+                output.push_str(&format!("- Defined: {}:{}\n", file, line));
+            }
+            if fixture.uses.is_empty() {
+                output.push_str("- Unused\n\n");
+                continue;
+            }
+            output.push_str(&format!("- Used by {} test(s):\n", fixture.uses.len()));
+            for usage in &fixture.uses {
+                output.push_str(&format!(
+                    "  - {} @ {}:{}\n",
+                    test_label(&usage.test),
+                    usage.test.file_path,
+                    usage.line
+                ));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_fixtures_human(&self, fixtures: &[Fixture]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Fixtures and mocks:".green(),
+            fixtures.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Name", "Kind", "Defined", "Used By"]);
+
+        for fixture in fixtures {
+            table.add_row(vec![
+                fixture.name.clone(),
+                fixture.kind.as_str().to_string(),
+                match &fixture.defined_at {
+                    Some((file, line)) => format!("{}:{}", file, line),
+                    None => "-".to_string(),
+                },
+                if fixture.uses.is_empty() {
+                    "unused".yellow().to_string()
+                } else {
+                    fixture
+                        .uses
+                        .iter()
+                        .map(|u| {
+                            format!("{} ({}:{})", test_label(&u.test), u.test.file_path, u.line)
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                },
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_fixtures_ai(&self, fixtures: &[Fixture]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[FIXTURES:{}]\n", fixtures.len()));
+
+        for fixture in fixtures {
+            output.push_str(&format!("{}|{}", fixture.name, fixture.kind.as_str()));
+            if let Some((file, line)) = &fixture.defined_at {
+                output.push_str(&format!("|{}:{}", file, line));
+            }
+            output.push_str(&format!("|uses:{}\n", fixture.uses.len()));
+            for usage in &fixture.uses {
+                output.push_str(&format!(
+                    " <{}|{}:{}\n",
+                    test_label(&usage.test),
+                    usage.test.file_path,
+                    usage.line
+                ));
+            }
+        }
+
+        output
+    }
+
+    fn format_fixtures_compact(&self, fixtures: &[Fixture]) -> String {
+        let mut output = String::new();
+        for fixture in fixtures {
+            let kind = fixture.kind.as_str();
+            if let Some((file, line)) = &fixture.defined_at {
+                let detail = format!("used by {} test(s)", fixture.uses.len());
+                output.push_str(&compact_line(file, *line, kind, &fixture.name, Some(&detail)));
+            }
+            for usage in &fixture.uses {
+                let detail = format!("used by {}", test_label(&usage.test));
+                output.push_str(&compact_line(
+                    &usage.test.file_path,
+                    usage.line,
+                    kind,
+                    &fixture.name,
+                    Some(&detail),
+                ));
+            }
+        }
+        output
+    }
+
+    pub fn format_mocks(&self, sites: &[MockSite], symbol: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_mocks_default(sites, symbol),
+            OutputFormat::Human => self.format_mocks_human(sites, symbol),
+            OutputFormat::AI => self.format_mocks_ai(sites, symbol),
+            OutputFormat::Compact => self.format_mocks_compact(sites),
+        }
+    }
+
+    fn format_mocks_default(&self, sites: &[MockSite], symbol: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Mocks of `{}`\n\n", symbol));
+        let files: BTreeSet<&Path> = sites.iter().map(|s| s.file_path.as_path()).collect();
+        output.push_str(&format!(
+            "Found {} mock(s) in {} test file(s)\n\n",
+            sites.len(),
+            files.len()
+        ));
+
+        for file in files {
+            output.push_str(&format!("## {}\n", file.display_slash()));
+            for site in sites.iter().filter(|s| s.file_path == file) {
+                output.push_str(&format!("- {}", site.line));
+                if let Some(test) = &site.test {
+                    output.push_str(&format!(" in `{}`", test));
+                }
+                output.push_str(&format!(
+                    ": `{}` (by {}) - `{}`\n",
+                    site.target,
+                    site.matched.as_str(),
+                    site.context
+                ));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_mocks_human(&self, sites: &[MockSite], symbol: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{} {}\n\n", "Mocks of".green(), symbol.bold()));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Target", "Matched By", "Test", "Location"]);
+
+        for site in sites {
+            table.add_row(vec![
+                site.target.clone(),
+                site.matched.as_str().to_string(),
+                site.test.clone().unwrap_or_else(|| "-".to_string()),
+                format!("{}:{}", site.file_path.display_slash(), site.line),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_mocks_ai(&self, sites: &[MockSite], symbol: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[MOCKS:{}:{}]\n", symbol, sites.len()));
+
+        for site in sites {
+            let location = match &site.test {
+                Some(test) => format!("{}:{}@{}", site.file_path.display_slash(), site.line, test),
+                None => format!("{}:{}", site.file_path.display_slash(), site.line),
+            };
+            output.push_str(&format!(
+                "{}|{}|by:{}\n",
+                location,
+                site.target,
+                site.matched.as_str()
+            ));
+        }
+
+        output
+    }
+
+    fn format_mocks_compact(&self, sites: &[MockSite]) -> String {
+        sites
+            .iter()
+            .map(|site| {
+                let detail = match &site.test {
+                    Some(test) => format!("by {} in {}", site.matched.as_str(), test),
+                    None => format!("by {}", site.matched.as_str()),
+                };
+                compact_line(
+                    &site.file_path.display_slash().to_string(),
+                    site.line,
+                    "mock",
+                    &site.target,
+                    Some(&detail),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_untested(&self, untested: &[UntestedInfo], total_symbols: usize) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_untested_default(untested, total_symbols),
+            OutputFormat::Human => self.format_untested_human(untested, total_symbols),
+            OutputFormat::AI => self.format_untested_ai(untested, total_symbols),
+            OutputFormat::Compact => self.format_untested_compact(untested),
+        }
+    }
+
+    fn format_untested_default(&self, untested: &[UntestedInfo], total_symbols: usize) -> String {
+        let mut output = String::new();
+        output.push_str("# Untested Symbols\n\n");
+
+        let tested_count = total_symbols.saturating_sub(untested.len());
+        let coverage_pct = if total_symbols > 0 {
+            (tested_count as f64 / total_symbols as f64) * 100.0
+        } else {
+            100.0
+        };
+
+        output.push_str(&format!(
+            "**Coverage**: {:.1}% ({} of {} symbols tested)\n",
+            coverage_pct, tested_count, total_symbols
+        ));
+        output.push_str(&format!("**Untested**: {} symbols\n\n", untested.len()));
+
+        let mut current_file = String::new();
+        for info in untested {
+            if info.file_path != current_file {
+                current_file = info.file_path.clone();
+                output.push_str(&format!("\n## {}\n\n", current_file));
+            }
+
+            output.push_str(&format!(
+                "- **{}** ({}) @ line {}",
+                info.name,
+                info.symbol_type.as_str(),
+                info.line
+            ));
+            if let Some(ref sig) = info.signature {
+                output.push_str(&format!(": `{}`", sig));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_untested_human(&self, untested: &[UntestedInfo], total_symbols: usize) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{}\n\n", "Untested Symbols".bold().red()));
+
+        let tested_count = total_symbols.saturating_sub(untested.len());
+        let coverage_pct = if total_symbols > 0 {
+            (tested_count as f64 / total_symbols as f64) * 100.0
+        } else {
+            100.0
+        };
+
+        let coverage_color = if coverage_pct >= 80.0 {
+            format!("{:.1}%", coverage_pct).green()
+        } else if coverage_pct >= 50.0 {
+            format!("{:.1}%", coverage_pct).yellow()
+        } else {
+            format!("{:.1}%", coverage_pct).red()
+        };
+
+        output.push_str(&format!(
+            "{}: {} ({} of {} symbols tested)\n",
+            "Coverage".cyan(),
+            coverage_color,
+            tested_count,
+            total_symbols
+        ));
+        output.push_str(&format!(
+            "{}: {}\n\n",
+            "Untested".cyan(),
+            untested.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Symbol", "Type", "File", "Line", "Signature"]);
+
+        for info in untested {
+            table.add_row(vec![
+                info.name.clone(),
+                info.symbol_type.as_str().to_string(),
+                info.file_path.clone(),
+                info.line.to_string(),
+                info.signature.as_deref().unwrap_or("-").to_string(),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_untested_ai(&self, untested: &[UntestedInfo], total_symbols: usize) -> String {
+        let mut output = String::new();
+
+        let tested_count = total_symbols.saturating_sub(untested.len());
+        let coverage_pct = if total_symbols > 0 {
+            (tested_count as f64 / total_symbols as f64) * 100.0
+        } else {
+            100.0
+        };
+
+        output.push_str(&format!(
+            "[UNTESTED:{}|{:.1}%]\n",
+            untested.len(),
+            coverage_pct
+        ));
+        output.push_str(&format!(
+            "TESTED:{} TOTAL:{}\n",
+            tested_count, total_symbols
+        ));
+
+        for info in untested {
+            output.push_str(&format!(
+                "{}|{}|{}:{}",
+                info.name,
+                info.symbol_type.code(),
+                info.file_path,
+                info.line
+            ));
+            if let Some(ref sig) = info.signature {
+                output.push_str(&format!("|sig:{}", sig));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_untested_compact(&self, untested: &[UntestedInfo]) -> String {
+        untested
+            .iter()
+            .map(|u| {
+                compact_line(
+                    &u.file_path,
+                    u.line,
+                    &format!("untested {}", u.symbol_type.as_str()),
+                    &u.name,
+                    u.signature.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_deprecated(&self, report: &DeprecationReport) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_deprecated_default(report),
+            OutputFormat::Human => self.format_deprecated_human(report),
+            OutputFormat::AI => self.format_deprecated_ai(report),
+            OutputFormat::Compact => self.format_deprecated_compact(report),
+        }
+    }
+
+    fn format_deprecated_default(&self, report: &DeprecationReport) -> String {
+        let mut output = String::new();
+        output.push_str("# Deprecated Symbols\n\n");
+        output.push_str(&format!(
+            "**Migrated**: {:.1}% ({} of {} with no callers left)\n",
+            report.migrated_pct(),
+            report.migrated(),
+            report.deprecations.len()
+        ));
+        output.push_str(&format!(
+            "**Remaining calls**: {}\n",
+            report.remaining_calls()
+        ));
+
+        let mut current_file = PathBuf::new();
+        for deprecation in &report.deprecations {
+            if deprecation.file_path != current_file {
+                current_file = deprecation.file_path.clone();
+                output.push_str(&format!("\n## {}\n\n", current_file.display_slash()));
+            }
+
+            output.push_str(&format!(
+                "- **{}** ({}) @ line {} — `{}`",
+                deprecation.name,
+                deprecation.symbol_type.as_str(),
+                deprecation.line,
+                deprecation.marker
+            ));
+            if let Some(ref note) = deprecation.note {
+                output.push_str(&format!(": {}", note));
+            }
+            output.push('\n');
+
+            if deprecation.callers.is_empty() {
+                output.push_str("  - no callers left\n");
+            }
+            for caller in &deprecation.callers {
+                output.push_str(&format!(
+                    "  - {}:{} in {} ({}): `{}`\n",
+                    caller.file_path,
+                    caller.line,
+                    caller.caller_name,
+                    caller.confidence.as_str(),
+                    caller.context
+                ));
+            }
+        }
+
+        output
+    }
+
+    fn format_deprecated_human(&self, report: &DeprecationReport) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{}\n\n", "Deprecated Symbols".bold().yellow()));
+
+        let migrated_pct = report.migrated_pct();
+        let migrated_color = if migrated_pct >= 80.0 {
+            format!("{:.1}%", migrated_pct).green()
+        } else if migrated_pct >= 50.0 {
+            format!("{:.1}%", migrated_pct).yellow()
+        } else {
+            format!("{:.1}%", migrated_pct).red()
+        };
+        output.push_str(&format!(
+            "{}: {} ({} of {} with no callers left)\n",
+            "Migrated".cyan(),
+            migrated_color,
+            report.migrated(),
+            report.deprecations.len()
+        ));
+        output.push_str(&format!(
+            "{}: {}\n\n",
+            "Remaining calls".cyan(),
+            report.remaining_calls().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Symbol", "Type", "Location", "Marker", "Note", "Calls"]);
+        for deprecation in &report.deprecations {
+            table.add_row(vec![
+                deprecation.name.clone(),
+                deprecation.symbol_type.as_str().to_string(),
+                format!(
+                    "{}:{}",
+                    deprecation.file_path.display_slash(),
+                    deprecation.line
+                ),
+                deprecation.marker.to_string(),
+                deprecation.note.as_deref().unwrap_or("-").to_string(),
+                deprecation.callers.len().to_string(),
+            ]);
+        }
+        output.push_str(&format!("{}\n", table));
+
+        if report.remaining_calls() == 0 {
+            return output;
+        }
+
+        let mut calls = Table::new();
+        calls
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Calls", "Caller", "Location", "Confidence", "Context"]);
+        for deprecation in &report.deprecations {
+            for caller in &deprecation.callers {
+                calls.add_row(vec![
+                    deprecation.name.clone(),
+                    caller.caller_name.clone(),
+                    format!("{}:{}", caller.file_path, caller.line),
+                    confidence_cell(caller.confidence),
+                    if caller.context.len() > 60 {
+                        format!("{}...", &caller.context[..57])
+                    } else {
+                        caller.context.clone()
+                    },
+                ]);
+            }
+        }
+        output.push_str(&format!("\n{}\n{}\n", "Remaining calls".bold(), calls));
+        output
+    }
+
+    fn format_deprecated_ai(&self, report: &DeprecationReport) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "[DEPRECATED:{}|migrated:{}|{:.1}%|calls:{}]\n",
+            report.deprecations.len(),
+            report.migrated(),
+            report.migrated_pct(),
+            report.remaining_calls()
+        ));
+
+        for deprecation in &report.deprecations {
+            output.push_str(&format!(
+                "{}|{}|{}:{}|{}",
+                deprecation.name,
+                deprecation.symbol_type.code(),
+                deprecation.file_path.display_slash(),
+                deprecation.line,
+                deprecation.marker
+            ));
+            if let Some(ref note) = deprecation.note {
+                output.push_str(&format!("|note:{}", note));
+            }
+            output.push_str(&format!("|calls:{}\n", deprecation.callers.len()));
+            for caller in &deprecation.callers {
+                output.push_str(&format!(
+                    " <{}|{}:{}|{}\n",
+                    caller.caller_name,
+                    caller.file_path,
+                    caller.line,
+                    caller.confidence.short()
+                ));
+            }
+        }
+
+        output
+    }
+
+    /// The deprecated symbols, each followed by the calls still reaching it
+    fn format_deprecated_compact(&self, report: &DeprecationReport) -> String {
+        let mut output = String::new();
+        for deprecation in &report.deprecations {
+            let detail = match deprecation.note {
+                Some(ref note) => format!("{} ({} call(s))", note, deprecation.callers.len()),
+                None => format!("{} call(s)", deprecation.callers.len()),
+            };
+            output.push_str(&compact_line(
+                &deprecation.file_path.display_slash().to_string(),
+                deprecation.line,
+                &format!("deprecated {}", deprecation.symbol_type.as_str()),
+                &deprecation.name,
+                Some(&detail),
+            ));
+            for caller in &deprecation.callers {
+                output.push_str(&compact_line(
+                    &caller.file_path,
+                    caller.line,
+                    "calls deprecated",
+                    &deprecation.name,
+                    Some(&format!(
+                        "[{}] {}",
+                        caller.confidence.as_str(),
+                        caller.context
+                    )),
+                ));
+            }
+        }
+        output
+    }
+
+    pub fn format_entrypoints(&self, entrypoints: &[EntrypointInfo]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_entrypoints_default(entrypoints),
+            OutputFormat::Human => self.format_entrypoints_human(entrypoints),
+            OutputFormat::AI => self.format_entrypoints_ai(entrypoints),
+            OutputFormat::Compact => self.format_entrypoints_compact(entrypoints),
+        }
+    }
+
+    fn format_entrypoints_default(&self, entrypoints: &[EntrypointInfo]) -> String {
+        let mut output = String::new();
+        output.push_str("# Entrypoints (Uncalled Exported Symbols)\n\n");
+        output.push_str(&format!("Found {} entrypoint(s)\n\n", entrypoints.len()));
+
+        let main_entries: Vec<_> = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::MainEntry)
+            .collect();
+        let api_funcs: Vec<_> = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::ApiFunction)
+            .collect();
+        let unused: Vec<_> = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::PossiblyUnused)
+            .collect();
+
+        if !main_entries.is_empty() {
+            output.push_str("## Main Entrypoints\n\n");
+            for entry in &main_entries {
+                output.push_str(&format!(
+                    "- **{}** ({}) @ `{}:{}`",
+                    entry.name,
+                    entry.symbol_type.as_str(),
+                    entry.file_path,
+                    entry.line
+                ));
+                if let Some(ref sig) = entry.signature {
+                    output.push_str(&format!("\n  `{}`", sig));
+                }
+                output.push('\n');
+            }
+            output.push('\n');
+        }
+
+        if !api_funcs.is_empty() {
+            output.push_str("## API Functions\n\n");
+            for entry in &api_funcs {
+                output.push_str(&format!(
+                    "- **{}** ({}) @ `{}:{}`",
+                    entry.name,
+                    entry.symbol_type.as_str(),
+                    entry.file_path,
+                    entry.line
+                ));
+                if let Some(ref sig) = entry.signature {
+                    output.push_str(&format!("\n  `{}`", sig));
+                }
+                output.push('\n');
+            }
+            output.push('\n');
+        }
+
+        if !unused.is_empty() {
+            output.push_str("## Possibly Unused\n\n");
+            for entry in &unused {
+                output.push_str(&format!(
+                    "- **{}** ({}) @ `{}:{}`",
+                    entry.name,
+                    entry.symbol_type.as_str(),
+                    entry.file_path,
+                    entry.line
+                ));
+                if let Some(ref sig) = entry.signature {
+                    output.push_str(&format!("\n  `{}`", sig));
+                }
+                output.push('\n');
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_entrypoints_human(&self, entrypoints: &[EntrypointInfo]) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{}\n\n",
+            "Entrypoints (Uncalled Exported Symbols)".bold().green()
+        ));
+
+        let main_count = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::MainEntry)
+            .count();
+        let api_count = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::ApiFunction)
+            .count();
+        let unused_count = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::PossiblyUnused)
+            .count();
+
+        output.push_str(&format!(
+            "{}: {} total ({} main, {} API, {} possibly unused)\n\n",
+            "Found".cyan(),
+            entrypoints.len().to_string().bold(),
+            main_count.to_string().green(),
+            api_count.to_string().cyan(),
+            unused_count.to_string().yellow()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec![
+                "Category",
+                "Symbol",
+                "Type",
+                "File",
+                "Line",
+                "Signature",
+            ]);
+
+        for entry in entrypoints {
+            let category_str = match entry.category {
+                EntrypointCategory::MainEntry => "Main".green().to_string(),
+                EntrypointCategory::ApiFunction => "API".cyan().to_string(),
+                EntrypointCategory::PossiblyUnused => "Unused?".yellow().to_string(),
+            };
+            table.add_row(vec![
+                category_str,
+                entry.name.clone(),
+                entry.symbol_type.as_str().to_string(),
+                entry.file_path.clone(),
+                entry.line.to_string(),
+                entry.signature.as_deref().unwrap_or("-").to_string(),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_entrypoints_ai(&self, entrypoints: &[EntrypointInfo]) -> String {
+        let mut output = String::new();
+
+        let main_count = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::MainEntry)
+            .count();
+        let api_count = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::ApiFunction)
+            .count();
+        let unused_count = entrypoints
+            .iter()
+            .filter(|e| e.category == EntrypointCategory::PossiblyUnused)
+            .count();
+
+        output.push_str(&format!("[ENTRYPOINTS:{}]\n", entrypoints.len()));
+        output.push_str(&format!(
+            "MAIN:{} API:{} UNUSED:{}\n",
+            main_count, api_count, unused_count
+        ));
+
+        for entry in entrypoints {
+            let cat_short = match entry.category {
+                EntrypointCategory::MainEntry => "main",
+                EntrypointCategory::ApiFunction => "api",
+                EntrypointCategory::PossiblyUnused => "unused",
+            };
+            output.push_str(&format!(
+                "{}|{}|{}|{}:{}",
+                cat_short,
+                entry.name,
+                entry.symbol_type.code(),
+                entry.file_path,
+                entry.line
+            ));
+            if let Some(ref sig) = entry.signature {
+                output.push_str(&format!("|sig:{}", sig));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_entrypoints_compact(&self, entrypoints: &[EntrypointInfo]) -> String {
+        entrypoints
+            .iter()
+            .map(|e| {
+                compact_line(
+                    &e.file_path,
+                    e.line,
+                    e.symbol_type.as_str(),
+                    &e.name,
+                    e.signature.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_blame(&self, result: &BlameResult) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_blame_default(result)
+            }
+            OutputFormat::Human => self.format_blame_human(result),
+            OutputFormat::AI => self.format_blame_ai(result),
+        }
+    }
+
+    fn format_blame_default(&self, result: &BlameResult) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Blame for `{}`\n\n", result.symbol_name));
+        output.push_str(&format!("Type: {}\n", result.symbol_type.as_str()));
+        output.push_str(&format!(
+            "Lines: {}-{}\n\n",
+            result.current_lines.0, result.current_lines.1
+        ));
+
+        output.push_str("## Last Modification\n\n");
+        output.push_str(&format!("- Commit: `{}`\n", result.last_commit.short_hash));
+        output.push_str(&format!("- Author: {}\n", result.last_commit.author));
+        output.push_str(&format!("- Date: {}\n", result.last_commit.date));
+        output.push_str(&format!("- Message: {}\n", result.last_commit.message));
+
+        if result.old_signature.is_some() || result.new_signature.is_some() {
+            output.push_str("\n## Signature Change\n\n");
+            if let Some(ref old_sig) = result.old_signature {
+                output.push_str(&format!("- Old: `{}`\n", old_sig));
+            }
+            if let Some(ref new_sig) = result.new_signature {
+                output.push_str(&format!("- New: `{}`\n", new_sig));
+            }
+        }
+
+        output
+    }
+
+    fn format_blame_human(&self, result: &BlameResult) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Blame for".green(),
+            result.symbol_name.bold()
+        ));
+        output.push_str(&format!(
+            "Type: {} | Lines: {}-{}\n\n",
+            result.symbol_type.as_str().cyan(),
+            result.current_lines.0,
+            result.current_lines.1
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Commit", "Author", "Date", "Message"]);
+
+        table.add_row(vec![
+            result.last_commit.short_hash.clone(),
+            result.last_commit.author.clone(),
+            result.last_commit.date.clone(),
+            if result.last_commit.message.len() > 50 {
+                format!("{}...", &result.last_commit.message[..47])
+            } else {
+                result.last_commit.message.clone()
+            },
+        ]);
+
+        output.push_str(&format!("{}\n", table));
+
+        if result.old_signature.is_some() || result.new_signature.is_some() {
+            output.push_str(&format!("\n{}\n", "Signature Change:".cyan()));
+            if let Some(ref old_sig) = result.old_signature {
+                output.push_str(&format!("  Old: {}\n", old_sig.red()));
+            }
+            if let Some(ref new_sig) = result.new_signature {
+                output.push_str(&format!("  New: {}\n", new_sig.green()));
+            }
+        }
+
+        output
+    }
+
+    fn format_blame_ai(&self, result: &BlameResult) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "[BLAME:{}|{}]\n",
+            result.symbol_name,
+            result.symbol_type.as_str()
+        ));
+        output.push_str(&format!(
+            "LINES:{}-{}\n",
+            result.current_lines.0, result.current_lines.1
+        ));
+        output.push_str(&format!(
+            "COMMIT:{}|{}|{}|{}\n",
+            result.last_commit.short_hash,
+            result.last_commit.author,
+            result.last_commit.date,
+            result.last_commit.message
+        ));
+
+        if let Some(ref old_sig) = result.old_signature {
+            output.push_str(&format!("OLD_SIG:{}\n", old_sig));
+        }
+        if let Some(ref new_sig) = result.new_signature {
+            output.push_str(&format!("NEW_SIG:{}\n", new_sig));
+        }
+
+        output
+    }
+
+    pub fn format_file_blame(&self, blame: &[SymbolBlame], file: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_file_blame_default(blame, file)
+            }
+            OutputFormat::Human => self.format_file_blame_human(blame, file),
+            OutputFormat::AI => self.format_file_blame_ai(blame, file),
+        }
+    }
+
+    fn format_file_blame_default(&self, blame: &[SymbolBlame], file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Blame for `{}`\n\n", file));
+        output.push_str(&format!(
+            "{} symbol(s) across {} commit(s)\n\n",
+            blame.len(),
+            distinct_commits(blame)
+        ));
+        output.push_str("| Symbol | Type | Lines | Commit | Author | Date | Message |\n");
+        output.push_str("|--------|------|-------|--------|--------|------|---------|\n");
+
+        for entry in blame {
+            let (commit, author, date, message) = match &entry.commit {
+                Some(c) => (
+                    format!("`{}`", c.short_hash),
+                    c.author.as_str(),
+                    c.date.as_str(),
+                    c.message.replace('|', "\\|"),
+                ),
+                None => ("uncommitted".to_string(), "-", "-", "-".to_string()),
+            };
+            output.push_str(&format!(
+                "| {} | {} | {}-{} | {} | {} | {} | {} |\n",
+                entry.symbol_name,
+                entry.symbol_type.as_str(),
+                entry.lines.0,
+                entry.lines.1,
+                commit,
+                author,
+                date,
+                message
+            ));
+        }
+
+        output
+    }
+
+    fn format_file_blame_human(&self, blame: &[SymbolBlame], file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{} {}\n\n", "Blame for".green(), file.bold()));
+        output.push_str(&format!(
+            "{} symbol(s) across {} commit(s)\n\n",
+            blame.len().to_string().bold(),
+            distinct_commits(blame).to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec![
+                "Symbol", "Type", "Lines", "Commit", "Author", "Date", "Message",
+            ]);
+
+        for entry in blame {
+            let mut row = vec![
+                entry.symbol_name.clone(),
+                entry.symbol_type.as_str().to_string(),
+                format!("{}-{}", entry.lines.0, entry.lines.1),
+            ];
+            match &entry.commit {
+                Some(c) => row.extend([
+                    c.short_hash.clone(),
+                    c.author.clone(),
+                    c.date.get(..10).unwrap_or(&c.date).to_string(),
+                    if c.message.chars().count() > 40 {
+                        format!("{}...", c.message.chars().take(37).collect::<String>())
+                    } else {
+                        c.message.clone()
+                    },
+                ]),
+                None => row.extend([
+                    "uncommitted".yellow().to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                ]),
+            }
+            table.add_row(row);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_file_blame_ai(&self, blame: &[SymbolBlame], file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "[BLAME_FILE:{}|{}|commits:{}]\n",
+            file,
+            blame.len(),
+            distinct_commits(blame)
+        ));
+
+        for entry in blame {
+            output.push_str(&format!(
+                "{}|{}|{}-{}|",
+                entry.symbol_name,
+                entry.symbol_type.as_str(),
+                entry.lines.0,
+                entry.lines.1
+            ));
+            match &entry.commit {
+                Some(c) => output.push_str(&format!(
+                    "{}|{}|{}|{}\n",
+                    c.short_hash, c.author, c.date, c.message
+                )),
+                None => output.push_str("uncommitted\n"),
+            }
+        }
+
+        output
+    }
+
+    pub fn format_history(&self, history: &[HistoryEntry], symbol_name: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_history_default(history, symbol_name)
+            }
+            OutputFormat::Human => self.format_history_human(history, symbol_name),
+            OutputFormat::AI => self.format_history_ai(history, symbol_name),
+        }
+    }
+
+    fn format_history_default(&self, history: &[HistoryEntry], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# History for `{}`\n\n", symbol_name));
+        output.push_str(&format!("Found {} version(s)\n\n", history.len()));
+
+        for (i, entry) in history.iter().enumerate() {
+            let version_num = history.len() - i;
+            let status = if entry.existed { "exists" } else { "deleted" };
+
+            output.push_str(&format!("## Version {} ({})\n\n", version_num, status));
+            output.push_str(&format!("- Commit: `{}`\n", entry.commit.short_hash));
+            output.push_str(&format!("- Author: {}\n", entry.commit.author));
+            output.push_str(&format!("- Date: {}\n", entry.commit.date));
+            output.push_str(&format!("- Message: {}\n", entry.commit.message));
+
+            if let Some((start, end)) = entry.lines {
+                output.push_str(&format!("- Lines: {}-{}\n", start, end));
+            }
+            if let Some(ref sig) = entry.signature {
+                output.push_str(&format!("- Signature: `{}`\n", sig));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_history_human(&self, history: &[HistoryEntry], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "History for".green(),
+            symbol_name.bold()
+        ));
+        output.push_str(&format!(
+            "Found {} version(s)\n\n",
+            history.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["#", "Commit", "Date", "Status", "Signature"]);
+
+        for (i, entry) in history.iter().enumerate() {
+            let version_num = history.len() - i;
+            let status = if entry.existed {
+                "✓".green().to_string()
+            } else {
+                "✗".red().to_string()
+            };
+
+            let sig = entry.signature.as_deref().unwrap_or("-");
+            let sig_display = if sig.len() > 40 {
+                format!("{}...", &sig[..37])
+            } else {
+                sig.to_string()
+            };
+
+            table.add_row(vec![
+                version_num.to_string(),
+                entry.commit.short_hash.clone(),
+                entry.commit.date.clone(),
+                status,
+                sig_display,
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_history_ai(&self, history: &[HistoryEntry], symbol_name: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[HISTORY:{}|{}]\n", symbol_name, history.len()));
+
+        for entry in history {
+            let status = if entry.existed { "+" } else { "-" };
+            output.push_str(&format!(
+                "{}|{}|{}|{}",
+                status, entry.commit.short_hash, entry.commit.date, entry.commit.message
+            ));
+
+            if let Some((start, end)) = entry.lines {
+                output.push_str(&format!("|{}-{}", start, end));
+            }
+            if let Some(ref sig) = entry.signature {
+                output.push_str(&format!("|sig:{}", sig));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    pub fn format_file_history(&self, history: &[FileApiEntry], file: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_file_history_default(history, file)
+            }
+            OutputFormat::Human => self.format_file_history_human(history, file),
+            OutputFormat::AI => self.format_file_history_ai(history, file),
+        }
+    }
+
+    fn format_file_history_default(&self, history: &[FileApiEntry], file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# API History for `{}`\n\n", file));
+        output.push_str(&format!(
+            "Found {} API-changing commit(s)\n\n",
+            history.len()
+        ));
+
+        for entry in history {
+            output.push_str(&format!(
+                "## `{}` {}\n\n",
+                entry.commit.short_hash, entry.commit.message
+            ));
+            output.push_str(&format!(
+                "- Author: {} ({})\n",
+                entry.commit.author, entry.commit.date
+            ));
+            output.push_str(&format!("- Exported symbols: {}\n", entry.exported_count));
+
+            for change in &entry.changes {
+                output.push_str(&format!(
+                    "- {} **{}** ({})",
+                    change.change_type.as_str(),
+                    change.name,
+                    change.symbol_type.as_str()
+                ));
+                match change.change_type {
+                    ChangeType::SignatureChanged => {
+                        output.push_str(&format!(
+                            ": `{}` → `{}`",
+                            change.old_signature.as_deref().unwrap_or("-"),
+                            change.new_signature.as_deref().unwrap_or("-")
+                        ));
+                    }
+                    ChangeType::Deleted => {
+                        if let Some(ref sig) = change.old_signature {
+                            output.push_str(&format!(": `{}`", sig));
+                        }
+                    }
+                    _ => {
+                        if let Some(ref sig) = change.new_signature {
+                            output.push_str(&format!(": `{}`", sig));
+                        }
+                    }
+                }
+                output.push('\n');
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_file_history_human(&self, history: &[FileApiEntry], file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{} {}\n\n", "API History for".green(), file.bold()));
+        output.push_str(&format!(
+            "Found {} API-changing commit(s)\n\n",
+            history.len().to_string().bold()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Commit", "Date", "Exported", "Added", "Removed", "Changed"]);
+
+        for entry in history {
+            let names = |change_type: ChangeType| -> String {
+                let list: Vec<&str> = entry
+                    .changes
+                    .iter()
+                    .filter(|c| c.change_type == change_type)
+                    .map(|c| c.name.as_str())
+                    .collect();
+                if list.is_empty() {
+                    "-".to_string()
+                } else {
+                    list.join(", ")
+                }
+            };
+
+            table.add_row(vec![
+                entry.commit.short_hash.clone(),
+                entry.commit.date.clone(),
+                entry.exported_count.to_string(),
+                names(ChangeType::Added),
+                names(ChangeType::Deleted),
+                names(ChangeType::SignatureChanged),
+            ]);
+        }
+
+        output.push_str(&format!("{}\n", table));
+        output
+    }
+
+    fn format_file_history_ai(&self, history: &[FileApiEntry], file: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("[API_HISTORY:{}|{}]\n", file, history.len()));
+
+        for entry in history {
+            output.push_str(&format!(
+                "@{}|{}|{}|exported:{}\n",
+                entry.commit.short_hash,
+                entry.commit.date,
+                entry.commit.message,
+                entry.exported_count
+            ));
+            for change in &entry.changes {
+                output.push_str(&format!("{}{}", change.change_type.short(), change.name));
+                if let Some(ref old_sig) = change.old_signature {
+                    output.push_str(&format!("|old:{}", old_sig));
+                }
+                if let Some(ref new_sig) = change.new_signature {
+                    output.push_str(&format!("|new:{}", new_sig));
+                }
+                output.push('\n');
+            }
+        }
+
+        output
+    }
+
+    pub fn format_trace(&self, trace: &TracePath, from: &str, to: &str) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_trace_default(trace, from, to)
+            }
+            OutputFormat::Human => self.format_trace_human(trace, from, to),
+            OutputFormat::AI => self.format_trace_ai(trace, from, to),
+        }
+    }
+
+    fn format_trace_default(&self, trace: &TracePath, from: &str, to: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("# Call Path: {} → {}\n\n", from, to));
+
+        if !trace.found {
+            output.push_str("No call path found between these symbols.\n");
+            return output;
+        }
+
+        output.push_str(&format!(
+            "**Path length**: {} step(s)\n\n",
+            trace.steps.len()
+        ));
+        output.push_str("## Call Chain\n\n");
+        output.push_str("```\n");
+
+        for (i, step) in trace.steps.iter().enumerate() {
+            if i > 0 {
+                output.push_str("    ↓\n");
+            }
+            output.push_str(&format!(
+                "[{}] {} ({})\n",
+                i + 1,
+                step.symbol_name,
+                step.symbol_type.as_str()
+            ));
+            output.push_str(&format!("    {}:{}\n", step.file_path, step.line));
+        }
+
+        output.push_str("```\n");
+        output
+    }
+
+    fn format_trace_human(&self, trace: &TracePath, from: &str, to: &str) -> String {
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {} {} {}\n\n",
+            "Call Path:".green(),
+            from.bold(),
+            "→".cyan(),
+            to.bold()
+        ));
+
+        if !trace.found {
+            output.push_str(&format!(
+                "{} No call path found between these symbols.\n",
+                "✗".yellow()
+            ));
+            return output;
+        }
+
+        output.push_str(&format!(
+            "{}: {} step(s)\n\n",
+            "Path length".cyan(),
+            trace.steps.len()
+        ));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["Step", "Symbol", "Type", "Location"]);
+
+        for (i, step) in trace.steps.iter().enumerate() {
+            let step_marker = if i == 0 {
+                "→".green().to_string()
+            } else if i == trace.steps.len() - 1 {
+                "◉".cyan().to_string()
+            } else {
+                "↓".white().to_string()
+            };
 
-            for sym in &deleted {
-                let was_sig = sym.old_signature.as_deref().unwrap_or("-");
-                table.add_row(vec![
-                    sym.name.clone(),
-                    sym.symbol_type.as_str().to_string(),
-                    sym.file_path.display().to_string(),
-                    was_sig.to_string(),
-                ]);
-            }
-            output.push_str(&format!("{}\n\n", table));
+            table.add_row(vec![
+                step_marker,
+                step.symbol_name.clone(),
+                step.symbol_type.as_str().to_string(),
+                format!("{}:{}", step.file_path, step.line),
+            ]);
         }
 
-        if !sig_changed.is_empty() {
-            output.push_str(&format!(
-                "{}\n\n",
-                "SIGNATURE CHANGED (callers may need updates)"
-                    .bold()
-                    .yellow()
-            ));
+        output.push_str(&format!("{}\n", table));
 
-            for sym in &sig_changed {
-                output.push_str(&format!(
-                    "  {} {} ({})\n",
-                    "→".cyan(),
-                    sym.name.bold(),
-                    sym.symbol_type.as_str()
-                ));
-                output.push_str(&format!("    File: {}\n", sym.file_path.display()));
-                if let Some(ref old_sig) = sym.old_signature {
-                    output.push_str(&format!("    Old: {}\n", old_sig.red()));
-                }
-                if let Some(ref new_sig) = sym.new_signature {
-                    output.push_str(&format!("    New: {}\n", new_sig.green()));
-                }
-                output.push('\n');
-            }
-        }
+        output.push_str(&format!("\n{}\n", "Call Chain:".cyan()));
+        let names: Vec<&str> = trace.steps.iter().map(|s| s.symbol_name.as_str()).collect();
+        output.push_str(&format!("  {}\n", names.join(" → ")));
 
         output
     }
 
-    fn format_breaking_ai(&self, result: &DiffResult) -> String {
+    fn format_trace_ai(&self, trace: &TracePath, from: &str, to: &str) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "[BREAKING:{}]\n",
-            &result.commit[..8.min(result.commit.len())]
-        ));
-        output.push_str(&format!("COUNT:{}\n", result.symbols.len()));
+        output.push_str(&format!("[TRACE:{}->{}]\n", from, to));
 
-        if result.symbols.is_empty() {
-            output.push_str("NO_BREAKING_CHANGES\n");
+        if !trace.found {
+            output.push_str("FOUND:false\n");
             return output;
         }
 
-        for sym in &result.symbols {
-            let change_marker = match sym.change_type {
-                ChangeType::Deleted => "REMOVED",
-                ChangeType::SignatureChanged => "SIG_CHANGED",
-                _ => continue,
-            };
+        output.push_str(&format!("FOUND:true STEPS:{}\n", trace.steps.len()));
+
+        let names: Vec<&str> = trace.steps.iter().map(|s| s.symbol_name.as_str()).collect();
+        output.push_str(&format!("PATH:{}\n", names.join("|")));
 
+        for step in &trace.steps {
             output.push_str(&format!(
-                "{}|{}|{}|{}",
-                change_marker,
-                sym.name,
-                match sym.symbol_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                sym.file_path.display()
+                "{}|{}|{}:{}\n",
+                step.symbol_name,
+                step.symbol_type.code(),
+                step.file_path,
+                step.line
             ));
-
-            if sym.change_type == ChangeType::Deleted {
-                if let Some(ref old_sig) = sym.old_signature {
-                    output.push_str(&format!("|was:{}", old_sig));
-                }
-            } else if sym.change_type == ChangeType::SignatureChanged {
-                if let Some(ref old_sig) = sym.old_signature {
-                    output.push_str(&format!("|old:{}", old_sig));
-                }
-                if let Some(ref new_sig) = sym.new_signature {
-                    output.push_str(&format!("|new:{}", new_sig));
-                }
-            }
-
-            output.push('\n');
         }
 
         output
     }
 
-    pub fn format_callers(&self, callers: &[CallInfo], symbol_name: &str) -> String {
+    pub fn format_cli_map(&self, commands: &[CliCommand]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_callers_default(callers, symbol_name),
-            OutputFormat::Human => self.format_callers_human(callers, symbol_name),
-            OutputFormat::AI => self.format_callers_ai(callers, symbol_name),
+            OutputFormat::Default | OutputFormat::Json => self.format_cli_map_default(commands),
+            OutputFormat::Human => self.format_cli_map_human(commands),
+            OutputFormat::AI => self.format_cli_map_ai(commands),
+            OutputFormat::Compact => self.format_cli_map_compact(commands),
         }
     }
 
-    fn format_callers_default(&self, callers: &[CallInfo], symbol_name: &str) -> String {
+    fn format_cli_map_default(&self, commands: &[CliCommand]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("# Callers of `{}`\n\n", symbol_name));
-        output.push_str(&format!("Found {} call site(s)\n\n", callers.len()));
+        output.push_str("# CLI Commands\n\n");
+        output.push_str(&format!("Found {} command(s)\n\n", commands.len()));
 
-        for caller in callers {
+        for command in commands {
+            output.push_str(&format!("## `{}`\n", command.path));
+            if let Some(about) = &command.about {
+                output.push_str(&format!("{}\n\n", about));
+            }
             output.push_str(&format!(
-                "## {} ({})\n",
-                caller.caller_name,
-                caller.caller_type.as_str()
+                "- Handler: {}\n",
+                command.handler.as_deref().unwrap_or("-")
             ));
-            output.push_str(&format!("- File: {}:{}\n", caller.file_path, caller.line));
-            if !caller.context.is_empty() {
-                output.push_str(&format!("- Context: `{}`\n", caller.context));
+            output.push_str(&format!(
+                "- Defined: {}:{} ({})\n",
+                command.file_path.display_slash(),
+                command.line,
+                command.framework
+            ));
+            for flag in &command.flags {
+                output.push_str(&format!("  - `{}`", flag.names.join(", ")));
+                if let Some(help) = &flag.help {
+                    output.push_str(&format!(" - {}", help));
+                }
+                output.push('\n');
             }
             output.push('\n');
         }
@@ -1226,30 +6495,27 @@ impl OutputFormatter {
         output
     }
 
-    fn format_callers_human(&self, callers: &[CallInfo], symbol_name: &str) -> String {
+    fn format_cli_map_human(&self, commands: &[CliCommand]) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "{} {}\n\n",
-            "Callers of".green(),
-            symbol_name.bold()
-        ));
+        output.push_str(&format!("{}\n\n", "CLI Commands".green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Caller", "Type", "Location", "Context"]);
-
-        for caller in callers {
+            .set_header(vec!["Command", "Handler", "Flags", "Location"]);
+
+        for command in commands {
+            let flags: Vec<String> = command
+                .flags
+                .iter()
+                .filter_map(|f| f.names.first().cloned())
+                .collect();
             table.add_row(vec![
-                caller.caller_name.clone(),
-                caller.caller_type.as_str().to_string(),
-                format!("{}:{}", caller.file_path, caller.line),
-                if caller.context.len() > 60 {
-                    format!("{}...", &caller.context[..57])
-                } else {
-                    caller.context.clone()
-                },
+                command.path.clone(),
+                command.handler.clone().unwrap_or_else(|| "-".to_string()),
+                flags.join(" "),
+                format!("{}:{}", command.file_path.display_slash(), command.line),
             ]);
         }
 
@@ -1257,63 +6523,62 @@ impl OutputFormatter {
         output
     }
 
-    fn format_callers_ai(&self, callers: &[CallInfo], symbol_name: &str) -> String {
+    fn format_cli_map_ai(&self, commands: &[CliCommand]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("[CALLERS:{}|{}]\n", symbol_name, callers.len()));
+        output.push_str(&format!("[CLI:{}]\n", commands.len()));
 
-        for caller in callers {
+        for command in commands {
+            let flags: Vec<String> = command.flags.iter().map(|f| f.names.join("/")).collect();
             output.push_str(&format!(
-                "{}|{}|{}:{}",
-                caller.caller_name,
-                match caller.caller_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                caller.file_path,
-                caller.line
+                "{}|{}|{}:{}|{}\n",
+                command.path,
+                command.handler.as_deref().unwrap_or("-"),
+                command.file_path.display_slash(),
+                command.line,
+                flags.join(",")
             ));
-            output.push('\n');
         }
 
         output
     }
 
-    pub fn format_callees(&self, callees: &[CallInfo], symbol_name: &str) -> String {
+    fn format_cli_map_compact(&self, commands: &[CliCommand]) -> String {
+        commands
+            .iter()
+            .map(|command| {
+                compact_line(
+                    &command.file_path.display_slash().to_string(),
+                    command.line,
+                    "command",
+                    &command.path,
+                    command.handler.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_envvars(&self, vars: &[EnvVar]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_callees_default(callees, symbol_name),
-            OutputFormat::Human => self.format_callees_human(callees, symbol_name),
-            OutputFormat::AI => self.format_callees_ai(callees, symbol_name),
+            OutputFormat::Default | OutputFormat::Json => self.format_envvars_default(vars),
+            OutputFormat::Human => self.format_envvars_human(vars),
+            OutputFormat::AI => self.format_envvars_ai(vars),
+            OutputFormat::Compact => self.format_envvars_compact(vars),
         }
     }
 
-    fn format_callees_default(&self, callees: &[CallInfo], symbol_name: &str) -> String {
+    fn format_envvars_default(&self, vars: &[EnvVar]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("# Functions called by `{}`\n\n", symbol_name));
-        output.push_str(&format!("Found {} callee(s)\n\n", callees.len()));
-
-        for callee in callees {
-            output.push_str(&format!(
-                "## {} ({})\n",
-                callee.caller_name,
-                callee.caller_type.as_str()
-            ));
-            if callee.file_path != "<external>" {
-                output.push_str(&format!(
-                    "- Definition: {}:{}\n",
-                    callee.file_path, callee.line
-                ));
-            } else {
-                output.push_str("- External/built-in function\n");
-            }
-            if !callee.context.is_empty() {
-                output.push_str(&format!("- Signature: `{}`\n", callee.context));
+        output.push_str("# Environment Variables\n\n");
+        output.push_str(&format!("Found {} variable(s)\n\n", vars.len()));
+
+        for var in vars {
+            output.push_str(&format!("## `{}`\n", var.name));
+            for usage in &var.usages {
+                output.push_str(&format!("- {}:{}", usage.file_path.display_slash(), usage.line));
+                if let Some(enclosing) = &usage.enclosing {
+                    output.push_str(&format!(" in `{}`", enclosing));
+                }
+                output.push_str(&format!(" - `{}`\n", usage.context));
             }
             output.push('\n');
         }
@@ -1321,132 +6586,122 @@ impl OutputFormatter {
         output
     }
 
-    fn format_callees_human(&self, callees: &[CallInfo], symbol_name: &str) -> String {
+    fn format_envvars_human(&self, vars: &[EnvVar]) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "{} {}\n\n",
-            "Functions called by".green(),
-            symbol_name.bold()
-        ));
+        output.push_str(&format!("{}\n\n", "Environment Variables".green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Callee", "Type", "Definition", "Signature"]);
+            .set_header(vec!["Variable", "Used In", "Location"]);
 
-        for callee in callees {
-            let location = if callee.file_path != "<external>" {
-                format!("{}:{}", callee.file_path, callee.line)
-            } else {
-                "<external>".to_string()
-            };
-
-            table.add_row(vec![
-                callee.caller_name.clone(),
-                callee.caller_type.as_str().to_string(),
-                location,
-                if callee.context.len() > 40 {
-                    format!("{}...", &callee.context[..37])
+        for var in vars {
+            for (i, usage) in var.usages.iter().enumerate() {
+                let name = if i == 0 {
+                    var.name.clone()
                 } else {
-                    callee.context.clone()
-                },
-            ]);
+                    String::new()
+                };
+                table.add_row(vec![
+                    name,
+                    usage.enclosing.clone().unwrap_or_else(|| "-".to_string()),
+                    format!("{}:{}", usage.file_path.display_slash(), usage.line),
+                ]);
+            }
         }
 
         output.push_str(&format!("{}\n", table));
         output
     }
 
-    fn format_callees_ai(&self, callees: &[CallInfo], symbol_name: &str) -> String {
+    fn format_envvars_ai(&self, vars: &[EnvVar]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("[CALLEES:{}|{}]\n", symbol_name, callees.len()));
-
-        for callee in callees {
-            output.push_str(&format!(
-                "{}|{}|{}:{}",
-                callee.caller_name,
-                match callee.caller_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                callee.file_path,
-                callee.line
-            ));
-            if !callee.context.is_empty() {
-                output.push_str(&format!("|sig:{}", callee.context));
-            }
-            output.push('\n');
+        output.push_str(&format!("[ENV:{}]\n", vars.len()));
+
+        for var in vars {
+            let sites: Vec<String> = var
+                .usages
+                .iter()
+                .map(|u| match &u.enclosing {
+                    Some(enclosing) => {
+                        format!("{}:{}@{}", u.file_path.display_slash(), u.line, enclosing)
+                    }
+                    None => format!("{}:{}", u.file_path.display_slash(), u.line),
+                })
+                .collect();
+            output.push_str(&format!("{}|{}\n", var.name, sites.join(",")));
         }
 
         output
     }
 
-    pub fn format_tests(&self, tests: &[TestInfo], symbol_name: &str) -> String {
+    fn format_envvars_compact(&self, vars: &[EnvVar]) -> String {
+        vars.iter()
+            .flat_map(|var| {
+                var.usages.iter().map(move |usage| {
+                    compact_line(
+                        &usage.file_path.display_slash().to_string(),
+                        usage.line,
+                        "env",
+                        &var.name,
+                        usage.enclosing.as_deref(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn format_sql(&self, queries: &[SqlQuery]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_tests_default(tests, symbol_name),
-            OutputFormat::Human => self.format_tests_human(tests, symbol_name),
-            OutputFormat::AI => self.format_tests_ai(tests, symbol_name),
+            OutputFormat::Default | OutputFormat::Json => self.format_sql_default(queries),
+            OutputFormat::Human => self.format_sql_human(queries),
+            OutputFormat::AI => self.format_sql_ai(queries),
+            OutputFormat::Compact => self.format_sql_compact(queries),
         }
     }
 
-    fn format_tests_default(&self, tests: &[TestInfo], symbol_name: &str) -> String {
+    fn format_sql_default(&self, queries: &[SqlQuery]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("# Tests covering `{}`\n\n", symbol_name));
-        output.push_str(&format!("Found {} test(s)\n\n", tests.len()));
+        output.push_str("# SQL Statements\n\n");
+        output.push_str(&format!("Found {} statement(s)\n\n", queries.len()));
 
-        for test in tests {
+        for query in queries {
             output.push_str(&format!(
-                "## {} ({})\n",
-                test.test_name,
-                test.test_type.as_str()
+                "## {} `{}`\n",
+                query.kind,
+                query.tables.join(", ")
             ));
             output.push_str(&format!(
-                "- Test definition: {}:{}\n",
-                test.file_path, test.line
+                "- Location: {}:{}\n",
+                query.file_path.display_slash(),
+                query.line
             ));
-            output.push_str(&format!("- Calls symbol at: line {}\n", test.call_line));
-            if !test.context.is_empty() {
-                output.push_str(&format!("- Context: `{}`\n", test.context));
+            if let Some(enclosing) = &query.enclosing {
+                output.push_str(&format!("- In: `{}`\n", enclosing));
             }
-            output.push('\n');
+            output.push_str(&format!("\n```sql\n{}\n```\n\n", query.sql));
         }
 
         output
     }
 
-    fn format_tests_human(&self, tests: &[TestInfo], symbol_name: &str) -> String {
+    fn format_sql_human(&self, queries: &[SqlQuery]) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "{} {}\n\n",
-            "Tests covering".green(),
-            symbol_name.bold()
-        ));
+        output.push_str(&format!("{}\n\n", "SQL Statements".green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Test", "Type", "Location", "Call Line", "Context"]);
+            .set_header(vec!["Type", "Tables", "In", "Location"]);
 
-        for test in tests {
+        for query in queries {
             table.add_row(vec![
-                test.test_name.clone(),
-                test.test_type.as_str().to_string(),
-                format!("{}:{}", test.file_path, test.line),
-                test.call_line.to_string(),
-                if test.context.len() > 50 {
-                    format!("{}...", &test.context[..47])
-                } else {
-                    test.context.clone()
-                },
+                query.kind.clone(),
+                query.tables.join(", "),
+                query.enclosing.clone().unwrap_or_else(|| "-".to_string()),
+                format!("{}:{}", query.file_path.display_slash(), query.line),
             ]);
         }
 
@@ -1454,94 +6709,96 @@ impl OutputFormatter {
         output
     }
 
-    fn format_tests_ai(&self, tests: &[TestInfo], symbol_name: &str) -> String {
+    fn format_sql_ai(&self, queries: &[SqlQuery]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("[TESTS:{}|{}]\n", symbol_name, tests.len()));
+        output.push_str(&format!("[SQL:{}]\n", queries.len()));
 
-        for test in tests {
+        for query in queries {
             output.push_str(&format!(
-                "{}|{}|{}:{}|call:{}",
-                test.test_name,
-                match test.test_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                test.file_path,
-                test.line,
-                test.call_line
+                "{}|{}|{}|{}:{}|{}\n",
+                query.kind,
+                query.tables.join(","),
+                query.enclosing.as_deref().unwrap_or("-"),
+                query.file_path.display_slash(),
+                query.line,
+                query.sql
             ));
-            output.push('\n');
         }
 
         output
     }
 
-    pub fn format_test_deps(&self, deps: &[TestDep], test_file: &str) -> String {
+    fn format_sql_compact(&self, queries: &[SqlQuery]) -> String {
+        queries
+            .iter()
+            .map(|query| {
+                compact_line(
+                    &query.file_path.display_slash().to_string(),
+                    query.line,
+                    &query.kind.to_lowercase(),
+                    &query.tables.join(","),
+                    query.enclosing.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_strings(&self, matches: &[StringMatch], pattern: &str) -> String {
         match self.format {
-            OutputFormat::Default => self.format_test_deps_default(deps, test_file),
-            OutputFormat::Human => self.format_test_deps_human(deps, test_file),
-            OutputFormat::AI => self.format_test_deps_ai(deps, test_file),
+            OutputFormat::Default | OutputFormat::Json => {
+                self.format_strings_default(matches, pattern)
+            }
+            OutputFormat::Human => self.format_strings_human(matches, pattern),
+            OutputFormat::AI => self.format_strings_ai(matches, pattern),
+            OutputFormat::Compact => self.format_strings_compact(matches),
         }
     }
 
-    fn format_test_deps_default(&self, deps: &[TestDep], test_file: &str) -> String {
+    fn format_strings_default(&self, matches: &[StringMatch], pattern: &str) -> String {
         let mut output = String::new();
-        output.push_str(&format!("# Production dependencies of `{}`\n\n", test_file));
-        output.push_str(&format!(
-            "Found {} production symbol(s) called\n\n",
-            deps.len()
-        ));
-
-        let mut current_file = String::new();
-        for dep in deps {
-            if dep.file_path != current_file {
-                current_file = dep.file_path.clone();
-                output.push_str(&format!("\n## {}\n\n", current_file));
-            }
+        output.push_str(&format!("# String Literals: `{}`\n\n", pattern));
+        output.push_str(&format!("Found {} literal(s)\n\n", matches.len()));
 
+        for found in matches {
+            output.push_str(&format!("- \"{}\"\n", found.text));
             output.push_str(&format!(
-                "- **{}** ({}) @ line {} (called from test line {})\n",
-                dep.name,
-                dep.symbol_type.as_str(),
-                dep.line,
-                dep.called_from_line
+                "  - Location: {}:{}\n",
+                found.file_path.display_slash(),
+                found.line
             ));
+            if let Some(enclosing) = &found.enclosing {
+                output.push_str(&format!("  - In: `{}`\n", enclosing));
+            }
+            output.push_str(&format!("  - Match: {}\n", found.kind.as_str()));
         }
 
         output
     }
 
-    fn format_test_deps_human(&self, deps: &[TestDep], test_file: &str) -> String {
+    fn format_strings_human(&self, matches: &[StringMatch], pattern: &str) -> String {
         let mut output = String::new();
         output.push_str(&format!(
             "{} {}\n\n",
-            "Production dependencies of".green(),
-            test_file.bold()
-        ));
-        output.push_str(&format!(
-            "Found {} production symbol(s)\n\n",
-            deps.len().to_string().bold()
+            "String literals matching".green(),
+            pattern.bold()
         ));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Symbol", "Type", "Location", "Called From"]);
+            .set_header(vec!["Literal", "Match", "In", "Location"]);
 
-        for dep in deps {
+        for found in matches {
             table.add_row(vec![
-                dep.name.clone(),
-                dep.symbol_type.as_str().to_string(),
-                format!("{}:{}", dep.file_path, dep.line),
-                format!("line {}", dep.called_from_line),
+                if found.text.chars().count() > 60 {
+                    format!("{}...", found.text.chars().take(57).collect::<String>())
+                } else {
+                    found.text.clone()
+                },
+                found.kind.as_str().to_string(),
+                found.enclosing.clone().unwrap_or_else(|| "-".to_string()),
+                format!("{}:{}", found.file_path.display_slash(), found.line),
             ]);
         }
 
@@ -1549,75 +6806,65 @@ impl OutputFormatter {
         output
     }
 
-    fn format_test_deps_ai(&self, deps: &[TestDep], test_file: &str) -> String {
+    fn format_strings_ai(&self, matches: &[StringMatch], pattern: &str) -> String {
         let mut output = String::new();
-        output.push_str(&format!("[TEST_DEPS:{}|{}]\n", test_file, deps.len()));
+        output.push_str(&format!("[STRINGS:{}|{}]\n", pattern, matches.len()));
 
-        for dep in deps {
+        for found in matches {
             output.push_str(&format!(
-                "{}|{}|{}:{}|from:{}",
-                dep.name,
-                match dep.symbol_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                dep.file_path,
-                dep.line,
-                dep.called_from_line
+                "{}:{}|{}|{}|{}\n",
+                found.file_path.display_slash(),
+                found.line,
+                found.enclosing.as_deref().unwrap_or("-"),
+                found.kind.as_str(),
+                found.text
             ));
-            output.push('\n');
         }
 
         output
     }
 
-    pub fn format_untested(&self, untested: &[UntestedInfo], total_symbols: usize) -> String {
+    fn format_strings_compact(&self, matches: &[StringMatch]) -> String {
+        matches
+            .iter()
+            .map(|found| {
+                compact_line(
+                    &found.file_path.display_slash().to_string(),
+                    found.line,
+                    "string",
+                    found.enclosing.as_deref().unwrap_or("<top-level>"),
+                    Some(&format!("\"{}\"", found.text)),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_flags(&self, flags: &[Flag]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_untested_default(untested, total_symbols),
-            OutputFormat::Human => self.format_untested_human(untested, total_symbols),
-            OutputFormat::AI => self.format_untested_ai(untested, total_symbols),
+            OutputFormat::Default | OutputFormat::Json => self.format_flags_default(flags),
+            OutputFormat::Human => self.format_flags_human(flags),
+            OutputFormat::AI => self.format_flags_ai(flags),
+            OutputFormat::Compact => self.format_flags_compact(flags),
         }
     }
 
-    fn format_untested_default(&self, untested: &[UntestedInfo], total_symbols: usize) -> String {
+    fn format_flags_default(&self, flags: &[Flag]) -> String {
         let mut output = String::new();
-        output.push_str("# Untested Symbols\n\n");
-
-        let tested_count = total_symbols.saturating_sub(untested.len());
-        let coverage_pct = if total_symbols > 0 {
-            (tested_count as f64 / total_symbols as f64) * 100.0
-        } else {
-            100.0
-        };
-
-        output.push_str(&format!(
-            "**Coverage**: {:.1}% ({} of {} symbols tested)\n",
-            coverage_pct, tested_count, total_symbols
-        ));
-        output.push_str(&format!("**Untested**: {} symbols\n\n", untested.len()));
-
-        let mut current_file = String::new();
-        for info in untested {
-            if info.file_path != current_file {
-                current_file = info.file_path.clone();
-                output.push_str(&format!("\n## {}\n\n", current_file));
-            }
+        output.push_str("# Feature Flags\n\n");
+        output.push_str(&format!("Found {} flag(s)\n\n", flags.len()));
 
+        for flag in flags {
             output.push_str(&format!(
-                "- **{}** ({}) @ line {}",
-                info.name,
-                info.symbol_type.as_str(),
-                info.line
+                "## `{}` ({} check(s))\n",
+                flag.key,
+                flag.checks.len()
             ));
-            if let Some(ref sig) = info.signature {
-                output.push_str(&format!(": `{}`", sig));
+            for check in &flag.checks {
+                output.push_str(&format!("- {}:{}", check.file_path.display_slash(), check.line));
+                if let Some(enclosing) = &check.enclosing {
+                    output.push_str(&format!(" in `{}`", enclosing));
+                }
+                output.push_str(&format!(" - `{}`\n", check.context));
             }
             output.push('\n');
         }
@@ -1625,490 +6872,545 @@ impl OutputFormatter {
         output
     }
 
-    fn format_untested_human(&self, untested: &[UntestedInfo], total_symbols: usize) -> String {
+    fn format_flags_human(&self, flags: &[Flag]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("{}\n\n", "Untested Symbols".bold().red()));
-
-        let tested_count = total_symbols.saturating_sub(untested.len());
-        let coverage_pct = if total_symbols > 0 {
-            (tested_count as f64 / total_symbols as f64) * 100.0
-        } else {
-            100.0
-        };
-
-        let coverage_color = if coverage_pct >= 80.0 {
-            format!("{:.1}%", coverage_pct).green()
-        } else if coverage_pct >= 50.0 {
-            format!("{:.1}%", coverage_pct).yellow()
-        } else {
-            format!("{:.1}%", coverage_pct).red()
-        };
-
-        output.push_str(&format!(
-            "{}: {} ({} of {} symbols tested)\n",
-            "Coverage".cyan(),
-            coverage_color,
-            tested_count,
-            total_symbols
-        ));
-        output.push_str(&format!(
-            "{}: {}\n\n",
-            "Untested".cyan(),
-            untested.len().to_string().bold()
-        ));
+        output.push_str(&format!("{}\n\n", "Feature Flags".green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Symbol", "Type", "File", "Line", "Signature"]);
+            .set_header(vec!["Flag", "Checked In", "Location"]);
 
-        for info in untested {
-            table.add_row(vec![
-                info.name.clone(),
-                info.symbol_type.as_str().to_string(),
-                info.file_path.clone(),
-                info.line.to_string(),
-                info.signature.as_deref().unwrap_or("-").to_string(),
-            ]);
+        for flag in flags {
+            for (i, check) in flag.checks.iter().enumerate() {
+                let key = if i == 0 {
+                    flag.key.clone()
+                } else {
+                    String::new()
+                };
+                table.add_row(vec![
+                    key,
+                    check.enclosing.clone().unwrap_or_else(|| "-".to_string()),
+                    format!("{}:{}", check.file_path.display_slash(), check.line),
+                ]);
+            }
         }
 
         output.push_str(&format!("{}\n", table));
         output
     }
 
-    fn format_untested_ai(&self, untested: &[UntestedInfo], total_symbols: usize) -> String {
+    fn format_flags_ai(&self, flags: &[Flag]) -> String {
         let mut output = String::new();
-
-        let tested_count = total_symbols.saturating_sub(untested.len());
-        let coverage_pct = if total_symbols > 0 {
-            (tested_count as f64 / total_symbols as f64) * 100.0
-        } else {
-            100.0
-        };
-
-        output.push_str(&format!(
-            "[UNTESTED:{}|{:.1}%]\n",
-            untested.len(),
-            coverage_pct
-        ));
-        output.push_str(&format!(
-            "TESTED:{} TOTAL:{}\n",
-            tested_count, total_symbols
-        ));
-
-        for info in untested {
+        output.push_str(&format!("[FLAGS:{}]\n", flags.len()));
+
+        for flag in flags {
+            let sites: Vec<String> = flag
+                .checks
+                .iter()
+                .map(|c| match &c.enclosing {
+                    Some(enclosing) => {
+                        format!("{}:{}@{}", c.file_path.display_slash(), c.line, enclosing)
+                    }
+                    None => format!("{}:{}", c.file_path.display_slash(), c.line),
+                })
+                .collect();
             output.push_str(&format!(
-                "{}|{}|{}:{}",
-                info.name,
-                match info.symbol_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                info.file_path,
-                info.line
+                "{}|{}|{}\n",
+                flag.key,
+                flag.checks.len(),
+                sites.join(",")
             ));
-            if let Some(ref sig) = info.signature {
-                output.push_str(&format!("|sig:{}", sig));
-            }
-            output.push('\n');
         }
 
         output
     }
 
-    pub fn format_entrypoints(&self, entrypoints: &[EntrypointInfo]) -> String {
+    fn format_flags_compact(&self, flags: &[Flag]) -> String {
+        flags
+            .iter()
+            .flat_map(|flag| {
+                flag.checks.iter().map(move |check| {
+                    compact_line(
+                        &check.file_path.display_slash().to_string(),
+                        check.line,
+                        "flag",
+                        &flag.key,
+                        check.enclosing.as_deref(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn format_errors(&self, reports: &[ErrorReport]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_entrypoints_default(entrypoints),
-            OutputFormat::Human => self.format_entrypoints_human(entrypoints),
-            OutputFormat::AI => self.format_entrypoints_ai(entrypoints),
+            OutputFormat::Default | OutputFormat::Json => self.format_errors_default(reports),
+            OutputFormat::Human => self.format_errors_human(reports),
+            OutputFormat::AI => self.format_errors_ai(reports),
+            OutputFormat::Compact => self.format_errors_compact(reports),
         }
     }
 
-    fn format_entrypoints_default(&self, entrypoints: &[EntrypointInfo]) -> String {
+    fn format_errors_default(&self, reports: &[ErrorReport]) -> String {
         let mut output = String::new();
-        output.push_str("# Entrypoints (Uncalled Exported Symbols)\n\n");
-        output.push_str(&format!("Found {} entrypoint(s)\n\n", entrypoints.len()));
 
-        let main_entries: Vec<_> = entrypoints
-            .iter()
-            .filter(|e| e.category == EntrypointCategory::MainEntry)
-            .collect();
-        let api_funcs: Vec<_> = entrypoints
-            .iter()
-            .filter(|e| e.category == EntrypointCategory::ApiFunction)
-            .collect();
-        let unused: Vec<_> = entrypoints
-            .iter()
-            .filter(|e| e.category == EntrypointCategory::PossiblyUnused)
-            .collect();
+        for report in reports {
+            output.push_str(&format!("# Errors: {}\n\n", report.name));
+            output.push_str(&format!(
+                "- Defined: {}:{}\n\n",
+                report.file_path.display_slash(),
+                report.line
+            ));
+            if report.sites.is_empty() {
+                output.push_str("No error sources found\n\n");
+                continue;
+            }
 
-        if !main_entries.is_empty() {
-            output.push_str("## Main Entrypoints\n\n");
-            for entry in &main_entries {
-                output.push_str(&format!(
-                    "- **{}** ({}) @ `{}:{}`",
-                    entry.name,
-                    entry.symbol_type.as_str(),
-                    entry.file_path,
-                    entry.line
-                ));
-                if let Some(ref sig) = entry.signature {
-                    output.push_str(&format!("\n  `{}`", sig));
+            let (own, via): (Vec<_>, Vec<_>) = report.sites.iter().partition(|s| s.via.is_none());
+            if !own.is_empty() {
+                output.push_str("## Own\n");
+                for site in own {
+                    output.push_str(&format!(
+                        "- `{}` ({}) - {}:{}\n",
+                        site.error,
+                        site.kind.as_str(),
+                        site.file_path.display_slash(),
+                        site.line
+                    ));
+                }
+                output.push('\n');
+            }
+            if !via.is_empty() {
+                output.push_str("## From Direct Callees\n");
+                for site in via {
+                    output.push_str(&format!(
+                        "- `{}` ({}) via `{}` - {}:{}\n",
+                        site.error,
+                        site.kind.as_str(),
+                        site.via.as_deref().unwrap_or("-"),
+                        site.file_path.display_slash(),
+                        site.line
+                    ));
                 }
                 output.push('\n');
             }
-            output.push('\n');
         }
 
-        if !api_funcs.is_empty() {
-            output.push_str("## API Functions\n\n");
-            for entry in &api_funcs {
+        output
+    }
+
+    fn format_errors_human(&self, reports: &[ErrorReport]) -> String {
+        let mut output = String::new();
+
+        for report in reports {
+            output.push_str(&format!(
+                "{} {} ({}:{})\n\n",
+                "Errors:".green(),
+                report.name.bold(),
+                report.file_path.display_slash(),
+                report.line
+            ));
+
+            let mut table = Table::new();
+            table
+                .load_preset(UTF8_FULL)
+                .apply_modifier(UTF8_ROUND_CORNERS)
+                .set_header(vec!["Error", "Kind", "Via", "Location"]);
+
+            for site in &report.sites {
+                table.add_row(vec![
+                    site.error.clone(),
+                    site.kind.as_str().to_string(),
+                    site.via.clone().unwrap_or_else(|| "-".to_string()),
+                    format!("{}:{}", site.file_path.display_slash(), site.line),
+                ]);
+            }
+
+            output.push_str(&format!("{}\n\n", table));
+        }
+
+        output
+    }
+
+    fn format_errors_ai(&self, reports: &[ErrorReport]) -> String {
+        let mut output = String::new();
+
+        for report in reports {
+            output.push_str(&format!(
+                "[ERRORS:{}|{}:{}|{}]\n",
+                report.name,
+                report.file_path.display_slash(),
+                report.line,
+                report.sites.len()
+            ));
+            for site in &report.sites {
                 output.push_str(&format!(
-                    "- **{}** ({}) @ `{}:{}`",
-                    entry.name,
-                    entry.symbol_type.as_str(),
-                    entry.file_path,
-                    entry.line
+                    "{}|{}|{}|{}:{}\n",
+                    site.error,
+                    site.kind.as_str(),
+                    site.via.as_deref().unwrap_or("-"),
+                    site.file_path.display_slash(),
+                    site.line
                 ));
-                if let Some(ref sig) = entry.signature {
-                    output.push_str(&format!("\n  `{}`", sig));
-                }
-                output.push('\n');
             }
-            output.push('\n');
         }
 
-        if !unused.is_empty() {
-            output.push_str("## Possibly Unused\n\n");
-            for entry in &unused {
-                output.push_str(&format!(
-                    "- **{}** ({}) @ `{}:{}`",
-                    entry.name,
-                    entry.symbol_type.as_str(),
-                    entry.file_path,
-                    entry.line
-                ));
-                if let Some(ref sig) = entry.signature {
-                    output.push_str(&format!("\n  `{}`", sig));
+        output
+    }
+
+    fn format_errors_compact(&self, reports: &[ErrorReport]) -> String {
+        reports
+            .iter()
+            .flat_map(|report| &report.sites)
+            .map(|site| {
+                let detail = site.via.as_ref().map(|via| format!("via {}", via));
+                compact_line(
+                    &site.file_path.display_slash().to_string(),
+                    site.line,
+                    site.kind.as_str(),
+                    &site.error,
+                    detail.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_logs(&self, calls: &[LogCall]) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_logs_default(calls),
+            OutputFormat::Human => self.format_logs_human(calls),
+            OutputFormat::AI => self.format_logs_ai(calls),
+            OutputFormat::Compact => self.format_logs_compact(calls),
+        }
+    }
+
+    /// Most severe level first; within a level, grouped by enclosing symbol
+    fn group_logs(calls: &[LogCall]) -> LogGroups<'_> {
+        let mut levels: LogGroups = Vec::new();
+        let mut sorted: Vec<&LogCall> = calls.iter().collect();
+        sorted.sort_by_key(|call| std::cmp::Reverse(call.level));
+        for call in sorted {
+            if levels.last().is_none_or(|(level, _)| *level != call.level) {
+                levels.push((call.level, Vec::new()));
+            }
+            let Some((_, symbols)) = levels.last_mut() else {
+                continue;
+            };
+            let enclosing = call.enclosing.as_deref().unwrap_or("-");
+            match symbols.iter_mut().find(|(name, _)| *name == enclosing) {
+                Some((_, group)) => group.push(call),
+                None => symbols.push((enclosing, vec![call])),
+            }
+        }
+        levels
+    }
+
+    fn format_logs_default(&self, calls: &[LogCall]) -> String {
+        let mut output = String::new();
+        output.push_str("# Logging Calls\n\n");
+        output.push_str(&format!("Found {} call(s)\n\n", calls.len()));
+
+        for (level, symbols) in Self::group_logs(calls) {
+            let count: usize = symbols.iter().map(|(_, group)| group.len()).sum();
+            output.push_str(&format!("## {} ({})\n\n", level.as_str(), count));
+            for (enclosing, group) in symbols {
+                output.push_str(&format!("### `{}`\n", enclosing));
+                for call in group {
+                    output.push_str(&format!(
+                        "- {}:{} `{}`",
+                        call.file_path.display_slash(),
+                        call.line,
+                        call.logger
+                    ));
+                    if let Some(message) = &call.message {
+                        output.push_str(&format!(" \"{}\"", message));
+                    }
+                    output.push('\n');
                 }
                 output.push('\n');
             }
-            output.push('\n');
         }
 
         output
     }
 
-    fn format_entrypoints_human(&self, entrypoints: &[EntrypointInfo]) -> String {
+    fn format_logs_human(&self, calls: &[LogCall]) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "{}\n\n",
-            "Entrypoints (Uncalled Exported Symbols)".bold().green()
-        ));
-
-        let main_count = entrypoints
-            .iter()
-            .filter(|e| e.category == EntrypointCategory::MainEntry)
-            .count();
-        let api_count = entrypoints
-            .iter()
-            .filter(|e| e.category == EntrypointCategory::ApiFunction)
-            .count();
-        let unused_count = entrypoints
-            .iter()
-            .filter(|e| e.category == EntrypointCategory::PossiblyUnused)
-            .count();
-
-        output.push_str(&format!(
-            "{}: {} total ({} main, {} API, {} possibly unused)\n\n",
-            "Found".cyan(),
-            entrypoints.len().to_string().bold(),
-            main_count.to_string().green(),
-            api_count.to_string().cyan(),
-            unused_count.to_string().yellow()
-        ));
+        output.push_str(&format!("{}\n\n", "Logging Calls".green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec![
-                "Category",
-                "Symbol",
-                "Type",
-                "File",
-                "Line",
-                "Signature",
-            ]);
+            .set_header(vec!["Level", "In", "Message", "Location"]);
 
-        for entry in entrypoints {
-            let category_str = match entry.category {
-                EntrypointCategory::MainEntry => "Main".green().to_string(),
-                EntrypointCategory::ApiFunction => "API".cyan().to_string(),
-                EntrypointCategory::PossiblyUnused => "Unused?".yellow().to_string(),
-            };
-            table.add_row(vec![
-                category_str,
-                entry.name.clone(),
-                entry.symbol_type.as_str().to_string(),
-                entry.file_path.clone(),
-                entry.line.to_string(),
-                entry.signature.as_deref().unwrap_or("-").to_string(),
-            ]);
+        for (level, symbols) in Self::group_logs(calls) {
+            for (enclosing, group) in symbols {
+                for call in group {
+                    table.add_row(vec![
+                        level.as_str().to_string(),
+                        enclosing.to_string(),
+                        call.message.clone().unwrap_or_default(),
+                        format!("{}:{}", call.file_path.display_slash(), call.line),
+                    ]);
+                }
+            }
         }
 
         output.push_str(&format!("{}\n", table));
         output
     }
 
-    fn format_entrypoints_ai(&self, entrypoints: &[EntrypointInfo]) -> String {
+    fn format_logs_ai(&self, calls: &[LogCall]) -> String {
         let mut output = String::new();
-
-        let main_count = entrypoints
-            .iter()
-            .filter(|e| e.category == EntrypointCategory::MainEntry)
-            .count();
-        let api_count = entrypoints
-            .iter()
-            .filter(|e| e.category == EntrypointCategory::ApiFunction)
-            .count();
-        let unused_count = entrypoints
+        let groups = Self::group_logs(calls);
+        let counts: Vec<String> = groups
             .iter()
-            .filter(|e| e.category == EntrypointCategory::PossiblyUnused)
-            .count();
-
-        output.push_str(&format!("[ENTRYPOINTS:{}]\n", entrypoints.len()));
-        output.push_str(&format!(
-            "MAIN:{} API:{} UNUSED:{}\n",
-            main_count, api_count, unused_count
-        ));
+            .map(|(level, symbols)| {
+                let count: usize = symbols.iter().map(|(_, group)| group.len()).sum();
+                format!("{}:{}", level.as_str(), count)
+            })
+            .collect();
+        output.push_str(&format!("[LOGS:{}|{}]\n", calls.len(), counts.join(",")));
 
-        for entry in entrypoints {
-            let cat_short = match entry.category {
-                EntrypointCategory::MainEntry => "main",
-                EntrypointCategory::ApiFunction => "api",
-                EntrypointCategory::PossiblyUnused => "unused",
-            };
-            output.push_str(&format!(
-                "{}|{}|{}|{}:{}",
-                cat_short,
-                entry.name,
-                match entry.symbol_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                entry.file_path,
-                entry.line
-            ));
-            if let Some(ref sig) = entry.signature {
-                output.push_str(&format!("|sig:{}", sig));
+        for (level, symbols) in groups {
+            for (enclosing, group) in symbols {
+                for call in group {
+                    output.push_str(&format!(
+                        "{}|{}|{}:{}|{}\n",
+                        level.as_str(),
+                        enclosing,
+                        call.file_path.display_slash(),
+                        call.line,
+                        call.message.as_deref().unwrap_or("-")
+                    ));
+                }
             }
-            output.push('\n');
         }
 
         output
     }
 
-    pub fn format_blame(&self, result: &BlameResult) -> String {
+    fn format_logs_compact(&self, calls: &[LogCall]) -> String {
+        calls
+            .iter()
+            .map(|call| {
+                compact_line(
+                    &call.file_path.display_slash().to_string(),
+                    call.line,
+                    call.level.as_str(),
+                    &call.logger,
+                    call.message.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_audit(&self, findings: &[Finding]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_blame_default(result),
-            OutputFormat::Human => self.format_blame_human(result),
-            OutputFormat::AI => self.format_blame_ai(result),
+            OutputFormat::Default | OutputFormat::Json => self.format_audit_default(findings),
+            OutputFormat::Human => self.format_audit_human(findings),
+            OutputFormat::AI => self.format_audit_ai(findings),
+            OutputFormat::Compact => self.format_audit_compact(findings),
         }
     }
 
-    fn format_blame_default(&self, result: &BlameResult) -> String {
-        let mut output = String::new();
-        output.push_str(&format!("# Blame for `{}`\n\n", result.symbol_name));
-        output.push_str(&format!("Type: {}\n", result.symbol_type.as_str()));
-        output.push_str(&format!(
-            "Lines: {}-{}\n\n",
-            result.current_lines.0, result.current_lines.1
-        ));
+    /// Findings per rule, in rule order, each group in file and line order
+    fn group_findings(findings: &[Finding]) -> Vec<(Rule, Vec<&Finding>)> {
+        let mut groups: Vec<(Rule, Vec<&Finding>)> = Vec::new();
+        let mut sorted: Vec<&Finding> = findings.iter().collect();
+        sorted.sort_by_key(|finding| finding.rule);
+        for finding in sorted {
+            match groups.last_mut() {
+                Some((rule, group)) if *rule == finding.rule => group.push(finding),
+                _ => groups.push((finding.rule, vec![finding])),
+            }
+        }
+        groups
+    }
 
-        output.push_str("## Last Modification\n\n");
-        output.push_str(&format!("- Commit: `{}`\n", result.last_commit.short_hash));
-        output.push_str(&format!("- Author: {}\n", result.last_commit.author));
-        output.push_str(&format!("- Date: {}\n", result.last_commit.date));
-        output.push_str(&format!("- Message: {}\n", result.last_commit.message));
+    fn format_audit_default(&self, findings: &[Finding]) -> String {
+        let mut output = String::new();
+        output.push_str("# Audit\n\n");
+        output.push_str(&format!("Found {} finding(s)\n\n", findings.len()));
 
-        if result.old_signature.is_some() || result.new_signature.is_some() {
-            output.push_str("\n## Signature Change\n\n");
-            if let Some(ref old_sig) = result.old_signature {
-                output.push_str(&format!("- Old: `{}`\n", old_sig));
-            }
-            if let Some(ref new_sig) = result.new_signature {
-                output.push_str(&format!("- New: `{}`\n", new_sig));
+        for (rule, group) in Self::group_findings(findings) {
+            output.push_str(&format!(
+                "## {} — {} ({})\n\n",
+                rule.as_str(),
+                rule.description(),
+                group.len()
+            ));
+            for finding in group {
+                output.push_str(&format!(
+                    "- {}:{}",
+                    finding.file_path.display_slash(),
+                    finding.line
+                ));
+                if let Some(enclosing) = &finding.enclosing {
+                    output.push_str(&format!(" in `{}`", enclosing));
+                }
+                output.push_str(&format!("\n  `{}`\n", finding.context));
             }
+            output.push('\n');
         }
 
         output
     }
 
-    fn format_blame_human(&self, result: &BlameResult) -> String {
+    fn format_audit_human(&self, findings: &[Finding]) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "{} {}\n\n",
-            "Blame for".green(),
-            result.symbol_name.bold()
-        ));
-        output.push_str(&format!(
-            "Type: {} | Lines: {}-{}\n\n",
-            result.symbol_type.as_str().cyan(),
-            result.current_lines.0,
-            result.current_lines.1
-        ));
+        output.push_str(&format!("{}\n\n", "Audit".green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Commit", "Author", "Date", "Message"]);
-
-        table.add_row(vec![
-            result.last_commit.short_hash.clone(),
-            result.last_commit.author.clone(),
-            result.last_commit.date.clone(),
-            if result.last_commit.message.len() > 50 {
-                format!("{}...", &result.last_commit.message[..47])
-            } else {
-                result.last_commit.message.clone()
-            },
-        ]);
-
-        output.push_str(&format!("{}\n", table));
+            .set_header(vec!["Rule", "In", "Code", "Location"]);
 
-        if result.old_signature.is_some() || result.new_signature.is_some() {
-            output.push_str(&format!("\n{}\n", "Signature Change:".cyan()));
-            if let Some(ref old_sig) = result.old_signature {
-                output.push_str(&format!("  Old: {}\n", old_sig.red()));
-            }
-            if let Some(ref new_sig) = result.new_signature {
-                output.push_str(&format!("  New: {}\n", new_sig.green()));
+        for (rule, group) in Self::group_findings(findings) {
+            for finding in group {
+                table.add_row(vec![
+                    rule.as_str().to_string(),
+                    finding.enclosing.clone().unwrap_or_else(|| "-".to_string()),
+                    finding.context.clone(),
+                    format!("{}:{}", finding.file_path.display_slash(), finding.line),
+                ]);
             }
         }
 
+        output.push_str(&format!("{}\n", table));
         output
     }
 
-    fn format_blame_ai(&self, result: &BlameResult) -> String {
+    fn format_audit_ai(&self, findings: &[Finding]) -> String {
         let mut output = String::new();
+        let groups = Self::group_findings(findings);
+        let counts: Vec<String> = groups
+            .iter()
+            .map(|(rule, group)| format!("{}:{}", rule.as_str(), group.len()))
+            .collect();
         output.push_str(&format!(
-            "[BLAME:{}|{}]\n",
-            result.symbol_name,
-            result.symbol_type.as_str()
-        ));
-        output.push_str(&format!(
-            "LINES:{}-{}\n",
-            result.current_lines.0, result.current_lines.1
-        ));
-        output.push_str(&format!(
-            "COMMIT:{}|{}|{}|{}\n",
-            result.last_commit.short_hash,
-            result.last_commit.author,
-            result.last_commit.date,
-            result.last_commit.message
+            "[AUDIT:{}|{}]\n",
+            findings.len(),
+            counts.join(",")
         ));
 
-        if let Some(ref old_sig) = result.old_signature {
-            output.push_str(&format!("OLD_SIG:{}\n", old_sig));
-        }
-        if let Some(ref new_sig) = result.new_signature {
-            output.push_str(&format!("NEW_SIG:{}\n", new_sig));
+        for (rule, group) in groups {
+            for finding in group {
+                output.push_str(&format!(
+                    "{}|{}|{}:{}|{}\n",
+                    rule.as_str(),
+                    finding.enclosing.as_deref().unwrap_or("-"),
+                    finding.file_path.display_slash(),
+                    finding.line,
+                    finding.context
+                ));
+            }
         }
 
         output
     }
 
-    pub fn format_history(&self, history: &[HistoryEntry], symbol_name: &str) -> String {
+    fn format_audit_compact(&self, findings: &[Finding]) -> String {
+        findings
+            .iter()
+            .map(|finding| {
+                compact_line(
+                    &finding.file_path.display_slash().to_string(),
+                    finding.line,
+                    finding.rule.as_str(),
+                    finding.enclosing.as_deref().unwrap_or("-"),
+                    Some(&finding.context),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_shadows(&self, shadows: &[Shadow]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_history_default(history, symbol_name),
-            OutputFormat::Human => self.format_history_human(history, symbol_name),
-            OutputFormat::AI => self.format_history_ai(history, symbol_name),
+            OutputFormat::Default | OutputFormat::Json => self.format_shadows_default(shadows),
+            OutputFormat::Human => self.format_shadows_human(shadows),
+            OutputFormat::AI => self.format_shadows_ai(shadows),
+            OutputFormat::Compact => self.format_shadows_compact(shadows),
         }
     }
 
-    fn format_history_default(&self, history: &[HistoryEntry], symbol_name: &str) -> String {
-        let mut output = String::new();
-        output.push_str(&format!("# History for `{}`\n\n", symbol_name));
-        output.push_str(&format!("Found {} version(s)\n\n", history.len()));
-
-        for (i, entry) in history.iter().enumerate() {
-            let version_num = history.len() - i;
-            let status = if entry.existed { "exists" } else { "deleted" };
+    /// Shadows are sorted by similarity already; this splits them at each change
+    fn group_shadows(shadows: &[Shadow]) -> Vec<(Similarity, Vec<&Shadow>)> {
+        let mut groups: Vec<(Similarity, Vec<&Shadow>)> = Vec::new();
+        for shadow in shadows {
+            match groups.last_mut() {
+                Some((similarity, group)) if *similarity == shadow.similarity => group.push(shadow),
+                _ => groups.push((shadow.similarity, vec![shadow])),
+            }
+        }
+        groups
+    }
 
-            output.push_str(&format!("## Version {} ({})\n\n", version_num, status));
-            output.push_str(&format!("- Commit: `{}`\n", entry.commit.short_hash));
-            output.push_str(&format!("- Author: {}\n", entry.commit.author));
-            output.push_str(&format!("- Date: {}\n", entry.commit.date));
-            output.push_str(&format!("- Message: {}\n", entry.commit.message));
+    fn format_shadows_default(&self, shadows: &[Shadow]) -> String {
+        let mut output = String::new();
+        output.push_str("# Shadowed Names\n\n");
+        output.push_str(&format!(
+            "Found {} name(s) defined in more than one file\n\n",
+            shadows.len()
+        ));
 
-            if let Some((start, end)) = entry.lines {
-                output.push_str(&format!("- Lines: {}-{}\n", start, end));
-            }
-            if let Some(ref sig) = entry.signature {
-                output.push_str(&format!("- Signature: `{}`\n", sig));
+        for (similarity, group) in Self::group_shadows(shadows) {
+            output.push_str(&format!(
+                "## {} — {} ({})\n\n",
+                similarity.as_str(),
+                similarity.description(),
+                group.len()
+            ));
+            for shadow in group {
+                output.push_str(&format!(
+                    "### `{}` ({}, {} files)\n\n",
+                    shadow.name,
+                    shadow.kind.as_str(),
+                    shadow.file_count()
+                ));
+                for symbol in &shadow.definitions {
+                    output.push_str(&format!(
+                        "- {}:{}",
+                        symbol.file_path.display_slash(),
+                        symbol.line_start
+                    ));
+                    if let Some(signature) = &symbol.signature {
+                        output.push_str(&format!(" `{}`", signature));
+                    }
+                    output.push('\n');
+                }
+                output.push('\n');
             }
-            output.push('\n');
         }
 
         output
     }
 
-    fn format_history_human(&self, history: &[HistoryEntry], symbol_name: &str) -> String {
+    fn format_shadows_human(&self, shadows: &[Shadow]) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "{} {}\n\n",
-            "History for".green(),
-            symbol_name.bold()
-        ));
-        output.push_str(&format!(
-            "Found {} version(s)\n\n",
-            history.len().to_string().bold()
-        ));
+        output.push_str(&format!("{}\n\n", "Shadowed Names".green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["#", "Commit", "Date", "Status", "Signature"]);
-
-        for (i, entry) in history.iter().enumerate() {
-            let version_num = history.len() - i;
-            let status = if entry.existed {
-                "✓".green().to_string()
-            } else {
-                "✗".red().to_string()
-            };
-
-            let sig = entry.signature.as_deref().unwrap_or("-");
-            let sig_display = if sig.len() > 40 {
-                format!("{}...", &sig[..37])
-            } else {
-                sig.to_string()
-            };
-
+            .set_header(vec!["Name", "Kind", "Similarity", "Definitions"]);
+
+        for shadow in shadows {
+            let locations: Vec<String> = shadow
+                .definitions
+                .iter()
+                .map(|s| format!("{}:{}", s.file_path.display_slash(), s.line_start))
+                .collect();
             table.add_row(vec![
-                version_num.to_string(),
-                entry.commit.short_hash.clone(),
-                entry.commit.date.clone(),
-                status,
-                sig_display,
+                shadow.name.clone(),
+                shadow.kind.as_str().to_string(),
+                shadow.similarity.as_str().to_string(),
+                locations.join("\n"),
             ]);
         }
 
@@ -2116,168 +7418,155 @@ impl OutputFormatter {
         output
     }
 
-    fn format_history_ai(&self, history: &[HistoryEntry], symbol_name: &str) -> String {
+    fn format_shadows_ai(&self, shadows: &[Shadow]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("[HISTORY:{}|{}]\n", symbol_name, history.len()));
+        let counts: Vec<String> = Self::group_shadows(shadows)
+            .iter()
+            .map(|(similarity, group)| format!("{}:{}", similarity.as_str(), group.len()))
+            .collect();
+        output.push_str(&format!(
+            "[SHADOWS:{}|{}]\n",
+            shadows.len(),
+            counts.join(",")
+        ));
 
-        for entry in history {
-            let status = if entry.existed { "+" } else { "-" };
+        for shadow in shadows {
+            let locations: Vec<String> = shadow
+                .definitions
+                .iter()
+                .map(|s| format!("{}:{}", s.file_path.display_slash(), s.line_start))
+                .collect();
             output.push_str(&format!(
-                "{}|{}|{}|{}",
-                status, entry.commit.short_hash, entry.commit.date, entry.commit.message
+                "{}|{}|{}|{}\n",
+                shadow.name,
+                shadow.kind.as_str(),
+                shadow.similarity.as_str(),
+                locations.join(",")
             ));
+        }
 
-            if let Some((start, end)) = entry.lines {
-                output.push_str(&format!("|{}-{}", start, end));
-            }
-            if let Some(ref sig) = entry.signature {
-                output.push_str(&format!("|sig:{}", sig));
+        output
+    }
+
+    fn format_shadows_compact(&self, shadows: &[Shadow]) -> String {
+        let mut output = String::new();
+        for shadow in shadows {
+            let detail = format!(
+                "defined in {} files ({})",
+                shadow.file_count(),
+                shadow.similarity.as_str()
+            );
+            for symbol in &shadow.definitions {
+                output.push_str(&compact_line(
+                    &symbol.file_path.display_slash().to_string(),
+                    symbol.line_start,
+                    shadow.kind.as_str(),
+                    &shadow.name,
+                    Some(&detail),
+                ));
             }
-            output.push('\n');
         }
-
         output
     }
 
-    pub fn format_trace(&self, trace: &TracePath, from: &str, to: &str) -> String {
+    pub fn format_routes(&self, routes: &[Route]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_trace_default(trace, from, to),
-            OutputFormat::Human => self.format_trace_human(trace, from, to),
-            OutputFormat::AI => self.format_trace_ai(trace, from, to),
+            OutputFormat::Default | OutputFormat::Json => self.format_routes_default(routes),
+            OutputFormat::Human => self.format_routes_human(routes),
+            OutputFormat::AI => self.format_routes_ai(routes),
+            OutputFormat::Compact => self.format_routes_compact(routes),
         }
     }
 
-    fn format_trace_default(&self, trace: &TracePath, from: &str, to: &str) -> String {
+    fn format_routes_default(&self, routes: &[Route]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("# Call Path: {} → {}\n\n", from, to));
-
-        if !trace.found {
-            output.push_str("No call path found between these symbols.\n");
-            return output;
-        }
-
-        output.push_str(&format!(
-            "**Path length**: {} step(s)\n\n",
-            trace.steps.len()
-        ));
-        output.push_str("## Call Chain\n\n");
-        output.push_str("```\n");
+        output.push_str("# HTTP Routes\n\n");
+        output.push_str(&format!("Found {} route(s)\n\n", routes.len()));
+        output.push_str("| Method | Path | Handler | Framework | Location |\n");
+        output.push_str("|--------|------|---------|-----------|----------|\n");
 
-        for (i, step) in trace.steps.iter().enumerate() {
-            if i > 0 {
-                output.push_str("    ↓\n");
-            }
+        for route in routes {
             output.push_str(&format!(
-                "[{}] {} ({})\n",
-                i + 1,
-                step.symbol_name,
-                step.symbol_type.as_str()
+                "| {} | `{}` | {} | {} | {}:{} |\n",
+                route.method,
+                route.path,
+                route.handler.as_deref().unwrap_or("(inline)"),
+                route.framework,
+                route.file_path.display_slash(),
+                route.line
             ));
-            output.push_str(&format!("    {}:{}\n", step.file_path, step.line));
         }
 
-        output.push_str("```\n");
         output
     }
 
-    fn format_trace_human(&self, trace: &TracePath, from: &str, to: &str) -> String {
+    fn format_routes_human(&self, routes: &[Route]) -> String {
         let mut output = String::new();
-        output.push_str(&format!(
-            "{} {} {} {}\n\n",
-            "Call Path:".green(),
-            from.bold(),
-            "→".cyan(),
-            to.bold()
-        ));
-
-        if !trace.found {
-            output.push_str(&format!(
-                "{} No call path found between these symbols.\n",
-                "✗".yellow()
-            ));
-            return output;
-        }
-
-        output.push_str(&format!(
-            "{}: {} step(s)\n\n",
-            "Path length".cyan(),
-            trace.steps.len()
-        ));
+        output.push_str(&format!("{}\n\n", "HTTP Routes".green()));
 
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
-            .set_header(vec!["Step", "Symbol", "Type", "Location"]);
-
-        for (i, step) in trace.steps.iter().enumerate() {
-            let step_marker = if i == 0 {
-                "→".green().to_string()
-            } else if i == trace.steps.len() - 1 {
-                "◉".cyan().to_string()
-            } else {
-                "↓".white().to_string()
-            };
+            .set_header(vec!["Method", "Path", "Handler", "Framework", "Location"]);
 
+        for route in routes {
             table.add_row(vec![
-                step_marker,
-                step.symbol_name.clone(),
-                step.symbol_type.as_str().to_string(),
-                format!("{}:{}", step.file_path, step.line),
+                route.method.clone(),
+                route.path.clone(),
+                route
+                    .handler
+                    .clone()
+                    .unwrap_or_else(|| "(inline)".to_string()),
+                route.framework.to_string(),
+                format!("{}:{}", route.file_path.display_slash(), route.line),
             ]);
         }
 
         output.push_str(&format!("{}\n", table));
-
-        output.push_str(&format!("\n{}\n", "Call Chain:".cyan()));
-        let names: Vec<&str> = trace.steps.iter().map(|s| s.symbol_name.as_str()).collect();
-        output.push_str(&format!("  {}\n", names.join(" → ")));
-
         output
     }
 
-    fn format_trace_ai(&self, trace: &TracePath, from: &str, to: &str) -> String {
+    fn format_routes_ai(&self, routes: &[Route]) -> String {
         let mut output = String::new();
-        output.push_str(&format!("[TRACE:{}->{}]\n", from, to));
+        output.push_str(&format!("[ROUTES:{}]\n", routes.len()));
 
-        if !trace.found {
-            output.push_str("FOUND:false\n");
-            return output;
-        }
-
-        output.push_str(&format!("FOUND:true STEPS:{}\n", trace.steps.len()));
-
-        let names: Vec<&str> = trace.steps.iter().map(|s| s.symbol_name.as_str()).collect();
-        output.push_str(&format!("PATH:{}\n", names.join("|")));
-
-        for step in &trace.steps {
+        for route in routes {
             output.push_str(&format!(
-                "{}|{}|{}:{}\n",
-                step.symbol_name,
-                match step.symbol_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
-                step.file_path,
-                step.line
+                "{} {}|{}|{}:{}|{}\n",
+                route.method,
+                route.path,
+                route.handler.as_deref().unwrap_or("-"),
+                route.file_path.display_slash(),
+                route.line,
+                route.framework
             ));
         }
 
         output
     }
 
+    fn format_routes_compact(&self, routes: &[Route]) -> String {
+        routes
+            .iter()
+            .map(|route| {
+                compact_line(
+                    &route.file_path.display_slash().to_string(),
+                    route.line,
+                    "route",
+                    &format!("{} {}", route.method, route.path),
+                    route.handler.as_deref(),
+                )
+            })
+            .collect()
+    }
+
     pub fn format_implements(&self, implementations: &[Implementation], interface: &str) -> String {
         match self.format {
-            OutputFormat::Default => self.format_implements_default(implementations, interface),
+            OutputFormat::Default | OutputFormat::Json => self.format_implements_default(implementations, interface),
             OutputFormat::Human => self.format_implements_human(implementations, interface),
             OutputFormat::AI => self.format_implements_ai(implementations, interface),
+            OutputFormat::Compact => self.format_implements_compact(implementations),
         }
     }
 
@@ -2298,9 +7587,10 @@ impl OutputFormatter {
             output.push_str(&format!("- Interface: {}\n", imp.interface_name));
             output.push_str(&format!("- Kind: {}\n", imp.kind.as_str()));
             output.push_str(&format!("- Language: {}\n", imp.language.as_str()));
+            output.push_str(&format!("- Confidence: {}\n", imp.confidence.as_str()));
             output.push_str(&format!(
                 "- Location: `{}:{}`\n",
-                imp.file_path.display(),
+                imp.file_path.display_slash(),
                 imp.line
             ));
             output.push('\n');
@@ -2336,6 +7626,7 @@ impl OutputFormatter {
                 "Kind",
                 "Language",
                 "Location",
+                "Confidence",
             ]);
 
         for imp in implementations {
@@ -2344,7 +7635,8 @@ impl OutputFormatter {
                 imp.interface_name.clone(),
                 imp.kind.as_str().to_string(),
                 imp.language.as_str().to_string(),
-                format!("{}:{}", imp.file_path.display(), imp.line),
+                format!("{}:{}", imp.file_path.display_slash(), imp.line),
+                confidence_cell(imp.confidence),
             ]);
         }
 
@@ -2362,22 +7654,196 @@ impl OutputFormatter {
 
         for imp in implementations {
             output.push_str(&format!(
-                "{}|{}|{}|{}|{}:{}\n",
+                "{}|{}|{}|{}|{}:{}|{}\n",
                 imp.implementor_name,
                 imp.interface_name,
                 imp.kind.as_str(),
                 imp.language.as_str(),
-                imp.file_path.display(),
-                imp.line
+                imp.file_path.display_slash(),
+                imp.line,
+                imp.confidence.short()
             ));
         }
 
         output
     }
 
+    fn format_implements_compact(&self, implementations: &[Implementation]) -> String {
+        implementations
+            .iter()
+            .map(|i| {
+                compact_line(
+                    &i.file_path.display_slash().to_string(),
+                    i.line,
+                    i.kind.as_str(),
+                    &i.implementor_name,
+                    Some(&format!("[{}] {}", i.confidence.as_str(), i.interface_name)),
+                )
+            })
+            .collect()
+    }
+
+    pub fn format_hierarchy(&self, hierarchy: &Hierarchy) -> String {
+        match self.format {
+            OutputFormat::Default | OutputFormat::Json => self.format_hierarchy_default(hierarchy),
+            OutputFormat::Human => self.format_hierarchy_human(hierarchy),
+            OutputFormat::AI => self.format_hierarchy_ai(hierarchy),
+            OutputFormat::Compact => self.format_hierarchy_compact(hierarchy),
+        }
+    }
+
+    fn format_hierarchy_default(&self, hierarchy: &Hierarchy) -> String {
+        fn push_nodes(output: &mut String, nodes: &[HierarchyNode], depth: usize) {
+            for node in nodes {
+                output.push_str(&format!(
+                    "{}- **{}** ({}) {}{}\n",
+                    "  ".repeat(depth),
+                    node.name,
+                    node.kind.as_str(),
+                    hierarchy_location(node.location.as_ref(), "`"),
+                    if node.cycle { " (cycle)" } else { "" }
+                ));
+                push_nodes(output, &node.children, depth + 1);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str(&format!("# Type Hierarchy of `{}`\n\n", hierarchy.name));
+        output.push_str(&format!(
+            "Defined at: {}\n\n",
+            hierarchy_location(hierarchy.location.as_ref(), "`")
+        ));
+
+        output.push_str(&format!(
+            "## Ancestors ({})\n\n",
+            hierarchy.ancestor_count()
+        ));
+        if hierarchy.ancestors.is_empty() {
+            output.push_str("None found.\n");
+        }
+        push_nodes(&mut output, &hierarchy.ancestors, 0);
+
+        output.push_str(&format!(
+            "\n## Descendants ({})\n\n",
+            hierarchy.descendant_count()
+        ));
+        if hierarchy.descendants.is_empty() {
+            output.push_str("None found.\n");
+        }
+        push_nodes(&mut output, &hierarchy.descendants, 0);
+
+        output
+    }
+
+    fn format_hierarchy_human(&self, hierarchy: &Hierarchy) -> String {
+        fn push_nodes(output: &mut String, nodes: &[HierarchyNode], prefix: &str) {
+            for (i, node) in nodes.iter().enumerate() {
+                let last = i + 1 == nodes.len();
+                output.push_str(&format!(
+                    "{}{} {} {} {}{}\n",
+                    prefix,
+                    if last { "└──" } else { "├──" },
+                    node.name.bold(),
+                    format!("[{}]", node.kind.as_str()).dimmed(),
+                    hierarchy_location(node.location.as_ref(), "").cyan(),
+                    if node.cycle {
+                        " ↺ cycle".yellow().to_string()
+                    } else {
+                        String::new()
+                    }
+                ));
+                let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                push_nodes(output, &node.children, &child_prefix);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{} {} ({} ancestors)\n",
+            "▲ Ancestors of".green(),
+            hierarchy.name.bold(),
+            hierarchy.ancestor_count()
+        ));
+        push_nodes(&mut output, &hierarchy.ancestors, "");
+
+        output.push_str(&format!(
+            "\n{} {}\n\n",
+            hierarchy.name.bold().green(),
+            hierarchy_location(hierarchy.location.as_ref(), "").cyan()
+        ));
+
+        output.push_str(&format!(
+            "{} {} ({} descendants)\n",
+            "▼ Descendants of".green(),
+            hierarchy.name.bold(),
+            hierarchy.descendant_count()
+        ));
+        push_nodes(&mut output, &hierarchy.descendants, "");
+
+        output
+    }
+
+    fn format_hierarchy_ai(&self, hierarchy: &Hierarchy) -> String {
+        fn push_nodes(output: &mut String, direction: &str, nodes: &[HierarchyNode], depth: usize) {
+            for node in nodes {
+                output.push_str(&format!(
+                    "{}:{}|{}|{}|{}{}\n",
+                    direction,
+                    depth,
+                    node.name,
+                    node.kind.as_str(),
+                    hierarchy_location(node.location.as_ref(), ""),
+                    if node.cycle { "|cycle" } else { "" }
+                ));
+                push_nodes(output, direction, &node.children, depth + 1);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str(&format!(
+            "[HIERARCHY:{}|up:{}|down:{}]\n",
+            hierarchy.name,
+            hierarchy.ancestor_count(),
+            hierarchy.descendant_count()
+        ));
+        output.push_str(&format!(
+            "ROOT:{}|{}\n",
+            hierarchy.name,
+            hierarchy_location(hierarchy.location.as_ref(), "")
+        ));
+        push_nodes(&mut output, "UP", &hierarchy.ancestors, 1);
+        push_nodes(&mut output, "DOWN", &hierarchy.descendants, 1);
+
+        output
+    }
+
+    fn format_hierarchy_compact(&self, hierarchy: &Hierarchy) -> String {
+        fn push_nodes(output: &mut String, relation: &str, nodes: &[HierarchyNode]) {
+            for node in nodes {
+                if let Some((file, line)) = &node.location {
+                    output.push_str(&compact_line(
+                        &file.display_slash().to_string(),
+                        *line,
+                        node.kind.as_str(),
+                        &node.name,
+                        Some(relation),
+                    ));
+                }
+                push_nodes(output, relation, &node.children);
+            }
+        }
+
+        let mut output = String::new();
+        push_nodes(&mut output, "ancestor", &hierarchy.ancestors);
+        push_nodes(&mut output, "descendant", &hierarchy.descendants);
+        output
+    }
+
     pub fn format_types(&self, types_info: &[SymbolTypes]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_types_default(types_info),
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_types_default(types_info)
+            }
             OutputFormat::Human => self.format_types_human(types_info),
             OutputFormat::AI => self.format_types_ai(types_info),
         }
@@ -2496,17 +7962,7 @@ impl OutputFormatter {
             output.push_str(&format!(
                 "SYM:{}|{}|{}:{}\n",
                 symbol.symbol_name,
-                match symbol.symbol_type {
-                    SymbolType::Function => "f",
-                    SymbolType::Class => "c",
-                    SymbolType::Method => "m",
-                    SymbolType::Enum => "e",
-                    SymbolType::StaticField => "s",
-                    SymbolType::Heading => "h",
-                    SymbolType::CodeBlock => "cb",
-                    SymbolType::Interface => "if",
-                    SymbolType::TypeAlias => "ty",
-                },
+                symbol.symbol_type.code(),
                 symbol.file_path,
                 symbol.line
             ));
@@ -2534,7 +7990,9 @@ impl OutputFormatter {
 
     pub fn format_snapshot_saved(&self, snapshot: &Snapshot) -> String {
         match self.format {
-            OutputFormat::Default => self.format_snapshot_saved_default(snapshot),
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_snapshot_saved_default(snapshot)
+            }
             OutputFormat::Human => self.format_snapshot_saved_human(snapshot),
             OutputFormat::AI => self.format_snapshot_saved_ai(snapshot),
         }
@@ -2592,7 +8050,9 @@ impl OutputFormatter {
 
     pub fn format_snapshot_list(&self, snapshots: &[String]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_snapshot_list_default(snapshots),
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_snapshot_list_default(snapshots)
+            }
             OutputFormat::Human => self.format_snapshot_list_human(snapshots),
             OutputFormat::AI => self.format_snapshot_list_ai(snapshots),
         }
@@ -2645,7 +8105,9 @@ impl OutputFormatter {
 
     pub fn format_schema(&self, schemas: &[SchemaInfo]) -> String {
         match self.format {
-            OutputFormat::Default => self.format_schema_default(schemas),
+            OutputFormat::Default | OutputFormat::Compact | OutputFormat::Json => {
+                self.format_schema_default(schemas)
+            }
             OutputFormat::Human => self.format_schema_human(schemas),
             OutputFormat::AI => self.format_schema_ai(schemas),
         }
@@ -2665,7 +8127,7 @@ impl OutputFormatter {
             output.push_str(&format!("- Language: {}\n", schema.language.as_str()));
             output.push_str(&format!(
                 "- Location: `{}:{}`\n",
-                schema.file_path.display(),
+                schema.file_path.display_slash(),
                 schema.line
             ));
             output.push_str(&format!("- Fields: {}\n\n", schema.fields.len()));
@@ -2709,7 +8171,7 @@ impl OutputFormatter {
             output.push_str(&format!(
                 "  Language: {} | Location: {}:{}\n\n",
                 schema.language.as_str(),
-                schema.file_path.display(),
+                schema.file_path.display_slash(),
                 schema.line
             ));
 
@@ -2758,7 +8220,7 @@ impl OutputFormatter {
                     _ => "?",
                 },
                 schema.language.as_str(),
-                schema.file_path.display(),
+                schema.file_path.display_slash(),
                 schema.line
             ));
 
@@ -2782,3 +8244,173 @@ impl OutputFormatter {
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol_diff(name: &str, change_type: ChangeType) -> SymbolDiff {
+        SymbolDiff {
+            name: name.to_string(),
+            symbol_type: SymbolType::Function,
+            change_type,
+            file_path: PathBuf::from("src/lib.rs"),
+            old_lines: Some((3, 5)),
+            new_lines: None,
+            old_signature: None,
+            new_signature: None,
+            is_exported: true,
+            old_name: None,
+            old_file_path: None,
+        }
+    }
+
+    #[test]
+    fn test_compact_lines() {
+        let path = Path::new("src/lib.rs");
+        let source = "pub struct Client;\n\npub fn connect(url: &str) -> Client {\n    Client\n}\n";
+        let file =
+            crate::indexer::index_file(path, source, crate::models::Language::Rust, None).unwrap();
+        let formatter = OutputFormatter::new(OutputFormat::Compact);
+        assert_eq!(
+            formatter.format_query(file.symbols.iter().collect(), false, false),
+            "src/lib.rs:1:1: class Client\n\
+             src/lib.rs:3:1: function connect — (url: &str)\n"
+        );
+
+        let mut renamed = symbol_diff("open", ChangeType::Renamed);
+        renamed.new_lines = Some((10, 12));
+        renamed.old_name = Some("connect".to_string());
+        let mut resigned = symbol_diff("send", ChangeType::SignatureChanged);
+        resigned.old_signature = Some("(a: i32)".to_string());
+        resigned.new_signature = Some("(a: i64)".to_string());
+        let result = DiffResult {
+            commit: "0123456789abcdef".to_string(),
+            against_worktree: false,
+            symbols: vec![
+                renamed,
+                resigned,
+                symbol_diff("close", ChangeType::Deleted),
+                symbol_diff("helper", ChangeType::Added),
+            ],
+            files_analyzed: 1,
+        };
+        assert_eq!(
+            formatter.format_diff(&result),
+            "src/lib.rs:10:1: renamed function open — was connect\n\
+             src/lib.rs:3:1: signature_changed function send — (a: i32) -> (a: i64)\n\
+             src/lib.rs:3:1: deleted function close\n\
+             src/lib.rs:3:1: added function helper\n"
+        );
+        // Additions don't break callers
+        assert_eq!(formatter.format_breaking(&result).lines().count(), 3);
+
+        // Results without a location fall back to the default markdown
+        assert_eq!(
+            formatter.format_snapshot_list(&["v1".to_string()]),
+            OutputFormatter::new(OutputFormat::Default).format_snapshot_list(&["v1".to_string()])
+        );
+    }
+
+    /// A stdout whose reader went away after `budget` bytes
+    struct ClosedPipe {
+        budget: usize,
+        writes: usize,
+    }
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes += 1;
+            if self.budget == 0 {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            let n = buf.len().min(self.budget);
+            self.budget -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_map_and_query_stream_until_the_reader_goes_away() {
+        let mut index = CodeIndex::new();
+        for i in 0..50 {
+            let path = PathBuf::from(format!("src/file{}.rs", i));
+            let source = format!("pub fn handler{}() {{}}\n\npub struct Item{};\n", i, i);
+            index.add_file(
+                crate::indexer::index_file(&path, &source, crate::models::Language::Rust, None)
+                    .unwrap(),
+            );
+        }
+
+        for format in [OutputFormat::Default, OutputFormat::AI, OutputFormat::Human] {
+            let formatter = OutputFormatter::new(format);
+            let mut streamed = Vec::new();
+            formatter.write_map(&mut streamed, &index, 2).unwrap();
+            assert!(String::from_utf8(streamed).unwrap().contains("file49.rs"));
+
+            // Writing stops at the first failed write instead of rendering everything
+            let mut pipe = ClosedPipe {
+                budget: 64,
+                writes: 0,
+            };
+            let err = formatter.write_map(&mut pipe, &index, 2).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+            let writes = pipe.writes;
+
+            let symbols = index.fuzzy_search("handler");
+            assert_eq!(symbols.len(), 50);
+            let mut pipe = ClosedPipe {
+                budget: 64,
+                writes: 0,
+            };
+            let err = formatter
+                .write_query(&mut pipe, symbols, false, false)
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+            assert!(writes < 50 && pipe.writes < 50, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_human_map_language_rows_match_the_header() {
+        let mut index = CodeIndex::new();
+        let path = Path::new("app.py");
+        let source = "# entry\ndef main():\n    pass\n";
+        index.add_file(
+            crate::indexer::index_file(path, source, crate::models::Language::Python, None)
+                .unwrap(),
+        );
+
+        let map = OutputFormatter::new(OutputFormat::Human).format_map(&index, 1);
+        let columns = |needle: &str| {
+            let line = map.lines().find(|line| line.contains(needle)).unwrap();
+            line.matches(['│', '┆']).count() - 1
+        };
+        assert_eq!(columns("Language"), 5);
+        assert_eq!(columns("python"), columns("Language"));
+    }
+
+    #[test]
+    fn test_diff_header_names_what_was_compared() {
+        let formatter = OutputFormatter::new(OutputFormat::Default);
+        let mut result = DiffResult {
+            commit: "0123456789abcdef".to_string(),
+            against_worktree: false,
+            symbols: Vec::new(),
+            files_analyzed: 0,
+        };
+        assert!(formatter
+            .format_diff(&result)
+            .contains("Comparing HEAD to commit: `01234567`"));
+
+        result.commit = "stash@{0}".to_string();
+        result.against_worktree = true;
+        assert!(formatter
+            .format_diff(&result)
+            .contains("Comparing working tree to: `stash@{0}`"));
+    }
+}
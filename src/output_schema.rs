@@ -0,0 +1,851 @@
+//! JSON Schemas (draft 2020-12) for every JSON document cm writes: the `--format json`
+//! payloads that are JSON rather than markdown, the error object that replaces them on
+//! failure, and the files cm saves (bench reports, embed chunks, snapshots, fingerprints).
+//! Printed by `cm output-schema`.
+
+use crate::failure::{ErrorCode, EXIT_ASSERTION_FAILED, EXIT_ERROR, EXIT_NOT_FOUND, EXIT_USAGE};
+use serde_json::{json, Value};
+
+pub const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+pub struct OutputSchema {
+    pub name: &'static str,
+    /// Where the payload comes from
+    pub produced_by: &'static str,
+    pub schema: fn() -> Value,
+}
+
+pub fn all() -> Vec<OutputSchema> {
+    vec![
+        OutputSchema {
+            name: "bench",
+            produced_by: "cm bench (stdout, --output, --baseline)",
+            schema: bench_report,
+        },
+        OutputSchema {
+            name: "embed-chunk",
+            produced_by: "cm embed --output (one object per line)",
+            schema: embed_chunk,
+        },
+        OutputSchema {
+            name: "snapshot",
+            produced_by: "cm snapshot (.codemapper/snapshots/<name>.json)",
+            schema: snapshot,
+        },
+        OutputSchema {
+            name: "error",
+            produced_by: "any command run with --format json that fails (stdout)",
+            schema: error,
+        },
+        OutputSchema {
+            name: "deps",
+            produced_by: "cm deps <symbol> --format json",
+            schema: deps,
+        },
+        OutputSchema {
+            name: "used-by",
+            produced_by: "cm deps <file or symbol> --direction used-by --format json",
+            schema: used_by,
+        },
+        OutputSchema {
+            name: "dep-tree",
+            produced_by: "cm deps <file> --format json",
+            schema: dep_tree,
+        },
+        OutputSchema {
+            name: "size-tree",
+            produced_by: "cm map --mode sizes --format json",
+            schema: size_tree,
+        },
+        OutputSchema {
+            name: "parse-dump",
+            produced_by: "cm parse-dump",
+            schema: parse_dump,
+        },
+        OutputSchema {
+            name: "symbol-diff",
+            produced_by: "cm diff --symbol <name> --format json",
+            schema: symbol_diff,
+        },
+        OutputSchema {
+            name: "kinds",
+            produced_by: "cm kinds --format json",
+            schema: kinds,
+        },
+        OutputSchema {
+            name: "command-history",
+            produced_by: "cm last --list --format json",
+            schema: command_history,
+        },
+        OutputSchema {
+            name: "sets",
+            produced_by: "cm show --format json (without a set name)",
+            schema: sets,
+        },
+        OutputSchema {
+            name: "fingerprint",
+            produced_by: "cm verify --record (the --fingerprint file)",
+            schema: fingerprint,
+        },
+        OutputSchema {
+            name: "fingerprint-drift",
+            produced_by: "cm verify --format json",
+            schema: fingerprint_drift,
+        },
+        OutputSchema {
+            name: "snippets",
+            produced_by: "cm extract --format json",
+            schema: snippets,
+        },
+    ]
+}
+
+pub fn find(name: &str) -> Option<OutputSchema> {
+    all().into_iter().find(|s| s.name == name)
+}
+
+/// One schema as a standalone document. The `$id` keeps `$ref`s inside it pointing at it
+/// when it is bundled.
+pub fn document(schema: &OutputSchema) -> Value {
+    let mut value = (schema.schema)();
+    value["$schema"] = json!(DRAFT);
+    value["$id"] = json!(format!("{}.schema.json", schema.name));
+    value["description"] = json!(format!(
+        "{} Produced by {}.",
+        value["description"].as_str().unwrap_or_default(),
+        schema.produced_by
+    ));
+    value
+}
+
+/// Every schema, under `$defs` keyed by name
+pub fn bundle() -> Value {
+    let defs: serde_json::Map<String, Value> = all()
+        .iter()
+        .map(|s| {
+            let mut value = document(s);
+            if let Some(object) = value.as_object_mut() {
+                object.remove("$schema");
+            }
+            (s.name.to_string(), value)
+        })
+        .collect();
+    json!({
+        "$schema": DRAFT,
+        "title": "codemapper JSON outputs",
+        "$defs": defs,
+    })
+}
+
+fn object(title: &str, description: &str, properties: Value) -> Value {
+    let required: Vec<&String> = properties
+        .as_object()
+        .map(|p| p.keys().collect())
+        .unwrap_or_default();
+    json!({
+        "title": title,
+        "description": description,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn count() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn strings() -> Value {
+    json!({ "type": "array", "items": { "type": "string" } })
+}
+
+/// `[start, end]`, 1-based and inclusive
+fn line_range() -> Value {
+    json!({ "type": "array", "items": count(), "minItems": 2, "maxItems": 2 })
+}
+
+fn symbol_type() -> Value {
+    json!({
+        "type": "string",
+        "description": "Kind name as `cm kinds` lists it: function, class, method, enum, static, \
+            heading, code_block, interface, type, field, or a custom kind from config",
+    })
+}
+
+fn bench_report() -> Value {
+    object(
+        "BenchReport",
+        "Median stage timings of one benchmark run.",
+        json!({
+            "version": { "type": "string", "description": "cm version that ran the benchmark" },
+            "files": count(),
+            "symbols": count(),
+            "iterations": count(),
+            "metrics_ms": {
+                "type": "object",
+                "description": "Median wall time per stage in milliseconds (lower is better)",
+                "additionalProperties": { "type": "number" },
+            },
+        }),
+    )
+}
+
+fn embed_chunk() -> Value {
+    object(
+        "Chunk",
+        "One embeddable unit: a symbol, or part of a long one.",
+        json!({
+            "id": { "type": "string", "description": "path:line_start:name, plus #part for split symbols" },
+            "file_path": { "type": "string" },
+            "name": { "type": "string" },
+            "symbol_type": symbol_type(),
+            "signature": { "type": ["string", "null"] },
+            "line_start": count(),
+            "line_end": count(),
+            "text": { "type": "string", "description": "Name, signature, docstring and body" },
+            "hash": { "type": "string", "description": "Hash of text; unchanged chunks are not re-embedded" },
+        }),
+    )
+}
+
+fn snapshot() -> Value {
+    let symbol = object(
+        "SnapshotSymbol",
+        "A symbol as it was when the snapshot was taken.",
+        json!({
+            "name": { "type": "string" },
+            "symbol_type": symbol_type(),
+            "signature": { "type": ["string", "null"] },
+            "file_path": { "type": "string" },
+            "line_start": count(),
+            "line_end": count(),
+            "is_exported": { "type": "boolean" },
+        }),
+    );
+    let timestamp = object(
+        "SystemTime",
+        "When the snapshot was taken.",
+        json!({
+            "secs_since_epoch": count(),
+            "nanos_since_epoch": count(),
+        }),
+    );
+    object(
+        "Snapshot",
+        "Every symbol of the project at one point in time.",
+        json!({
+            "name": { "type": "string" },
+            "timestamp": timestamp,
+            "commit": { "type": ["string", "null"], "description": "HEAD when the snapshot was taken" },
+            "symbols": { "type": "array", "items": symbol },
+            "file_count": count(),
+            "symbol_count": count(),
+        }),
+    )
+}
+
+fn error() -> Value {
+    let codes: Vec<&str> = ErrorCode::ALL.iter().map(ErrorCode::as_str).collect();
+    let error = object(
+        "Error",
+        "Why the command failed.",
+        json!({
+            "code": { "enum": codes },
+            "message": { "type": "string" },
+            "exit_code": {
+                "enum": [EXIT_ERROR, EXIT_USAGE, EXIT_ASSERTION_FAILED, EXIT_NOT_FOUND],
+                "description": "The process exit status, which follows from code",
+            },
+        }),
+    );
+    object(
+        "ErrorOutput",
+        "Printed in place of the command's output when it fails.",
+        json!({ "error": error }),
+    )
+}
+
+fn deps() -> Value {
+    object(
+        "Deps",
+        "What a symbol's file imports.",
+        json!({
+            "target": { "type": "string" },
+            "direction": { "enum": ["imports"] },
+            "dependencies": strings(),
+        }),
+    )
+}
+
+fn used_by() -> Value {
+    let usage = object(
+        "Usage",
+        "One place that uses the target.",
+        json!({
+            "location": { "type": "string" },
+            "confidence": { "enum": ["text-match", "name-match", "exact-ast"] },
+        }),
+    );
+    object(
+        "UsedBy",
+        "Files or call sites that use a file or symbol.",
+        json!({
+            "target": { "type": "string" },
+            "direction": { "enum": ["used-by"] },
+            "dependencies": { "type": "array", "items": { "type": "string" }, "description": "The usages' locations" },
+            "usages": { "type": "array", "items": usage },
+        }),
+    )
+}
+
+fn dep_tree() -> Value {
+    let node = object(
+        "DepNode",
+        "One import, with the imports of the file it resolves to.",
+        json!({
+            "name": { "type": "string", "description": "The import as written" },
+            "file": { "type": ["string", "null"], "description": "Indexed file it resolves to; null for external packages" },
+            "child_count": count(),
+            "cycle": { "type": "boolean", "description": "file is already on the path from the root" },
+            "repeated": { "type": "boolean", "description": "file was expanded earlier in the tree" },
+            "dependencies": { "type": "array", "items": { "$ref": "#/$defs/node" } },
+        }),
+    );
+    let mut tree = object(
+        "DepTree",
+        "A file's imports, expanded depth levels deep (0 = until every branch ends).",
+        json!({
+            "target": { "type": "string" },
+            "direction": { "enum": ["imports"] },
+            "depth": count(),
+            "dependencies": { "type": "array", "items": { "$ref": "#/$defs/node" } },
+        }),
+    );
+    tree["$defs"] = json!({ "node": node });
+    tree
+}
+
+fn size_tree() -> Value {
+    let mut node = object(
+        "SizeNode",
+        "One box of the treemap; children never add up to more than their parent.",
+        json!({
+            "name": { "type": "string", "description": "Directory, file or symbol name" },
+            "kind": { "type": "string", "description": "dir, file, or the symbol's kind" },
+            "lines": count(),
+            "symbols": count(),
+            "children": { "type": "array", "items": { "$ref": "#" }, "description": "Largest first" },
+        }),
+    );
+    // Leaves leave out children
+    node["required"] = json!(["name", "kind", "lines", "symbols"]);
+    node
+}
+
+fn parse_dump() -> Value {
+    let mut symbol = object(
+        "DumpedSymbol",
+        "A symbol as the parser extracted it.",
+        json!({
+            "name": { "type": "string" },
+            "kind": symbol_type(),
+            "line_start": count(),
+            "line_end": count(),
+            "exported": { "type": "boolean" },
+            "signature": { "type": "string" },
+            "docstring": { "type": "string" },
+            "annotations": strings(),
+        }),
+    );
+    symbol["required"] = json!(["name", "kind", "line_start", "line_end", "exported"]);
+    let mut dependency = object(
+        "DumpedDependency",
+        "An import.",
+        json!({
+            "import": { "type": "string" },
+            "from": { "type": "string" },
+        }),
+    );
+    dependency["required"] = json!(["import"]);
+    object(
+        "ParseDump",
+        "What the parser extracts from one file, symbols in source order.",
+        json!({
+            "version": { "type": "integer", "description": "Changes whenever the dump's shape does" },
+            "language": { "type": "string" },
+            "symbols": { "type": "array", "items": symbol },
+            "dependencies": { "type": "array", "items": dependency },
+        }),
+    )
+}
+
+fn symbol_diff() -> Value {
+    let hunk = object(
+        "Hunk",
+        "A unified diff hunk; line numbers are the file's.",
+        json!({
+            "old_start": count(),
+            "old_count": count(),
+            "new_start": count(),
+            "new_count": count(),
+            "lines": { "type": "array", "items": { "type": "string" }, "description": "Prefixed with ' ', '-' or '+'" },
+        }),
+    );
+    let nullable_range = json!({ "type": ["array", "null"], "items": count() });
+    let change = object(
+        "SymbolChange",
+        "One definition of the symbol, before and after.",
+        json!({
+            "name": { "type": "string" },
+            "type": symbol_type(),
+            "change": { "enum": ["ADDED", "DELETED", "MODIFIED"] },
+            "old_file": { "type": ["string", "null"] },
+            "new_file": { "type": ["string", "null"] },
+            "old_lines": nullable_range,
+            "new_lines": nullable_range,
+            "hunks": { "type": "array", "items": hunk },
+        }),
+    );
+    object(
+        "SymbolDiff",
+        "How one symbol changed between two revisions.",
+        json!({
+            "range": { "type": "string", "description": "base..head, head being worktree for uncommitted changes" },
+            "changes": { "type": "array", "items": change },
+        }),
+    )
+}
+
+fn kinds() -> Value {
+    let kind = object(
+        "Kind",
+        "A symbol kind and the languages that produce it.",
+        json!({
+            "name": symbol_type(),
+            "code": { "type": "string", "description": "Short code used by --format ai" },
+            "custom": { "type": "boolean", "description": "Declared in config or by a plugin" },
+            "languages": strings(),
+        }),
+    );
+    object(
+        "Kinds",
+        "Every symbol kind cm knows.",
+        json!({ "kinds": { "type": "array", "items": kind } }),
+    )
+}
+
+fn command_history() -> Value {
+    let record = object(
+        "CommandRecord",
+        "One recorded command.",
+        json!({
+            "id": count(),
+            "args": { "type": "array", "items": { "type": "string" }, "description": "Arguments after cm, as given" },
+            "cwd": { "type": "string" },
+            "started": { "type": "integer", "minimum": 0, "description": "Seconds since the Unix epoch" },
+            "duration_ms": count(),
+            "exit_code": { "type": "integer" },
+            "output_bytes": count(),
+            "output_lines": count(),
+            "truncated": { "type": "boolean", "description": "Only the start of the output was kept" },
+        }),
+    );
+    object(
+        "CommandHistory",
+        "Recorded commands, newest first.",
+        json!({ "commands": { "type": "array", "items": record } }),
+    )
+}
+
+fn sets() -> Value {
+    let set = object(
+        "SetSummary",
+        "A saved result set.",
+        json!({
+            "name": { "type": "string" },
+            "command": { "type": "string", "description": "The command that found the symbols" },
+            "saved": { "type": "integer", "minimum": 0, "description": "Seconds since the Unix epoch" },
+            "symbols": count(),
+        }),
+    );
+    object(
+        "Sets",
+        "Saved result sets.",
+        json!({ "sets": { "type": "array", "items": set } }),
+    )
+}
+
+fn fingerprint() -> Value {
+    object(
+        "Fingerprint",
+        "The structure of a directory's declarations, line numbers left out.",
+        json!({
+            "version": { "type": "integer" },
+            "hash": { "type": "string", "description": "Over every file path and file hash" },
+            "symbol_count": count(),
+            "files": {
+                "type": "object",
+                "description": "File hash by path relative to the root, /-separated",
+                "additionalProperties": { "type": "string" },
+            },
+        }),
+    )
+}
+
+fn fingerprint_drift() -> Value {
+    object(
+        "FingerprintDrift",
+        "Files whose structure differs from the recorded fingerprint.",
+        json!({
+            "clean": { "type": "boolean" },
+            "baseline": { "type": "string", "description": "Hash of the recorded fingerprint" },
+            "current": { "type": "string" },
+            "changed": strings(),
+            "added": strings(),
+            "removed": strings(),
+        }),
+    )
+}
+
+fn snippets() -> Value {
+    let snippet = object(
+        "Snippet",
+        "A definition's code with the imports it needs.",
+        json!({
+            "name": { "type": "string" },
+            "type": symbol_type(),
+            "file": { "type": "string" },
+            "lines": line_range(),
+            "language": { "type": "string" },
+            "imports": strings(),
+            "local": { "type": "array", "items": { "type": "string" }, "description": "Symbols of the same file the code uses but leaves out" },
+            "code": { "type": "string", "description": "Imports, a blank line, then the body" },
+        }),
+    );
+    object(
+        "Snippets",
+        "Extracted definitions.",
+        json!({ "snippets": { "type": "array", "items": snippet } }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::BenchReport;
+    use crate::confidence::Confidence;
+    use crate::config::KindConfig;
+    use crate::dep_tree::DepNode;
+    use crate::diff::{ChangeType, Hunk, HunkLine, SymbolChange};
+    use crate::embed::Chunk;
+    use crate::extract::Snippet;
+    use crate::fingerprint::{Drift, Fingerprint};
+    use crate::history::CommandRecord;
+    use crate::models::{Language, SymbolType};
+    use crate::output::{OutputFormat, OutputFormatter};
+    use crate::sets::SymbolSet;
+    use crate::snapshot::{Snapshot, SnapshotSymbol};
+    use crate::stats::SizeNode;
+    use std::path::{Path, PathBuf};
+
+    /// The subset of JSON Schema used above: type, enum, properties, required,
+    /// additionalProperties, items and `$ref`s within the same document
+    fn validate(root: &Value, schema: &Value, value: &Value, at: &str) -> Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let target = reference
+                .strip_prefix('#')
+                .and_then(|pointer| root.pointer(pointer))
+                .ok_or_else(|| format!("{}: cannot resolve {}", at, reference))?;
+            return validate(root, target, value, at);
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                return Err(format!("{}: {} not in enum", at, value));
+            }
+        }
+        if !schema["type"].is_null() {
+            let types: Vec<&str> = match &schema["type"] {
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                other => vec![other.as_str().unwrap_or_default()],
+            };
+            let matches = types.iter().any(|t| match *t {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "integer" => value.is_u64() || value.is_i64(),
+                "number" => value.is_number(),
+                "boolean" => value.is_boolean(),
+                "null" => value.is_null(),
+                _ => false,
+            });
+            if !matches {
+                return Err(format!("{}: {} is not {:?}", at, value, types));
+            }
+        }
+        if let Some(object) = value.as_object() {
+            for required in schema["required"].as_array().into_iter().flatten() {
+                let key = required.as_str().unwrap_or_default();
+                if !object.contains_key(key) {
+                    return Err(format!("{}: missing {}", at, key));
+                }
+            }
+            for (key, field) in object {
+                let path = format!("{}.{}", at, key);
+                match schema["properties"].get(key) {
+                    Some(property) => validate(root, property, field, &path)?,
+                    None => match &schema["additionalProperties"] {
+                        Value::Bool(false) => return Err(format!("{}: not in schema", path)),
+                        Value::Object(_) => {
+                            validate(root, &schema["additionalProperties"], field, &path)?
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        if let Some(items) = value.as_array() {
+            for (i, item) in items.iter().enumerate() {
+                validate(root, &schema["items"], item, &format!("{}[{}]", at, i))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_schemas_match_serialized_outputs() {
+        let report = BenchReport {
+            version: "0.1.0".to_string(),
+            files: 3,
+            symbols: 40,
+            iterations: 5,
+            metrics_ms: [("parse".to_string(), 12.5)].into_iter().collect(),
+        };
+        let chunk = Chunk {
+            id: "src/a.rs:1:main".to_string(),
+            file_path: PathBuf::from("src/a.rs"),
+            name: "main".to_string(),
+            symbol_type: SymbolType::Function,
+            signature: None,
+            line_start: 1,
+            line_end: 3,
+            text: "fn main() {}".to_string(),
+            hash: "abc".to_string(),
+        };
+        let symbol = |symbol_type| SnapshotSymbol {
+            name: "main".to_string(),
+            symbol_type,
+            signature: Some("()".to_string()),
+            file_path: PathBuf::from("src/a.rs"),
+            line_start: 1,
+            line_end: 3,
+            is_exported: true,
+        };
+        // Exhaustive, so a new symbol type fails to compile here until the enum is updated
+        let types = [
+            SymbolType::Function,
+            SymbolType::Class,
+            SymbolType::Method,
+            SymbolType::Enum,
+            SymbolType::StaticField,
+            SymbolType::Heading,
+            SymbolType::CodeBlock,
+            SymbolType::Interface,
+            SymbolType::TypeAlias,
+            SymbolType::Field,
+            crate::kinds::register(&KindConfig {
+                name: "migration".to_string(),
+                code: "mg".to_string(),
+                plural: None,
+            })
+            .unwrap(),
+        ];
+        for t in types {
+            match t {
+                SymbolType::Function
+                | SymbolType::Class
+                | SymbolType::Method
+                | SymbolType::Enum
+                | SymbolType::StaticField
+                | SymbolType::Heading
+                | SymbolType::CodeBlock
+                | SymbolType::Interface
+                | SymbolType::TypeAlias
+                | SymbolType::Field
+                | SymbolType::Custom(_) => {}
+            }
+        }
+        let snapshot = Snapshot::new(
+            "base".to_string(),
+            None,
+            types.into_iter().map(symbol).collect(),
+            1,
+        );
+
+        let mut samples = vec![
+            ("bench", serde_json::to_value(&report).unwrap()),
+            ("embed-chunk", serde_json::to_value(&chunk).unwrap()),
+            ("snapshot", serde_json::to_value(&snapshot).unwrap()),
+        ];
+        samples.extend(formatted_samples());
+        let mut names: Vec<&str> = samples.iter().map(|(name, _)| *name).collect();
+        names.sort_unstable();
+        names.dedup();
+        let mut registered: Vec<&str> = all().iter().map(|s| s.name).collect();
+        registered.sort_unstable();
+        assert_eq!(names, registered);
+        for (name, sample) in samples {
+            let schema = document(&find(name).unwrap());
+            validate(&schema, &schema, &sample, name).unwrap();
+        }
+
+        assert!(bundle()["$defs"]["snapshot"]["properties"]["symbols"].is_object());
+    }
+
+    /// What the commands print with `--format json` (or write, for the fingerprint), with
+    /// every optional part filled in
+    fn formatted_samples() -> Vec<(&'static str, Value)> {
+        let json = OutputFormatter::new(OutputFormat::Json);
+        let parse = |output: String| serde_json::from_str::<Value>(&output).unwrap();
+        let leaf = |name: &str, file: Option<&str>| DepNode {
+            name: name.to_string(),
+            file: file.map(PathBuf::from),
+            child_count: 0,
+            cycle: false,
+            repeated: false,
+            children: Vec::new(),
+        };
+        let tree = [DepNode {
+            child_count: 2,
+            children: vec![leaf("os", None), leaf("./b", Some("src/b.py"))],
+            ..leaf("./a", Some("src/a.py"))
+        }];
+        let size_leaf = |name: &str, kind| SizeNode {
+            name: name.to_string(),
+            kind,
+            lines: 3,
+            symbols: 1,
+            children: Vec::new(),
+        };
+        let sizes = SizeNode {
+            children: vec![SizeNode {
+                children: vec![size_leaf("main", "function")],
+                ..size_leaf("a.rs", "file")
+            }],
+            ..size_leaf("src", "dir")
+        };
+        let dump = crate::parse_dump::dump(
+            Path::new("lib.rs"),
+            "use std::fmt;\n/// Docs\npub fn add(a: i32) -> i32 { a }\n",
+            Language::Rust,
+        )
+        .unwrap();
+        let change = SymbolChange {
+            name: "add".to_string(),
+            symbol_type: SymbolType::Function,
+            change_type: ChangeType::Modified,
+            old_file: Some(PathBuf::from("src/lib.rs")),
+            new_file: None,
+            old_lines: Some((2, 3)),
+            new_lines: None,
+            hunks: vec![Hunk {
+                old_start: 2,
+                old_count: 1,
+                new_start: 2,
+                new_count: 1,
+                lines: vec![
+                    HunkLine::Removed("a".to_string()),
+                    HunkLine::Added("b".to_string()),
+                    HunkLine::Context("c".to_string()),
+                ],
+            }],
+        };
+        let record = CommandRecord {
+            id: 7,
+            args: vec!["stats".to_string()],
+            cwd: PathBuf::from("/repo"),
+            started: 1_700_000_000,
+            duration_ms: 12,
+            exit_code: 0,
+            output_bytes: 10,
+            output_lines: 1,
+            truncated: false,
+        };
+        let set = SymbolSet {
+            name: "todo".to_string(),
+            command: "cm query add".to_string(),
+            saved: 1_700_000_000,
+            symbols: Vec::new(),
+        };
+        let fingerprint = Fingerprint {
+            version: 1,
+            hash: "abc".to_string(),
+            symbol_count: 3,
+            files: [("src/a.rs".to_string(), "def".to_string())].into(),
+        };
+        let drift = Drift {
+            baseline: "abc".to_string(),
+            current: "abd".to_string(),
+            changed: vec!["src/a.rs".to_string()],
+            ..Drift::default()
+        };
+        let snippet = Snippet {
+            name: "add".to_string(),
+            symbol_type: SymbolType::Function,
+            file_path: PathBuf::from("src/lib.rs"),
+            line_start: 2,
+            line_end: 3,
+            language: Language::Rust,
+            imports: vec!["use std::fmt;".to_string()],
+            local: vec!["helper".to_string()],
+            body: "fn add() {}".to_string(),
+        };
+
+        let mut samples: Vec<(&str, Value)> = ErrorCode::ALL
+            .iter()
+            .map(|code| ("error", parse(json.format_error(*code, "failed"))))
+            .collect();
+        samples.extend([
+            (
+                "deps",
+                parse(json.format_deps("add", vec!["std::fmt".to_string()], "imports")),
+            ),
+            (
+                "used-by",
+                parse(json.format_used_by(
+                    "add",
+                    &[
+                        ("src/a.rs:3".to_string(), Confidence::ExactAst),
+                        ("src/b.rs:9".to_string(), Confidence::TextMatch),
+                    ],
+                )),
+            ),
+            (
+                "dep-tree",
+                parse(json.format_dep_tree("src/a.py", &tree, 2)),
+            ),
+            ("size-tree", parse(json.format_size_tree(&sizes, 10))),
+            ("parse-dump", parse(dump.to_json())),
+            (
+                "symbol-diff",
+                parse(json.format_symbol_changes(&[change], "HEAD~1..worktree")),
+            ),
+            (
+                "kinds",
+                parse(json.format_kinds(&crate::kinds::catalog(None))),
+            ),
+            (
+                "command-history",
+                parse(json.format_command_history(&[record])),
+            ),
+            ("sets", parse(json.format_sets(&[set]))),
+            ("fingerprint", serde_json::to_value(&fingerprint).unwrap()),
+            (
+                "fingerprint-drift",
+                parse(json.format_fingerprint_drift(&drift, Path::new("gen.fingerprint"))),
+            ),
+            ("snippets", parse(json.format_snippets(&[snippet]))),
+        ]);
+        samples
+    }
+}
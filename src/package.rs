@@ -0,0 +1,335 @@
+//! Published package sources for `cm api-diff`: resolve `crates:name@version`,
+//! `npm:name@version` or `pypi:name@version` against its registry, then download and unpack
+//! the source archive.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use serde_json::Value;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+const USER_AGENT: &str = "codemapper (cm api-diff)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Registry {
+    Crates,
+    Npm,
+    PyPI,
+}
+
+impl Registry {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Registry::Crates => "crates",
+            Registry::Npm => "npm",
+            Registry::PyPI => "pypi",
+        }
+    }
+
+    /// Source extensions compared for packages from this registry
+    pub fn extensions(&self) -> &'static str {
+        match self {
+            Registry::Crates => "rs",
+            Registry::Npm => "js,ts,jsx,tsx",
+            Registry::PyPI => "py",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PackageSpec {
+    pub registry: Registry,
+    pub name: String,
+    /// Exact version or a prefix such as `0.3`; `None` means the latest release
+    pub version: Option<String>,
+}
+
+impl PackageSpec {
+    /// `crates:codemapper@0.3`, `npm:@scope/pkg@1.2.0`, `pypi:requests` (latest)
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (registry, rest) = spec.split_once(':').with_context(|| {
+            format!(
+                "Invalid package '{}' (expected REGISTRY:NAME[@VERSION], e.g. crates:serde@1.0)",
+                spec
+            )
+        })?;
+        let registry = match registry {
+            "crates" | "crates.io" | "cargo" => Registry::Crates,
+            "npm" => Registry::Npm,
+            "pypi" | "pip" => Registry::PyPI,
+            other => bail!(
+                "Unknown registry '{}' (expected crates, npm or pypi)",
+                other
+            ),
+        };
+
+        // Scoped npm names start with '@', so only a later '@' separates the version
+        let (name, version) = match rest.char_indices().skip(1).find(|&(_, c)| c == '@') {
+            Some((at, _)) => (&rest[..at], Some(rest[at + 1..].to_string())),
+            None => (rest, None),
+        };
+        if name.is_empty() || version.as_deref() == Some("") {
+            bail!("Invalid package '{}': missing name or version", spec);
+        }
+
+        Ok(Self {
+            registry,
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+/// Numeric release components, `None` for pre-releases (`1.0.0-rc.1`, `2.0b1`, `1.0.dev0`)
+fn release_key(version: &str) -> Option<Vec<u64>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Highest release matching `requested`: the exact version, or every `0.3.x` for a prefix
+/// like `0.3`. Pre-releases are only picked when requested by their full version.
+pub fn pick_version<'a>(
+    available: impl IntoIterator<Item = &'a str>,
+    requested: Option<&str>,
+) -> Option<&'a str> {
+    let available: Vec<&str> = available.into_iter().collect();
+    if let Some(exact) = requested.and_then(|r| available.iter().find(|v| **v == r)) {
+        return Some(exact);
+    }
+
+    available
+        .into_iter()
+        .filter(|v| match requested {
+            Some(prefix) => v.starts_with(&format!("{}.", prefix)),
+            None => true,
+        })
+        .filter_map(|v| release_key(v).map(|key| (key, v)))
+        .max()
+        .map(|(_, v)| v)
+}
+
+/// `value` for one URL path segment: everything but unreserved characters and the `@` of
+/// scoped npm names percent-encoded, so a name cannot reach another path or host
+fn path_segment(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => {
+                escaped.push(byte as char)
+            }
+            _ => escaped.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    escaped
+}
+
+fn get(url: &str) -> Result<Vec<u8>> {
+    let response = attohttpc::get(url)
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .with_context(|| format!("Failed to download {}", url))?;
+    if !response.is_success() {
+        bail!("Download failed: {} returned {}", url, response.status());
+    }
+    Ok(response.bytes()?)
+}
+
+fn get_json(url: &str) -> Result<Value> {
+    let body = get(url)?;
+    serde_json::from_slice(&body).with_context(|| format!("Invalid JSON from {}", url))
+}
+
+/// Resolved version and source archive URL
+fn resolve(spec: &PackageSpec) -> Result<(String, String)> {
+    let requested = spec.version.as_deref();
+    let not_found = || {
+        anyhow::anyhow!(
+            "No {} release of {} matches '{}'",
+            spec.registry.as_str(),
+            spec.name,
+            requested.unwrap_or("latest")
+        )
+    };
+
+    match spec.registry {
+        Registry::Crates => {
+            let name = path_segment(&spec.name);
+            let meta = get_json(&format!("https://crates.io/api/v1/crates/{}", name))?;
+            let versions = meta["versions"].as_array().cloned().unwrap_or_default();
+            let published = versions
+                .iter()
+                .filter(|v| !v["yanked"].as_bool().unwrap_or(false))
+                .filter_map(|v| v["num"].as_str());
+            let version = pick_version(published, requested).ok_or_else(not_found)?;
+            let url = format!(
+                "https://static.crates.io/crates/{0}/{0}-{1}.crate",
+                name,
+                path_segment(version)
+            );
+            Ok((version.to_string(), url))
+        }
+        Registry::Npm => {
+            let meta = get_json(&format!(
+                "https://registry.npmjs.org/{}",
+                path_segment(&spec.name)
+            ))?;
+            let versions = meta["versions"].as_object().cloned().unwrap_or_default();
+            let version = match requested {
+                None => meta["dist-tags"]["latest"].as_str(),
+                Some(_) => pick_version(versions.keys().map(String::as_str), requested),
+            }
+            .ok_or_else(not_found)?;
+            let url = versions
+                .get(version)
+                .and_then(|v| v["dist"]["tarball"].as_str())
+                .ok_or_else(not_found)?;
+            Ok((version.to_string(), url.to_string()))
+        }
+        Registry::PyPI => {
+            let meta = get_json(&format!(
+                "https://pypi.org/pypi/{}/json",
+                path_segment(&spec.name)
+            ))?;
+            let releases = meta["releases"].as_object().cloned().unwrap_or_default();
+            let version = pick_version(releases.keys().map(String::as_str), requested)
+                .ok_or_else(not_found)?;
+            let url = releases[version]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|file| file["packagetype"] == "sdist")
+                .and_then(|file| file["url"].as_str())
+                .with_context(|| format!("{} {} has no source distribution", spec.name, version))?;
+            Ok((version.to_string(), url.to_string()))
+        }
+    }
+}
+
+/// Download the package into `dest`; returns the resolved version and the unpacked source root
+pub fn fetch(spec: &PackageSpec, dest: &Path) -> Result<(String, PathBuf)> {
+    let (version, url) = resolve(spec)?;
+    std::fs::create_dir_all(dest)?;
+
+    // Only the extension of the URL's file name is kept, so it cannot pick the path
+    let file_name = url.split(['?', '#']).next().unwrap_or(&url);
+    let archive = match file_name.rsplit('/').next() {
+        Some(name) if name.to_lowercase().ends_with(".zip") => dest.join("package.zip"),
+        _ => dest.join("package.tar.gz"),
+    };
+    std::fs::write(&archive, get(&url)?)
+        .with_context(|| format!("Failed to write {}", archive.display()))?;
+
+    let root = unpack(&archive, &dest.join("src"))?;
+    Ok((version, root))
+}
+
+/// Unpack a `.crate`, `.tgz`, `.tar.gz` or `.zip` archive into `dest`. Archives holding a
+/// single top-level directory (`name-1.0/`, npm's `package/`) return that directory.
+pub fn unpack(archive: &Path, dest: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dest)?;
+
+    // Both refuse entries that would land outside `dest`
+    let name = archive.to_string_lossy().to_lowercase();
+    let file =
+        File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+    let unpacked = if name.ends_with(".zip") || name.ends_with(".whl") {
+        zip::ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(dest))
+            .map_err(anyhow::Error::from)
+    } else {
+        tar::Archive::new(GzDecoder::new(file))
+            .unpack(dest)
+            .map_err(anyhow::Error::from)
+    };
+    unpacked.with_context(|| format!("Failed to unpack {}", archive.display()))?;
+
+    let entries: Vec<PathBuf> = std::fs::read_dir(dest)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    match entries.as_slice() {
+        [single] if single.is_dir() => Ok(single.clone()),
+        _ => Ok(dest.to_path_buf()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec_and_pick_version() {
+        let spec = PackageSpec::parse("crates:codemapper@0.3").unwrap();
+        assert_eq!(spec.registry, Registry::Crates);
+        assert_eq!(
+            (spec.name.as_str(), spec.version.as_deref()),
+            ("codemapper", Some("0.3"))
+        );
+        let scoped = PackageSpec::parse("npm:@types/node@20").unwrap();
+        assert_eq!(
+            (scoped.name.as_str(), scoped.version.as_deref()),
+            ("@types/node", Some("20"))
+        );
+        assert_eq!(PackageSpec::parse("pypi:requests").unwrap().version, None);
+        assert!(PackageSpec::parse("gems:rails@7").is_err());
+        assert!(PackageSpec::parse("codemapper@0.3").is_err());
+
+        let versions = ["0.2.9", "0.3.0", "0.3.10", "0.3.2", "0.4.0-rc.1", "0.30.0"];
+        assert_eq!(pick_version(versions, Some("0.3")), Some("0.3.10"));
+        assert_eq!(pick_version(versions, Some("0.3.2")), Some("0.3.2"));
+        assert_eq!(pick_version(versions, None), Some("0.30.0"));
+        assert_eq!(
+            pick_version(versions, Some("0.4.0-rc.1")),
+            Some("0.4.0-rc.1")
+        );
+        assert_eq!(pick_version(versions, Some("0.5")), None);
+    }
+    #[test]
+    fn test_path_segment_escapes_names() {
+        assert_eq!(path_segment("serde_json"), "serde_json");
+        assert_eq!(path_segment("@types/node"), "@types%2Fnode");
+        assert_eq!(
+            path_segment("x/../../admin?a=1#b"),
+            "x%2F..%2F..%2Fadmin%3Fa%3D1%23b"
+        );
+    }
+
+    #[test]
+    fn test_unpack_tarball_and_zip() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = b"pub fn run() {}\n";
+
+        let tarball = dir.path().join("pkg.crate");
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            File::create(&tarball).unwrap(),
+            flate2::Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(source.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "pkg-1.0/src/lib.rs", &source[..])
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let root = unpack(&tarball, &dir.path().join("from-tar")).unwrap();
+        assert_eq!(root, dir.path().join("from-tar").join("pkg-1.0"));
+        assert_eq!(std::fs::read(root.join("src/lib.rs")).unwrap(), source);
+
+        let archive = dir.path().join("pkg.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("pkg/app.py", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"def run():\n    pass\n").unwrap();
+        zip.start_file("pkg/util.py", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let root = unpack(&archive, &dir.path().join("from-zip")).unwrap();
+        assert_eq!(root, dir.path().join("from-zip").join("pkg"));
+        assert!(root.join("app.py").is_file() && root.join("util.py").is_file());
+
+        assert!(unpack(&dir.path().join("missing.tar.gz"), dir.path()).is_err());
+    }
+}
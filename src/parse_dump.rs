@@ -0,0 +1,153 @@
+//! `cm parse-dump`: what the parser extracts from one file, as canonical JSON for golden-file
+//! tests. Symbols are in source order, fields that depend on the index or the machine (parent
+//! ids, byte offsets, the file's path) are left out, and keys come in a fixed order, so the same
+//! parser output always dumps to the same bytes. The fixtures in `test_files/` are checked
+//! against their dumps in `test_files/golden/`.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::path::Path;
+
+use crate::indexer;
+use crate::models::{Dependency, Language, Symbol};
+
+/// Bumped when the dump's shape changes, so stale golden files fail loudly
+pub const DUMP_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct ParseDump {
+    pub version: u32,
+    pub language: &'static str,
+    pub symbols: Vec<DumpedSymbol>,
+    pub dependencies: Vec<DumpedDependency>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DumpedSymbol {
+    pub name: String,
+    pub kind: &'static str,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub exported: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docstring: Option<String>,
+    /// `@cm:` markers, as written
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DumpedDependency {
+    pub import: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+impl From<&Symbol> for DumpedSymbol {
+    fn from(symbol: &Symbol) -> Self {
+        DumpedSymbol {
+            name: symbol.name.to_string(),
+            kind: symbol.symbol_type.as_str(),
+            line_start: symbol.line_start,
+            line_end: symbol.line_end,
+            exported: symbol.is_exported,
+            signature: symbol.signature.clone(),
+            docstring: symbol.docstring.clone(),
+            annotations: symbol.annotations.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+impl From<&Dependency> for DumpedDependency {
+    fn from(dependency: &Dependency) -> Self {
+        DumpedDependency {
+            import: dependency.import_name.clone(),
+            from: dependency.from_file.clone(),
+        }
+    }
+}
+
+impl ParseDump {
+    /// Pretty-printed with a trailing newline, ready to be written as a golden file
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).unwrap_or_default();
+        json.push('\n');
+        json
+    }
+}
+
+/// Parse `content` as `language` and dump the result. `path` only matters to parsers that
+/// look at the file name (plugins, `__init__.py`); it does not appear in the dump.
+pub fn dump(path: &Path, content: &str, language: Language) -> Result<ParseDump> {
+    let file_info = indexer::index_file(path, content, language, None)?;
+
+    // Outermost first where two symbols start on the same line
+    let mut symbols: Vec<&Symbol> = file_info.symbols.iter().collect();
+    symbols.sort_by_key(|s| {
+        (
+            s.line_start,
+            Reverse(s.line_end),
+            s.symbol_type.as_str(),
+            s.name.as_str(),
+        )
+    });
+    let mut dependencies: Vec<DumpedDependency> =
+        file_info.dependencies.iter().map(Into::into).collect();
+    dependencies.sort_by(|a, b| (&a.import, &a.from).cmp(&(&b.import, &b.from)));
+
+    Ok(ParseDump {
+        version: DUMP_VERSION,
+        language: language.as_str(),
+        symbols: symbols.into_iter().map(Into::into).collect(),
+        dependencies,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Set `CM_UPDATE_GOLDEN=1` to rewrite the golden files after an intended parser change
+    #[test]
+    fn test_fixtures_match_golden_dumps() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+        let golden_dir = fixtures.join("golden");
+        let update = std::env::var_os("CM_UPDATE_GOLDEN").is_some();
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&fixtures)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty());
+
+        for path in paths {
+            let language = indexer::detect_language(&path);
+            let content = fs::read_to_string(&path).unwrap();
+            let actual = dump(&path, &content, language).unwrap().to_json();
+            assert_eq!(actual, dump(&path, &content, language).unwrap().to_json());
+
+            let name = path.file_name().unwrap().to_string_lossy();
+            let golden = golden_dir.join(format!("{}.json", name));
+            if update {
+                fs::create_dir_all(&golden_dir).unwrap();
+                fs::write(&golden, &actual).unwrap();
+                continue;
+            }
+            let expected = fs::read_to_string(&golden)
+                .unwrap_or_else(|_| panic!("missing {}", golden.display()));
+            assert!(
+                actual == expected,
+                "parse dump of {} differs from {} (CM_UPDATE_GOLDEN=1 to accept)",
+                name,
+                golden.display()
+            );
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use super::{ParseResult, Parser as ParserTrait};
+use super::{cached_query, pooled_parse, ParseResult, Parser as ParserTrait};
 use crate::models::{Dependency, Symbol, SymbolType};
 use anyhow::{Context, Result};
 use std::path::Path;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tree_sitter::{Node, QueryCursor};
 
 pub struct CParser;
 
@@ -46,11 +46,11 @@ impl CParser {
         let language: tree_sitter::Language = tree_sitter_c::LANGUAGE.into();
 
         // Query for function definitions
-        let func_query = Query::new(&language, r#"(function_definition) @func.def"#)
+        let func_query = cached_query(&language, r#"(function_definition) @func.def"#)
             .context("Failed to create C function query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&func_query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(func_query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             for capture in match_.captures {
@@ -91,15 +91,17 @@ impl CParser {
                 if let Some(name) = func_name {
                     let docstring = self.extract_comment(node, source);
                     symbols.push(Symbol {
-                        name,
+                        name: name.into(),
                         symbol_type: SymbolType::Function,
                         signature: func_params,
                         docstring,
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: false,
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
             }
@@ -118,11 +120,11 @@ impl CParser {
         let language: tree_sitter::Language = tree_sitter_c::LANGUAGE.into();
 
         // Query for struct definitions
-        let struct_query = Query::new(&language, r#"(struct_specifier) @struct.def"#)
+        let struct_query = cached_query(&language, r#"(struct_specifier) @struct.def"#)
             .context("Failed to create C struct query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&struct_query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(struct_query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             for capture in match_.captures {
@@ -149,26 +151,28 @@ impl CParser {
                 if let Some(name) = struct_name {
                     let docstring = self.extract_comment(node, source);
                     symbols.push(Symbol {
-                        name,
+                        name: name.into(),
                         symbol_type: SymbolType::Class,
                         signature: Some("struct".to_string()),
                         docstring,
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: false,
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
             }
         }
 
         // Query for union definitions
-        let union_query = Query::new(&language, r#"(union_specifier) @union.def"#)
+        let union_query = cached_query(&language, r#"(union_specifier) @union.def"#)
             .context("Failed to create C union query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&union_query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(union_query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             for capture in match_.captures {
@@ -195,15 +199,17 @@ impl CParser {
                 if let Some(name) = union_name {
                     let docstring = self.extract_comment(node, source);
                     symbols.push(Symbol {
-                        name,
+                        name: name.into(),
                         symbol_type: SymbolType::Class,
                         signature: Some("union".to_string()),
                         docstring,
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: false,
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
             }
@@ -259,14 +265,9 @@ impl CParser {
 
 impl ParserTrait for CParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
-        let mut parser = Parser::new();
         let language: tree_sitter::Language = tree_sitter_c::LANGUAGE.into();
-        parser
-            .set_language(&language)
-            .context("Failed to set C language")?;
-
-        let tree = parser
-            .parse(content, None)
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set C language")?
             .context("Failed to parse C file")?;
 
         let root = tree.root_node();
@@ -0,0 +1,562 @@
+use super::walk::{child_of_kind, collapsed_text, Scope};
+use super::{constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::path::Path;
+use tree_sitter::Node;
+
+pub struct CppParser;
+
+/// Split `a::B<std::string>::c` on the `::` outside template arguments
+fn split_qualified(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'<' => depth += 1,
+            b'>' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                parts.push(text[start..i].trim());
+                start = i + 2;
+                i += 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+impl CppParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn extract_text(&self, node: Node, source: &str) -> Option<String> {
+        let start = node.start_byte();
+        let end = node.end_byte();
+        if end <= source.len() && start <= end {
+            source.get(start..end).map(|s| s.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// The comment right above a declaration, or above the `template <...>` line wrapping it
+    fn extract_comment(&self, node: Node, source: &str) -> Option<String> {
+        let node = match node.parent() {
+            Some(parent) if parent.kind() == "template_declaration" => parent,
+            _ => node,
+        };
+        let prev = node.prev_sibling()?;
+        if prev.kind() == "comment" {
+            return self.extract_text(prev, source);
+        }
+        None
+    }
+
+    fn has_specifier(&self, node: Node, source: &str, keyword: &str) -> bool {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).any(|child| {
+            child.kind() == "storage_class_specifier"
+                && self.extract_text(child, source).as_deref() == Some(keyword)
+        });
+        found
+    }
+
+    /// The `function_declarator` under pointer and reference declarators, if `node` declares
+    /// a function at all
+    fn function_declarator<'a>(&self, node: Node<'a>) -> Option<Node<'a>> {
+        let mut current = node.child_by_field_name("declarator")?;
+        loop {
+            if current.kind() == "function_declarator" {
+                return Some(current);
+            }
+            current = match current.child_by_field_name("declarator") {
+                Some(inner) => inner,
+                // `reference_declarator` has no field for what it wraps
+                None if current.kind() == "reference_declarator" => {
+                    current.named_child(current.named_child_count().checked_sub(1)?)?
+                }
+                None => return None,
+            };
+        }
+    }
+
+    fn symbol(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        name: String,
+        symbol_type: SymbolType,
+        signature: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            name: name.into(),
+            symbol_type,
+            signature,
+            docstring: self.extract_comment(node, source),
+            line_start: node.start_position().row + 1,
+            line_end: node.end_position().row + 1,
+            parent_id: None,
+            file_path: file_path.into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    fn visit(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut exported = scope.visible && scope.public_members;
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "access_specifier" => {
+                    exported = scope.visible
+                        && self.extract_text(child, source).as_deref() == Some("public");
+                }
+                "namespace_definition" => self.namespace(child, source, file_path, scope, symbols),
+                "class_specifier" | "struct_specifier" | "union_specifier" => {
+                    self.class(child, source, file_path, scope, exported, symbols)
+                }
+                "enum_specifier" => {
+                    self.enumeration(child, source, file_path, scope, exported, symbols)
+                }
+                "function_definition" => {
+                    let exported = exported && !self.has_specifier(child, source, "static");
+                    self.function(child, source, file_path, scope, exported, symbols);
+                    // A definition may start with a type it defines, as in `struct P { } make()`
+                    self.type_of(child, source, file_path, scope, exported, symbols);
+                }
+                "field_declaration" | "declaration" | "type_definition" => {
+                    // Method declarations in a class body; free prototypes are not definitions
+                    if scope.in_class && self.function_declarator(child).is_some() {
+                        self.function(child, source, file_path, scope, exported, symbols);
+                    }
+                    self.type_of(child, source, file_path, scope, exported, symbols);
+                }
+                "template_declaration"
+                | "linkage_specification"
+                | "preproc_if"
+                | "preproc_ifdef"
+                | "preproc_else"
+                | "preproc_elif" => {
+                    let inner = Scope {
+                        public_members: exported,
+                        ..scope
+                    };
+                    let body = child.child_by_field_name("body").unwrap_or(child);
+                    self.visit(body, source, file_path, inner, symbols);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Classes and enums defined in the type of a declaration, as in `typedef struct P { } P;`
+    fn type_of(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        exported: bool,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(type_node) = node.child_by_field_name("type") else {
+            return;
+        };
+        match type_node.kind() {
+            "class_specifier" | "struct_specifier" | "union_specifier" => {
+                self.class(type_node, source, file_path, scope, exported, symbols)
+            }
+            "enum_specifier" => {
+                self.enumeration(type_node, source, file_path, scope, exported, symbols)
+            }
+            _ => {}
+        }
+    }
+
+    fn namespace(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let name = node
+            .child_by_field_name("name")
+            .and_then(|n| self.extract_text(n, source));
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let visible = match name {
+            Some(name) => {
+                let mut symbol = self.symbol(
+                    node,
+                    source,
+                    file_path,
+                    name,
+                    SymbolType::Class,
+                    Some("namespace".to_string()),
+                );
+                symbol.is_exported = scope.visible;
+                symbols.push(symbol);
+                scope.visible
+            }
+            // Everything in an anonymous namespace is local to the file
+            None => false,
+        };
+        let inner = Scope {
+            visible,
+            public_members: true,
+            ..scope
+        };
+        self.visit(body, source, file_path, inner, symbols);
+    }
+
+    fn class(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        exported: bool,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        // `class Foo;` and `struct Foo x;` only name the type
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let Some(name) = node
+            .child_by_field_name("name")
+            .and_then(|n| self.extract_text(n, source))
+        else {
+            return;
+        };
+        let keyword = match node.kind() {
+            "class_specifier" => "class",
+            "struct_specifier" => "struct",
+            _ => "union",
+        };
+        let bases = child_of_kind(node, "base_class_clause")
+            .and_then(|clause| collapsed_text(clause, source));
+        let signature = match bases {
+            Some(bases) => format!("{} {}", keyword, bases),
+            None => keyword.to_string(),
+        };
+
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name,
+            SymbolType::Class,
+            Some(signature),
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+
+        let inner = Scope {
+            public_members: keyword != "class",
+            in_class: true,
+            ..Scope::member_of(symbols.len() - 1, exported)
+        };
+        self.visit(body, source, file_path, inner, symbols);
+    }
+
+    fn enumeration(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        exported: bool,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let Some(name) = node
+            .child_by_field_name("name")
+            .and_then(|n| self.extract_text(n, source))
+        else {
+            return;
+        };
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name.clone(),
+            SymbolType::Enum,
+            None,
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+
+        // Enumerators, as `Color::Red`, with their explicit values
+        let mut cursor = body.walk();
+        for enumerator in body.named_children(&mut cursor) {
+            if enumerator.kind() != "enumerator" {
+                continue;
+            }
+            let Some(variant) = enumerator
+                .child_by_field_name("name")
+                .and_then(|n| self.extract_text(n, source))
+            else {
+                continue;
+            };
+            let value = enumerator
+                .child_by_field_name("value")
+                .and_then(|v| self.extract_text(v, source));
+            let mut symbol = self.symbol(
+                enumerator,
+                source,
+                file_path,
+                format!("{}::{}", name, variant),
+                SymbolType::Enum,
+                constant_signature(Some(&format!("variant of {}", name)), value.as_deref()),
+            );
+            symbol.parent_id = scope.parent;
+            symbol.is_exported = exported;
+            symbols.push(symbol);
+        }
+    }
+
+    /// A function or method definition, or a method declared in a class body. Out-of-line
+    /// definitions (`Foo::bar`) are methods of `Foo` unless `Foo` is a namespace declared in
+    /// this file.
+    fn function(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        exported: bool,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(declarator) = self.function_declarator(node) else {
+            return;
+        };
+        let Some(full_name) = declarator
+            .child_by_field_name("declarator")
+            .and_then(|n| self.extract_text(n, source))
+        else {
+            return;
+        };
+        let parts = split_qualified(&full_name);
+        let Some(name) = parts.last().filter(|name| !name.is_empty()) else {
+            return;
+        };
+        let owner = parts
+            .len()
+            .checked_sub(2)
+            .map(|i| parts[i].split('<').next().unwrap_or(parts[i]));
+
+        let (symbol_type, parent_id) = match owner {
+            _ if scope.in_class => (SymbolType::Method, scope.parent),
+            Some(owner) => {
+                let is_namespace = symbols.iter().any(|s| {
+                    s.name.as_str() == owner && s.signature.as_deref() == Some("namespace")
+                });
+                let class = symbols.iter().rposition(|s| {
+                    s.name.as_str() == owner
+                        && s.symbol_type == SymbolType::Class
+                        && s.signature.as_deref() != Some("namespace")
+                });
+                if is_namespace && class.is_none() {
+                    (SymbolType::Function, None)
+                } else {
+                    (SymbolType::Method, class)
+                }
+            }
+            None => (SymbolType::Function, None),
+        };
+
+        let signature = declarator
+            .child_by_field_name("parameters")
+            .and_then(|p| self.extract_text(p, source));
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name.to_string(),
+            symbol_type,
+            signature,
+        );
+        symbol.parent_id = parent_id;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+    }
+
+    fn process_includes(&self, tree_root: Node, source: &str) -> Vec<Dependency> {
+        let mut includes = Vec::new();
+        let mut stack = vec![tree_root];
+
+        while let Some(node) = stack.pop() {
+            if node.kind() == "preproc_include" {
+                if let Some(path) = node
+                    .child_by_field_name("path")
+                    .and_then(|p| self.extract_text(p, source))
+                {
+                    includes.push(Dependency {
+                        import_name: path.trim_matches(['"', '<', '>']).to_string(),
+                        from_file: None,
+                    });
+                }
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+        }
+
+        includes
+    }
+}
+
+impl ParserTrait for CppParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let language: tree_sitter::Language = tree_sitter_cpp::LANGUAGE.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set C++ language")?
+            .context("Failed to parse C++ file")?;
+
+        let root = tree.root_node();
+        let mut result = ParseResult::new();
+        let scope = Scope {
+            public_members: true,
+            ..Scope::TOP_LEVEL
+        };
+        self.visit(root, content, file_path, scope, &mut result.symbols);
+        result.dependencies = self.process_includes(root, content);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParseResult> {
+        CppParser::new()?.parse(source, Path::new("test.cpp"))
+    }
+
+    #[test]
+    fn test_parse_classes_and_methods() -> Result<()> {
+        let result = parse(
+            r#"
+#ifndef SHAPES_H
+#define SHAPES_H
+namespace geo {
+/// A drawable shape
+class Shape : public Base {
+public:
+    virtual double area() const = 0;
+    void draw() { render(this); }
+private:
+    int id_;
+    void reset();
+};
+}
+#endif
+"#,
+        )?;
+        let names: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("geo", SymbolType::Class, true),
+                ("Shape", SymbolType::Class, true),
+                ("area", SymbolType::Method, true),
+                ("draw", SymbolType::Method, true),
+                ("reset", SymbolType::Method, false),
+            ]
+        );
+        let shape = &result.symbols[1];
+        assert_eq!(shape.signature.as_deref(), Some("class : public Base"));
+        assert_eq!(shape.docstring.as_deref(), Some("/// A drawable shape"));
+        assert_eq!(result.symbols[3].parent_id, Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_functions_and_out_of_line_methods() -> Result<()> {
+        let result = parse(
+            r#"
+#include "shape.hpp"
+#include <vector>
+
+namespace {
+int helper(int x) { return x; }
+}
+
+template <typename T>
+T clamp(T v, T lo, T hi) { return v; }
+
+double Shape::area() const { return 0; }
+static void local() {}
+
+enum class Color { Red, Green = 2 };
+"#,
+        )?;
+        let symbols: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("helper", SymbolType::Function, false),
+                ("clamp", SymbolType::Function, true),
+                ("area", SymbolType::Method, true),
+                ("local", SymbolType::Function, false),
+                ("Color", SymbolType::Enum, true),
+                ("Color::Red", SymbolType::Enum, true),
+                ("Color::Green", SymbolType::Enum, true),
+            ]
+        );
+        assert_eq!(
+            result.symbols[6].signature.as_deref(),
+            Some("variant of Color = 2")
+        );
+        let includes: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| d.import_name.as_str())
+            .collect();
+        assert_eq!(includes.len(), 2);
+        assert!(includes.contains(&"shape.hpp") && includes.contains(&"vector"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_qualified() {
+        assert_eq!(
+            split_qualified("ns::Map<std::string, int>::find"),
+            ["ns", "Map<std::string, int>", "find"]
+        );
+        assert_eq!(split_qualified("Foo::~Foo"), ["Foo", "~Foo"]);
+    }
+}
@@ -0,0 +1,472 @@
+use super::walk::{child_of_kind, collapsed_text, doc_comment, name_of, Scope};
+use super::{constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::path::Path;
+use tree_sitter::Node;
+
+pub struct CSharpParser;
+
+impl CSharpParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn extract_text(&self, node: Node, source: &str) -> Option<String> {
+        let start = node.start_byte();
+        let end = node.end_byte();
+        if end <= source.len() && start <= end {
+            source.get(start..end).map(|s| s.to_string())
+        } else {
+            None
+        }
+    }
+
+    fn modifiers(&self, node: Node, source: &str) -> Vec<String> {
+        let mut cursor = node.walk();
+        let modifiers = node
+            .children(&mut cursor)
+            .filter(|child| child.kind() == "modifier")
+            .filter_map(|child| self.extract_text(child, source))
+            .collect();
+        modifiers
+    }
+
+    fn is_exported(&self, modifiers: &[String], scope: Scope) -> bool {
+        scope.visible && (scope.public_members || modifiers.iter().any(|m| m == "public"))
+    }
+
+    fn symbol(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        name: String,
+        symbol_type: SymbolType,
+        signature: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            name: name.into(),
+            symbol_type,
+            signature,
+            docstring: doc_comment(node, node.prev_named_sibling(), source, Some("///")),
+            line_start: node.start_position().row + 1,
+            line_end: node.end_position().row + 1,
+            parent_id: None,
+            file_path: file_path.into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    fn visit(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "namespace_declaration" | "file_scoped_namespace_declaration" => {
+                    self.namespace(child, source, file_path, scope, symbols)
+                }
+                "class_declaration"
+                | "struct_declaration"
+                | "record_declaration"
+                | "interface_declaration" => {
+                    self.type_declaration(child, source, file_path, scope, symbols)
+                }
+                "enum_declaration" => self.enumeration(child, source, file_path, scope, symbols),
+                "method_declaration" | "constructor_declaration" | "destructor_declaration" => {
+                    self.method(child, source, file_path, scope, symbols)
+                }
+                "property_declaration" => self.property(child, source, file_path, scope, symbols),
+                "field_declaration" => self.fields(child, source, file_path, scope, symbols),
+                "declaration_list" | "preproc_if" | "preproc_else" | "preproc_elif" => {
+                    self.visit(child, source, file_path, scope, symbols)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `namespace A.B { }`, or `namespace A.B;` covering the rest of the file
+    fn namespace(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name,
+            SymbolType::Class,
+            Some("namespace".to_string()),
+        );
+        if node.kind() == "file_scoped_namespace_declaration" {
+            let mut root = node;
+            while let Some(parent) = root.parent() {
+                root = parent;
+            }
+            symbol.line_end = root.end_position().row + 1;
+        }
+        symbol.is_exported = scope.visible;
+        symbols.push(symbol);
+
+        if let Some(body) = node.child_by_field_name("body") {
+            self.visit(body, source, file_path, scope, symbols);
+        }
+    }
+
+    fn type_declaration(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let (symbol_type, keyword) = match node.kind() {
+            "interface_declaration" => (SymbolType::Interface, "interface"),
+            "struct_declaration" => (SymbolType::Class, "struct"),
+            "record_declaration" => (SymbolType::Class, "record"),
+            _ => (SymbolType::Class, "class"),
+        };
+        let bases =
+            child_of_kind(node, "base_list").and_then(|bases| collapsed_text(bases, source));
+        let signature = match bases {
+            Some(bases) => format!("{} {}", keyword, bases),
+            None => keyword.to_string(),
+        };
+
+        let exported = self.is_exported(&self.modifiers(node, source), scope);
+        let mut symbol = self.symbol(node, source, file_path, name, symbol_type, Some(signature));
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let inner = Scope {
+                public_members: symbol_type == SymbolType::Interface,
+                ..Scope::member_of(symbols.len() - 1, exported)
+            };
+            self.visit(body, source, file_path, inner, symbols);
+        }
+    }
+
+    fn enumeration(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let exported = self.is_exported(&self.modifiers(node, source), scope);
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name.clone(),
+            SymbolType::Enum,
+            None,
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+
+        // Members, as `Color.Red`, with their explicit values
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            if member.kind() != "enum_member_declaration" {
+                continue;
+            }
+            let Some(member_name) = name_of(member, source) else {
+                continue;
+            };
+            let value = member
+                .child_by_field_name("value")
+                .and_then(|v| self.extract_text(v, source));
+            let mut symbol = self.symbol(
+                member,
+                source,
+                file_path,
+                format!("{}.{}", name, member_name),
+                SymbolType::Enum,
+                constant_signature(Some(&format!("variant of {}", name)), value.as_deref()),
+            );
+            symbol.parent_id = scope.parent;
+            symbol.is_exported = exported;
+            symbols.push(symbol);
+        }
+    }
+
+    /// Methods, constructors and finalizers (`~Name`)
+    fn method(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let name = match node.kind() {
+            "destructor_declaration" => format!("~{}", name),
+            _ => name,
+        };
+        let signature = node
+            .child_by_field_name("parameters")
+            .and_then(|p| self.extract_text(p, source));
+        let mut symbol = self.symbol(node, source, file_path, name, SymbolType::Method, signature);
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = self.is_exported(&self.modifiers(node, source), scope);
+        symbols.push(symbol);
+    }
+
+    /// A property, as a field of its type
+    fn property(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let modifiers = self.modifiers(node, source);
+        let type_name = node
+            .child_by_field_name("type")
+            .and_then(|t| self.extract_text(t, source));
+        let symbol_type = match modifiers.iter().any(|m| m == "static") {
+            true => SymbolType::StaticField,
+            false => SymbolType::Field,
+        };
+        let mut symbol = self.symbol(node, source, file_path, name, symbol_type, type_name);
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = self.is_exported(&modifiers, scope);
+        symbols.push(symbol);
+    }
+
+    /// Fields, one symbol per declarator; `const` and `static` ones carry their value
+    fn fields(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let modifiers = self.modifiers(node, source);
+        let is_static = modifiers.iter().any(|m| m == "static" || m == "const");
+        let Some(declaration) = child_of_kind(node, "variable_declaration") else {
+            return;
+        };
+        let type_name = declaration
+            .child_by_field_name("type")
+            .and_then(|t| self.extract_text(t, source));
+
+        let mut cursor = declaration.walk();
+        for declarator in declaration.named_children(&mut cursor) {
+            if declarator.kind() != "variable_declarator" {
+                continue;
+            }
+            let Some(name) = name_of(declarator, source) else {
+                continue;
+            };
+            let (symbol_type, signature) = if is_static {
+                let mut value_cursor = declarator.walk();
+                let value = declarator
+                    .named_children(&mut value_cursor)
+                    .find(|child| child.kind() != "identifier")
+                    .and_then(|v| self.extract_text(v, source));
+                (
+                    SymbolType::StaticField,
+                    constant_signature(type_name.as_deref(), value.as_deref()),
+                )
+            } else {
+                (SymbolType::Field, type_name.clone())
+            };
+            let mut symbol = self.symbol(node, source, file_path, name, symbol_type, signature);
+            symbol.parent_id = scope.parent;
+            symbol.is_exported = self.is_exported(&modifiers, scope);
+            symbols.push(symbol);
+        }
+    }
+
+    /// `using A.B;`, `using static A.B;` and `using X = A.B;` all import `A.B`
+    fn process_usings(&self, tree_root: Node, source: &str) -> Vec<Dependency> {
+        let mut usings = Vec::new();
+        let mut stack = vec![tree_root];
+
+        while let Some(node) = stack.pop() {
+            if node.kind() == "using_directive" {
+                let target = node
+                    .child_by_field_name("name")
+                    .map_or(node.named_child(0), |_| node.named_child(1))
+                    .and_then(|n| self.extract_text(n, source));
+                if let Some(target) = target {
+                    usings.push(Dependency {
+                        import_name: target,
+                        from_file: None,
+                    });
+                }
+                continue;
+            }
+            // Usings only appear at the top and in namespace bodies
+            if matches!(
+                node.kind(),
+                "compilation_unit"
+                    | "namespace_declaration"
+                    | "file_scoped_namespace_declaration"
+                    | "declaration_list"
+            ) {
+                let mut cursor = node.walk();
+                stack.extend(node.named_children(&mut cursor));
+            }
+        }
+
+        usings
+    }
+}
+
+impl ParserTrait for CSharpParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let language: tree_sitter::Language = tree_sitter_c_sharp::LANGUAGE.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set C# language")?
+            .context("Failed to parse C# file")?;
+
+        let root = tree.root_node();
+        let mut result = ParseResult::new();
+        self.visit(
+            root,
+            content,
+            file_path,
+            Scope::TOP_LEVEL,
+            &mut result.symbols,
+        );
+        result.dependencies = self.process_usings(root, content);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParseResult> {
+        CSharpParser::new()?.parse(source, Path::new("Test.cs"))
+    }
+
+    #[test]
+    fn test_parse_types_and_members() -> Result<()> {
+        let result = parse(
+            r#"
+using System;
+using static System.Math;
+using Json = System.Text.Json;
+
+namespace Shop.Orders
+{
+    /// <summary>An order line</summary>
+    public class OrderLine : Entity, IPriced
+    {
+        public const int MaxQuantity = 99;
+        private readonly decimal price;
+        public int Quantity { get; set; }
+
+        public OrderLine(decimal price) { this.price = price; }
+
+        public decimal Total() => price * Quantity;
+        private void Check() { }
+    }
+
+    public interface IPriced
+    {
+        decimal Total();
+    }
+
+    internal enum Status { Open, Paid = 2 }
+}
+"#,
+        )?;
+        let symbols: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("Shop.Orders", SymbolType::Class, true),
+                ("OrderLine", SymbolType::Class, true),
+                ("MaxQuantity", SymbolType::StaticField, true),
+                ("price", SymbolType::Field, false),
+                ("Quantity", SymbolType::Field, true),
+                ("OrderLine", SymbolType::Method, true),
+                ("Total", SymbolType::Method, true),
+                ("Check", SymbolType::Method, false),
+                ("IPriced", SymbolType::Interface, true),
+                ("Total", SymbolType::Method, true),
+                ("Status", SymbolType::Enum, false),
+                ("Status.Open", SymbolType::Enum, false),
+                ("Status.Paid", SymbolType::Enum, false),
+            ]
+        );
+        let line = &result.symbols[1];
+        assert_eq!(line.signature.as_deref(), Some("class : Entity, IPriced"));
+        assert_eq!(
+            line.docstring.as_deref(),
+            Some("/// <summary>An order line</summary>")
+        );
+        assert_eq!(result.symbols[2].signature.as_deref(), Some("int = 99"));
+        assert_eq!(result.symbols[6].parent_id, Some(1));
+
+        let usings: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| d.import_name.as_str())
+            .collect();
+        assert_eq!(usings.len(), 3);
+        assert!(usings.contains(&"System.Math") && usings.contains(&"System.Text.Json"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scoped_namespace() -> Result<()> {
+        let result = parse("namespace Shop;\n\npublic record Money(decimal Amount);\n")?;
+        assert_eq!(result.symbols.len(), 2);
+        assert_eq!(result.symbols[0].line_end, 4);
+        assert_eq!(result.symbols[1].name, "Money");
+        assert_eq!(result.symbols[1].signature.as_deref(), Some("record"));
+
+        Ok(())
+    }
+}
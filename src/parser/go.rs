@@ -1,9 +1,10 @@
-use super::{ParseResult, Parser as ParserTrait};
-use crate::models::{Dependency, Symbol, SymbolType};
+use super::{cached_query, constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Language, Symbol, SymbolType};
+use crate::schema;
 use anyhow::{Context, Result};
 use std::path::Path;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tree_sitter::{Node, QueryCursor};
 
 pub struct GoParser;
 
@@ -53,11 +54,11 @@ impl GoParser {
         let language: tree_sitter::Language = tree_sitter_go::LANGUAGE.into();
 
         // Query for function declarations
-        let func_query = Query::new(&language, r#"(function_declaration) @func.def"#)
+        let func_query = cached_query(&language, r#"(function_declaration) @func.def"#)
             .context("Failed to create Go function query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&func_query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(func_query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             for capture in match_.captures {
@@ -88,26 +89,28 @@ impl GoParser {
                     let docstring = self.extract_comment(node, source);
                     let exported = is_go_exported(&name);
                     symbols.push(Symbol {
-                        name,
+                        name: name.into(),
                         symbol_type: SymbolType::Function,
                         signature: func_params,
                         docstring,
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: exported,
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
             }
         }
 
         // Query for method declarations
-        let method_query = Query::new(&language, r#"(method_declaration) @method.def"#)
+        let method_query = cached_query(&language, r#"(method_declaration) @method.def"#)
             .context("Failed to create Go method query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&method_query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(method_query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             for capture in match_.captures {
@@ -138,15 +141,17 @@ impl GoParser {
                     let docstring = self.extract_comment(node, source);
                     let exported = is_go_exported(&name);
                     symbols.push(Symbol {
-                        name,
+                        name: name.into(),
                         symbol_type: SymbolType::Method,
                         signature: method_params,
                         docstring,
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: exported,
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
             }
@@ -165,7 +170,7 @@ impl GoParser {
         let language: tree_sitter::Language = tree_sitter_go::LANGUAGE.into();
 
         // Query for type declarations (structs, interfaces, etc.)
-        let type_query = Query::new(
+        let type_query = cached_query(
             &language,
             r#"
             (type_declaration
@@ -176,7 +181,7 @@ impl GoParser {
         .context("Failed to create Go type query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&type_query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(type_query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let mut type_name = None;
@@ -227,15 +232,17 @@ impl GoParser {
 
                 let exported = is_go_exported(&name);
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Class,
                     signature: Some(type_kind.to_string()),
                     docstring,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: exported,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -252,7 +259,7 @@ impl GoParser {
         let mut symbols = Vec::new();
         let language: tree_sitter::Language = tree_sitter_go::LANGUAGE.into();
 
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (const_declaration
@@ -265,13 +272,14 @@ impl GoParser {
         .context("Failed to create Go const query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
 
             let mut const_name = None;
             let mut const_type = None;
+            let mut const_value = None;
             let mut const_node = None;
 
             for capture in captures {
@@ -291,6 +299,9 @@ impl GoParser {
                     Some("const.type") => {
                         const_type = self.extract_text(capture.node, source);
                     }
+                    Some("const.value") => {
+                        const_value = self.extract_text(capture.node, source);
+                    }
                     Some("const.def") => {
                         const_node = Some(capture.node);
                     }
@@ -305,15 +316,17 @@ impl GoParser {
                 let exported = is_go_exported(&name);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::StaticField,
-                    signature: const_type,
+                    signature: constant_signature(const_type.as_deref(), const_value.as_deref()),
                     docstring,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: exported,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -424,14 +437,9 @@ impl GoParser {
 
 impl ParserTrait for GoParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
-        let mut parser = Parser::new();
         let language: tree_sitter::Language = tree_sitter_go::LANGUAGE.into();
-        parser
-            .set_language(&language)
-            .context("Failed to set Go language")?;
-
-        let tree = parser
-            .parse(content, None)
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Go language")?
             .context("Failed to parse Go file")?;
 
         let root = tree.root_node();
@@ -443,7 +451,7 @@ impl ParserTrait for GoParser {
 
         types.extend(functions);
         types.extend(consts);
-        result.symbols = types;
+        result.symbols = schema::with_field_symbols(root, content, Language::Go, types);
         result.dependencies = self.process_imports(root, content)?;
 
         Ok(result)
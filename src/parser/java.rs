@@ -1,9 +1,10 @@
-use super::{ParseResult, Parser as ParserTrait};
-use crate::models::{Dependency, Symbol, SymbolType};
+use super::{cached_query, constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Language, Symbol, SymbolType};
+use crate::schema;
 use anyhow::{Context, Result};
 use std::path::Path;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tree_sitter::{Node, QueryCursor};
 
 pub struct JavaParser;
 
@@ -105,7 +106,7 @@ impl JavaParser {
         let mut symbols = Vec::new();
 
         let language = tree_sitter_java::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (class_declaration
@@ -115,7 +116,7 @@ impl JavaParser {
         .context("Failed to create Java class query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -151,15 +152,17 @@ impl JavaParser {
                 let parent_id = self.find_parent_class(node, &symbols);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Class,
                     signature: None,
                     docstring,
                     line_start,
                     line_end,
                     parent_id,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: has_public_modifier(node, source),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -176,7 +179,7 @@ impl JavaParser {
         let mut symbols = Vec::new();
 
         let language = tree_sitter_java::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (interface_declaration
@@ -186,7 +189,7 @@ impl JavaParser {
         .context("Failed to create Java interface query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -221,15 +224,17 @@ impl JavaParser {
                 let line_end = node.end_position().row + 1;
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Class,
                     signature: None,
                     docstring,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: has_public_modifier(node, source),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -245,7 +250,7 @@ impl JavaParser {
         symbols: &mut Vec<Symbol>,
     ) -> Result<()> {
         let language = tree_sitter_java::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (method_declaration
@@ -256,7 +261,7 @@ impl JavaParser {
         .context("Failed to create Java method query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -302,7 +307,7 @@ impl JavaParser {
                 };
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: if is_method {
                         SymbolType::Method
                     } else {
@@ -313,8 +318,10 @@ impl JavaParser {
                     line_start,
                     line_end,
                     parent_id,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: has_public_modifier(node, source),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -331,7 +338,7 @@ impl JavaParser {
         let mut symbols = Vec::new();
 
         let language = tree_sitter_java::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (enum_declaration
@@ -341,7 +348,7 @@ impl JavaParser {
         .context("Failed to create Java enum query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -375,17 +382,57 @@ impl JavaParser {
                 let line_start = node.start_position().row + 1;
                 let line_end = node.end_position().row + 1;
 
+                let is_exported = has_public_modifier(node, source);
                 symbols.push(Symbol {
-                    name,
+                    name: name.clone().into(),
                     symbol_type: SymbolType::Enum,
                     signature: None,
                     docstring,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
-                    is_exported: has_public_modifier(node, source),
+                    file_path: file_path.into(),
+                    is_exported,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
+
+                // Constants, as `Color.RED`; constructor arguments stand in for the value
+                let Some(body) = node.child_by_field_name("body") else {
+                    continue;
+                };
+                let mut body_cursor = body.walk();
+                for constant in body.named_children(&mut body_cursor) {
+                    if constant.kind() != "enum_constant" {
+                        continue;
+                    }
+                    let Some(constant_name) = constant
+                        .child_by_field_name("name")
+                        .and_then(|n| self.extract_text(n, source))
+                    else {
+                        continue;
+                    };
+                    let arguments = constant
+                        .child_by_field_name("arguments")
+                        .and_then(|a| self.extract_text(a, source));
+
+                    symbols.push(Symbol {
+                        name: format!("{}.{}", name, constant_name).into(),
+                        symbol_type: SymbolType::Enum,
+                        signature: constant_signature(
+                            Some(&format!("variant of {}", name)),
+                            arguments.as_deref(),
+                        ),
+                        docstring: self.extract_javadoc(constant, source),
+                        line_start: constant.start_position().row + 1,
+                        line_end: constant.end_position().row + 1,
+                        parent_id: None,
+                        file_path: file_path.into(),
+                        is_exported,
+                        byte_range: None,
+                        annotations: Vec::new(),
+                    });
+                }
             }
         }
 
@@ -401,26 +448,28 @@ impl JavaParser {
         let mut symbols = Vec::new();
 
         let language = tree_sitter_java::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (field_declaration
                 (modifiers) @field.mods
                 type: (_) @field.type
                 declarator: (variable_declarator
-                    name: (identifier) @field.name)) @field.def
+                    name: (identifier) @field.name
+                    value: (_)? @field.value)) @field.def
             "#,
         )
         .context("Failed to create Java static field query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
 
             let mut field_name = None;
             let mut field_type = None;
+            let mut field_value = None;
             let mut field_mods = None;
             let mut field_node = None;
 
@@ -441,6 +490,9 @@ impl JavaParser {
                     Some("field.type") => {
                         field_type = self.extract_text(capture.node, source);
                     }
+                    Some("field.value") => {
+                        field_value = self.extract_text(capture.node, source);
+                    }
                     Some("field.mods") => {
                         field_mods = self.extract_text(capture.node, source);
                     }
@@ -459,15 +511,20 @@ impl JavaParser {
                         let line_end = node.end_position().row + 1;
 
                         symbols.push(Symbol {
-                            name,
+                            name: name.into(),
                             symbol_type: SymbolType::StaticField,
-                            signature: field_type,
+                            signature: constant_signature(
+                                field_type.as_deref(),
+                                field_value.as_deref(),
+                            ),
                             docstring,
                             line_start,
                             line_end,
                             parent_id: None,
-                            file_path: file_path.to_path_buf(),
+                            file_path: file_path.into(),
                             is_exported: has_public_modifier(node, source),
+                            byte_range: None,
+                            annotations: Vec::new(),
                         });
                     }
                 }
@@ -485,7 +542,7 @@ impl JavaParser {
         symbols: &mut Vec<Symbol>,
     ) -> Result<()> {
         let language = tree_sitter_java::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (constructor_declaration
@@ -496,7 +553,7 @@ impl JavaParser {
         .context("Failed to create Java constructor query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -537,15 +594,17 @@ impl JavaParser {
                 let parent_id = self.find_parent_class(node, symbols);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Method,
                     signature,
                     docstring,
                     line_start,
                     line_end,
                     parent_id,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: has_public_modifier(node, source),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -557,7 +616,7 @@ impl JavaParser {
         let mut imports = Vec::new();
 
         let language = tree_sitter_java::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (import_declaration
@@ -567,7 +626,7 @@ impl JavaParser {
         .context("Failed to create Java import query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -599,14 +658,9 @@ impl JavaParser {
 
 impl ParserTrait for JavaParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
-        let mut parser = Parser::new();
         let language = tree_sitter_java::LANGUAGE.into();
-        parser
-            .set_language(&language)
-            .context("Failed to set Java language")?;
-
-        let tree = parser
-            .parse(content, None)
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Java language")?
             .context("Failed to parse Java file")?;
 
         let root = tree.root_node();
@@ -626,6 +680,7 @@ impl ParserTrait for JavaParser {
 
         self.process_methods(root, content, file_path, &mut result.symbols)?;
         self.process_constructors(root, content, file_path, &mut result.symbols)?;
+        result.symbols = schema::with_field_symbols(root, content, Language::Java, result.symbols);
 
         result.dependencies = self.process_imports(root, content)?;
 
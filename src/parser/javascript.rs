@@ -1,9 +1,9 @@
-use super::{ParseResult, Parser};
+use super::{cached_query, pooled_parse, ParseResult, Parser};
 use crate::models::{Dependency, Symbol, SymbolType};
 use anyhow::{Context, Result};
 use std::path::Path;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Node, Parser as TSParser, Query, QueryCursor};
+use tree_sitter::{Node, Query, QueryCursor};
 
 pub struct JavaScriptParser;
 
@@ -31,15 +31,6 @@ impl JavaScriptParser {
         Ok(Self)
     }
 
-    fn create_parser() -> Result<TSParser> {
-        let mut parser = TSParser::new();
-        let language = tree_sitter_javascript::LANGUAGE.into();
-        parser
-            .set_language(&language)
-            .context("Failed to set JavaScript language")?;
-        Ok(parser)
-    }
-
     fn extract_functions(
         &self,
         tree: &tree_sitter::Tree,
@@ -47,7 +38,7 @@ impl JavaScriptParser {
         file_path: &Path,
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_javascript::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             [
@@ -66,7 +57,7 @@ impl JavaScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -75,21 +66,23 @@ impl JavaScriptParser {
                 continue;
             }
 
-            if let Some(name) = self.extract_function_name(captures, source, &query)? {
-                if let Some(node) = self.extract_function_node(captures, &query)? {
+            if let Some(name) = self.extract_function_name(captures, source, query)? {
+                if let Some(node) = self.extract_function_node(captures, query)? {
                     let signature = self.extract_signature(node, source)?;
                     let (line_start, line_end) = self.get_line_range(node);
 
                     symbols.push(Symbol {
-                        name,
+                        name: name.into(),
                         symbol_type: SymbolType::Function,
                         signature: Some(signature),
                         docstring: None,
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: is_exported(node),
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
             }
@@ -246,7 +239,7 @@ impl JavaScriptParser {
         file_path: &Path,
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_javascript::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (class_declaration
@@ -258,7 +251,7 @@ impl JavaScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -293,15 +286,17 @@ impl JavaScriptParser {
                 let (line_start, line_end) = self.get_line_range(def_cap.node);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Class,
                     signature: None,
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: is_exported(def_cap.node),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -317,7 +312,7 @@ impl JavaScriptParser {
         class_symbols: &[Symbol],
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_javascript::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (class_declaration
@@ -331,7 +326,7 @@ impl JavaScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -369,15 +364,17 @@ impl JavaScriptParser {
                 let parent_id = self.find_parent_class(line_start, class_symbols);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Method,
                     signature: Some(signature),
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: false,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -391,7 +388,7 @@ impl JavaScriptParser {
         source: &str,
     ) -> Result<Vec<Dependency>> {
         let language = tree_sitter_javascript::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             [
@@ -408,7 +405,7 @@ impl JavaScriptParser {
         let root_node = tree.root_node();
         let mut dependencies = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -486,9 +483,9 @@ impl JavaScriptParser {
 
 impl Parser for JavaScriptParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
-        let mut parser = Self::create_parser()?;
-        let tree = parser
-            .parse(content, None)
+        let language = tree_sitter_javascript::LANGUAGE.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set JavaScript language")?
             .context("Failed to parse JavaScript content")?;
 
         let classes = self.extract_classes(&tree, content, file_path)?;
@@ -0,0 +1,656 @@
+use super::walk::{child_of_kind, collapsed_text, doc_comment, name_of, Scope};
+use super::{constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::path::Path;
+use tree_sitter::{Node, Tree};
+
+pub struct KotlinParser;
+
+impl KotlinParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn extract_text(&self, node: Node, source: &str) -> Option<String> {
+        let start = node.start_byte();
+        let end = node.end_byte();
+        if end <= source.len() && start <= end {
+            source.get(start..end).map(|s| s.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Modifier keywords (`data`, `private`, `override`, ...), annotations left out
+    fn modifiers(&self, node: Node, source: &str) -> Vec<(String, &'static str)> {
+        let Some(modifiers) = child_of_kind(node, "modifiers") else {
+            return Vec::new();
+        };
+        let mut cursor = modifiers.walk();
+        let list = modifiers
+            .named_children(&mut cursor)
+            .filter(|m| m.kind() != "annotation")
+            .filter_map(|m| Some((self.extract_text(m, source)?, m.kind())))
+            .collect();
+        list
+    }
+
+    /// Declarations are public unless marked `private`, `protected` or `internal`
+    fn is_exported(&self, modifiers: &[(String, &str)], scope: Scope) -> bool {
+        scope.visible
+            && !modifiers
+                .iter()
+                .any(|(text, kind)| *kind == "visibility_modifier" && text != "public")
+    }
+
+    fn symbol(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        name: String,
+        symbol_type: SymbolType,
+        signature: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            name: name.into(),
+            symbol_type,
+            signature,
+            docstring: doc_comment(node, node.prev_named_sibling(), source, None),
+            line_start: node.start_position().row + 1,
+            line_end: node.end_position().row + 1,
+            parent_id: None,
+            file_path: file_path.into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    fn visit(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                // Declarations the parser could not fit anywhere still belong to this scope
+                "ERROR" => self.visit(child, source, file_path, scope, symbols),
+                "class_declaration" => {
+                    self.class_declaration(child, source, file_path, scope, symbols)
+                }
+                "object_declaration" | "companion_object" => {
+                    self.object(child, source, file_path, scope, symbols)
+                }
+                "function_declaration" => self.function(child, source, file_path, scope, symbols),
+                "secondary_constructor" => {
+                    let signature = child_of_kind(child, "function_value_parameters")
+                        .and_then(|p| self.parameters(p, source));
+                    let exported = self.is_exported(&self.modifiers(child, source), scope);
+                    let mut symbol = self.symbol(
+                        child,
+                        source,
+                        file_path,
+                        "constructor".to_string(),
+                        SymbolType::Method,
+                        signature,
+                    );
+                    symbol.parent_id = scope.parent;
+                    symbol.is_exported = exported;
+                    symbols.push(symbol);
+                }
+                "property_declaration" => self.property(child, source, file_path, scope, symbols),
+                "type_alias" => {
+                    let Some(name) = child
+                        .child_by_field_name("type")
+                        .and_then(|n| self.extract_text(n, source))
+                    else {
+                        continue;
+                    };
+                    let mut cursor = child.walk();
+                    let aliased = child
+                        .named_children(&mut cursor)
+                        .skip_while(|c| c.kind() != "identifier")
+                        .nth(1)
+                        .and_then(|t| collapsed_text(t, source));
+                    let exported = self.is_exported(&self.modifiers(child, source), scope);
+                    let mut symbol = self.symbol(
+                        child,
+                        source,
+                        file_path,
+                        name,
+                        SymbolType::TypeAlias,
+                        aliased,
+                    );
+                    symbol.parent_id = scope.parent;
+                    symbol.is_exported = exported;
+                    symbols.push(symbol);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Classes, data/sealed/abstract classes, interfaces, annotation and enum classes
+    fn class_declaration(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let modifiers = self.modifiers(node, source);
+        let keyword = if child_of_kind(node, "interface").is_some() {
+            "interface"
+        } else {
+            "class"
+        };
+        let symbol_type = match keyword {
+            "interface" => SymbolType::Interface,
+            _ if modifiers.iter().any(|(m, _)| m == "enum") => SymbolType::Enum,
+            _ => SymbolType::Class,
+        };
+
+        // `data class(val id: String) : Priced, Base()`
+        let mut signature: Vec<String> = modifiers
+            .iter()
+            .filter(|(_, kind)| *kind != "visibility_modifier")
+            .map(|(text, _)| text.clone())
+            .collect();
+        signature.push(keyword.to_string());
+        let mut signature = signature.join(" ");
+        let parameters = child_of_kind(node, "primary_constructor")
+            .and_then(|c| child_of_kind(c, "class_parameters"));
+        if let Some(parameters) = parameters.and_then(|p| self.parameters(p, source)) {
+            signature.push_str(&parameters);
+        }
+        if let Some(supertypes) =
+            child_of_kind(node, "delegation_specifiers").and_then(|d| collapsed_text(d, source))
+        {
+            signature.push_str(&format!(" : {}", supertypes));
+        }
+
+        let exported = self.is_exported(&modifiers, scope);
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name.clone(),
+            symbol_type,
+            Some(signature),
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+        let inner = Scope::member_of(symbols.len() - 1, exported);
+
+        // `val`/`var` constructor parameters are properties
+        if let Some(parameters) = parameters {
+            let mut cursor = parameters.walk();
+            for parameter in parameters.named_children(&mut cursor) {
+                if parameter.kind() != "class_parameter"
+                    || (child_of_kind(parameter, "val").is_none()
+                        && child_of_kind(parameter, "var").is_none())
+                {
+                    continue;
+                }
+                let Some(property) = child_of_kind(parameter, "identifier")
+                    .and_then(|n| self.extract_text(n, source))
+                else {
+                    continue;
+                };
+                let mut cursor = parameter.walk();
+                let type_name = parameter
+                    .named_children(&mut cursor)
+                    .skip_while(|c| c.kind() != "identifier")
+                    .nth(1)
+                    .and_then(|t| collapsed_text(t, source));
+                let exported = self.is_exported(&self.modifiers(parameter, source), inner);
+                let mut symbol = self.symbol(
+                    parameter,
+                    source,
+                    file_path,
+                    property,
+                    SymbolType::Field,
+                    type_name,
+                );
+                symbol.parent_id = inner.parent;
+                symbol.is_exported = exported;
+                symbols.push(symbol);
+            }
+        }
+
+        if let Some(body) = child_of_kind(node, "class_body") {
+            self.visit(body, source, file_path, inner, symbols);
+        }
+        if let Some(body) = child_of_kind(node, "enum_class_body") {
+            // Entries, as `Status.OPEN`, then the enum's own members
+            let mut cursor = body.walk();
+            for entry in body.named_children(&mut cursor) {
+                if entry.kind() != "enum_entry" {
+                    continue;
+                }
+                let Some(entry_name) =
+                    child_of_kind(entry, "identifier").and_then(|n| self.extract_text(n, source))
+                else {
+                    continue;
+                };
+                let mut symbol = self.symbol(
+                    entry,
+                    source,
+                    file_path,
+                    format!("{}.{}", name, entry_name),
+                    SymbolType::Enum,
+                    constant_signature(Some(&format!("variant of {}", name)), None),
+                );
+                symbol.parent_id = scope.parent;
+                symbol.is_exported = exported;
+                symbols.push(symbol);
+            }
+            self.visit(body, source, file_path, inner, symbols);
+        }
+    }
+
+    /// `object Registry` and `companion object` (named `Companion` unless given a name)
+    fn object(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let companion = node.kind() == "companion_object";
+        let Some(name) =
+            name_of(node, source).or_else(|| companion.then(|| "Companion".to_string()))
+        else {
+            return;
+        };
+        let modifiers = self.modifiers(node, source);
+        let mut signature = match companion {
+            true => "companion object".to_string(),
+            false => "object".to_string(),
+        };
+        if let Some(supertypes) =
+            child_of_kind(node, "delegation_specifiers").and_then(|d| collapsed_text(d, source))
+        {
+            signature.push_str(&format!(" : {}", supertypes));
+        }
+
+        let exported = self.is_exported(&modifiers, scope);
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name,
+            SymbolType::Class,
+            Some(signature),
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+
+        if let Some(body) = child_of_kind(node, "class_body") {
+            let inner = Scope {
+                static_members: true,
+                ..Scope::member_of(symbols.len() - 1, exported)
+            };
+            self.visit(body, source, file_path, inner, symbols);
+        }
+    }
+
+    /// `(id: String, total: Double = 0.0)`, one parameter after another on one line
+    fn parameters(&self, node: Node, source: &str) -> Option<String> {
+        let mut cursor = node.walk();
+        let parameters = node
+            .named_children(&mut cursor)
+            .filter(|p| !p.kind().ends_with("comment"))
+            .collect::<Vec<_>>();
+        // `vararg` and a default value are siblings of their parameter, joined back on here
+        let mut list: Vec<String> = Vec::new();
+        let mut modifiers: Option<String> = None;
+        for parameter in parameters {
+            let text = collapsed_text(parameter, source)?;
+            match parameter.kind() {
+                "parameter_modifiers" => modifiers = Some(text),
+                "parameter" | "class_parameter" => match modifiers.take() {
+                    Some(modifiers) => list.push(format!("{} {}", modifiers, text)),
+                    None => list.push(text),
+                },
+                _ => match list.last_mut() {
+                    Some(last) => {
+                        last.push_str(" = ");
+                        last.push_str(&text);
+                    }
+                    None => list.push(text),
+                },
+            }
+        }
+        Some(format!("({})", list.join(", ")))
+    }
+
+    /// Top-level functions and extension functions, or methods inside a class or object
+    fn function(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+
+        // `<T> String.(times: Int = 1): String`: the receiver type comes before the name,
+        // the return type after the parameters
+        let mut type_parameters = None;
+        let mut receiver = None;
+        let mut parameters = None;
+        let mut return_type = None;
+        let mut seen_name = false;
+        let mut cursor = node.walk();
+        for (i, child) in node.children(&mut cursor).enumerate() {
+            if !child.is_named() || child.kind().ends_with("comment") {
+                continue;
+            }
+            if node.field_name_for_child(i as u32) == Some("name") {
+                seen_name = true;
+                continue;
+            }
+            match child.kind() {
+                "modifiers" | "function_body" | "type_constraints" => {}
+                "type_parameters" => type_parameters = self.extract_text(child, source),
+                "function_value_parameters" => parameters = self.parameters(child, source),
+                _ if !seen_name => receiver = collapsed_text(child, source),
+                _ if parameters.is_some() => return_type = collapsed_text(child, source),
+                _ => {}
+            }
+        }
+        let mut signature = String::new();
+        if let Some(type_parameters) = type_parameters {
+            signature.push_str(&type_parameters);
+            signature.push(' ');
+        }
+        if let Some(receiver) = receiver {
+            signature.push_str(&receiver);
+            signature.push('.');
+        }
+        signature.push_str(parameters.as_deref().unwrap_or("()"));
+        if let Some(return_type) = return_type {
+            signature.push_str(&format!(": {}", return_type));
+        }
+
+        let symbol_type = match scope.parent {
+            Some(_) => SymbolType::Method,
+            None => SymbolType::Function,
+        };
+        let exported = self.is_exported(&self.modifiers(node, source), scope);
+        let mut symbol = self.symbol(node, source, file_path, name, symbol_type, Some(signature));
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+    }
+
+    /// `val`/`var` properties: static at the top level, in objects or when `const`, with
+    /// their value; otherwise fields of their declared type
+    fn property(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        // Destructuring declarations (`val (a, b) = pair`) have no single name
+        let Some(variable) = child_of_kind(node, "variable_declaration") else {
+            return;
+        };
+        let Some(name) =
+            child_of_kind(variable, "identifier").and_then(|n| self.extract_text(n, source))
+        else {
+            return;
+        };
+        let mut cursor = variable.walk();
+        let type_name = variable
+            .named_children(&mut cursor)
+            .skip_while(|c| c.kind() != "identifier")
+            .nth(1)
+            .and_then(|t| collapsed_text(t, source));
+
+        let modifiers = self.modifiers(node, source);
+        let is_static = scope.static_members || modifiers.iter().any(|(m, _)| m == "const");
+        let (symbol_type, signature) = if is_static {
+            let mut cursor = node.walk();
+            let value = node
+                .named_children(&mut cursor)
+                .skip_while(|c| c.kind() != "variable_declaration")
+                .nth(1)
+                .filter(|v| {
+                    !matches!(
+                        v.kind(),
+                        "getter" | "setter" | "property_delegate" | "type_constraints"
+                    )
+                })
+                .and_then(|v| self.extract_text(v, source));
+            (
+                SymbolType::StaticField,
+                constant_signature(type_name.as_deref(), value.as_deref()),
+            )
+        } else {
+            (SymbolType::Field, type_name)
+        };
+
+        let mut symbol = self.symbol(node, source, file_path, name, symbol_type, signature);
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = self.is_exported(&modifiers, scope);
+        symbols.push(symbol);
+    }
+
+    /// `import a.b.C` imports `a.b.C`; `import a.b.*` imports `a.b`, as for Java
+    fn process_imports(&self, tree_root: Node, source: &str) -> Vec<Dependency> {
+        let mut imports = Vec::new();
+        let mut cursor = tree_root.walk();
+        for node in tree_root.named_children(&mut cursor) {
+            if node.kind() != "import" {
+                continue;
+            }
+            let Some(path) = node
+                .named_child(0)
+                .and_then(|n| self.extract_text(n, source))
+            else {
+                continue;
+            };
+            imports.push(Dependency {
+                import_name: path,
+                from_file: None,
+            });
+        }
+        imports
+    }
+}
+
+impl ParserTrait for KotlinParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let language: tree_sitter::Language = tree_sitter_kotlin_ng::LANGUAGE.into();
+        let parse = |source: &str| -> Result<Tree> {
+            pooled_parse(&language, source)
+                .context("Failed to set Kotlin language")?
+                .context("Failed to parse Kotlin file")
+        };
+        let mut tree = parse(content)?;
+        // The grammar wants a `;` or line break between a member and a `}` on the same line
+        // (`object R { fun f() = 1 }`) and can drop the rest of the file without one. Putting
+        // `;` in place of the space before each `}` keeps every byte offset, so the symbols
+        // still read their text from `content`.
+        if tree.root_node().has_error() {
+            let retry = parse(&content.replace(" }", ";}"))?;
+            if !retry.root_node().has_error() {
+                tree = retry;
+            }
+        }
+
+        let root = tree.root_node();
+        let mut result = ParseResult::new();
+        let scope = Scope {
+            static_members: true,
+            ..Scope::TOP_LEVEL
+        };
+        self.visit(root, content, file_path, scope, &mut result.symbols);
+        result.dependencies = self.process_imports(root, content);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParseResult> {
+        KotlinParser::new()?.parse(source, Path::new("Order.kt"))
+    }
+
+    #[test]
+    fn test_parse_classes_and_members() -> Result<()> {
+        let result = parse(
+            r#"package com.example.shop
+
+import com.example.shop.model.Customer
+import kotlinx.coroutines.*
+
+/** An order */
+data class Order(val id: String, private var total: Double = 0.0) : Priced {
+    private lateinit var note: String
+    constructor(id: String) : this(id, 0.0)
+    override fun price(): Double = total
+    companion object {
+        const val MAX = 5
+        fun create(): Order = Order("x")
+    }
+}
+
+sealed interface Priced {
+    fun price(): Double
+}
+
+internal object Registry {
+    fun all() = listOf<Order>()
+}
+
+enum class Status {
+    OPEN,
+    CLOSED;
+
+    fun isOpen() = this == OPEN
+}
+
+typealias Orders = List<Order>
+fun <T> String.shout(times: Int = 1): String = uppercase().repeat(times)
+val VERSION = "1.0"
+"#,
+        )?;
+        let symbols: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("Order", SymbolType::Class, true),
+                ("id", SymbolType::Field, true),
+                ("total", SymbolType::Field, false),
+                ("note", SymbolType::Field, false),
+                ("constructor", SymbolType::Method, true),
+                ("price", SymbolType::Method, true),
+                ("Companion", SymbolType::Class, true),
+                ("MAX", SymbolType::StaticField, true),
+                ("create", SymbolType::Method, true),
+                ("Priced", SymbolType::Interface, true),
+                ("price", SymbolType::Method, true),
+                ("Registry", SymbolType::Class, false),
+                ("all", SymbolType::Method, false),
+                ("Status", SymbolType::Enum, true),
+                ("Status.OPEN", SymbolType::Enum, true),
+                ("Status.CLOSED", SymbolType::Enum, true),
+                ("isOpen", SymbolType::Method, true),
+                ("Orders", SymbolType::TypeAlias, true),
+                ("shout", SymbolType::Function, true),
+                ("VERSION", SymbolType::StaticField, true),
+            ]
+        );
+        let order = &result.symbols[0];
+        assert_eq!(
+            order.signature.as_deref(),
+            Some("data class(val id: String, private var total: Double = 0.0) : Priced")
+        );
+        assert_eq!(order.docstring.as_deref(), Some("/** An order */"));
+        assert_eq!(result.symbols[1].parent_id, Some(0));
+        assert_eq!(result.symbols[7].signature.as_deref(), Some("5"));
+        assert_eq!(result.symbols[7].parent_id, Some(6));
+        assert_eq!(result.symbols[17].signature.as_deref(), Some("List<Order>"));
+        assert_eq!(
+            result.symbols[18].signature.as_deref(),
+            Some("<T> String.(times: Int = 1): String")
+        );
+        assert_eq!(result.symbols[19].signature.as_deref(), Some("\"1.0\""));
+
+        let imports: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| d.import_name.as_str())
+            .collect();
+        assert_eq!(
+            imports,
+            ["com.example.shop.model.Customer", "kotlinx.coroutines"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_one_line_bodies() -> Result<()> {
+        let names = |result: &ParseResult| -> Vec<(String, Option<usize>)> {
+            result
+                .symbols
+                .iter()
+                .map(|s| (s.name.to_string(), s.parent_id))
+                .collect()
+        };
+        let result =
+            parse("object R { fun f(): U? = null }\ninterface Repo { fun save(u: User) }\n")?;
+        assert_eq!(
+            names(&result),
+            [
+                ("R".to_string(), None),
+                ("f".to_string(), Some(0)),
+                ("Repo".to_string(), None),
+                ("save".to_string(), Some(2)),
+            ]
+        );
+        assert_eq!(result.symbols[1].signature.as_deref(), Some("(): U?"));
+
+        // `;}` breaks the template, so the first tree is kept with `x` in an ERROR node
+        let result = parse("object Q { val x = 1 }\nval s = \"${ 1 }\"\n")?;
+        assert_eq!(
+            names(&result),
+            [
+                ("Q".to_string(), None),
+                ("x".to_string(), Some(0)),
+                ("s".to_string(), None),
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,478 @@
+use super::walk::{child_of_kind, collapsed_text};
+use super::{constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use tree_sitter::Node;
+
+pub struct LuaParser;
+
+/// What the chunk being walked has declared so far
+struct Module<'a> {
+    /// Table variables (`M`, `M.util`) to the index of their symbol
+    tables: HashMap<String, usize>,
+    /// The local the chunk ends with `return M` of
+    returned: Option<&'a str>,
+}
+
+impl LuaParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn extract_text<'a>(&self, node: Node, source: &'a str) -> Option<&'a str> {
+        let start = node.start_byte();
+        let end = node.end_byte();
+        if end <= source.len() && start <= end {
+            source.get(start..end)
+        } else {
+            None
+        }
+    }
+
+    /// The LDoc block right above a declaration: `---` followed by any number of `--` lines
+    fn extract_doc_comment(&self, node: Node, source: &str) -> Option<String> {
+        let mut lines: Vec<&str> = Vec::new();
+        let mut next_row = node.start_position().row;
+        let mut prev = node.prev_named_sibling();
+        while let Some(comment) = prev {
+            if comment.kind() != "comment" || comment.end_position().row + 1 < next_row {
+                break;
+            }
+            let text = self.extract_text(comment, source)?;
+            if text.starts_with("--[") {
+                break;
+            }
+            lines.push(text);
+            next_row = comment.start_position().row;
+            prev = comment.prev_named_sibling();
+        }
+        // Plain `--` comments above a function are not documentation
+        while lines.last().is_some_and(|line| !line.starts_with("---")) {
+            lines.pop();
+        }
+        lines.reverse();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// `local function f` and `local x = ...`
+    fn is_local(&self, node: Node) -> bool {
+        node.child(0).is_some_and(|first| first.kind() == "local")
+    }
+
+    fn symbol(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        name: &str,
+        symbol_type: SymbolType,
+        signature: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            name: name.into(),
+            symbol_type,
+            signature,
+            docstring: self.extract_doc_comment(node, source),
+            line_start: node.start_position().row + 1,
+            line_end: node.end_position().row + 1,
+            parent_id: None,
+            file_path: file_path.into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    /// The local a chunk hands out with its last `return M`
+    fn returned_name<'a>(&self, root: Node, source: &'a str) -> Option<&'a str> {
+        let mut cursor = root.walk();
+        let statement = root
+            .named_children(&mut cursor)
+            .filter(|child| child.kind() != "comment")
+            .last()
+            .filter(|child| child.kind() == "return_statement")?;
+        let values = statement.named_child(0)?;
+        let value = values
+            .named_child(0)
+            .filter(|_| values.named_child_count() == 1)?;
+        (value.kind() == "identifier")
+            .then(|| self.extract_text(value, source))
+            .flatten()
+    }
+
+    fn statement(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        module: &mut Module,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        match node.kind() {
+            "function_declaration" => {
+                let (Some(name), Some(parameters)) = (
+                    node.child_by_field_name("name"),
+                    node.child_by_field_name("parameters"),
+                ) else {
+                    return;
+                };
+                let local = self.is_local(node);
+                self.function(
+                    node, name, parameters, local, source, file_path, module, symbols,
+                );
+            }
+            "variable_declaration" => {
+                let Some(assignment) = node
+                    .named_child(0)
+                    .filter(|child| child.kind() == "assignment_statement")
+                else {
+                    return;
+                };
+                self.assignment(node, assignment, true, source, file_path, module, symbols);
+            }
+            "assignment_statement" => {
+                self.assignment(node, node, false, source, file_path, module, symbols)
+            }
+            _ => {}
+        }
+    }
+
+    /// `a, b = x, y`: functions and tables by name, and values set on a module table
+    #[allow(clippy::too_many_arguments)]
+    fn assignment(
+        &self,
+        statement: Node,
+        assignment: Node,
+        local: bool,
+        source: &str,
+        file_path: &Path,
+        module: &mut Module,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(variables) = child_of_kind(assignment, "variable_list") else {
+            return;
+        };
+        let Some(values) = child_of_kind(assignment, "expression_list") else {
+            return;
+        };
+        let mut cursor = variables.walk();
+        let names: Vec<Node> = variables
+            .children_by_field_name("name", &mut cursor)
+            .collect();
+        let mut cursor = values.walk();
+        let values: Vec<Node> = values
+            .children_by_field_name("value", &mut cursor)
+            .collect();
+
+        for (name, value) in names.into_iter().zip(values) {
+            match value.kind() {
+                "function_definition" => {
+                    let Some(parameters) = value.child_by_field_name("parameters") else {
+                        continue;
+                    };
+                    self.function(
+                        statement, name, parameters, local, source, file_path, module, symbols,
+                    );
+                }
+                "table_constructor" => {
+                    let Some(table) = self.extract_text(name, source) else {
+                        continue;
+                    };
+                    let mut symbol =
+                        self.symbol(statement, source, file_path, table, SymbolType::Class, None);
+                    symbol.is_exported = !local || module.returned == Some(table);
+                    let exported = symbol.is_exported;
+                    symbols.push(symbol);
+                    let parent = symbols.len() - 1;
+                    module.tables.insert(table.to_string(), parent);
+                    self.table_fields(value, parent, exported, source, file_path, symbols);
+                }
+                // `M.VERSION = "1.0"` on a table declared above
+                _ => {
+                    let Some((parent, field)) = self.member_of(name, source, module) else {
+                        continue;
+                    };
+                    let mut symbol = self.symbol(
+                        statement,
+                        source,
+                        file_path,
+                        field,
+                        SymbolType::StaticField,
+                        constant_signature(None, self.extract_text(value, source)),
+                    );
+                    symbol.parent_id = Some(parent);
+                    symbol.is_exported = symbols[parent].is_exported;
+                    symbols.push(symbol);
+                }
+            }
+        }
+    }
+
+    /// The module table and field name of `M.field`, when `M` was declared in the chunk
+    fn member_of<'a>(
+        &self,
+        name: Node,
+        source: &'a str,
+        module: &Module,
+    ) -> Option<(usize, &'a str)> {
+        if name.kind() != "dot_index_expression" {
+            return None;
+        }
+        let table = self.extract_text(name.child_by_field_name("table")?, source)?;
+        let field = self.extract_text(name.child_by_field_name("field")?, source)?;
+        Some((*module.tables.get(table)?, field))
+    }
+
+    /// `function f()`, `local function f()`, `function M.f()`, `function M:f()` and the
+    /// same names assigned a `function() end`. Functions on a table are its methods.
+    #[allow(clippy::too_many_arguments)]
+    fn function(
+        &self,
+        node: Node,
+        name: Node,
+        parameters: Node,
+        local: bool,
+        source: &str,
+        file_path: &Path,
+        module: &Module,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let (table, field) = match name.kind() {
+            "identifier" => (None, name),
+            "dot_index_expression" | "method_index_expression" => {
+                let field = name
+                    .child_by_field_name("field")
+                    .or_else(|| name.child_by_field_name("method"));
+                match field {
+                    Some(field) => (name.child_by_field_name("table"), field),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let Some(function_name) = self.extract_text(field, source) else {
+            return;
+        };
+        let signature = collapsed_text(parameters, source);
+
+        let symbol = match table {
+            Some(table) => {
+                let parent = self
+                    .extract_text(table, source)
+                    .and_then(|table| module.tables.get(table).copied());
+                let mut symbol = self.symbol(
+                    node,
+                    source,
+                    file_path,
+                    function_name,
+                    SymbolType::Method,
+                    signature,
+                );
+                symbol.parent_id = parent;
+                // Functions put on a global table such as `string` are reachable too
+                symbol.is_exported = parent.is_none_or(|parent| symbols[parent].is_exported);
+                symbol
+            }
+            None => {
+                let mut symbol = self.symbol(
+                    node,
+                    source,
+                    file_path,
+                    function_name,
+                    SymbolType::Function,
+                    signature,
+                );
+                symbol.is_exported = !local || module.returned == Some(function_name);
+                symbol
+            }
+        };
+        symbols.push(symbol);
+    }
+
+    /// Named fields of a table constructor: `f = function() end` is a method, anything
+    /// else a static value
+    fn table_fields(
+        &self,
+        table: Node,
+        parent: usize,
+        exported: bool,
+        source: &str,
+        file_path: &Path,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut cursor = table.walk();
+        for field in table.named_children(&mut cursor) {
+            let (Some(name), Some(value)) = (
+                field.child_by_field_name("name"),
+                field.child_by_field_name("value"),
+            ) else {
+                continue;
+            };
+            // `[key] = value` entries are data, not members
+            if field.kind() != "field" || name.kind() != "identifier" {
+                continue;
+            }
+            let Some(field_name) = self.extract_text(name, source) else {
+                continue;
+            };
+            let (symbol_type, signature) = match value.kind() {
+                "function_definition" => (
+                    SymbolType::Method,
+                    value
+                        .child_by_field_name("parameters")
+                        .and_then(|p| collapsed_text(p, source)),
+                ),
+                _ => (
+                    SymbolType::StaticField,
+                    constant_signature(None, self.extract_text(value, source)),
+                ),
+            };
+            let mut symbol =
+                self.symbol(field, source, file_path, field_name, symbol_type, signature);
+            symbol.parent_id = Some(parent);
+            symbol.is_exported = exported;
+            symbols.push(symbol);
+        }
+    }
+
+    /// `require("a.b")`, `require "a.b"` and `require 'a.b'` anywhere in the chunk
+    fn process_requires(&self, node: Node, source: &str, imports: &mut Vec<Dependency>) {
+        if node.kind() == "function_call" {
+            let callee = node
+                .child_by_field_name("name")
+                .and_then(|n| self.extract_text(n, source));
+            let module = node
+                .child_by_field_name("arguments")
+                .and_then(|arguments| arguments.named_child(0))
+                .filter(|argument| argument.kind() == "string")
+                .and_then(|string| string.child_by_field_name("content"))
+                .and_then(|content| self.extract_text(content, source));
+            if let (Some("require"), Some(module)) = (callee, module) {
+                imports.push(Dependency {
+                    import_name: module.to_string(),
+                    from_file: None,
+                });
+            }
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.process_requires(child, source, imports);
+        }
+    }
+}
+
+impl ParserTrait for LuaParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let language: tree_sitter::Language = tree_sitter_lua::LANGUAGE.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Lua language")?
+            .context("Failed to parse Lua file")?;
+
+        let root = tree.root_node();
+        let mut result = ParseResult::new();
+        let mut module = Module {
+            tables: HashMap::new(),
+            returned: self.returned_name(root, content),
+        };
+        let mut cursor = root.walk();
+        for statement in root.named_children(&mut cursor) {
+            self.statement(
+                statement,
+                content,
+                file_path,
+                &mut module,
+                &mut result.symbols,
+            );
+        }
+        self.process_requires(root, content, &mut result.dependencies);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParseResult> {
+        LuaParser::new()?.parse(source, Path::new("shop.lua"))
+    }
+
+    #[test]
+    fn test_parse_module_functions_and_requires() -> Result<()> {
+        let result = parse(
+            r#"local json = require("dkjson")
+local util = require "lib.util"
+local M = {}
+
+--- Adds two numbers
+-- @param a number
+function M.add(a, b)
+  return a + b
+end
+
+function M:greet(name, ...)
+  print("hi " .. name)
+end
+
+M.sub = function(a, b) return a - b end
+M.VERSION = "1.0"
+
+local function helper(x)
+  return x * 2
+end
+
+function setup() end
+
+local Config = {
+  retries = 3,
+  load = function(path) return path end,
+}
+
+return M
+"#,
+        )?;
+        let symbols: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("M", SymbolType::Class, true),
+                ("add", SymbolType::Method, true),
+                ("greet", SymbolType::Method, true),
+                ("sub", SymbolType::Method, true),
+                ("VERSION", SymbolType::StaticField, true),
+                ("helper", SymbolType::Function, false),
+                ("setup", SymbolType::Function, true),
+                ("Config", SymbolType::Class, false),
+                ("retries", SymbolType::StaticField, false),
+                ("load", SymbolType::Method, false),
+            ]
+        );
+        let add = &result.symbols[1];
+        assert_eq!(add.parent_id, Some(0));
+        assert_eq!(add.signature.as_deref(), Some("(a, b)"));
+        assert_eq!(
+            add.docstring.as_deref(),
+            Some("--- Adds two numbers\n-- @param a number")
+        );
+        assert_eq!((add.line_start, add.line_end), (7, 9));
+        assert_eq!(result.symbols[2].signature.as_deref(), Some("(name, ...)"));
+        assert_eq!(result.symbols[4].signature.as_deref(), Some("\"1.0\""));
+        assert_eq!(result.symbols[9].parent_id, Some(7));
+
+        let imports: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| d.import_name.as_str())
+            .collect();
+        assert_eq!(imports, ["dkjson", "lib.util"]);
+
+        Ok(())
+    }
+}
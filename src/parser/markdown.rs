@@ -1,8 +1,8 @@
-use super::{ParseResult, Parser as ParserTrait};
+use super::{pooled_parse, ParseResult, Parser as ParserTrait};
 use crate::models::{Symbol, SymbolType};
 use anyhow::{Context, Result};
 use std::path::Path;
-use tree_sitter::{Node, Parser};
+use tree_sitter::Node;
 
 pub struct MarkdownParser;
 
@@ -112,15 +112,17 @@ impl MarkdownParser {
                         let level_prefix = "#".repeat(level);
 
                         symbols.push(Symbol {
-                            name: text,
+                            name: text.into(),
                             symbol_type: SymbolType::Heading,
                             signature: Some(format!("h{} ({})", level, level_prefix)),
                             docstring: None,
                             line_start,
                             line_end,
                             parent_id,
-                            file_path: file_path.to_path_buf(),
+                            file_path: file_path.into(),
                             is_exported: false,
+                            byte_range: None,
+                            annotations: Vec::new(),
                         });
                     }
                 }
@@ -192,7 +194,7 @@ impl MarkdownParser {
                     }
 
                     code_blocks.push(Symbol {
-                        name: format!("[code: {}]", language),
+                        name: format!("[code: {}]", language).into(),
                         symbol_type: SymbolType::CodeBlock,
                         signature: Some(language.clone()),
                         docstring: if code_content.is_empty() {
@@ -203,8 +205,10 @@ impl MarkdownParser {
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: false,
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
                 _ => {}
@@ -232,14 +236,9 @@ impl MarkdownParser {
 
 impl ParserTrait for MarkdownParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
-        let mut parser = Parser::new();
         let language = tree_sitter_md::LANGUAGE.into();
-        parser
-            .set_language(&language)
-            .context("Failed to set Markdown language")?;
-
-        let tree = parser
-            .parse(content, None)
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Markdown language")?
             .context("Failed to parse Markdown file")?;
 
         let root = tree.root_node();
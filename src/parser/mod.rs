@@ -1,15 +1,30 @@
 pub mod c;
+pub mod cpp;
+pub mod csharp;
 pub mod go;
 pub mod java;
 pub mod javascript;
+pub mod kotlin;
+pub mod lua;
 pub mod markdown;
+pub mod php;
+pub mod plugin;
 pub mod python;
+pub mod registry;
 pub mod rust;
+pub mod scala;
+pub mod swift;
 pub mod typescript;
+mod walk;
+pub mod zig;
 
 use crate::models::{Dependency, Symbol};
 use anyhow::Result;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+use tree_sitter::{Language, LanguageError, Parser as TSParser, Query, QueryError, Tree};
 
 pub trait Parser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult>;
@@ -30,5 +45,117 @@ impl ParseResult {
     }
 }
 
+/// Compile `source` for `language` once and share it across files and threads.
+/// The set of queries is fixed, so compiled queries live for the whole process.
+pub fn cached_query(
+    language: &Language,
+    source: &'static str,
+) -> Result<&'static Query, QueryError> {
+    type QueryCache = RwLock<HashMap<(Language, &'static str), &'static Query>>;
+    static QUERIES: OnceLock<QueryCache> = OnceLock::new();
+
+    let queries = QUERIES.get_or_init(Default::default);
+    let key = (language.clone(), source);
+
+    if let Some(query) = queries.read().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Ok(query);
+    }
+
+    let query = Query::new(language, source)?;
+    let mut queries = queries.write().unwrap_or_else(|e| e.into_inner());
+    Ok(*queries.entry(key).or_insert_with(|| Box::leak(Box::new(query))))
+}
+
+/// Parse `source` with a parser for `language` kept for reuse on the current thread, so
+/// indexing, fast-mode validation and call extraction don't build and configure a parser per
+/// file. `Ok(None)` when tree-sitter gives up on the source.
+pub fn pooled_parse(language: &Language, source: &str) -> Result<Option<Tree>, LanguageError> {
+    thread_local! {
+        static PARSERS: RefCell<HashMap<Language, TSParser>> = RefCell::new(HashMap::new());
+    }
+
+    // Taken out while in use, so a parse that parses again meanwhile gets a parser of its own
+    let mut parser = match PARSERS.with_borrow_mut(|parsers| parsers.remove(language)) {
+        Some(parser) => parser,
+        None => {
+            let mut parser = TSParser::new();
+            parser.set_language(language)?;
+            parser
+        }
+    };
+    let tree = parser.parse(source, None);
+    if tree.is_none() {
+        parser.reset();
+    }
+    PARSERS.with_borrow_mut(|parsers| parsers.insert(language.clone(), parser));
+    Ok(tree)
+}
+
+/// Longest constant value shown in a signature
+const MAX_VALUE_CHARS: usize = 60;
+
+/// Signature of a constant or enum member: `type = value`, or whichever of the two exists.
+/// Values are cut to their first line so large literals stay readable in listings.
+pub fn constant_signature(type_name: Option<&str>, value: Option<&str>) -> Option<String> {
+    let value = value.map(|value| {
+        let first_line = value.lines().next().unwrap_or_default().trim_end();
+        if first_line.len() < value.trim_end().len() || first_line.chars().count() > MAX_VALUE_CHARS
+        {
+            let cut: String = first_line.chars().take(MAX_VALUE_CHARS).collect();
+            format!("{}…", cut)
+        } else {
+            first_line.to_string()
+        }
+    });
+    match (type_name, value) {
+        (Some(type_name), Some(value)) => Some(format!("{} = {}", type_name, value)),
+        (Some(type_name), None) => Some(type_name.to_string()),
+        (None, value) => value,
+    }
+}
+
 // pub use javascript::JavaScriptParser;
 // pub use python::PythonParser;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_query_compiles_once() {
+        let language: Language = tree_sitter_python::LANGUAGE.into();
+        let first = cached_query(&language, "(function_definition) @func").unwrap();
+        let second = cached_query(&language, "(function_definition) @func").unwrap();
+        assert!(std::ptr::eq(first, second));
+
+        assert!(cached_query(&language, "(not_a_node) @x").is_err());
+    }
+
+    #[test]
+    fn test_pooled_parse_reuses_parsers_per_language() {
+        let python: Language = tree_sitter_python::LANGUAGE.into();
+        let rust: Language = tree_sitter_rust::LANGUAGE.into();
+        for _ in 0..2 {
+            let tree = pooled_parse(&python, "def f():\n    pass\n")
+                .unwrap()
+                .unwrap();
+            assert_eq!(tree.root_node().kind(), "module");
+            let tree = pooled_parse(&rust, "fn f() {}\n").unwrap().unwrap();
+            assert_eq!(tree.root_node().kind(), "source_file");
+        }
+    }
+
+    #[test]
+    fn test_constant_signature() {
+        assert_eq!(
+            constant_signature(Some("u32"), Some("3")).as_deref(),
+            Some("u32 = 3")
+        );
+        assert_eq!(constant_signature(None, Some("30")).as_deref(), Some("30"));
+        assert_eq!(
+            constant_signature(None, Some("{\n    \"a\": 1,\n}")).as_deref(),
+            Some("{…")
+        );
+        assert_eq!(constant_signature(None, None), None);
+    }
+}
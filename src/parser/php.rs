@@ -0,0 +1,562 @@
+use super::walk::{child_of_kind, doc_comment, name_of};
+use super::{constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::path::Path;
+use tree_sitter::Node;
+
+pub struct PhpParser;
+
+impl PhpParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn extract_text(&self, node: Node, source: &str) -> Option<String> {
+        let start = node.start_byte();
+        let end = node.end_byte();
+        if end <= source.len() && start <= end {
+            source.get(start..end).map(|s| s.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Members without `private` or `protected` are public
+    fn is_public(&self, node: Node, source: &str) -> bool {
+        let mut cursor = node.walk();
+        let public = !node.children(&mut cursor).any(|child| {
+            child.kind() == "visibility_modifier"
+                && self
+                    .extract_text(child, source)
+                    .is_some_and(|v| v != "public")
+        });
+        public
+    }
+
+    fn symbol(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        name: String,
+        symbol_type: SymbolType,
+        signature: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            name: name.into(),
+            symbol_type,
+            signature,
+            docstring: doc_comment(node, node.prev_named_sibling(), source, None),
+            line_start: node.start_position().row + 1,
+            line_end: node.end_position().row + 1,
+            parent_id: None,
+            file_path: file_path.into(),
+            is_exported: true,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    /// `(int $id): ?self`, on one line however the parameters were laid out
+    fn function_signature(&self, node: Node, source: &str) -> Option<String> {
+        let parameters = node.child_by_field_name("parameters")?;
+        let mut cursor = parameters.walk();
+        let parameters = parameters
+            .named_children(&mut cursor)
+            .filter(|p| p.kind() != "comment")
+            .filter_map(|p| self.extract_text(p, source))
+            .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>();
+        let parameters = format!("({})", parameters.join(", "));
+        match node
+            .child_by_field_name("return_type")
+            .and_then(|r| self.extract_text(r, source))
+        {
+            Some(return_type) => Some(format!("{}: {}", parameters, return_type)),
+            None => Some(parameters),
+        }
+    }
+
+    fn visit(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        parent: Option<usize>,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "namespace_definition" => self.namespace(child, source, file_path, symbols),
+                "class_declaration" | "interface_declaration" | "trait_declaration" => {
+                    self.type_declaration(child, source, file_path, parent, symbols)
+                }
+                "enum_declaration" => self.enumeration(child, source, file_path, parent, symbols),
+                "function_definition" => {
+                    let Some(name) = name_of(child, source) else {
+                        continue;
+                    };
+                    let signature = self.function_signature(child, source);
+                    let symbol = self.symbol(
+                        child,
+                        source,
+                        file_path,
+                        name,
+                        SymbolType::Function,
+                        signature,
+                    );
+                    symbols.push(symbol);
+                }
+                "method_declaration" => {
+                    let Some(name) = name_of(child, source) else {
+                        continue;
+                    };
+                    let signature = self.function_signature(child, source);
+                    let mut symbol = self.symbol(
+                        child,
+                        source,
+                        file_path,
+                        name,
+                        SymbolType::Method,
+                        signature,
+                    );
+                    symbol.parent_id = parent;
+                    symbol.is_exported = self.is_public(child, source);
+                    symbols.push(symbol);
+                }
+                "property_declaration" => {
+                    self.properties(child, source, file_path, parent, symbols)
+                }
+                "const_declaration" => self.constants(child, source, file_path, parent, symbols),
+                "declaration_list" | "compound_statement" => {
+                    self.visit(child, source, file_path, parent, symbols)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `namespace A\B { }`, or `namespace A\B;` covering the file up to the next namespace
+    fn namespace(&self, node: Node, source: &str, file_path: &Path, symbols: &mut Vec<Symbol>) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name,
+            SymbolType::Class,
+            Some("namespace".to_string()),
+        );
+        match node.child_by_field_name("body") {
+            Some(body) => {
+                symbols.push(symbol);
+                self.visit(body, source, file_path, None, symbols);
+            }
+            None => {
+                let mut end = node;
+                let mut next = node.next_named_sibling();
+                while let Some(sibling) = next {
+                    if sibling.kind() == "namespace_definition" {
+                        break;
+                    }
+                    end = sibling;
+                    next = sibling.next_named_sibling();
+                }
+                symbol.line_end = end.end_position().row + 1;
+                symbols.push(symbol);
+            }
+        }
+    }
+
+    fn type_declaration(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        parent: Option<usize>,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let (symbol_type, keyword) = match node.kind() {
+            "interface_declaration" => (SymbolType::Interface, "interface"),
+            "trait_declaration" => (SymbolType::Class, "trait"),
+            _ => (SymbolType::Class, "class"),
+        };
+        // `abstract class Order extends Model implements Priced`
+        let mut parts = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "abstract_modifier" | "final_modifier" | "readonly_modifier" => {
+                    parts.extend(self.extract_text(child, source))
+                }
+                "base_clause" | "class_interface_clause" => parts.extend(
+                    self.extract_text(child, source)
+                        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" ")),
+                ),
+                _ => {}
+            }
+        }
+        let position = parts
+            .iter()
+            .position(|p| p.starts_with("extends") || p.starts_with("implements"))
+            .unwrap_or(parts.len());
+        parts.insert(position, keyword.to_string());
+
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name,
+            symbol_type,
+            Some(parts.join(" ")),
+        );
+        symbol.parent_id = parent;
+        symbols.push(symbol);
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let parent = Some(symbols.len() - 1);
+            self.visit(body, source, file_path, parent, symbols);
+        }
+    }
+
+    fn enumeration(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        parent: Option<usize>,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name.clone(),
+            SymbolType::Enum,
+            None,
+        );
+        symbol.parent_id = parent;
+        symbols.push(symbol);
+        let enum_id = Some(symbols.len() - 1);
+
+        // Cases, as `Status::Open`, with their backing values; methods and constants too
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            if member.kind() != "enum_case" {
+                continue;
+            }
+            let Some(case) = name_of(member, source) else {
+                continue;
+            };
+            let value = member
+                .child_by_field_name("value")
+                .and_then(|v| self.extract_text(v, source));
+            let mut symbol = self.symbol(
+                member,
+                source,
+                file_path,
+                format!("{}::{}", name, case),
+                SymbolType::Enum,
+                constant_signature(Some(&format!("variant of {}", name)), value.as_deref()),
+            );
+            symbol.parent_id = parent;
+            symbols.push(symbol);
+        }
+        self.visit(body, source, file_path, enum_id, symbols);
+    }
+
+    /// Properties, one symbol per `$name`; static ones carry their default value
+    fn properties(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        parent: Option<usize>,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let is_static = child_of_kind(node, "static_modifier").is_some();
+        let type_name = node
+            .child_by_field_name("type")
+            .and_then(|t| self.extract_text(t, source));
+        let mut cursor = node.walk();
+        for element in node.named_children(&mut cursor) {
+            if element.kind() != "property_element" {
+                continue;
+            }
+            // `$count` is `$this->count` and `self::$count` in use, so it goes by `count`
+            let Some(name) =
+                name_of(element, source).map(|n| n.trim_start_matches('$').to_string())
+            else {
+                continue;
+            };
+            let (symbol_type, signature) = if is_static {
+                let value = element
+                    .child_by_field_name("default_value")
+                    .and_then(|v| self.extract_text(v, source));
+                (
+                    SymbolType::StaticField,
+                    constant_signature(type_name.as_deref(), value.as_deref()),
+                )
+            } else {
+                (SymbolType::Field, type_name.clone())
+            };
+            let mut symbol = self.symbol(node, source, file_path, name, symbol_type, signature);
+            symbol.parent_id = parent;
+            symbol.is_exported = self.is_public(node, source);
+            symbols.push(symbol);
+        }
+    }
+
+    /// `const A = 1, B = 2;` in a class or at the top level
+    fn constants(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        parent: Option<usize>,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut cursor = node.walk();
+        for element in node.named_children(&mut cursor) {
+            if element.kind() != "const_element" {
+                continue;
+            }
+            let mut element_cursor = element.walk();
+            let mut parts = element.named_children(&mut element_cursor);
+            let Some(name) = parts.next().and_then(|n| self.extract_text(n, source)) else {
+                continue;
+            };
+            let value = parts.next().and_then(|v| self.extract_text(v, source));
+            let mut symbol = self.symbol(
+                node,
+                source,
+                file_path,
+                name,
+                SymbolType::StaticField,
+                constant_signature(None, value.as_deref()),
+            );
+            symbol.parent_id = parent;
+            symbol.is_exported = self.is_public(node, source);
+            symbols.push(symbol);
+        }
+    }
+
+    /// `use A\B;` (and group uses `use A\{B, C}`) import `A\B`; `require`/`include` of a
+    /// literal path import that path, made relative to the file for `__DIR__ . '/x.php'`
+    fn process_imports(&self, tree_root: Node, source: &str) -> Vec<Dependency> {
+        let mut imports = Vec::new();
+        let mut stack = vec![tree_root];
+
+        while let Some(node) = stack.pop() {
+            match node.kind() {
+                "namespace_use_declaration" => {
+                    let prefix = child_of_kind(node, "namespace_name")
+                        .and_then(|n| self.extract_text(n, source));
+                    let clauses = match node.child_by_field_name("body") {
+                        Some(group) => group,
+                        None => node,
+                    };
+                    let mut cursor = clauses.walk();
+                    for clause in clauses.named_children(&mut cursor) {
+                        if clause.kind() != "namespace_use_clause" {
+                            continue;
+                        }
+                        let Some(name) = clause
+                            .named_child(0)
+                            .and_then(|n| self.extract_text(n, source))
+                        else {
+                            continue;
+                        };
+                        let import_name = match &prefix {
+                            Some(prefix) => format!("{}\\{}", prefix, name),
+                            None => name,
+                        };
+                        imports.push(Dependency {
+                            import_name,
+                            from_file: None,
+                        });
+                    }
+                }
+                "require_expression"
+                | "require_once_expression"
+                | "include_expression"
+                | "include_once_expression" => {
+                    if let Some(path) = self.required_path(node, source) {
+                        imports.push(Dependency {
+                            import_name: path,
+                            from_file: None,
+                        });
+                    }
+                }
+                _ => {
+                    // Reversed so imports come off the stack in source order
+                    let mut cursor = node.walk();
+                    let children: Vec<_> = node.named_children(&mut cursor).collect();
+                    stack.extend(children.into_iter().rev());
+                }
+            }
+        }
+
+        imports
+    }
+
+    /// The literal path of a `require`/`include`, if the argument is one
+    fn required_path(&self, node: Node, source: &str) -> Option<String> {
+        let argument = node.named_child(0)?;
+        let (dir_relative, string) = match argument.kind() {
+            "string" | "encapsed_string" => (false, argument),
+            "binary_expression" => {
+                let left = argument.child_by_field_name("left")?;
+                let right = argument.child_by_field_name("right")?;
+                let left = self.extract_text(left, source)?;
+                (left == "__DIR__" || left == "dirname(__FILE__)", right)
+            }
+            _ => return None,
+        };
+        let content =
+            child_of_kind(string, "string_content").and_then(|c| self.extract_text(c, source))?;
+        match dir_relative {
+            true => Some(format!(".{}", content)),
+            false => Some(content),
+        }
+    }
+}
+
+impl ParserTrait for PhpParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let language: tree_sitter::Language = tree_sitter_php::LANGUAGE_PHP.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set PHP language")?
+            .context("Failed to parse PHP file")?;
+
+        let root = tree.root_node();
+        let mut result = ParseResult::new();
+        self.visit(root, content, file_path, None, &mut result.symbols);
+        result.dependencies = self.process_imports(root, content);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParseResult> {
+        PhpParser::new()?.parse(source, Path::new("Order.php"))
+    }
+
+    #[test]
+    fn test_parse_classes_and_members() -> Result<()> {
+        let result = parse(
+            r#"<?php
+namespace App\Models;
+
+use Illuminate\Database\Eloquent\Model;
+use App\Contracts\{Priced, Shippable as Ship};
+require_once __DIR__ . '/bootstrap.php';
+
+/** An order */
+final class Order extends Model implements Priced {
+    use HasFactory;
+    const MAX = 5;
+    public static $count = 0;
+    protected string $table = 'orders';
+
+    public static function find(int $id): ?self { return null; }
+    private function check() {}
+    function total() {}
+}
+
+interface Priced { public function price(): float; }
+trait HasFactory { public function factory() {} }
+enum Status: string { case Open = 'open'; }
+function helper($x = null) {}
+"#,
+        )?;
+        let symbols: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("App\\Models", SymbolType::Class, true),
+                ("Order", SymbolType::Class, true),
+                ("MAX", SymbolType::StaticField, true),
+                ("count", SymbolType::StaticField, true),
+                ("table", SymbolType::Field, false),
+                ("find", SymbolType::Method, true),
+                ("check", SymbolType::Method, false),
+                ("total", SymbolType::Method, true),
+                ("Priced", SymbolType::Interface, true),
+                ("price", SymbolType::Method, true),
+                ("HasFactory", SymbolType::Class, true),
+                ("factory", SymbolType::Method, true),
+                ("Status", SymbolType::Enum, true),
+                ("Status::Open", SymbolType::Enum, true),
+                ("helper", SymbolType::Function, true),
+            ]
+        );
+        let order = &result.symbols[1];
+        assert_eq!(
+            order.signature.as_deref(),
+            Some("final class extends Model implements Priced")
+        );
+        assert_eq!(order.docstring.as_deref(), Some("/** An order */"));
+        assert_eq!(result.symbols[0].line_end, 23);
+        assert_eq!(
+            result.symbols[5].signature.as_deref(),
+            Some("(int $id): ?self")
+        );
+        assert_eq!(result.symbols[5].parent_id, Some(1));
+        assert_eq!(result.symbols[10].signature.as_deref(), Some("trait"));
+
+        let imports: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| d.import_name.as_str())
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                "Illuminate\\Database\\Eloquent\\Model",
+                "App\\Contracts\\Priced",
+                "App\\Contracts\\Shippable",
+                "./bootstrap.php",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_braced_namespaces() -> Result<()> {
+        let result = parse(
+            "<?php\nnamespace A {\n    function f() {}\n}\nnamespace B {\n    class C {}\n}\n",
+        )?;
+        let names: Vec<_> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["A", "f", "B", "C"]);
+        assert_eq!(
+            (result.symbols[0].line_start, result.symbols[0].line_end),
+            (2, 4)
+        );
+        assert_eq!(result.symbols[3].parent_id, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use super::{ParseResult, Parser};
+use crate::config::PluginConfig;
+use crate::failure::{ErrorCode, Failure};
+use crate::models::{Dependency, Symbol, SymbolType};
+use crate::paths::DisplaySlash;
+use crate::process;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Parser backed by an external process declared in `.codemapper.toml`
+pub struct SubprocessParser {
+    config: PluginConfig,
+}
+
+#[derive(Deserialize)]
+struct PluginOutput {
+    #[serde(default)]
+    symbols: Vec<PluginSymbol>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PluginSymbol {
+    name: String,
+    /// Any `SymbolType` name: function, class, method, enum, static, interface, type, ...,
+    /// or a kind declared in config (`[[kinds]]`, the plugin's `kinds`)
+    kind: String,
+    signature: Option<String>,
+    docstring: Option<String>,
+    line_start: usize,
+    line_end: Option<usize>,
+    /// Index of the parent symbol in the same `symbols` list
+    parent: Option<usize>,
+    #[serde(default)]
+    exported: bool,
+}
+
+impl SubprocessParser {
+    pub fn new(config: PluginConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+}
+
+impl Parser for SubprocessParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let child = Command::new(&self.config.command)
+            .args(&self.config.args)
+            .arg(file_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to start parser plugin '{}'", self.config.name))?;
+
+        let output = process::feed_and_wait(child, content.as_bytes())
+            .with_context(|| format!("Parser plugin '{}' did not finish", self.config.name))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!(Failure::new(
+                ErrorCode::ExternalCommandFailed,
+                format!(
+                    "Parser plugin '{}' failed on {}: {}",
+                    self.config.name,
+                    file_path.display_slash(),
+                    stderr.trim()
+                )
+            ));
+        }
+
+        parse_plugin_output(&output.stdout, file_path)
+            .with_context(|| format!("Invalid output from parser plugin '{}'", self.config.name))
+    }
+}
+
+fn parse_plugin_output(stdout: &[u8], file_path: &Path) -> Result<ParseResult> {
+    let output: PluginOutput = serde_json::from_slice(stdout)?;
+
+    let symbols = output
+        .symbols
+        .into_iter()
+        .map(|s| Symbol {
+            symbol_type: SymbolType::from_str(&s.kind).unwrap_or(SymbolType::Function),
+            name: s.name.into(),
+            signature: s.signature,
+            docstring: s.docstring,
+            line_start: s.line_start,
+            line_end: s.line_end.unwrap_or(s.line_start),
+            parent_id: s.parent,
+            file_path: file_path.into(),
+            is_exported: s.exported,
+            byte_range: None,
+            annotations: Vec::new(),
+        })
+        .collect();
+
+    let dependencies = output
+        .dependencies
+        .into_iter()
+        .map(|import_name| Dependency {
+            import_name,
+            from_file: Some(file_path.display_slash().to_string()),
+        })
+        .collect();
+
+    Ok(ParseResult {
+        symbols,
+        dependencies,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plugin_output() {
+        let json = br#"{
+            "symbols": [
+                {"name": "Greeter", "kind": "class", "line_start": 1, "line_end": 9, "exported": true},
+                {"name": "hello", "kind": "method", "signature": "(name)", "line_start": 2, "parent": 0}
+            ],
+            "dependencies": ["Logger"]
+        }"#;
+
+        let result = parse_plugin_output(json, Path::new("greeter.ex")).unwrap();
+        assert_eq!(result.symbols.len(), 2);
+        assert_eq!(result.symbols[0].symbol_type, SymbolType::Class);
+        assert!(result.symbols[0].is_exported);
+        assert_eq!(result.symbols[1].parent_id, Some(0));
+        assert_eq!(result.symbols[1].line_end, 2);
+        assert_eq!(result.dependencies[0].import_name, "Logger");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_subprocess_parser() {
+        // `sh -c cat <file>` echoes stdin back, so the source itself is the plugin output
+        let parser = SubprocessParser::new(PluginConfig {
+            name: "echo".to_string(),
+            extensions: vec!["echo".to_string()],
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), "cat".to_string()],
+            kinds: Vec::new(),
+        });
+
+        let source = r#"{"symbols": [{"name": "run", "kind": "fn", "line_start": 3}]}"#;
+        let result = parser.parse(source, Path::new("main.echo")).unwrap();
+        assert_eq!(result.symbols[0].name, "run");
+        assert_eq!(&*result.symbols[0].file_path, Path::new("main.echo"));
+
+        assert!(parser.parse("not json", Path::new("main.echo")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_subprocess_parser_writes_before_reading() {
+        // More than a pipe buffer each way: printing before reading stdin must not deadlock
+        let parser = SubprocessParser::new(PluginConfig {
+            name: "chatty".to_string(),
+            extensions: vec!["chatty".to_string()],
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "head -c 200000 /dev/zero >&2; cat >/dev/null; echo '{}'".to_string(),
+            ],
+            kinds: Vec::new(),
+        });
+
+        let source = "x".repeat(200_000);
+        let result = parser.parse(&source, Path::new("big.chatty")).unwrap();
+        assert!(result.symbols.is_empty());
+    }
+}
@@ -1,9 +1,10 @@
-use super::{ParseResult, Parser as ParserTrait};
-use crate::models::{Dependency, Symbol, SymbolType};
+use super::{cached_query, constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Language, Symbol, SymbolType};
+use crate::schema;
 use anyhow::{Context, Result};
 use std::path::Path;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tree_sitter::{Node, QueryCursor};
 
 pub struct PythonParser;
 
@@ -86,7 +87,7 @@ impl PythonParser {
         let mut symbols = Vec::new();
 
         let language = tree_sitter_python::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (class_definition
@@ -97,7 +98,7 @@ impl PythonParser {
         .context("Failed to create Python class query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -137,15 +138,17 @@ impl PythonParser {
                 let parent_id = self.find_parent_class(node, &symbols);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Class,
                     signature: None,
                     docstring,
                     line_start,
                     line_end,
                     parent_id,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: parent_id.is_none(),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -161,7 +164,7 @@ impl PythonParser {
         symbols: &mut Vec<Symbol>,
     ) -> Result<()> {
         let language = tree_sitter_python::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (function_definition
@@ -173,7 +176,7 @@ impl PythonParser {
         .context("Failed to create Python function query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -223,7 +226,7 @@ impl PythonParser {
                 };
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: if is_method {
                         SymbolType::Method
                     } else {
@@ -234,8 +237,10 @@ impl PythonParser {
                     line_start,
                     line_end,
                     parent_id,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: !is_method,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -252,7 +257,7 @@ impl PythonParser {
         let mut symbols = Vec::new();
         let language = tree_sitter_python::LANGUAGE.into();
 
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (module
@@ -265,7 +270,7 @@ impl PythonParser {
         .context("Failed to create Python constants query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -308,15 +313,17 @@ impl PythonParser {
                     let line_end = node.end_position().row + 1;
 
                     symbols.push(Symbol {
-                        name,
+                        name: name.into(),
                         symbol_type: SymbolType::StaticField,
-                        signature: const_value,
+                        signature: constant_signature(None, const_value.as_deref()),
                         docstring: None,
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: true,
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
             }
@@ -325,6 +332,94 @@ impl PythonParser {
         Ok(symbols)
     }
 
+    /// Members of `Enum`/`IntEnum`/`Flag`... subclasses, named `Color.RED`
+    fn process_enum_members(
+        &self,
+        tree_root: Node,
+        source: &str,
+        file_path: &Path,
+        symbols: &mut Vec<Symbol>,
+    ) -> Result<()> {
+        let language = tree_sitter_python::LANGUAGE.into();
+        let query = cached_query(
+            &language,
+            r#"
+            (class_definition
+                name: (identifier) @enum.name
+                superclasses: (argument_list) @enum.bases
+                body: (block
+                    (expression_statement
+                        (assignment
+                            left: (identifier) @member.name
+                            right: (_) @member.value) @member.def)))
+            "#,
+        )
+        .context("Failed to create Python enum member query")?;
+
+        let mut members = Vec::new();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
+
+        while let Some(match_) = matches.next() {
+            let mut enum_name = None;
+            let mut bases = None;
+            let mut member_name = None;
+            let mut member_value = None;
+            let mut member_node = None;
+
+            for capture in match_.captures {
+                let text = capture.node.utf8_text(source.as_bytes()).ok();
+                match query
+                    .capture_names()
+                    .get(capture.index as usize)
+                    .map(|s| s.as_ref())
+                {
+                    Some("enum.name") => enum_name = text,
+                    Some("enum.bases") => bases = text,
+                    Some("member.name") => member_name = text,
+                    Some("member.value") => member_value = text,
+                    Some("member.def") => member_node = Some(capture.node),
+                    _ => {}
+                }
+            }
+
+            let (Some(enum_name), Some(bases), Some(name), Some(node)) =
+                (enum_name, bases, member_name, member_node)
+            else {
+                continue;
+            };
+            let is_enum = bases
+                .trim_matches(|c| c == '(' || c == ')')
+                .split(',')
+                .map(|base| base.trim().rsplit('.').next().unwrap_or_default())
+                .any(|base| base.ends_with("Enum") || base.ends_with("Flag"));
+            // Annotated assignments are fields, `_ignore_` and friends are enum settings
+            if !is_enum || node.child_by_field_name("type").is_some() || name.starts_with('_') {
+                continue;
+            }
+
+            members.push(Symbol {
+                name: format!("{}.{}", enum_name, name).into(),
+                symbol_type: SymbolType::Enum,
+                signature: constant_signature(
+                    Some(&format!("variant of {}", enum_name)),
+                    member_value,
+                ),
+                docstring: None,
+                line_start: node.start_position().row + 1,
+                line_end: node.end_position().row + 1,
+                parent_id: self.find_parent_class(node, symbols),
+                file_path: file_path.into(),
+                is_exported: true,
+                byte_range: None,
+                annotations: Vec::new(),
+            });
+        }
+
+        symbols.extend(members);
+        Ok(())
+    }
+
     fn process_imports(&self, tree_root: Node, source: &str) -> Result<Vec<Dependency>> {
         let mut imports = Vec::new();
         let mut stack = vec![tree_root];
@@ -437,14 +532,9 @@ impl PythonParser {
 impl ParserTrait for PythonParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
         // Create a fresh parser for each call
-        let mut parser = Parser::new();
         let language = tree_sitter_python::LANGUAGE.into();
-        parser
-            .set_language(&language)
-            .context("Failed to set Python language")?;
-
-        let tree = parser
-            .parse(content, None)
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Python language")?
             .context("Failed to parse Python file")?;
 
         let root = tree.root_node();
@@ -457,6 +547,9 @@ impl ParserTrait for PythonParser {
         result.symbols.extend(constants);
 
         self.process_functions(root, content, file_path, &mut result.symbols)?;
+        self.process_enum_members(root, content, file_path, &mut result.symbols)?;
+        result.symbols =
+            schema::with_field_symbols(root, content, Language::Python, result.symbols);
         result.dependencies = self.process_imports(root, content)?;
 
         Ok(result)
@@ -527,4 +620,38 @@ from typing import List, Dict
         assert!(result.dependencies.len() >= 4);
         Ok(())
     }
+
+    #[test]
+    fn test_parse_constants_and_enum_members() -> Result<()> {
+        let parser = PythonParser::new()?;
+        let source = r#"
+MAX_RETRIES = 3
+
+class Color(enum.IntEnum):
+    RED = 1
+    GREEN: int = 2
+    _ignore_ = ["x"]
+
+class Plain:
+    BLUE = 3
+"#;
+        let result = parser.parse(source, Path::new("test.py"))?;
+        let names: Vec<(&str, Option<&str>)> = result
+            .symbols
+            .iter()
+            .filter(|s| matches!(s.symbol_type, SymbolType::StaticField | SymbolType::Enum))
+            .map(|s| (s.name.as_str(), s.signature.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("MAX_RETRIES", Some("3")),
+                ("Color.RED", Some("variant of Color = 1")),
+            ]
+        );
+        let red = result.symbols.iter().position(|s| s.name == "Color.RED");
+        let color = result.symbols.iter().position(|s| s.name == "Color");
+        assert_eq!(red.map(|i| result.symbols[i].parent_id), Some(color));
+        Ok(())
+    }
 }
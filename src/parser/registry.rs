@@ -0,0 +1,62 @@
+use super::plugin::SubprocessParser;
+use super::Parser;
+use crate::config::{PluginConfig, ProjectConfig};
+use crate::kinds;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A parser that can be shared across indexing threads
+pub type SharedParser = Arc<dyn Parser + Send + Sync>;
+
+/// Extension -> parser for languages added at runtime (out-of-tree plugins)
+fn registry() -> &'static RwLock<HashMap<String, SharedParser>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, SharedParser>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Register `parser` for files with any of `extensions` (without the leading dot).
+/// Built-in languages always take precedence over registered parsers.
+pub fn register_parser(extensions: &[&str], parser: SharedParser) {
+    let mut parsers = registry().write().unwrap_or_else(|e| e.into_inner());
+    for ext in extensions {
+        parsers.insert(ext.trim_start_matches('.').to_string(), parser.clone());
+    }
+}
+
+pub fn parser_for_extension(ext: &str) -> Option<SharedParser> {
+    let parsers = registry().read().unwrap_or_else(|e| e.into_inner());
+    parsers
+        .iter()
+        .find(|(registered, _)| registered.eq_ignore_ascii_case(ext))
+        .map(|(_, parser)| parser.clone())
+}
+
+pub fn registered_extensions() -> Vec<String> {
+    let parsers = registry().read().unwrap_or_else(|e| e.into_inner());
+    let mut extensions: Vec<String> = parsers.keys().cloned().collect();
+    extensions.sort();
+    extensions
+}
+
+/// Register the subprocess plugins declared in `root/.codemapper.toml`; returns how many were loaded
+pub fn load_plugins(root: &Path) -> Result<usize> {
+    register_plugins(ProjectConfig::load(root)?.plugins)
+}
+
+/// Register subprocess plugins and the kinds they declare; later entries win when
+/// extensions overlap
+pub fn register_plugins(plugins: Vec<PluginConfig>) -> Result<usize> {
+    let count = plugins.len();
+
+    for plugin in plugins {
+        kinds::register_kinds(&plugin.kinds)
+            .with_context(|| format!("Parser plugin '{}'", plugin.name))?;
+        let extensions = plugin.extensions.clone();
+        let ext_refs: Vec<&str> = extensions.iter().map(|e| e.as_str()).collect();
+        register_parser(&ext_refs, Arc::new(SubprocessParser::new(plugin)));
+    }
+
+    Ok(count)
+}
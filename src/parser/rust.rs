@@ -1,9 +1,10 @@
-use super::{ParseResult, Parser as ParserTrait};
-use crate::models::{Dependency, Symbol, SymbolType};
+use super::{cached_query, constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Language, Symbol, SymbolType};
+use crate::schema;
 use anyhow::{Context, Result};
 use std::path::Path;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tree_sitter::{Node, QueryCursor};
 
 pub struct RustParser;
 
@@ -102,7 +103,7 @@ impl RustParser {
         let mut symbols = Vec::new();
 
         let language = tree_sitter_rust::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (struct_item
@@ -112,7 +113,7 @@ impl RustParser {
         .context("Failed to create Rust struct query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -147,15 +148,17 @@ impl RustParser {
                 let line_end = node.end_position().row + 1;
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Class,
                     signature: None,
                     docstring,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: has_pub_visibility(node, source),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -174,7 +177,7 @@ impl RustParser {
         let language = tree_sitter_rust::LANGUAGE.into();
 
         // capture enum definitions
-        let enum_query = Query::new(
+        let enum_query = cached_query(
             &language,
             r#"
             (enum_item
@@ -184,7 +187,7 @@ impl RustParser {
         .context("Failed to create Rust enum query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&enum_query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(enum_query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let mut enum_name: Option<String> = None;
@@ -218,38 +221,42 @@ impl RustParser {
                 let is_exported = has_pub_visibility(node, source);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Enum,
                     signature: None,
                     docstring,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
 
         // capture enum variants (clap-style command enums etc)
-        let variant_query = Query::new(
+        let variant_query = cached_query(
             &language,
             r#"
             (enum_item
                 name: (type_identifier) @enum.name
                 body: (enum_variant_list
                     (enum_variant
-                        (identifier) @variant.name) @variant.def))
+                        (identifier) @variant.name
+                        value: (_)? @variant.value) @variant.def))
             "#,
         )
         .context("Failed to create Rust enum variant query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&variant_query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(variant_query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let mut enum_name: Option<String> = None;
             let mut variant_name: Option<String> = None;
+            let mut variant_value: Option<&str> = None;
             let mut variant_node: Option<Node> = None;
 
             for capture in match_.captures {
@@ -273,6 +280,9 @@ impl RustParser {
                             .ok()
                             .map(|s| s.to_string());
                     }
+                    Some("variant.value") => {
+                        variant_value = capture.node.utf8_text(source.as_bytes()).ok();
+                    }
                     Some("variant.def") => {
                         variant_node = Some(capture.node);
                     }
@@ -287,16 +297,22 @@ impl RustParser {
                 let line_end = node.end_position().row + 1;
 
                 symbols.push(Symbol {
-                    name: format!("{}::{}", enum_name, variant_name),
+                    name: format!("{}::{}", enum_name, variant_name).into(),
                     symbol_type: SymbolType::Enum,
-                    signature: Some(format!("variant of {}", enum_name)),
+                    // Explicit discriminants (`NotFound = 404`) follow the enum name
+                    signature: constant_signature(
+                        Some(&format!("variant of {}", enum_name)),
+                        variant_value,
+                    ),
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     // variants inherit enum visibility; we don't resolve that here, so keep this conservative
                     is_exported: true,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -313,27 +329,30 @@ impl RustParser {
         let mut symbols = Vec::new();
 
         let language = tree_sitter_rust::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (const_item
                 name: (identifier) @const.name
-                type: (_) @const.type) @const.def
+                type: (_) @const.type
+                value: (_)? @const.value) @const.def
             (static_item
                 name: (identifier) @static.name
-                type: (_) @static.type) @static.def
+                type: (_) @static.type
+                value: (_)? @static.value) @static.def
             "#,
         )
         .context("Failed to create Rust static/const query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
 
             let mut field_name = None;
             let mut field_type = None;
+            let mut field_value = None;
             let mut field_node = None;
 
             for capture in captures {
@@ -357,6 +376,9 @@ impl RustParser {
                             .ok()
                             .map(|s| s.to_string());
                     }
+                    Some("const.value") | Some("static.value") => {
+                        field_value = capture.node.utf8_text(source.as_bytes()).ok();
+                    }
                     Some("const.def") | Some("static.def") => {
                         field_node = Some(capture.node);
                     }
@@ -370,15 +392,17 @@ impl RustParser {
                 let line_end = node.end_position().row + 1;
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::StaticField,
-                    signature: field_type,
+                    signature: constant_signature(field_type.as_deref(), field_value),
                     docstring,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: has_pub_visibility(node, source),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -395,7 +419,7 @@ impl RustParser {
         let mut symbols = Vec::new();
 
         let language = tree_sitter_rust::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (impl_item
@@ -405,7 +429,7 @@ impl RustParser {
         .context("Failed to create Rust impl query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -439,15 +463,17 @@ impl RustParser {
                 let line_end = node.end_position().row + 1;
 
                 symbols.push(Symbol {
-                    name: format!("impl {}", name),
+                    name: format!("impl {}", name).into(),
                     symbol_type: SymbolType::Class,
                     signature: None,
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: false,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -463,7 +489,7 @@ impl RustParser {
         symbols: &mut Vec<Symbol>,
     ) -> Result<()> {
         let language = tree_sitter_rust::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (function_item
@@ -474,7 +500,7 @@ impl RustParser {
         .context("Failed to create Rust function query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -520,7 +546,7 @@ impl RustParser {
                 };
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: if is_method {
                         SymbolType::Method
                     } else {
@@ -531,8 +557,10 @@ impl RustParser {
                     line_start,
                     line_end,
                     parent_id,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: has_pub_visibility(node, source),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -544,7 +572,7 @@ impl RustParser {
         let mut imports = Vec::new();
 
         let language = tree_sitter_rust::LANGUAGE.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (use_declaration
@@ -554,7 +582,7 @@ impl RustParser {
         .context("Failed to create Rust use query")?;
 
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, tree_root, source.as_bytes());
+        let mut matches = cursor.matches(query, tree_root, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -590,14 +618,9 @@ impl RustParser {
 
 impl ParserTrait for RustParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
-        let mut parser = Parser::new();
         let language = tree_sitter_rust::LANGUAGE.into();
-        parser
-            .set_language(&language)
-            .context("Failed to set Rust language")?;
-
-        let tree = parser
-            .parse(content, None)
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Rust language")?
             .context("Failed to parse Rust file")?;
 
         let root = tree.root_node();
@@ -616,6 +639,7 @@ impl ParserTrait for RustParser {
         result.symbols.extend(impls);
 
         self.process_functions(root, content, file_path, &mut result.symbols)?;
+        result.symbols = schema::with_field_symbols(root, content, Language::Rust, result.symbols);
 
         result.dependencies = self.process_imports(root, content)?;
 
@@ -681,4 +705,38 @@ use anyhow::Result;
         assert!(result.dependencies.len() >= 3);
         Ok(())
     }
+
+    #[test]
+    fn test_parse_constants_and_variants() -> Result<()> {
+        let parser = RustParser::new()?;
+        let source = r#"
+pub const MAX_RETRIES: u32 = 3;
+static GREETING: &str = "hi";
+
+enum Status {
+    Ok = 200,
+    NotFound = 404,
+    Unknown,
+}
+"#;
+        let result = parser.parse(source, Path::new("test.rs"))?;
+        let signature = |name: &str| {
+            result
+                .symbols
+                .iter()
+                .find(|s| s.name == name)
+                .and_then(|s| s.signature.clone())
+        };
+        assert_eq!(signature("MAX_RETRIES").as_deref(), Some("u32 = 3"));
+        assert_eq!(signature("GREETING").as_deref(), Some("&str = \"hi\""));
+        assert_eq!(
+            signature("Status::NotFound").as_deref(),
+            Some("variant of Status = 404")
+        );
+        assert_eq!(
+            signature("Status::Unknown").as_deref(),
+            Some("variant of Status")
+        );
+        Ok(())
+    }
 }
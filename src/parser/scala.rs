@@ -0,0 +1,556 @@
+use super::walk::{child_of_kind, collapsed_text, doc_comment, name_of, Scope};
+use super::{constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::path::Path;
+use tree_sitter::Node;
+
+pub struct ScalaParser;
+
+impl ScalaParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn extract_text(&self, node: Node, source: &str) -> Option<String> {
+        let start = node.start_byte();
+        let end = node.end_byte();
+        if end <= source.len() && start <= end {
+            source.get(start..end).map(|s| s.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// The Scaladoc `/** */` block right above a declaration
+    fn extract_doc_comment(&self, node: Node, source: &str) -> Option<String> {
+        let mut prev = node.prev_named_sibling()?;
+        // Without braces, a comment after an indented block is parsed as the block's last line
+        while prev.kind() != "block_comment" {
+            prev = prev.named_child(prev.named_child_count().checked_sub(1)?)?;
+        }
+        doc_comment(node, Some(prev), source, None)
+    }
+
+    /// Modifier keywords (`final`, `case`, `sealed`, `lazy`, ...), access modifiers left out
+    fn modifiers(&self, node: Node, source: &str) -> Vec<String> {
+        let mut list = Vec::new();
+        if let Some(modifiers) = child_of_kind(node, "modifiers") {
+            let mut cursor = modifiers.walk();
+            list.extend(
+                modifiers
+                    .children(&mut cursor)
+                    .filter(|m| m.kind() != "access_modifier")
+                    .filter_map(|m| self.extract_text(m, source)),
+            );
+        }
+        // `case class` and `case object` keep `case` outside the modifiers
+        if child_of_kind(node, "case").is_some() {
+            list.push("case".to_string());
+        }
+        list
+    }
+
+    /// Members are public unless `private` or `protected`, with or without a qualifier
+    fn is_exported(&self, node: Node, scope: Scope) -> bool {
+        scope.visible
+            && child_of_kind(node, "modifiers")
+                .and_then(|m| child_of_kind(m, "access_modifier"))
+                .is_none()
+    }
+
+    fn symbol(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        name: String,
+        symbol_type: SymbolType,
+        signature: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            name: name.into(),
+            symbol_type,
+            signature,
+            docstring: self.extract_doc_comment(node, source),
+            line_start: node.start_position().row + 1,
+            line_end: node.end_position().row + 1,
+            parent_id: None,
+            file_path: file_path.into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    fn visit(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.declaration(child, source, file_path, scope, symbols);
+        }
+    }
+
+    fn declaration(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        match node.kind() {
+            "class_definition" | "trait_definition" | "object_definition" | "package_object"
+            | "enum_definition" => self.type_definition(node, source, file_path, scope, symbols),
+            "extension_definition" => self.extension(node, source, file_path, scope, symbols),
+            "function_definition" | "function_declaration" => {
+                self.function(node, source, file_path, scope, symbols)
+            }
+            "val_definition" | "var_definition" | "val_declaration" | "var_declaration"
+            | "given_definition" => self.value(node, source, file_path, scope, symbols),
+            "type_definition" => {
+                let Some(name) = name_of(node, source) else {
+                    return;
+                };
+                let aliased = node
+                    .child_by_field_name("type")
+                    .and_then(|t| collapsed_text(t, source));
+                let mut symbol = self.symbol(
+                    node,
+                    source,
+                    file_path,
+                    name,
+                    SymbolType::TypeAlias,
+                    aliased,
+                );
+                symbol.parent_id = scope.parent;
+                symbol.is_exported = self.is_exported(node, scope);
+                symbols.push(symbol);
+            }
+            // `package a.b { ... }` holds declarations of its own
+            "package_clause" => {
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit(body, source, file_path, scope, symbols);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Classes, traits, objects and enums. A trait is an interface; an enum's cases follow
+    /// it as `Color.Red`.
+    fn type_definition(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        let (keyword, symbol_type) = match node.kind() {
+            "trait_definition" => ("trait", SymbolType::Interface),
+            "object_definition" => ("object", SymbolType::Class),
+            "package_object" => ("package object", SymbolType::Class),
+            "enum_definition" => ("enum", SymbolType::Enum),
+            _ => ("class", SymbolType::Class),
+        };
+
+        // `final case class[T](id: String) extends Base with Codec`
+        let mut signature = self.modifiers(node, source);
+        signature.push(keyword.to_string());
+        let mut signature = signature.join(" ");
+        for field in ["type_parameters", "class_parameters"] {
+            if let Some(text) = node
+                .child_by_field_name(field)
+                .and_then(|t| collapsed_text(t, source))
+            {
+                signature.push_str(&text);
+            }
+        }
+        if let Some(extends) = node
+            .child_by_field_name("extend")
+            .and_then(|e| collapsed_text(e, source))
+        {
+            signature.push(' ');
+            signature.push_str(&extends);
+        }
+
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name.clone(),
+            symbol_type,
+            Some(signature),
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = self.is_exported(node, scope);
+        let exported = symbol.is_exported;
+        symbols.push(symbol);
+
+        let inner = Scope {
+            static_members: keyword != "class" && keyword != "trait",
+            ..Scope::member_of(symbols.len() - 1, exported)
+        };
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        if body.kind() == "enum_body" {
+            let mut cursor = body.walk();
+            for cases in body.named_children(&mut cursor) {
+                if cases.kind() != "enum_case_definitions" {
+                    continue;
+                }
+                // `case Red, Green` declares two cases
+                let mut cursor = cases.walk();
+                for case in cases.named_children(&mut cursor) {
+                    let Some(case_name) = name_of(case, source) else {
+                        continue;
+                    };
+                    let mut symbol = self.symbol(
+                        cases,
+                        source,
+                        file_path,
+                        format!("{}.{}", name, case_name),
+                        SymbolType::Enum,
+                        constant_signature(Some(&format!("variant of {}", name)), None),
+                    );
+                    symbol.parent_id = scope.parent;
+                    symbol.is_exported = self.is_exported(cases, scope);
+                    symbols.push(symbol);
+                }
+            }
+        }
+        self.visit(body, source, file_path, inner, symbols);
+    }
+
+    /// Scala 3 `extension (o: Order)` blocks, named after the extended type as
+    /// `extension Order`, with their methods under them
+    fn extension(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(extended) = node
+            .child_by_field_name("parameters")
+            .and_then(|p| child_of_kind(p, "parameter"))
+            .and_then(|p| p.child_by_field_name("type"))
+            .and_then(|t| collapsed_text(t, source))
+        else {
+            return;
+        };
+        let signature = node
+            .child_by_field_name("parameters")
+            .and_then(|p| collapsed_text(p, source))
+            .map(|parameters| format!("extension {}", parameters));
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            format!("extension {}", extended),
+            SymbolType::Class,
+            signature,
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = self.is_exported(node, scope);
+        symbols.push(symbol);
+
+        let inner = Scope::member_of(symbols.len() - 1, scope.visible);
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        // A single method is the body itself
+        match body.kind() {
+            "function_definition" => self.function(body, source, file_path, inner, symbols),
+            _ => self.visit(body, source, file_path, inner, symbols),
+        }
+    }
+
+    /// Functions at the top level, methods in a type: `[T](item: T)(implicit ev: Ord[T]): Unit`
+    fn function(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = name_of(node, source) else {
+            return;
+        };
+        // Type parameters and every parameter list share the `parameters` field
+        let mut cursor = node.walk();
+        let mut signature: String = node
+            .children_by_field_name("parameters", &mut cursor)
+            .filter_map(|p| collapsed_text(p, source))
+            .collect();
+        if let Some(return_type) = node
+            .child_by_field_name("return_type")
+            .and_then(|t| collapsed_text(t, source))
+        {
+            signature.push_str(&format!(": {}", return_type));
+        }
+
+        let symbol_type = match scope.parent {
+            Some(_) => SymbolType::Method,
+            None => SymbolType::Function,
+        };
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name,
+            symbol_type,
+            (!signature.is_empty()).then_some(signature),
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = self.is_exported(node, scope);
+        symbols.push(symbol);
+    }
+
+    /// `val`, `var` and named `given` definitions: static at the top level and in objects,
+    /// with their value; otherwise fields of their declared type. `val a, b = 0` declares
+    /// both names; tuple patterns are skipped.
+    fn value(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(pattern) = node
+            .child_by_field_name("pattern")
+            .or_else(|| node.child_by_field_name("name"))
+        else {
+            return;
+        };
+        let names: Vec<String> = match pattern.kind() {
+            "identifier" => self.extract_text(pattern, source).into_iter().collect(),
+            "identifiers" => {
+                let mut cursor = pattern.walk();
+                let names = pattern
+                    .named_children(&mut cursor)
+                    .filter_map(|n| self.extract_text(n, source))
+                    .collect();
+                names
+            }
+            _ => return,
+        };
+        let type_name = node
+            .child_by_field_name("type")
+            .or_else(|| node.child_by_field_name("return_type"))
+            .and_then(|t| collapsed_text(t, source));
+
+        let (symbol_type, signature) = if scope.static_members {
+            let value = node
+                .child_by_field_name("value")
+                .and_then(|v| self.extract_text(v, source));
+            (
+                SymbolType::StaticField,
+                constant_signature(type_name.as_deref(), value.as_deref()),
+            )
+        } else {
+            (SymbolType::Field, type_name)
+        };
+
+        for name in names {
+            let mut symbol = self.symbol(
+                node,
+                source,
+                file_path,
+                name,
+                symbol_type,
+                signature.clone(),
+            );
+            symbol.parent_id = scope.parent;
+            symbol.is_exported = self.is_exported(node, scope);
+            symbols.push(symbol);
+        }
+    }
+
+    /// `import a.b.C` imports `a.b.C`; `import a.b.{C, D => E}` imports `a.b.C` and `a.b.D`;
+    /// `import a.b._` imports `a.b`
+    fn process_imports(&self, node: Node, source: &str, imports: &mut Vec<Dependency>) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "import_declaration" => {}
+                "package_clause" => {
+                    if let Some(body) = child.child_by_field_name("body") {
+                        self.process_imports(body, source, imports);
+                    }
+                    continue;
+                }
+                _ => continue,
+            }
+            let mut cursor = child.walk();
+            let path: Vec<String> = child
+                .children_by_field_name("path", &mut cursor)
+                .filter(|p| p.kind() == "identifier")
+                .filter_map(|p| self.extract_text(p, source))
+                .collect();
+            if path.is_empty() {
+                continue;
+            }
+            let path = path.join(".");
+
+            let mut names = Vec::new();
+            if let Some(selectors) = child_of_kind(child, "namespace_selectors") {
+                let mut cursor = selectors.walk();
+                for selector in selectors.named_children(&mut cursor) {
+                    let name = match selector.kind() {
+                        "identifier" => self.extract_text(selector, source),
+                        "arrow_renamed_identifier" | "as_renamed_identifier" => {
+                            name_of(selector, source)
+                        }
+                        _ => None,
+                    };
+                    names.extend(name.map(|name| format!("{}.{}", path, name)));
+                }
+            }
+            if names.is_empty() {
+                names.push(path);
+            }
+            imports.extend(names.into_iter().map(|import_name| Dependency {
+                import_name,
+                from_file: None,
+            }));
+        }
+    }
+}
+
+impl ParserTrait for ScalaParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let language: tree_sitter::Language = tree_sitter_scala::LANGUAGE.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Scala language")?
+            .context("Failed to parse Scala file")?;
+
+        let root = tree.root_node();
+        let mut result = ParseResult::new();
+        let scope = Scope {
+            static_members: true,
+            ..Scope::TOP_LEVEL
+        };
+        self.visit(root, content, file_path, scope, &mut result.symbols);
+        self.process_imports(root, content, &mut result.dependencies);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParseResult> {
+        ScalaParser::new()?.parse(source, Path::new("Order.scala"))
+    }
+
+    #[test]
+    fn test_parse_types_and_members() -> Result<()> {
+        let result = parse(
+            r#"package shop
+
+import scala.collection.mutable
+import akka.actor.{Actor, Props => P}
+import org.apache.spark.sql._
+
+/** An order */
+final case class Order(id: String) extends Priced {
+  private var count = 0
+
+  def price(): Double = 1.0
+}
+
+trait Priced {
+  def price(): Double
+}
+
+object Order {
+  val Max = 5
+  def apply[T](id: String)(implicit ev: T): Order = new Order(id)
+}
+
+enum Color {
+  case Red, Green
+}
+
+extension (o: Order)
+  def doubled: Double = o.price() * 2
+
+type Orders = List[Order]
+private def helper(x: Int): Int = x
+"#,
+        )?;
+        let symbols: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("Order", SymbolType::Class, true),
+                ("count", SymbolType::Field, false),
+                ("price", SymbolType::Method, true),
+                ("Priced", SymbolType::Interface, true),
+                ("price", SymbolType::Method, true),
+                ("Order", SymbolType::Class, true),
+                ("Max", SymbolType::StaticField, true),
+                ("apply", SymbolType::Method, true),
+                ("Color", SymbolType::Enum, true),
+                ("Color.Red", SymbolType::Enum, true),
+                ("Color.Green", SymbolType::Enum, true),
+                ("extension Order", SymbolType::Class, true),
+                ("doubled", SymbolType::Method, true),
+                ("Orders", SymbolType::TypeAlias, true),
+                ("helper", SymbolType::Function, false),
+            ]
+        );
+        let order = &result.symbols[0];
+        assert_eq!(
+            order.signature.as_deref(),
+            Some("final case class(id: String) extends Priced")
+        );
+        assert_eq!(order.docstring.as_deref(), Some("/** An order */"));
+        assert_eq!(result.symbols[1].parent_id, Some(0));
+        assert_eq!(result.symbols[6].signature.as_deref(), Some("5"));
+        assert_eq!(
+            result.symbols[7].signature.as_deref(),
+            Some("[T](id: String)(implicit ev: T): Order")
+        );
+        assert_eq!(result.symbols[12].parent_id, Some(11));
+        assert_eq!(result.symbols[13].signature.as_deref(), Some("List[Order]"));
+
+        let imports: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| d.import_name.as_str())
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                "scala.collection.mutable",
+                "akka.actor.Actor",
+                "akka.actor.Props",
+                "org.apache.spark.sql"
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,526 @@
+use super::walk::{child_of_kind, collapsed_text, doc_comment, Scope};
+use super::{constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::path::Path;
+use tree_sitter::Node;
+
+pub struct SwiftParser;
+
+impl SwiftParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn extract_text(&self, node: Node, source: &str) -> Option<String> {
+        let start = node.start_byte();
+        let end = node.end_byte();
+        if end <= source.len() && start <= end {
+            source.get(start..end).map(|s| s.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Every child under `field`; Swift's grammar gives the name and the type the same one
+    fn field_children<'a>(&self, node: Node<'a>, field: &str) -> Vec<Node<'a>> {
+        let mut cursor = node.walk();
+        let children = node.children_by_field_name(field, &mut cursor).collect();
+        children
+    }
+
+    /// Modifier keywords (`final`, `private`, `static`, ...), attributes left out
+    fn modifiers(&self, node: Node, source: &str) -> Vec<(String, &'static str)> {
+        let Some(modifiers) = child_of_kind(node, "modifiers") else {
+            return Vec::new();
+        };
+        let mut cursor = modifiers.walk();
+        let list = modifiers
+            .named_children(&mut cursor)
+            .filter(|m| m.kind() != "attribute")
+            .filter_map(|m| Some((self.extract_text(m, source)?, m.kind())))
+            .collect();
+        list
+    }
+
+    /// `public` and `open` declarations are the module's API; the default `internal` is not
+    fn is_exported(&self, modifiers: &[(String, &str)], scope: Scope) -> bool {
+        let access = modifiers
+            .iter()
+            .find(|(_, kind)| *kind == "visibility_modifier")
+            .map(|(text, _)| text.as_str());
+        scope.visible
+            && match access {
+                Some(access) => access == "public" || access == "open",
+                None => scope.public_members,
+            }
+    }
+
+    fn symbol(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        name: String,
+        symbol_type: SymbolType,
+        signature: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            name: name.into(),
+            symbol_type,
+            signature,
+            docstring: doc_comment(node, node.prev_named_sibling(), source, Some("///")),
+            line_start: node.start_position().row + 1,
+            line_end: node.end_position().row + 1,
+            parent_id: None,
+            file_path: file_path.into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    fn visit(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "class_declaration" | "protocol_declaration" => {
+                    self.type_declaration(child, source, file_path, scope, symbols)
+                }
+                "function_declaration"
+                | "protocol_function_declaration"
+                | "init_declaration"
+                | "deinit_declaration"
+                | "subscript_declaration" => {
+                    self.function(child, source, file_path, scope, symbols)
+                }
+                "property_declaration" | "protocol_property_declaration" => {
+                    self.property(child, source, file_path, scope, symbols)
+                }
+                "typealias_declaration" => {
+                    let names = self.field_children(child, "name");
+                    let Some(name) = names.first().and_then(|n| self.extract_text(*n, source))
+                    else {
+                        continue;
+                    };
+                    let aliased = names.get(1).and_then(|t| collapsed_text(*t, source));
+                    let exported = self.is_exported(&self.modifiers(child, source), scope);
+                    let mut symbol = self.symbol(
+                        child,
+                        source,
+                        file_path,
+                        name,
+                        SymbolType::TypeAlias,
+                        aliased,
+                    );
+                    symbol.parent_id = scope.parent;
+                    symbol.is_exported = exported;
+                    symbols.push(symbol);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Classes, structs, enums, actors, protocols and extensions. An extension is named
+    /// after the type it extends, as `extension Order`.
+    fn type_declaration(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(keyword) = node
+            .child_by_field_name("declaration_kind")
+            .and_then(|k| self.extract_text(k, source))
+        else {
+            return;
+        };
+        let Some(type_name) = node
+            .child_by_field_name("name")
+            .and_then(|n| collapsed_text(n, source))
+        else {
+            return;
+        };
+        let (name, symbol_type) = match keyword.as_str() {
+            "protocol" => (type_name, SymbolType::Interface),
+            "enum" => (type_name, SymbolType::Enum),
+            "extension" => (format!("extension {}", type_name), SymbolType::Class),
+            _ => (type_name, SymbolType::Class),
+        };
+        let modifiers = self.modifiers(node, source);
+
+        // `final class<T>: Base, Codable`
+        let mut signature: Vec<String> = modifiers
+            .iter()
+            .filter(|(_, kind)| *kind != "visibility_modifier")
+            .map(|(text, _)| text.clone())
+            .collect();
+        signature.push(keyword.clone());
+        let mut signature = signature.join(" ");
+        if let Some(type_parameters) =
+            child_of_kind(node, "type_parameters").and_then(|t| collapsed_text(t, source))
+        {
+            signature.push_str(&type_parameters);
+        }
+        let mut cursor = node.walk();
+        let supertypes: Vec<String> = node
+            .named_children(&mut cursor)
+            .filter(|c| c.kind() == "inheritance_specifier")
+            .filter_map(|c| collapsed_text(c, source))
+            .collect();
+        if !supertypes.is_empty() {
+            signature.push_str(&format!(": {}", supertypes.join(", ")));
+        }
+
+        let exported = self.is_exported(&modifiers, scope);
+        let mut symbol = self.symbol(
+            node,
+            source,
+            file_path,
+            name.clone(),
+            symbol_type,
+            Some(signature),
+        );
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+
+        // Members of an extension default to the extension's own access level
+        let declares_public = modifiers
+            .iter()
+            .any(|(text, kind)| *kind == "visibility_modifier" && text == "public");
+        let inner = Scope {
+            public_members: keyword == "protocol" || (keyword == "extension" && declares_public),
+            ..Scope::member_of(symbols.len() - 1, exported || keyword == "extension")
+        };
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        if body.kind() == "enum_class_body" {
+            // Cases, as `Status.open`, then the enum's own members
+            let mut cursor = body.walk();
+            for entry in body.named_children(&mut cursor) {
+                if entry.kind() != "enum_entry" {
+                    continue;
+                }
+                // `case open = "o", closed` declares two cases, each with its raw value
+                let mut cursor = entry.walk();
+                let mut cases: Vec<(String, Option<String>)> = Vec::new();
+                for (i, child) in entry.children(&mut cursor).enumerate() {
+                    match entry.field_name_for_child(i as u32) {
+                        Some("name") => {
+                            if let Some(case) = self.extract_text(child, source) {
+                                cases.push((case, None));
+                            }
+                        }
+                        Some("raw_value") => {
+                            if let Some(last) = cases.last_mut() {
+                                last.1 = self.extract_text(child, source);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                for (case, value) in cases {
+                    let mut symbol = self.symbol(
+                        entry,
+                        source,
+                        file_path,
+                        format!("{}.{}", name, case),
+                        SymbolType::Enum,
+                        constant_signature(Some(&format!("variant of {}", name)), value.as_deref()),
+                    );
+                    symbol.parent_id = scope.parent;
+                    symbol.is_exported = exported;
+                    symbols.push(symbol);
+                }
+            }
+        }
+        self.visit(body, source, file_path, inner, symbols);
+    }
+
+    /// `(_ id: String, times count: Int...)`, one parameter after another on one line
+    fn parameters(&self, node: Node, source: &str) -> String {
+        // A default value is a sibling of its parameter, joined back on here
+        let mut list: Vec<String> = Vec::new();
+        let mut cursor = node.walk();
+        for (i, child) in node.children(&mut cursor).enumerate() {
+            if child.kind() == "parameter" {
+                if let Some(text) = collapsed_text(child, source) {
+                    list.push(text);
+                }
+            } else if node.field_name_for_child(i as u32) == Some("default_value") {
+                if let (Some(last), Some(value)) = (list.last_mut(), collapsed_text(child, source))
+                {
+                    last.push_str(" = ");
+                    last.push_str(&value);
+                }
+            }
+        }
+        format!("({})", list.join(", "))
+    }
+
+    /// Functions at the top level, methods in a type; `init`, `deinit` and `subscript` are
+    /// methods named after their keyword
+    fn function(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let (name, signature) = match node.kind() {
+            "deinit_declaration" => ("deinit".to_string(), None),
+            "subscript_declaration" => {
+                let mut signature = self.parameters(node, source);
+                if let Some(return_type) = self
+                    .field_children(node, "name")
+                    .last()
+                    .and_then(|t| collapsed_text(*t, source))
+                {
+                    signature.push_str(&format!(" -> {}", return_type));
+                }
+                ("subscript".to_string(), Some(signature))
+            }
+            "init_declaration" => {
+                let mut signature = self.parameters(node, source);
+                self.push_effects(node, source, &mut signature);
+                ("init".to_string(), Some(signature))
+            }
+            _ => {
+                let names = self.field_children(node, "name");
+                let Some(name) = names.first().and_then(|n| self.extract_text(*n, source)) else {
+                    return;
+                };
+                // `<T>(name: String) async throws -> Order?`
+                let mut signature = String::new();
+                if let Some(type_parameters) =
+                    child_of_kind(node, "type_parameters").and_then(|t| collapsed_text(t, source))
+                {
+                    signature.push_str(&type_parameters);
+                }
+                signature.push_str(&self.parameters(node, source));
+                self.push_effects(node, source, &mut signature);
+                if let Some(return_type) = names.get(1).and_then(|t| collapsed_text(*t, source)) {
+                    signature.push_str(&format!(" -> {}", return_type));
+                }
+                (name, Some(signature))
+            }
+        };
+
+        let symbol_type = match scope.parent {
+            Some(_) => SymbolType::Method,
+            None => SymbolType::Function,
+        };
+        let exported = self.is_exported(&self.modifiers(node, source), scope);
+        let mut symbol = self.symbol(node, source, file_path, name, symbol_type, signature);
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+    }
+
+    /// ` async throws`, as written between the parameters and the return type
+    fn push_effects(&self, node: Node, source: &str, signature: &mut String) {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if matches!(child.kind(), "async" | "throws") {
+                if let Some(effect) = self.extract_text(child, source) {
+                    signature.push(' ');
+                    signature.push_str(&effect);
+                }
+            }
+        }
+    }
+
+    /// `let`/`var` properties: static at the top level or when `static`/`class`, with their
+    /// value; otherwise fields of their declared type
+    fn property(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        // Tuple patterns (`let (a, b) = pair`) have no single name
+        let Some(name) = node
+            .child_by_field_name("name")
+            .and_then(|p| p.child_by_field_name("bound_identifier"))
+            .and_then(|n| self.extract_text(n, source))
+        else {
+            return;
+        };
+        let type_name = child_of_kind(node, "type_annotation")
+            .and_then(|t| t.child_by_field_name("name"))
+            .and_then(|t| collapsed_text(t, source));
+
+        let modifiers = self.modifiers(node, source);
+        let is_static =
+            scope.parent.is_none() || modifiers.iter().any(|(m, _)| m == "static" || m == "class");
+        let (symbol_type, signature) = if is_static {
+            let value = node
+                .child_by_field_name("value")
+                .and_then(|v| self.extract_text(v, source));
+            (
+                SymbolType::StaticField,
+                constant_signature(type_name.as_deref(), value.as_deref()),
+            )
+        } else {
+            (SymbolType::Field, type_name)
+        };
+
+        let mut symbol = self.symbol(node, source, file_path, name, symbol_type, signature);
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = self.is_exported(&modifiers, scope);
+        symbols.push(symbol);
+    }
+
+    /// `import UIKit` imports `UIKit`; `import struct UIKit.CGPoint` imports `UIKit.CGPoint`
+    fn process_imports(&self, tree_root: Node, source: &str) -> Vec<Dependency> {
+        let mut imports = Vec::new();
+        let mut cursor = tree_root.walk();
+        for node in tree_root.named_children(&mut cursor) {
+            if node.kind() != "import_declaration" {
+                continue;
+            }
+            let Some(path) =
+                child_of_kind(node, "identifier").and_then(|n| self.extract_text(n, source))
+            else {
+                continue;
+            };
+            imports.push(Dependency {
+                import_name: path,
+                from_file: None,
+            });
+        }
+        imports
+    }
+}
+
+impl ParserTrait for SwiftParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let language: tree_sitter::Language = tree_sitter_swift::LANGUAGE.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Swift language")?
+            .context("Failed to parse Swift file")?;
+
+        let root = tree.root_node();
+        let mut result = ParseResult::new();
+        self.visit(
+            root,
+            content,
+            file_path,
+            Scope::TOP_LEVEL,
+            &mut result.symbols,
+        );
+        result.dependencies = self.process_imports(root, content);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParseResult> {
+        SwiftParser::new()?.parse(source, Path::new("Order.swift"))
+    }
+
+    #[test]
+    fn test_parse_types_and_members() -> Result<()> {
+        let result = parse(
+            r#"import Foundation
+import struct UIKit.CGPoint
+
+/// An order
+public final class Order: Priced {
+    public let id: String
+    private var total: Double = 0.0
+    static let max = 5
+
+    public init(id: String) {
+        self.id = id
+    }
+
+    public func price() -> Double { total }
+}
+
+public protocol Priced {
+    func price() -> Double
+}
+
+enum Status: String {
+    case open = "o", closed
+}
+
+public extension Order {
+    func shout<T>(_ times: Int = 1) async throws -> T? { nil }
+}
+
+typealias Orders = [Order]
+let version = "1.0"
+"#,
+        )?;
+        let symbols: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("Order", SymbolType::Class, true),
+                ("id", SymbolType::Field, true),
+                ("total", SymbolType::Field, false),
+                ("max", SymbolType::StaticField, false),
+                ("init", SymbolType::Method, true),
+                ("price", SymbolType::Method, true),
+                ("Priced", SymbolType::Interface, true),
+                ("price", SymbolType::Method, true),
+                ("Status", SymbolType::Enum, false),
+                ("Status.open", SymbolType::Enum, false),
+                ("Status.closed", SymbolType::Enum, false),
+                ("extension Order", SymbolType::Class, true),
+                ("shout", SymbolType::Method, true),
+                ("Orders", SymbolType::TypeAlias, false),
+                ("version", SymbolType::StaticField, false),
+            ]
+        );
+        let order = &result.symbols[0];
+        assert_eq!(order.signature.as_deref(), Some("final class: Priced"));
+        assert_eq!(order.docstring.as_deref(), Some("/// An order"));
+        assert_eq!(result.symbols[1].parent_id, Some(0));
+        assert_eq!(result.symbols[3].signature.as_deref(), Some("5"));
+        assert_eq!(
+            result.symbols[9].signature.as_deref(),
+            Some("variant of Status = \"o\"")
+        );
+        assert_eq!(result.symbols[12].parent_id, Some(11));
+        assert_eq!(
+            result.symbols[12].signature.as_deref(),
+            Some("<T>(_ times: Int = 1) async throws -> T?")
+        );
+        assert_eq!(result.symbols[13].signature.as_deref(), Some("[Order]"));
+
+        let imports: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| d.import_name.as_str())
+            .collect();
+        assert_eq!(imports, ["Foundation", "UIKit.CGPoint"]);
+
+        Ok(())
+    }
+}
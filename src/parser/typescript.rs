@@ -1,9 +1,10 @@
-use super::{ParseResult, Parser};
-use crate::models::{Dependency, Symbol, SymbolType};
+use super::{cached_query, constant_signature, pooled_parse, ParseResult, Parser};
+use crate::models::{Dependency, Language, Symbol, SymbolType};
+use crate::schema;
 use anyhow::{Context, Result};
 use std::path::Path;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Node, Parser as TSParser, Query, QueryCursor};
+use tree_sitter::{Node, Query, QueryCursor};
 
 pub struct TypeScriptParser;
 
@@ -31,15 +32,6 @@ impl TypeScriptParser {
         Ok(Self)
     }
 
-    fn create_parser() -> Result<TSParser> {
-        let mut parser = TSParser::new();
-        let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
-        parser
-            .set_language(&language)
-            .context("Failed to set TypeScript language")?;
-        Ok(parser)
-    }
-
     fn extract_functions(
         &self,
         tree: &tree_sitter::Tree,
@@ -47,7 +39,7 @@ impl TypeScriptParser {
         file_path: &Path,
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             [
@@ -66,7 +58,7 @@ impl TypeScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -75,21 +67,23 @@ impl TypeScriptParser {
                 continue;
             }
 
-            if let Some(name) = self.extract_function_name(captures, source, &query)? {
-                if let Some(node) = self.extract_function_node(captures, &query)? {
+            if let Some(name) = self.extract_function_name(captures, source, query)? {
+                if let Some(node) = self.extract_function_node(captures, query)? {
                     let signature = self.extract_signature(node, source)?;
                     let (line_start, line_end) = self.get_line_range(node);
 
                     symbols.push(Symbol {
-                        name,
+                        name: name.into(),
                         symbol_type: SymbolType::Function,
                         signature: Some(signature),
                         docstring: None,
                         line_start,
                         line_end,
                         parent_id: None,
-                        file_path: file_path.to_path_buf(),
+                        file_path: file_path.into(),
                         is_exported: is_exported(node),
+                        byte_range: None,
+                        annotations: Vec::new(),
                     });
                 }
             }
@@ -246,7 +240,7 @@ impl TypeScriptParser {
         file_path: &Path,
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (class_declaration
@@ -258,7 +252,7 @@ impl TypeScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -293,15 +287,17 @@ impl TypeScriptParser {
                 let (line_start, line_end) = self.get_line_range(def_cap.node);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Class,
                     signature: None,
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: is_exported(def_cap.node),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -317,7 +313,7 @@ impl TypeScriptParser {
         class_symbols: &[Symbol],
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (class_declaration
@@ -331,7 +327,7 @@ impl TypeScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -369,15 +365,17 @@ impl TypeScriptParser {
                 let parent_id = self.find_parent_class(line_start, class_symbols);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Method,
                     signature: Some(signature),
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: false,
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -392,7 +390,7 @@ impl TypeScriptParser {
         file_path: &Path,
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (interface_declaration
@@ -404,7 +402,7 @@ impl TypeScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -439,15 +437,17 @@ impl TypeScriptParser {
                 let (line_start, line_end) = self.get_line_range(def_cap.node);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::Interface,
                     signature: None,
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: is_exported(def_cap.node),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -462,7 +462,7 @@ impl TypeScriptParser {
         file_path: &Path,
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (type_alias_declaration
@@ -474,7 +474,7 @@ impl TypeScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -509,15 +509,17 @@ impl TypeScriptParser {
                 let (line_start, line_end) = self.get_line_range(def_cap.node);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.into(),
                     symbol_type: SymbolType::TypeAlias,
                     signature: None,
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: is_exported(def_cap.node),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
             }
         }
@@ -532,7 +534,7 @@ impl TypeScriptParser {
         file_path: &Path,
     ) -> Result<Vec<Symbol>> {
         let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             (enum_declaration
@@ -544,7 +546,7 @@ impl TypeScriptParser {
         let root_node = tree.root_node();
         let mut symbols = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -579,17 +581,131 @@ impl TypeScriptParser {
                 let (line_start, line_end) = self.get_line_range(def_cap.node);
 
                 symbols.push(Symbol {
-                    name,
+                    name: name.clone().into(),
                     symbol_type: SymbolType::Enum,
                     signature: None,
                     docstring: None,
                     line_start,
                     line_end,
                     parent_id: None,
-                    file_path: file_path.to_path_buf(),
+                    file_path: file_path.into(),
                     is_exported: is_exported(def_cap.node),
+                    byte_range: None,
+                    annotations: Vec::new(),
                 });
+
+                // Members, as `Color.Red`; initialized ones carry their value
+                let Some(body) = def_cap.node.child_by_field_name("body") else {
+                    continue;
+                };
+                let mut body_cursor = body.walk();
+                for member in body.named_children(&mut body_cursor) {
+                    let (member_name, value) = match member.kind() {
+                        "enum_assignment" => (
+                            member.child_by_field_name("name"),
+                            member.child_by_field_name("value"),
+                        ),
+                        "comment" => continue,
+                        _ => (Some(member), None),
+                    };
+                    let Some(member_name) =
+                        member_name.and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                    else {
+                        continue;
+                    };
+                    let value = value.and_then(|v| v.utf8_text(source.as_bytes()).ok());
+                    let (line_start, line_end) = self.get_line_range(member);
+
+                    symbols.push(Symbol {
+                        name: format!("{}.{}", name, member_name).into(),
+                        symbol_type: SymbolType::Enum,
+                        signature: constant_signature(Some(&format!("variant of {}", name)), value),
+                        docstring: None,
+                        line_start,
+                        line_end,
+                        parent_id: None,
+                        file_path: file_path.into(),
+                        is_exported: is_exported(def_cap.node),
+                        byte_range: None,
+                        annotations: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        Ok(symbols)
+    }
+
+    /// `export const` values; exported arrow functions are picked up as functions instead
+    fn extract_constants(
+        &self,
+        tree: &tree_sitter::Tree,
+        source: &str,
+        file_path: &Path,
+    ) -> Result<Vec<Symbol>> {
+        let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
+        let query = cached_query(
+            &language,
+            r#"
+            (export_statement
+                declaration: (lexical_declaration
+                    kind: "const"
+                    (variable_declarator
+                        name: (identifier) @const.name
+                        type: (type_annotation (_) @const.type)?
+                        value: (_) @const.value) @const.def))
+            "#,
+        )
+        .context("Failed to create constant query")?;
+
+        let root_node = tree.root_node();
+        let mut symbols = Vec::new();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
+
+        while let Some(match_) = matches.next() {
+            let mut name = None;
+            let mut type_name = None;
+            let mut value = None;
+            let mut def = None;
+
+            for capture in match_.captures {
+                match query
+                    .capture_names()
+                    .get(capture.index as usize)
+                    .map(|s| s.as_ref())
+                {
+                    Some("const.name") => name = capture.node.utf8_text(source.as_bytes()).ok(),
+                    Some("const.type") => {
+                        type_name = capture.node.utf8_text(source.as_bytes()).ok()
+                    }
+                    Some("const.value") => value = Some(capture.node),
+                    Some("const.def") => def = Some(capture.node),
+                    _ => {}
+                }
+            }
+
+            let (Some(name), Some(value), Some(def)) = (name, value, def) else {
+                continue;
+            };
+            if matches!(value.kind(), "arrow_function" | "function_expression") {
+                continue;
             }
+            let (line_start, line_end) = self.get_line_range(def);
+
+            symbols.push(Symbol {
+                name: name.into(),
+                symbol_type: SymbolType::StaticField,
+                signature: constant_signature(type_name, value.utf8_text(source.as_bytes()).ok()),
+                docstring: None,
+                line_start,
+                line_end,
+                parent_id: None,
+                file_path: file_path.into(),
+                is_exported: true,
+                byte_range: None,
+                annotations: Vec::new(),
+            });
         }
 
         Ok(symbols)
@@ -601,7 +717,7 @@ impl TypeScriptParser {
         source: &str,
     ) -> Result<Vec<Dependency>> {
         let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
-        let query = Query::new(
+        let query = cached_query(
             &language,
             r#"
             [
@@ -618,7 +734,7 @@ impl TypeScriptParser {
         let root_node = tree.root_node();
         let mut dependencies = Vec::new();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, source.as_bytes());
+        let mut matches = cursor.matches(query, root_node, source.as_bytes());
 
         while let Some(match_) = matches.next() {
             let captures = match_.captures;
@@ -696,9 +812,9 @@ impl TypeScriptParser {
 
 impl Parser for TypeScriptParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
-        let mut parser = Self::create_parser()?;
-        let tree = parser
-            .parse(content, None)
+        let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set TypeScript language")?
             .context("Failed to parse TypeScript content")?;
 
         let classes = self.extract_classes(&tree, content, file_path)?;
@@ -707,6 +823,7 @@ impl Parser for TypeScriptParser {
         let interfaces = self.extract_interfaces(&tree, content, file_path)?;
         let type_aliases = self.extract_type_aliases(&tree, content, file_path)?;
         let enums = self.extract_enums(&tree, content, file_path)?;
+        let constants = self.extract_constants(&tree, content, file_path)?;
         let dependencies = self.extract_dependencies(&tree, content)?;
 
         let total_capacity = classes.len()
@@ -714,7 +831,8 @@ impl Parser for TypeScriptParser {
             + methods.len()
             + interfaces.len()
             + type_aliases.len()
-            + enums.len();
+            + enums.len()
+            + constants.len();
         let mut symbols = Vec::with_capacity(total_capacity);
         symbols.extend(classes);
         symbols.extend(functions);
@@ -722,6 +840,9 @@ impl Parser for TypeScriptParser {
         symbols.extend(interfaces);
         symbols.extend(type_aliases);
         symbols.extend(enums);
+        symbols.extend(constants);
+        let symbols =
+            schema::with_field_symbols(tree.root_node(), content, Language::TypeScript, symbols);
 
         Ok(ParseResult {
             symbols,
@@ -1002,4 +1123,45 @@ test('handles empty input', () => {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_parse_constants_and_enum_members() -> Result<()> {
+        let parser = TypeScriptParser::new()?;
+        let content = r#"
+export const MAX_RETRIES: number = 3;
+export const handler = () => {};
+const local = 1;
+
+export enum Level {
+    Debug,
+    Error = "error",
+}
+"#;
+        let result = parser.parse(content, Path::new("test.ts"))?;
+        let signature = |name: &str| {
+            result
+                .symbols
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| (s.symbol_type, s.signature.clone()))
+        };
+        assert_eq!(
+            signature("MAX_RETRIES"),
+            Some((SymbolType::StaticField, Some("number = 3".to_string())))
+        );
+        assert_eq!(
+            signature("handler").map(|s| s.0),
+            Some(SymbolType::Function)
+        );
+        assert_eq!(signature("local"), None);
+        assert_eq!(
+            signature("Level.Debug"),
+            Some((SymbolType::Enum, Some("variant of Level".to_string())))
+        );
+        assert_eq!(
+            signature("Level.Error").and_then(|s| s.1).as_deref(),
+            Some("variant of Level = \"error\"")
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,95 @@
+//! Shared pieces of the parsers that walk the syntax tree by hand rather than running
+//! queries (C++, C#, Kotlin, Lua, PHP, Scala, Swift, Zig)
+
+use tree_sitter::Node;
+
+/// Where a declaration sits while walking the tree. Each language sets the member flags it
+/// has a use for and leaves the others false.
+#[derive(Clone, Copy)]
+pub struct Scope {
+    /// Enclosing type or container; namespaces and packages are not parents
+    pub parent: Option<usize>,
+    /// False inside a declaration that is not public itself
+    pub visible: bool,
+    /// Members are public without an access modifier: interface and protocol requirements,
+    /// `public extension` members, and C++ struct, union and namespace members until an
+    /// access specifier says otherwise
+    pub public_members: bool,
+    /// Members are static: the Kotlin and Scala top level and `object` bodies
+    pub static_members: bool,
+    /// Declarations here are members: C++ methods rather than functions
+    pub in_class: bool,
+}
+
+impl Scope {
+    /// The file itself
+    pub const TOP_LEVEL: Scope = Scope {
+        parent: None,
+        visible: true,
+        public_members: false,
+        static_members: false,
+        in_class: false,
+    };
+
+    /// The body of `symbols[parent]`, with every member flag off
+    pub fn member_of(parent: usize, visible: bool) -> Self {
+        Scope {
+            parent: Some(parent),
+            visible,
+            ..Self::TOP_LEVEL
+        }
+    }
+}
+
+/// Text of a node on one line, however it was laid out
+pub fn collapsed_text(node: Node, source: &str) -> Option<String> {
+    source
+        .get(node.start_byte()..node.end_byte())
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Text of the node's `name` field
+pub fn name_of(node: Node, source: &str) -> Option<String> {
+    let name = node.child_by_field_name("name")?;
+    source.get(name.byte_range()).map(str::to_string)
+}
+
+/// First child of the given kind, named or not
+pub fn child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    let child = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == kind);
+    child
+}
+
+/// The doc comment right above `node`: one `/** */` block, or consecutive lines starting with
+/// `line_prefix` in languages that have doc lines (`///`). The search starts at `above`,
+/// usually the previous sibling, and stops at a blank line.
+pub fn doc_comment(
+    node: Node,
+    above: Option<Node>,
+    source: &str,
+    line_prefix: Option<&str>,
+) -> Option<String> {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut next_row = node.start_position().row;
+    let mut prev = above;
+    while let Some(comment) = prev {
+        if !comment.kind().ends_with("comment") || comment.end_position().row + 1 < next_row {
+            break;
+        }
+        let text = source.get(comment.byte_range())?;
+        if line_prefix.is_some_and(|prefix| text.starts_with(prefix)) {
+            lines.push(text);
+        } else if text.starts_with("/**") && lines.is_empty() {
+            return Some(text.to_string());
+        } else {
+            break;
+        }
+        next_row = comment.start_position().row;
+        prev = comment.prev_named_sibling();
+    }
+    lines.reverse();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
@@ -0,0 +1,400 @@
+use super::walk::{child_of_kind, collapsed_text, doc_comment, Scope};
+use super::{constant_signature, pooled_parse, ParseResult, Parser as ParserTrait};
+use crate::models::{Dependency, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::path::Path;
+use tree_sitter::Node;
+
+pub struct ZigParser;
+
+impl ZigParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn extract_text<'a>(&self, node: Node, source: &'a str) -> Option<&'a str> {
+        let start = node.start_byte();
+        let end = node.end_byte();
+        if end <= source.len() && start <= end {
+            source.get(start..end)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a declaration starts with the `pub`, `export` or `extern` keyword
+    fn has_keyword(&self, node: Node, keyword: &str) -> bool {
+        let mut cursor = node.walk();
+        let found = node
+            .children(&mut cursor)
+            .take_while(|child| !child.is_named() || child.kind() == "string")
+            .any(|child| child.kind() == keyword);
+        found
+    }
+
+    fn symbol(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        name: &str,
+        symbol_type: SymbolType,
+        signature: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            name: name.into(),
+            symbol_type,
+            signature,
+            docstring: doc_comment(node, node.prev_named_sibling(), source, Some("///")),
+            line_start: node.start_position().row + 1,
+            line_end: node.end_position().row + 1,
+            parent_id: None,
+            file_path: file_path.into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Declarations of a file or of a container body
+    fn visit(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "function_declaration" => self.function(child, source, file_path, scope, symbols),
+                "variable_declaration" => self.variable(child, source, file_path, scope, symbols),
+                _ => {}
+            }
+        }
+    }
+
+    /// `pub fn f(a: T) R`, `export fn` and `extern fn`; inside a container, a method
+    fn function(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) = node
+            .child_by_field_name("name")
+            .and_then(|n| self.extract_text(n, source))
+        else {
+            return;
+        };
+        let parameters = child_of_kind(node, "parameters").and_then(|p| collapsed_text(p, source));
+        let return_type = node
+            .child_by_field_name("type")
+            .and_then(|t| collapsed_text(t, source));
+        let signature = match (parameters, return_type) {
+            (Some(parameters), Some(return_type)) => {
+                Some(format!("{} {}", parameters, return_type))
+            }
+            (parameters, _) => parameters,
+        };
+
+        let symbol_type = match scope.parent {
+            Some(_) => SymbolType::Method,
+            None => SymbolType::Function,
+        };
+        let mut symbol = self.symbol(node, source, file_path, name, symbol_type, signature);
+        symbol.parent_id = scope.parent;
+        // An `export fn` is part of the C ABI, whatever container it is in
+        symbol.is_exported =
+            self.has_keyword(node, "export") || (scope.visible && self.has_keyword(node, "pub"));
+        symbols.push(symbol);
+    }
+
+    /// `const Point = struct { ... }` and its kin are types; other `const` and `var`
+    /// declarations are static values. `@import` aliases are dependencies only.
+    fn variable(
+        &self,
+        node: Node,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let Some(name) =
+            child_of_kind(node, "identifier").and_then(|n| self.extract_text(n, source))
+        else {
+            return;
+        };
+        let type_node = node.child_by_field_name("type");
+        let mut cursor = node.walk();
+        let Some(value) = node
+            .named_children(&mut cursor)
+            .filter(|child| Some(*child) != type_node && child.kind() != "identifier")
+            .last()
+            .or_else(|| {
+                // `const x = y;` has the value as its second identifier
+                let mut cursor = node.walk();
+                let value = node
+                    .named_children(&mut cursor)
+                    .filter(|child| child.kind() == "identifier")
+                    .nth(1);
+                value
+            })
+        else {
+            return;
+        };
+        let exported = scope.visible && self.has_keyword(node, "pub");
+
+        let symbol_type = match value.kind() {
+            "struct_declaration" | "union_declaration" => SymbolType::Class,
+            "enum_declaration" => SymbolType::Enum,
+            "builtin_function" if self.is_alias(value, source) => return,
+            _ => {
+                let mut symbol = self.symbol(
+                    node,
+                    source,
+                    file_path,
+                    name,
+                    SymbolType::StaticField,
+                    constant_signature(
+                        type_node.and_then(|t| self.extract_text(t, source)),
+                        self.extract_text(value, source),
+                    ),
+                );
+                symbol.parent_id = scope.parent;
+                symbol.is_exported = exported;
+                symbols.push(symbol);
+                return;
+            }
+        };
+
+        let mut symbol = self.symbol(node, source, file_path, name, symbol_type, None);
+        symbol.parent_id = scope.parent;
+        symbol.is_exported = exported;
+        symbols.push(symbol);
+        let inner = Scope::member_of(symbols.len() - 1, exported);
+        self.container_fields(value, name, source, file_path, inner, symbols);
+        self.visit(value, source, file_path, inner, symbols);
+    }
+
+    /// `@import("std")` and `@This()` name a module or type, they hold no value
+    fn is_alias(&self, node: Node, source: &str) -> bool {
+        let builtin = node
+            .named_child(0)
+            .and_then(|n| self.extract_text(n, source));
+        matches!(builtin, Some("@import" | "@This"))
+    }
+
+    /// Fields of a struct or union, or the `Color.red` variants of an enum
+    fn container_fields(
+        &self,
+        container: Node,
+        container_name: &str,
+        source: &str,
+        file_path: &Path,
+        scope: Scope,
+        symbols: &mut Vec<Symbol>,
+    ) {
+        let enumeration = container.kind() == "enum_declaration";
+        let mut cursor = container.walk();
+        for field in container.named_children(&mut cursor) {
+            if field.kind() != "container_field" {
+                continue;
+            }
+            let Some(name) = field
+                .child_by_field_name("name")
+                .and_then(|n| self.extract_text(n, source))
+            else {
+                continue;
+            };
+            let type_name = field
+                .child_by_field_name("type")
+                .and_then(|t| collapsed_text(t, source));
+            let mut symbol = if enumeration {
+                let mut field_cursor = field.walk();
+                let value = field
+                    .named_children(&mut field_cursor)
+                    .nth(1)
+                    .and_then(|v| self.extract_text(v, source));
+                self.symbol(
+                    field,
+                    source,
+                    file_path,
+                    &format!("{}.{}", container_name, name),
+                    SymbolType::Enum,
+                    constant_signature(Some(&format!("variant of {}", container_name)), value),
+                )
+            } else {
+                self.symbol(field, source, file_path, name, SymbolType::Field, type_name)
+            };
+            symbol.parent_id = scope.parent;
+            // Container fields have no visibility of their own
+            symbol.is_exported = scope.visible;
+            symbols.push(symbol);
+        }
+    }
+
+    /// `@import("std")` and `@import("util.zig")` anywhere in the file
+    fn process_imports(&self, node: Node, source: &str, imports: &mut Vec<Dependency>) {
+        if node.kind() == "builtin_function" {
+            let builtin = node
+                .named_child(0)
+                .and_then(|n| self.extract_text(n, source));
+            let module = node
+                .child_by_field_name("arguments")
+                .or_else(|| node.named_child(1))
+                .and_then(|arguments| arguments.named_child(0))
+                .filter(|argument| argument.kind() == "string")
+                .and_then(|string| string.named_child(0))
+                .and_then(|content| self.extract_text(content, source));
+            if let (Some("@import"), Some(module)) = (builtin, module) {
+                imports.push(Dependency {
+                    import_name: module.to_string(),
+                    from_file: None,
+                });
+            }
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.process_imports(child, source, imports);
+        }
+    }
+}
+
+impl ParserTrait for ZigParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<ParseResult> {
+        let language: tree_sitter::Language = tree_sitter_zig::LANGUAGE.into();
+        let tree = pooled_parse(&language, content)
+            .context("Failed to set Zig language")?
+            .context("Failed to parse Zig file")?;
+
+        let root = tree.root_node();
+        let mut result = ParseResult::new();
+        self.visit(
+            root,
+            content,
+            file_path,
+            Scope::TOP_LEVEL,
+            &mut result.symbols,
+        );
+        self.process_imports(root, content, &mut result.dependencies);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParseResult> {
+        ZigParser::new()?.parse(source, Path::new("shapes.zig"))
+    }
+
+    #[test]
+    fn test_parse_containers_functions_and_imports() -> Result<()> {
+        let result = parse(
+            r#"const std = @import("std");
+const util = @import("util.zig");
+
+/// Most items in a list
+pub const MAX_ITEMS: usize = 100;
+
+/// A point in 2D
+pub const Point = struct {
+    x: f32,
+    y: f32 = 0,
+
+    const Self = @This();
+
+    /// Creates a point
+    pub fn init(x: f32, y: f32) Point {
+        return .{ .x = x, .y = y };
+    }
+
+    fn len(self: Self) f32 {
+        return @sqrt(self.x * self.x + self.y * self.y);
+    }
+};
+
+const Color = enum(u8) {
+    red,
+    green = 2,
+
+    pub fn isRed(self: Color) bool {
+        return self == .red;
+    }
+};
+
+pub const Value = union(enum) {
+    int: i64,
+    float: f64,
+};
+
+export fn c_add(a: c_int, b: c_int) c_int {
+    return a + b;
+}
+
+fn generic(comptime T: type, items: []const T) !void {
+    _ = items;
+}
+
+test "add works" {
+    try std.testing.expect(c_add(1, 2) == 3);
+}
+"#,
+        )?;
+        let symbols: Vec<_> = result
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.is_exported))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("MAX_ITEMS", SymbolType::StaticField, true),
+                ("Point", SymbolType::Class, true),
+                ("x", SymbolType::Field, true),
+                ("y", SymbolType::Field, true),
+                ("init", SymbolType::Method, true),
+                ("len", SymbolType::Method, false),
+                ("Color", SymbolType::Enum, false),
+                ("Color.red", SymbolType::Enum, false),
+                ("Color.green", SymbolType::Enum, false),
+                ("isRed", SymbolType::Method, false),
+                ("Value", SymbolType::Class, true),
+                ("int", SymbolType::Field, true),
+                ("float", SymbolType::Field, true),
+                ("c_add", SymbolType::Function, true),
+                ("generic", SymbolType::Function, false),
+            ]
+        );
+        let init = &result.symbols[4];
+        assert_eq!(init.parent_id, Some(1));
+        assert_eq!(init.signature.as_deref(), Some("(x: f32, y: f32) Point"));
+        assert_eq!(init.docstring.as_deref(), Some("/// Creates a point"));
+        assert_eq!((init.line_start, init.line_end), (15, 17));
+        assert_eq!(result.symbols[0].signature.as_deref(), Some("usize = 100"));
+        assert_eq!(result.symbols[3].signature.as_deref(), Some("f32"));
+        assert_eq!(
+            result.symbols[8].signature.as_deref(),
+            Some("variant of Color = 2")
+        );
+        assert_eq!(
+            result.symbols[14].signature.as_deref(),
+            Some("(comptime T: type, items: []const T) !void")
+        );
+
+        let imports: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| d.import_name.as_str())
+            .collect();
+        assert_eq!(imports, ["std", "util.zig"]);
+
+        Ok(())
+    }
+}
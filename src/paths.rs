@@ -0,0 +1,129 @@
+//! Paths as cm prints and compares them, the same on every platform. Output always uses `/`,
+//! the `\\?\` long-path prefix Windows adds on canonicalization is dropped (`\\?\UNC\server`
+//! becomes `\\server`), extensions match regardless of case, and on Windows paths compare
+//! case-insensitively like the file system does.
+
+use serde::Serializer;
+use std::borrow::Cow;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+/// `path.display()` with `/` separators and without a verbatim prefix
+pub trait DisplaySlash {
+    fn display_slash(&self) -> SlashDisplay<'_>;
+}
+
+impl DisplaySlash for Path {
+    fn display_slash(&self) -> SlashDisplay<'_> {
+        SlashDisplay(self)
+    }
+}
+
+pub struct SlashDisplay<'a>(&'a Path);
+
+impl fmt::Display for SlashDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lossy = self.0.to_string_lossy();
+        let text = strip_verbatim_str(&lossy);
+        // A `\` is an ordinary file name character on Unix
+        if MAIN_SEPARATOR == '\\' {
+            f.pad(&text.replace('\\', "/"))
+        } else {
+            f.pad(&text)
+        }
+    }
+}
+
+/// For `#[serde(serialize_with)]` on path fields of JSON output
+pub fn serialize_slash<S: Serializer>(
+    path: &Option<PathBuf>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match path {
+        Some(path) => serializer.collect_str(&path.display_slash()),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn strip_verbatim_str(path: &str) -> Cow<'_, str> {
+    if let Some(share) = path.strip_prefix(r"\\?\UNC\") {
+        return Cow::Owned(format!(r"\\{}", share));
+    }
+    match path.strip_prefix(r"\\?\") {
+        // Only drive paths; `\\?\Volume{...}` has no other spelling
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => Cow::Borrowed(rest),
+        _ => Cow::Borrowed(path),
+    }
+}
+
+/// `path` without a `\\?\` or `\\?\UNC\` prefix
+pub fn strip_verbatim(path: &Path) -> PathBuf {
+    match path.to_str().map(strip_verbatim_str) {
+        Some(Cow::Owned(stripped)) => PathBuf::from(stripped),
+        Some(Cow::Borrowed(stripped)) if stripped.len() != path.as_os_str().len() => {
+            PathBuf::from(stripped)
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `fs::canonicalize` without the verbatim prefix, so the result still compares equal to
+/// (and strips as a prefix of) paths that were never canonicalized
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    fs::canonicalize(path).map(|canonical| strip_verbatim(&canonical))
+}
+
+/// `path` in the form used to compare it: lowercased where the file system ignores case
+pub fn fold_case(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Whether `path` ends in one of `extensions`, ignoring case (`Main.PY` is a `py` file)
+pub fn has_extension<S: AsRef<str>>(path: &Path, extensions: &[S]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|wanted| wanted.as_ref().eq_ignore_ascii_case(ext))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbatim_prefixes_and_extensions() {
+        assert_eq!(
+            strip_verbatim(Path::new(r"\\?\C:\work\src\main.rs")),
+            PathBuf::from(r"C:\work\src\main.rs")
+        );
+        assert_eq!(
+            strip_verbatim(Path::new(r"\\?\UNC\server\share\lib.rs")),
+            PathBuf::from(r"\\server\share\lib.rs")
+        );
+        assert_eq!(
+            strip_verbatim(Path::new(r"\\?\Volume{1234}\a.rs")),
+            PathBuf::from(r"\\?\Volume{1234}\a.rs")
+        );
+        assert_eq!(
+            strip_verbatim(Path::new("./src/lib.rs")),
+            PathBuf::from("./src/lib.rs")
+        );
+        assert_eq!(
+            format!("[{:<12}]", Path::new("src/a.rs").display_slash()),
+            "[src/a.rs    ]"
+        );
+
+        assert!(has_extension(Path::new("Main.PY"), &["py", "rs"]));
+        assert!(has_extension(Path::new("lib.rs"), &["RS".to_string()]));
+        assert!(!has_extension(Path::new("Makefile"), &["py"]));
+    }
+}
@@ -0,0 +1,215 @@
+//! Inline fuzzy picker for `--pick`: type to filter, arrows to move, Enter to choose.
+//! Draws on stderr below the prompt so stdout only carries the chosen result.
+
+use crate::failure::{ErrorCode, Failure};
+use anyhow::{Context, Result};
+use crossterm::cursor::{MoveToColumn, MoveUp};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{queue, QueueableCommand};
+use std::io::{self, IsTerminal, Write};
+
+/// Rows of results shown under the prompt
+const VISIBLE_ROWS: usize = 10;
+
+/// Score `candidate` against `query` as a case-insensitive subsequence match, or `None`
+/// when some query character is missing. Consecutive characters and matches at word
+/// starts score higher, so `pc` ranks `parse_config` above `upcase`.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut score = 0i64;
+    let mut next = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for (i, c) in chars.iter().enumerate() {
+        if next == query.len() {
+            break;
+        }
+        if c.to_lowercase().next() != Some(query[next]) {
+            continue;
+        }
+
+        score += 1;
+        if previous_match == Some(i.wrapping_sub(1)) {
+            score += 5;
+        }
+        let at_word_start = i == 0
+            || matches!(chars[i - 1], '_' | '-' | '.' | '/' | ':' | ' ')
+            || (c.is_uppercase() && chars[i - 1].is_lowercase());
+        if at_word_start {
+            score += 3;
+        }
+        previous_match = Some(i);
+        next += 1;
+    }
+
+    // Shorter candidates win ties
+    (next == query.len()).then(|| score * 100 - chars.len() as i64)
+}
+
+/// Indices into `items` that match `query`, best first
+pub fn filter(items: &[String], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| fuzzy_score(query, item).map(|score| (score, i)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Let the user choose one of `items`; `None` if they cancel with Esc or Ctrl-C
+pub fn pick(items: &[String], initial_query: &str) -> Result<Option<usize>> {
+    if !io::stderr().is_terminal() || !io::stdin().is_terminal() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            "--pick needs an interactive terminal"
+        ));
+    }
+
+    terminal::enable_raw_mode().context("Failed to enable raw terminal mode")?;
+    let mut stderr = io::stderr();
+    let result = run(&mut stderr, items, initial_query);
+
+    // Leave the terminal as we found it even if drawing failed
+    let _ = queue!(stderr, MoveToColumn(0), Clear(ClearType::FromCursorDown));
+    let _ = stderr.flush();
+    let _ = terminal::disable_raw_mode();
+    result
+}
+
+fn run(out: &mut impl Write, items: &[String], initial_query: &str) -> Result<Option<usize>> {
+    let mut query = initial_query.to_string();
+    let mut matches = filter(items, &query);
+    let mut selected = 0usize;
+
+    loop {
+        draw(out, items, &query, &matches, selected)?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+        if kind != KeyEventKind::Press {
+            continue;
+        }
+
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Enter => return Ok(matches.get(selected).copied()),
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') | KeyCode::Char('g') if ctrl => return Ok(None),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p') | KeyCode::Char('k') if ctrl => {
+                selected = selected.saturating_sub(1)
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                selected = (selected + 1).min(matches.len().saturating_sub(1))
+            }
+            KeyCode::Char('n') | KeyCode::Char('j') if ctrl => {
+                selected = (selected + 1).min(matches.len().saturating_sub(1))
+            }
+            KeyCode::Char('u') if ctrl => {
+                query.clear();
+                matches = filter(items, &query);
+                selected = 0;
+            }
+            KeyCode::Backspace => {
+                query.pop();
+                matches = filter(items, &query);
+                selected = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                query.push(c);
+                matches = filter(items, &query);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(
+    out: &mut impl Write,
+    items: &[String],
+    query: &str,
+    matches: &[usize],
+    selected: usize,
+) -> Result<()> {
+    let width = terminal::size()
+        .map(|(w, _)| w as usize)
+        .unwrap_or(80)
+        .max(10);
+
+    // Keep the selection in view when it moves past the visible rows
+    let first = selected.saturating_sub(VISIBLE_ROWS - 1);
+    let rows: Vec<(usize, &String)> = matches
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(VISIBLE_ROWS)
+        .map(|(pos, &i)| (pos, &items[i]))
+        .collect();
+
+    let prompt = format!("> {}", query);
+    let count = format!("  {}/{}", matches.len(), items.len());
+
+    out.queue(MoveToColumn(0))?
+        .queue(Clear(ClearType::FromCursorDown))?
+        .queue(Print(&prompt))?
+        .queue(SetAttribute(Attribute::Dim))?
+        .queue(Print(&count))?
+        .queue(SetAttribute(Attribute::Reset))?;
+
+    for (pos, item) in &rows {
+        let line: String = item.chars().take(width - 2).collect();
+        out.queue(Print("\r\n"))?;
+        if *pos == selected {
+            out.queue(SetAttribute(Attribute::Reverse))?
+                .queue(Print(format!("> {}", line)))?
+                .queue(SetAttribute(Attribute::Reset))?;
+        } else {
+            out.queue(Print(format!("  {}", line)))?;
+        }
+    }
+
+    if !rows.is_empty() {
+        out.queue(MoveUp(rows.len() as u16))?;
+    }
+    out.queue(MoveToColumn(prompt.chars().count() as u16))?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_ranks_word_starts_and_runs() {
+        let items: Vec<String> = ["upcase", "parse_config", "ParseConfig", "parse", "render"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let ranked: Vec<&str> = filter(&items, "pc")
+            .into_iter()
+            .map(|i| items[i].as_str())
+            .collect();
+        assert_eq!(ranked, vec!["ParseConfig", "parse_config", "upcase"]);
+
+        assert_eq!(fuzzy_score("xyz", "parse"), None);
+        assert_eq!(filter(&items, "").len(), items.len());
+    }
+}
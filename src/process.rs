@@ -0,0 +1,59 @@
+//! External commands that take their input on stdin: parser plugins, embedding commands
+//! and the summarizer
+
+use std::io::{self, Write};
+use std::process::{Child, Output};
+use std::thread;
+
+/// Write `input` to the child's stdin and collect its output. The input is written from
+/// another thread: a command that prints before it has read all of its input would
+/// otherwise fill its output pipe while cm blocks filling its stdin. A command that closes
+/// stdin without reading everything (it may read the file itself) is not an error; its
+/// exit status says how it went.
+pub fn feed_and_wait(mut child: Child, input: &[u8]) -> io::Result<Output> {
+    let stdin = child.stdin.take();
+    let (written, output) = thread::scope(|scope| {
+        let writer = scope.spawn(move || match stdin {
+            Some(mut stdin) => stdin.write_all(input),
+            None => Ok(()),
+        });
+        let output = child.wait_with_output();
+        (writer.join(), output)
+    });
+    match written {
+        Ok(Err(e)) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+        Err(_) => return Err(io::Error::other("writing the command's input panicked")),
+        _ => {}
+    }
+    output
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    fn sh(script: &str) -> Child {
+        Command::new("sh")
+            .args(["-c", script])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_feed_and_wait() {
+        // More than a pipe buffer each way: printing before reading stdin must not deadlock
+        let input = vec![b'x'; 200_000];
+        let output = feed_and_wait(sh("head -c 200000 /dev/zero; wc -c"), &input).unwrap();
+        assert!(output.status.success());
+        let counted = String::from_utf8_lossy(&output.stdout[200_000..]);
+        assert_eq!(counted.trim(), "200000");
+
+        // Exiting without reading is up to the exit status
+        let output = feed_and_wait(sh("exit 3"), &input).unwrap();
+        assert_eq!(output.status.code(), Some(3));
+    }
+}
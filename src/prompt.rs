@@ -0,0 +1,275 @@
+//! `cm prompt`: an orientation preamble for LLM agents. Project size, entrypoints, the
+//! most-called symbols and most central files, and the `cm` commands worth running next,
+//! trimmed to fit a token budget.
+
+use crate::callgraph::{self, EntrypointCategory, EntrypointInfo};
+use crate::index::CodeIndex;
+use crate::models::{Symbol, SymbolType};
+use crate::paths::DisplaySlash;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub struct RankedSymbol<'a> {
+    pub symbol: &'a Symbol,
+    /// Call sites of the name across the project
+    pub calls: usize,
+    /// Symbols sharing the name; calls are split between them when ranking
+    pub definitions: usize,
+}
+
+pub struct RankedFile<'a> {
+    pub path: &'a Path,
+    pub symbols: usize,
+    /// Calls into the file's symbols, split like `RankedSymbol`
+    pub calls: usize,
+}
+
+pub struct Orientation<'a> {
+    pub project: String,
+    pub files: usize,
+    pub symbols: usize,
+    /// Most files first
+    pub languages: Vec<(&'static str, usize)>,
+    /// Most symbols first
+    pub kinds: Vec<(SymbolType, usize)>,
+    pub entrypoints: Vec<EntrypointInfo>,
+    pub top_symbols: Vec<RankedSymbol<'a>>,
+    pub top_files: Vec<RankedFile<'a>>,
+    /// (command, what it shows)
+    pub next_steps: Vec<(String, &'static str)>,
+}
+
+/// Rough token count (about four characters per token for code and English)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+fn ranks(symbol: &Symbol) -> bool {
+    !symbol.name.is_empty()
+        && symbol.name != "anonymous"
+        && !matches!(
+            symbol.symbol_type,
+            SymbolType::Heading
+                | SymbolType::CodeBlock
+                | SymbolType::StaticField
+                | SymbolType::Field
+        )
+}
+
+impl<'a> Orientation<'a> {
+    /// `calls` is `callgraph::call_counts(index)`; each list holds at most `limit` entries
+    pub fn build(
+        index: &'a CodeIndex,
+        root: &Path,
+        calls: &HashMap<String, usize>,
+        limit: usize,
+    ) -> Self {
+        let project = root
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| root.display_slash().to_string());
+
+        let mut languages: HashMap<&'static str, usize> = HashMap::new();
+        let mut kinds: HashMap<SymbolType, usize> = HashMap::new();
+        // name -> (representative definition, definition count)
+        let mut names: HashMap<&str, (&Symbol, usize)> = HashMap::new();
+        let mut source_files: Vec<(&Path, Vec<&Symbol>)> = Vec::new();
+
+        for file in index.files() {
+            *languages.entry(file.language.as_str()).or_default() += 1;
+            let symbols = index.get_file_symbols(&file.path);
+            for symbol in &symbols {
+                *kinds.entry(symbol.symbol_type).or_default() += 1;
+            }
+            if callgraph::is_test_file(&file.path, file.language) {
+                continue;
+            }
+
+            let ranked: Vec<&Symbol> = symbols.into_iter().filter(|s| ranks(s)).collect();
+            for &symbol in &ranked {
+                let entry = names.entry(symbol.name.as_str()).or_insert((symbol, 0));
+                entry.1 += 1;
+                if symbol.is_exported && !entry.0.is_exported {
+                    entry.0 = symbol;
+                }
+            }
+            source_files.push((&file.path, ranked));
+        }
+
+        let share = |name: &str| -> f64 {
+            match (calls.get(name), names.get(name)) {
+                (Some(&calls), Some(&(_, definitions))) => calls as f64 / definitions as f64,
+                _ => 0.0,
+            }
+        };
+
+        let mut top_symbols: Vec<RankedSymbol> = names
+            .values()
+            .filter_map(|&(symbol, definitions)| {
+                let calls = *calls.get(symbol.name.as_str())?;
+                Some(RankedSymbol {
+                    symbol,
+                    calls,
+                    definitions,
+                })
+            })
+            .collect();
+        top_symbols.sort_by(|a, b| {
+            let score = |r: &RankedSymbol| r.calls as f64 / r.definitions as f64;
+            score(b)
+                .total_cmp(&score(a))
+                .then(a.symbol.name.as_str().cmp(b.symbol.name.as_str()))
+        });
+        top_symbols.truncate(limit);
+
+        let mut top_files: Vec<(f64, RankedFile)> = source_files
+            .into_iter()
+            .map(|(path, symbols)| {
+                let score: f64 = symbols.iter().map(|s| share(&s.name)).sum();
+                let file = RankedFile {
+                    path,
+                    symbols: symbols.len(),
+                    calls: score.round() as usize,
+                };
+                (score, file)
+            })
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+        top_files.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.path.cmp(b.1.path)));
+        top_files.truncate(limit);
+        let top_files: Vec<RankedFile> = top_files.into_iter().map(|(_, f)| f).collect();
+
+        let mut entrypoints: Vec<EntrypointInfo> = callgraph::entrypoints_given_calls(index, calls)
+            .into_iter()
+            .filter(|e| e.category != EntrypointCategory::PossiblyUnused)
+            .collect();
+        entrypoints.truncate(limit);
+
+        let mut languages: Vec<(&'static str, usize)> = languages.into_iter().collect();
+        languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let mut kinds: Vec<(SymbolType, usize)> = kinds.into_iter().collect();
+        kinds.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.as_str().cmp(b.0.as_str())));
+
+        let next_steps = next_steps(root, &entrypoints, &top_symbols, &top_files);
+
+        Self {
+            project,
+            files: index.total_files(),
+            symbols: index.total_symbols(),
+            languages,
+            kinds,
+            entrypoints,
+            top_symbols,
+            top_files,
+            next_steps,
+        }
+    }
+
+    /// Render with `render`, dropping entries from the longest list until the text fits
+    /// `budget` tokens (or every list is empty)
+    pub fn fit(&mut self, budget: usize, render: impl Fn(&Self) -> String) -> String {
+        loop {
+            let text = render(self);
+            if estimate_tokens(&text) <= budget {
+                return text;
+            }
+            let longest = [
+                self.entrypoints.len(),
+                self.top_symbols.len(),
+                self.top_files.len(),
+            ];
+            match longest
+                .iter()
+                .enumerate()
+                .max_by_key(|(i, len)| (**len, *i))
+            {
+                Some((_, 0)) | None => return text,
+                Some((0, _)) => drop(self.entrypoints.pop()),
+                Some((1, _)) => drop(self.top_symbols.pop()),
+                Some(_) => drop(self.top_files.pop()),
+            }
+        }
+    }
+}
+
+fn next_steps(
+    root: &Path,
+    entrypoints: &[EntrypointInfo],
+    top_symbols: &[RankedSymbol],
+    top_files: &[RankedFile],
+) -> Vec<(String, &'static str)> {
+    let mut steps = Vec::new();
+    if let Some(file) = top_files.first() {
+        steps.push((
+            format!("cm inspect {}", file.path.display_slash()),
+            "symbols of the most central file",
+        ));
+    }
+    if let Some(top) = top_symbols.first() {
+        let name = &top.symbol.name;
+        steps.push((
+            format!("cm query {} --exact --show-body", name),
+            "read the most-called symbol",
+        ));
+        steps.push((format!("cm callers {}", name), "see where it is used"));
+        if let Some(entry) = entrypoints
+            .iter()
+            .find(|e| e.category == EntrypointCategory::MainEntry && e.name != **name)
+        {
+            steps.push((
+                format!("cm trace {} {}", entry.name, name),
+                "follow the path from the entry point",
+            ));
+        }
+    }
+    steps.push((
+        format!("cm map {} --level 2 --format ai", root.display_slash()),
+        "every file with its symbol counts",
+    ));
+    steps.push((
+        "cm query <name> --format ai".to_string(),
+        "find any symbol by (fuzzy) name",
+    ));
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+    use crate::models::Language;
+
+    #[test]
+    fn test_orientation_ranks_and_fits_budget() {
+        let mut index = CodeIndex::new();
+        let source = "def main():\n    load()\n    load()\n    save()\n\ndef load():\n    pass\n\ndef save():\n    load()\n";
+        index.add_file(index_file(Path::new("app.py"), source, Language::Python, None).unwrap());
+
+        let calls: HashMap<String, usize> = [("load".to_string(), 3), ("save".to_string(), 1)]
+            .into_iter()
+            .collect();
+        let mut orientation = Orientation::build(&index, Path::new("."), &calls, 10);
+
+        let ranked: Vec<(&str, usize)> = orientation
+            .top_symbols
+            .iter()
+            .map(|r| (r.symbol.name.as_str(), r.calls))
+            .collect();
+        assert_eq!(ranked, vec![("load", 3), ("save", 1)]);
+        assert_eq!(orientation.top_files[0].calls, 4);
+        assert!(orientation.next_steps[1].0.starts_with("cm query load"));
+
+        let render = |o: &Orientation| {
+            let mut text = "x".repeat(40);
+            for r in &o.top_symbols {
+                text.push_str(&format!("{}{}", r.symbol.name, " ".repeat(36)));
+            }
+            text
+        };
+        let text = orientation.fit(20, render);
+        assert!(estimate_tokens(&text) <= 20);
+        assert_eq!(orientation.top_symbols.len(), 1);
+        assert_eq!(orientation.top_symbols[0].symbol.name, "load");
+    }
+}
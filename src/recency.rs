@@ -0,0 +1,212 @@
+//! `cm map --since`: which files and symbols changed since a git ref, or within the last N
+//! days, so the map tells recently-touched areas apart from dormant code. Changes are measured
+//! against the working tree, so uncommitted edits count as recent too.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::failure::{ErrorCode, Failure};
+use crate::git;
+use crate::index::CodeIndex;
+use crate::models::Symbol;
+use crate::paths::{self, DisplaySlash};
+
+/// Git's empty tree, the base when the whole history falls within a `--since Nd` window
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// A `--since` value: a commit-ish, or `<N>d` for the last N days
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Since {
+    Ref(String),
+    Days(u64),
+}
+
+impl Since {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("expected a git ref or a number of days such as 14d".to_string());
+        }
+        match text.strip_suffix('d').map(str::parse::<u64>) {
+            Some(Ok(days)) => Ok(Since::Days(days)),
+            _ => Ok(Since::Ref(text.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Since {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Since::Ref(commit) => write!(f, "{}", commit),
+            Since::Days(days) => write!(f, "{}d", days),
+        }
+    }
+}
+
+/// How one file changed since the base
+#[derive(Debug, Clone, Default)]
+pub struct FileChange {
+    /// The file did not exist at the base
+    pub added: bool,
+    /// Commits touching the file since the base; uncommitted edits are not counted
+    pub commits: usize,
+    /// Unix time of the newest of those commits
+    pub last_commit: Option<i64>,
+    /// Changed lines in the working tree as (first line, count); a count of 0 is a deletion
+    /// after that line
+    pub ranges: Vec<(usize, usize)>,
+}
+
+impl FileChange {
+    /// Lines added or rewritten since the base
+    pub fn lines(&self) -> usize {
+        self.ranges.iter().map(|(_, count)| count).sum()
+    }
+
+    pub fn touches(&self, symbol: &Symbol) -> bool {
+        self.added
+            || self.ranges.iter().any(|&(start, count)| {
+                let end = start + count.max(1) - 1;
+                start <= symbol.line_end && end >= symbol.line_start
+            })
+    }
+}
+
+/// Changes since a base, keyed by the index's own file paths
+#[derive(Debug, Clone, Default)]
+pub struct Recency {
+    /// What `--since` said, e.g. `v1.2` or `14d`
+    pub label: String,
+    /// Short hash of the base commit; None when all of history is within the window
+    pub base: Option<String>,
+    /// Commits since the base
+    pub commits: usize,
+    files: HashMap<PathBuf, FileChange>,
+}
+
+impl Recency {
+    pub fn file(&self, path: &Path) -> Option<&FileChange> {
+        self.files.get(path)
+    }
+
+    /// Whether `symbol`'s lines changed since the base
+    pub fn symbol_changed(&self, symbol: &Symbol) -> bool {
+        self.file(&symbol.file_path)
+            .is_some_and(|change| change.touches(symbol))
+    }
+
+    /// Changed files, and how many of those are new
+    pub fn file_counts(&self) -> (usize, usize) {
+        let added = self.files.values().filter(|c| c.added).count();
+        (self.files.len(), added)
+    }
+
+    /// Symbols in `index` whose lines changed since the base
+    pub fn changed_symbols(&self, index: &CodeIndex) -> usize {
+        index
+            .files()
+            .filter_map(|file| Some((file, self.file(&file.path)?)))
+            .map(|(file, change)| {
+                index
+                    .get_file_symbols(&file.path)
+                    .into_iter()
+                    .filter(|s| change.touches(s))
+                    .count()
+            })
+            .sum()
+    }
+}
+
+/// Files and line ranges of `index` that changed since `since`, from git history under `root`
+pub fn since(index: &CodeIndex, root: &Path, since: &Since) -> Result<Recency> {
+    if !git::is_git_repo(root) {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotAGitRepo,
+            format!("--since needs a git repository: {}", root.display_slash())
+        ));
+    }
+
+    let base = match since {
+        Since::Ref(commit) => Some(git::resolve_commit(root, commit)?),
+        Since::Days(days) => git::commit_before_days(root, *days)?,
+    };
+    let hunks = git::diff_hunks(root, base.as_deref().unwrap_or(EMPTY_TREE), None, None)?;
+    let commits = git::commits_since(root, base.as_deref())?;
+
+    // git reports absolute paths under the repository root; the index may not
+    let index_paths: HashMap<PathBuf, &Path> = index
+        .files()
+        .filter_map(|file| Some((paths::canonicalize(&file.path).ok()?, file.path.as_path())))
+        .collect();
+
+    let mut files: HashMap<PathBuf, FileChange> = HashMap::new();
+    for file in hunks {
+        let Some(path) = file.new_path.as_ref().and_then(|p| index_paths.get(p)) else {
+            continue;
+        };
+        let change = files.entry(path.to_path_buf()).or_default();
+        change.added = file.old_path.is_none();
+        change.ranges = file.new_ranges;
+    }
+    for (path, change) in files.iter_mut() {
+        let canonical = paths::canonicalize(path).unwrap_or_else(|_| path.clone());
+        if let Some(&(count, newest)) = commits.get(&canonical) {
+            change.commits = count;
+            change.last_commit = Some(newest);
+        }
+    }
+
+    let total_commits = match &base {
+        Some(base) => git::commit_count(root, &format!("{}..HEAD", base))?,
+        None => git::commit_count(root, "HEAD")?,
+    };
+    Ok(Recency {
+        label: since.to_string(),
+        base: base.map(|hash| hash.chars().take(7).collect()),
+        commits: total_commits,
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SymbolType;
+
+    #[test]
+    fn test_since_parse() {
+        assert_eq!(Since::parse("14d"), Ok(Since::Days(14)));
+        assert_eq!(Since::parse("v1.2"), Ok(Since::Ref("v1.2".to_string())));
+        // A branch that happens to end in `d` stays a ref
+        assert_eq!(Since::parse("old"), Ok(Since::Ref("old".to_string())));
+        assert!(Since::parse(" ").is_err());
+    }
+
+    #[test]
+    fn test_file_change_touches() {
+        let symbol = |line_start, line_end| Symbol {
+            name: "f".into(),
+            symbol_type: SymbolType::Function,
+            signature: None,
+            docstring: None,
+            line_start,
+            line_end,
+            parent_id: None,
+            file_path: Path::new("a.py").into(),
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        };
+        let change = FileChange {
+            ranges: vec![(10, 2), (30, 0)],
+            ..FileChange::default()
+        };
+        assert!(change.touches(&symbol(5, 10)));
+        assert!(change.touches(&symbol(11, 20)));
+        assert!(!change.touches(&symbol(12, 29)));
+        assert!(change.touches(&symbol(30, 31)));
+        assert_eq!(change.lines(), 2);
+    }
+}
@@ -0,0 +1,311 @@
+//! `--redact`: pseudonymize file names, identifiers, string literals and docstrings so a
+//! structural map can be shared outside the team. Kinds, counts, sizes and line ranges
+//! are kept, so the shape of the code survives while its vocabulary does not.
+
+use crate::annotations::Annotation;
+use crate::failure::{ErrorCode, Failure};
+use crate::index::CodeIndex;
+use crate::models::{Dependency, FileInfo, Symbol};
+use anyhow::Result;
+use std::path::{Component, Path, PathBuf};
+
+/// Words left as-is because they are language vocabulary, not project vocabulary
+const KEEP: &[&str] = &[
+    "self",
+    "Self",
+    "cls",
+    "this",
+    "super",
+    "impl",
+    "for",
+    "fn",
+    "def",
+    "function",
+    "class",
+    "struct",
+    "enum",
+    "trait",
+    "interface",
+    "type",
+    "async",
+    "await",
+    "pub",
+    "crate",
+    "mut",
+    "const",
+    "static",
+    "let",
+    "var",
+    "dyn",
+    "ref",
+    "where",
+    "in",
+    "of",
+    "new",
+    "return",
+    "int",
+    "float",
+    "str",
+    "bool",
+    "char",
+    "bytes",
+    "void",
+    "None",
+    "True",
+    "False",
+    "null",
+    "undefined",
+    "nil",
+    "true",
+    "false",
+    "string",
+    "number",
+    "boolean",
+    "any",
+    "unknown",
+    "object",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "f32",
+    "f64",
+    "String",
+    "Vec",
+    "Option",
+    "Result",
+    "Box",
+    "Arc",
+    "Rc",
+    "Some",
+    "Ok",
+    "Err",
+    "List",
+    "Dict",
+    "Set",
+    "Tuple",
+    "Optional",
+    "Any",
+    "Callable",
+    "Promise",
+    "Array",
+    "Map",
+    "Object",
+    "Integer",
+    "Long",
+    "Double",
+    "Boolean",
+    "Float",
+    "error",
+];
+
+pub struct Redactor {
+    key: [u8; 32],
+}
+
+impl Redactor {
+    /// Pseudonyms are stable for a given salt; without one, every run draws a fresh key
+    /// from the OS random source so names cannot be recovered by hashing guesses
+    pub fn new(salt: Option<&str>) -> Result<Self> {
+        let key = match salt {
+            Some(salt) => blake3::derive_key("codemapper redact v1", salt.as_bytes()),
+            None => {
+                let mut key = [0u8; 32];
+                getrandom::fill(&mut key).map_err(|e| {
+                    Failure::new(
+                        ErrorCode::Io,
+                        format!("Failed to draw a redaction salt: {}", e),
+                    )
+                })?;
+                key
+            }
+        };
+        Ok(Self { key })
+    }
+
+    fn pseudonym(&self, prefix: &str, word: &str) -> String {
+        let hash = blake3::keyed_hash(&self.key, word.as_bytes()).to_hex();
+        format!("{}{}", prefix, &hash[..8])
+    }
+
+    /// Every path component renamed; file extensions are kept so languages still show
+    pub fn path(&self, path: &Path) -> PathBuf {
+        let components: Vec<Component> = path.components().collect();
+        components
+            .iter()
+            .enumerate()
+            .map(|(i, component)| match component {
+                Component::Normal(part) if i + 1 == components.len() => {
+                    let part = Path::new(part);
+                    let stem = part.file_stem().unwrap_or_default().to_string_lossy();
+                    let name = self.pseudonym("f_", &stem);
+                    match part.extension() {
+                        Some(ext) => format!("{}.{}", name, ext.to_string_lossy()).into(),
+                        None => name.into(),
+                    }
+                }
+                Component::Normal(part) => self.pseudonym("d_", &part.to_string_lossy()).into(),
+                other => other.as_os_str().to_os_string(),
+            })
+            .collect()
+    }
+
+    /// Identifiers replaced (the same word always maps to the same pseudonym), string
+    /// literals blanked, punctuation and language keywords kept
+    pub fn text(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '"' || c == '\'' || c == '`' {
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    if next == c && !escaped {
+                        break;
+                    }
+                    escaped = next == '\\' && !escaped;
+                }
+                out.push(c);
+                out.push(c);
+            } else if c.is_alphabetic() || c == '_' {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                if KEEP.contains(&word.as_str()) {
+                    out.push_str(&word);
+                } else {
+                    out.push_str(&self.pseudonym("id_", &word));
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    pub fn symbol(&self, symbol: &Symbol, file_path: &Path) -> Symbol {
+        Symbol {
+            name: self.text(&symbol.name).into(),
+            signature: symbol.signature.as_deref().map(|s| self.text(s)),
+            docstring: symbol.docstring.as_ref().map(|_| "[redacted]".to_string()),
+            file_path: file_path.into(),
+            byte_range: None,
+            // Tags say what a symbol is for; values may name people or teams
+            annotations: symbol
+                .annotations
+                .iter()
+                .map(|a| Annotation {
+                    tag: a.tag.clone(),
+                    value: a.value.as_deref().map(|v| self.text(v)),
+                })
+                .collect(),
+            ..symbol.clone()
+        }
+    }
+
+    /// A copy of `index` with every file, symbol and import redacted
+    pub fn index(&self, index: &CodeIndex) -> CodeIndex {
+        let mut redacted = CodeIndex::new();
+        for file in index.files() {
+            let path = self.path(&file.path);
+            let mut info = FileInfo::new(
+                path.clone(),
+                file.language,
+                file.size,
+                self.pseudonym("blake3:", &file.hash),
+            );
+            info.lines = file.lines;
+            info.symbols = index
+                .get_file_symbols(&file.path)
+                .into_iter()
+                .map(|s| self.symbol(s, &path))
+                .collect();
+            info.dependencies = index
+                .get_dependencies(&file.path)
+                .into_iter()
+                .flatten()
+                .map(|import| Dependency {
+                    import_name: self.text(import),
+                    from_file: None,
+                })
+                .collect();
+            redacted.add_file(info);
+        }
+        redacted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+    use crate::models::Language;
+
+    #[test]
+    fn test_redaction_keeps_structure() {
+        let redactor = Redactor::new(Some("salt")).unwrap();
+        let source = "class Billing:\n    \"\"\"Charges cards.\"\"\"\n    def charge(self, card: str = \"visa\"):\n        pass\n";
+        let mut index = CodeIndex::new();
+        index.add_file(
+            index_file(
+                Path::new("./acme/billing.py"),
+                source,
+                Language::Python,
+                None,
+            )
+            .unwrap(),
+        );
+
+        let redacted = redactor.index(&index);
+        let file = redacted.files().next().unwrap();
+        let path = file.path.to_string_lossy().to_string();
+        assert!(path.starts_with("./d_") && path.ends_with(".py"));
+        assert!(!path.contains("acme") && !path.contains("billing"));
+
+        let original = index.get_file_symbols(Path::new("./acme/billing.py"));
+        let symbols = redacted.get_file_symbols(&file.path);
+        assert_eq!(symbols.len(), original.len());
+        for (before, after) in original.iter().zip(&symbols) {
+            assert_eq!(before.symbol_type, after.symbol_type);
+            assert_eq!(
+                (before.line_start, before.line_end),
+                (after.line_start, after.line_end)
+            );
+            assert_eq!(before.docstring.is_some(), after.docstring.is_some());
+            assert!(!after.name.contains("harge") && !after.name.contains("illing"));
+        }
+
+        // The same word maps to the same pseudonym, everywhere and on every run
+        let charge = symbols
+            .iter()
+            .find(|s| s.name == redactor.text("charge"))
+            .unwrap();
+        let signature = charge.signature.as_deref().unwrap();
+        assert!(signature.contains("self") && signature.contains("str"));
+        assert!(!signature.contains("visa") && !signature.contains("card"));
+        assert_eq!(
+            Redactor::new(Some("salt")).unwrap().text("Billing"),
+            redactor.text("Billing")
+        );
+        assert_ne!(
+            Redactor::new(Some("other")).unwrap().text("Billing"),
+            redactor.text("Billing")
+        );
+        assert_ne!(
+            Redactor::new(None).unwrap().text("Billing"),
+            Redactor::new(None).unwrap().text("Billing")
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Result cache for `cm query`, `cm callers` and `cm deps`: what a command printed, kept in
+//! `.codemapper/results/` under a key made of its arguments, working directory and `CM_*`
+//! settings plus the generation of the index it ran against. Agents repeat identical
+//! queries a lot; while no indexed file has changed, a repeat prints the stored output
+//! without loading the index. Output is captured through the same stdout tee as the command
+//! history, and only a successful, complete run is stored.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+/// Results directory, inside the cache base directory (`.codemapper/`)
+pub const RESULTS_DIR: &str = "results";
+/// Set to any value to neither read nor store results
+pub const ENV_NO_RESULT_CACHE: &str = "CM_NO_RESULT_CACHE";
+/// Older results are dropped past this many
+const MAX_RESULTS: usize = 200;
+/// A result larger than this is not stored
+const MAX_OUTPUT_BYTES: usize = 4 * 1024 * 1024;
+
+/// Key of a command run: `args` as given in `cwd`, the extensions it indexes, the index
+/// `generation` and every setting that can change what it prints
+pub fn key(args: &[String], cwd: &Path, extensions: &str, generation: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(cwd.to_string_lossy().as_bytes());
+    for part in args
+        .iter()
+        .map(String::as_str)
+        .chain([extensions, generation])
+    {
+        hasher.update(b"\0");
+        hasher.update(part.as_bytes());
+    }
+    let mut settings: Vec<(String, String)> = std::env::vars()
+        .filter(|(name, _)| {
+            name.starts_with("CM_") || matches!(name.as_str(), "NO_COLOR" | "CLICOLOR_FORCE")
+        })
+        .collect();
+    settings.sort();
+    for (name, value) in settings {
+        hasher.update(format!("\0{}={}", name, value).as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn result_path(base_dir: &Path, key: &str) -> PathBuf {
+    base_dir
+        .join(RESULTS_DIR)
+        .join(format!("{}.out", &key[..32]))
+}
+
+/// Stored output for `key`, if any
+pub fn lookup(base_dir: &Path, key: &str) -> Option<Vec<u8>> {
+    if std::env::var_os(ENV_NO_RESULT_CACHE).is_some() {
+        return None;
+    }
+    fs::read(result_path(base_dir, key)).ok()
+}
+
+struct Recording {
+    path: PathBuf,
+    output: Vec<u8>,
+    /// Output went past `MAX_OUTPUT_BYTES`
+    overflowed: bool,
+}
+
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+fn recording() -> std::sync::MutexGuard<'static, Option<Recording>> {
+    RECORDING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start capturing stdout to store as the result for `key` when the command succeeds
+pub fn start(base_dir: &Path, key: &str) {
+    if std::env::var_os(ENV_NO_RESULT_CACHE).is_some() {
+        return;
+    }
+    *recording() = Some(Recording {
+        path: result_path(base_dir, key),
+        output: Vec::new(),
+        overflowed: false,
+    });
+}
+
+/// Add bytes printed to stdout to the result being recorded, if any
+pub fn capture(bytes: &[u8]) {
+    if let Some(recording) = recording().as_mut() {
+        if recording.output.len() + bytes.len() > MAX_OUTPUT_BYTES {
+            recording.overflowed = true;
+        } else {
+            recording.output.extend_from_slice(bytes);
+        }
+    }
+}
+
+/// Store the recorded result if the command exited with 0 and was not cut short by
+/// `--timeout`. Like the history, a failure to store never fails the command.
+pub fn finish(exit_code: i32) -> std::io::Result<()> {
+    let Some(recording) = recording().take() else {
+        return Ok(());
+    };
+    if exit_code != 0 || recording.overflowed || crate::deadline::truncated() {
+        return Ok(());
+    }
+    store(&recording.path, &recording.output)
+}
+
+fn store(path: &Path, output: &[u8]) -> std::io::Result<()> {
+    let Some(dir) = path.parent() else {
+        return Ok(());
+    };
+    fs::create_dir_all(dir)?;
+    fs::write(path, output)?;
+    prune(dir);
+    Ok(())
+}
+
+/// Drop the least recently stored results past `MAX_RESULTS`
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut results: Vec<_> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+    if results.len() <= MAX_RESULTS {
+        return;
+    }
+    results.sort();
+    for (_, path) in &results[..results.len() - MAX_RESULTS] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = vec!["query".to_string(), "parse".to_string()];
+        let key = key(&args, dir.path(), "py", "gen-1");
+        assert_ne!(key, super::key(&args, dir.path(), "py", "gen-2"));
+        assert_eq!(lookup(dir.path(), &key), None);
+
+        store(&result_path(dir.path(), &key), b"[RESULTS:1]\n").unwrap();
+        assert_eq!(lookup(dir.path(), &key).unwrap(), b"[RESULTS:1]\n");
+
+        let results = dir.path().join(RESULTS_DIR);
+        for i in 0..MAX_RESULTS {
+            fs::write(results.join(format!("{}.out", i)), "").unwrap();
+        }
+        store(&results.join("last.out"), b"").unwrap();
+        assert_eq!(fs::read_dir(&results).unwrap().count(), MAX_RESULTS);
+    }
+}
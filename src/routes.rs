@@ -0,0 +1,721 @@
+//! `cm routes`: the HTTP routes a service declares, read from each framework's routing
+//! conventions: FastAPI/Flask decorators, Express-style `app.get(...)` and NestJS
+//! decorators, axum routers and actix/rocket attributes, Spring mapping annotations, and
+//! Go's net/http, gorilla/mux, gin, echo and chi.
+
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::CodeIndex;
+use crate::models::Language;
+use crate::scan::regex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// Upper-case HTTP method, or `ANY` when the route accepts every method
+    pub method: String,
+    pub path: String,
+    /// Function handling the route; `None` for inline closures
+    pub handler: Option<String>,
+    pub framework: &'static str,
+    pub file_path: PathBuf,
+    pub line: usize,
+}
+
+pub fn find_routes(index: &CodeIndex) -> Result<Vec<Route>> {
+    let mut routes = Vec::new();
+
+    for file in index.files() {
+        let content = fs::read_to_string(&file.path).unwrap_or_default();
+        if content.is_empty() {
+            continue;
+        }
+        routes.extend(routes_in(&content, file.language, &file.path));
+    }
+
+    routes.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then_with(|| a.method.cmp(&b.method))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+
+    Ok(routes)
+}
+
+/// Routes declared in one file
+pub fn routes_in(content: &str, language: Language, file_path: &Path) -> Vec<Route> {
+    let lines: Vec<&str> = content.lines().collect();
+    let found = match language {
+        Language::Python => python_routes(content, &lines),
+        Language::JavaScript | Language::TypeScript => js_routes(content, &lines),
+        Language::Rust => rust_routes(content, &lines),
+        Language::Java => java_routes(&lines),
+        Language::Go => go_routes(content, &lines),
+        _ => Vec::new(),
+    };
+
+    found
+        .into_iter()
+        .map(|(method, path, handler, framework, line)| Route {
+            method,
+            path,
+            handler,
+            framework,
+            file_path: file_path.to_path_buf(),
+            line,
+        })
+        .collect()
+}
+
+/// (method, path, handler, framework, 1-based line)
+type Found = (String, String, Option<String>, &'static str, usize);
+
+/// Text of the parenthesized group opening at or after `col` on `lines[line]`, which may
+/// span several lines. With `chained`, method calls following the group (`.route(...)`)
+/// are included too.
+pub(crate) fn group_text(lines: &[&str], line: usize, col: usize, chained: bool) -> String {
+    let mut text = String::new();
+    let mut depth = 0usize;
+    let mut opened = false;
+    let mut closed = false;
+
+    for (i, l) in lines.iter().enumerate().skip(line).take(20) {
+        let start = if i == line { col.min(l.len()) } else { 0 };
+        for c in l[start..].chars() {
+            if closed {
+                if c.is_whitespace() {
+                    continue;
+                }
+                if c != '.' {
+                    return text;
+                }
+                closed = false;
+            }
+            text.push(c);
+            match c {
+                '(' => {
+                    depth += 1;
+                    opened = true;
+                }
+                ')' if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 && opened {
+                        if !chained {
+                            return text;
+                        }
+                        closed = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Arguments between the first `(` of `text` and its matching `)`
+pub(crate) fn args(text: &str) -> &str {
+    let Some(open) = text.find('(') else {
+        return "";
+    };
+    let mut depth = 0;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &text[open + 1..open + i];
+                }
+            }
+            _ => {}
+        }
+    }
+    &text[open + 1..]
+}
+
+/// Top-level comma-separated arguments
+pub(crate) fn split_args(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(args[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// The last argument when it names a function (`listUsers`, `handlers.Get`), not a closure
+fn handler_arg(args: &str) -> Option<String> {
+    let last = *split_args(args).last()?;
+    let named = last
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.' | ':'))
+        && last.chars().next().is_some_and(|c| !c.is_ascii_digit());
+    named.then(|| last.to_string())
+}
+
+/// Content of the first string literal in `text`
+pub(crate) fn first_string(text: &str) -> Option<String> {
+    let start = text.find(['"', '\'', '`'])?;
+    let quote = text[start..].chars().next()?;
+    let rest = &text[start + 1..];
+    rest.find(quote).map(|end| rest[..end].to_string())
+}
+
+/// The first match of `re` in the lines after `line`, skipping decorators and annotations
+fn next_definition(lines: &[&str], line: usize, re: &Regex) -> Option<String> {
+    for l in lines.iter().skip(line + 1).take(12) {
+        let trimmed = l.trim();
+        if trimmed.is_empty()
+            || trimmed.starts_with('@')
+            || trimmed.starts_with("#[")
+            || trimmed.starts_with("//")
+            || trimmed.starts_with('#')
+            || trimmed.starts_with(')')
+            || trimmed.starts_with('}')
+        {
+            continue;
+        }
+        if let Some(caps) = re.captures(trimmed) {
+            return caps.get(1).map(|m| m.as_str().to_string());
+        }
+    }
+    None
+}
+
+fn join_path(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    let path = path.trim_start_matches('/');
+    match (prefix.is_empty(), path.is_empty()) {
+        (true, true) => "/".to_string(),
+        (_, true) => prefix.to_string(),
+        _ => format!("{}/{}", prefix, path),
+    }
+}
+
+fn leading_slash(path: String) -> String {
+    if path.starts_with('/') || path.starts_with('*') {
+        path
+    } else {
+        format!("/{}", path)
+    }
+}
+
+fn quoted_methods(text: &str) -> Vec<String> {
+    regex(r#"["'](\w+)["']"#)
+        .captures_iter(text)
+        .filter_map(|c| c.get(1).map(|m| m.as_str().to_uppercase()))
+        .collect()
+}
+
+fn python_routes(content: &str, lines: &[&str]) -> Vec<Found> {
+    let framework = if content.contains("fastapi") {
+        "fastapi"
+    } else if content.contains("flask") {
+        "flask"
+    } else {
+        "python"
+    };
+    let router_re = regex(r"^\s*(\w+)\s*=\s*(?:\w+\.)?(?:APIRouter|Blueprint)\s*\(");
+    let prefix_re = regex(r#"(?:url_)?prefix\s*=\s*[rbuf]*["']([^"']*)["']"#);
+    let decorator_re =
+        regex(r"^\s*@(\w+)\.(get|post|put|delete|patch|head|options|route|api_route)\s*\(");
+    let methods_re = regex(r"methods\s*=\s*[\[({]([^\])}]*)");
+    let def_re = regex(r"^(?:async\s+)?def\s+(\w+)");
+
+    let mut prefixes: HashMap<&str, String> = HashMap::new();
+    let mut results = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = router_re.captures(line) {
+            let call = group_text(lines, i, 0, false);
+            if let Some(prefix) = prefix_re.captures(args(&call)).and_then(|c| c.get(1)) {
+                prefixes.insert(
+                    caps.get(1).map_or("", |m| m.as_str()),
+                    prefix.as_str().to_string(),
+                );
+            }
+            continue;
+        }
+
+        let Some(caps) = decorator_re.captures(line) else {
+            continue;
+        };
+        let object = caps.get(1).map_or("", |m| m.as_str());
+        let verb = caps.get(2).map_or("", |m| m.as_str());
+        let call = group_text(lines, i, 0, false);
+        let call_args = args(&call);
+        let path = split_args(call_args)
+            .first()
+            .and_then(|first| first_string(first))
+            .unwrap_or_default();
+        let path = match prefixes.get(object) {
+            Some(prefix) => join_path(prefix, &path),
+            None => path,
+        };
+
+        let methods = if matches!(verb, "route" | "api_route") {
+            let listed = methods_re
+                .captures(call_args)
+                .map(|c| quoted_methods(c.get(1).map_or("", |m| m.as_str())))
+                .unwrap_or_default();
+            if listed.is_empty() {
+                vec!["GET".to_string()]
+            } else {
+                listed
+            }
+        } else {
+            vec![verb.to_uppercase()]
+        };
+
+        let handler = next_definition(lines, i, &def_re);
+        for method in methods {
+            results.push((method, path.clone(), handler.clone(), framework, i + 1));
+        }
+    }
+
+    results
+}
+
+fn js_routes(content: &str, lines: &[&str]) -> Vec<Found> {
+    let framework = if content.contains("fastify") {
+        "fastify"
+    } else if content.contains("hono") {
+        "hono"
+    } else if content.contains("koa") {
+        "koa"
+    } else {
+        "express"
+    };
+    let call_re = regex(r"\b(\w+)\.(get|post|put|delete|patch|head|options|all)\s*\(\s*['`]");
+    let call_dq_re = regex(r#"\b(\w+)\.(get|post|put|delete|patch|head|options|all)\s*\(\s*""#);
+    let controller_re = regex(r"^\s*@Controller\s*\(");
+    let decorator_re = regex(r"^\s*@(Get|Post|Put|Delete|Patch|Head|Options|All)\s*\(");
+    let method_re = regex(r"^(?:(?:public|private|protected|static|async)\s+)*(\w+)\s*\(");
+
+    let mut controller_prefix = String::new();
+    let mut results = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if controller_re.is_match(line) {
+            controller_prefix =
+                first_string(args(&group_text(lines, i, 0, false))).unwrap_or_default();
+            continue;
+        }
+
+        if let Some(caps) = decorator_re.captures(line) {
+            let verb = caps.get(1).map_or("", |m| m.as_str());
+            let path = first_string(args(&group_text(lines, i, 0, false))).unwrap_or_default();
+            let method = match verb {
+                "All" => "ANY".to_string(),
+                other => other.to_uppercase(),
+            };
+            results.push((
+                method,
+                leading_slash(join_path(&controller_prefix, &path)),
+                next_definition(lines, i, &method_re),
+                "nestjs",
+                i + 1,
+            ));
+            continue;
+        }
+
+        let Some(caps) = call_re.captures(line).or_else(|| call_dq_re.captures(line)) else {
+            continue;
+        };
+        let verb = caps.get(2).map_or("", |m| m.as_str());
+        let start = caps.get(0).map_or(0, |m| m.start());
+        let call = group_text(lines, i, start, false);
+        let call_args = args(&call);
+        let Some(path) = split_args(call_args).first().and_then(|a| first_string(a)) else {
+            continue;
+        };
+        // `map.get('key')` and `headers.get("x")` are not routes
+        if !path.starts_with('/') && path != "*" {
+            continue;
+        }
+        let method = match verb {
+            "all" => "ANY".to_string(),
+            other => other.to_uppercase(),
+        };
+        let handler = if split_args(call_args).len() > 1 {
+            handler_arg(call_args)
+        } else {
+            None
+        };
+        results.push((method, path, handler, framework, i + 1));
+    }
+
+    results
+}
+
+fn rust_routes(content: &str, lines: &[&str]) -> Vec<Found> {
+    let attribute_framework = if content.contains("rocket") {
+        "rocket"
+    } else {
+        "actix"
+    };
+    let route_re = regex(r#"\.route\s*\(\s*""#);
+    let resource_re = regex(r#"web::resource\s*\(\s*""#);
+    let axum_method_re = regex(
+        r"(?:^|[^\w:])(?:axum::)?(?:routing::)?(get|post|put|delete|patch|head|options|any)\s*\(\s*([\w:]+)\s*\)",
+    );
+    let actix_method_re = regex(
+        r"web::(get|post|put|delete|patch|head|method)\s*\([^)]*\)\s*\.to\s*\(\s*([\w:]+)\s*\)",
+    );
+    let attribute_re = regex(
+        r#"^\s*#\[(?:actix_web::|rocket::)?(get|post|put|delete|patch|head|options)\s*\(\s*"([^"]*)""#,
+    );
+    let route_attribute_re = regex(r#"^\s*#\[(?:actix_web::)?route\s*\(\s*"([^"]*)"(.*)"#);
+    let attribute_method_re = regex(r#"method\s*=\s*"(\w+)""#);
+    let fn_re = regex(r"^(?:pub(?:\([^)]*\))?\s+)?(?:async\s+)?fn\s+(\w+)");
+
+    let mut results = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = attribute_re.captures(line) {
+            let method = caps.get(1).map_or("", |m| m.as_str()).to_uppercase();
+            let path = caps.get(2).map_or("", |m| m.as_str()).to_string();
+            let handler = next_definition(lines, i, &fn_re);
+            results.push((method, path, handler, attribute_framework, i + 1));
+            continue;
+        }
+        if let Some(caps) = route_attribute_re.captures(line) {
+            let path = caps.get(1).map_or("", |m| m.as_str()).to_string();
+            let handler = next_definition(lines, i, &fn_re);
+            for m in attribute_method_re.captures_iter(caps.get(2).map_or("", |m| m.as_str())) {
+                let method = m.get(1).map_or("", |m| m.as_str()).to_uppercase();
+                results.push((method, path.clone(), handler.clone(), "actix", i + 1));
+            }
+            continue;
+        }
+
+        let (start, chained) = match (route_re.find(line), resource_re.find(line)) {
+            (_, Some(m)) => (m.start(), true),
+            (Some(m), None) => (m.start(), false),
+            (None, None) => continue,
+        };
+        let call = group_text(lines, i, start, chained);
+        let Some(path) = first_string(args(&call)) else {
+            continue;
+        };
+
+        let mut found_actix = false;
+        for caps in actix_method_re.captures_iter(&call) {
+            found_actix = true;
+            let method = match caps.get(1).map_or("", |m| m.as_str()) {
+                "method" => "ANY".to_string(),
+                other => other.to_uppercase(),
+            };
+            let handler = caps.get(2).map(|m| m.as_str().to_string());
+            results.push((method, path.clone(), handler, "actix", i + 1));
+        }
+        if found_actix || chained {
+            continue;
+        }
+        for caps in axum_method_re.captures_iter(args(&call)) {
+            let method = match caps.get(1).map_or("", |m| m.as_str()) {
+                "any" => "ANY".to_string(),
+                other => other.to_uppercase(),
+            };
+            let handler = caps.get(2).map(|m| m.as_str().to_string());
+            results.push((method, path.clone(), handler, "axum", i + 1));
+        }
+    }
+
+    results
+}
+
+fn java_routes(lines: &[&str]) -> Vec<Found> {
+    let mapping_re = regex(r"^\s*@(Get|Post|Put|Delete|Patch|Request)Mapping\b");
+    let request_method_re = regex(r"RequestMethod\.(\w+)");
+    let method_re = regex(r"(\w+)\s*\(");
+
+    let mut class_prefix = String::new();
+    let mut results = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let Some(caps) = mapping_re.captures(line) else {
+            continue;
+        };
+        let verb = caps.get(1).map_or("", |m| m.as_str());
+        let annotation = if line.contains('(') {
+            group_text(lines, i, 0, false)
+        } else {
+            String::new()
+        };
+        let call_args = args(&annotation);
+        // `value`/`path` or the bare first argument, possibly inside `{...}`
+        let path = split_args(call_args)
+            .into_iter()
+            .find(|a| {
+                let a = a.trim_start_matches('{').trim();
+                a.starts_with('"') || a.starts_with("value") || a.starts_with("path")
+            })
+            .and_then(first_string)
+            .unwrap_or_default();
+
+        let declares_class = lines
+            .iter()
+            .skip(i + 1)
+            .take(12)
+            .map(|l| l.trim())
+            .find(|l| !l.is_empty() && !l.starts_with('@') && !l.starts_with("//"))
+            .is_some_and(|l| {
+                l.contains("class ") || l.contains("interface ") || l.contains("record ")
+            });
+        if declares_class {
+            if verb == "Request" {
+                class_prefix = path;
+            }
+            continue;
+        }
+
+        let methods: Vec<String> = if verb == "Request" {
+            let listed: Vec<String> = request_method_re
+                .captures_iter(call_args)
+                .filter_map(|c| c.get(1).map(|m| m.as_str().to_string()))
+                .collect();
+            if listed.is_empty() {
+                vec!["ANY".to_string()]
+            } else {
+                listed
+            }
+        } else {
+            vec![verb.to_uppercase()]
+        };
+
+        let path = leading_slash(join_path(&class_prefix, &path));
+        let handler = next_definition(lines, i, &method_re);
+        for method in methods {
+            results.push((method, path.clone(), handler.clone(), "spring", i + 1));
+        }
+    }
+
+    results
+}
+
+fn go_routes(content: &str, lines: &[&str]) -> Vec<Found> {
+    let framework = if content.contains("gin-gonic/gin") {
+        "gin"
+    } else if content.contains("labstack/echo") {
+        "echo"
+    } else if content.contains("go-chi/chi") {
+        "chi"
+    } else if content.contains("gofiber/fiber") {
+        "fiber"
+    } else if content.contains("gorilla/mux") {
+        "gorilla/mux"
+    } else {
+        "net/http"
+    };
+    let group_re = regex(r#"^\s*(\w+)\s*:?=\s*(\w+)\.Group\s*\(\s*"([^"]*)""#);
+    let verb_re = regex(
+        r#"\b(\w+)\.(GET|POST|PUT|DELETE|PATCH|HEAD|OPTIONS|Any|Get|Post|Put|Delete|Patch|Head|Options)\s*\(\s*"(/[^"]*)""#,
+    );
+    let handle_re = regex(r#"\b(\w+)\.(?:HandleFunc|Handle)\s*\(\s*"([^"]*)""#);
+    let mux_methods_re = regex(r"\.Methods\s*\(([^)]*)\)");
+
+    let mut prefixes: HashMap<&str, String> = HashMap::new();
+    let mut results = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = group_re.captures(line) {
+            let parent = caps.get(2).map_or("", |m| m.as_str());
+            let path = caps.get(3).map_or("", |m| m.as_str());
+            let prefix = join_path(prefixes.get(parent).map_or("", |p| p.as_str()), path);
+            prefixes.insert(caps.get(1).map_or("", |m| m.as_str()), prefix);
+            continue;
+        }
+
+        if let Some(caps) = verb_re.captures(line) {
+            let object = caps.get(1).map_or("", |m| m.as_str());
+            let method = match caps.get(2).map_or("", |m| m.as_str()) {
+                "Any" => "ANY".to_string(),
+                other => other.to_uppercase(),
+            };
+            let path = caps.get(3).map_or("", |m| m.as_str());
+            let path = match prefixes.get(object) {
+                Some(prefix) => join_path(prefix, path),
+                None => path.to_string(),
+            };
+            let start = caps.get(0).map_or(0, |m| m.start());
+            let handler = handler_arg(args(&group_text(lines, i, start, false)));
+            results.push((method, path, handler, framework, i + 1));
+            continue;
+        }
+
+        let Some(caps) = handle_re.captures(line) else {
+            continue;
+        };
+        let pattern = caps.get(2).map_or("", |m| m.as_str());
+        let start = caps.get(0).map_or(0, |m| m.start());
+        let call = group_text(lines, i, start, true);
+        let handler = handler_arg(args(&call));
+
+        // Go 1.22 patterns carry the method: "GET /users/{id}"
+        let (methods, path) = match pattern.split_once(' ') {
+            Some((method, path)) => (vec![method.to_string()], path.trim().to_string()),
+            None => {
+                let listed = mux_methods_re
+                    .captures(&call)
+                    .map(|c| quoted_methods(c.get(1).map_or("", |m| m.as_str())))
+                    .unwrap_or_default();
+                let methods = if listed.is_empty() {
+                    vec!["ANY".to_string()]
+                } else {
+                    listed
+                };
+                (methods, pattern.to_string())
+            }
+        };
+        for method in methods {
+            results.push((method, path.clone(), handler.clone(), framework, i + 1));
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(content: &str, language: Language) -> Vec<(String, String, Option<String>)> {
+        routes_in(content, language, Path::new("app"))
+            .into_iter()
+            .map(|r| (r.method, r.path, r.handler))
+            .collect()
+    }
+
+    fn route(method: &str, path: &str, handler: Option<&str>) -> (String, String, Option<String>) {
+        (
+            method.to_string(),
+            path.to_string(),
+            handler.map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn test_python_and_js_routes() {
+        let python = r#"
+from fastapi import APIRouter
+
+router = APIRouter(prefix="/users")
+
+@router.get("/{user_id}")
+async def get_user(user_id: int):
+    pass
+
+@app.route("/login", methods=["GET", "POST"])
+def login():
+    pass
+"#;
+        assert_eq!(
+            summary(python, Language::Python),
+            vec![
+                route("GET", "/users/{user_id}", Some("get_user")),
+                route("GET", "/login", Some("login")),
+                route("POST", "/login", Some("login")),
+            ]
+        );
+
+        let js = r#"
+app.get('/users', auth, listUsers);
+router.post("/users/:id",
+    (req, res) => res.send(cache.get('key')));
+
+@Controller('items')
+export class ItemsController {
+  @Get(':id')
+  findOne(@Param('id') id: string) {}
+}
+"#;
+        assert_eq!(
+            summary(js, Language::TypeScript),
+            vec![
+                route("GET", "/users", Some("listUsers")),
+                route("POST", "/users/:id", None),
+                route("GET", "/items/:id", Some("findOne")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_java_and_go_routes() {
+        let rust = r#"
+let app = Router::new()
+    .route("/users", get(list_users).post(create_user))
+    .route("/health", any(health));
+
+#[get("/items/{id}")]
+async fn get_item(path: web::Path<u32>) -> impl Responder {}
+
+App::new().service(web::resource("/orders").route(web::get().to(orders::list)));
+"#;
+        assert_eq!(
+            summary(rust, Language::Rust),
+            vec![
+                route("GET", "/users", Some("list_users")),
+                route("POST", "/users", Some("create_user")),
+                route("ANY", "/health", Some("health")),
+                route("GET", "/items/{id}", Some("get_item")),
+                route("GET", "/orders", Some("orders::list")),
+            ]
+        );
+
+        let java = r#"
+@RestController
+@RequestMapping("/api/users")
+public class UserController {
+    @GetMapping("/{id}")
+    public ResponseEntity<User> getUser(@PathVariable Long id) {}
+
+    @RequestMapping(value = "/search", method = RequestMethod.POST)
+    public List<User> search() {}
+}
+"#;
+        assert_eq!(
+            summary(java, Language::Java),
+            vec![
+                route("GET", "/api/users/{id}", Some("getUser")),
+                route("POST", "/api/users/search", Some("search")),
+            ]
+        );
+
+        let go = r#"
+import "github.com/gin-gonic/gin"
+
+func main() {
+	r := gin.Default()
+	v1 := r.Group("/v1")
+	v1.GET("/users", handlers.ListUsers)
+	http.HandleFunc("GET /health", health)
+}
+"#;
+        assert_eq!(
+            summary(go, Language::Go),
+            vec![
+                route("GET", "/v1/users", Some("handlers.ListUsers")),
+                route("GET", "/health", Some("health")),
+            ]
+        );
+    }
+}
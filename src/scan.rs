@@ -0,0 +1,61 @@
+//! Helpers shared by the analyses that scan source text rather than walking the syntax tree:
+//! `cm audit`, `cm env`, `cm errors`, `cm logs`, `cm routes`, `cm sql` and the like
+
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+use crate::models::Language;
+
+/// A file's source without its Rust `#[cfg(test)]` module, so test fixtures are not reported
+/// as the code's own. Unreadable files read as empty.
+pub(crate) fn non_test_source(path: &Path, language: Language) -> String {
+    let mut content = fs::read_to_string(path).unwrap_or_default();
+    if language == Language::Rust {
+        if let Some(end) = content.find("\n#[cfg(test)]") {
+            content.truncate(end + 1);
+        }
+    }
+    content
+}
+
+/// One of cm's built-in patterns, compiled; an invalid one matches only empty text instead of
+/// panicking
+pub(crate) fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).unwrap_or_else(|_| Regex::new(r"^$").expect("fallback regex"))
+}
+
+/// The line starts with a comment, or continues a block comment with `*`
+pub(crate) fn is_comment(line: &str, language: Language) -> bool {
+    let line = line.trim_start();
+    match language {
+        Language::Python => line.starts_with('#'),
+        Language::Lua => line.starts_with("--"),
+        Language::Php if line.starts_with('#') => !line.starts_with("#["),
+        _ => line.starts_with("//") || line.starts_with("/*") || line.starts_with('*'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_comment() {
+        assert!(is_comment("    # note", Language::Python));
+        assert!(is_comment("  -- os.getenv('HOME')", Language::Lua));
+        assert!(is_comment("# eval($code);", Language::Php));
+        assert!(!is_comment("#[Route('/users')]", Language::Php));
+        assert!(is_comment(" * @deprecated", Language::Java));
+        assert!(!is_comment("let x = 1; // trailing", Language::Rust));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "fn run() {}\n#[cfg(test)]\nmod tests {}\n").unwrap();
+        assert_eq!(non_test_source(&path, Language::Rust), "fn run() {}\n");
+        assert_eq!(
+            non_test_source(&path, Language::Python),
+            "fn run() {}\n#[cfg(test)]\nmod tests {}\n"
+        );
+    }
+}
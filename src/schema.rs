@@ -1,11 +1,13 @@
 use crate::index::CodeIndex;
 use crate::models::{Language, Symbol, SymbolType};
+use crate::parser::{cached_query, pooled_parse};
+use crate::paths::DisplaySlash;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Parser, Query, QueryCursor};
+use tree_sitter::{Node, QueryCursor};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldInfo {
@@ -14,6 +16,9 @@ pub struct FieldInfo {
     pub default_value: Option<String>,
     pub is_optional: bool,
     pub docstring: Option<String>,
+    /// Line of the field's declaration
+    #[serde(default)]
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,22 +44,28 @@ pub fn analyze_schema(
 
     let class_symbols: Vec<&Symbol> = symbols
         .into_iter()
-        .filter(|s| matches!(s.symbol_type, SymbolType::Class | SymbolType::Enum))
+        .filter(|s| {
+            matches!(
+                s.symbol_type,
+                SymbolType::Class | SymbolType::Enum | SymbolType::Interface
+            )
+        })
         .collect();
 
     let mut schemas = Vec::new();
 
     for symbol in class_symbols {
-        let content = fs::read_to_string(&symbol.file_path)
-            .with_context(|| format!("Failed to read file: {}", symbol.file_path.display()))?;
+        let content = fs::read_to_string(&symbol.file_path).with_context(|| {
+            format!("Failed to read file: {}", symbol.file_path.display_slash())
+        })?;
 
         let language = detect_language(&symbol.file_path);
         let fields = extract_fields(&content, symbol, language)?;
 
         schemas.push(SchemaInfo {
-            symbol_name: symbol.name.clone(),
+            symbol_name: symbol.name.to_string(),
             symbol_type: symbol.symbol_type,
-            file_path: symbol.file_path.clone(),
+            file_path: symbol.file_path.to_path_buf(),
             line: symbol.line_start,
             fields,
             language,
@@ -64,7 +75,7 @@ pub fn analyze_schema(
     Ok(schemas)
 }
 
-fn detect_language(path: &PathBuf) -> Language {
+fn detect_language(path: &Path) -> Language {
     path.extension()
         .and_then(|ext| ext.to_str())
         .map(Language::from_extension)
@@ -72,31 +83,154 @@ fn detect_language(path: &PathBuf) -> Language {
 }
 
 fn extract_fields(content: &str, symbol: &Symbol, language: Language) -> Result<Vec<FieldInfo>> {
+    // Rust impl blocks show the fields of every struct in the file
+    let fields: Vec<FieldInfo> = fields_by_owner(content, language)?
+        .into_iter()
+        .filter(|(owner, _)| symbol.name == owner.as_str() || symbol.name.starts_with("impl "))
+        .map(|(_, field)| field)
+        .collect();
+
+    if fields.is_empty() && language == Language::Python {
+        return extract_python_fields_fallback(content, symbol);
+    }
+    Ok(fields)
+}
+
+/// Fields of every struct, class and interface in the file, each with its owner's name
+fn fields_by_owner(content: &str, language: Language) -> Result<Vec<(String, FieldInfo)>> {
+    let grammar: tree_sitter::Language = match language {
+        Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+        Language::Python => tree_sitter_python::LANGUAGE.into(),
+        // Class fields are written the same way in both, so JavaScript parses as TypeScript
+        Language::TypeScript | Language::JavaScript => {
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()
+        }
+        Language::Java => tree_sitter_java::LANGUAGE.into(),
+        Language::Go => tree_sitter_go::LANGUAGE.into(),
+        _ => return Ok(Vec::new()),
+    };
+    let tree = pooled_parse(&grammar, content)
+        .with_context(|| format!("Failed to set {} language", language.as_str()))?
+        .with_context(|| format!("Failed to parse {} file", language.as_str()))?;
+
+    let language = match language {
+        Language::JavaScript => Language::TypeScript,
+        other => other,
+    };
+    fields_in_tree(tree.root_node(), content, language)
+}
+
+/// `fields_by_owner` on a tree already parsed with the language's grammar
+fn fields_in_tree(
+    root: Node,
+    content: &str,
+    language: Language,
+) -> Result<Vec<(String, FieldInfo)>> {
     match language {
-        Language::Rust => extract_rust_fields(content, symbol),
-        Language::Python => extract_python_fields(content, symbol),
-        Language::TypeScript | Language::JavaScript => extract_typescript_fields(content, symbol),
-        Language::Java => extract_java_fields(content, symbol),
-        Language::Go => extract_go_fields(content, symbol),
+        Language::Rust => rust_fields(root, content),
+        Language::Python => python_fields(root, content),
+        Language::TypeScript => typescript_fields(root, content),
+        Language::Java => java_fields(root, content),
+        Language::Go => go_fields(root, content),
         _ => Ok(Vec::new()),
     }
 }
 
-fn extract_rust_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_rust::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set Rust language")?;
+fn is_field_owner(symbol: &Symbol) -> bool {
+    matches!(
+        symbol.symbol_type,
+        SymbolType::Class | SymbolType::Interface
+    )
+}
+
+/// `symbols` with the fields of each struct, class and interface inserted right after it as
+/// `Field` children, so listings show the type's shape inline. Parsers call this with the
+/// tree they parsed `content` into.
+pub fn with_field_symbols(
+    root: Node,
+    content: &str,
+    language: Language,
+    symbols: Vec<Symbol>,
+) -> Vec<Symbol> {
+    if !symbols.iter().any(is_field_owner) {
+        return symbols;
+    }
+    let Ok(fields) = fields_in_tree(root, content, language) else {
+        return symbols;
+    };
+
+    // Each field belongs to the innermost type of its owner's name that spans its line
+    let mut owned: Vec<Vec<FieldInfo>> = vec![Vec::new(); symbols.len()];
+    for (owner, field) in fields {
+        let found = symbols
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                is_field_owner(s)
+                    && s.name == owner.as_str()
+                    && s.line_start <= field.line
+                    && field.line <= s.line_end
+            })
+            .min_by_key(|(_, s)| s.line_end - s.line_start);
+        // Java static fields and Python class constants are already symbols
+        let known = symbols
+            .iter()
+            .any(|s| s.line_start == field.line && s.name == field.name.as_str());
+        if let (Some((i, _)), false) = (found, known) {
+            if !owned[i]
+                .iter()
+                .any(|f| f.name == field.name && f.line == field.line)
+            {
+                owned[i].push(field);
+            }
+        }
+    }
+    if owned.iter().all(Vec::is_empty) {
+        return symbols;
+    }
+
+    // Inserting fields shifts every later symbol, so parent ids are remapped
+    let mut positions = Vec::with_capacity(symbols.len());
+    let mut next = 0;
+    for fields in &owned {
+        positions.push(next);
+        next += 1 + fields.len();
+    }
+
+    let mut result = Vec::with_capacity(next);
+    for ((mut symbol, fields), &position) in symbols.into_iter().zip(owned).zip(&positions) {
+        symbol.parent_id = symbol.parent_id.and_then(|p| positions.get(p).copied());
+        let file_path = symbol.file_path.clone();
+        result.push(symbol);
 
-    let tree = parser
-        .parse(content, None)
-        .context("Failed to parse Rust file")?;
+        for field in fields {
+            let mut signature = field.type_name;
+            if let Some(default) = &field.default_value {
+                signature.push_str(&format!(" = {}", default));
+            }
+            result.push(Symbol {
+                name: field.name.into(),
+                symbol_type: SymbolType::Field,
+                signature: Some(signature),
+                docstring: field.docstring,
+                line_start: field.line,
+                line_end: field.line,
+                parent_id: Some(position),
+                file_path: file_path.clone(),
+                is_exported: false,
+                byte_range: None,
+                annotations: Vec::new(),
+            });
+        }
+    }
+    result
+}
 
-    let root = tree.root_node();
+fn rust_fields(root: Node, content: &str) -> Result<Vec<(String, FieldInfo)>> {
+    let language: tree_sitter::Language = tree_sitter_rust::LANGUAGE.into();
     let mut fields = Vec::new();
 
-    let query = Query::new(
+    let query = cached_query(
         &language,
         r#"
         (struct_item
@@ -110,7 +244,7 @@ fn extract_rust_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>>
     .context("Failed to create Rust struct field query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, root, content.as_bytes());
+    let mut matches = cursor.matches(query, root, content.as_bytes());
 
     while let Some(match_) = matches.next() {
         let captures = match_.captures;
@@ -127,10 +261,6 @@ fn extract_rust_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>>
             .and_then(|c| c.node.utf8_text(content.as_bytes()).ok())
             .unwrap_or_default();
 
-        if struct_name != symbol.name && !symbol.name.starts_with("impl ") {
-            continue;
-        }
-
         let field_name_cap = captures.iter().find(|c| {
             query
                 .capture_names()
@@ -161,34 +291,28 @@ fn extract_rust_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>>
 
             let is_optional = type_name.starts_with("Option<");
 
-            fields.push(FieldInfo {
-                name,
-                type_name,
-                default_value: None,
-                is_optional,
-                docstring: None,
-            });
+            fields.push((
+                struct_name.to_string(),
+                FieldInfo {
+                    name,
+                    type_name,
+                    default_value: None,
+                    is_optional,
+                    docstring: None,
+                    line: name_cap.node.start_position().row + 1,
+                },
+            ));
         }
     }
 
     Ok(fields)
 }
 
-fn extract_python_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_python::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set Python language")?;
-
-    let tree = parser
-        .parse(content, None)
-        .context("Failed to parse Python file")?;
-
-    let root = tree.root_node();
+fn python_fields(root: Node, content: &str) -> Result<Vec<(String, FieldInfo)>> {
+    let language: tree_sitter::Language = tree_sitter_python::LANGUAGE.into();
     let mut fields = Vec::new();
 
-    let query = Query::new(
+    let query = cached_query(
         &language,
         r#"
         (class_definition
@@ -204,7 +328,7 @@ fn extract_python_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo
     .context("Failed to create Python class field query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, root, content.as_bytes());
+    let mut matches = cursor.matches(query, root, content.as_bytes());
 
     while let Some(match_) = matches.next() {
         let captures = match_.captures;
@@ -221,10 +345,6 @@ fn extract_python_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo
             .and_then(|c| c.node.utf8_text(content.as_bytes()).ok())
             .unwrap_or_default();
 
-        if class_name != symbol.name {
-            continue;
-        }
-
         let field_name_cap = captures.iter().find(|c| {
             query
                 .capture_names()
@@ -267,20 +387,20 @@ fn extract_python_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo
 
             let is_optional = type_name.contains("Optional") || default_value.is_some();
 
-            fields.push(FieldInfo {
-                name,
-                type_name,
-                default_value,
-                is_optional,
-                docstring: None,
-            });
+            fields.push((
+                class_name.to_string(),
+                FieldInfo {
+                    name,
+                    type_name,
+                    default_value,
+                    is_optional,
+                    docstring: None,
+                    line: name_cap.node.start_position().row + 1,
+                },
+            ));
         }
     }
 
-    if fields.is_empty() {
-        fields = extract_python_fields_fallback(content, symbol)?;
-    }
-
     Ok(fields)
 }
 
@@ -295,7 +415,7 @@ fn extract_python_fields_fallback(content: &str, symbol: &Symbol) -> Result<Vec<
     let start_idx = symbol.line_start.saturating_sub(1);
     let end_idx = symbol.line_end.min(lines.len());
 
-    for line in &lines[start_idx..end_idx] {
+    for (offset, line) in lines[start_idx..end_idx].iter().enumerate() {
         let trimmed = line.trim();
 
         if trimmed.starts_with("def ") || trimmed.starts_with("class ") || trimmed.starts_with('#')
@@ -330,6 +450,7 @@ fn extract_python_fields_fallback(content: &str, symbol: &Symbol) -> Result<Vec<
                         default_value,
                         is_optional,
                         docstring: None,
+                        line: start_idx + offset + 1,
                     });
                 }
             }
@@ -339,26 +460,16 @@ fn extract_python_fields_fallback(content: &str, symbol: &Symbol) -> Result<Vec<
     Ok(fields)
 }
 
-fn extract_typescript_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_javascript::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set JavaScript/TypeScript language")?;
-
-    let tree = parser
-        .parse(content, None)
-        .context("Failed to parse TypeScript file")?;
-
-    let root = tree.root_node();
+fn typescript_fields(root: Node, content: &str) -> Result<Vec<(String, FieldInfo)>> {
+    let language: tree_sitter::Language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
     let mut fields = Vec::new();
 
-    let interface_query = Query::new(
+    let interface_query = cached_query(
         &language,
         r#"
         (interface_declaration
             name: (type_identifier) @interface.name
-            body: (object_type
+            body: (interface_body
                 (property_signature
                     name: (property_identifier) @prop.name
                     type: (type_annotation (_) @prop.type)))) @interface.def
@@ -367,7 +478,7 @@ fn extract_typescript_fields(content: &str, symbol: &Symbol) -> Result<Vec<Field
     .context("Failed to create TypeScript interface query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&interface_query, root, content.as_bytes());
+    let mut matches = cursor.matches(interface_query, root, content.as_bytes());
 
     while let Some(match_) = matches.next() {
         let captures = match_.captures;
@@ -384,10 +495,6 @@ fn extract_typescript_fields(content: &str, symbol: &Symbol) -> Result<Vec<Field
             .and_then(|c| c.node.utf8_text(content.as_bytes()).ok())
             .unwrap_or_default();
 
-        if iface_name != symbol.name {
-            continue;
-        }
-
         let prop_name_cap = captures.iter().find(|c| {
             interface_query
                 .capture_names()
@@ -416,19 +523,28 @@ fn extract_typescript_fields(content: &str, symbol: &Symbol) -> Result<Vec<Field
                 .unwrap_or_default()
                 .to_string();
 
-            let is_optional = name.ends_with('?') || type_name.contains("undefined");
-
-            fields.push(FieldInfo {
-                name: name.trim_end_matches('?').to_string(),
-                type_name,
-                default_value: None,
-                is_optional,
-                docstring: None,
-            });
+            // `email?: string` marks the property optional with a token after the name
+            let is_optional = name_cap
+                .node
+                .next_sibling()
+                .is_some_and(|n| n.kind() == "?")
+                || type_name.contains("undefined");
+
+            fields.push((
+                iface_name.to_string(),
+                FieldInfo {
+                    name: name.trim_end_matches('?').to_string(),
+                    type_name,
+                    default_value: None,
+                    is_optional,
+                    docstring: None,
+                    line: name_cap.node.start_position().row + 1,
+                },
+            ));
         }
     }
 
-    let class_query = Query::new(
+    let class_query = cached_query(
         &language,
         r#"
         (class_declaration
@@ -443,7 +559,7 @@ fn extract_typescript_fields(content: &str, symbol: &Symbol) -> Result<Vec<Field
     .context("Failed to create TypeScript class field query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&class_query, root, content.as_bytes());
+    let mut matches = cursor.matches(class_query, root, content.as_bytes());
 
     while let Some(match_) = matches.next() {
         let captures = match_.captures;
@@ -460,10 +576,6 @@ fn extract_typescript_fields(content: &str, symbol: &Symbol) -> Result<Vec<Field
             .and_then(|c| c.node.utf8_text(content.as_bytes()).ok())
             .unwrap_or_default();
 
-        if class_name != symbol.name {
-            continue;
-        }
-
         let field_name_cap = captures.iter().find(|c| {
             class_query
                 .capture_names()
@@ -506,34 +618,28 @@ fn extract_typescript_fields(content: &str, symbol: &Symbol) -> Result<Vec<Field
 
             let is_optional = type_name.contains("?") || type_name.contains("undefined");
 
-            fields.push(FieldInfo {
-                name,
-                type_name,
-                default_value,
-                is_optional,
-                docstring: None,
-            });
+            fields.push((
+                class_name.to_string(),
+                FieldInfo {
+                    name,
+                    type_name,
+                    default_value,
+                    is_optional,
+                    docstring: None,
+                    line: name_cap.node.start_position().row + 1,
+                },
+            ));
         }
     }
 
     Ok(fields)
 }
 
-fn extract_java_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>> {
-    let mut parser = Parser::new();
-    let language = tree_sitter_java::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set Java language")?;
-
-    let tree = parser
-        .parse(content, None)
-        .context("Failed to parse Java file")?;
-
-    let root = tree.root_node();
+fn java_fields(root: Node, content: &str) -> Result<Vec<(String, FieldInfo)>> {
+    let language: tree_sitter::Language = tree_sitter_java::LANGUAGE.into();
     let mut fields = Vec::new();
 
-    let query = Query::new(
+    let query = cached_query(
         &language,
         r#"
         (class_declaration
@@ -549,7 +655,7 @@ fn extract_java_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>>
     .context("Failed to create Java class field query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, root, content.as_bytes());
+    let mut matches = cursor.matches(query, root, content.as_bytes());
 
     while let Some(match_) = matches.next() {
         let captures = match_.captures;
@@ -566,10 +672,6 @@ fn extract_java_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>>
             .and_then(|c| c.node.utf8_text(content.as_bytes()).ok())
             .unwrap_or_default();
 
-        if class_name != symbol.name {
-            continue;
-        }
-
         let field_name_cap = captures.iter().find(|c| {
             query
                 .capture_names()
@@ -613,34 +715,28 @@ fn extract_java_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>>
 
             let is_optional = type_name.contains("Optional");
 
-            fields.push(FieldInfo {
-                name,
-                type_name,
-                default_value,
-                is_optional,
-                docstring: None,
-            });
+            fields.push((
+                class_name.to_string(),
+                FieldInfo {
+                    name,
+                    type_name,
+                    default_value,
+                    is_optional,
+                    docstring: None,
+                    line: name_cap.node.start_position().row + 1,
+                },
+            ));
         }
     }
 
     Ok(fields)
 }
 
-fn extract_go_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>> {
-    let mut parser = Parser::new();
+fn go_fields(root: Node, content: &str) -> Result<Vec<(String, FieldInfo)>> {
     let language: tree_sitter::Language = tree_sitter_go::LANGUAGE.into();
-    parser
-        .set_language(&language)
-        .context("Failed to set Go language")?;
-
-    let tree = parser
-        .parse(content, None)
-        .context("Failed to parse Go file")?;
-
-    let root = tree.root_node();
     let mut fields = Vec::new();
 
-    let query = Query::new(
+    let query = cached_query(
         &language,
         r#"
         (type_declaration
@@ -656,7 +752,7 @@ fn extract_go_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>> {
     .context("Failed to create Go struct field query")?;
 
     let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, root, content.as_bytes());
+    let mut matches = cursor.matches(query, root, content.as_bytes());
 
     while let Some(match_) = matches.next() {
         let captures = match_.captures;
@@ -673,10 +769,6 @@ fn extract_go_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>> {
             .and_then(|c| c.node.utf8_text(content.as_bytes()).ok())
             .unwrap_or_default();
 
-        if struct_name != symbol.name {
-            continue;
-        }
-
         let field_name_cap = captures.iter().find(|c| {
             query
                 .capture_names()
@@ -708,13 +800,17 @@ fn extract_go_fields(content: &str, symbol: &Symbol) -> Result<Vec<FieldInfo>> {
 
             let is_optional = type_name.starts_with('*');
 
-            fields.push(FieldInfo {
-                name,
-                type_name,
-                default_value: None,
-                is_optional,
-                docstring: None,
-            });
+            fields.push((
+                struct_name.to_string(),
+                FieldInfo {
+                    name,
+                    type_name,
+                    default_value: None,
+                    is_optional,
+                    docstring: None,
+                    line: name_cap.node.start_position().row + 1,
+                },
+            ));
         }
     }
 
@@ -736,18 +832,20 @@ struct User {
 }
 "#;
         let symbol = Symbol {
-            name: "User".to_string(),
+            name: "User".into(),
             symbol_type: SymbolType::Class,
             signature: None,
             docstring: None,
             line_start: 2,
             line_end: 6,
             parent_id: None,
-            file_path: Path::new("test.rs").to_path_buf(),
+            file_path: Path::new("test.rs").into(),
             is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
         };
 
-        let fields = extract_rust_fields(content, &symbol)?;
+        let fields = extract_fields(content, &symbol, Language::Rust)?;
         assert_eq!(fields.len(), 3);
         assert_eq!(fields[0].name, "name");
         assert_eq!(fields[0].type_name, "String");
@@ -766,15 +864,17 @@ class User:
     email: Optional[str] = None
 "#;
         let symbol = Symbol {
-            name: "User".to_string(),
+            name: "User".into(),
             symbol_type: SymbolType::Class,
             signature: None,
             docstring: None,
             line_start: 2,
             line_end: 6,
             parent_id: None,
-            file_path: Path::new("test.py").to_path_buf(),
+            file_path: Path::new("test.py").into(),
             is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
         };
 
         let fields = extract_python_fields_fallback(content, &symbol)?;
@@ -782,6 +882,30 @@ class User:
         Ok(())
     }
 
+    #[test]
+    fn test_field_symbols_follow_their_type() {
+        let content = "class Cart {\n  total: number = 0;\n  add(item: string) {}\n}\n\ninterface Item {\n  sku?: string;\n}\n";
+        let path = Path::new("cart.ts");
+        let parsed = crate::indexer::index_file(path, content, Language::TypeScript, None).unwrap();
+        let listed: Vec<(&str, SymbolType, Option<usize>)> = parsed
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type, s.parent_id))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("Cart", SymbolType::Class, None),
+                ("total", SymbolType::Field, Some(0)),
+                ("add", SymbolType::Method, Some(0)),
+                ("Item", SymbolType::Interface, None),
+                ("sku", SymbolType::Field, Some(3)),
+            ]
+        );
+        assert_eq!(parsed.symbols[1].signature.as_deref(), Some("number = 0"));
+        assert_eq!(parsed.symbols[4].line_start, 7);
+    }
+
     #[test]
     fn test_extract_go_fields() -> Result<()> {
         let content = r#"
@@ -794,18 +918,20 @@ type User struct {
 }
 "#;
         let symbol = Symbol {
-            name: "User".to_string(),
+            name: "User".into(),
             symbol_type: SymbolType::Class,
             signature: Some("struct".to_string()),
             docstring: None,
             line_start: 4,
             line_end: 8,
             parent_id: None,
-            file_path: Path::new("test.go").to_path_buf(),
+            file_path: Path::new("test.go").into(),
             is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
         };
 
-        let fields = extract_go_fields(content, &symbol)?;
+        let fields = extract_fields(content, &symbol, Language::Go)?;
         assert_eq!(fields.len(), 3);
         assert_eq!(fields[0].name, "Name");
         assert_eq!(fields[0].type_name, "string");
@@ -0,0 +1,187 @@
+use crate::diff::{ChangeType, DiffResult};
+use crate::models::SymbolType;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bump::None => "none",
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Justification {
+    pub bump: Bump,
+    pub change_type: ChangeType,
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: PathBuf,
+    pub old_signature: Option<String>,
+    pub new_signature: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct SemverAdvice {
+    pub from: String,
+    pub commit: String,
+    pub bump: Bump,
+    pub next_version: Option<String>,
+    pub justifications: Vec<Justification>,
+}
+
+/// Classify each change to decide the release bump:
+/// removed/changed/moved public symbols => major, added public symbols => minor, anything else => patch
+pub fn advise(result: &DiffResult, from: &str) -> SemverAdvice {
+    let mut justifications: Vec<Justification> = result
+        .symbols
+        .iter()
+        .filter(|s| !matches!(s.symbol_type, SymbolType::Heading | SymbolType::CodeBlock))
+        .map(|s| {
+            let bump = match (s.is_exported, s.change_type) {
+                (
+                    true,
+                    ChangeType::Deleted
+                    | ChangeType::SignatureChanged
+                    | ChangeType::Moved
+                    | ChangeType::Renamed,
+                ) => Bump::Major,
+                (true, ChangeType::Added) => Bump::Minor,
+                _ => Bump::Patch,
+            };
+            Justification {
+                bump,
+                change_type: s.change_type,
+                name: s.name.clone(),
+                symbol_type: s.symbol_type,
+                file_path: s.file_path.clone(),
+                old_signature: s.old_signature.clone(),
+                new_signature: s.new_signature.clone(),
+            }
+        })
+        .collect();
+
+    justifications.sort_by(|a, b| {
+        b.bump
+            .cmp(&a.bump)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let bump = justifications
+        .iter()
+        .map(|j| j.bump)
+        .max()
+        .unwrap_or(Bump::None);
+
+    SemverAdvice {
+        from: from.to_string(),
+        commit: result.commit.clone(),
+        bump,
+        next_version: next_version(from, bump),
+        justifications,
+    }
+}
+
+/// Compute the next version when `from` looks like `1.2.3` or `v1.2.3`.
+/// Pre-1.0 releases follow the cargo convention: breaking changes bump the minor version.
+fn next_version(from: &str, bump: Bump) -> Option<String> {
+    let (prefix, version) = match from.strip_prefix('v') {
+        Some(rest) => ("v", rest),
+        None => ("", from),
+    };
+
+    let core = version.split(['-', '+']).next()?;
+    let parts: Vec<u64> = core
+        .split('.')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if parts.len() != 3 {
+        return None;
+    }
+
+    let (major, minor, patch) = (parts[0], parts[1], parts[2]);
+    let (major, minor, patch) = match bump {
+        Bump::None => (major, minor, patch),
+        Bump::Patch => (major, minor, patch + 1),
+        Bump::Minor if major == 0 => (0, minor, patch + 1),
+        Bump::Minor => (major, minor + 1, 0),
+        Bump::Major if major == 0 => (0, minor + 1, 0),
+        Bump::Major => (major + 1, 0, 0),
+    };
+
+    Some(format!("{}{}.{}.{}", prefix, major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::SymbolDiff;
+
+    fn diff(name: &str, change_type: ChangeType, is_exported: bool) -> SymbolDiff {
+        SymbolDiff {
+            name: name.to_string(),
+            symbol_type: SymbolType::Function,
+            change_type,
+            file_path: PathBuf::from("src/lib.rs"),
+            old_lines: None,
+            new_lines: Some((1, 2)),
+            old_signature: None,
+            new_signature: None,
+            is_exported,
+            old_name: None,
+            old_file_path: None,
+        }
+    }
+
+    #[test]
+    fn test_advise_bump_levels() {
+        let result = DiffResult {
+            commit: "abc".to_string(),
+            against_worktree: false,
+            symbols: vec![
+                diff("helper", ChangeType::Deleted, false),
+                diff("new_api", ChangeType::Added, true),
+            ],
+            files_analyzed: 1,
+        };
+        let advice = advise(&result, "v1.2.0");
+        assert_eq!(advice.bump, Bump::Minor);
+        assert_eq!(advice.next_version.as_deref(), Some("v1.3.0"));
+        assert_eq!(advice.justifications[0].name, "new_api");
+
+        let result = DiffResult {
+            commit: "abc".to_string(),
+            against_worktree: false,
+            symbols: vec![diff("old_api", ChangeType::Deleted, true)],
+            files_analyzed: 1,
+        };
+        assert_eq!(advise(&result, "v1.2.0").bump, Bump::Major);
+    }
+
+    #[test]
+    fn test_next_version() {
+        assert_eq!(next_version("1.2.3", Bump::Patch).as_deref(), Some("1.2.4"));
+        assert_eq!(
+            next_version("v1.2.3", Bump::Major).as_deref(),
+            Some("v2.0.0")
+        );
+        assert_eq!(next_version("0.4.1", Bump::Major).as_deref(), Some("0.5.0"));
+        assert_eq!(
+            next_version("v2.0.0-rc.1", Bump::Minor).as_deref(),
+            Some("v2.1.0")
+        );
+        assert_eq!(next_version("main", Bump::Major), None);
+    }
+}
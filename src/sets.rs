@@ -0,0 +1,174 @@
+//! Named result sets: `cm query auth --save-as auth-hits` pins the symbols a query found in
+//! `.codemapper/sets/auth-hits.json`, and later steps (`cm show auth-hits`,
+//! `cm callers --from-set auth-hits`) work from that list instead of searching again and
+//! getting results that drift as the code or the query changes.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::failure::{ErrorCode, Failure};
+use crate::models::Symbol;
+
+/// Sets directory, inside the cache base directory (`.codemapper/`)
+pub const SETS_DIR: &str = "sets";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSet {
+    pub name: String,
+    /// The command that found the symbols
+    pub command: String,
+    /// Seconds since the Unix epoch
+    pub saved: u64,
+    pub symbols: Vec<Symbol>,
+}
+
+impl SymbolSet {
+    /// Distinct symbol names, in set order
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for symbol in &self.symbols {
+            if !names.iter().any(|name| *name == *symbol.name) {
+                names.push(symbol.name.to_string());
+            }
+        }
+        names
+    }
+
+    /// Files of the set modified since it was saved, where lines may have moved
+    pub fn changed_files(&self) -> Vec<&Path> {
+        let mut changed: Vec<&Path> = Vec::new();
+        for symbol in &self.symbols {
+            let path: &Path = &symbol.file_path;
+            if changed.contains(&path) {
+                continue;
+            }
+            let modified = fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .map(|time| unix_secs(time) > self.saved)
+                // A file that is gone has changed too
+                .unwrap_or(true);
+            if modified {
+                changed.push(path);
+            }
+        }
+        changed
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+fn set_path(base_dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        && !name.starts_with('.');
+    if !valid {
+        anyhow::bail!(Failure::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "Invalid set name '{}': use letters, digits, '_', '-' and '.'",
+                name
+            )
+        ));
+    }
+    Ok(base_dir.join(SETS_DIR).join(format!("{}.json", name)))
+}
+
+/// Save `symbols` as the set `name`, replacing any set of that name
+pub fn save_set(
+    base_dir: &Path,
+    name: &str,
+    command: String,
+    symbols: &[&Symbol],
+) -> Result<SymbolSet> {
+    let path = set_path(base_dir, name)?;
+    fs::create_dir_all(base_dir.join(SETS_DIR)).context("Failed to create sets directory")?;
+    let set = SymbolSet {
+        name: name.to_string(),
+        command,
+        saved: unix_secs(SystemTime::now()),
+        symbols: symbols.iter().map(|symbol| (*symbol).clone()).collect(),
+    };
+    fs::write(&path, serde_json::to_string(&set)?).context("Failed to write set file")?;
+    Ok(set)
+}
+
+pub fn load_set(base_dir: &Path, name: &str) -> Result<SymbolSet> {
+    let path = set_path(base_dir, name)?;
+    if !path.exists() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("Set '{}' not found; save one with --save-as {}", name, name)
+        ));
+    }
+    let json = fs::read_to_string(&path).context("Failed to read set file")?;
+    serde_json::from_str(&json).context("Failed to parse set file")
+}
+
+/// Every saved set, by name
+pub fn list_sets(base_dir: &Path) -> Result<Vec<SymbolSet>> {
+    let dir = base_dir.join(SETS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut sets = Vec::new();
+    for entry in fs::read_dir(&dir).context("Failed to read sets directory")? {
+        let path = entry.context("Failed to read directory entry")?.path();
+        if path.extension().is_some_and(|e| e == "json") {
+            if let Some(name) = path.file_stem() {
+                sets.push(load_set(base_dir, &name.to_string_lossy())?);
+            }
+        }
+    }
+    sets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(sets)
+}
+
+pub fn delete_set(base_dir: &Path, name: &str) -> Result<()> {
+    let path = set_path(base_dir, name)?;
+    if !path.exists() {
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("Set '{}' not found", name)
+        ));
+    }
+    fs::remove_file(&path).context("Failed to delete set file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+    use crate::models::Language;
+
+    #[test]
+    fn test_save_and_load_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("auth.py");
+        let code =
+            "def login(user):\n    pass\n\nclass Auth:\n    def login(self):\n        pass\n";
+        fs::write(&source, code).unwrap();
+        let file = index_file(&source, code, Language::Python, None).unwrap();
+        let symbols: Vec<&Symbol> = file.symbols.iter().collect();
+
+        let base = dir.path().join(".codemapper");
+        save_set(&base, "auth-hits", "cm query login".to_string(), &symbols).unwrap();
+        let set = load_set(&base, "auth-hits").unwrap();
+        assert_eq!(set.symbols.len(), 3);
+        assert_eq!(set.names(), ["Auth", "login"]);
+        assert_eq!(set.command, "cm query login");
+        assert_eq!(list_sets(&base).unwrap().len(), 1);
+
+        assert!(load_set(&base, "missing").is_err());
+        assert!(save_set(&base, "../escape", String::new(), &symbols).is_err());
+        delete_set(&base, "auth-hits").unwrap();
+        assert!(list_sets(&base).unwrap().is_empty());
+    }
+}
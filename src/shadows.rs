@@ -0,0 +1,216 @@
+//! `cm shadows`: names defined as the same kind in more than one non-test file. Each
+//! collision is classified by comparing the definitions, so copy-pasted code (identical
+//! bodies) and copies that have since drifted apart (same signature, different bodies)
+//! stand out from names that merely happen to coincide.
+
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::callgraph;
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+
+/// Methods and fields are left out: `new`, `__init__` or `id` in many types is normal
+const KINDS: &[SymbolType] = &[
+    SymbolType::Function,
+    SymbolType::Class,
+    SymbolType::Enum,
+    SymbolType::Interface,
+    SymbolType::TypeAlias,
+    SymbolType::StaticField,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Similarity {
+    /// Every definition has the same body, modulo whitespace
+    Identical,
+    /// Same signature, different bodies
+    Drifted,
+    /// Unrelated definitions that share a name
+    Different,
+}
+
+impl Similarity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Similarity::Identical => "identical",
+            Similarity::Drifted => "drifted",
+            Similarity::Different => "different",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Similarity::Identical => "copy-pasted, bodies match",
+            Similarity::Drifted => "same signature, bodies differ",
+            Similarity::Different => "unrelated definitions",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Shadow {
+    pub name: String,
+    pub kind: SymbolType,
+    pub similarity: Similarity,
+    /// In file and line order
+    pub definitions: Vec<Symbol>,
+}
+
+impl Shadow {
+    pub fn file_count(&self) -> usize {
+        self.definitions
+            .iter()
+            .map(|s| &s.file_path)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+/// Whitespace-collapsed source of `symbol`, read from `content`
+pub(crate) fn normalized_body(content: &str, symbol: &Symbol) -> String {
+    content
+        .lines()
+        .skip(symbol.line_start.saturating_sub(1))
+        .take(symbol.line_end + 1 - symbol.line_start.max(1))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn similarity(definitions: &[Symbol], contents: &mut HashMap<Arc<Path>, String>) -> Similarity {
+    let bodies: HashSet<String> = definitions
+        .iter()
+        .map(|s| {
+            let content = contents
+                .entry(s.file_path.clone())
+                .or_insert_with(|| fs::read_to_string(&s.file_path).unwrap_or_default());
+            normalized_body(content, s)
+        })
+        .collect();
+    if bodies.len() == 1 {
+        return Similarity::Identical;
+    }
+    let signatures: HashSet<Option<String>> = definitions
+        .iter()
+        .map(|s| {
+            s.signature
+                .as_ref()
+                .map(|sig| sig.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .collect();
+    match signatures.iter().next() {
+        Some(Some(_)) if signatures.len() == 1 => Similarity::Drifted,
+        _ => Similarity::Different,
+    }
+}
+
+/// Every name defined as the same kind in two or more non-test files, copies first, then by
+/// number of definitions
+pub fn find_shadows(index: &CodeIndex, exported_only: bool) -> Result<Vec<Shadow>> {
+    let mut groups: HashMap<(&str, SymbolType), Vec<&Symbol>> = HashMap::new();
+    for file in index.files() {
+        if file.language == Language::Markdown || callgraph::is_test_file(&file.path, file.language)
+        {
+            continue;
+        }
+        // Helpers in a Rust `#[cfg(test)]` module are test code too
+        let test_module = match file.language {
+            Language::Rust => fs::read_to_string(&file.path)
+                .unwrap_or_default()
+                .lines()
+                .position(|line| line.starts_with("#[cfg(test)]"))
+                .map(|i| i + 1),
+            _ => None,
+        };
+        for symbol in index.get_file_symbols(&file.path) {
+            if !KINDS.contains(&symbol.symbol_type)
+                || exported_only && !symbol.is_exported
+                || test_module.is_some_and(|line| symbol.line_start > line)
+            {
+                continue;
+            }
+            groups
+                .entry((&symbol.name, symbol.symbol_type))
+                .or_default()
+                .push(symbol);
+        }
+    }
+
+    let mut contents = HashMap::new();
+    let mut shadows = Vec::new();
+    for ((name, kind), symbols) in groups {
+        let files: HashSet<&Path> = symbols.iter().map(|s| s.file_path.as_ref()).collect();
+        if files.len() < 2 {
+            continue;
+        }
+        let mut definitions: Vec<Symbol> = symbols.into_iter().cloned().collect();
+        definitions.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.line_start.cmp(&b.line_start))
+        });
+        shadows.push(Shadow {
+            name: name.to_string(),
+            kind,
+            similarity: similarity(&definitions, &mut contents),
+            definitions,
+        });
+    }
+
+    shadows.sort_by(|a, b| {
+        a.similarity
+            .cmp(&b.similarity)
+            .then(b.definitions.len().cmp(&a.definitions.len()))
+            .then(a.name.cmp(&b.name))
+    });
+    Ok(shadows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_directory;
+
+    #[test]
+    fn test_find_shadows_classifies_copies() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write(
+            "a.py",
+            "def slugify(text):\n    return text.lower()\n\ndef load(path):\n    return open(path).read()\n\nclass Config:\n    debug = False\n",
+        );
+        write(
+            "b.py",
+            "def slugify(text):\n    return   text.lower()\n\ndef load(path):\n    with open(path) as f:\n        return f.read()\n\nclass Config(Base):\n    verbose = True\n    debug = True\n",
+        );
+        write("test_a.py", "def slugify(text):\n    return text\n");
+        write("x.rs", "pub fn run() {}\nfn helper() {}\n");
+        write("y.rs", "pub fn run() {}\nfn helper() {}\n");
+
+        let index = index_directory(dir.path(), &["py", "rs"]).unwrap();
+        let summary = |shadows: Vec<Shadow>| -> Vec<(String, Similarity, usize)> {
+            shadows
+                .into_iter()
+                .map(|s| (s.name.clone(), s.similarity, s.file_count()))
+                .collect()
+        };
+
+        assert_eq!(
+            summary(find_shadows(&index, false).unwrap()),
+            vec![
+                ("helper".to_string(), Similarity::Identical, 2),
+                ("run".to_string(), Similarity::Identical, 2),
+                ("slugify".to_string(), Similarity::Identical, 2),
+                ("load".to_string(), Similarity::Drifted, 2),
+                ("Config".to_string(), Similarity::Different, 2),
+            ]
+        );
+        let exported = summary(find_shadows(&index, true).unwrap());
+        assert!(exported.iter().any(|(name, ..)| name == "run"));
+        assert!(exported.iter().all(|(name, ..)| name != "helper"));
+    }
+}
@@ -1,7 +1,9 @@
 use crate::diff::{ChangeType, DiffResult, SymbolDiff};
+use crate::failure::{ErrorCode, Failure};
 use crate::git;
 use crate::index::CodeIndex;
 use crate::models::SymbolType;
+use crate::paths::DisplaySlash;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -19,6 +21,8 @@ pub struct SnapshotSymbol {
     pub file_path: PathBuf,
     pub line_start: usize,
     pub line_end: usize,
+    #[serde(default)]
+    pub is_exported: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,12 +77,13 @@ pub fn save_snapshot(
         .iter()
         .filter(|s| !s.name.is_empty())
         .map(|s| SnapshotSymbol {
-            name: s.name.clone(),
+            name: s.name.to_string(),
             symbol_type: s.symbol_type,
             signature: s.signature.clone(),
-            file_path: s.file_path.clone(),
+            file_path: s.file_path.to_path_buf(),
             line_start: s.line_start,
             line_end: s.line_end,
+            is_exported: s.is_exported,
         })
         .collect();
 
@@ -98,11 +103,14 @@ pub fn load_snapshot(name: &str, root_path: &Path, cache_dir: Option<&Path>) ->
     let snapshot_path = snapshots_dir.join(format!("{}.json", name));
 
     if !snapshot_path.exists() {
-        anyhow::bail!(
-            "Snapshot '{}' not found at {}",
-            name,
-            snapshot_path.display()
-        );
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!(
+                "Snapshot '{}' not found at {}",
+                name,
+                snapshot_path.display_slash()
+            )
+        ));
     }
 
     let json = fs::read_to_string(&snapshot_path).context("Failed to read snapshot file")?;
@@ -141,7 +149,10 @@ pub fn delete_snapshot(name: &str, root_path: &Path, cache_dir: Option<&Path>) -
     let snapshot_path = snapshots_dir.join(format!("{}.json", name));
 
     if !snapshot_path.exists() {
-        anyhow::bail!("Snapshot '{}' not found", name);
+        anyhow::bail!(Failure::new(
+            ErrorCode::NotFound,
+            format!("Snapshot '{}' not found", name)
+        ));
     }
 
     fs::remove_file(&snapshot_path).context("Failed to delete snapshot file")?;
@@ -155,38 +166,42 @@ pub fn compare_to_snapshot(index: &CodeIndex, snapshot: &Snapshot) -> DiffResult
     let old_map: HashMap<(&str, SymbolType, &Path), &SnapshotSymbol> = snapshot
         .symbols
         .iter()
-        .map(|s| ((s.name.as_str(), s.symbol_type, s.file_path.as_path()), s))
+        .map(|s| ((s.name.as_str(), s.symbol_type, &*s.file_path), s))
         .collect();
 
     let current_symbols = index.all_symbols();
     let new_map: HashMap<(&str, SymbolType, &Path), _> = current_symbols
         .iter()
         .filter(|s| !s.name.is_empty())
-        .map(|s| ((s.name.as_str(), s.symbol_type, s.file_path.as_path()), *s))
+        .map(|s| ((s.name.as_str(), s.symbol_type, &*s.file_path), *s))
         .collect();
 
     for new_sym in current_symbols.iter().filter(|s| !s.name.is_empty()) {
         let key = (
             new_sym.name.as_str(),
             new_sym.symbol_type,
-            new_sym.file_path.as_path(),
+            &*new_sym.file_path,
         );
 
         match old_map.get(&key) {
             None => {
                 symbol_diffs.push(SymbolDiff {
-                    name: new_sym.name.clone(),
+                    name: new_sym.name.to_string(),
                     symbol_type: new_sym.symbol_type,
                     change_type: ChangeType::Added,
-                    file_path: new_sym.file_path.clone(),
+                    file_path: new_sym.file_path.to_path_buf(),
                     old_lines: None,
                     new_lines: Some((new_sym.line_start, new_sym.line_end)),
                     old_signature: None,
                     new_signature: new_sym.signature.clone(),
+                    is_exported: new_sym.is_exported,
+                    old_name: None,
+                    old_file_path: None,
                 });
             }
             Some(old_sym) => {
-                let sig_changed = old_sym.signature != new_sym.signature;
+                let sig_changed = old_sym.signature != new_sym.signature
+                    || (old_sym.is_exported && !new_sym.is_exported);
                 let lines_changed = old_sym.line_start != new_sym.line_start
                     || old_sym.line_end != new_sym.line_end;
                 let size_changed = (old_sym.line_end.saturating_sub(old_sym.line_start))
@@ -194,25 +209,31 @@ pub fn compare_to_snapshot(index: &CodeIndex, snapshot: &Snapshot) -> DiffResult
 
                 if sig_changed {
                     symbol_diffs.push(SymbolDiff {
-                        name: new_sym.name.clone(),
+                        name: new_sym.name.to_string(),
                         symbol_type: new_sym.symbol_type,
                         change_type: ChangeType::SignatureChanged,
-                        file_path: new_sym.file_path.clone(),
+                        file_path: new_sym.file_path.to_path_buf(),
                         old_lines: Some((old_sym.line_start, old_sym.line_end)),
                         new_lines: Some((new_sym.line_start, new_sym.line_end)),
                         old_signature: old_sym.signature.clone(),
                         new_signature: new_sym.signature.clone(),
+                        is_exported: old_sym.is_exported || new_sym.is_exported,
+                        old_name: None,
+                        old_file_path: None,
                     });
                 } else if lines_changed || size_changed {
                     symbol_diffs.push(SymbolDiff {
-                        name: new_sym.name.clone(),
+                        name: new_sym.name.to_string(),
                         symbol_type: new_sym.symbol_type,
                         change_type: ChangeType::Modified,
-                        file_path: new_sym.file_path.clone(),
+                        file_path: new_sym.file_path.to_path_buf(),
                         old_lines: Some((old_sym.line_start, old_sym.line_end)),
                         new_lines: Some((new_sym.line_start, new_sym.line_end)),
                         old_signature: old_sym.signature.clone(),
                         new_signature: new_sym.signature.clone(),
+                        is_exported: old_sym.is_exported || new_sym.is_exported,
+                        old_name: None,
+                        old_file_path: None,
                     });
                 }
             }
@@ -236,6 +257,9 @@ pub fn compare_to_snapshot(index: &CodeIndex, snapshot: &Snapshot) -> DiffResult
                 new_lines: None,
                 old_signature: old_sym.signature.clone(),
                 new_signature: None,
+                is_exported: old_sym.is_exported,
+                old_name: None,
+                old_file_path: None,
             });
         }
     }
@@ -253,6 +277,7 @@ pub fn compare_to_snapshot(index: &CodeIndex, snapshot: &Snapshot) -> DiffResult
 
     DiffResult {
         commit,
+        against_worktree: true,
         symbols: symbol_diffs,
         files_analyzed,
     }
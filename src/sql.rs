@@ -0,0 +1,316 @@
+//! `cm sql`: SQL statements embedded in string literals. Candidate strings are picked by
+//! their leading keyword, validated with sqlparser, and reported with the statement type,
+//! the tables they reference and the symbol they live in.
+
+use anyhow::{Context, Result};
+use sqlparser::ast::{visit_relations, ObjectName, Statement};
+use sqlparser::dialect::{Dialect, GenericDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
+use sqlparser::parser::Parser as SqlParser;
+use std::collections::BTreeSet;
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use tree_sitter::{Node, Parser};
+
+use crate::index::CodeIndex;
+use crate::scan::regex;
+use crate::value_refs::{enclosing_symbol, grammar};
+
+#[derive(Debug, Clone)]
+pub struct SqlQuery {
+    /// `SELECT`, `INSERT`, `CREATE TABLE`, ...
+    pub kind: String,
+    /// Referenced tables, CTE names excluded
+    pub tables: Vec<String>,
+    /// The statement on one line, placeholders as written
+    pub sql: String,
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// Innermost symbol containing the literal
+    pub enclosing: Option<String>,
+}
+
+const KEYWORDS: [&str; 11] = [
+    "SELECT", "INSERT", "UPDATE", "DELETE", "WITH", "CREATE", "ALTER", "DROP", "MERGE", "REPLACE",
+    "TRUNCATE",
+];
+
+/// Every embedded statement in the indexed files, in file and line order
+pub fn find_sql(index: &CodeIndex) -> Result<Vec<SqlQuery>> {
+    let mut queries = Vec::new();
+    let mut parser = Parser::new();
+
+    for file in index.files() {
+        let Some(language) = grammar(file.language) else {
+            continue;
+        };
+        let content = fs::read_to_string(&file.path).unwrap_or_default();
+        let upper = content.to_ascii_uppercase();
+        if !KEYWORDS.iter().any(|k| upper.contains(k)) {
+            continue;
+        }
+        parser
+            .set_language(&language)
+            .with_context(|| format!("Failed to set {} language", file.language.as_str()))?;
+        let Some(tree) = parser.parse(&content, None) else {
+            continue;
+        };
+
+        for (offset, text) in string_literals(tree.root_node(), &content) {
+            let Some((kind, tables)) = analyze(&text) else {
+                continue;
+            };
+            let line = content[..offset].matches('\n').count() + 1;
+            queries.push(SqlQuery {
+                kind,
+                tables,
+                sql: text.split_whitespace().collect::<Vec<_>>().join(" "),
+                file_path: file.path.clone(),
+                line,
+                enclosing: enclosing_symbol(index, &file.path, line).map(|s| s.name.to_string()),
+            });
+        }
+    }
+
+    queries.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+    Ok(queries)
+}
+
+fn is_string(node: Node) -> bool {
+    let kind = node.kind();
+    kind.contains("string") && !kind.contains("content") && !kind.contains("fragment")
+        || kind == "text_block"
+}
+
+/// `a + "b"` chains, which concatenate one statement over several literals
+fn is_concatenation(node: Node) -> bool {
+    node.kind() == "binary_expression"
+        && node
+            .child_by_field_name("operator")
+            .is_some_and(|op| op.kind() == "+")
+}
+
+/// Contents of a literal; interpolations (`${x}`, f-string `{x}`) become `?`
+fn literal_text(node: Node, content: &str) -> String {
+    if node.kind() == "concatenated_string" {
+        let mut cursor = node.walk();
+        let parts: Vec<String> = node
+            .named_children(&mut cursor)
+            .map(|child| literal_text(child, content))
+            .collect();
+        return parts.join("");
+    }
+    match node.child(0) {
+        None => {
+            return content[node.byte_range()]
+                .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+                .to_string()
+        }
+        // Rust raw strings keep their delimiters in the literal itself
+        Some(only) if node.child_count() == 1 => return content[only.byte_range()].to_string(),
+        Some(_) => {}
+    }
+    let first = node.child(0).map_or(node.start_byte(), |c| c.end_byte());
+    let last = node
+        .child(node.child_count() - 1)
+        .map_or(node.end_byte(), |c| c.start_byte());
+    let mut text = String::new();
+    let mut at = first;
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if matches!(child.kind(), "interpolation" | "template_substitution") {
+            text.push_str(&content[at..child.start_byte()]);
+            text.push('?');
+            at = child.end_byte();
+        }
+    }
+    if at < last {
+        text.push_str(&content[at..last]);
+    }
+    text
+}
+
+/// Flattened operands of a `+` chain: literals as text, anything else as `?`
+fn concatenated_text(node: Node, content: &str) -> String {
+    if is_concatenation(node) {
+        let left = node.child_by_field_name("left");
+        let right = node.child_by_field_name("right");
+        return [left, right]
+            .into_iter()
+            .flatten()
+            .map(|operand| concatenated_text(operand, content))
+            .collect();
+    }
+    if is_string(node) {
+        literal_text(node, content)
+    } else {
+        "?".to_string()
+    }
+}
+
+/// (byte offset, text) of every outermost string literal or literal concatenation
+pub(crate) fn string_literals(root: Node, content: &str) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if is_concatenation(node) {
+            let text = concatenated_text(node, content);
+            if text != "?" && text.contains(|c: char| c.is_alphabetic()) {
+                found.push((node.start_byte(), text));
+                continue;
+            }
+        } else if is_string(node) {
+            found.push((node.start_byte(), literal_text(node, content)));
+            continue;
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    found
+}
+
+fn looks_like_sql(text: &str) -> bool {
+    let Some(first) = text.split_whitespace().next() else {
+        return false;
+    };
+    // "Select a file" in a message or docstring is prose
+    let upper = first.to_uppercase();
+    (first == upper || first == first.to_lowercase())
+        && KEYWORDS.contains(&upper.as_str())
+        && text.split_whitespace().nth(1).is_some()
+}
+
+/// Driver placeholders the dialects don't accept, and `format!`/`str.format` holes
+fn normalize(text: &str) -> String {
+    let text = regex(r"%\([A-Za-z_]\w*\)s|%s|%d").replace_all(text, "?");
+    regex(r"\{[A-Za-z0-9_.:]*\}")
+        .replace_all(&text, "?")
+        .to_string()
+}
+
+fn kind_of(statement: &Statement, text: &str) -> String {
+    match statement {
+        Statement::Query(_) => "SELECT".to_string(),
+        Statement::Insert(_) => "INSERT".to_string(),
+        Statement::Update { .. } => "UPDATE".to_string(),
+        Statement::Delete(_) => "DELETE".to_string(),
+        Statement::CreateTable(_) => "CREATE TABLE".to_string(),
+        Statement::CreateIndex(_) => "CREATE INDEX".to_string(),
+        Statement::CreateView { .. } => "CREATE VIEW".to_string(),
+        Statement::AlterTable { .. } => "ALTER TABLE".to_string(),
+        Statement::Drop { object_type, .. } => format!("DROP {}", object_type),
+        Statement::Merge { .. } => "MERGE".to_string(),
+        Statement::Truncate { .. } => "TRUNCATE".to_string(),
+        _ => text
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_uppercase(),
+    }
+}
+
+/// Statement type and tables of `text` when it parses as SQL in one of the common dialects
+pub fn analyze(text: &str) -> Option<(String, Vec<String>)> {
+    if !looks_like_sql(text) {
+        return None;
+    }
+    let sql = normalize(text);
+    let dialects: [&dyn Dialect; 4] = [
+        &GenericDialect {},
+        &PostgreSqlDialect {},
+        &MySqlDialect {},
+        &SQLiteDialect {},
+    ];
+    let statements = dialects
+        .iter()
+        .find_map(|dialect| SqlParser::parse_sql(*dialect, &sql).ok())
+        .filter(|statements| !statements.is_empty())?;
+
+    let mut ctes = BTreeSet::new();
+    for statement in &statements {
+        if let Statement::Query(query) = statement {
+            for cte in query.with.iter().flat_map(|w| &w.cte_tables) {
+                ctes.insert(cte.alias.name.value.to_lowercase());
+            }
+        }
+    }
+    let mut relations: Vec<ObjectName> = Vec::new();
+    let _ = visit_relations(&statements, |relation| {
+        relations.push(relation.clone());
+        ControlFlow::<()>::Continue(())
+    });
+    for statement in &statements {
+        if let Statement::Drop { names, .. } = statement {
+            relations.extend(names.iter().cloned());
+        }
+    }
+    // Unquoted, so `"users"` and `users` are the same table
+    let tables: BTreeSet<String> = relations
+        .into_iter()
+        .map(|name| {
+            let parts: Vec<&str> = name.0.iter().map(|ident| ident.value.as_str()).collect();
+            parts.join(".")
+        })
+        .filter(|name| !ctes.contains(&name.to_lowercase()))
+        .collect();
+
+    Some((kind_of(&statements[0], text), tables.into_iter().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+    use crate::models::Language;
+
+    #[test]
+    fn test_analyze_statements() {
+        let (kind, tables) = analyze(
+            "WITH recent AS (SELECT * FROM orders) SELECT u.name FROM users u JOIN recent r ON r.user_id = u.id WHERE u.id = %s",
+        )
+        .unwrap();
+        assert_eq!(kind, "SELECT");
+        assert_eq!(tables, vec!["orders", "users"]);
+
+        let (kind, tables) = analyze("INSERT INTO audit_log (msg) VALUES ($1)").unwrap();
+        assert_eq!(
+            (kind.as_str(), tables),
+            ("INSERT", vec!["audit_log".to_string()])
+        );
+        let (kind, tables) = analyze(r#"DROP TABLE IF EXISTS "Tmp""#).unwrap();
+        assert_eq!(
+            (kind.as_str(), tables),
+            ("DROP TABLE", vec!["Tmp".to_string()])
+        );
+
+        assert!(analyze("Select an option").is_none());
+        assert!(analyze("update").is_none());
+    }
+
+    #[test]
+    fn test_find_sql_in_literals() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = r#"def load(db, user_id):
+    """Select the user."""
+    return db.execute(f"SELECT * FROM users WHERE id = {user_id}")
+
+def save(db, name):
+    db.execute("UPDATE accounts "
+               "SET name = ? WHERE id = 1", (name,))
+"#;
+        let index = index_of(dir.path(), &[("repo.py", source, Language::Python)]);
+
+        let queries = find_sql(&index).unwrap();
+        let found: Vec<(&str, usize, Option<&str>)> = queries
+            .iter()
+            .map(|q| (q.kind.as_str(), q.line, q.enclosing.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("SELECT", 3, Some("load")), ("UPDATE", 6, Some("save"))]
+        );
+        assert_eq!(queries[1].tables, vec!["accounts"]);
+        assert_eq!(queries[1].sql, "UPDATE accounts SET name = ? WHERE id = 1");
+    }
+}
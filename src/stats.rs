@@ -0,0 +1,503 @@
+//! Aggregate views computed from the index: the per-language matrix behind `cm stats`,
+//! `cm stats --top` (the largest files, longest functions, classes with the most methods
+//! and files with the most symbols), the
+//! per-directory rollups behind `cm map --group-by dir` and the directory → file → symbol
+//! size tree behind `cm map --mode sizes`.
+
+use crate::callgraph::is_test_file;
+use crate::index::CodeIndex;
+use crate::models::{FileInfo, Language, LineCounts, Symbol, SymbolType};
+use crate::paths::DisplaySlash;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+/// One row of the `cm stats` language matrix
+pub struct LanguageStats {
+    pub language: Language,
+    pub files: usize,
+    /// Files `cm tests` would treat as test code; the rest are production files
+    pub test_files: usize,
+    pub lines: LineCounts,
+    pub functions: usize,
+    pub classes: usize,
+    pub methods: usize,
+    /// Every symbol kind, the three above included
+    pub symbols: usize,
+}
+
+impl LanguageStats {
+    fn new(language: Language) -> Self {
+        LanguageStats {
+            language,
+            files: 0,
+            test_files: 0,
+            lines: LineCounts::default(),
+            functions: 0,
+            classes: 0,
+            methods: 0,
+            symbols: 0,
+        }
+    }
+
+    pub fn prod_files(&self) -> usize {
+        self.files - self.test_files
+    }
+
+    pub fn avg_symbols_per_file(&self) -> f64 {
+        if self.files == 0 {
+            0.0
+        } else {
+            self.symbols as f64 / self.files as f64
+        }
+    }
+}
+
+/// Per-language file, line and symbol counts, most files first
+pub fn language_breakdown(index: &CodeIndex) -> Vec<LanguageStats> {
+    let mut langs: HashMap<Language, LanguageStats> = HashMap::new();
+
+    for file in index.files() {
+        let stats = langs
+            .entry(file.language)
+            .or_insert_with(|| LanguageStats::new(file.language));
+        stats.files += 1;
+        if is_test_file(&file.path, file.language) {
+            stats.test_files += 1;
+        }
+        stats.lines.add(file.lines);
+        for symbol in index.get_file_symbols(&file.path) {
+            stats.symbols += 1;
+            match symbol.symbol_type {
+                SymbolType::Function => stats.functions += 1,
+                SymbolType::Class => stats.classes += 1,
+                SymbolType::Method => stats.methods += 1,
+                _ => {}
+            }
+        }
+    }
+
+    let mut langs: Vec<LanguageStats> = langs.into_values().collect();
+    langs.sort_by(|a, b| {
+        b.files
+            .cmp(&a.files)
+            .then(a.language.as_str().cmp(b.language.as_str()))
+    });
+    langs
+}
+
+pub struct TopReport<'a> {
+    /// (file, lines of code)
+    pub largest_files: Vec<(&'a Path, usize)>,
+    /// (function or method, length in lines)
+    pub longest_functions: Vec<(&'a Symbol, usize)>,
+    pub biggest_classes: Vec<ClassSize<'a>>,
+    /// (file, symbol count)
+    pub densest_files: Vec<(&'a Path, usize)>,
+}
+
+pub struct ClassSize<'a> {
+    /// The type's own definition when the file has one, otherwise its first impl block
+    pub class: &'a Symbol,
+    pub name: String,
+    pub methods: usize,
+}
+
+/// Name of the type a class-like symbol belongs to: `impl<T> Display for Foo<T>` -> `Foo`
+fn owner_name(name: &str) -> &str {
+    let Some(rest) = name.strip_prefix("impl") else {
+        return name;
+    };
+    let rest = rest.rsplit(" for ").next().unwrap_or(rest);
+    let rest = if rest.starts_with('<') {
+        rest.split_once("> ").map(|(_, ty)| ty).unwrap_or(rest)
+    } else {
+        rest
+    };
+    rest.trim().split('<').next().unwrap_or(rest).trim()
+}
+
+impl<'a> TopReport<'a> {
+    pub fn compute(index: &'a CodeIndex, limit: usize) -> Self {
+        let mut largest_files: Vec<(&Path, usize)> = Vec::new();
+        let mut densest_files: Vec<(&Path, usize)> = Vec::new();
+        let mut longest_functions: Vec<(&Symbol, usize)> = Vec::new();
+        let mut classes: HashMap<(&Path, &str), ClassSize> = HashMap::new();
+
+        for file in index.files() {
+            let symbols = index.get_file_symbols(&file.path);
+            largest_files.push((&file.path, file.lines.code));
+            densest_files.push((&file.path, symbols.len()));
+
+            let containers: Vec<&Symbol> = symbols
+                .iter()
+                .copied()
+                .filter(|s| s.symbol_type == SymbolType::Class)
+                .collect();
+            for &container in &containers {
+                let name = owner_name(&container.name);
+                let entry = classes
+                    .entry((&*file.path, name))
+                    .or_insert_with(|| ClassSize {
+                        class: container,
+                        name: name.to_string(),
+                        methods: 0,
+                    });
+                if container.name == name {
+                    entry.class = container;
+                }
+            }
+
+            for &symbol in &symbols {
+                if !matches!(
+                    symbol.symbol_type,
+                    SymbolType::Function | SymbolType::Method
+                ) || symbol.name == "anonymous"
+                {
+                    continue;
+                }
+                longest_functions.push((symbol, symbol.line_end + 1 - symbol.line_start));
+
+                // Innermost class (or impl block) enclosing the method
+                let owner = containers
+                    .iter()
+                    .filter(|c| c.line_start <= symbol.line_start && symbol.line_end <= c.line_end)
+                    .min_by_key(|c| c.line_end - c.line_start);
+                if let Some(owner) = owner {
+                    if let Some(class) = classes.get_mut(&(&*file.path, owner_name(&owner.name))) {
+                        class.methods += 1;
+                    }
+                }
+            }
+        }
+
+        largest_files.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        largest_files.truncate(limit);
+        densest_files.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        densest_files.truncate(limit);
+        longest_functions.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then(a.0.file_path.cmp(&b.0.file_path))
+                .then(a.0.line_start.cmp(&b.0.line_start))
+        });
+        longest_functions.truncate(limit);
+
+        let mut biggest_classes: Vec<ClassSize> =
+            classes.into_values().filter(|c| c.methods > 0).collect();
+        biggest_classes.sort_by(|a, b| {
+            b.methods
+                .cmp(&a.methods)
+                .then(a.class.file_path.cmp(&b.class.file_path))
+                .then(a.class.line_start.cmp(&b.class.line_start))
+        });
+        biggest_classes.truncate(limit);
+
+        Self {
+            largest_files,
+            longest_functions,
+            biggest_classes,
+            densest_files,
+        }
+    }
+}
+
+/// Files under one directory, with their symbols counted per kind
+pub struct DirRollup {
+    pub dir: PathBuf,
+    pub files: usize,
+    pub lines: LineCounts,
+    pub symbols: usize,
+    /// Most common kind first
+    pub by_type: Vec<(SymbolType, usize)>,
+}
+
+/// Files grouped by their directory, cut to `depth` levels below `root`; files in
+/// shallower directories are counted under those directories
+pub fn dir_rollups(index: &CodeIndex, root: &Path, depth: usize) -> Vec<DirRollup> {
+    let mut dirs: BTreeMap<PathBuf, (usize, LineCounts, HashMap<SymbolType, usize>)> =
+        BTreeMap::new();
+
+    for file in index.files() {
+        let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+        let dir: PathBuf = relative
+            .parent()
+            .into_iter()
+            .flat_map(|parent| parent.components())
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .take(depth)
+            .collect();
+
+        let dir = if dir.as_os_str().is_empty() {
+            root.to_path_buf()
+        } else {
+            root.join(dir)
+        };
+        let (files, lines, by_type) = dirs.entry(dir).or_default();
+        *files += 1;
+        lines.add(file.lines);
+        for symbol in index.get_file_symbols(&file.path) {
+            *by_type.entry(symbol.symbol_type).or_default() += 1;
+        }
+    }
+
+    dirs.into_iter()
+        .map(|(dir, (files, lines, by_type))| {
+            let mut by_type: Vec<(SymbolType, usize)> = by_type.into_iter().collect();
+            by_type.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.as_str().cmp(b.0.as_str())));
+            DirRollup {
+                dir,
+                files,
+                lines,
+                symbols: by_type.iter().map(|(_, count)| count).sum(),
+                by_type,
+            }
+        })
+        .collect()
+}
+
+/// One box of the `cm map --mode sizes` treemap. Children are nested inside their parent
+/// and never add up to more than it, so treemap tools can size boxes by `lines` directly.
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeNode {
+    /// Directory or file name (the mapped path itself at the root), or symbol name
+    pub name: String,
+    /// `dir`, `file`, or the symbol's kind
+    pub kind: &'static str,
+    /// All lines of a file, the line span of a symbol, the sum over a directory
+    pub lines: usize,
+    /// Symbols at or below this node, itself included
+    pub symbols: usize,
+    /// Largest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SizeNode>,
+}
+
+impl SizeNode {
+    fn dir(name: String) -> Self {
+        SizeNode {
+            name,
+            kind: "dir",
+            lines: 0,
+            symbols: 0,
+            children: Vec::new(),
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        self.kind == "dir"
+    }
+
+    fn subdir(&mut self, name: &str) -> &mut SizeNode {
+        let position = self
+            .children
+            .iter()
+            .position(|c| c.is_dir() && c.name == name)
+            .unwrap_or_else(|| {
+                self.children.push(SizeNode::dir(name.to_string()));
+                self.children.len() - 1
+            });
+        &mut self.children[position]
+    }
+
+    /// Add up directories bottom-up and order every level largest first
+    fn finish(&mut self) {
+        for child in &mut self.children {
+            child.finish();
+        }
+        if self.is_dir() {
+            self.lines = self.children.iter().map(|c| c.lines).sum();
+            self.symbols = self.children.iter().map(|c| c.symbols).sum();
+        }
+        self.children
+            .sort_by(|a, b| b.lines.cmp(&a.lines).then(a.name.cmp(&b.name)));
+    }
+}
+
+/// `symbols` (sorted by start, outermost first) with each one's nested symbols as children
+fn symbol_nodes(symbols: &[&Symbol]) -> Vec<SizeNode> {
+    let mut nodes = Vec::new();
+    let mut i = 0;
+    while i < symbols.len() {
+        let symbol = symbols[i];
+        let end = symbols[i + 1..]
+            .iter()
+            .position(|s| s.line_end > symbol.line_end)
+            .map_or(symbols.len(), |n| i + 1 + n);
+        let children = symbol_nodes(&symbols[i + 1..end]);
+        nodes.push(SizeNode {
+            name: symbol.name.to_string(),
+            kind: symbol.symbol_type.as_str(),
+            lines: (symbol.line_end + 1).saturating_sub(symbol.line_start.max(1)),
+            symbols: 1 + children.iter().map(|c| c.symbols).sum::<usize>(),
+            children,
+        });
+        i = end;
+    }
+    nodes
+}
+
+fn file_node(index: &CodeIndex, file: &FileInfo, name: String) -> SizeNode {
+    let mut symbols = index.get_file_symbols(&file.path);
+    symbols.sort_by(|a, b| {
+        a.line_start
+            .cmp(&b.line_start)
+            .then(b.line_end.cmp(&a.line_end))
+    });
+    let children = symbol_nodes(&symbols);
+    SizeNode {
+        name,
+        kind: "file",
+        lines: file.lines.total(),
+        symbols: children.iter().map(|c| c.symbols).sum(),
+        children,
+    }
+}
+
+/// Every indexed file under `root`, nested by directory, with its symbols nested inside it
+pub fn size_tree(index: &CodeIndex, root: &Path) -> SizeNode {
+    let mut tree = SizeNode::dir(root.display_slash().to_string());
+    for file in index.files() {
+        let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+        let mut parts: Vec<String> = relative
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let Some(name) = parts.pop() else {
+            continue;
+        };
+        let mut dir = &mut tree;
+        for part in &parts {
+            dir = dir.subdir(part);
+        }
+        dir.children.push(file_node(index, file, name));
+    }
+    tree.finish();
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_file;
+
+    #[test]
+    fn test_top_report_ranks_offenders() {
+        let mut index = CodeIndex::new();
+        let rust = "struct Foo;\nimpl Foo {\n    fn a(&self) {}\n    fn b(&self) {\n        let _ = 1;\n    }\n}\nimpl std::fmt::Debug for Foo {\n    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { Ok(()) }\n}\nfn main() {}\n";
+        index.add_file(index_file(Path::new("src/foo.rs"), rust, Language::Rust, None).unwrap());
+        let python = "def long():\n    a = 1\n    b = 2\n    c = 3\n    return a + b + c\n";
+        index.add_file(index_file(Path::new("tools.py"), python, Language::Python, None).unwrap());
+
+        let report = TopReport::compute(&index, 2);
+
+        assert_eq!(report.largest_files[0].0, Path::new("src/foo.rs"));
+        assert_eq!(report.longest_functions.len(), 2);
+        assert_eq!(report.longest_functions[0].0.name, "long");
+        assert_eq!(report.longest_functions[0].1, 5);
+
+        assert_eq!(report.biggest_classes.len(), 1);
+        let foo = &report.biggest_classes[0];
+        assert_eq!((foo.name.as_str(), foo.methods), ("Foo", 3));
+        assert_eq!(foo.class.name, "Foo");
+
+        assert_eq!(owner_name("impl<T: Clone> Wrapper<T>"), "Wrapper");
+        assert_eq!(owner_name("impl<T> From<T> for Wrapper<T>"), "Wrapper");
+        assert_eq!(owner_name("Config"), "Config");
+    }
+
+    #[test]
+    fn test_language_breakdown_counts_kinds_and_tests() {
+        let mut index = CodeIndex::new();
+        for (path, source, language) in [
+            (
+                "src/app.py",
+                "class App:\n    def run(self):\n        pass\n\ndef main():\n    pass\n",
+                Language::Python,
+            ),
+            ("src/util.py", "def helper():\n    pass\n", Language::Python),
+            ("tests/test_app.py", "def test_run():\n    pass\n", Language::Python),
+            ("src/lib.rs", "pub fn add() {}\n", Language::Rust),
+        ] {
+            index.add_file(index_file(Path::new(path), source, language, None).unwrap());
+        }
+
+        let langs = language_breakdown(&index);
+        assert_eq!(langs.len(), 2);
+        let python = &langs[0];
+        assert_eq!(python.language, Language::Python);
+        assert_eq!((python.files, python.test_files, python.prod_files()), (3, 1, 2));
+        assert_eq!(
+            (python.functions, python.classes, python.methods, python.symbols),
+            (3, 1, 1, 5)
+        );
+        assert!((python.avg_symbols_per_file() - 5.0 / 3.0).abs() < 1e-9);
+        assert_eq!((langs[1].language, langs[1].functions), (Language::Rust, 1));
+    }
+
+    #[test]
+    fn test_dir_rollups_cut_at_depth() {
+        let mut index = CodeIndex::new();
+        for (path, source) in [
+            (
+                "./svc/api/handlers/users.py",
+                "def get():\n    pass\n\ndef put():\n    pass\n",
+            ),
+            (
+                "./svc/api/routes.py",
+                "class Router:\n    def add(self):\n        pass\n",
+            ),
+            ("./svc/main.py", "def main():\n    pass\n"),
+            ("./setup.py", "def setup():\n    pass\n"),
+        ] {
+            index.add_file(index_file(Path::new(path), source, Language::Python, None).unwrap());
+        }
+
+        let rollups = dir_rollups(&index, Path::new("."), 2);
+        let dirs: Vec<(String, usize, usize)> = rollups
+            .iter()
+            .map(|r| (r.dir.display().to_string(), r.files, r.symbols))
+            .collect();
+        assert_eq!(
+            dirs,
+            vec![
+                (".".to_string(), 1, 1),
+                ("./svc".to_string(), 1, 1),
+                ("./svc/api".to_string(), 2, 4),
+            ]
+        );
+        assert_eq!(rollups[2].by_type[0], (SymbolType::Function, 2));
+    }
+
+    #[test]
+    fn test_size_tree_nests_dirs_files_and_symbols() {
+        let mut index = CodeIndex::new();
+        for (path, source) in [
+            (
+                "./svc/api/routes.py",
+                "class Router:\n    def add(self):\n        pass\n\n    def remove(self):\n        pass\n",
+            ),
+            ("./svc/main.py", "def main():\n    pass\n"),
+            ("./setup.py", "def setup():\n    pass\n"),
+        ] {
+            index.add_file(index_file(Path::new(path), source, Language::Python, None).unwrap());
+        }
+
+        let tree = size_tree(&index, Path::new("."));
+        assert_eq!((tree.name.as_str(), tree.lines, tree.symbols), (".", 10, 5));
+        let names: Vec<&str> = tree.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["svc", "setup.py"]);
+
+        let svc = &tree.children[0];
+        assert_eq!((svc.kind, svc.lines, svc.symbols), ("dir", 8, 4));
+        let routes = &svc.children[0].children[0];
+        assert_eq!((routes.name.as_str(), routes.kind), ("routes.py", "file"));
+        let router = &routes.children[0];
+        assert_eq!(
+            (router.name.as_str(), router.lines, router.symbols),
+            ("Router", 6, 3)
+        );
+        assert_eq!(router.children.len(), 2);
+        assert!(router.children.iter().all(|m| m.kind == "method"));
+    }
+}
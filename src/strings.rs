@@ -0,0 +1,343 @@
+//! `cm strings`: search string literals (messages, error strings, keys) and report the symbol
+//! each one sits in, the quickest way from an error message in a bug report to the code that
+//! produced it. Literals live in an index of their own, `.codemapper/strings.bin`, which is
+//! opt-in: nothing is extracted until the first `cm strings`, and later runs only re-read
+//! files whose hash changed.
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tree_sitter::Node;
+
+use crate::failure::{ErrorCode, Failure};
+use crate::index::CodeIndex;
+use crate::models::Language;
+use crate::parser::pooled_parse;
+use crate::paths::DisplaySlash;
+use crate::sql::string_literals;
+use crate::value_refs::{enclosing_symbol, grammar};
+
+/// String index file, inside the cache base directory (`.codemapper/`)
+pub const STRINGS_FILE: &str = "strings.bin";
+/// Bumped when extraction changes, so an older index is rebuilt instead of trusted
+const STRINGS_VERSION: u32 = 1;
+/// Letters and digits a literal's fixed text needs before an error message is matched
+/// against it as a template; shorter ones (`"{}: {}"`, `"error"`) fit almost any message
+const MIN_TEMPLATE_CHARS: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Literal {
+    line: usize,
+    /// Whitespace collapsed; interpolations (`${x}`, f-string `{x}`) as `?`
+    text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileStrings {
+    hash: String,
+    literals: Vec<Literal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StringIndex {
+    version: u32,
+    /// Keyed by the file's path as the code index has it
+    files: HashMap<String, FileStrings>,
+}
+
+impl Default for StringIndex {
+    fn default() -> Self {
+        StringIndex {
+            version: STRINGS_VERSION,
+            files: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The literal contains the pattern
+    Contains,
+    /// The pattern is the literal with its placeholders filled in, or quotes it whole
+    Template,
+    /// `--regex` matched the literal
+    Regex,
+}
+
+impl MatchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchKind::Contains => "contains",
+            MatchKind::Template => "template",
+            MatchKind::Regex => "regex",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StringMatch {
+    pub text: String,
+    pub kind: MatchKind,
+    /// Characters of the pattern the literal accounts for; higher is more specific
+    pub score: usize,
+    pub file_path: PathBuf,
+    pub line: usize,
+    /// Innermost symbol containing the literal
+    pub enclosing: Option<String>,
+}
+
+/// What `cm strings` looks for
+#[derive(Debug, Clone)]
+pub enum StringPattern {
+    /// Lowercased, whitespace collapsed
+    Text(String),
+    Regex(Regex),
+}
+
+impl StringPattern {
+    pub fn text(pattern: &str) -> Self {
+        StringPattern::Text(collapse(pattern).to_lowercase())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Regex::new(pattern).map(StringPattern::Regex).map_err(|e| {
+            Failure::new(
+                ErrorCode::InvalidArgument,
+                format!("Invalid regex '{}': {}", pattern, e),
+            )
+            .into()
+        })
+    }
+
+    fn matches(&self, literal: &str) -> Option<(MatchKind, usize)> {
+        match self {
+            StringPattern::Regex(re) => re
+                .find(literal)
+                .map(|found| (MatchKind::Regex, found.as_str().len())),
+            StringPattern::Text(pattern) => {
+                let literal = literal.to_lowercase();
+                if literal.contains(pattern.as_str()) {
+                    return Some((MatchKind::Contains, pattern.len()));
+                }
+                template_match(&literal, pattern).map(|score| (MatchKind::Template, score))
+            }
+        }
+    }
+}
+
+/// Whether `message` could have been produced from `literal` (both lowercased): the pieces
+/// of the literal between placeholders (`{}`, `{name}`, `%s`, `%d`, interpolations) appear
+/// in the message in order. Returns the letters and digits the pieces account for.
+fn template_match(literal: &str, message: &str) -> Option<usize> {
+    static PLACEHOLDERS: OnceLock<Regex> = OnceLock::new();
+    let placeholders = PLACEHOLDERS.get_or_init(|| {
+        Regex::new(
+            r"\?|\{[^{}]*\}|%\([A-Za-z_]\w*\)[sdr]|%[-+ #0]*\d*(?:\.\d+)?[sdifvqxXeEgGpTr]|\\.",
+        )
+        .expect("placeholder regex")
+    });
+    let mut at = 0;
+    let mut fixed = 0;
+    for piece in placeholders.split(literal) {
+        let piece = piece.trim();
+        if piece.is_empty() {
+            continue;
+        }
+        let found = message[at..].find(piece)?;
+        at += found + piece.len();
+        fixed += piece.chars().filter(|c| c.is_alphanumeric()).count();
+    }
+    (fixed >= MIN_TEMPLATE_CHARS).then_some(fixed)
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Start bytes of Python docstrings: a string standing alone as the first statement of a
+/// module, class or function. They describe the code rather than being printed by it.
+fn docstring_starts(root: Node) -> HashSet<usize> {
+    let mut starts = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        if matches!(node.kind(), "module" | "block") {
+            let first = children.iter().find(|child| child.kind() != "comment");
+            if let Some(statement) = first.filter(|s| s.kind() == "expression_statement") {
+                if let Some(string) = statement
+                    .named_child(0)
+                    .filter(|n| n.kind() == "string" && statement.named_child_count() == 1)
+                {
+                    starts.insert(string.start_byte());
+                }
+            }
+        }
+        stack.extend(children);
+    }
+    starts
+}
+
+/// Literals of one file, in source order
+fn extract(path: &Path, language: Language) -> Option<Vec<Literal>> {
+    let grammar = grammar(language)?;
+    let content = fs::read_to_string(path).ok()?;
+    let tree = pooled_parse(&grammar, &content).ok()??;
+    let docstrings = match language {
+        Language::Python => docstring_starts(tree.root_node()),
+        _ => HashSet::new(),
+    };
+    let mut literals: Vec<Literal> = string_literals(tree.root_node(), &content)
+        .into_iter()
+        .filter(|(offset, _)| !docstrings.contains(offset))
+        .map(|(offset, text)| Literal {
+            line: content[..offset].matches('\n').count() + 1,
+            text: collapse(&text),
+        })
+        .filter(|literal| literal.text.chars().any(|c| c.is_alphanumeric()))
+        .collect();
+    literals.sort_by_key(|literal| literal.line);
+    Some(literals)
+}
+
+impl StringIndex {
+    pub fn path(base_dir: &Path) -> PathBuf {
+        base_dir.join(STRINGS_FILE)
+    }
+
+    /// The saved index, or an empty one when there is none yet or it is from another version
+    pub fn load(base_dir: &Path) -> Self {
+        fs::read(Self::path(base_dir))
+            .ok()
+            .and_then(|bytes| bincode::deserialize::<StringIndex>(&bytes).ok())
+            .filter(|index| index.version == STRINGS_VERSION)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, base_dir: &Path) -> Result<()> {
+        fs::create_dir_all(base_dir).context("Failed to create cache directory")?;
+        fs::write(Self::path(base_dir), bincode::serialize(self)?)
+            .context("Failed to write string index")
+    }
+
+    /// Bring the literals in line with `index`: read new and changed files, forget removed
+    /// ones. Returns how many files were read.
+    pub fn refresh(&mut self, index: &CodeIndex) -> usize {
+        let current: HashSet<String> = index
+            .files()
+            .map(|file| file.path.display_slash().to_string())
+            .collect();
+        self.files.retain(|key, _| current.contains(key));
+
+        let stale: Vec<_> = index
+            .files()
+            .filter(|file| grammar(file.language).is_some())
+            .map(|file| (file.path.display_slash().to_string(), file))
+            .filter(|(key, file)| {
+                self.files
+                    .get(key)
+                    .is_none_or(|strings| strings.hash != file.hash)
+            })
+            .collect();
+        let read: Vec<(String, FileStrings)> = stale
+            .par_iter()
+            .filter_map(|(key, file)| {
+                let literals = extract(&file.path, file.language)?;
+                Some((
+                    key.clone(),
+                    FileStrings {
+                        hash: file.hash.clone(),
+                        literals,
+                    },
+                ))
+            })
+            .collect();
+
+        let count = read.len();
+        self.files.extend(read);
+        count
+    }
+
+    /// Literals matching `pattern` in the files of `index`, most specific first
+    pub fn search(&self, index: &CodeIndex, pattern: &StringPattern) -> Vec<StringMatch> {
+        let mut matches = Vec::new();
+        for file in index.files() {
+            let Some(strings) = self.files.get(&file.path.display_slash().to_string()) else {
+                continue;
+            };
+            for literal in &strings.literals {
+                let Some((kind, score)) = pattern.matches(&literal.text) else {
+                    continue;
+                };
+                matches.push(StringMatch {
+                    text: literal.text.clone(),
+                    kind,
+                    score,
+                    file_path: file.path.clone(),
+                    line: literal.line,
+                    enclosing: enclosing_symbol(index, &file.path, literal.line)
+                        .map(|s| s.name.to_string()),
+                });
+            }
+        }
+
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+                .then(a.line.cmp(&b.line))
+        });
+        matches
+    }
+
+    pub fn total_literals(&self) -> usize {
+        self.files
+            .values()
+            .map(|strings| strings.literals.len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+
+    #[test]
+    fn test_error_messages_find_their_templates() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = "\"\"\"Config loading.\"\"\"\n\ndef load(path):\n    \"\"\"Load the config file at path.\"\"\"\n    raise ConfigError(f\"failed to open config file {path}: permission denied\")\n\ndef save():\n    log.info(\"saved %s rows\", n)\n    return \"ok\"\n";
+        let index = index_of(dir.path(), &[("config.py", source, Language::Python)]);
+
+        let mut strings = StringIndex::default();
+        assert_eq!(strings.refresh(&index), 1);
+        assert_eq!(strings.refresh(&index), 0);
+        assert_eq!(strings.total_literals(), 3);
+
+        let found = strings.search(
+            &index,
+            &StringPattern::text(
+                "ConfigError: Failed to open config file /etc/app.toml: permission denied",
+            ),
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, MatchKind::Template);
+        assert_eq!(found[0].line, 5);
+        assert_eq!(found[0].enclosing.as_deref(), Some("load"));
+
+        let found = strings.search(&index, &StringPattern::text("SAVED"));
+        assert_eq!(found[0].kind, MatchKind::Contains);
+        assert_eq!(found[0].enclosing.as_deref(), Some("save"));
+
+        // Too little fixed text to match a message as a template
+        assert!(strings
+            .search(&index, &StringPattern::text("it was ok"))
+            .is_empty());
+        assert!(StringPattern::regex("(").is_err());
+    }
+}
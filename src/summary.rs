@@ -0,0 +1,318 @@
+//! Short prose summaries of files and symbols produced by an external command
+//! (typically an LLM CLI), cached in `.codemapper/summaries.json`.
+
+use crate::cache::CacheManager;
+use crate::config::SummarizerConfig;
+use crate::failure::{ErrorCode, Failure};
+use crate::index::CodeIndex;
+use crate::models::{FileInfo, Symbol};
+use crate::paths::DisplaySlash;
+use crate::process;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+pub const SUMMARIES_FILE: &str = "summaries.json";
+const DEFAULT_MAX_INPUT_CHARS: usize = 12_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryEntry {
+    /// Hash of the summarized text; the summary is stale once it differs
+    pub hash: String,
+    pub summary: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SummaryCache {
+    /// The directory `cm summarize` ran on, which stored keys are relative to; recorded for
+    /// readers that find the file through `--cache-dir` rather than under the directory
+    #[serde(default)]
+    pub root: Option<PathBuf>,
+    pub entries: BTreeMap<String, SummaryEntry>,
+}
+
+/// Key of a file in the summaries handed to `OutputFormatter`: its path as indexed, without
+/// `./` components
+pub fn file_key(path: &Path) -> String {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect::<PathBuf>()
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+pub fn symbol_key(symbol: &Symbol) -> String {
+    format!(
+        "{}#{}:{}",
+        file_key(&symbol.file_path),
+        symbol.name,
+        symbol.line_start
+    )
+}
+
+/// Key of a file in `summaries.json`: its path relative to the summarized root, so it does
+/// not depend on the directory `cm summarize` or a later reader runs from
+pub fn stored_file_key(root: &Path, path: &Path) -> String {
+    let root = std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf());
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    file_key(path.strip_prefix(&root).unwrap_or(&path))
+}
+
+pub fn stored_symbol_key(root: &Path, symbol: &Symbol) -> String {
+    format!(
+        "{}#{}:{}",
+        stored_file_key(root, &symbol.file_path),
+        symbol.name,
+        symbol.line_start
+    )
+}
+
+pub fn symbol_text(symbol: &Symbol, lines: &[&str]) -> String {
+    let start = symbol.line_start.saturating_sub(1).min(lines.len());
+    let end = symbol.line_end.min(lines.len()).max(start);
+    lines[start..end].join("\n")
+}
+
+pub fn text_hash(text: &str) -> String {
+    format!("blake3:{}", blake3::hash(text.as_bytes()).to_hex())
+}
+
+impl SummaryCache {
+    /// The summaries covering `target` and the root their keys are relative to: those in
+    /// `cache_dir` when set, otherwise in the nearest `.codemapper/` at or above `target`
+    /// that has any
+    pub fn find(target: &Path, cache_dir: Option<&Path>) -> Result<(PathBuf, Self)> {
+        let target = std::path::absolute(target).unwrap_or_else(|_| target.to_path_buf());
+        if let Some(cache_dir) = cache_dir {
+            let cache = Self::load(cache_dir)?;
+            let root = cache.root.clone().unwrap_or(target);
+            return Ok((root, cache));
+        }
+        for dir in target.ancestors() {
+            let base_dir = CacheManager::base_dir(dir, None);
+            if base_dir.join(SUMMARIES_FILE).is_file() {
+                return Ok((dir.to_path_buf(), Self::load(&base_dir)?));
+            }
+        }
+        Ok((target, Self::default()))
+    }
+
+    pub fn load(base_dir: &Path) -> Result<Self> {
+        let path = base_dir.join(SUMMARIES_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read summaries: {}", path.display_slash()))?;
+        serde_json::from_str(&content).with_context(|| format!("in {}", path.display_slash()))
+    }
+
+    pub fn save(&self, base_dir: &Path) -> Result<()> {
+        fs::create_dir_all(base_dir).context("Failed to create cache directory")?;
+        fs::write(
+            base_dir.join(SUMMARIES_FILE),
+            serde_json::to_string_pretty(self)?,
+        )
+        .context("Failed to write summaries")
+    }
+
+    pub fn get(&self, key: &str, hash: &str) -> Option<&str> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.hash == hash)
+            .map(|entry| entry.summary.as_str())
+    }
+
+    pub fn insert(&mut self, key: String, hash: String, summary: String) {
+        self.entries.insert(key, SummaryEntry { hash, summary });
+    }
+
+    /// Up-to-date file summaries for every file in the index, keyed by `file_key`; stored
+    /// keys are relative to `root`
+    pub fn fresh_for_index(&self, root: &Path, index: &CodeIndex) -> HashMap<String, String> {
+        index
+            .files()
+            .filter_map(|file| {
+                let summary = self.get(&stored_file_key(root, &file.path), &file.hash)?;
+                Some((file_key(&file.path), summary.to_string()))
+            })
+            .collect()
+    }
+
+    /// Up-to-date summaries for one file and its symbols, keyed by `file_key` and `symbol_key`
+    pub fn fresh_for_file(
+        &self,
+        root: &Path,
+        file: &FileInfo,
+        content: &str,
+    ) -> HashMap<String, String> {
+        let mut fresh = HashMap::new();
+
+        if let Some(summary) = self.get(&stored_file_key(root, &file.path), &file.hash) {
+            fresh.insert(file_key(&file.path), summary.to_string());
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+        for symbol in &file.symbols {
+            let hash = text_hash(&symbol_text(symbol, &lines));
+            if let Some(summary) = self.get(&stored_symbol_key(root, symbol), &hash) {
+                fresh.insert(symbol_key(symbol), summary.to_string());
+            }
+        }
+
+        fresh
+    }
+}
+
+/// Runs `command args...` with the text on stdin and `CM_SUMMARY_KIND` / `CM_SUMMARY_PATH` /
+/// `CM_SUMMARY_NAME` in the environment; stdout is the summary.
+pub struct Summarizer {
+    config: SummarizerConfig,
+}
+
+impl Summarizer {
+    pub fn new(config: SummarizerConfig) -> Result<Self> {
+        if config.command.is_none() {
+            anyhow::bail!(Failure::new(
+                ErrorCode::NotConfigured,
+                "No summarizer configured. Add a [summarizer] section with `command` to .codemapper.toml"
+            ));
+        }
+        Ok(Self { config })
+    }
+
+    pub fn summarize(&self, kind: &str, path: &Path, name: &str, text: &str) -> Result<String> {
+        let command = self.config.command.as_deref().unwrap_or_default();
+        let max_chars = self.config.max_chars.unwrap_or(DEFAULT_MAX_INPUT_CHARS);
+        let input: String = text.chars().take(max_chars).collect();
+
+        let child = Command::new(command)
+            .args(&self.config.args)
+            .env("CM_SUMMARY_KIND", kind)
+            .env("CM_SUMMARY_PATH", path)
+            .env("CM_SUMMARY_NAME", name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to start summarizer '{}'", command))?;
+
+        let output = process::feed_and_wait(child, input.as_bytes())
+            .with_context(|| format!("Summarizer '{}' did not finish", command))?;
+        if !output.status.success() {
+            anyhow::bail!(Failure::new(
+                ErrorCode::ExternalCommandFailed,
+                format!(
+                    "Summarizer '{}' failed on {}: {}",
+                    command,
+                    path.display_slash(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+            ));
+        }
+
+        Ok(normalize_summary(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// Collapse whitespace so summaries fit on one output line
+fn normalize_summary(raw: &str) -> String {
+    raw.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SymbolType;
+
+    #[test]
+    fn test_stale_summaries_are_hidden() {
+        let symbol = Symbol {
+            name: "load".into(),
+            symbol_type: SymbolType::Function,
+            file_path: Path::new("./src/config.py").into(),
+            line_start: 2,
+            line_end: 3,
+            signature: None,
+            docstring: None,
+            parent_id: None,
+            is_exported: false,
+            byte_range: None,
+            annotations: Vec::new(),
+        };
+        assert_eq!(file_key(&symbol.file_path), "src/config.py");
+        assert_eq!(symbol_key(&symbol), "src/config.py#load:2");
+
+        let lines = ["import os", "def load():", "    return 1"];
+        let hash = text_hash(&symbol_text(&symbol, &lines));
+
+        let mut cache = SummaryCache::default();
+        cache.insert(
+            symbol_key(&symbol),
+            hash.clone(),
+            "Loads config".to_string(),
+        );
+        assert_eq!(cache.get(&symbol_key(&symbol), &hash), Some("Loads config"));
+        assert_eq!(cache.get(&symbol_key(&symbol), "blake3:other"), None);
+
+        assert_eq!(
+            normalize_summary("  Loads\n the  config.\n"),
+            "Loads the config."
+        );
+    }
+    #[test]
+    fn test_stored_keys_are_relative_to_the_summarized_root() {
+        assert_eq!(
+            stored_file_key(Path::new("."), Path::new("./src/config.py")),
+            "src/config.py"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        let file = root.join("src").join("config.py");
+        assert_eq!(stored_file_key(&root, &file), "src/config.py");
+        assert_eq!(stored_file_key(&root.join("src"), &file), "config.py");
+
+        let mut cache = SummaryCache::default();
+        cache.insert(
+            "src/config.py".to_string(),
+            "blake3:h".to_string(),
+            "Loads config".to_string(),
+        );
+        cache.save(&CacheManager::base_dir(&root, None)).unwrap();
+
+        // Found from the file, wherever the reader runs
+        let (found_root, found) = SummaryCache::find(&file, None).unwrap();
+        assert_eq!(found_root, root);
+        assert_eq!(
+            found.get(&stored_file_key(&found_root, &file), "blake3:h"),
+            Some("Loads config")
+        );
+        let (_, nothing) = SummaryCache::find(dir.path(), None).unwrap();
+        assert!(nothing.entries.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_summarizer_gets_large_inputs() {
+        // Over a pipe buffer, and the command prints before it reads
+        let summarizer = Summarizer::new(SummarizerConfig {
+            command: Some("sh".to_string()),
+            args: vec![
+                "-c".to_string(),
+                "head -c 200000 /dev/zero >&2; wc -c; echo bytes".to_string(),
+            ],
+            max_chars: Some(150_000),
+        })
+        .unwrap();
+
+        let text = "x".repeat(200_000);
+        let summary = summarizer
+            .summarize("file", Path::new("big.rs"), "big.rs", &text)
+            .unwrap();
+        assert_eq!(summary, "150000 bytes");
+    }
+}
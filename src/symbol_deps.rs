@@ -0,0 +1,626 @@
+//! `cm deps <symbol> --direction imports`: what one symbol's body depends on. Calls and
+//! named types resolve to their indexed definitions; an import of the enclosing file counts
+//! when a name it binds is used inside the body.
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+
+use crate::callgraph;
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+use crate::paths::DisplaySlash;
+use crate::scan::regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DepKind {
+    /// A function or method the body calls
+    Call,
+    /// A class, struct, enum, interface or alias the body names
+    Type,
+    /// An import of the file whose bound name the body uses
+    Import,
+}
+
+impl DepKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DepKind::Call => "call",
+            DepKind::Type => "type",
+            DepKind::Import => "import",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolDep {
+    pub kind: DepKind,
+    /// The name as used in the body
+    pub name: String,
+    /// `file:line` of the definition, or the imported module path
+    pub target: String,
+}
+
+fn is_type(symbol: &Symbol) -> bool {
+    matches!(
+        symbol.symbol_type,
+        SymbolType::Class | SymbolType::Enum | SymbolType::Interface | SymbolType::TypeAlias
+    )
+}
+
+fn is_callable(symbol: &Symbol) -> bool {
+    matches!(
+        symbol.symbol_type,
+        SymbolType::Function | SymbolType::Method | SymbolType::Class
+    )
+}
+
+/// Whether every call of `name` in `body` is `receiver.name(...)`, so only a method can be
+/// what is called
+fn called_as_method(body: &str, name: &str) -> bool {
+    regex(&format!(r"\b{}\s*\(", regex::escape(name)))
+        .find_iter(body)
+        .all(|m| body[..m.start()].trim_end().ends_with('.'))
+}
+
+/// The definition of `name` accepted by `keep`: the one in `symbol`'s own file, else the
+/// only one. Common method names (`new`, `get`) defined in many files stay unresolved.
+fn resolve<'a>(
+    index: &'a CodeIndex,
+    symbol: &Symbol,
+    name: &str,
+    keep: impl Fn(&Symbol) -> bool,
+) -> Option<&'a Symbol> {
+    let candidates: Vec<&Symbol> = index
+        .query_symbol(name)
+        .into_iter()
+        .filter(|s| keep(s))
+        .collect();
+    match candidates.iter().find(|s| s.file_path == symbol.file_path) {
+        Some(local) => Some(*local),
+        None if candidates.len() == 1 => Some(candidates[0]),
+        None => None,
+    }
+}
+
+/// Calls, types and imports used within `symbol`'s line range, by kind then name
+pub fn symbol_dependencies(index: &CodeIndex, symbol: &Symbol) -> Result<Vec<SymbolDep>> {
+    let Ok(content) = fs::read_to_string(&symbol.file_path) else {
+        return Ok(Vec::new());
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let start = symbol.line_start.saturating_sub(1);
+    let end = symbol.line_end.min(lines.len());
+    if start >= end {
+        return Ok(Vec::new());
+    }
+    let body = lines[start..end].join("\n");
+    let language = Language::from_extension(
+        symbol
+            .file_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or(""),
+    );
+
+    let identifiers: HashSet<&str> = regex(r"[A-Za-z_][A-Za-z0-9_]*")
+        .find_iter(&body)
+        .map(|m| m.as_str())
+        .collect();
+    let location = |s: &Symbol| format!("{}:{}", s.file_path.display_slash(), s.line_start);
+    let mut deps = Vec::new();
+
+    for &name in &identifiers {
+        if name == symbol.name {
+            continue;
+        }
+        if let Some(target) = resolve(index, symbol, name, is_type) {
+            deps.push(SymbolDep {
+                kind: DepKind::Type,
+                name: name.to_string(),
+                target: location(target),
+            });
+        }
+    }
+
+    let types: HashSet<String> = deps.iter().map(|d| d.name.clone()).collect();
+    for (call, _, _) in callgraph::extract_calls_from_source(&body, language)? {
+        if call == symbol.name || types.contains(&call) {
+            continue;
+        }
+        // `items.collect()` is not the free fn `collect`, nor a method of a type the body
+        // never mentions
+        let as_method = called_as_method(&body, &call);
+        let keep = |s: &Symbol| {
+            if !as_method {
+                return is_callable(s);
+            }
+            s.symbol_type == SymbolType::Method
+                && (s.file_path == symbol.file_path
+                    || index
+                        .get_file_symbols(&s.file_path)
+                        .iter()
+                        .any(|owner| is_type(owner) && identifiers.contains(&*owner.name)))
+        };
+        if let Some(target) = resolve(index, symbol, &call, keep) {
+            deps.push(SymbolDep {
+                kind: DepKind::Call,
+                name: call,
+                target: location(target),
+            });
+        }
+    }
+
+    for (bound, module) in import_bindings(&content, language) {
+        if identifiers.contains(bound.as_str()) {
+            deps.push(SymbolDep {
+                kind: DepKind::Import,
+                name: bound,
+                target: module,
+            });
+        }
+    }
+
+    deps.sort_by(|a, b| a.kind.cmp(&b.kind).then(a.name.cmp(&b.name)));
+    deps.dedup_by(|a, b| a.kind == b.kind && a.name == b.name);
+    Ok(deps)
+}
+
+/// Split `text` at commas outside braces
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts.into_iter().filter(|p| !p.is_empty()).collect()
+}
+
+/// `name as alias` -> (`alias`, `name`); otherwise (`name`, `name`)
+fn alias(item: &str) -> (&str, &str) {
+    match item.split_once(" as ") {
+        Some((name, alias)) => (alias.trim(), name.trim()),
+        None => (item.trim(), item.trim()),
+    }
+}
+
+/// A name an import statement brings into scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// The name as used in code
+    pub bound: String,
+    /// The imported module or item path
+    pub path: String,
+    /// A statement importing just this name, e.g. `from decimal import Decimal`
+    pub statement: String,
+    /// Line of the import statement, 1-based
+    pub line: usize,
+}
+
+impl Import {
+    fn new(bound: &str, path: &str, statement: String, line: usize) -> Self {
+        Self {
+            bound: bound.to_string(),
+            path: path.to_string(),
+            statement,
+            line,
+        }
+    }
+}
+
+/// 1-based line of byte `offset`, given the offset each line starts at
+fn line_at(line_starts: &[usize], offset: usize) -> usize {
+    line_starts.partition_point(|start| *start <= offset)
+}
+
+/// `name` or `name as bound`, as Rust and Python spell a renamed import
+fn renamed(name: &str, bound: &str) -> String {
+    if name == bound {
+        name.to_string()
+    } else {
+        format!("{} as {}", name, bound)
+    }
+}
+
+/// Rust use tree: `a::{b, c::d as e, self}` under `prefix`
+fn expand_use(tree: &str, prefix: &str, line: usize, out: &mut Vec<Import>) {
+    let tree = tree.trim();
+    if let (Some(open), true) = (tree.find('{'), tree.ends_with('}')) {
+        let head = format!("{}{}", prefix, &tree[..open]);
+        for item in split_top_level(&tree[open + 1..tree.len() - 1]) {
+            expand_use(item, &head, line, out);
+        }
+        return;
+    }
+    let (bound, path) = alias(tree);
+    if path == "*" {
+        return;
+    }
+    let (bound, path) = if path == "self" {
+        let module = prefix.trim_end_matches("::");
+        let name = module.rsplit("::").next().unwrap_or(module);
+        let bound = if bound == "self" { name } else { bound };
+        (bound, module.to_string())
+    } else {
+        let bound = bound.rsplit("::").next().unwrap_or(bound);
+        (bound, format!("{}{}", prefix, path))
+    };
+    let name = path.rsplit("::").next().unwrap_or(&path);
+    let statement = if name == bound {
+        format!("use {};", path)
+    } else {
+        format!("use {} as {};", path, bound)
+    };
+    out.push(Import::new(bound, &path, statement, line));
+}
+
+/// (bound name, imported path) for each name an import statement brings into scope
+pub fn import_bindings(content: &str, language: Language) -> Vec<(String, String)> {
+    imports(content, language)
+        .into_iter()
+        .map(|import| (import.bound, import.path))
+        .collect()
+}
+
+/// Each name an import statement brings into scope, with a statement importing it alone
+pub fn imports(content: &str, language: Language) -> Vec<Import> {
+    let mut imports = Vec::new();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_of = |m: Option<regex::Match>| m.map_or(0, |m| line_at(&line_starts, m.start()));
+    match language {
+        Language::Rust => {
+            for caps in
+                regex(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").captures_iter(content)
+            {
+                let tree: String = caps[1].split_whitespace().collect::<Vec<_>>().join(" ");
+                expand_use(&tree, "", line_of(caps.get(1)), &mut imports);
+            }
+        }
+        Language::Python => {
+            for caps in regex(r"(?m)^\s*import\s+(.+)$").captures_iter(content) {
+                for item in caps[1].split(',') {
+                    let (bound, module) = alias(item);
+                    // `import os.path` binds `os`
+                    let statement = format!("import {}", renamed(module, bound));
+                    let bound = if bound == module {
+                        module.split('.').next().unwrap_or(module)
+                    } else {
+                        bound
+                    };
+                    imports.push(Import::new(bound, module, statement, line_of(caps.get(1))));
+                }
+            }
+            let from = regex(r"(?m)^\s*from\s+(\S+)\s+import\s+(?:\(([^)]*)\)|(.+)$)");
+            for caps in from.captures_iter(content) {
+                let names = caps.get(2).or(caps.get(3)).map_or("", |m| m.as_str());
+                for item in names.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+                    let (bound, name) = alias(item);
+                    if name != "*" {
+                        imports.push(Import::new(
+                            bound,
+                            &format!("{}.{}", &caps[1], name),
+                            format!("from {} import {}", &caps[1], renamed(name, bound)),
+                            line_of(caps.get(1)),
+                        ));
+                    }
+                }
+            }
+        }
+        Language::JavaScript | Language::TypeScript => {
+            let import = regex(r#"(?s)\bimport\s+(?:type\s+)?([^'"]+?)\s+from\s+['"]([^'"]+)['"]"#);
+            let require = regex(
+                r#"\b(?:const|let|var)\s+(\{[^}]*\}|[A-Za-z_$][\w$]*)\s*=\s*require\(\s*['"]([^'"]+)['"]\s*\)"#,
+            );
+            let captures = import
+                .captures_iter(content)
+                .map(|caps| (caps, false))
+                .chain(require.captures_iter(content).map(|caps| (caps, true)));
+            for (caps, required) in captures {
+                let module = &caps[2];
+                for part in split_top_level(&caps[1]) {
+                    let (names, named): (Vec<&str>, bool) = match part.strip_prefix('{') {
+                        Some(inner) => (inner.trim_end_matches('}').split(',').collect(), true),
+                        None => (vec![part], false),
+                    };
+                    for name in names.into_iter().map(str::trim).filter(|n| !n.is_empty()) {
+                        // `{ a as b }` in imports, `{ a: b }` in destructured requires
+                        let binding = name.trim_start_matches("* as ");
+                        let (bound, _) = alias(binding.split(':').next_back().unwrap_or(binding));
+                        let statement = match (required, named) {
+                            (true, true) => {
+                                format!("const {{ {} }} = require('{}');", name, module)
+                            }
+                            (true, false) => format!("const {} = require('{}');", name, module),
+                            (false, true) => format!("import {{ {} }} from '{}';", name, module),
+                            (false, false) => format!("import {} from '{}';", name, module),
+                        };
+                        imports.push(Import::new(bound, module, statement, line_of(caps.get(0))));
+                    }
+                }
+            }
+        }
+        Language::Go => {
+            let spec = regex(r#"(?m)^\s*(?:import\s+)?([A-Za-z_.]\w*\s+)?"([^"]+)"\s*$"#);
+            for caps in spec.captures_iter(content) {
+                let path = &caps[2];
+                let named = caps.get(1).map(|m| m.as_str().trim());
+                let bound = named.unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path));
+                if bound != "_" && bound != "." {
+                    let statement = match named {
+                        Some(name) => format!("import {} \"{}\"", name, path),
+                        None => format!("import \"{}\"", path),
+                    };
+                    imports.push(Import::new(bound, path, statement, line_of(caps.get(2))));
+                }
+            }
+        }
+        Language::Java => {
+            let import = regex(r"(?m)^\s*import\s+(static\s+)?([\w.]+)\s*;");
+            for caps in import.captures_iter(content) {
+                let path = &caps[2];
+                let bound = path.rsplit('.').next().unwrap_or(path);
+                let keyword = if caps.get(1).is_some() {
+                    "import static"
+                } else {
+                    "import"
+                };
+                let statement = format!("{} {};", keyword, path);
+                imports.push(Import::new(bound, path, statement, line_of(caps.get(2))));
+            }
+        }
+        Language::Kotlin => {
+            let import = regex(r"(?m)^\s*import\s+([\w.]+)(?:\s+as\s+(\w+))?\s*;?\s*$");
+            for caps in import.captures_iter(content) {
+                let path = &caps[1];
+                let alias = caps.get(2).map(|m| m.as_str());
+                let bound = alias.unwrap_or_else(|| path.rsplit('.').next().unwrap_or(path));
+                let statement = match alias {
+                    Some(alias) => format!("import {} as {}", path, alias),
+                    None => format!("import {}", path),
+                };
+                imports.push(Import::new(bound, path, statement, line_of(caps.get(1))));
+            }
+        }
+        // `import UIKit`, or one declaration of a module: `import struct UIKit.CGPoint`
+        Language::Swift => {
+            let import = regex(
+                r"(?m)^\s*(?:@\w+\s+)*import\s+(?:(typealias|struct|class|enum|protocol|let|var|func)\s+)?([\w.]+)",
+            );
+            for caps in import.captures_iter(content) {
+                let path = &caps[2];
+                let bound = path.rsplit('.').next().unwrap_or(path);
+                let statement = match caps.get(1) {
+                    Some(kind) => format!("import {} {}", kind.as_str(), path),
+                    None => format!("import {}", path),
+                };
+                imports.push(Import::new(bound, path, statement, line_of(caps.get(2))));
+            }
+        }
+        // `import a.b.C`, `import a.b.C as D`, and each name of `import a.b.{C, D => E}`
+        Language::Scala => {
+            let import =
+                regex(r"(?m)^\s*import\s+([\w.]+?)(?:\.\{([^}]*)\}|\s+as\s+(\w+))?\s*;?\s*$");
+            for caps in import.captures_iter(content) {
+                let path = &caps[1];
+                let Some(selectors) = caps.get(2) else {
+                    let alias = caps.get(3).map(|m| m.as_str());
+                    let bound = alias.unwrap_or_else(|| path.rsplit('.').next().unwrap_or(path));
+                    // `import a.b._` binds nothing by one name
+                    if bound == "_" {
+                        continue;
+                    }
+                    let statement = match alias {
+                        Some(alias) => format!("import {} as {}", path, alias),
+                        None => format!("import {}", path),
+                    };
+                    imports.push(Import::new(bound, path, statement, line_of(caps.get(1))));
+                    continue;
+                };
+                for selector in selectors.as_str().split(',').map(str::trim) {
+                    let (name, bound) = selector
+                        .split_once("=>")
+                        .or_else(|| selector.split_once(" as "))
+                        .map_or((selector, selector), |(name, bound)| {
+                            (name.trim(), bound.trim())
+                        });
+                    if name.is_empty() || matches!(bound, "_" | "*") || name == "given" {
+                        continue;
+                    }
+                    let module = format!("{}.{}", path, name);
+                    let statement = format!("import {}.{{{}}}", path, selector);
+                    imports.push(Import::new(bound, &module, statement, line_of(caps.get(1))));
+                }
+            }
+        }
+        // `local json = require("dkjson")`, with or without the parentheses
+        Language::Lua => {
+            let require =
+                regex(r#"(?m)^\s*local\s+(\w+)\s*=\s*require\s*\(?\s*["']([\w./-]+)["']"#);
+            for caps in require.captures_iter(content) {
+                let bound = &caps[1];
+                let path = &caps[2];
+                let statement = format!("local {} = require(\"{}\")", bound, path);
+                imports.push(Import::new(bound, path, statement, line_of(caps.get(2))));
+            }
+        }
+        // `const util = @import("util.zig");`
+        Language::Zig => {
+            let import =
+                regex(r#"(?m)^\s*(?:pub\s+)?const\s+(\w+)\s*=\s*@import\s*\(\s*"([^"]+)"\s*\)"#);
+            for caps in import.captures_iter(content) {
+                let bound = &caps[1];
+                let path = &caps[2];
+                let statement = format!("const {} = @import(\"{}\");", bound, path);
+                imports.push(Import::new(bound, path, statement, line_of(caps.get(2))));
+            }
+        }
+        Language::CSharp => {
+            let using =
+                regex(r"(?m)^\s*(?:global\s+)?using\s+(static\s+)?(?:(\w+)\s*=\s*)?([\w.]+)\s*;");
+            for caps in using.captures_iter(content) {
+                let path = &caps[3];
+                let alias = caps.get(2).map(|m| m.as_str());
+                let bound = alias.unwrap_or_else(|| path.rsplit('.').next().unwrap_or(path));
+                let statement = match (alias, caps.get(1)) {
+                    (Some(alias), _) => format!("using {} = {};", alias, path),
+                    (None, Some(_)) => format!("using static {};", path),
+                    (None, None) => format!("using {};", path),
+                };
+                imports.push(Import::new(bound, path, statement, line_of(caps.get(3))));
+            }
+        }
+        Language::Php => {
+            // Only unindented `use`: inside a class body it pulls in a trait instead
+            let using = regex(
+                r"(?m)^use\s+((?:function|const)\s+)?([\w\\]+?)(?:\\\{([^}]*)\})?(?:\s+as\s+(\w+))?\s*;",
+            );
+            for caps in using.captures_iter(content) {
+                let keyword = caps.get(1).map_or("", |m| m.as_str());
+                let clauses: Vec<(String, Option<&str>)> = match caps.get(3) {
+                    Some(group) => group
+                        .as_str()
+                        .split(',')
+                        .filter_map(|clause| {
+                            let mut parts = clause.split(" as ").map(str::trim);
+                            let name = parts.next().filter(|n| !n.is_empty())?;
+                            Some((format!("{}\\{}", &caps[2], name), parts.next()))
+                        })
+                        .collect(),
+                    None => vec![(caps[2].to_string(), caps.get(4).map(|m| m.as_str()))],
+                };
+                for (path, alias) in clauses {
+                    let bound = alias.unwrap_or_else(|| path.rsplit('\\').next().unwrap_or(&path));
+                    let statement = match alias {
+                        Some(alias) => format!("use {}{} as {};", keyword, path, alias),
+                        None => format!("use {}{};", keyword, path),
+                    };
+                    imports.push(Import::new(bound, &path, statement, line_of(caps.get(2))));
+                }
+            }
+        }
+        _ => {}
+    }
+    imports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+
+    #[test]
+    fn test_import_bindings() {
+        let rust = "use std::collections::{HashMap, HashSet as Set};\nuse crate::routes::{self, regex};\nuse anyhow::Result;\n";
+        assert_eq!(
+            import_bindings(rust, Language::Rust),
+            vec![
+                (
+                    "HashMap".to_string(),
+                    "std::collections::HashMap".to_string()
+                ),
+                ("Set".to_string(), "std::collections::HashSet".to_string()),
+                ("routes".to_string(), "crate::routes".to_string()),
+                ("regex".to_string(), "crate::routes::regex".to_string()),
+                ("Result".to_string(), "anyhow::Result".to_string()),
+            ]
+        );
+
+        let python =
+            "import os.path, numpy as np\nfrom .models import (\n    User,\n    Order as O,\n)\n";
+        let bound: Vec<String> = import_bindings(python, Language::Python)
+            .into_iter()
+            .map(|(bound, module)| format!("{}={}", bound, module))
+            .collect();
+        assert_eq!(
+            bound,
+            vec![
+                "os=os.path",
+                "np=numpy",
+                "User=.models.User",
+                "O=.models.Order"
+            ]
+        );
+
+        let ts = "import React, { useState as useS } from 'react';\nimport * as fs from 'fs';\nconst { join } = require('path');\n";
+        let bound: Vec<String> = import_bindings(ts, Language::TypeScript)
+            .into_iter()
+            .map(|(bound, _)| bound)
+            .collect();
+        assert_eq!(bound, vec!["React", "useS", "fs", "join"]);
+
+        let scala = "import scala.collection.mutable\nimport akka.actor.{Actor, Props => P, _}\nimport org.apache.spark.sql._\nimport cats.Id as I\n";
+        let bound: Vec<String> = import_bindings(scala, Language::Scala)
+            .into_iter()
+            .map(|(bound, module)| format!("{}={}", bound, module))
+            .collect();
+        assert_eq!(
+            bound,
+            vec![
+                "mutable=scala.collection.mutable",
+                "Actor=akka.actor.Actor",
+                "P=akka.actor.Props",
+                "I=cats.Id"
+            ]
+        );
+
+        let lua = "local json = require(\"dkjson\")\nlocal util = require 'lib.util'\nrequire(\"side_effect\")\n";
+        let bound: Vec<String> = import_bindings(lua, Language::Lua)
+            .into_iter()
+            .map(|(bound, module)| format!("{}={}", bound, module))
+            .collect();
+        assert_eq!(bound, vec!["json=dkjson", "util=lib.util"]);
+
+        let zig = "const std = @import(\"std\");\npub const util = @import(\"util.zig\");\nconst mem = std.mem;\n";
+        let bound: Vec<String> = import_bindings(zig, Language::Zig)
+            .into_iter()
+            .map(|(bound, module)| format!("{}={}", bound, module))
+            .collect();
+        assert_eq!(bound, vec!["std=std", "util=util.zig"]);
+    }
+
+    #[test]
+    fn test_symbol_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = r#"import json
+from decimal import Decimal
+
+class Order:
+    pass
+
+def total(order):
+    return Decimal(order.amount)
+
+def checkout(order: Order):
+    data = json.dumps({"total": str(total(order))})
+    return data
+"#;
+        let index = index_of(dir.path(), &[("shop.py", source, Language::Python)]);
+
+        let checkout = index.query_symbol("checkout")[0].clone();
+        let deps: Vec<(DepKind, String)> = symbol_dependencies(&index, &checkout)
+            .unwrap()
+            .into_iter()
+            .map(|dep| (dep.kind, dep.name))
+            .collect();
+        assert_eq!(
+            deps,
+            vec![
+                (DepKind::Call, "total".to_string()),
+                (DepKind::Type, "Order".to_string()),
+                (DepKind::Import, "json".to_string()),
+            ]
+        );
+    }
+}
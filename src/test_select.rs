@@ -0,0 +1,292 @@
+//! `cm impact --emit`: turn the tests found for a symbol into the arguments a test runner
+//! takes to run just those tests, so a change can go straight to its smallest test run.
+//! Tests resolve to the runner's own ids where the test is known (pytest node ids, jest's
+//! full test names, libtest paths); a test found only as a helper or a file-level call
+//! selects its whole file or module instead, so nothing that exercises the symbol is left out.
+
+use clap::ValueEnum;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::callgraph::{list_tests_in, TestCase, TestInfo};
+use crate::index::CodeIndex;
+use crate::indexer::detect_language;
+use crate::models::Language;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Runner {
+    /// `pytest path::Class::test_name`
+    Pytest,
+    /// `jest path -t '^(describe name test name)$'`
+    Jest,
+    /// `cargo test -- module::tests::test_name`
+    Cargo,
+}
+
+impl Runner {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Runner::Pytest => "pytest",
+            Runner::Jest => "jest",
+            Runner::Cargo => "cargo",
+        }
+    }
+
+    fn runs(&self, language: Language) -> bool {
+        match self {
+            Runner::Pytest => language == Language::Python,
+            Runner::Jest => matches!(language, Language::JavaScript | Language::TypeScript),
+            Runner::Cargo => language == Language::Rust,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub runner: Runner,
+    /// Arguments after the runner's program name, unquoted
+    pub args: Vec<String>,
+    /// Tests selected by name
+    pub selected: usize,
+    /// Tests only reachable by running their whole file or module
+    pub widened: usize,
+    /// Tests in files the runner does not run (another language)
+    pub skipped: usize,
+}
+
+impl Selection {
+    /// Nothing to run: no test the runner can select was found
+    pub fn is_empty(&self) -> bool {
+        self.selected + self.widened == 0
+    }
+
+    /// The full command line, quoted for a POSIX shell
+    pub fn command(&self) -> String {
+        let program: &[&str] = match self.runner {
+            Runner::Pytest => &["pytest"],
+            Runner::Jest => &["jest"],
+            Runner::Cargo => &["cargo", "test"],
+        };
+        program
+            .iter()
+            .map(|word| word.to_string())
+            .chain(self.args.iter().map(|arg| shell_quote(arg)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Arguments for `runner` that run `tests` (as `find_tests` reports them)
+pub fn select(index: &CodeIndex, tests: &[TestInfo], runner: Runner) -> Selection {
+    let mut selection = Selection {
+        runner,
+        args: Vec::new(),
+        selected: 0,
+        widened: 0,
+        skipped: 0,
+    };
+    let runnable: Vec<&TestInfo> = tests
+        .iter()
+        .filter(|test| {
+            let runs = runner.runs(detect_language(Path::new(&test.file_path)));
+            if !runs {
+                selection.skipped += 1;
+            }
+            runs
+        })
+        .collect();
+
+    let files: HashSet<PathBuf> = runnable
+        .iter()
+        .map(|test| PathBuf::from(&test.file_path))
+        .collect();
+    let cases: HashMap<(String, usize), TestCase> = list_tests_in(index, Some(&files))
+        .into_iter()
+        .map(|case| ((case.file_path.clone(), case.line), case))
+        .collect();
+
+    // BTreeSets keep the arguments stable from run to run
+    let mut selectors = BTreeSet::new();
+    let mut files = BTreeSet::new();
+    // Files (pytest, jest) or module filters (cargo) to run whole
+    let mut scopes = BTreeSet::new();
+    for test in runnable {
+        files.insert(test.file_path.clone());
+        match cases.get(&(test.file_path.clone(), test.line)) {
+            Some(case) => {
+                selection.selected += 1;
+                selectors.insert(selector(runner, case));
+            }
+            None => {
+                selection.widened += 1;
+                scopes.insert(match runner {
+                    Runner::Cargo => format!("{}::", rust_module_path(&test.file_path)),
+                    _ => test.file_path.clone(),
+                });
+            }
+        }
+    }
+    // A test inside a scope that runs whole needs no selector of its own
+    selectors.retain(|selector: &String| {
+        !scopes.iter().any(|scope| match runner {
+            Runner::Pytest => selector.starts_with(&format!("{}::", scope)),
+            Runner::Jest => false,
+            Runner::Cargo => selector.starts_with(scope.as_str()),
+        })
+    });
+
+    selection.args = match runner {
+        Runner::Pytest => scopes.into_iter().chain(selectors).collect(),
+        Runner::Jest => {
+            let mut args: Vec<String> = files.into_iter().collect();
+            // `-t` applies to every file given, so a file run whole rules out filtering names
+            if scopes.is_empty() && !selectors.is_empty() {
+                let names: Vec<String> =
+                    selectors.iter().map(|name| js_regex_escape(name)).collect();
+                args.push("-t".to_string());
+                args.push(format!("^({})$", names.join("|")));
+            }
+            args
+        }
+        Runner::Cargo => {
+            // An empty module path is the crate root: every test in the crate
+            if scopes.contains("::") {
+                Vec::new()
+            } else {
+                std::iter::once("--".to_string())
+                    .chain(scopes)
+                    .chain(selectors)
+                    .collect()
+            }
+        }
+    };
+    if selection.is_empty() {
+        selection.args.clear();
+    }
+    selection
+}
+
+fn selector(runner: Runner, case: &TestCase) -> String {
+    match runner {
+        Runner::Pytest => std::iter::once(case.file_path.as_str())
+            .chain(case.suites.iter().map(String::as_str))
+            .chain(std::iter::once(case.name.as_str()))
+            .collect::<Vec<_>>()
+            .join("::"),
+        Runner::Jest => case
+            .suites
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(case.name.as_str()))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Runner::Cargo => {
+            let module = rust_module_path(&case.file_path);
+            std::iter::once(module.as_str())
+                .filter(|module| !module.is_empty())
+                .chain(case.suites.iter().map(String::as_str))
+                .chain(std::iter::once(case.name.as_str()))
+                .collect::<Vec<_>>()
+                .join("::")
+        }
+    }
+}
+
+/// The module a Rust file defines, relative to its crate root: `src/parser/rust.rs` is
+/// `parser::rust`; crate roots (`lib.rs`, `main.rs`, files under `tests/`) are empty
+fn rust_module_path(file_path: &str) -> String {
+    let parts: Vec<&str> = file_path.split('/').collect();
+    let Some(src) = parts.iter().rposition(|part| *part == "src") else {
+        return String::new();
+    };
+    let mut module: Vec<&str> = parts[src + 1..]
+        .iter()
+        .map(|part| part.strip_suffix(".rs").unwrap_or(part))
+        .collect();
+    if matches!(module.last(), Some(&"mod") | Some(&"lib") | Some(&"main")) {
+        module.pop();
+    }
+    module.join("::")
+}
+
+fn js_regex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.*+?()[]{}|/".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callgraph::find_tests;
+    use crate::test_support::index_of;
+
+    #[test]
+    fn test_select_per_runner() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = index_of(
+            dir.path(),
+            &[
+                (
+                    "test_price.py",
+                    "class TestPrice:\n    def test_total(self):\n        assert total(1) == 1\n\ndef test_twice():\n    total(2)\n",
+                    Language::Python,
+                ),
+                (
+                    "price.test.ts",
+                    "describe('price (EUR)', () => {\n  it('adds tax', () => {\n    expect(total(1)).toBe(1);\n  });\n});\n",
+                    Language::TypeScript,
+                ),
+                (
+                    "src/price/mod.rs",
+                    "pub fn total(x: u32) -> u32 { x }\n\n#[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn test_total() {\n        let sum = total(1);\n        assert_eq!(sum, 1);\n    }\n}\n",
+                    Language::Rust,
+                ),
+            ],
+        );
+        let tests = find_tests(&index, "total", false).unwrap();
+        let py = dir
+            .path()
+            .join("test_price.py")
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let pytest = select(&index, &tests, Runner::Pytest);
+        assert_eq!((pytest.selected, pytest.skipped), (2, 2));
+        assert_eq!(pytest.args[0], format!("{}::TestPrice::test_total", py));
+
+        let jest = select(&index, &tests, Runner::Jest);
+        assert_eq!(jest.selected, 1);
+        assert_eq!(jest.args[1..], ["-t", r"^(price \(EUR\) adds tax)$"]);
+        assert!(jest.command().ends_with(r"-t '^(price \(EUR\) adds tax)$'"));
+
+        let cargo = select(&index, &tests, Runner::Cargo);
+        assert_eq!(cargo.args, ["--", "price::tests::test_total"]);
+        assert_eq!(cargo.command(), "cargo test -- price::tests::test_total");
+    }
+
+    #[test]
+    fn test_rust_module_path() {
+        assert_eq!(rust_module_path("crate/src/parser/rust.rs"), "parser::rust");
+        assert_eq!(rust_module_path("src/parser/mod.rs"), "parser");
+        assert_eq!(rust_module_path("src/lib.rs"), "");
+        assert_eq!(rust_module_path("tests/cli.rs"), "");
+    }
+}
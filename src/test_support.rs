@@ -0,0 +1,21 @@
+//! Fixtures shared by the unit tests
+
+use std::fs;
+use std::path::Path;
+
+use crate::index::CodeIndex;
+use crate::indexer::index_file;
+use crate::models::Language;
+
+/// An index of `files` (path under `dir`, source, language), each written to disk first:
+/// most analyses read the source again rather than trusting the index
+pub(crate) fn index_of(dir: &Path, files: &[(&str, &str, Language)]) -> CodeIndex {
+    let mut index = CodeIndex::new();
+    for (name, source, language) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, source).unwrap();
+        index.add_file(index_file(&path, source, *language, None).unwrap());
+    }
+    index
+}
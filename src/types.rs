@@ -1,5 +1,6 @@
 use crate::index::CodeIndex;
 use crate::models::{Language, Symbol, SymbolType};
+use crate::paths::DisplaySlash;
 use anyhow::Result;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -31,7 +32,7 @@ pub struct TypeInfo {
     pub name: String,
     /// What kind of type reference (param, return, field, generic)
     pub kind: TypeKind,
-    /// The actual type name (e.g., "String", "Vec<T>", "User")
+    /// The actual type name (e.g., `String`, `Vec<T>`, `User`)
     pub type_name: String,
     /// Where this type is defined (file:line), if found
     pub defined_in: Option<String>,
@@ -89,9 +90,9 @@ pub fn analyze_types(
         let resolved_return = return_type.map(|rt| resolve_type(index, rt));
 
         results.push(SymbolTypes {
-            symbol_name: symbol.name.clone(),
+            symbol_name: symbol.name.to_string(),
             symbol_type: symbol.symbol_type,
-            file_path: symbol.file_path.display().to_string(),
+            file_path: symbol.file_path.display_slash().to_string(),
             line: symbol.line_start,
             signature: symbol.signature.clone(),
             params: resolved_params,
@@ -114,9 +115,13 @@ fn parse_signature(signature: &str, language: Language) -> (Vec<TypeInfo>, Optio
         Language::Rust => parse_rust_signature(signature),
         Language::Python => parse_python_signature(signature),
         Language::TypeScript | Language::JavaScript => parse_typescript_signature(signature),
+        Language::Php => parse_php_signature(signature),
+        Language::Kotlin => parse_kotlin_signature(signature),
+        Language::Swift => parse_swift_signature(signature),
+        Language::Scala => parse_scala_signature(signature),
         Language::Go => parse_go_signature(signature),
-        Language::Java => parse_java_signature(signature),
-        Language::C => parse_c_signature(signature),
+        Language::Java | Language::CSharp => parse_java_signature(signature),
+        Language::C | Language::Cpp => parse_c_signature(signature),
         _ => (Vec::new(), None),
     }
 }
@@ -324,6 +329,249 @@ fn parse_typescript_params(param_str: &str) -> Vec<TypeInfo> {
     params
 }
 
+/// Parse PHP signature: `(Type $x, ?Type $y = null): RetType`; nullable types are looked up
+/// without their `?`
+fn parse_php_signature(signature: &str) -> (Vec<TypeInfo>, Option<TypeInfo>) {
+    let mut params = Vec::new();
+    let mut return_type = None;
+
+    let return_re = Regex::new(r"\)\s*:\s*([^{]+)$")
+        .unwrap_or_else(|_| Regex::new(r"$").unwrap_or_else(|_| panic!("Failed to compile regex")));
+
+    if let Some(cap) = return_re.captures(signature) {
+        let ret_type = cap.get(1).map(|m| m.as_str().trim()).unwrap_or_default();
+        if !ret_type.is_empty() && ret_type != "void" {
+            return_type = Some(TypeInfo {
+                name: String::new(),
+                kind: TypeKind::Return,
+                type_name: clean_type_name(ret_type.trim_start_matches('?')),
+                defined_in: None,
+            });
+        }
+    }
+
+    let param_str = signature
+        .find('(')
+        .zip(signature.rfind(')'))
+        .filter(|(open, close)| open < close)
+        .map_or("", |(open, close)| &signature[open + 1..close]);
+
+    for part in split_by_comma_respecting_brackets(param_str) {
+        // Constructor promotion modifiers and defaults say nothing about the type
+        let part = part.split('=').next().unwrap_or_default();
+        let tokens: Vec<&str> = part
+            .split_whitespace()
+            .filter(|t| !matches!(*t, "public" | "protected" | "private" | "readonly"))
+            .collect();
+        let Some((name, type_tokens)) = tokens.split_last() else {
+            continue;
+        };
+        if type_tokens.is_empty() {
+            continue;
+        }
+        params.push(TypeInfo {
+            name: name.trim_start_matches(['&', '.']).to_string(),
+            kind: TypeKind::Parameter,
+            type_name: clean_type_name(type_tokens.join(" ").trim_start_matches('?')),
+            defined_in: None,
+        });
+    }
+
+    (params, return_type)
+}
+
+/// Parse Kotlin signature: `<T> Receiver.(x: Type, y: Type = 1): RetType`. Parameter types
+/// can hold parentheses of their own (`block: (Order) -> T`), so the list is matched by depth.
+fn parse_kotlin_signature(signature: &str) -> (Vec<TypeInfo>, Option<TypeInfo>) {
+    let mut params = Vec::new();
+    let mut return_type = None;
+
+    let Some((open, close)) = parameter_list(signature) else {
+        return (params, return_type);
+    };
+
+    if let Some(ret_type) = signature[close + 1..].trim_start().strip_prefix(':') {
+        let ret_type = ret_type.trim();
+        if !ret_type.is_empty() && ret_type != "Unit" {
+            return_type = Some(TypeInfo {
+                name: String::new(),
+                kind: TypeKind::Return,
+                type_name: clean_type_name(ret_type.trim_end_matches('?')),
+                defined_in: None,
+            });
+        }
+    }
+
+    for part in split_by_comma_respecting_brackets(&signature[open + 1..close]) {
+        let part = part.trim_start_matches("vararg ");
+        let part = part.split(" = ").next().unwrap_or_default();
+        let Some((name, type_name)) = part.split_once(':') else {
+            continue;
+        };
+        // `val`/`var` on constructor parameters
+        let name = name.split_whitespace().last().unwrap_or_default();
+        params.push(TypeInfo {
+            name: name.to_string(),
+            kind: TypeKind::Parameter,
+            type_name: clean_type_name(type_name.trim().trim_end_matches('?')),
+            defined_in: None,
+        });
+    }
+
+    (params, return_type)
+}
+
+/// Byte offsets of the parentheses around a parameter list: the first `(` outside the type
+/// parameters and the `)` matching it
+fn parameter_list(signature: &str) -> Option<(usize, usize)> {
+    let mut angle: usize = 0;
+    let open = signature.char_indices().find_map(|(i, c)| {
+        match c {
+            '<' => angle += 1,
+            '>' => angle = angle.saturating_sub(1),
+            '(' if angle == 0 => return Some(i),
+            _ => {}
+        }
+        None
+    })?;
+    let mut depth: usize = 0;
+    let close = signature[open..].char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+        None
+    })?;
+    Some((open, close))
+}
+
+/// Parse Swift signature: `<T>(_ id: T, times count: Int = 1) async throws -> RetType`.
+/// Parameters are named by their internal name, the one the body uses.
+fn parse_swift_signature(signature: &str) -> (Vec<TypeInfo>, Option<TypeInfo>) {
+    let mut params = Vec::new();
+    let mut return_type = None;
+
+    let Some((open, close)) = parameter_list(signature) else {
+        return (params, return_type);
+    };
+
+    if let Some((_, ret_type)) = signature[close + 1..].split_once("->") {
+        let ret_type = ret_type.trim();
+        if !ret_type.is_empty() && ret_type != "Void" && ret_type != "()" {
+            return_type = Some(TypeInfo {
+                name: String::new(),
+                kind: TypeKind::Return,
+                type_name: clean_type_name(ret_type.trim_end_matches(['?', '!'])),
+                defined_in: None,
+            });
+        }
+    }
+
+    for part in split_by_comma_respecting_brackets(&signature[open + 1..close]) {
+        let part = part.split(" = ").next().unwrap_or_default();
+        let Some((name, type_name)) = part.split_once(':') else {
+            continue;
+        };
+        let name = name.split_whitespace().last().unwrap_or_default();
+        let type_name = type_name
+            .trim()
+            .trim_start_matches("@escaping ")
+            .trim_start_matches("inout ")
+            .trim_end_matches("...");
+        params.push(TypeInfo {
+            name: name.to_string(),
+            kind: TypeKind::Parameter,
+            type_name: clean_type_name(type_name.trim_end_matches(['?', '!'])),
+            defined_in: None,
+        });
+    }
+
+    (params, return_type)
+}
+
+/// Parse Scala signature: `[T <: Base](x: Type, y: Type = 1)(implicit ev: Ord[T]): RetType`,
+/// every parameter list in turn. Square brackets are skipped whole, since a bound's `<:`
+/// would throw off `parameter_list`.
+fn parse_scala_signature(signature: &str) -> (Vec<TypeInfo>, Option<TypeInfo>) {
+    let mut params = Vec::new();
+    let mut return_type = None;
+
+    let mut lists = Vec::new();
+    let (mut square, mut depth) = (0usize, 0usize);
+    let mut open = 0;
+    // The return type follows the first `:` outside brackets and parameter lists
+    let mut returns = None;
+    for (i, c) in signature.char_indices() {
+        match c {
+            '[' => square += 1,
+            ']' => square = square.saturating_sub(1),
+            ':' if square == 0 && depth == 0 => {
+                returns = Some(&signature[i + 1..]);
+                break;
+            }
+            '(' if square == 0 => {
+                if depth == 0 {
+                    open = i + 1;
+                }
+                depth += 1;
+            }
+            ')' if square == 0 && depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    lists.push(&signature[open..i]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(ret_type) = returns {
+        let ret_type = ret_type.trim();
+        if !ret_type.is_empty() && ret_type != "Unit" {
+            return_type = Some(TypeInfo {
+                name: String::new(),
+                kind: TypeKind::Return,
+                type_name: clean_type_name(ret_type),
+                defined_in: None,
+            });
+        }
+    }
+
+    for part in lists
+        .into_iter()
+        .flat_map(split_by_comma_respecting_brackets)
+    {
+        let part = part
+            .trim_start_matches("implicit ")
+            .trim_start_matches("using ");
+        let part = part.split(" = ").next().unwrap_or_default();
+        let Some((name, type_name)) = part.split_once(':') else {
+            continue;
+        };
+        // `val`/`var` on class parameters
+        let name = name.split_whitespace().last().unwrap_or_default();
+        // By-name `=> T` and repeated `T*` parameters
+        let type_name = type_name
+            .trim()
+            .trim_start_matches("=> ")
+            .trim_end_matches('*');
+        params.push(TypeInfo {
+            name: name.to_string(),
+            kind: TypeKind::Parameter,
+            type_name: clean_type_name(type_name),
+            defined_in: None,
+        });
+    }
+
+    (params, return_type)
+}
+
 /// Parse Go signature: `func name(x Type, y Type) RetType`
 fn parse_go_signature(signature: &str) -> (Vec<TypeInfo>, Option<TypeInfo>) {
     let mut params = Vec::new();
@@ -729,7 +977,7 @@ fn resolve_type(index: &CodeIndex, mut type_info: TypeInfo) -> TypeInfo {
             if matches!(symbol.symbol_type, SymbolType::Class | SymbolType::Enum) {
                 type_info.defined_in = Some(format!(
                     "{}:{}",
-                    symbol.file_path.display(),
+                    symbol.file_path.display_slash(),
                     symbol.line_start
                 ));
                 return type_info;
@@ -743,7 +991,7 @@ fn resolve_type(index: &CodeIndex, mut type_info: TypeInfo) -> TypeInfo {
                 if symbol.name.to_lowercase() == base_type.to_lowercase() {
                     type_info.defined_in = Some(format!(
                         "{}:{}",
-                        symbol.file_path.display(),
+                        symbol.file_path.display_slash(),
                         symbol.line_start
                     ));
                     return type_info;
@@ -813,6 +1061,99 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_php_signature() {
+        let sig = "(Order $order, ?Customer $customer = null, $raw, int ...$ids): ?Invoice";
+        let (params, ret) = parse_php_signature(sig);
+
+        let params: Vec<_> = params
+            .iter()
+            .map(|p| (p.name.as_str(), p.type_name.as_str()))
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("$order", "Order"),
+                ("$customer", "Customer"),
+                ("$ids", "int")
+            ]
+        );
+        assert_eq!(ret.map(|r| r.type_name), Some("Invoice".to_string()));
+    }
+
+    #[test]
+    fn test_parse_kotlin_signature() {
+        let sig = "<T> List<T>.(block: (Order) -> T, vararg ids: Int, note: String? = null): Map<String, T>?";
+        let (params, ret) = parse_kotlin_signature(sig);
+
+        let params: Vec<_> = params
+            .iter()
+            .map(|p| (p.name.as_str(), p.type_name.as_str()))
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("block", "(Order) -> T"),
+                ("ids", "Int"),
+                ("note", "String")
+            ]
+        );
+        assert_eq!(
+            ret.map(|r| r.type_name),
+            Some("Map<String, T>".to_string())
+        );
+        assert!(parse_kotlin_signature("(): Unit").1.is_none());
+    }
+
+    #[test]
+    fn test_parse_swift_signature() {
+        let sig = "<T>(_ id: T, times count: Int..., block: @escaping (Order) -> Void, note: String? = nil) async throws -> [T]?";
+        let (params, ret) = parse_swift_signature(sig);
+
+        let params: Vec<_> = params
+            .iter()
+            .map(|p| (p.name.as_str(), p.type_name.as_str()))
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("id", "T"),
+                ("count", "Int"),
+                ("block", "(Order) -> Void"),
+                ("note", "String")
+            ]
+        );
+        assert_eq!(ret.map(|r| r.type_name), Some("[T]".to_string()));
+        assert!(parse_swift_signature("(x: Int) -> Void").1.is_none());
+    }
+
+    #[test]
+    fn test_parse_scala_signature() {
+        let sig = "[T <: Priced](items: Seq[T], f: (T, Int) => Int, body: => Unit, tags: String*)(implicit ev: Ordering[T]): Map[String, T]";
+        let (params, ret) = parse_scala_signature(sig);
+
+        let params: Vec<_> = params
+            .iter()
+            .map(|p| (p.name.as_str(), p.type_name.as_str()))
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("items", "Seq[T]"),
+                ("f", "(T, Int) => Int"),
+                ("body", "Unit"),
+                ("tags", "String"),
+                ("ev", "Ordering[T]")
+            ]
+        );
+        assert_eq!(ret.map(|r| r.type_name), Some("Map[String, T]".to_string()));
+        assert!(parse_scala_signature("(x: Int): Unit").1.is_none());
+        assert_eq!(
+            parse_scala_signature(": Double").1.map(|r| r.type_name),
+            Some("Double".to_string())
+        );
+    }
+
     #[test]
     fn test_extract_base_types() {
         assert_eq!(extract_base_types("Vec<User>"), vec!["User"]);
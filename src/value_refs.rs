@@ -0,0 +1,335 @@
+//! `cm value-refs`: every occurrence of a constant or enum variant, read from the syntax
+//! tree and classified as its definition, a use in code, or a textual coincidence inside a
+//! string or comment (a config key or log message that happens to share the name).
+
+use crate::index::CodeIndex;
+use crate::models::{Language, Symbol, SymbolType};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tree_sitter::{Node, Parser};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RefKind {
+    Definition,
+    Usage,
+    /// Inside a string literal or comment
+    Text,
+}
+
+impl RefKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefKind::Definition => "definition",
+            RefKind::Usage => "usage",
+            RefKind::Text => "text",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValueRef {
+    pub kind: RefKind,
+    pub file_path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    /// The source line, trimmed
+    pub context: String,
+    /// Innermost symbol containing the occurrence
+    pub enclosing: Option<String>,
+}
+
+/// `Status::NotFound` or `Color.RED` -> (Some("Status"), "NotFound"); `MAX` -> (None, "MAX")
+fn split_name(name: &str) -> (Option<&str>, &str) {
+    let name = name.trim();
+    let cut = name
+        .rfind("::")
+        .map(|i| (i, i + 2))
+        .into_iter()
+        .chain(name.rfind('.').map(|i| (i, i + 1)))
+        .max();
+    match cut {
+        Some((end, start)) if end > 0 && start < name.len() => {
+            let qualifier = &name[..end];
+            let qualifier = qualifier.rsplit([':', '.']).next().unwrap_or(qualifier);
+            (Some(qualifier), &name[start..])
+        }
+        _ => (None, name),
+    }
+}
+
+fn is_value(symbol: &Symbol) -> bool {
+    match symbol.symbol_type {
+        SymbolType::StaticField => true,
+        // Variants are indexed as `Enum::Variant` / `Enum.MEMBER`; the enum itself is not a value
+        SymbolType::Enum => symbol.name.contains("::") || symbol.name.contains('.'),
+        _ => false,
+    }
+}
+
+/// Indexed constants and enum variants `name` refers to. A bare variant name (`RED`)
+/// matches the variant of every enum.
+pub fn definitions<'a>(index: &'a CodeIndex, name: &str) -> Vec<&'a Symbol> {
+    let (qualifier, leaf) = split_name(name);
+    let mut found = Vec::new();
+    for file in index.files() {
+        for symbol in index.get_file_symbols(&file.path) {
+            if !is_value(symbol) {
+                continue;
+            }
+            let (owner, symbol_leaf) = split_name(&symbol.name);
+            if symbol_leaf == leaf && (qualifier.is_none() || owner == qualifier) {
+                found.push(symbol);
+            }
+        }
+    }
+    found.sort_by(|a, b| {
+        a.file_path
+            .cmp(&b.file_path)
+            .then(a.line_start.cmp(&b.line_start))
+    });
+    found
+}
+
+pub(crate) fn grammar(language: Language) -> Option<tree_sitter::Language> {
+    Some(match language {
+        Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+        Language::Python => tree_sitter_python::LANGUAGE.into(),
+        Language::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+        Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        Language::Java => tree_sitter_java::LANGUAGE.into(),
+        Language::Go => tree_sitter_go::LANGUAGE.into(),
+        Language::C => tree_sitter_c::LANGUAGE.into(),
+        Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+        Language::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
+        Language::Php => tree_sitter_php::LANGUAGE_PHP.into(),
+        Language::Kotlin => tree_sitter_kotlin_ng::LANGUAGE.into(),
+        Language::Swift => tree_sitter_swift::LANGUAGE.into(),
+        Language::Scala => tree_sitter_scala::LANGUAGE.into(),
+        Language::Lua => tree_sitter_lua::LANGUAGE.into(),
+        Language::Zig => tree_sitter_zig::LANGUAGE.into(),
+        _ => return None,
+    })
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Byte offsets of whole-word occurrences of `word` in `text`
+pub(crate) fn word_matches(text: &str, word: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    text.match_indices(word)
+        .map(|(i, _)| i)
+        .filter(|&i| {
+            let end = i + word.len();
+            (i == 0 || !is_word_byte(bytes[i - 1]))
+                && (end == bytes.len() || !is_word_byte(bytes[end]))
+        })
+        .collect()
+}
+
+/// An identifier qualified by something other than `qualifier` (`Other::X`, `obj.X`) names
+/// a different value. Unqualified uses count: glob imports and Java `case` labels.
+fn qualifier_matches(content: &str, start: usize, qualifier: Option<&str>) -> bool {
+    let Some(qualifier) = qualifier else {
+        return true;
+    };
+    let before = content[..start].trim_end();
+    let Some(before) = before
+        .strip_suffix("::")
+        .or_else(|| before.strip_suffix('.'))
+    else {
+        return true;
+    };
+    let before = before.trim_end();
+    before.ends_with(qualifier)
+        && before[..before.len() - qualifier.len()]
+            .bytes()
+            .next_back()
+            .is_none_or(|b| !is_word_byte(b))
+}
+
+/// (byte offset, kind) of every occurrence of `leaf` in the tree, in source order
+fn occurrences(
+    root: Node,
+    content: &str,
+    leaf: &str,
+    qualifier: Option<&str>,
+) -> Vec<(usize, RefKind)> {
+    let mut found = Vec::new();
+    // (node, inside a string literal)
+    let mut stack = vec![(root, false)];
+    while let Some((node, in_string)) = stack.pop() {
+        let kind = node.kind();
+        let text = &content[node.byte_range()];
+        if kind.contains("comment") || (in_string && node.child_count() == 0) {
+            for offset in word_matches(text, leaf) {
+                found.push((node.start_byte() + offset, RefKind::Text));
+            }
+            continue;
+        }
+        if node.child_count() == 0 {
+            if kind.ends_with("identifier")
+                && text == leaf
+                && qualifier_matches(content, node.start_byte(), qualifier)
+            {
+                found.push((node.start_byte(), RefKind::Usage));
+            }
+            continue;
+        }
+
+        // f-string and template substitutions are code again
+        let in_string = match kind {
+            "interpolation" | "template_substitution" => false,
+            _ => in_string || kind.contains("string") || kind == "char_literal",
+        };
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev().map(|child| (child, in_string)));
+    }
+    found.sort_by_key(|(offset, _)| *offset);
+    found
+}
+
+pub(crate) fn enclosing_symbol<'a>(index: &'a CodeIndex, path: &Path, line: usize) -> Option<&'a Symbol> {
+    index
+        .get_file_symbols(path)
+        .into_iter()
+        .filter(|s| s.line_start <= line && s.line_end >= line)
+        .filter(|s| s.symbol_type != SymbolType::Field)
+        .min_by_key(|s| s.line_end - s.line_start)
+}
+
+/// Every occurrence of the constant or variant `name` in the indexed files (or only in
+/// `candidates`). Within the line range of each of `definitions`, the first use in code is
+/// the definition itself.
+pub fn find_value_refs(
+    index: &CodeIndex,
+    name: &str,
+    definitions: &[&Symbol],
+    candidates: Option<&HashSet<PathBuf>>,
+) -> Result<Vec<ValueRef>> {
+    let (qualifier, leaf) = split_name(name);
+    let mut defined_at: HashMap<&Path, Vec<&Symbol>> = HashMap::new();
+    for symbol in definitions {
+        defined_at
+            .entry(symbol.file_path.as_ref())
+            .or_default()
+            .push(symbol);
+    }
+
+    let mut refs = Vec::new();
+    let mut parser = Parser::new();
+    for file in index
+        .files()
+        .filter(|f| candidates.is_none_or(|c| c.contains(&f.path)))
+    {
+        let Some(grammar) = grammar(file.language) else {
+            continue;
+        };
+        let content = match fs::read_to_string(&file.path) {
+            Ok(c) if c.contains(leaf) => c,
+            _ => continue,
+        };
+        parser
+            .set_language(&grammar)
+            .with_context(|| format!("Failed to set {} language", file.language.as_str()))?;
+        let Some(tree) = parser.parse(&content, None) else {
+            continue;
+        };
+
+        let mut unclaimed = defined_at
+            .get(file.path.as_path())
+            .cloned()
+            .unwrap_or_default();
+        for (offset, mut kind) in occurrences(tree.root_node(), &content, leaf, qualifier) {
+            let line = content[..offset].matches('\n').count() + 1;
+            let mut enclosing = None;
+            if kind == RefKind::Usage {
+                if let Some(i) = unclaimed
+                    .iter()
+                    .position(|s| s.line_start <= line && line <= s.line_end)
+                {
+                    enclosing = Some(unclaimed.remove(i));
+                    kind = RefKind::Definition;
+                }
+            }
+            let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+            let context = content[line_start..].lines().next().unwrap_or("").trim();
+            refs.push(ValueRef {
+                kind,
+                file_path: file.path.clone(),
+                line,
+                column: content[line_start..offset].chars().count() + 1,
+                context: context.to_string(),
+                enclosing: enclosing
+                    .or_else(|| enclosing_symbol(index, &file.path, line))
+                    .map(|s| s.name.to_string()),
+            });
+        }
+    }
+
+    refs.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then(a.file_path.cmp(&b.file_path))
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+    Ok(refs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_of;
+
+    #[test]
+    fn test_value_refs_classify_occurrences() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = r#"pub enum Status {
+    Ok,
+    NotFound = 404,
+}
+
+pub const MAX_RETRIES: u32 = 3;
+
+/// Gives up after MAX_RETRIES attempts
+pub fn fetch(status: Status) -> u32 {
+    let key = "MAX_RETRIES";
+    match status {
+        Status::NotFound => MAX_RETRIES,
+        Other::NotFound => 0,
+        _ => MAX_RETRIES + 1,
+    }
+}
+"#;
+        let index = index_of(dir.path(), &[("lib.rs", source, Language::Rust)]);
+
+        let defs = definitions(&index, "MAX_RETRIES");
+        assert_eq!(defs.len(), 1);
+        let refs = find_value_refs(&index, "MAX_RETRIES", &defs, None).unwrap();
+        let kinds: Vec<(RefKind, usize)> = refs.iter().map(|r| (r.kind, r.line)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (RefKind::Definition, 6),
+                (RefKind::Usage, 12),
+                (RefKind::Usage, 14),
+                (RefKind::Text, 8),
+                (RefKind::Text, 10),
+            ]
+        );
+        assert_eq!(refs[1].enclosing.as_deref(), Some("fetch"));
+
+        // `Other::NotFound` is a different value; the bare name matches every variant
+        let defs = definitions(&index, "Status::NotFound");
+        let refs = find_value_refs(&index, "Status::NotFound", &defs, None).unwrap();
+        let kinds: Vec<(RefKind, usize)> = refs.iter().map(|r| (r.kind, r.line)).collect();
+        assert_eq!(kinds, vec![(RefKind::Definition, 3), (RefKind::Usage, 12)]);
+        assert_eq!(definitions(&index, "NotFound").len(), 1);
+        assert!(definitions(&index, "Status").is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+//! JavaScript bindings for single-file parsing, enabled with the `wasm` feature.
+//!
+//! ```js
+//! import init, { parseFile } from "./pkg/codemapper.js";
+//! await init();
+//! const { symbols, dependencies } = JSON.parse(parseFile(source, "python"));
+//! ```
+
+use crate::indexer;
+use crate::models::{Dependency, Language, Symbol};
+use serde::Serialize;
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+const LANGUAGES: &[Language] = &[
+    Language::Python,
+    Language::JavaScript,
+    Language::TypeScript,
+    Language::Rust,
+    Language::Java,
+    Language::Go,
+    Language::C,
+    Language::Cpp,
+    Language::CSharp,
+    Language::Php,
+    Language::Kotlin,
+    Language::Swift,
+    Language::Scala,
+    Language::Lua,
+    Language::Zig,
+    Language::Markdown,
+];
+
+#[derive(Serialize)]
+struct ParsedFile {
+    language: &'static str,
+    symbols: Vec<Symbol>,
+    dependencies: Vec<Dependency>,
+}
+
+fn parse_to_json(content: &str, lang: &str) -> Result<String, String> {
+    let language = Language::from_name(lang);
+    if language == Language::Unknown {
+        return Err(format!("Unsupported language: {}", lang));
+    }
+
+    let virtual_path = Path::new("input");
+    let file_info = indexer::index_file(virtual_path, content, language, None)
+        .map_err(|e| e.to_string())?;
+
+    let parsed = ParsedFile {
+        language: language.as_str(),
+        symbols: file_info.symbols,
+        dependencies: file_info.dependencies,
+    };
+
+    serde_json::to_string(&parsed).map_err(|e| e.to_string())
+}
+
+/// Parse one file's source and return `{language, symbols, dependencies}` as a JSON string
+#[wasm_bindgen(js_name = parseFile)]
+pub fn parse_file(content: &str, lang: &str) -> Result<String, JsValue> {
+    parse_to_json(content, lang).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Language names accepted by `parseFile` (file extensions work too)
+#[wasm_bindgen(js_name = supportedLanguages)]
+pub fn supported_languages() -> Vec<String> {
+    LANGUAGES.iter().map(|l| l.as_str().to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_to_json() {
+        let json = parse_to_json("import os\n\ndef greet(name):\n    pass\n", "py").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["language"], "python");
+        assert_eq!(value["symbols"][0]["name"], "greet");
+        assert_eq!(value["dependencies"][0]["import_name"], "os");
+
+        assert_eq!(Language::from_name("Rust"), Language::Rust);
+        assert!(parse_to_json("", "cobol").is_err());
+    }
+}
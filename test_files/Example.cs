@@ -0,0 +1,54 @@
+using System;
+using System.Collections.Generic;
+using Json = System.Text.Json.JsonSerializer;
+
+namespace Shop.Orders
+{
+    /// <summary>Anything with a price</summary>
+    public interface IPriced
+    {
+        decimal Price { get; }
+        decimal Discounted(decimal rate);
+    }
+
+    /// <summary>Lifecycle of an order</summary>
+    public enum Status { Open, Paid = 2, Shipped }
+
+    /// <summary>An order of several lines</summary>
+    public class Order : IPriced
+    {
+        public const int MaxLines = 50;
+        private static readonly string Prefix = "ORD";
+        private readonly List<decimal> lines = new List<decimal>();
+
+        public Order(string id) { Id = id; }
+
+        public string Id { get; }
+        public Status Status { get; private set; }
+        public decimal Price => Sum();
+
+        public decimal Discounted(decimal rate)
+        {
+            return Price * (1 - rate);
+        }
+
+        [Obsolete("Use Discounted")]
+        public decimal WithDiscount(decimal rate) => Discounted(rate);
+
+        private decimal Sum()
+        {
+            decimal total = 0;
+            foreach (var line in lines) { total += line; }
+            return total;
+        }
+
+        internal string ToJson() => Json.Serialize(this);
+    }
+
+    public readonly struct Money
+    {
+        public decimal Amount { get; init; }
+    }
+
+    public record Receipt(string OrderId, decimal Total);
+}
@@ -0,0 +1,48 @@
+#include <string>
+#include <vector>
+#include "shape.hpp"
+
+namespace geometry {
+
+// Color of a shape's outline
+enum class Color { Red, Green = 2, Blue };
+
+// Shape is the base for everything drawable
+class Shape {
+public:
+    Shape(std::string name);
+    virtual ~Shape();
+    virtual double area() const = 0;
+
+protected:
+    std::string name_;
+};
+
+// Circle with a radius
+struct Circle : public Shape {
+    double radius;
+    double area() const override { return 3.14159 * radius * radius; }
+};
+
+// Destructor out of line
+Shape::~Shape() {}
+
+Shape::Shape(std::string name) : name_(name) {}
+
+template <typename T>
+T clamp(T value, T low, T high) {
+    return value < low ? low : (value > high ? high : value);
+}
+
+}  // namespace geometry
+
+namespace {
+int countShapes(const std::vector<geometry::Shape*>& shapes) {
+    return static_cast<int>(shapes.size());
+}
+}
+
+int main() {
+    geometry::Circle circle("c");
+    return countShapes({&circle});
+}
@@ -0,0 +1,65 @@
+package com.example.billing
+
+import com.example.billing.model.Customer
+import com.example.billing.model.Order as PurchaseOrder
+import kotlinx.coroutines.*
+
+const val DEFAULT_CURRENCY = "EUR"
+
+/**
+ * Anything that can be charged
+ */
+interface Chargeable {
+    fun amount(): Int
+}
+
+/** Lifecycle of an invoice */
+enum class InvoiceStatus(val label: String) {
+    DRAFT("draft"),
+    PAID("paid");
+
+    fun isFinal() = this == PAID
+}
+
+/** An invoice for one order */
+data class Invoice(
+    val customer: Customer,
+    private val order: PurchaseOrder,
+) : Chargeable {
+    var status: InvoiceStatus = InvoiceStatus.DRAFT
+
+    override fun amount(): Int {
+        return order.total() * (100 + VAT_RATE) / 100
+    }
+
+    @Deprecated("use amount()", ReplaceWith("amount()"))
+    fun total(): Int = amount()
+
+    private fun currency(): String {
+        return System.getenv("BILLING_CURRENCY") ?: DEFAULT_CURRENCY
+    }
+
+    companion object {
+        const val VAT_RATE = 21
+
+        fun draft(customer: Customer, order: PurchaseOrder) = Invoice(customer, order)
+    }
+}
+
+/** Where issued invoices are kept */
+object InvoiceRegistry {
+    private val issued = mutableListOf<Invoice>()
+
+    fun register(invoice: Invoice) {
+        when (invoice.status) {
+            InvoiceStatus.DRAFT -> return
+            InvoiceStatus.PAID -> issued.add(invoice)
+        }
+    }
+}
+
+typealias Invoices = List<Invoice>
+
+fun Invoice.render(prefix: String = ""): String = prefix + formatMoney(amount())
+
+internal fun formatMoney(cents: Int): String = "%.2f".format(cents / 100.0)
@@ -0,0 +1,47 @@
+-- Inventory module for the example shop
+local json = require("dkjson")
+local log = require "shop.log"
+
+local Inventory = {}
+Inventory.MAX_ITEMS = 100
+
+--- Creates an empty inventory
+-- @return table
+function Inventory.new()
+  return setmetatable({ items = {} }, { __index = Inventory })
+end
+
+--- Adds an item, refusing once full
+-- @param name string
+-- @param count number
+function Inventory:add(name, count)
+  if #self.items >= Inventory.MAX_ITEMS then
+    log.warn("inventory full")
+    return false
+  end
+  self.items[name] = (self.items[name] or 0) + count
+  return true
+end
+
+Inventory.to_json = function(self)
+  return json.encode(self.items)
+end
+
+local function total(items)
+  local sum = 0
+  for _, count in pairs(items) do
+    sum = sum + count
+  end
+  return sum
+end
+
+function describe(inventory)
+  return string.format("%d items", total(inventory.items))
+end
+
+local defaults = {
+  currency = "EUR",
+  format = function(amount) return string.format("%.2f", amount) end,
+}
+
+return Inventory
@@ -0,0 +1,76 @@
+<?php
+namespace App\Billing;
+
+use App\Models\{Customer, Order as PurchaseOrder};
+use function App\Support\format_money;
+require_once __DIR__ . '/bootstrap.php';
+
+const DEFAULT_CURRENCY = 'EUR';
+
+/**
+ * Anything that can be charged
+ */
+interface Chargeable
+{
+    public function amount(): int;
+}
+
+/** Adds audit timestamps */
+trait Audited
+{
+    protected ?string $updatedAt = null;
+
+    public function touch(): void
+    {
+        $this->updatedAt = date('c');
+    }
+}
+
+/** Lifecycle of an invoice */
+enum InvoiceStatus: string
+{
+    case Draft = 'draft';
+    case Paid = 'paid';
+
+    public function label(): string
+    {
+        return ucfirst($this->value);
+    }
+}
+
+/** An invoice for one order */
+final class Invoice implements Chargeable
+{
+    use Audited;
+
+    public const VAT_RATE = 21;
+    private static int $issued = 0;
+
+    public function __construct(
+        private Customer $customer,
+        private PurchaseOrder $order,
+    ) {
+        self::$issued++;
+    }
+
+    public function amount(): int
+    {
+        return $this->order->total() * (100 + self::VAT_RATE) / 100;
+    }
+
+    /** @deprecated use amount() */
+    public function total(): int
+    {
+        return $this->amount();
+    }
+
+    private function currency(): string
+    {
+        return getenv('BILLING_CURRENCY') ?: DEFAULT_CURRENCY;
+    }
+}
+
+function render_invoice(Invoice $invoice): string
+{
+    return format_money($invoice->amount());
+}
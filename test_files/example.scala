@@ -0,0 +1,59 @@
+package com.example.billing
+
+import com.example.billing.model.{Customer, Order => PurchaseOrder}
+import scala.concurrent.Future
+import scala.collection.mutable._
+
+/** Anything that can be charged */
+trait Chargeable {
+  def amount(): Int
+  val currency: String
+}
+
+/** Lifecycle of an invoice */
+enum InvoiceStatus(val label: String) {
+  case Draft extends InvoiceStatus("draft")
+  case Paid extends InvoiceStatus("paid")
+
+  def isFinal: Boolean = this == Paid
+}
+
+/** An invoice for one order */
+final case class Invoice(customer: Customer, private val order: PurchaseOrder)
+    extends Chargeable {
+  var status: InvoiceStatus = InvoiceStatus.Draft
+  val currency: String = sys.env.getOrElse("BILLING_CURRENCY", Invoice.DefaultCurrency)
+
+  override def amount(): Int = order.total() * (100 + Invoice.VatRate) / 100
+
+  @deprecated("use amount()", "1.2")
+  def total(): Int = amount()
+
+  private def rounded[T](value: T)(implicit num: Numeric[T]): Int = num.toInt(value)
+}
+
+/** Factory and constants for invoices */
+object Invoice {
+  val VatRate = 21
+  final val DefaultCurrency = "EUR"
+
+  def draft(customer: Customer, order: PurchaseOrder): Invoice = new Invoice(customer, order)
+}
+
+/** Where issued invoices are kept */
+object InvoiceRegistry {
+  private val issued = ListBuffer.empty[Invoice]
+
+  def register(invoice: Invoice): Future[Unit] = invoice.status match {
+    case InvoiceStatus.Draft => Future.unit
+    case InvoiceStatus.Paid  => Future.successful(issued += invoice)
+  }
+}
+
+extension (invoice: Invoice)
+  def isPaid: Boolean = invoice.status == InvoiceStatus.Paid
+
+type Invoices = Seq[Invoice]
+
+def issueAll(invoices: Invoices): Unit =
+  invoices.foreach(InvoiceRegistry.register)
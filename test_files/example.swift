@@ -0,0 +1,78 @@
+import Foundation
+import struct CoreGraphics.CGFloat
+@testable import Billing
+
+let defaultCurrency = "EUR"
+
+/// Anything that can be charged
+public protocol Chargeable {
+    var amount: Int { get }
+    func charge() throws -> Int
+}
+
+/// Lifecycle of an invoice
+enum InvoiceStatus: String {
+    case draft = "draft", paid = "paid"
+    case refunded(reason: String)
+
+    func isFinal() -> Bool {
+        return self == .paid
+    }
+}
+
+/// An invoice for one order
+public final class Invoice: Chargeable {
+    public let customer: Customer
+    private let order: Order
+    var status: InvoiceStatus = .draft
+    static let vatRate = 21
+
+    public init(customer: Customer, order: Order) {
+        self.customer = customer
+        self.order = order
+    }
+
+    public var amount: Int {
+        return order.total() * (100 + Invoice.vatRate) / 100
+    }
+
+    public func charge() throws -> Int {
+        guard status != .paid else { return 0 }
+        return amount
+    }
+
+    @available(*, deprecated, message: "use charge()")
+    func total() -> Int { amount }
+
+    private func currency() -> String {
+        return ProcessInfo.processInfo.environment["BILLING_CURRENCY"] ?? defaultCurrency
+    }
+}
+
+/// Where issued invoices are kept
+struct InvoiceRegistry {
+    private var issued: [Invoice] = []
+
+    mutating func register(_ invoice: Invoice) {
+        switch invoice.status {
+        case .draft: return
+        default: issued.append(invoice)
+        }
+    }
+}
+
+extension Invoice: CustomStringConvertible {
+    public var description: String {
+        return render(prefix: "#")
+    }
+
+    func render(prefix: String = "") -> String {
+        return prefix + formatMoney(amount)
+    }
+}
+
+typealias Invoices = [Invoice]
+
+func formatMoney<T: BinaryInteger>(_ cents: T) async -> String {
+    return String(format: "%.2f", Double(cents) / 100.0)
+}
@@ -0,0 +1,58 @@
+//! Geometry helpers for the example shop
+
+const std = @import("std");
+const util = @import("util.zig");
+const mem = std.mem;
+
+/// Maximum items
+pub const MAX_ITEMS: usize = 100;
+var counter: u32 = 0;
+
+/// A point in 2D
+pub const Point = struct {
+    x: f32,
+    y: f32 = 0,
+
+    const Self = @This();
+
+    /// Creates a point
+    pub fn init(x: f32, y: f32) Point {
+        return .{ .x = x, .y = y };
+    }
+
+    fn len(self: Self) f32 {
+        return @sqrt(self.x * self.x);
+    }
+};
+
+pub const Color = enum(u8) {
+    red,
+    green = 2,
+
+    pub fn isRed(self: Color) bool {
+        return self == .red;
+    }
+};
+
+const Value = union(enum) {
+    int: i64,
+    float: f64,
+};
+
+pub fn add(a: i32, b: i32) i32 {
+    return a + b;
+}
+
+export fn c_add(a: c_int, b: c_int) c_int {
+    return a + b;
+}
+
+fn generic(comptime T: type, items: []const T) !void {
+    _ = items;
+}
+
+extern "c" fn puts(s: [*:0]const u8) c_int;
+
+test "add works" {
+    try std.testing.expect(add(1, 2) == 3);
+}